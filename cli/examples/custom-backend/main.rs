@@ -14,7 +14,6 @@
 
 use std::path::Path;
 use std::pin::Pin;
-use std::time::SystemTime;
 
 use async_trait::async_trait;
 use futures::stream::BoxStream;
@@ -37,6 +36,8 @@ use jj_lib::backend::SigningFn;
 use jj_lib::backend::SymlinkId;
 use jj_lib::backend::Tree;
 use jj_lib::backend::TreeId;
+use jj_lib::gc::GcOptions;
+use jj_lib::gc::GcStats;
 use jj_lib::git_backend::GitBackend;
 use jj_lib::index::Index;
 use jj_lib::repo::StoreFactories;
@@ -209,7 +210,7 @@ impl Backend for JitBackend {
         self.inner.get_copy_records(paths, root, head)
     }
 
-    fn gc(&self, index: &dyn Index, keep_newer: SystemTime) -> BackendResult<()> {
-        self.inner.gc(index, keep_newer)
+    fn gc(&self, index: &dyn Index, options: &GcOptions) -> BackendResult<GcStats> {
+        self.inner.gc(index, options)
     }
 }
@@ -27,8 +27,10 @@ use std::mem;
 use std::path::Path;
 use std::path::PathBuf;
 use std::rc::Rc;
+use std::slice;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 use std::time::SystemTime;
 
 use bstr::ByteVec as _;
@@ -53,6 +55,7 @@ use itertools::Itertools as _;
 use jj_lib::backend::BackendResult;
 use jj_lib::backend::ChangeId;
 use jj_lib::backend::CommitId;
+use jj_lib::backend::MillisSinceEpoch;
 use jj_lib::backend::TreeValue;
 use jj_lib::commit::Commit;
 use jj_lib::config::ConfigGetError;
@@ -64,6 +67,8 @@ use jj_lib::config::ConfigSource;
 use jj_lib::config::ConfigValue;
 use jj_lib::config::StackedConfig;
 use jj_lib::conflicts::ConflictMarkerStyle;
+use jj_lib::copies::CopyRecords;
+use jj_lib::file_util::IoResultExt as _;
 use jj_lib::fileset;
 use jj_lib::fileset::FilesetDiagnostics;
 use jj_lib::fileset::FilesetExpression;
@@ -123,6 +128,7 @@ use jj_lib::store::Store;
 use jj_lib::str_util::StringExpression;
 use jj_lib::str_util::StringMatcher;
 use jj_lib::str_util::StringPattern;
+use jj_lib::time_util;
 use jj_lib::transaction::Transaction;
 use jj_lib::working_copy;
 use jj_lib::working_copy::CheckoutStats;
@@ -194,6 +200,11 @@ use crate::ui::Ui;
 
 const SHORT_CHANGE_ID_TEMPLATE_TEXT: &str = "format_short_change_id_with_change_offset(self)";
 
+/// Minimum interval between automatic compactions of snapshot operations.
+const COMPACTION_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+/// File in the repo directory whose mtime records the last compaction.
+const SNAPSHOT_COMPACTION_STAMP: &str = "snapshot_compaction";
+
 #[derive(Clone)]
 struct ChromeTracingFlushGuard {
     _inner: Option<Rc<tracing_chrome::FlushGuard>>,
@@ -1908,11 +1919,64 @@ to the current parents may contain changes from multiple commits.
         Err(error)
     }
 
+    /// Starts the inotify daemon in the background if
+    /// `fsmonitor.inotify.start-daemon` is enabled but the daemon isn't
+    /// running yet. Until it's running, snapshots crawl the working copy.
+    #[cfg(target_os = "linux")]
+    fn start_inotify_daemon(&self, ui: &Ui) {
+        use std::os::unix::process::CommandExt as _;
+        use std::process::Command;
+        use std::process::Stdio;
+
+        use jj_lib::fsmonitor::FsmonitorSettings;
+        use jj_lib::fsmonitor::InotifyConfig;
+        use jj_lib::local_working_copy::LocalWorkingCopy;
+
+        if !matches!(
+            FsmonitorSettings::from_settings(self.settings()),
+            Ok(FsmonitorSettings::Inotify(InotifyConfig {
+                start_daemon: true
+            }))
+        ) {
+            return;
+        }
+        let Some(wc) = self.working_copy().downcast_ref::<LocalWorkingCopy>() else {
+            return;
+        };
+        if wc.inotify_fsmonitor().is_daemon_running().unwrap_or(true) {
+            return;
+        }
+        tracing::info!("Starting the inotify daemon in the background");
+        let result = env::current_exe().and_then(|program| {
+            Command::new(program)
+                .args(["--ignore-working-copy", "--repository"])
+                .arg(self.workspace_root())
+                .args(["debug", "inotify", "daemon"])
+                .current_dir(self.workspace_root())
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                // Don't receive the signals sent to the terminal's foreground
+                // process group.
+                .process_group(0)
+                .spawn()
+        });
+        if let Err(err) = result {
+            writeln!(
+                ui.warning_default(),
+                "Failed to start the inotify daemon: {err}"
+            )
+            .ok();
+        }
+    }
+
     #[instrument(skip_all)]
     async fn snapshot_working_copy(
         &mut self,
         ui: &Ui,
     ) -> Result<SnapshotStats, SnapshotWorkingCopyError> {
+        #[cfg(target_os = "linux")]
+        self.start_inotify_daemon(ui);
         let workspace_name = self.workspace_name().to_owned();
         let repo = self.repo().clone();
         let auto_tracking_matcher = self
@@ -1922,6 +1986,10 @@ to the current parents may contain changes from multiple commits.
             .snapshot_options_with_start_tracking_matcher(&auto_tracking_matcher)
             .map_err(snapshot_command_error)?;
 
+        let repo_path = self.repo_path().to_owned();
+        let compaction_threshold = snapshot_compaction_threshold(self.settings(), &repo_path)
+            .map_err(snapshot_command_error)?;
+
         // Compare working-copy tree and operation with repo's, and reload as needed.
         let mut locked_ws = self
             .workspace
@@ -1961,6 +2029,11 @@ to the current parents may contain changes from multiple commits.
             mut_repo
                 .set_wc_commit(workspace_name, commit.id().clone())
                 .map_err(snapshot_command_error)?;
+            // Record before rebasing so descendants with the same conflicts
+            // can be resolved.
+            record_conflict_resolutions(ui, &wc_commit.tree(), &new_tree)
+                .await
+                .map_err(snapshot_command_error)?;
 
             // Rebase descendants
             let num_rebased = mut_repo
@@ -1987,6 +2060,13 @@ to the current parents may contain changes from multiple commits.
                 .commit("snapshot working copy")
                 .await
                 .map_err(snapshot_command_error)?;
+            let repo = if let Some(older_than) = compaction_threshold {
+                compact_snapshot_operations(ui, &repo_path, repo, older_than)
+                    .await
+                    .map_err(snapshot_command_error)?
+            } else {
+                repo
+            };
             self.user_repo = ReadonlyUserRepo::new(repo);
         }
 
@@ -2038,6 +2118,23 @@ to the current parents may contain changes from multiple commits.
             maybe_old_commit,
             new_commit,
         )?;
+        #[cfg(feature = "git")]
+        if jj_lib::git::get_git_backend(self.repo().store()).is_ok()
+            && self.settings().get_bool("git.update-submodules")?
+        {
+            let old_tree = match maybe_old_commit {
+                Some(commit) => commit.tree(),
+                None => self.repo().store().empty_merged_tree(),
+            };
+            crate::git_util::update_submodules(
+                ui,
+                self.repo(),
+                self.workspace_root(),
+                self.path_converter(),
+                &old_tree,
+                &new_commit.tree(),
+            )?;
+        }
         self.print_updated_working_copy_stats(ui, maybe_old_commit, new_commit, &stats)
     }
 
@@ -2099,6 +2196,15 @@ to the current parents may contain changes from multiple commits.
         if num_rebased > 0 {
             writeln!(ui.status(), "Rebased {num_rebased} descendant commits")?;
         }
+        if let Some(resolutions) = tx.repo().store().resolution_store() {
+            for path in resolutions.take_resolved_paths() {
+                writeln!(
+                    ui.status(),
+                    "Resolved conflicts in {} using previous resolutions",
+                    self.format_file_path(&path)
+                )?;
+            }
+        }
 
         for (name, wc_commit_id) in &tx.repo().view().wc_commit_ids().clone() {
             // This can fail if trunk() bookmark gets deleted or conflicted. If
@@ -2463,6 +2569,35 @@ pub fn export_working_copy_changes_to_git(
     Ok(())
 }
 
+/// Records how the conflicts in `old_tree` were resolved in `new_tree` if
+/// `merge.rerere` is enabled. Failures are reported as warnings since the
+/// recorded resolutions are only used to resolve future conflicts.
+pub async fn record_conflict_resolutions(
+    ui: &Ui,
+    old_tree: &MergedTree,
+    new_tree: &MergedTree,
+) -> io::Result<()> {
+    let Some(resolutions) = old_tree.store().resolution_store() else {
+        return Ok(());
+    };
+    if !old_tree.has_conflict() {
+        return Ok(());
+    }
+    match jj_lib::rerere::record_resolutions(resolutions, old_tree, new_tree).await {
+        Ok(0) => {}
+        Ok(count) => {
+            tracing::info!(count, "recorded conflict resolutions");
+        }
+        Err(err) => {
+            writeln!(
+                ui.warning_default(),
+                "Failed to record conflict resolutions: {err}"
+            )?;
+        }
+    }
+    Ok(())
+}
+
 /// An ongoing [`Transaction`] tied to a particular workspace.
 ///
 /// `WorkspaceCommandTransaction`s are created with
@@ -2671,6 +2806,67 @@ pub fn start_repo_transaction(repo: &Arc<ReadonlyRepo>, string_args: &[String])
     tx
 }
 
+/// Returns the `snapshot.compaction.older-than` threshold if compaction of
+/// snapshot operations is enabled and the last compaction was run more than a
+/// day ago.
+fn snapshot_compaction_threshold(
+    settings: &UserSettings,
+    repo_path: &Path,
+) -> Result<Option<MillisSinceEpoch>, CommandError> {
+    if !settings.get_bool("snapshot.compaction.enabled")? {
+        return Ok(None);
+    }
+    let older_than = settings.get_string("snapshot.compaction.older-than")?;
+    let older_than = time_util::parse_date(&older_than, chrono::Local::now()).map_err(|err| {
+        config_error_with_message("Invalid `snapshot.compaction.older-than`", err)
+    })?;
+    let last_run = std::fs::metadata(repo_path.join(SNAPSHOT_COMPACTION_STAMP))
+        .and_then(|metadata| metadata.modified());
+    if last_run.is_ok_and(|time| time.elapsed().is_ok_and(|age| age < COMPACTION_INTERVAL)) {
+        return Ok(None);
+    }
+    Ok(Some(older_than))
+}
+
+/// Squashes runs of snapshot operations older than `older_than`.
+///
+/// This is skipped if there are other workspaces or concurrent operations
+/// since their working-copy operations can't be remapped here.
+async fn compact_snapshot_operations(
+    ui: &Ui,
+    repo_path: &Path,
+    repo: Arc<ReadonlyRepo>,
+    older_than: MillisSinceEpoch,
+) -> Result<Arc<ReadonlyRepo>, CommandError> {
+    if repo.view().wc_commit_ids().len() > 1
+        || repo.op_heads_store().get_op_heads().await? != [repo.op_id().clone()]
+    {
+        return Ok(repo);
+    }
+    let stats = op_walk::compact_snapshot_operations(
+        repo.op_store().as_ref(),
+        slice::from_ref(repo.operation()),
+        older_than,
+    )?;
+    let stamp_path = repo_path.join(SNAPSHOT_COMPACTION_STAMP);
+    std::fs::write(&stamp_path, "").context(&stamp_path)?;
+    let [new_op_id] = stats.new_head_ids.try_into().unwrap();
+    if &new_op_id == repo.op_id() {
+        return Ok(repo);
+    }
+    repo.op_heads_store()
+        .update_op_heads(slice::from_ref(repo.op_id()), &new_op_id)
+        .await?;
+    let new_op = repo.loader().load_operation(&new_op_id).await?;
+    let repo = repo.reload_at(&new_op).await?;
+    writeln!(
+        ui.status(),
+        "Compacted {} old snapshot operations.",
+        stats.unreachable_count
+    )?;
+    Ok(repo)
+}
+
 /// Check if the working copy is stale and reload the repo if the repo is ahead
 /// of the working copy.
 ///
@@ -3190,13 +3386,16 @@ impl DiffSelector {
     /// Restores diffs from the `right_tree` to the `left_tree` by using an
     /// interactive editor if enabled.
     ///
-    /// Only files matching the `matcher` will be copied to the new tree.
+    /// Only files matching the `matcher` will be copied to the new tree. The
+    /// `copy_records` are loaded only if the editor shows copied and renamed
+    /// files.
     pub fn select(
         &self,
         ui: &Ui,
         trees: Diff<&MergedTree>,
         tree_labels: Diff<String>,
         matcher: &dyn Matcher,
+        copy_records: impl FnOnce() -> BackendResult<CopyRecords>,
         format_instructions: impl FnOnce() -> String,
     ) -> Result<MergedTree, CommandError> {
         let selected_tree = restore_tree(
@@ -3220,6 +3419,7 @@ impl DiffSelector {
                     Ok(editor.edit(
                         Diff::new(trees.before, &selected_tree),
                         matcher,
+                        copy_records,
                         format_instructions,
                     )?)
                 }
@@ -3925,6 +4125,18 @@ pub fn format_template<C: Clone>(ui: &Ui, arg: &C, template: &TemplateRenderer<C
     output.into_string_lossy()
 }
 
+/// Writes `value` as a single line of JSON, as printed by the `--json` option
+/// of commands.
+///
+/// The output isn't labeled, so it never contains color codes.
+pub fn write_json_line(
+    formatter: &mut dyn Formatter,
+    value: &impl serde::Serialize,
+) -> io::Result<()> {
+    serde_json::to_writer(&mut *formatter, value)?;
+    writeln!(formatter)
+}
+
 /// CLI command builder and runner.
 #[must_use]
 pub struct CliRunner<'a> {
@@ -36,6 +36,8 @@ use jj_lib::fileset::FilesetParseErrorKind;
 use jj_lib::fix::FixError;
 use jj_lib::gitignore::GitIgnoreError;
 use jj_lib::index::IndexError;
+use jj_lib::lfs::LfsError;
+use jj_lib::merge_driver::ExternalMergeError;
 use jj_lib::op_heads_store::OpHeadResolutionError;
 use jj_lib::op_heads_store::OpHeadsStoreError;
 use jj_lib::op_store::OpStoreError;
@@ -47,6 +49,7 @@ use jj_lib::repo::RepoLoaderError;
 use jj_lib::repo::RewriteRootCommit;
 use jj_lib::repo_path::RepoPathBuf;
 use jj_lib::repo_path::UiPathParseError;
+use jj_lib::rerere::RerereError;
 use jj_lib::revset;
 use jj_lib::revset::RevsetEvaluationError;
 use jj_lib::revset::RevsetParseError;
@@ -319,6 +322,7 @@ impl From<BackendError> for CommandError {
     fn from(err: BackendError) -> Self {
         match &err {
             BackendError::Unsupported(_) => user_error(err),
+            BackendError::Other(source) if source.is::<ExternalMergeError>() => user_error(err),
             _ => internal_error_with_message("Unexpected error from backend", err),
         }
     }
@@ -463,11 +467,14 @@ impl From<ConflictResolveError> for CommandError {
         match err {
             ConflictResolveError::Backend(err) => err.into(),
             ConflictResolveError::Io(err) => err.into(),
+            ConflictResolveError::TextEdit(err) => err.into(),
             _ => {
                 let hint = match &err {
-                    ConflictResolveError::ConflictTooComplicated { .. } => {
-                        Some("Edit the conflict markers manually to resolve this.".to_owned())
-                    }
+                    ConflictResolveError::ConflictTooComplicated { .. } => Some(
+                        "Use `jj resolve --editor` or edit the conflict markers manually to \
+                         resolve this."
+                            .to_owned(),
+                    ),
                     ConflictResolveError::ExecutableConflict { .. } => {
                         Some("Use `jj file chmod` to update the executable bit.".to_owned())
                     }
@@ -754,6 +761,26 @@ impl From<FixError> for CommandError {
     }
 }
 
+impl From<LfsError> for CommandError {
+    fn from(err: LfsError) -> Self {
+        match err {
+            LfsError::Io { .. } => user_error_with_message("Failed to transfer LFS objects", err),
+            LfsError::Backend(err) => err.into(),
+        }
+    }
+}
+
+impl From<RerereError> for CommandError {
+    fn from(err: RerereError) -> Self {
+        match err {
+            RerereError::Backend(err) => err.into(),
+            RerereError::Io(_) | RerereError::Corrupt { .. } => {
+                internal_error_with_message("Failed to access recorded conflict resolutions", err)
+            }
+        }
+    }
+}
+
 impl From<BisectionError> for CommandError {
     fn from(err: BisectionError) -> Self {
         match err {
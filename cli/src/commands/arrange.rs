@@ -12,9 +12,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::io;
+use std::io::Write as _;
 use std::time::Duration;
 
+use clap_complete::ArgValueCompleter;
 use crossterm::ExecutableCommand as _;
 use crossterm::event::Event;
 use crossterm::event::KeyCode;
@@ -25,72 +29,625 @@ use crossterm::terminal::EnterAlternateScreen;
 use crossterm::terminal::LeaveAlternateScreen;
 use crossterm::terminal::disable_raw_mode;
 use crossterm::terminal::enable_raw_mode;
+use itertools::Itertools as _;
+use jj_lib::backend::CommitId;
+use jj_lib::commit::Commit;
+use jj_lib::dag_walk;
+use jj_lib::graph::GraphEdge;
+use jj_lib::repo::MutableRepo;
+use jj_lib::repo::Repo as _;
+use jj_lib::rewrite::CommitWithSelection;
+use jj_lib::rewrite::RewriteRefsOptions;
+use jj_lib::rewrite::squash_commits;
+use pollster::FutureExt as _;
 use ratatui::Terminal;
 use ratatui::layout::Constraint;
 use ratatui::layout::Direction;
 use ratatui::layout::Layout;
 use ratatui::prelude::CrosstermBackend;
 use ratatui::style::Color;
+use ratatui::style::Modifier;
 use ratatui::style::Style;
 use ratatui::text::Line;
 use ratatui::text::Span;
+use ratatui::widgets::Paragraph;
 use tracing::instrument;
 
 use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::cli_util::short_change_hash;
+use crate::cli_util::short_commit_hash;
 use crate::command_error::CommandError;
 use crate::command_error::internal_error;
+use crate::command_error::user_error;
+use crate::complete;
+use crate::description_util::join_message_paragraphs;
+use crate::graphlog::GraphStyle;
+use crate::graphlog::get_graphlog;
 use crate::ui::Ui;
 
-/// Interactively rearrange the commit graph.
+/// Interactively rearrange the commit graph
+///
+/// The revisions are shown as a graph which can be edited with the keyboard.
+/// Revisions can be moved up and down the stack, rebased onto other
+/// revisions in the graph, squashed into their parent, dropped, or made
+/// siblings of their parent. Nothing is rewritten until the plan is applied,
+/// and then all the changes are made in a single operation.
+///
+/// Keys:
+///
+/// * `j`/`k`: Select the next/previous revision
+/// * `J`/`K`: Swap the revision with its parent/child
+/// * `r`: Rebase the revision onto another revision, which is then selected and
+///   confirmed with `Enter`. The children of the revision are rebased onto its
+///   parents.
+/// * `s`: Squash the revision into its parent
+/// * `d`: Drop the revision
+/// * `p`: Make the revision a sibling of its parent
+/// * `u`: Undo the last edit
+/// * `a`: Apply the changes and quit
+/// * `q`: Quit without changing anything
 #[derive(clap::Args, Clone, Debug)]
-pub(crate) struct ArrangeArgs {}
+#[command(verbatim_doc_comment)]
+pub(crate) struct ArrangeArgs {
+    /// The revisions to rearrange
+    #[arg(
+        long,
+        short,
+        default_value = "reachable(@, mutable())",
+        value_name = "REVSETS"
+    )]
+    #[arg(add = ArgValueCompleter::new(complete::revset_expression_mutable))]
+    revisions: Vec<RevisionArg>,
+}
 
 #[instrument(skip_all)]
 pub(crate) fn cmd_arrange(
     ui: &mut Ui,
-    _command: &CommandHelper,
-    _args: &ArrangeArgs,
+    command: &CommandHelper,
+    args: &ArrangeArgs,
 ) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    // Children before parents
+    let commits: Vec<Commit> = workspace_command
+        .parse_union_revsets(ui, &args.revisions)?
+        .evaluate_to_commits()?
+        .try_collect()?;
+    if commits.is_empty() {
+        writeln!(ui.status(), "No revisions to arrange.")?;
+        return Ok(());
+    }
+    workspace_command.check_rewritable(commits.iter().map(Commit::id))?;
+
+    let wc_commit_id = workspace_command.get_wc_commit_id().cloned();
+    let nodes = commits
+        .iter()
+        .map(|commit| {
+            let summary = commit.description().lines().next().unwrap_or_default();
+            let summary = if summary.is_empty() {
+                "(no description set)"
+            } else {
+                summary
+            };
+            let label = format!(
+                "{} {} {summary}",
+                short_change_hash(commit.change_id()),
+                short_commit_hash(commit.id()),
+            );
+            let is_wc = wc_commit_id.as_ref() == Some(commit.id());
+            (commit.id().clone(), NodeInfo { label, is_wc })
+        })
+        .collect();
+    let plan = Plan::new(
+        commits
+            .iter()
+            .map(|commit| (commit.id().clone(), commit.parent_ids().to_vec())),
+    );
+    let mut app = App::new(plan, nodes, GraphStyle::from_settings(command.settings())?);
+
     // Set up the terminal
     io::stdout().execute(EnterAlternateScreen)?;
     enable_raw_mode()?;
     let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
     terminal.clear()?;
 
-    let result = run_tui(ui, &mut terminal);
+    let result = run_tui(&mut app, &mut terminal);
 
     // Restore the terminal
     disable_raw_mode()?;
     io::stdout().execute(LeaveAlternateScreen)?;
 
-    result
+    let Some(plan) = result? else {
+        writeln!(ui.status(), "Nothing changed.")?;
+        return Ok(());
+    };
+    if !plan.has_changes() {
+        writeln!(ui.status(), "Nothing changed.")?;
+        return Ok(());
+    }
+
+    let mut tx = workspace_command.start_transaction();
+    apply_plan(tx.repo_mut(), commits, &plan).block_on()?;
+    tx.finish(ui, format!("arrange {} commits", plan.len()))
 }
 
-fn run_tui<B: ratatui::backend::Backend>(
-    _ui: &mut Ui,
-    terminal: &mut Terminal<B>,
+/// Rewrites the `commits` according to the `plan`.
+async fn apply_plan(
+    repo: &mut MutableRepo,
+    commits: Vec<Commit>,
+    plan: &Plan,
 ) -> Result<(), CommandError> {
-    let help_items = [("q", "quit")];
-    let mut help_spans = Vec::new();
-    for (i, (key, desc)) in help_items.iter().enumerate() {
-        if i > 0 {
-            help_spans.push(Span::raw(" "));
+    let new_parents_map = plan.new_parents_map();
+    let mut invalid_squash = None;
+    repo.transform_commits(
+        commits,
+        &new_parents_map,
+        &RewriteRefsOptions::default(),
+        async |mut rewriter| {
+            if invalid_squash.is_some() {
+                return Ok(());
+            }
+            match plan.action(rewriter.old_commit().id()) {
+                Action::Keep => {
+                    if rewriter.parents_changed() {
+                        rewriter.rebase().await?.write().await?;
+                    }
+                }
+                Action::Drop => rewriter.abandon(),
+                Action::Squash => {
+                    let source = rewriter.old_commit().clone();
+                    let [destination_id] = rewriter.new_parents() else {
+                        invalid_squash = Some(source.id().clone());
+                        return Ok(());
+                    };
+                    let destination_id = destination_id.clone();
+                    let repo = rewriter.repo_mut();
+                    let destination = repo.store().get_commit_async(&destination_id).await?;
+                    let selection = CommitWithSelection {
+                        selected_tree: source.tree(),
+                        parent_tree: source.parent_tree_async(repo).await?,
+                        commit: source.clone(),
+                    };
+                    if let Some(squashed) =
+                        squash_commits(repo, &[selection], &destination, false).await?
+                    {
+                        let description = join_message_paragraphs(
+                            &[destination.description(), source.description()]
+                                .into_iter()
+                                .filter(|description| !description.is_empty())
+                                .map(str::to_owned)
+                                .collect_vec(),
+                        );
+                        squashed
+                            .commit_builder
+                            .set_description(description)
+                            .write()
+                            .await?;
+                        // `squash_commits()` moves the children onto the
+                        // original parents, which aren't the destination if the
+                        // revision was also moved.
+                        repo.record_abandoned_commit_with_parents(
+                            source.id().clone(),
+                            [destination_id],
+                        );
+                    }
+                }
+            }
+            Ok(())
+        },
+    )
+    .await?;
+    if let Some(id) = invalid_squash {
+        return Err(user_error(format!(
+            "Cannot squash revision {} because it doesn't have a single parent",
+            short_commit_hash(&id)
+        )));
+    }
+    Ok(())
+}
+
+/// What to do with a revision when the plan is applied.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Action {
+    Keep,
+    Drop,
+    /// Move the changes into the (single) parent, and abandon the revision.
+    Squash,
+}
+
+/// The edited graph of the revisions being arranged.
+///
+/// Parents outside the set of revisions are kept as they are, but can only be
+/// inherited from other revisions in the set.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Plan {
+    /// Revisions in the original order, children first.
+    order: Vec<CommitId>,
+    original_parents: HashMap<CommitId, Vec<CommitId>>,
+    parents: HashMap<CommitId, Vec<CommitId>>,
+    actions: HashMap<CommitId, Action>,
+}
+
+impl Plan {
+    fn new(commits: impl IntoIterator<Item = (CommitId, Vec<CommitId>)>) -> Self {
+        let mut order = vec![];
+        let mut parents = HashMap::new();
+        for (id, parent_ids) in commits {
+            order.push(id.clone());
+            parents.insert(id, parent_ids);
+        }
+        let actions = order.iter().map(|id| (id.clone(), Action::Keep)).collect();
+        Self {
+            order,
+            original_parents: parents.clone(),
+            parents,
+            actions,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    fn contains(&self, id: &CommitId) -> bool {
+        self.parents.contains_key(id)
+    }
+
+    fn parents(&self, id: &CommitId) -> &[CommitId] {
+        &self.parents[id]
+    }
+
+    fn action(&self, id: &CommitId) -> Action {
+        self.actions[id]
+    }
+
+    /// Returns the children of `id` in the set.
+    fn children(&self, id: &CommitId) -> Vec<CommitId> {
+        self.order
+            .iter()
+            .filter(|child_id| self.parents[*child_id].contains(id))
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the revisions in the edited graph, children first.
+    fn topo_order(&self) -> Vec<CommitId> {
+        dag_walk::topo_order_reverse(
+            self.order.iter().cloned(),
+            |id| id.clone(),
+            |id| {
+                self.parents[id]
+                    .iter()
+                    .filter(|parent_id| self.contains(parent_id))
+                    .cloned()
+                    .collect_vec()
+            },
+            |_| (),
+        )
+        .expect("plan should not have cycles")
+    }
+
+    /// Returns the single parent of `id`, which must be in the set.
+    fn single_parent_in_set(&self, id: &CommitId) -> Result<CommitId, String> {
+        match &self.parents[id][..] {
+            [parent_id] if self.contains(parent_id) => Ok(parent_id.clone()),
+            [_] => Err("The parent isn't being arranged".to_owned()),
+            _ => Err("Merge commits can't be moved this way".to_owned()),
+        }
+    }
+
+    /// Replaces `old` by `new` in the parents of the children of `old`.
+    fn replace_in_children(&mut self, old: &CommitId, new: &[CommitId]) {
+        for child_id in self.children(old) {
+            let parent_ids = self
+                .parents
+                .get_mut(&child_id)
+                .unwrap()
+                .drain(..)
+                .flat_map(|parent_id| {
+                    if &parent_id == old {
+                        new.to_vec()
+                    } else {
+                        vec![parent_id]
+                    }
+                })
+                .unique()
+                .collect();
+            self.parents.insert(child_id, parent_ids);
+        }
+    }
+
+    /// Moves `id` below its parent, so the parent becomes its child.
+    fn swap_with_parent(&mut self, id: &CommitId) -> Result<(), String> {
+        let parent_id = self.single_parent_in_set(id)?;
+        self.replace_in_children(id, std::slice::from_ref(&parent_id));
+        let grandparent_ids = self.parents[&parent_id].clone();
+        self.parents.insert(id.clone(), grandparent_ids);
+        self.parents.insert(parent_id, vec![id.clone()]);
+        Ok(())
+    }
+
+    /// Moves `id` above its child, so the child becomes its parent.
+    fn swap_with_child(&mut self, id: &CommitId) -> Result<(), String> {
+        match &self.children(id)[..] {
+            [child_id] => self.swap_with_parent(child_id),
+            [] => Err("The revision has no children".to_owned()),
+            _ => Err("The revision has multiple children".to_owned()),
+        }
+    }
+
+    /// Rebases `id` onto `destination`. The children of `id` are rebased
+    /// onto its parents.
+    fn rebase_onto(&mut self, id: &CommitId, destination: &CommitId) -> Result<(), String> {
+        if id == destination {
+            return Err("Cannot rebase a revision onto itself".to_owned());
+        }
+        let parent_ids = self.parents[id].clone();
+        self.replace_in_children(id, &parent_ids);
+        self.parents.insert(id.clone(), vec![destination.clone()]);
+        Ok(())
+    }
+
+    /// Makes `id` a sibling of its parent. The children of `id` become merges
+    /// of `id` and its former parent.
+    fn parallelize_with_parent(&mut self, id: &CommitId) -> Result<(), String> {
+        let parent_id = self.single_parent_in_set(id)?;
+        self.replace_in_children(id, &[parent_id.clone(), id.clone()]);
+        let grandparent_ids = self.parents[&parent_id].clone();
+        self.parents.insert(id.clone(), grandparent_ids);
+        Ok(())
+    }
+
+    fn toggle_drop(&mut self, id: &CommitId) -> Result<(), String> {
+        let action = match self.actions[id] {
+            Action::Drop => Action::Keep,
+            _ if self
+                .children(id)
+                .iter()
+                .any(|child_id| self.actions[child_id] == Action::Squash) =>
+            {
+                return Err("A child is squashed into the revision".to_owned());
+            }
+            _ => Action::Drop,
+        };
+        self.actions.insert(id.clone(), action);
+        Ok(())
+    }
+
+    fn toggle_squash(&mut self, id: &CommitId) -> Result<(), String> {
+        let action = match self.actions[id] {
+            Action::Squash => Action::Keep,
+            _ => {
+                let parent_id = self.single_parent_in_set(id)?;
+                if self.actions[&parent_id] == Action::Drop {
+                    return Err("The parent is dropped".to_owned());
+                }
+                Action::Squash
+            }
+        };
+        self.actions.insert(id.clone(), action);
+        Ok(())
+    }
+
+    /// Checks that the squashed revisions still have a parent to squash into.
+    fn validate(&self) -> Result<(), (CommitId, String)> {
+        for id in &self.order {
+            if self.actions[id] != Action::Squash {
+                continue;
+            }
+            let parent_id = self
+                .single_parent_in_set(id)
+                .map_err(|message| (id.clone(), message))?;
+            if self.actions[&parent_id] == Action::Drop {
+                return Err((id.clone(), "The parent is dropped".to_owned()));
+            }
+        }
+        Ok(())
+    }
+
+    fn has_changes(&self) -> bool {
+        self.parents != self.original_parents
+            || self.actions.values().any(|action| *action != Action::Keep)
+    }
+
+    /// Returns the new parents of the revisions whose parents were edited.
+    fn new_parents_map(&self) -> HashMap<CommitId, Vec<CommitId>> {
+        self.parents
+            .iter()
+            .filter(|(id, parent_ids)| self.original_parents[*id] != **parent_ids)
+            .map(|(id, parent_ids)| (id.clone(), parent_ids.clone()))
+            .collect()
+    }
+}
+
+struct NodeInfo {
+    label: String,
+    is_wc: bool,
+}
+
+struct App {
+    plan: Plan,
+    history: Vec<Plan>,
+    nodes: HashMap<CommitId, NodeInfo>,
+    graph_style: GraphStyle,
+    selected: usize,
+    /// Revision being rebased, while the destination is being chosen.
+    rebase_source: Option<CommitId>,
+    message: String,
+}
+
+impl App {
+    fn new(plan: Plan, nodes: HashMap<CommitId, NodeInfo>, graph_style: GraphStyle) -> Self {
+        Self {
+            plan,
+            history: vec![],
+            nodes,
+            graph_style,
+            selected: 0,
+            rebase_source: None,
+            message: String::new(),
+        }
+    }
+
+    fn selected_id(&self) -> CommitId {
+        self.plan.topo_order()[self.selected].clone()
+    }
+
+    fn edit(&mut self, f: impl FnOnce(&mut Plan, &CommitId) -> Result<(), String>) {
+        let id = self.selected_id();
+        let mut plan = self.plan.clone();
+        match f(&mut plan, &id) {
+            Ok(()) => {
+                self.history.push(std::mem::replace(&mut self.plan, plan));
+                // Keep the same revision selected
+                self.selected = self
+                    .plan
+                    .topo_order()
+                    .iter()
+                    .position(|x| *x == id)
+                    .unwrap();
+                self.message.clear();
+            }
+            Err(message) => self.message = message,
+        }
+    }
+
+    /// Renders the graph, and returns the lines and the index of the first
+    /// line of each node.
+    fn render_graph(&self) -> (Vec<String>, Vec<usize>) {
+        let ascii = matches!(self.graph_style, GraphStyle::Ascii | GraphStyle::AsciiLarge);
+        let output = RefCell::new(vec![]);
+        let mut writer = SharedWriter(&output);
+        let mut graph = get_graphlog(self.graph_style, &mut writer);
+        let mut node_lines = vec![];
+        for id in self.plan.topo_order() {
+            node_lines.push(output.borrow().iter().filter(|b| **b == b'\n').count());
+            let edges = self
+                .plan
+                .parents(&id)
+                .iter()
+                .map(|parent_id| {
+                    if self.plan.contains(parent_id) {
+                        GraphEdge::direct(parent_id.clone())
+                    } else {
+                        GraphEdge::missing(parent_id.clone())
+                    }
+                })
+                .collect_vec();
+            let node = &self.nodes[&id];
+            let (symbol, tag) = match self.plan.action(&id) {
+                Action::Keep if node.is_wc => ("@", ""),
+                Action::Keep => (if ascii { "o" } else { "○" }, ""),
+                Action::Drop => (if ascii { "x" } else { "×" }, " [drop]"),
+                Action::Squash => (if ascii { "o" } else { "○" }, " [squash]"),
+            };
+            let text = format!("{}{tag}\n", node.label);
+            // Writing to memory can't fail
+            graph.add_node(&id, &edges, symbol, &text).unwrap();
         }
-        help_spans.push(Span::styled(*key, Style::default().fg(Color::Magenta)));
-        help_spans.push(Span::raw(format!(" {desc}")));
+        drop(graph);
+        let lines = String::from_utf8_lossy(&output.into_inner())
+            .lines()
+            .map(str::to_owned)
+            .collect();
+        (lines, node_lines)
     }
-    let help_line = Line::from(help_spans);
+}
+
+/// Writer which lets the output be inspected while the graph is rendered.
+struct SharedWriter<'a>(&'a RefCell<Vec<u8>>);
 
+impl io::Write for SharedWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn run_tui<B: ratatui::backend::Backend>(
+    app: &mut App,
+    terminal: &mut Terminal<B>,
+) -> Result<Option<Plan>, CommandError> {
+    let mut scroll = 0;
     loop {
+        let (graph_lines, node_lines) = app.render_graph();
+        let selected_line = node_lines[app.selected];
+        let rebase_source_line = app.rebase_source.as_ref().map(|id| {
+            let index = app.plan.topo_order().iter().position(|x| x == id).unwrap();
+            node_lines[index]
+        });
+        let help_items: &[(&str, &str)] = if app.rebase_source.is_some() {
+            &[
+                ("j/k", "choose destination"),
+                ("enter", "rebase onto it"),
+                ("esc", "cancel"),
+            ]
+        } else {
+            &[
+                ("j/k", "select"),
+                ("J/K", "swap with parent/child"),
+                ("r", "rebase"),
+                ("s", "squash"),
+                ("d", "drop"),
+                ("p", "parallelize"),
+                ("u", "undo"),
+                ("a", "apply"),
+                ("q", "quit"),
+            ]
+        };
+        let mut help_spans = Vec::new();
+        for (i, (key, desc)) in help_items.iter().enumerate() {
+            if i > 0 {
+                help_spans.push(Span::raw(" "));
+            }
+            help_spans.push(Span::styled(*key, Style::default().fg(Color::Magenta)));
+            help_spans.push(Span::raw(format!(" {desc}")));
+        }
+        let help_line = Line::from(help_spans);
+        let message_line = Line::styled(app.message.as_str(), Style::default().fg(Color::Yellow));
+
         terminal
             .draw(|frame| {
                 let layout = Layout::default()
                     .direction(Direction::Vertical)
-                    .constraints([Constraint::Fill(1), Constraint::Length(1)])
+                    .constraints([
+                        Constraint::Fill(1),
+                        Constraint::Length(1),
+                        Constraint::Length(1),
+                    ])
                     .split(frame.area());
-                let _main_area = layout[0];
-                let help_area = layout[1];
+                let main_area = layout[0];
+                let message_area = layout[1];
+                let help_area = layout[2];
 
+                // Keep the selected node visible
+                let height = usize::from(main_area.height.max(1));
+                if selected_line < scroll {
+                    scroll = selected_line;
+                } else if selected_line >= scroll + height {
+                    scroll = selected_line + 1 - height;
+                }
+                let lines = graph_lines
+                    .iter()
+                    .enumerate()
+                    .map(|(i, line)| {
+                        let style = if i == selected_line {
+                            Style::default().add_modifier(Modifier::REVERSED)
+                        } else if Some(i) == rebase_source_line {
+                            Style::default().fg(Color::Cyan)
+                        } else {
+                            Style::default()
+                        };
+                        Line::styled(line.as_str(), style)
+                    })
+                    .collect_vec();
+                let graph =
+                    Paragraph::new(lines).scroll((scroll.try_into().unwrap_or(u16::MAX), 0));
+                frame.render_widget(graph, main_area);
+                frame.render_widget(&message_line, message_area);
                 frame.render_widget(&help_line, help_area);
             })
             .map_err(|e| internal_error(format!("Failed to draw TUI: {e}")))?;
@@ -102,13 +659,350 @@ fn run_tui<B: ratatui::backend::Backend>(
             }) = event::read()
                 .map_err(|e| internal_error(format!("Failed to read TUI events: {e}")))?
         {
-            #[expect(clippy::single_match)] // There will soon be more matches
-            match (code, modifiers) {
-                (KeyCode::Char('q'), KeyModifiers::NONE) => {
-                    return Ok(());
+            if code == KeyCode::Char('c') && modifiers == KeyModifiers::CONTROL {
+                return Ok(None);
+            }
+            match code {
+                KeyCode::Char('j') | KeyCode::Down => {
+                    app.selected = (app.selected + 1).min(app.plan.len() - 1);
                 }
+                KeyCode::Char('k') | KeyCode::Up => {
+                    app.selected = app.selected.saturating_sub(1);
+                }
+                KeyCode::Esc if app.rebase_source.is_some() => {
+                    app.rebase_source = None;
+                }
+                KeyCode::Enter if app.rebase_source.is_some() => {
+                    let source = app.rebase_source.take().unwrap();
+                    let destination = app.selected_id();
+                    app.selected = app
+                        .plan
+                        .topo_order()
+                        .iter()
+                        .position(|x| *x == source)
+                        .unwrap();
+                    app.edit(|plan, id| plan.rebase_onto(id, &destination));
+                }
+                _ if app.rebase_source.is_some() => {}
+                KeyCode::Char('J') => app.edit(Plan::swap_with_parent),
+                KeyCode::Char('K') => app.edit(Plan::swap_with_child),
+                KeyCode::Char('r') => {
+                    app.rebase_source = Some(app.selected_id());
+                    app.message.clear();
+                }
+                KeyCode::Char('s') => app.edit(Plan::toggle_squash),
+                KeyCode::Char('d') => app.edit(Plan::toggle_drop),
+                KeyCode::Char('p') => app.edit(Plan::parallelize_with_parent),
+                KeyCode::Char('u') => {
+                    if let Some(plan) = app.history.pop() {
+                        let id = app.selected_id();
+                        app.plan = plan;
+                        app.selected = app.plan.topo_order().iter().position(|x| *x == id).unwrap();
+                        app.message.clear();
+                    } else {
+                        app.message = "Nothing to undo".to_owned();
+                    }
+                }
+                KeyCode::Char('a') => match app.plan.validate() {
+                    Ok(()) => return Ok(Some(app.plan.clone())),
+                    Err((id, message)) => {
+                        app.selected = app.plan.topo_order().iter().position(|x| *x == id).unwrap();
+                        app.message = format!("Cannot squash the selected revision: {message}");
+                    }
+                },
+                KeyCode::Char('q') => return Ok(None),
                 _ => {}
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use jj_lib::repo::ReadonlyRepo;
+    use testutils::TestRepo;
+    use testutils::create_tree;
+    use testutils::repo_path;
+
+    use super::*;
+
+    fn id(hex: &'static str) -> CommitId {
+        CommitId::from_hex(hex)
+    }
+
+    fn ids(hexes: &[&'static str]) -> Vec<CommitId> {
+        hexes.iter().map(|hex| id(hex)).collect()
+    }
+
+    /// Plan for the stack `01 <- 02 <- 03 <- 04`, where `01` isn't arranged.
+    fn stack_plan() -> Plan {
+        Plan::new([
+            (id("04"), ids(&["03"])),
+            (id("03"), ids(&["02"])),
+            (id("02"), ids(&["01"])),
+        ])
+    }
+
+    #[test]
+    fn test_swap_with_parent() {
+        let mut plan = stack_plan();
+        plan.swap_with_parent(&id("03")).unwrap();
+        assert_eq!(plan.parents(&id("04")), ids(&["02"]));
+        assert_eq!(plan.parents(&id("02")), ids(&["03"]));
+        assert_eq!(plan.parents(&id("03")), ids(&["01"]));
+        assert_eq!(plan.topo_order(), ids(&["04", "02", "03"]));
+        assert_eq!(
+            plan.new_parents_map(),
+            HashMap::from([
+                (id("04"), ids(&["02"])),
+                (id("03"), ids(&["01"])),
+                (id("02"), ids(&["03"])),
+            ])
+        );
+
+        // The parent of the bottom revision isn't arranged
+        assert!(plan.swap_with_parent(&id("03")).is_err());
+    }
+
+    #[test]
+    fn test_swap_with_child() {
+        let mut plan = stack_plan();
+        plan.swap_with_child(&id("02")).unwrap();
+        assert_eq!(plan.topo_order(), ids(&["04", "02", "03"]));
+        assert!(plan.swap_with_child(&id("04")).is_err());
+
+        // Swapping back restores the original graph
+        plan.swap_with_parent(&id("02")).unwrap();
+        assert!(!plan.has_changes());
+    }
+
+    #[test]
+    fn test_rebase_onto() {
+        let mut plan = stack_plan();
+        plan.rebase_onto(&id("02"), &id("04")).unwrap();
+        assert_eq!(plan.parents(&id("03")), ids(&["01"]));
+        assert_eq!(plan.parents(&id("02")), ids(&["04"]));
+        assert_eq!(plan.topo_order(), ids(&["02", "04", "03"]));
+        assert!(plan.rebase_onto(&id("02"), &id("02")).is_err());
+    }
+
+    #[test]
+    fn test_parallelize_with_parent() {
+        let mut plan = stack_plan();
+        plan.parallelize_with_parent(&id("03")).unwrap();
+        assert_eq!(plan.parents(&id("04")), ids(&["02", "03"]));
+        assert_eq!(plan.parents(&id("03")), ids(&["01"]));
+        assert_eq!(plan.parents(&id("02")), ids(&["01"]));
+
+        // Merge commits can't be moved
+        assert!(plan.swap_with_parent(&id("04")).is_err());
+        assert!(plan.parallelize_with_parent(&id("04")).is_err());
+    }
+
+    #[test]
+    fn test_drop_and_squash() {
+        let mut plan = stack_plan();
+        plan.toggle_squash(&id("04")).unwrap();
+        assert_eq!(plan.action(&id("04")), Action::Squash);
+        assert!(plan.has_changes());
+        assert!(plan.new_parents_map().is_empty());
+        // The destination of a squash can't be dropped, and vice versa
+        assert!(plan.toggle_drop(&id("03")).is_err());
+        plan.toggle_drop(&id("02")).unwrap();
+        assert!(plan.toggle_squash(&id("03")).is_err());
+        // The bottom revision has no parent to squash into
+        plan.toggle_drop(&id("02")).unwrap();
+        assert!(plan.toggle_squash(&id("02")).is_err());
+        plan.validate().unwrap();
+
+        // Moving a squashed revision away from its parent is caught later
+        plan.rebase_onto(&id("04"), &id("02")).unwrap();
+        plan.validate().unwrap();
+        plan.swap_with_parent(&id("04")).unwrap();
+        assert_eq!(plan.validate().unwrap_err().0, id("04"));
+
+        plan.toggle_squash(&id("04")).unwrap();
+        assert_eq!(plan.action(&id("04")), Action::Keep);
+    }
+
+    /// The stack `A <- B <- C <- D <- E`, where each commit adds the file
+    /// named after its description. Only `B`, `C` and `D` are arranged.
+    struct ApplyTest {
+        _test_repo: TestRepo,
+        repo: Arc<ReadonlyRepo>,
+        commits: HashMap<&'static str, Commit>,
+    }
+
+    impl ApplyTest {
+        fn new() -> Self {
+            let test_repo = TestRepo::init();
+            let repo = &test_repo.repo;
+            let mut tx = repo.start_transaction();
+            let mut commits = HashMap::new();
+            let mut parent = repo.store().root_commit();
+            let mut files = vec![];
+            for name in ["A", "B", "C", "D", "E"] {
+                files.push(name.to_lowercase());
+                let path_contents = files
+                    .iter()
+                    .map(|file| (repo_path(file), file.as_str()))
+                    .collect_vec();
+                let commit = tx
+                    .repo_mut()
+                    .new_commit(vec![parent.id().clone()], create_tree(repo, &path_contents))
+                    .set_description(name)
+                    .write()
+                    .block_on()
+                    .unwrap();
+                commits.insert(name, commit.clone());
+                parent = commit;
+            }
+            let repo = tx.commit("test").block_on().unwrap();
+            Self {
+                _test_repo: test_repo,
+                repo,
+                commits,
+            }
+        }
+
+        fn plan(&self) -> Plan {
+            Plan::new(["D", "C", "B"].map(|name| {
+                let commit = &self.commits[name];
+                (commit.id().clone(), commit.parent_ids().to_vec())
+            }))
+        }
+
+        fn id(&self, name: &str) -> CommitId {
+            self.commits[name].id().clone()
+        }
+
+        /// Applies the plan, and describes the resulting graph.
+        fn apply(&self, plan: &Plan) -> Result<Vec<String>, CommandError> {
+            let mut tx = self.repo.start_transaction();
+            let commits = ["D", "C", "B"].map(|name| self.commits[name].clone());
+            apply_plan(tx.repo_mut(), commits.to_vec(), plan).block_on()?;
+            tx.repo_mut().rebase_descendants().block_on().unwrap();
+            let repo = tx.commit("test").block_on().unwrap();
+            Ok(describe_graph(&repo))
+        }
+    }
+
+    /// Describes every commit as `description <- parent descriptions: files`.
+    fn describe_graph(repo: &Arc<ReadonlyRepo>) -> Vec<String> {
+        let describe = |commit: &Commit| commit.description().split_whitespace().join(" ");
+        let mut commits = repo.view().heads().iter().cloned().collect_vec();
+        let mut lines = vec![];
+        while let Some(id) = commits.pop() {
+            if &id == repo.store().root_commit_id() {
+                continue;
+            }
+            let commit = repo.store().get_commit(&id).unwrap();
+            let parents: Vec<_> = commit.parents().try_collect().unwrap();
+            let files = commit
+                .tree()
+                .entries()
+                .map(|(path, _)| path.as_internal_file_string().to_owned())
+                .join(",");
+            let parent_descriptions = parents.iter().map(describe).join(", ");
+            lines.push(format!(
+                "{} <- {parent_descriptions}: {files}",
+                describe(&commit)
+            ));
+            commits.extend(parents.iter().map(|parent| parent.id().clone()));
+        }
+        lines.sort();
+        lines.dedup();
+        lines
+    }
+
+    #[test]
+    fn test_apply_drop() {
+        let test = ApplyTest::new();
+        let mut plan = test.plan();
+        plan.toggle_drop(&test.id("C")).unwrap();
+        assert_eq!(
+            test.apply(&plan).unwrap(),
+            [
+                "A <- : a",
+                "B <- A: a,b",
+                "D <- B: a,b,d",
+                "E <- D: a,b,d,e"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_apply_squash() {
+        let test = ApplyTest::new();
+        let mut plan = test.plan();
+        plan.toggle_squash(&test.id("C")).unwrap();
+        assert_eq!(
+            test.apply(&plan).unwrap(),
+            [
+                "A <- : a",
+                "B C <- A: a,b,c",
+                "D <- B C: a,b,c,d",
+                "E <- D: a,b,c,d,e"
+            ]
+        );
+
+        // Chained squashes end up in the bottom revision
+        plan.toggle_squash(&test.id("D")).unwrap();
+        assert_eq!(
+            test.apply(&plan).unwrap(),
+            ["A <- : a", "B C D <- A: a,b,c,d", "E <- B C D: a,b,c,d,e"]
+        );
+    }
+
+    #[test]
+    fn test_apply_reparent() {
+        let test = ApplyTest::new();
+        let mut plan = test.plan();
+        plan.swap_with_parent(&test.id("D")).unwrap();
+        assert_eq!(
+            test.apply(&plan).unwrap(),
+            [
+                "A <- : a",
+                "B <- A: a,b",
+                "C <- D: a,b,c,d",
+                "D <- B: a,b,d",
+                "E <- D: a,b,d,e",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_apply_reparent_and_squash() {
+        let test = ApplyTest::new();
+        let mut plan = test.plan();
+        plan.rebase_onto(&test.id("D"), &test.id("B")).unwrap();
+        plan.toggle_squash(&test.id("D")).unwrap();
+        // The child of the squashed revision is moved onto the destination
+        // rather than onto the original parent
+        assert_eq!(
+            test.apply(&plan).unwrap(),
+            [
+                "A <- : a",
+                "B D <- A: a,b,d",
+                "C <- B D: a,b,c,d",
+                "E <- B D: a,b,d,e",
+            ]
+        );
+
+        // The squash destination can also be moved
+        let mut plan = test.plan();
+        plan.swap_with_parent(&test.id("D")).unwrap();
+        plan.toggle_squash(&test.id("C")).unwrap();
+        assert_eq!(
+            test.apply(&plan).unwrap(),
+            [
+                "A <- : a",
+                "B <- A: a,b",
+                "D C <- B: a,b,c,d",
+                "E <- D C: a,b,c,d,e"
+            ]
+        );
+    }
+}
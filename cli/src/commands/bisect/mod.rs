@@ -12,16 +12,52 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod bad;
+mod good;
+mod reset;
 mod run;
+mod skip;
+mod start;
+
+use std::io;
+use std::io::Write as _;
+use std::path::Path;
+use std::path::PathBuf;
+
+use itertools::Itertools as _;
+use jj_lib::backend::CommitId;
+use jj_lib::bisect::BisectionResult;
+use jj_lib::bisect::Bisector;
+use jj_lib::bisect::Evaluation;
+use jj_lib::bisect::NextStep;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::op_store::OperationId;
+use jj_lib::repo::Repo as _;
+use jj_lib::revset::RevsetExpression;
+use serde::Deserialize;
+use serde::Serialize;
 
 use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::cli_util::WorkspaceCommandHelper;
+use crate::cli_util::short_operation_hash;
 use crate::command_error::CommandError;
+use crate::command_error::internal_error_with_message;
+use crate::command_error::user_error;
 use crate::ui::Ui;
 
 /// Find a bad revision by bisection.
+///
+/// Use `jj bisect run` to let a command evaluate the revisions, or `jj bisect
+/// start` followed by `jj bisect good`/`bad`/`skip` to evaluate them by hand.
 #[derive(clap::Subcommand, Clone, Debug)]
 pub enum BisectCommand {
+    Bad(bad::BisectBadArgs),
+    Good(good::BisectGoodArgs),
+    Reset(reset::BisectResetArgs),
     Run(run::BisectRunArgs),
+    Skip(skip::BisectSkipArgs),
+    Start(start::BisectStartArgs),
 }
 
 pub fn cmd_bisect(
@@ -30,6 +66,239 @@ pub fn cmd_bisect(
     subcommand: &BisectCommand,
 ) -> Result<(), CommandError> {
     match subcommand {
+        BisectCommand::Bad(args) => bad::cmd_bisect_bad(ui, command, args),
+        BisectCommand::Good(args) => good::cmd_bisect_good(ui, command, args),
+        BisectCommand::Reset(args) => reset::cmd_bisect_reset(ui, command, args),
         BisectCommand::Run(args) => run::cmd_bisect_run(ui, command, args),
+        BisectCommand::Skip(args) => skip::cmd_bisect_skip(ui, command, args),
+        BisectCommand::Start(args) => start::cmd_bisect_start(ui, command, args),
+    }
+}
+
+/// State of a manual bisection session, persisted between invocations.
+///
+/// Commit and operation IDs are stored as hex strings.
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct BisectState {
+    /// The operation the bisection was started at.
+    start_operation: String,
+    /// The working-copy commit at the time the bisection was started.
+    start_working_copy: Option<String>,
+    /// The commits in the input range. The heads are assumed to be bad.
+    range: Vec<String>,
+    good: Vec<String>,
+    bad: Vec<String>,
+    skipped: Vec<String>,
+    /// The commit that is currently being evaluated.
+    current: Option<String>,
+}
+
+impl BisectState {
+    fn path(workspace_command: &WorkspaceCommandHelper) -> PathBuf {
+        workspace_command.repo_path().join("bisect.json")
+    }
+
+    fn load(path: &Path) -> Result<Option<Self>, CommandError> {
+        let content = match std::fs::read(path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => {
+                return Err(internal_error_with_message(
+                    "Failed to read bisection state",
+                    err,
+                ));
+            }
+        };
+        let state = serde_json::from_slice(&content)
+            .map_err(|err| internal_error_with_message("Failed to parse bisection state", err))?;
+        Ok(Some(state))
+    }
+
+    /// Loads the state of the bisection in progress, or fails if there's none.
+    fn load_in_progress(path: &Path) -> Result<Self, CommandError> {
+        Self::load(path)?.ok_or_else(|| {
+            user_error("No bisection in progress").hinted("Use `jj bisect start` to start one.")
+        })
+    }
+
+    fn save(&self, path: &Path) -> Result<(), CommandError> {
+        let content = serde_json::to_vec_pretty(self).unwrap();
+        std::fs::write(path, content)
+            .map_err(|err| internal_error_with_message("Failed to write bisection state", err))
+    }
+
+    fn start_operation_id(&self) -> Result<OperationId, CommandError> {
+        OperationId::try_from_hex(&self.start_operation)
+            .ok_or_else(|| internal_error_with_message("Invalid bisection state", "bad op id"))
+    }
+
+    /// Records the evaluation of the given commit, replacing any previous
+    /// evaluation of it.
+    fn mark(&mut self, id: &CommitId, evaluation: &Evaluation) {
+        let hex = id.hex();
+        for list in [&mut self.good, &mut self.bad, &mut self.skipped] {
+            list.retain(|other| *other != hex);
+        }
+        let list = match evaluation {
+            Evaluation::Good => &mut self.good,
+            Evaluation::Bad => &mut self.bad,
+            Evaluation::Skip | Evaluation::Abort => &mut self.skipped,
+        };
+        list.push(hex);
+    }
+}
+
+fn parse_commit_ids(hexes: &[String]) -> Result<Vec<CommitId>, CommandError> {
+    hexes
+        .iter()
+        .map(|hex| {
+            CommitId::try_from_hex(hex).ok_or_else(|| {
+                internal_error_with_message(
+                    "Invalid bisection state",
+                    format!("Invalid commit ID: {hex}"),
+                )
+            })
+        })
+        .try_collect()
+}
+
+/// Builds a bisector that has seen all evaluations recorded in `state`.
+fn restore_bisector<'repo>(
+    workspace_command: &'repo WorkspaceCommandHelper,
+    state: &BisectState,
+) -> Result<Bisector<'repo>, CommandError> {
+    let range = RevsetExpression::commits(parse_commit_ids(&state.range)?);
+    let mut bisector = Bisector::new(workspace_command.repo().as_ref(), range)?;
+    for id in parse_commit_ids(&state.good)? {
+        if bisector.bad_commits().contains(&id) {
+            return Err(user_error(format!(
+                "Revision {} is a head of the bisection range, which is assumed to be bad",
+                workspace_command
+                    .format_commit_summary(&workspace_command.repo().store().get_commit(&id)?)
+            )));
+        }
+        bisector.mark_good(id);
+    }
+    for id in parse_commit_ids(&state.bad)? {
+        bisector.mark_bad(id);
+    }
+    for id in parse_commit_ids(&state.skipped)? {
+        if !bisector.bad_commits().contains(&id) {
+            bisector.mark_skipped(id);
+        }
+    }
+    Ok(bisector)
+}
+
+/// Marks the given revisions (or the revision being evaluated) and checks out
+/// the next revision to evaluate.
+fn mark_and_advance(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    revisions: &[RevisionArg],
+    evaluation: Evaluation,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let state_path = BisectState::path(&workspace_command);
+    let mut state = BisectState::load_in_progress(&state_path)?;
+    let ids: Vec<CommitId> = if revisions.is_empty() {
+        let current = state.current.as_slice();
+        if current.is_empty() {
+            return Err(user_error("No revision is being evaluated")
+                .hinted("Specify the revisions to mark as arguments."));
+        }
+        parse_commit_ids(current)?
+    } else {
+        workspace_command
+            .parse_union_revsets(ui, revisions)?
+            .evaluate()?
+            .iter()
+            .try_collect()?
+    };
+    for id in &ids {
+        state.mark(id, &evaluation);
+    }
+    advance(ui, workspace_command, &state_path, state)
+}
+
+/// Determines the next revision to evaluate and checks it out, or prints the
+/// result of the bisection if it's complete.
+fn advance(
+    ui: &mut Ui,
+    mut workspace_command: WorkspaceCommandHelper,
+    state_path: &Path,
+    mut state: BisectState,
+) -> Result<(), CommandError> {
+    let (next_step, num_candidates) = {
+        let mut bisector = restore_bisector(&workspace_command, &state)?;
+        (bisector.next_step()?, bisector.num_candidates()?)
+    };
+    match next_step {
+        NextStep::Evaluate(commit) => {
+            let mut tx = workspace_command.start_transaction();
+            tx.check_out(&commit)?;
+            tx.finish(
+                ui,
+                format!("Updated to revision {} for bisection", commit.id().hex()),
+            )?;
+            // Save the state only once the revision is checked out, so a failed
+            // checkout doesn't leave a stale revision to mark.
+            state.current = Some(commit.id().hex());
+            state.save(state_path)?;
+            let mut formatter = ui.stdout_formatter();
+            write!(formatter, "Now evaluating: ")?;
+            workspace_command
+                .commit_summary_template()
+                .format(&commit, formatter.as_mut())?;
+            writeln!(formatter)?;
+            // Each step halves the remaining candidates.
+            let num_steps = usize::BITS - num_candidates.leading_zeros();
+            writeln!(
+                formatter,
+                "{num_candidates} revision{} left to test (roughly {num_steps} step{})",
+                if num_candidates == 1 { "" } else { "s" },
+                if num_steps == 1 { "" } else { "s" },
+            )?;
+            writeln!(
+                formatter,
+                "Mark it with `jj bisect good`, `jj bisect bad`, or `jj bisect skip`."
+            )?;
+        }
+        NextStep::Done(result) => {
+            state.current = None;
+            state.save(state_path)?;
+            let mut formatter = ui.stdout_formatter();
+            match result {
+                BisectionResult::Abort => {
+                    return Err(user_error("Bisection aborted"));
+                }
+                BisectionResult::Indeterminate => {
+                    return Err(user_error(
+                        "Could not find the first bad revision. Were all candidates skipped?",
+                    ));
+                }
+                BisectionResult::Found(first_bad_commits) => {
+                    let commit_template = workspace_command.commit_summary_template();
+                    if let [first_bad_commit] = first_bad_commits.as_slice() {
+                        write!(formatter, "The first bad revision is: ")?;
+                        commit_template.format(first_bad_commit, formatter.as_mut())?;
+                        writeln!(formatter)?;
+                    } else {
+                        writeln!(formatter, "The first bad revisions are:")?;
+                        for first_bad_commit in first_bad_commits {
+                            commit_template.format(&first_bad_commit, formatter.as_mut())?;
+                            writeln!(formatter)?;
+                        }
+                    }
+                }
+            }
+            writeln!(
+                formatter,
+                "Search complete. Run `jj bisect reset` to end the bisection (or `jj op restore \
+                 {}` to also discard any revisions created during search).",
+                short_operation_hash(&state.start_operation_id()?)
+            )?;
+        }
     }
+    Ok(())
 }
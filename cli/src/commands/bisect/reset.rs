@@ -0,0 +1,86 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+
+use jj_lib::backend::CommitId;
+use jj_lib::repo::Repo as _;
+use pollster::FutureExt as _;
+use tracing::instrument;
+
+use super::BisectState;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::short_operation_hash;
+use crate::command_error::CommandError;
+use crate::command_error::internal_error_with_message;
+use crate::ui::Ui;
+
+/// End a manual bisection
+///
+/// Forgets the state of the bisection and goes back to the working-copy
+/// revision that was checked out when `jj bisect start` was run. Revisions
+/// created during the search are kept; use `jj op restore` to discard them.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct BisectResetArgs {}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_bisect_reset(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    _args: &BisectResetArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let state_path = BisectState::path(&workspace_command);
+    let state = BisectState::load_in_progress(&state_path)?;
+
+    let start_wc_id = state
+        .start_working_copy
+        .as_deref()
+        .and_then(CommitId::try_from_hex);
+    if let Some(start_wc_id) = start_wc_id
+        && workspace_command.get_wc_commit_id() != Some(&start_wc_id)
+    {
+        let start_wc_commit = workspace_command.repo().store().get_commit(&start_wc_id)?;
+        let mut tx = workspace_command.start_transaction();
+        if start_wc_commit.is_hidden(tx.repo())? {
+            // The working-copy commit was probably abandoned when we moved away from it
+            // because it was empty. Create an equivalent one.
+            let new_wc_commit = tx
+                .repo_mut()
+                .new_commit(
+                    start_wc_commit.parent_ids().to_vec(),
+                    start_wc_commit.tree(),
+                )
+                .write()
+                .block_on()?;
+            tx.edit(&new_wc_commit)?;
+        } else {
+            tx.edit(&start_wc_commit)?;
+        }
+        tx.finish(ui, "end bisection")?;
+    }
+
+    std::fs::remove_file(&state_path)
+        .map_err(|err| internal_error_with_message("Failed to remove bisection state", err))?;
+    writeln!(
+        ui.status(),
+        "Bisection reset. To discard any revisions created during search, run:"
+    )?;
+    writeln!(
+        ui.status(),
+        "  jj op restore {}",
+        short_operation_hash(&state.start_operation_id()?)
+    )?;
+    Ok(())
+}
@@ -0,0 +1,75 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use clap_complete::ArgValueCompleter;
+use itertools::Itertools as _;
+use jj_lib::object_id::ObjectId as _;
+use tracing::instrument;
+
+use super::BisectState;
+use super::advance;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::CommandError;
+use crate::command_error::user_error;
+use crate::complete;
+use crate::ui::Ui;
+
+/// Start a manual bisection
+///
+/// Checks out the first revision to evaluate. Test it however you like, then
+/// mark it with `jj bisect good`, `jj bisect bad`, or `jj bisect skip` to
+/// proceed to the next revision. The state of the bisection is kept until `jj
+/// bisect reset` is run, so it can span many commands.
+///
+/// It is assumed that if a given revision is bad, then all its descendants
+/// in the input range are also bad.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct BisectStartArgs {
+    /// Range of revisions to bisect
+    ///
+    /// This is typically a range like `v1.0..main`. The heads of the range are
+    /// assumed to be bad. Ancestors of the range that are not also in the range
+    /// are assumed to be good.
+    #[arg(long, short, value_name = "REVSETS", required = true)]
+    #[arg(add = ArgValueCompleter::new(complete::revset_expression_all))]
+    range: Vec<RevisionArg>,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_bisect_start(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &BisectStartArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let state_path = BisectState::path(&workspace_command);
+    if BisectState::load(&state_path)?.is_some() {
+        return Err(user_error("A bisection is already in progress")
+            .hinted("Use `jj bisect reset` to end it first."));
+    }
+    let range: Vec<_> = workspace_command
+        .parse_union_revsets(ui, &args.range)?
+        .evaluate()?
+        .iter()
+        .map_ok(|id| id.hex())
+        .try_collect()?;
+    let state = BisectState {
+        start_operation: workspace_command.repo().op_id().hex(),
+        start_working_copy: workspace_command.get_wc_commit_id().map(|id| id.hex()),
+        range,
+        ..Default::default()
+    };
+    advance(ui, workspace_command, &state_path, state)
+}
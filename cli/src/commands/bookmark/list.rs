@@ -25,6 +25,7 @@ use super::warn_unmatched_local_or_remote_bookmarks;
 use crate::cli_util::CommandHelper;
 use crate::cli_util::RevisionArg;
 use crate::cli_util::default_ignored_remote_name;
+use crate::cli_util::write_json_line;
 use crate::command_error::CommandError;
 use crate::commit_ref_list;
 use crate::commit_ref_list::RefFilterPredicates;
@@ -110,6 +111,15 @@ pub struct BookmarkListArgs {
     #[arg(add = ArgValueCandidates::new(complete::template_aliases))]
     template: Option<String>,
 
+    /// Print each bookmark as a line of JSON instead of rendering a template
+    ///
+    /// See [JSON output] for the format.
+    ///
+    /// [JSON output]:
+    ///     https://docs.jj-vcs.dev/latest/json-output/
+    #[arg(long, conflicts_with = "template")]
+    json: bool,
+
     /// Sort bookmarks based on the given key (or multiple keys)
     ///
     /// Suffix the key with `-` to sort in descending order of the value (e.g.
@@ -147,7 +157,9 @@ pub fn cmd_bookmark_list(
         HashSet::new()
     };
 
-    let template: TemplateRenderer<Rc<CommitRef>> = {
+    let template: Option<TemplateRenderer<Rc<CommitRef>>> = if args.json {
+        None
+    } else {
         let language = workspace_command.commit_template_language();
         let text = match &args.template {
             Some(value) => value.to_owned(),
@@ -155,9 +167,10 @@ pub fn cmd_bookmark_list(
                 .settings()
                 .get("templates.bookmark_list")?,
         };
-        workspace_command
+        let template = workspace_command
             .parse_template(ui, &language, &text)?
-            .labeled(["bookmark_list"])
+            .labeled(["bookmark_list"]);
+        Some(template)
     };
 
     let ignored_tracked_remote = default_ignored_remote_name(repo.store());
@@ -193,10 +206,14 @@ pub fn cmd_bookmark_list(
 
     ui.request_pager();
     let mut formatter = ui.stdout_formatter();
-    bookmark_list_items
+    let mut commit_refs = bookmark_list_items
         .iter()
-        .flat_map(|item| itertools::chain([&item.primary], &item.tracked))
-        .try_for_each(|commit_ref| template.format(commit_ref, formatter.as_mut()))?;
+        .flat_map(|item| itertools::chain([&item.primary], &item.tracked));
+    if let Some(template) = &template {
+        commit_refs.try_for_each(|commit_ref| template.format(commit_ref, formatter.as_mut()))?;
+    } else {
+        commit_refs.try_for_each(|commit_ref| write_json_line(formatter.as_mut(), commit_ref))?;
+    }
     drop(formatter);
 
     warn_unmatched_local_or_remote_bookmarks(ui, view, &name_expr)?;
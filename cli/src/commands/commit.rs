@@ -30,6 +30,9 @@ use crate::description_util::add_trailers;
 use crate::description_util::description_template;
 use crate::description_util::edit_description;
 use crate::description_util::join_message_paragraphs;
+use crate::diff_util::get_copy_records_between;
+use crate::hooks::run_commit_msg_hook;
+use crate::hooks::run_pre_commit_hook;
 use crate::text_util::parse_author;
 use crate::ui::Ui;
 
@@ -131,6 +134,12 @@ pub(crate) fn cmd_commit(
         .get_wc_commit_id()
         .ok_or_else(|| user_error("This command requires a working copy"))?;
     let commit = workspace_command.repo().store().get_commit(commit_id)?;
+    run_pre_commit_hook(
+        ui,
+        workspace_command.settings(),
+        workspace_command.workspace_root(),
+        &commit,
+    )?;
     let matcher = workspace_command
         .parse_file_patterns(ui, &args.paths)?
         .to_matcher();
@@ -153,11 +162,20 @@ new working-copy commit.
             tx.format_commit_summary(&commit)
         )
     };
+    let copy_records = || {
+        get_copy_records_between(
+            tx.repo().store(),
+            commit.parent_ids(),
+            [commit.id()],
+            &matcher,
+        )
+    };
     let tree = diff_selector.select(
         ui,
         Diff::new(&base_tree, &commit.tree()),
         Diff::new(commit.parents_conflict_label()?, commit.conflict_label()),
         matcher.as_ref(),
+        copy_records,
         format_instructions,
     )?;
     if !args.paths.is_empty() && tree.tree_ids() == base_tree.tree_ids() {
@@ -217,6 +235,12 @@ new working-copy commit.
     } else {
         description
     };
+    let description = run_commit_msg_hook(
+        ui,
+        tx.settings(),
+        tx.base_workspace_helper().workspace_root(),
+        description,
+    )?;
     commit_builder.set_description(description);
     let new_commit = commit_builder.write(tx.repo_mut()).block_on()?;
 
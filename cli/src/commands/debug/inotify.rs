@@ -0,0 +1,101 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[cfg(target_os = "linux")]
+use std::io::Write as _;
+
+use clap::Subcommand;
+#[cfg(target_os = "linux")]
+use jj_lib::fsmonitor::FsmonitorSettings;
+
+#[cfg(target_os = "linux")]
+use super::check_local_disk_wc;
+use crate::cli_util::CommandHelper;
+use crate::command_error::CommandError;
+#[cfg(target_os = "linux")]
+use crate::command_error::internal_error;
+#[cfg(not(target_os = "linux"))]
+use crate::command_error::user_error;
+#[cfg(target_os = "linux")]
+use crate::command_error::user_error_with_message;
+use crate::ui::Ui;
+
+#[derive(Subcommand, Clone, Debug)]
+pub enum DebugInotifyCommand {
+    /// Check whether the inotify filesystem monitor is enabled and whether its
+    /// daemon is running
+    Status,
+    QueryChangedFiles,
+    /// Watch the working copy in the foreground
+    Daemon,
+}
+
+#[cfg(target_os = "linux")]
+pub fn cmd_debug_inotify(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    subcommand: &DebugInotifyCommand,
+) -> Result<(), CommandError> {
+    match subcommand {
+        DebugInotifyCommand::Status => {
+            let workspace_command = command.workspace_helper_no_snapshot(ui)?;
+            if let FsmonitorSettings::Inotify(_) =
+                FsmonitorSettings::from_settings(workspace_command.settings())?
+            {
+                writeln!(ui.stdout(), "Inotify is enabled via `fsmonitor.backend`.")?;
+            } else {
+                writeln!(
+                    ui.stdout(),
+                    r#"Inotify is disabled. Set `fsmonitor.backend="inotify"` to enable."#
+                )?;
+            }
+            let wc = check_local_disk_wc(workspace_command.working_copy())?;
+            let is_running = wc
+                .inotify_fsmonitor()
+                .is_daemon_running()
+                .map_err(internal_error)?;
+            writeln!(
+                ui.stdout(),
+                "The inotify daemon is {}.",
+                if is_running { "running" } else { "not running" }
+            )?;
+        }
+        DebugInotifyCommand::QueryChangedFiles => {
+            let workspace_command = command.workspace_helper_no_snapshot(ui)?;
+            let wc = check_local_disk_wc(workspace_command.working_copy())?;
+            let (clock, changed_files) = wc.query_inotify()?;
+            writeln!(ui.stdout(), "Clock: {clock:?}")?;
+            writeln!(ui.stdout(), "Changed files: {changed_files:?}")?;
+        }
+        DebugInotifyCommand::Daemon => {
+            let workspace = command.load_workspace()?;
+            let wc = check_local_disk_wc(workspace.working_copy())?;
+            wc.inotify_fsmonitor()
+                .run_daemon()
+                .map_err(|err| user_error_with_message("The inotify daemon failed", err))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn cmd_debug_inotify(
+    _ui: &mut Ui,
+    _command: &CommandHelper,
+    _subcommand: &DebugInotifyCommand,
+) -> Result<(), CommandError> {
+    Err(user_error(
+        "The inotify filesystem monitor is only supported on Linux",
+    ))
+}
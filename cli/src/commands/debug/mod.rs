@@ -17,6 +17,7 @@ mod fileset;
 mod index;
 mod index_changed_paths;
 mod init_simple;
+mod inotify;
 mod local_working_copy;
 mod object;
 mod reindex;
@@ -42,6 +43,8 @@ use self::index_changed_paths::DebugIndexChangedPathsArgs;
 use self::index_changed_paths::cmd_debug_index_changed_paths;
 use self::init_simple::DebugInitSimpleArgs;
 use self::init_simple::cmd_debug_init_simple;
+use self::inotify::DebugInotifyCommand;
+use self::inotify::cmd_debug_inotify;
 use self::local_working_copy::DebugLocalWorkingCopyArgs;
 use self::local_working_copy::cmd_debug_local_working_copy;
 use self::object::DebugObjectArgs;
@@ -76,6 +79,8 @@ pub enum DebugCommand {
     Index(DebugIndexArgs),
     IndexChangedPaths(DebugIndexChangedPathsArgs),
     InitSimple(DebugInitSimpleArgs),
+    #[command(subcommand)]
+    Inotify(DebugInotifyCommand),
     LocalWorkingCopy(DebugLocalWorkingCopyArgs),
     #[command(subcommand)]
     Object(DebugObjectArgs),
@@ -101,6 +106,7 @@ pub fn cmd_debug(
         DebugCommand::Index(args) => cmd_debug_index(ui, command, args),
         DebugCommand::IndexChangedPaths(args) => cmd_debug_index_changed_paths(ui, command, args),
         DebugCommand::InitSimple(args) => cmd_debug_init_simple(ui, command, args),
+        DebugCommand::Inotify(args) => cmd_debug_inotify(ui, command, args),
         DebugCommand::LocalWorkingCopy(args) => cmd_debug_local_working_copy(ui, command, args),
         DebugCommand::Object(args) => cmd_debug_object(ui, command, args),
         DebugCommand::Reindex(args) => cmd_debug_reindex(ui, command, args),
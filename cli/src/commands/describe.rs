@@ -38,6 +38,7 @@ use crate::description_util::edit_description;
 use crate::description_util::edit_multiple_descriptions;
 use crate::description_util::join_message_paragraphs;
 use crate::description_util::parse_trailers_template;
+use crate::hooks::run_commit_msg_hook;
 use crate::text_util::complete_newline;
 use crate::text_util::parse_author;
 use crate::ui::Ui;
@@ -289,6 +290,18 @@ pub(crate) fn cmd_describe(
         }
     }
 
+    for (commit, commit_builder) in iter::zip(&commits, &mut commit_builders) {
+        if commit.description() != commit_builder.description() {
+            let description = run_commit_msg_hook(
+                ui,
+                tx.settings(),
+                tx.base_workspace_helper().workspace_root(),
+                commit_builder.description().to_owned(),
+            )?;
+            commit_builder.set_description(description);
+        }
+    }
+
     // Filter out unchanged commits to avoid rebasing descendants in
     // `transform_descendants` below unnecessarily.
     let commit_builders: HashMap<_, _> = iter::zip(&commits, commit_builders)
@@ -19,6 +19,7 @@ use clap_complete::ArgValueCompleter;
 use itertools::Itertools as _;
 use jj_lib::merge::Diff;
 use jj_lib::object_id::ObjectId as _;
+use jj_lib::repo::Repo as _;
 use jj_lib::rewrite::merge_commit_trees;
 use pollster::FutureExt as _;
 use tracing::instrument;
@@ -28,6 +29,7 @@ use crate::cli_util::RevisionArg;
 use crate::cli_util::print_unmatched_explicit_paths;
 use crate::command_error::CommandError;
 use crate::complete;
+use crate::diff_util::get_copy_records_between;
 use crate::ui::Ui;
 
 /// Touch up the content changes in a revision with a diff editor
@@ -136,8 +138,20 @@ don't make any changes, then the operation will be aborted.",
     };
     let base_tree = merge_commit_trees(tx.repo(), base_commits.as_slice()).block_on()?;
     let tree = target_commit.tree();
-    let edited_tree =
-        diff_editor.edit(Diff::new(&base_tree, &tree), &matcher, format_instructions)?;
+    let copy_records = || {
+        get_copy_records_between(
+            tx.repo().store(),
+            base_commits.iter().map(|commit| commit.id()),
+            [target_commit.id()],
+            &matcher,
+        )
+    };
+    let edited_tree = diff_editor.edit(
+        Diff::new(&base_tree, &tree),
+        &matcher,
+        copy_records,
+        format_instructions,
+    )?;
     if edited_tree.tree_ids() == target_commit.tree_ids() {
         writeln!(ui.status(), "Nothing changed.")?;
     } else {
@@ -12,12 +12,19 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::path::Path;
+
 use clap_complete::ArgValueCandidates;
 use clap_complete::ArgValueCompleter;
+use itertools::Itertools as _;
 use jj_lib::annotate::FileAnnotation;
 use jj_lib::annotate::FileAnnotator;
 use jj_lib::annotate::LineOrigin;
+use jj_lib::backend::CommitId;
+use jj_lib::config::ConfigGetResultExt as _;
+use jj_lib::object_id::ObjectId as _;
 use jj_lib::repo::Repo;
+use jj_lib::repo_path::RepoPath;
 use jj_lib::revset::RevsetExpression;
 use tracing::instrument;
 
@@ -25,6 +32,7 @@ use crate::cli_util::CommandHelper;
 use crate::cli_util::RevisionArg;
 use crate::command_error::CommandError;
 use crate::command_error::user_error;
+use crate::command_error::user_error_with_message;
 use crate::commit_templater::AnnotationLine;
 use crate::complete;
 use crate::templater::TemplateRenderer;
@@ -34,6 +42,14 @@ use crate::ui::Ui;
 ///
 /// Annotates a revision line by line. Each line includes the source change that
 /// introduced the associated line. A path to the desired file must be provided.
+///
+/// Lines are traced through renames and copies of the file. The path the line
+/// was introduced at is available as `original_path` in the template.
+///
+/// Revisions which only reformat code can be skipped with `--ignore-rev`, or
+/// listed in the file set by the `annotate.ignore-revs-file` config, which has
+/// the same format as Git's `blame.ignoreRevsFile`. The lines changed by the
+/// ignored revisions are attributed to the lines they replaced.
 #[derive(clap::Args, Clone, Debug)]
 pub(crate) struct FileAnnotateArgs {
     /// the file to annotate
@@ -46,6 +62,11 @@ pub(crate) struct FileAnnotateArgs {
     #[arg(add = ArgValueCompleter::new(complete::revset_expression_all))]
     revision: Option<RevisionArg>,
 
+    /// Revisions to skip, attributing their changes to the revisions before
+    #[arg(long, value_name = "REVSETS")]
+    #[arg(add = ArgValueCompleter::new(complete::revset_expression_all))]
+    ignore_rev: Vec<RevisionArg>,
+
     /// Render each line using the given template
     ///
     /// All 0-argument methods of the [`AnnotationLine` type] are available as
@@ -101,18 +122,67 @@ pub(crate) fn cmd_file_annotate(
     // Note that this is probably different from "--skip REVS", which won't
     // exclude the revisions, but will ignore diffs in those revisions as if
     // ancestor revisions had new content.
+    let mut ignored_ids: Vec<CommitId> = workspace_command
+        .parse_union_revsets(ui, &args.ignore_rev)?
+        .evaluate_to_commit_ids()?
+        .try_collect()?;
+    if let Some(path) = workspace_command
+        .settings()
+        .get_string("annotate.ignore-revs-file")
+        .optional()?
+        .filter(|path| !path.is_empty())
+    {
+        let path = workspace_command.workspace_root().join(path);
+        ignored_ids.extend(load_ignore_revs_file(repo.as_ref(), &path)?);
+    }
     let mut annotator = FileAnnotator::from_commit(&starting_commit, &file_path)?;
-    annotator.compute(repo.as_ref(), &RevsetExpression::all())?;
+    annotator.compute_ignoring(
+        repo.as_ref(),
+        &RevsetExpression::all(),
+        &RevsetExpression::commits(ignored_ids),
+    )?;
     let annotation = annotator.to_annotation();
 
-    render_file_annotation(repo.as_ref(), ui, &template, &annotation)?;
+    render_file_annotation(repo.as_ref(), ui, &template, &file_path, &annotation)?;
     Ok(())
 }
 
+/// Reads commit ids from a file in the format of Git's `blame.ignoreRevsFile`.
+/// Commits which aren't in the repo are skipped.
+fn load_ignore_revs_file(repo: &dyn Repo, path: &Path) -> Result<Vec<CommitId>, CommandError> {
+    let content = std::fs::read_to_string(path).map_err(|err| {
+        user_error_with_message(
+            format!("Failed to read ignore-revs file {}", path.display()),
+            err,
+        )
+    })?;
+    let mut ids = vec![];
+    for line in content.lines() {
+        let line = line.split_once('#').map_or(line, |(line, _comment)| line);
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let id = CommitId::try_from_hex(line)
+            .filter(|id| id.as_bytes().len() == repo.store().commit_id_length())
+            .ok_or_else(|| {
+                user_error(format!(
+                    "Invalid commit id in ignore-revs file {}: {line}",
+                    path.display()
+                ))
+            })?;
+        if repo.index().has_id(&id)? {
+            ids.push(id);
+        }
+    }
+    Ok(ids)
+}
+
 fn render_file_annotation(
     repo: &dyn Repo,
     ui: &mut Ui,
     template_render: &TemplateRenderer<AnnotationLine>,
+    file_path: &RepoPath,
     annotation: &FileAnnotation,
 ) -> Result<(), CommandError> {
     ui.request_pager();
@@ -125,6 +195,7 @@ fn render_file_annotation(
     let default_line_origin = LineOrigin {
         commit_id: repo.store().root_commit_id().clone(),
         line_number: 0,
+        path: file_path.to_owned(),
     };
     for (line_number, (line_origin, content)) in annotation.line_origins().enumerate() {
         let line_origin = line_origin.unwrap_or(&default_line_origin);
@@ -135,6 +206,7 @@ fn render_file_annotation(
             content: content.to_owned(),
             line_number: line_number + 1,
             original_line_number: line_origin.line_number + 1,
+            original_path: line_origin.path.clone(),
             first_line_in_hunk,
         };
         template_render.format(&annotation_line, formatter.as_mut())?;
@@ -0,0 +1,381 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fs::File;
+use std::io;
+use std::io::BufWriter;
+use std::io::Seek as _;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use chrono::Datelike as _;
+use chrono::Timelike as _;
+use clap_complete::ArgValueCompleter;
+use flate2::write::GzEncoder;
+use jj_lib::backend::Timestamp;
+use jj_lib::conflicts::ConflictMaterializeOptions;
+use jj_lib::conflicts::MaterializedTreeValue;
+use jj_lib::conflicts::materialize_merge_result_to_bytes;
+use jj_lib::conflicts::materialize_tree_value;
+use jj_lib::file_util::copy_async_to_sync;
+use jj_lib::repo::Repo as _;
+use pollster::FutureExt as _;
+use tokio::io::AsyncRead;
+use tracing::instrument;
+use zip::ZipWriter;
+use zip::write::SimpleFileOptions;
+
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::cli_util::print_unmatched_explicit_paths;
+use crate::command_error::CommandError;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_message;
+use crate::complete;
+use crate::ui::Ui;
+
+/// Export files in a revision to an archive
+///
+/// Executable bits and symlinks are preserved. The modification time of the
+/// archived files is set to the committer timestamp of the revision, so the
+/// same revision always produces the same archive.
+///
+/// Conflicted files are rejected unless `--allow-conflicts` is specified, in
+/// which case they are written with conflict markers. Git submodules are
+/// skipped.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct FileArchiveArgs {
+    /// The revision to archive
+    #[arg(long, short, default_value = "@", value_name = "REVSET")]
+    #[arg(add = ArgValueCompleter::new(complete::revset_expression_all))]
+    revision: RevisionArg,
+
+    /// The file to write the archive to
+    #[arg(long, short, value_name = "PATH", value_hint = clap::ValueHint::FilePath)]
+    output: PathBuf,
+
+    /// The archive format
+    ///
+    /// If not specified, the format is detected from the extension of the
+    /// output file.
+    #[arg(long, value_enum)]
+    format: Option<ArchiveFormat>,
+
+    /// Prepend the given prefix to each path in the archive
+    ///
+    /// Add a trailing slash to put the files in a directory.
+    #[arg(long, value_name = "PREFIX")]
+    prefix: Option<String>,
+
+    /// Write conflicted files with conflict markers instead of failing
+    #[arg(long)]
+    allow_conflicts: bool,
+
+    /// Only archive files matching these prefixes (instead of all files)
+    #[arg(value_name = "FILESETS", value_hint = clap::ValueHint::AnyPath)]
+    #[arg(add = ArgValueCompleter::new(complete::all_revision_files))]
+    paths: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, clap::ValueEnum)]
+enum ArchiveFormat {
+    /// Uncompressed tar archive
+    Tar,
+    /// Gzip-compressed tar archive
+    #[value(name = "tar.gz", alias = "tgz")]
+    TarGz,
+    /// Zip archive
+    Zip,
+}
+
+impl ArchiveFormat {
+    fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?.to_ascii_lowercase();
+        if name.ends_with(".tar") {
+            Some(Self::Tar)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(Self::TarGz)
+        } else if name.ends_with(".zip") {
+            Some(Self::Zip)
+        } else {
+            None
+        }
+    }
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_file_archive(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &FileArchiveArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let format = match args.format {
+        Some(format) => format,
+        None => ArchiveFormat::from_path(&args.output).ok_or_else(|| {
+            user_error(format!(
+                "Cannot detect the archive format of {}",
+                args.output.display()
+            ))
+            .hinted("Use --format to specify the format, or use .tar, .tar.gz or .zip extension.")
+        })?,
+    };
+    let commit = workspace_command.resolve_single_rev(ui, &args.revision)?;
+    let tree = commit.tree();
+    let fileset_expression = workspace_command.parse_file_patterns(ui, &args.paths)?;
+    let matcher = fileset_expression.to_matcher();
+
+    // Write to a temporary file in the same directory, so a partially written
+    // archive doesn't replace the output file on error.
+    let output_dir = match args.output.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let create_err =
+        |err| user_error_with_message(format!("Failed to create {}", args.output.display()), err);
+    let mut temp_file_builder = tempfile::Builder::new();
+    // Temporary files are only readable by the owner by default, whereas the
+    // archive should be created with the usual permissions.
+    #[cfg(unix)]
+    temp_file_builder.permissions(std::os::unix::fs::PermissionsExt::from_mode(0o666));
+    let temp_file = temp_file_builder
+        .tempfile_in(output_dir)
+        .map_err(create_err)?;
+    let mut archive = ArchiveWriter::new(
+        format,
+        BufWriter::new(temp_file),
+        commit.committer().timestamp,
+    )?;
+    let prefix = args.prefix.as_deref().unwrap_or("");
+    let store = workspace_command.repo().store();
+    let materialize_options = ConflictMaterializeOptions {
+        marker_style: workspace_command.env().conflict_marker_style(),
+        marker_len: None,
+        merge: store.merge_options().clone(),
+    };
+    let mut num_files = 0;
+    for (path, value) in tree.entries_matching(matcher.as_ref()) {
+        let value = value?;
+        let archive_path = format!("{prefix}{}", path.as_internal_file_string());
+        let ui_path = workspace_command.format_file_path(&path);
+        let is_conflict = !value.is_resolved();
+        if is_conflict && !args.allow_conflicts {
+            return Err(
+                user_error(format!("Cannot archive conflicted file {ui_path}")).hinted(
+                    "Use --allow-conflicts to write conflicted files with conflict markers.",
+                ),
+            );
+        }
+        let materialized = materialize_tree_value(store, &path, value, tree.labels()).block_on()?;
+        match materialized {
+            MaterializedTreeValue::Absent => panic!("absent values should be excluded"),
+            MaterializedTreeValue::AccessDenied(err) => {
+                return Err(user_error_with_message(
+                    format!("Access denied to {ui_path}"),
+                    err,
+                ));
+            }
+            MaterializedTreeValue::File(file) => {
+                archive.add_file(&archive_path, file.reader, file.executable)?;
+            }
+            MaterializedTreeValue::Symlink { id: _, target } => {
+                archive.add_symlink(&archive_path, &target)?;
+            }
+            MaterializedTreeValue::GitSubmodule(_) => {
+                writeln!(ui.warning_default(), "Skipping Git submodule {ui_path}")?;
+                continue;
+            }
+            MaterializedTreeValue::FileConflict(file) => {
+                let contents = materialize_merge_result_to_bytes(
+                    &file.contents,
+                    &file.labels,
+                    &materialize_options,
+                );
+                let executable = file.executable.unwrap_or(false);
+                archive.add_file(&archive_path, contents.as_slice(), executable)?;
+            }
+            MaterializedTreeValue::OtherConflict { id, labels } => {
+                let contents = id.describe(&labels);
+                archive.add_file(&archive_path, contents.as_bytes(), false)?;
+            }
+            MaterializedTreeValue::Tree(_) => panic!("entries should not contain trees"),
+        }
+        if is_conflict {
+            writeln!(
+                ui.warning_default(),
+                "Archived conflicted file {ui_path} with conflict markers"
+            )?;
+        }
+        num_files += 1;
+    }
+    let temp_file = archive
+        .finish()?
+        .into_inner()
+        .map_err(|err| err.into_error())?;
+    temp_file.persist(&args.output).map_err(|err| {
+        user_error_with_message(format!("Failed to write {}", args.output.display()), err)
+    })?;
+    print_unmatched_explicit_paths(ui, &workspace_command, &fileset_expression, [&tree])?;
+    writeln!(
+        ui.status(),
+        "Wrote {num_files} file{} to {}",
+        if num_files == 1 { "" } else { "s" },
+        args.output.display()
+    )?;
+    Ok(())
+}
+
+enum ArchiveWriter<W: Write + io::Seek> {
+    Tar(tar::Builder<W>, u64),
+    /// The uncompressed tar archive is written to a temporary file since the
+    /// size of each file is only known after writing its contents. It's
+    /// compressed into the output writer when finished.
+    TarGz(tar::Builder<BufWriter<File>>, W, u64),
+    Zip(Box<ZipWriter<W>>, zip::DateTime),
+}
+
+impl<W: Write + io::Seek> ArchiveWriter<W> {
+    fn new(format: ArchiveFormat, writer: W, timestamp: Timestamp) -> io::Result<Self> {
+        // Tar stores the UTC time, whereas zip stores the local time without
+        // time zone.
+        let mtime = timestamp
+            .timestamp
+            .0
+            .div_euclid(1000)
+            .try_into()
+            .unwrap_or(0);
+        let archive = match format {
+            ArchiveFormat::Tar => Self::Tar(tar::Builder::new(writer), mtime),
+            ArchiveFormat::TarGz => {
+                let tar_file = BufWriter::new(tempfile::tempfile()?);
+                Self::TarGz(tar::Builder::new(tar_file), writer, mtime)
+            }
+            ArchiveFormat::Zip => {
+                let datetime = timestamp
+                    .to_datetime()
+                    .ok()
+                    .and_then(|datetime| {
+                        zip::DateTime::from_date_and_time(
+                            datetime.year().try_into().ok()?,
+                            datetime.month().try_into().ok()?,
+                            datetime.day().try_into().ok()?,
+                            datetime.hour().try_into().ok()?,
+                            datetime.minute().try_into().ok()?,
+                            datetime.second().try_into().ok()?,
+                        )
+                        .ok()
+                    })
+                    .unwrap_or_default();
+                Self::Zip(Box::new(ZipWriter::new(writer)), datetime)
+            }
+        };
+        Ok(archive)
+    }
+
+    /// Adds a file, streaming its `contents` into the archive.
+    fn add_file(
+        &mut self,
+        path: &str,
+        contents: impl AsyncRead,
+        executable: bool,
+    ) -> io::Result<()> {
+        let mode = if executable { 0o755 } else { 0o644 };
+        match self {
+            Self::Tar(builder, mtime) => append_tar_file(builder, *mtime, path, contents, mode),
+            Self::TarGz(builder, _, mtime) => {
+                append_tar_file(builder, *mtime, path, contents, mode)
+            }
+            Self::Zip(writer, datetime) => {
+                // The size isn't known in advance, so allow files larger than
+                // 4 GiB.
+                let options = SimpleFileOptions::default()
+                    .compression_method(zip::CompressionMethod::Deflated)
+                    .last_modified_time(*datetime)
+                    .unix_permissions(mode)
+                    .large_file(true);
+                writer.start_file(path, options)?;
+                copy_async_to_sync(contents, writer.as_mut()).block_on()?;
+                Ok(())
+            }
+        }
+    }
+
+    fn add_symlink(&mut self, path: &str, target: &str) -> io::Result<()> {
+        match self {
+            Self::Tar(builder, mtime) => append_tar_symlink(builder, *mtime, path, target),
+            Self::TarGz(builder, _, mtime) => append_tar_symlink(builder, *mtime, path, target),
+            Self::Zip(writer, datetime) => {
+                let options = SimpleFileOptions::default().last_modified_time(*datetime);
+                writer.add_symlink(path, target, options)?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Finishes the archive and returns the output writer.
+    fn finish(self) -> io::Result<W> {
+        match self {
+            Self::Tar(builder, _) => {
+                let mut writer = builder.into_inner()?;
+                writer.flush()?;
+                Ok(writer)
+            }
+            Self::TarGz(builder, writer, _) => {
+                let mut tar_file = builder.into_inner()?.into_inner()?;
+                tar_file.rewind()?;
+                let mut encoder = GzEncoder::new(writer, flate2::Compression::default());
+                io::copy(&mut tar_file, &mut encoder)?;
+                let mut writer = encoder.finish()?;
+                writer.flush()?;
+                Ok(writer)
+            }
+            Self::Zip(writer, _) => {
+                let mut writer = (*writer).finish()?;
+                writer.flush()?;
+                Ok(writer)
+            }
+        }
+    }
+}
+
+fn append_tar_file<W: Write + io::Seek>(
+    builder: &mut tar::Builder<W>,
+    mtime: u64,
+    path: &str,
+    contents: impl AsyncRead,
+    mode: u32,
+) -> io::Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_entry_type(tar::EntryType::Regular);
+    header.set_mode(mode);
+    header.set_mtime(mtime);
+    let mut entry = builder.append_writer(&mut header, path)?;
+    copy_async_to_sync(contents, &mut entry).block_on()?;
+    entry.finish()
+}
+
+fn append_tar_symlink(
+    builder: &mut tar::Builder<impl Write>,
+    mtime: u64,
+    path: &str,
+    target: &str,
+) -> io::Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_entry_type(tar::EntryType::Symlink);
+    header.set_size(0);
+    header.set_mode(0o777);
+    header.set_mtime(mtime);
+    builder.append_link(&mut header, path, target)
+}
@@ -0,0 +1,191 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use clap_complete::ArgValueCompleter;
+use itertools::Itertools as _;
+use jj_lib::backend::CopyHistory;
+use jj_lib::backend::CopyId;
+use jj_lib::backend::TreeValue;
+use jj_lib::matchers::PrefixMatcher;
+use jj_lib::merge::Merge;
+use jj_lib::merge::MergedTreeValue;
+use jj_lib::merged_tree::MergedTree;
+use jj_lib::merged_tree_builder::MergedTreeBuilder;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::repo::Repo as _;
+use jj_lib::repo_path::RepoPath;
+use jj_lib::repo_path::RepoPathBuf;
+use jj_lib::store::Store;
+use pollster::FutureExt as _;
+use tracing::instrument;
+
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::cli_util::WorkspaceCommandHelper;
+use crate::command_error::CommandError;
+use crate::command_error::user_error;
+use crate::complete;
+use crate::ui::Ui;
+
+/// Copy a file or directory, recording the copy for copy tracking
+///
+/// The copy is recorded in the revision, so it is shown as a copy in diffs
+/// regardless of how the contents are modified afterwards. If the destination
+/// is an existing directory, the source is copied into it.
+///
+/// Conflicted files are copied as is, but their copies are not tracked.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct FileCpArgs {
+    /// The revision to update
+    #[arg(long, short, default_value = "@", value_name = "REVSET")]
+    #[arg(add = ArgValueCompleter::new(complete::revset_expression_mutable))]
+    revision: RevisionArg,
+
+    /// The file or directory to copy
+    #[arg(value_name = "SOURCE", value_hint = clap::ValueHint::AnyPath)]
+    #[arg(add = ArgValueCompleter::new(complete::all_revision_files))]
+    source: String,
+
+    /// The path to copy to
+    #[arg(value_name = "DESTINATION", value_hint = clap::ValueHint::AnyPath)]
+    destination: String,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_file_cp(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &FileCpArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let commit = workspace_command.resolve_single_rev(ui, &args.revision)?;
+    workspace_command.check_rewritable([commit.id()])?;
+    let source = workspace_command.parse_file_path(&args.source)?;
+    let destination = workspace_command.parse_file_path(&args.destination)?;
+    let tree = commit.tree();
+    let copies = plan_copies(&workspace_command, &tree, &source, &destination, "copy")?;
+
+    let mut tx = workspace_command.start_transaction();
+    let store = tx.repo().store().clone();
+    let mut tree_builder = MergedTreeBuilder::new(tree);
+    for (source_path, target_path, value) in copies {
+        let value = copy_tree_value(&store, &source_path, &target_path, value).block_on()?;
+        tree_builder.set_or_remove(target_path, value);
+    }
+    let new_tree = tree_builder.write_tree().block_on()?;
+    tx.repo_mut()
+        .rewrite_commit(&commit)
+        .set_tree(new_tree)
+        .write()
+        .block_on()?;
+    tx.finish(
+        ui,
+        format!(
+            "copy {} to {} in commit {}",
+            args.source,
+            args.destination,
+            commit.id().hex()
+        ),
+    )
+}
+
+/// Lists the entries under `source` and the paths to copy them to. The
+/// `action` is used in error messages.
+pub(super) fn plan_copies(
+    workspace_command: &WorkspaceCommandHelper,
+    tree: &MergedTree,
+    source: &RepoPath,
+    destination: &RepoPath,
+    action: &str,
+) -> Result<Vec<(RepoPathBuf, RepoPathBuf, MergedTreeValue)>, CommandError> {
+    let ui_source = workspace_command.format_file_path(source);
+    if source.is_root() {
+        return Err(user_error(format!("Cannot {action} the workspace root")));
+    }
+    if destination.starts_with(source) {
+        return Err(user_error(format!(
+            "Cannot {action} {ui_source} into itself"
+        )));
+    }
+    let entries: Vec<_> = tree
+        .entries_matching(&PrefixMatcher::new([source]))
+        .map(|(path, value)| Ok((path, value?)))
+        .try_collect::<_, _, CommandError>()?;
+    if entries.is_empty() {
+        return Err(user_error(format!("No such path: {ui_source}")));
+    }
+    // Like cp and mv, copy the source into the destination directory.
+    let destination = if tree.path_value(destination)?.is_tree() {
+        destination.join(source.components().next_back().unwrap())
+    } else {
+        destination.to_owned()
+    };
+    entries
+        .into_iter()
+        .map(|(path, value)| {
+            let relative_path = path.strip_prefix(source).unwrap();
+            let mut target = destination.clone();
+            for component in relative_path.components() {
+                target = target.join(component);
+            }
+            if tree.path_value(&target)?.is_present() {
+                let ui_target = workspace_command.format_file_path(&target);
+                return Err(user_error(format!(
+                    "Destination {ui_target} already exists"
+                )));
+            }
+            Ok((path, target, value))
+        })
+        .collect()
+}
+
+/// Returns the value to be written at `target` with a new copy history
+/// recording that it was copied from `source`.
+pub(super) async fn copy_tree_value(
+    store: &Store,
+    source: &RepoPath,
+    target: &RepoPath,
+    value: MergedTreeValue,
+) -> Result<MergedTreeValue, CommandError> {
+    let Some(Some(TreeValue::File {
+        id,
+        executable,
+        copy_id,
+    })) = value.as_resolved()
+    else {
+        return Ok(value);
+    };
+    // A file without copy history starts a new one.
+    let source_copy_id = if *copy_id == CopyId::placeholder() {
+        let history = CopyHistory {
+            current_path: source.to_owned(),
+            parents: vec![],
+            salt: vec![],
+        };
+        store.write_copy(&history).await?
+    } else {
+        copy_id.clone()
+    };
+    let history = CopyHistory {
+        current_path: target.to_owned(),
+        parents: vec![source_copy_id],
+        salt: vec![],
+    };
+    let copy_id = store.write_copy(&history).await?;
+    Ok(Merge::normal(TreeValue::File {
+        id: id.clone(),
+        executable: *executable,
+        copy_id,
+    }))
+}
@@ -13,8 +13,11 @@
 // limitations under the License.
 
 mod annotate;
+mod archive;
 mod chmod;
+mod cp;
 mod list;
+mod mv;
 mod search;
 mod show;
 mod track;
@@ -28,8 +31,11 @@ use crate::ui::Ui;
 #[derive(clap::Subcommand, Clone, Debug)]
 pub enum FileCommand {
     Annotate(annotate::FileAnnotateArgs),
+    Archive(archive::FileArchiveArgs),
     Chmod(chmod::FileChmodArgs),
+    Cp(cp::FileCpArgs),
     List(list::FileListArgs),
+    Mv(mv::FileMvArgs),
     Search(search::FileSearchArgs),
     Show(show::FileShowArgs),
     Track(track::FileTrackArgs),
@@ -43,8 +49,11 @@ pub fn cmd_file(
 ) -> Result<(), CommandError> {
     match subcommand {
         FileCommand::Annotate(args) => annotate::cmd_file_annotate(ui, command, args),
+        FileCommand::Archive(args) => archive::cmd_file_archive(ui, command, args),
         FileCommand::Chmod(args) => chmod::cmd_file_chmod(ui, command, args),
+        FileCommand::Cp(args) => cp::cmd_file_cp(ui, command, args),
         FileCommand::List(args) => list::cmd_file_list(ui, command, args),
+        FileCommand::Mv(args) => mv::cmd_file_mv(ui, command, args),
         FileCommand::Search(args) => search::cmd_file_search(ui, command, args),
         FileCommand::Show(args) => show::cmd_file_show(ui, command, args),
         FileCommand::Track(args) => track::cmd_file_track(ui, command, args),
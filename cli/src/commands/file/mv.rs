@@ -0,0 +1,92 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use clap_complete::ArgValueCompleter;
+use jj_lib::merge::Merge;
+use jj_lib::merged_tree_builder::MergedTreeBuilder;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::repo::Repo as _;
+use pollster::FutureExt as _;
+use tracing::instrument;
+
+use super::cp::copy_tree_value;
+use super::cp::plan_copies;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::CommandError;
+use crate::complete;
+use crate::ui::Ui;
+
+/// Move or rename a file or directory, recording the move for copy tracking
+///
+/// The move is recorded in the revision, so it is shown as a rename in diffs
+/// regardless of how the contents are modified afterwards. If the destination
+/// is an existing directory, the source is moved into it.
+///
+/// Conflicted files are moved as is, but their moves are not tracked.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct FileMvArgs {
+    /// The revision to update
+    #[arg(long, short, default_value = "@", value_name = "REVSET")]
+    #[arg(add = ArgValueCompleter::new(complete::revset_expression_mutable))]
+    revision: RevisionArg,
+
+    /// The file or directory to move
+    #[arg(value_name = "SOURCE", value_hint = clap::ValueHint::AnyPath)]
+    #[arg(add = ArgValueCompleter::new(complete::all_revision_files))]
+    source: String,
+
+    /// The path to move to
+    #[arg(value_name = "DESTINATION", value_hint = clap::ValueHint::AnyPath)]
+    destination: String,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_file_mv(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &FileMvArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let commit = workspace_command.resolve_single_rev(ui, &args.revision)?;
+    workspace_command.check_rewritable([commit.id()])?;
+    let source = workspace_command.parse_file_path(&args.source)?;
+    let destination = workspace_command.parse_file_path(&args.destination)?;
+    let tree = commit.tree();
+    let copies = plan_copies(&workspace_command, &tree, &source, &destination, "move")?;
+
+    let mut tx = workspace_command.start_transaction();
+    let store = tx.repo().store().clone();
+    let mut tree_builder = MergedTreeBuilder::new(tree);
+    for (source_path, target_path, value) in copies {
+        let value = copy_tree_value(&store, &source_path, &target_path, value).block_on()?;
+        tree_builder.set_or_remove(source_path, Merge::absent());
+        tree_builder.set_or_remove(target_path, value);
+    }
+    let new_tree = tree_builder.write_tree().block_on()?;
+    tx.repo_mut()
+        .rewrite_commit(&commit)
+        .set_tree(new_tree)
+        .write()
+        .block_on()?;
+    tx.finish(
+        ui,
+        format!(
+            "move {} to {} in commit {}",
+            args.source,
+            args.destination,
+            commit.id().hex()
+        ),
+    )
+}
@@ -12,11 +12,18 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use bstr::ByteSlice as _;
+use clap_complete::ArgValueCandidates;
 use clap_complete::ArgValueCompleter;
+use itertools::Itertools as _;
+use jj_lib::commit::Commit;
 use jj_lib::conflicts::MaterializedTreeValue;
 use jj_lib::conflicts::materialize_tree_value;
 use jj_lib::repo::Repo as _;
+use jj_lib::repo_path::RepoPath;
+use jj_lib::str_util::StringMatcher;
 use jj_lib::str_util::StringPattern;
+use jj_lib::str_util::StringPatternParseError;
 use pollster::FutureExt as _;
 use tracing::instrument;
 
@@ -24,17 +31,23 @@ use crate::cli_util::CommandHelper;
 use crate::cli_util::RevisionArg;
 use crate::cli_util::print_unmatched_explicit_paths;
 use crate::command_error::CommandError;
-use crate::command_error::cli_error;
+use crate::commit_templater::FileSearchMatch;
 use crate::complete;
+use crate::formatter::Formatter;
+use crate::templater::TemplateRenderer;
 use crate::ui::Ui;
 
 /// Search for content in files
 ///
-/// Lists files containing the specified pattern.
+/// Lists files containing the specified pattern. With `--lines`, `--context`,
+/// or `--template`, the matching lines are printed instead, along with their
+/// line numbers.
 ///
-/// This is an early version of the command. It only supports glob matching for
-/// now, it doesn't search files concurrently, and it doesn't indicate where in
-/// the file the match was found.
+/// Conflicted files are searched on each side of the conflict. The side the
+/// match was found in is shown next to the line number.
+///
+/// This is an early version of the command. It doesn't search files
+/// concurrently.
 #[derive(clap::Args, Clone, Debug)]
 pub(crate) struct FileSearchArgs {
     /// The revision to search files in
@@ -42,12 +55,44 @@ pub(crate) struct FileSearchArgs {
     #[arg(add = ArgValueCompleter::new(complete::revset_expression_all))]
     revision: RevisionArg,
 
-    /// The glob pattern to search for
+    /// The pattern to search for
+    ///
+    /// By default, this is a glob pattern, and the whole line must match the
+    /// pattern, so you may want to pass something like `--pattern '*foo*'`.
+    /// Other kinds of [string patterns] can be selected by prefix, e.g.
+    /// `--pattern 'regex:fo+'` or `--pattern 'substring:foo'`.
     ///
-    /// The whole line must match the pattern, so you may want to pass something
-    /// like `--pattern '*foo*'`.
-    #[arg(long, short, value_name = "PATTERN")]
-    pattern: String,
+    /// [string patterns]:
+    ///     https://docs.jj-vcs.dev/latest/revsets/#string-patterns
+    #[arg(long, short, value_name = "PATTERN", value_parser = parse_search_pattern)]
+    pattern: StringPattern,
+
+    /// Print matching lines with their line numbers instead of file names
+    #[arg(long)]
+    lines: bool,
+
+    /// Print this many lines of context around each matching line
+    ///
+    /// Implies `--lines`.
+    #[arg(long, short = 'C', value_name = "NUM")]
+    context: Option<usize>,
+
+    /// Render each matching line using the given template
+    ///
+    /// All 0-argument methods of the [`FileSearchMatch` type] are available as
+    /// keywords in the template expression. See [`jj help -k templates`] for
+    /// more information. Implies `--lines`.
+    ///
+    /// If not specified, this defaults to the `templates.file_search` setting.
+    ///
+    /// [`FileSearchMatch` type]:
+    ///     https://docs.jj-vcs.dev/latest/templates/#filesearchmatch-type
+    ///
+    /// [`jj help -k templates`]:
+    ///     https://docs.jj-vcs.dev/latest/templates/
+    #[arg(long, short = 'T')]
+    #[arg(add = ArgValueCandidates::new(complete::template_aliases))]
+    template: Option<String>,
 
     /// Only search files matching these prefixes (instead of all files)
     #[arg(value_name = "FILESETS", value_hint = clap::ValueHint::AnyPath)]
@@ -55,6 +100,18 @@ pub(crate) struct FileSearchArgs {
     paths: Vec<String>,
 }
 
+/// Parses `kind:pattern`, falling back to a glob pattern if there's no known
+/// kind prefix.
+fn parse_search_pattern(src: &str) -> Result<StringPattern, StringPatternParseError> {
+    if let Some((kind, pat)) = src.split_once(':') {
+        match StringPattern::from_str_kind(pat, kind) {
+            Err(StringPatternParseError::InvalidKind(_)) => {}
+            result => return result,
+        }
+    }
+    StringPattern::glob(src)
+}
+
 #[instrument(skip_all)]
 pub(crate) fn cmd_file_search(
     ui: &mut Ui,
@@ -67,13 +124,25 @@ pub(crate) fn cmd_file_search(
     let fileset_expression = workspace_command.parse_file_patterns(ui, &args.paths)?;
     let file_matcher = fileset_expression.to_matcher();
 
+    let template = if args.lines || args.context.is_some() || args.template.is_some() {
+        let template_text = match &args.template {
+            Some(value) => value.clone(),
+            None => workspace_command
+                .settings()
+                .get_string("templates.file_search")?,
+        };
+        let language = workspace_command.commit_template_language();
+        Some(workspace_command.parse_template(ui, &language, &template_text)?)
+    } else {
+        None
+    };
+    let context = args.context.unwrap_or(0);
+
     ui.request_pager();
     let mut formatter = ui.stdout_formatter();
     let store = workspace_command.repo().store().clone();
 
-    // TODO: Support other patterns than glob
-    let pattern = StringPattern::glob(&args.pattern).map_err(|err| cli_error(err.to_string()))?;
-    let pattern_matcher = pattern.to_matcher();
+    let pattern_matcher = args.pattern.to_matcher();
     // TODO: Read files concurrently (depending on backend)
     for (path, value) in tree.entries_matching(file_matcher.as_ref()) {
         let value = value?;
@@ -90,22 +159,42 @@ pub(crate) fn cmd_file_search(
             }
             MaterializedTreeValue::File(mut materialized_file_value) => {
                 let content = materialized_file_value.read_all(&path).block_on()?;
-                // TODO: Make output templated
-                let ui_path = workspace_command.format_file_path(&path);
-                if let Some(_line) = pattern_matcher.match_lines(&content).next() {
-                    // TODO: Optionally also print the line and line number
+                if let Some(template) = &template {
+                    let search = FileSearch {
+                        commit: &commit,
+                        path: &path,
+                        matcher: &pattern_matcher,
+                        context,
+                    };
+                    search.render_lines(template, formatter.as_mut(), &content, None)?;
+                } else if pattern_matcher.match_lines(&content).next().is_some() {
+                    let ui_path = workspace_command.format_file_path(&path);
                     writeln!(formatter, "{ui_path}")?;
                 }
             }
             MaterializedTreeValue::Symlink { .. } => {}
             MaterializedTreeValue::FileConflict(materialized_file_value) => {
-                let ui_path = workspace_command.format_file_path(&path);
-                for content in materialized_file_value.contents.adds() {
-                    if let Some(_line) = pattern_matcher.match_lines(content).next() {
-                        // TODO: Optionally also print the conflict side, line and line number
-                        writeln!(formatter, "{ui_path}")?;
-                        break;
+                let contents = materialized_file_value.contents.adds();
+                if let Some(template) = &template {
+                    let search = FileSearch {
+                        commit: &commit,
+                        path: &path,
+                        matcher: &pattern_matcher,
+                        context,
+                    };
+                    for (add_index, content) in contents.enumerate() {
+                        let side = materialized_file_value
+                            .labels
+                            .get_add(add_index)
+                            .map_or_else(|| format!("side #{}", add_index + 1), str::to_owned);
+                        search.render_lines(template, formatter.as_mut(), content, Some(side))?;
                     }
+                } else if contents
+                    .into_iter()
+                    .any(|content| pattern_matcher.match_lines(content).next().is_some())
+                {
+                    let ui_path = workspace_command.format_file_path(&path);
+                    writeln!(formatter, "{ui_path}")?;
                 }
             }
             MaterializedTreeValue::OtherConflict { .. } => {}
@@ -116,3 +205,48 @@ pub(crate) fn cmd_file_search(
     print_unmatched_explicit_paths(ui, &workspace_command, &fileset_expression, [&tree])?;
     Ok(())
 }
+
+struct FileSearch<'a> {
+    commit: &'a Commit,
+    path: &'a RepoPath,
+    matcher: &'a StringMatcher,
+    context: usize,
+}
+
+impl FileSearch<'_> {
+    /// Renders the lines of `content` matching the pattern, and the context
+    /// lines around them.
+    fn render_lines(
+        &self,
+        template: &TemplateRenderer<FileSearchMatch>,
+        formatter: &mut dyn Formatter,
+        content: &[u8],
+        conflict_side: Option<String>,
+    ) -> Result<(), CommandError> {
+        let lines = content.lines().collect_vec();
+        let matched = lines
+            .iter()
+            .map(|line| self.matcher.is_match_bytes(line))
+            .collect_vec();
+        let mut shown = vec![false; lines.len()];
+        for (index, _) in matched.iter().enumerate().filter(|(_, matched)| **matched) {
+            let end = (index + self.context + 1).min(lines.len());
+            shown[index.saturating_sub(self.context)..end].fill(true);
+        }
+        for (index, line) in lines.iter().enumerate() {
+            if !shown[index] {
+                continue;
+            }
+            let search_match = FileSearchMatch {
+                commit: self.commit.clone(),
+                path: self.path.to_owned(),
+                line_number: index + 1,
+                content: line.to_vec().into(),
+                is_context: !matched[index],
+                conflict_side: conflict_side.clone(),
+            };
+            template.format(&search_match, formatter)?;
+        }
+        Ok(())
+    }
+}
@@ -14,6 +14,7 @@
 
 use std::collections::HashMap;
 use std::io::Write as _;
+use std::ops::Range;
 use std::path::Path;
 use std::process::Stdio;
 
@@ -46,6 +47,8 @@ use crate::command_error::config_error;
 use crate::command_error::print_parse_diagnostics;
 use crate::complete;
 use crate::config::CommandNameAndArgs;
+use crate::config::find_all_variables;
+use crate::config::interpolate_variables;
 use crate::ui::Ui;
 
 /// Update files with formatting fixes or other changes
@@ -224,10 +227,15 @@ pub(crate) fn cmd_fix(
 
     print_unmatched_explicit_paths(ui, tx.base_workspace_helper(), &fileset_expression, &trees)?;
 
+    let include_line_ranges = tools_config
+        .tools
+        .iter()
+        .any(|tool_config| tool_config.uses_line_ranges);
     let summary = fix_files(
         commit_ids,
         &matcher,
         args.include_unchanged_files,
+        include_line_ranges,
         tx.repo_mut(),
         &mut parallel_fixer,
     )
@@ -249,7 +257,8 @@ pub(crate) fn cmd_fix(
 /// into the next tool. Returns FixError if there is an error reading or writing
 /// the file. However, if a tool invocation fails for whatever reason, the tool
 /// is simply skipped and we proceed to invoke the next tool (this is
-/// indistinguishable from succeeding with no changes).
+/// indistinguishable from succeeding with no changes). Tools that only fix
+/// the changed lines are skipped if the file has no changed lines.
 ///
 /// TODO: Better error handling so we can tell the user what went wrong with
 /// each failed input.
@@ -265,6 +274,7 @@ async fn fix_one_file(
         .tools
         .iter()
         .filter(|tool_config| tool_config.matcher.matches(&file_to_fix.repo_path))
+        .filter(|tool_config| !tool_config.uses_line_ranges || !file_to_fix.line_ranges.is_empty())
         .peekable();
     if matching_tools.peek().is_some() {
         // The first matching tool gets its input from the committed file, and any
@@ -280,7 +290,7 @@ async fn fix_one_file(
                 ui,
                 workspace_root,
                 path_converter,
-                &tool_config.command,
+                tool_config,
                 file_to_fix,
                 &prev_content,
             ) {
@@ -302,7 +312,7 @@ async fn fix_one_file(
     Ok(None)
 }
 
-/// Runs the `tool_command` to fix the given file content.
+/// Runs the command of the `tool_config` to fix the given file content.
 ///
 /// The `old_content` is assumed to be that of the `file_to_fix`'s `FileId`, but
 /// this is not verified.
@@ -314,12 +324,14 @@ fn run_tool(
     ui: &Ui,
     workspace_root: &Path,
     path_converter: &RepoPathUiConverter,
-    tool_command: &CommandNameAndArgs,
+    tool_config: &ToolConfig,
     file_to_fix: &FileToFix,
     old_content: &[u8],
 ) -> Result<Vec<u8>, ()> {
     let mut vars: HashMap<&str, &str> = HashMap::new();
     vars.insert("path", file_to_fix.repo_path.as_internal_file_string());
+    let line_ranges = format_line_ranges(&file_to_fix.line_ranges);
+    vars.insert("line_ranges", &line_ranges);
     // TODO: workspace_root.to_str() returns None if the workspace path is not
     // UTF-8, but we ignore that failure so `jj fix` still runs in that
     // situation. Maybe we should do something like substituting bytes instead
@@ -327,6 +339,11 @@ fn run_tool(
     if let Some(root) = workspace_root.to_str() {
         vars.insert("root", root);
     }
+    let tool_command = if tool_config.uses_line_ranges {
+        &expand_line_range_args(&tool_config.command, &file_to_fix.line_ranges)
+    } else {
+        &tool_config.command
+    };
     let mut command = tool_command.to_command_with_variables(&vars);
     tracing::debug!(?command, ?file_to_fix.repo_path, "spawning fix tool");
     let Ok(mut child) = command
@@ -379,10 +396,54 @@ fn run_tool(
     }
 }
 
+/// Formats the line ranges as comma-separated 1-based inclusive ranges, such
+/// as `1-3,7-7`.
+fn format_line_ranges(line_ranges: &[Range<usize>]) -> String {
+    line_ranges
+        .iter()
+        .map(|range| format!("{}-{}", range.start + 1, range.end))
+        .join(",")
+}
+
+/// Repeats the arguments which refer to `$first_line` or `$last_line` once for
+/// each of the `line_ranges`, substituting the 1-based inclusive line numbers.
+fn expand_line_range_args(
+    command: &CommandNameAndArgs,
+    line_ranges: &[Range<usize>],
+) -> CommandNameAndArgs {
+    let (name, args) = command.split_name_and_args();
+    let mut expanded_args = vec![name.into_owned()];
+    for arg in args.iter() {
+        let arg = std::slice::from_ref(arg);
+        if find_all_variables(arg).any(|name| matches!(name, "first_line" | "last_line")) {
+            expanded_args.extend(line_ranges.iter().flat_map(|range| {
+                let vars = HashMap::from([
+                    ("first_line", (range.start + 1).to_string()),
+                    ("last_line", range.end.to_string()),
+                ]);
+                interpolate_variables(arg, &vars)
+            }));
+        } else {
+            expanded_args.extend_from_slice(arg);
+        }
+    }
+    let env = match command {
+        CommandNameAndArgs::Structured { env, .. } => env.clone(),
+        CommandNameAndArgs::String(_) | CommandNameAndArgs::Vec(_) => HashMap::new(),
+    };
+    CommandNameAndArgs::Structured {
+        env,
+        command: expanded_args.try_into().unwrap(),
+    }
+}
+
 /// Represents an entry in the `fix.tools` config table.
 struct ToolConfig {
     /// The command that will be run to fix a matching file.
     command: CommandNameAndArgs,
+    /// Whether the command refers to the changed lines of the file, in which
+    /// case it is only run on files with changed lines.
+    uses_line_ranges: bool,
     /// The matcher that determines if this tool matches a file.
     matcher: Box<dyn Matcher>,
     /// Whether the tool is enabled
@@ -441,7 +502,11 @@ fn get_tools_config(ui: &mut Ui, settings: &UserSettings) -> Result<ToolsConfig,
                     .try_collect()?,
             );
             print_parse_diagnostics(ui, &format!("In `fix.tools.{name}`"), &diagnostics)?;
+            let (_, args) = tool.command.split_name_and_args();
+            let uses_line_ranges = find_all_variables(&args)
+                .any(|name| matches!(name, "line_ranges" | "first_line" | "last_line"));
             Ok(ToolConfig {
+                uses_line_ranges,
                 command: tool.command,
                 matcher: expression.to_matcher(),
                 enabled: tool.enabled,
@@ -42,6 +42,7 @@ use crate::command_error::user_error;
 use crate::command_error::user_error_with_message;
 use crate::git_util::GitSubprocessUi;
 use crate::git_util::print_push_stats;
+use crate::hooks::run_pre_push_hook;
 use crate::ui::Ui;
 
 /// Upload changes to Gerrit for code review, or update existing changes.
@@ -487,6 +488,7 @@ pub fn cmd_gerrit_upload(
         }
     }
 
+    let upload_order = to_upload.iter().rev().map(|c| c.id().clone()).collect_vec();
     let mut old_to_new: HashMap<CommitId, Commit> = HashMap::new();
     for original_commit in to_upload.into_iter().rev() {
         let trailers = parse_description_trailers(original_commit.description());
@@ -595,6 +597,20 @@ pub fn cmd_gerrit_upload(
         remote_branch,
     )?;
 
+    if !args.dry_run {
+        let commits_to_push = upload_order
+            .iter()
+            .map(|id| old_to_new[id].clone())
+            .collect_vec();
+        run_pre_push_hook(
+            ui,
+            tx.settings(),
+            tx.base_workspace_helper().workspace_root(),
+            remote.as_str(),
+            &commits_to_push,
+        )?;
+    }
+
     // NOTE (aseipp): because we are pushing everything to the same remote ref,
     // we have to loop and push each commit one at a time, even though
     // push_updates in theory supports multiple GitRefUpdates at once, because
@@ -0,0 +1,146 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io;
+use std::io::Read as _;
+use std::io::Write as _;
+use std::path::PathBuf;
+
+use clap_complete::ArgValueCompleter;
+use jj_lib::patch::apply_patch;
+use jj_lib::patch::parse_mailbox;
+use pollster::FutureExt as _;
+use tracing::instrument;
+
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::CommandError;
+use crate::command_error::user_error_with_message;
+use crate::complete;
+use crate::ui::Ui;
+
+/// Apply patches from mailboxes as new commits
+///
+/// Each patch in the mailboxes, as produced by `jj git format-patch` or `git
+/// format-patch`, becomes a new commit on top of the previous one. The author
+/// and the description are taken from the email.
+///
+/// Changes which don't apply cleanly are recorded as conflicts in the new
+/// commits, so they can be resolved later.
+#[derive(clap::Args, Clone, Debug)]
+pub struct GitAmArgs {
+    /// Mailbox files to read the patches from [default: read from stdin]
+    #[arg(value_name = "MBOX", value_hint = clap::ValueHint::FilePath)]
+    paths: Vec<PathBuf>,
+
+    /// The revision to apply the first patch onto
+    #[arg(long, short, default_value = "@", value_name = "REVSET")]
+    #[arg(add = ArgValueCompleter::new(complete::revset_expression_all))]
+    onto: RevisionArg,
+}
+
+#[instrument(skip_all)]
+pub fn cmd_git_am(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &GitAmArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let mut parent = workspace_command.resolve_single_rev(ui, &args.onto)?;
+
+    let mut messages = vec![];
+    if args.paths.is_empty() {
+        let mut data = vec![];
+        io::stdin()
+            .read_to_end(&mut data)
+            .map_err(|err| user_error_with_message("Failed to read patches from stdin", err))?;
+        messages.extend(
+            parse_mailbox(&data)
+                .map_err(|err| user_error_with_message("Failed to parse patches", err))?,
+        );
+    }
+    for path in &args.paths {
+        let path = command.cwd().join(path);
+        let data = std::fs::read(&path).map_err(|err| {
+            user_error_with_message(format!("Failed to read {}", path.display()), err)
+        })?;
+        messages.extend(parse_mailbox(&data).map_err(|err| {
+            user_error_with_message(format!("Failed to parse {}", path.display()), err)
+        })?);
+    }
+    if messages.is_empty() {
+        writeln!(ui.status(), "No patches to apply.")?;
+        return Ok(());
+    }
+
+    let mut tx = workspace_command.start_transaction();
+    let mut new_commits = vec![];
+    for (index, message) in messages.iter().enumerate() {
+        let number = index + 1;
+        let applied = apply_patch(&parent.tree(), &message.files).block_on()?;
+        let builder = tx
+            .repo_mut()
+            .new_commit(vec![parent.id().clone()], applied.tree)
+            .set_description(&message.description);
+        let mut author = builder.author().clone();
+        author.name = message.author_name.clone();
+        author.email = message.author_email.clone();
+        if let Some(timestamp) = message.author_timestamp {
+            author.timestamp = timestamp;
+        }
+        let commit = builder.set_author(author).write().block_on()?;
+
+        if !applied.conflicted_paths.is_empty() {
+            writeln!(
+                ui.warning_default(),
+                "Patch {number}/{total} didn't apply cleanly. These paths have conflicts:",
+                total = messages.len()
+            )?;
+            for path in &applied.conflicted_paths {
+                writeln!(
+                    ui.stderr(),
+                    "  {}",
+                    tx.base_workspace_helper().format_file_path(path)
+                )?;
+            }
+        }
+        if !applied.skipped_paths.is_empty() {
+            writeln!(
+                ui.warning_default(),
+                "Patch {number}/{total} has binary changes which can't be applied to these paths:",
+                total = messages.len()
+            )?;
+            for path in &applied.skipped_paths {
+                writeln!(
+                    ui.stderr(),
+                    "  {}",
+                    tx.base_workspace_helper().format_file_path(path)
+                )?;
+            }
+        }
+        new_commits.push(commit.clone());
+        parent = commit;
+    }
+
+    if let Some(mut formatter) = ui.status_formatter() {
+        writeln!(formatter, "Applied {} patches:", new_commits.len())?;
+        for commit in &new_commits {
+            write!(formatter, "  ")?;
+            tx.write_commit_summary(formatter.as_mut(), commit)?;
+            writeln!(formatter)?;
+        }
+    }
+    tx.finish(ui, format!("apply {} patches", new_commits.len()))?;
+    Ok(())
+}
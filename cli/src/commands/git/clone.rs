@@ -47,6 +47,7 @@ use crate::commands::git::maybe_add_gitignore;
 use crate::config::ConfigEnv;
 use crate::git_util::GitSubprocessUi;
 use crate::git_util::absolute_git_url;
+use crate::git_util::fetch_lfs_objects;
 use crate::git_util::load_git_import_options;
 use crate::git_util::print_git_import_stats;
 use crate::revset_util::parse_union_name_patterns;
@@ -427,6 +428,7 @@ fn fetch_new_remote(
         tx.repo_mut().track_remote_bookmark(remote_symbol)?;
     }
     print_git_import_stats(ui, &tx, &import_stats)?;
+    fetch_lfs_objects(ui, tx.repo(), &import_stats)?;
     if git_settings.auto_local_bookmark && !should_track_default {
         writeln!(
             ui.hint_default(),
@@ -40,6 +40,7 @@ use crate::command_error::user_error;
 use crate::commands::git::get_single_remote;
 use crate::complete;
 use crate::git_util::GitSubprocessUi;
+use crate::git_util::fetch_lfs_objects;
 use crate::git_util::load_git_import_options;
 use crate::git_util::print_git_import_stats;
 use crate::revset_util::parse_union_name_patterns;
@@ -230,6 +231,7 @@ pub fn cmd_git_fetch(
 
     let import_stats = git_fetch.import_refs()?;
     print_git_import_stats(ui, &tx, &import_stats)?;
+    fetch_lfs_objects(ui, tx.repo(), &import_stats)?;
 
     if let Some(bookmark_expr) = &common_bookmark_expr {
         warn_if_branches_not_found(ui, &tx, bookmark_expr, &matching_remotes)?;
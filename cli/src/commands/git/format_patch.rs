@@ -0,0 +1,435 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Write as _;
+use std::io;
+use std::io::Write as _;
+use std::path::PathBuf;
+
+use clap_complete::ArgValueCompleter;
+use itertools::Itertools as _;
+use jj_lib::commit::Commit;
+use jj_lib::conflicts::ConflictMarkerStyle;
+use jj_lib::gitattributes::GitAttributesLookup;
+use jj_lib::matchers::EverythingMatcher;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::repo::Repo;
+use jj_lib::repo_path::RepoPathUiConverter;
+use pollster::FutureExt as _;
+use tracing::instrument;
+
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::cli_util::short_commit_hash;
+use crate::command_error::CommandError;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_message;
+use crate::complete;
+use crate::diff_util::DiffFormat;
+use crate::diff_util::DiffRenderer;
+use crate::diff_util::DiffStatOptions;
+use crate::diff_util::DiffStats;
+use crate::diff_util::UnifiedDiffOptions;
+use crate::diff_util::get_copy_records_between;
+use crate::diff_util::show_diff_stat_entries;
+use crate::formatter::Formatter;
+use crate::formatter::PlainTextFormatter;
+use crate::ui::Ui;
+
+/// Export revisions as patches in the mailbox format
+///
+/// The patches are formatted like the output of `git format-patch`, and can
+/// be sent by email, or applied with `jj git am` or `git am`. Each revision is
+/// written to a numbered `.patch` file.
+///
+/// Merge commits can't be exported.
+#[derive(clap::Args, Clone, Debug)]
+pub struct GitFormatPatchArgs {
+    /// The revisions to export
+    #[arg(long, short, default_value = "@", value_name = "REVSETS")]
+    #[arg(add = ArgValueCompleter::new(complete::revset_expression_all))]
+    revisions: Vec<RevisionArg>,
+
+    /// Directory to write the patch files to [default: the current directory]
+    #[arg(long, short, value_name = "DIR", value_hint = clap::ValueHint::DirPath)]
+    output_directory: Option<PathBuf>,
+
+    /// Write all patches to stdout as a single mailbox instead of files
+    #[arg(long, conflicts_with = "output_directory")]
+    stdout: bool,
+}
+
+#[instrument(skip_all)]
+pub fn cmd_git_format_patch(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &GitFormatPatchArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let repo = workspace_command.repo().as_ref();
+    let commits: Vec<Commit> = workspace_command
+        .parse_union_revsets(ui, &args.revisions)?
+        .evaluate_to_commits()?
+        .try_collect()?;
+    // Parents first
+    let commits = commits.into_iter().rev().collect_vec();
+    if let Some(commit) = commits.iter().find(|commit| commit.parent_ids().len() > 1) {
+        return Err(user_error(format!(
+            "Cannot export merge commit {} as a patch",
+            short_commit_hash(commit.id())
+        )));
+    }
+    if commits.is_empty() {
+        writeln!(ui.status(), "No revisions to export.")?;
+        return Ok(());
+    }
+
+    // Paths in patches are relative to the workspace root.
+    let path_converter = RepoPathUiConverter::Fs {
+        cwd: workspace_command.workspace_root().to_owned(),
+        base: workspace_command.workspace_root().to_owned(),
+    };
+    let diff_options = UnifiedDiffOptions::from_settings(workspace_command.settings())?;
+    let diff_renderer = DiffRenderer::new(
+        repo,
+        &path_converter,
+        workspace_command.env().conflict_marker_style(),
+        vec![DiffFormat::Git(Box::new(diff_options))],
+    );
+
+    let output_dir = args
+        .output_directory
+        .as_ref()
+        .map(|dir| command.cwd().join(dir));
+    if let Some(dir) = &output_dir {
+        std::fs::create_dir_all(dir).map_err(|err| {
+            user_error_with_message(format!("Failed to create {}", dir.display()), err)
+        })?;
+    }
+    let mut stdout = ui.stdout();
+    for (index, commit) in commits.iter().enumerate() {
+        let number = index + 1;
+        let stats = calculate_diff_stats(
+            repo,
+            workspace_command.env().conflict_marker_style(),
+            commit,
+        )?;
+        let patch = format_patch(
+            ui,
+            &diff_renderer,
+            &path_converter,
+            commit,
+            &stats,
+            number,
+            commits.len(),
+        )?;
+        if args.stdout {
+            stdout.write_all(&patch)?;
+            continue;
+        }
+        let file_name = patch_file_name(number, commit.description());
+        let path = match &output_dir {
+            Some(dir) => dir.join(&file_name),
+            None => command.cwd().join(&file_name),
+        };
+        std::fs::write(&path, &patch).map_err(|err| {
+            user_error_with_message(format!("Failed to write {}", path.display()), err)
+        })?;
+        match &args.output_directory {
+            Some(dir) => writeln!(stdout, "{}", dir.join(&file_name).display())?,
+            None => writeln!(stdout, "{file_name}")?,
+        }
+    }
+    Ok(())
+}
+
+/// Renders the `commit` as a mail message.
+fn format_patch(
+    ui: &Ui,
+    diff_renderer: &DiffRenderer,
+    path_converter: &RepoPathUiConverter,
+    commit: &Commit,
+    stats: &DiffStats,
+    number: usize,
+    total: usize,
+) -> Result<Vec<u8>, CommandError> {
+    let (subject, body) = split_description(commit.description());
+    let subject_prefix = if total == 1 {
+        "[PATCH]".to_owned()
+    } else {
+        let width = total.to_string().len();
+        format!("[PATCH {number:0width$}/{total}]")
+    };
+    let author = commit.author();
+    let date = author
+        .timestamp
+        .to_datetime()
+        .map_err(|err| user_error_with_message("Invalid author date", err))?;
+
+    let mut output = vec![];
+    let mut formatter = PlainTextFormatter::new(&mut output);
+    writeln!(
+        formatter,
+        "From {} Mon Sep 17 00:00:00 2001",
+        commit.id().hex()
+    )?;
+    writeln!(
+        formatter,
+        "{}",
+        format_from_header(&author.name, &author.email)
+    )?;
+    writeln!(formatter, "Date: {}", date.to_rfc2822())?;
+    writeln!(
+        formatter,
+        "{}",
+        format_subject_header(&subject_prefix, subject)
+    )?;
+    writeln!(formatter, "MIME-Version: 1.0")?;
+    writeln!(formatter, "Content-Type: text/plain; charset=UTF-8")?;
+    writeln!(formatter, "Content-Transfer-Encoding: 8bit")?;
+    writeln!(formatter)?;
+    if !body.is_empty() {
+        for line in body.lines() {
+            // Escape lines which would be taken as the start of the next
+            // message, in the same way as the mboxrd format.
+            if line.trim_start_matches('>').starts_with("From ") {
+                write!(formatter, ">")?;
+            }
+            writeln!(formatter, "{line}")?;
+        }
+    }
+    writeln!(formatter, "---")?;
+    if !stats.entries().is_empty() {
+        write_diff_stats(&mut formatter, stats, path_converter)?;
+        writeln!(formatter)?;
+    }
+    diff_renderer
+        .show_patch(ui, &mut formatter, commit, &EverythingMatcher, 72)
+        .block_on()?;
+    Ok(output)
+}
+
+fn calculate_diff_stats(
+    repo: &dyn Repo,
+    conflict_marker_style: ConflictMarkerStyle,
+    commit: &Commit,
+) -> Result<DiffStats, CommandError> {
+    let matcher = EverythingMatcher;
+    let from_tree = commit.parent_tree(repo)?;
+    let to_tree = commit.tree();
+    let copy_records =
+        get_copy_records_between(repo.store(), commit.parent_ids(), [commit.id()], &matcher)?;
+    let tree_diff = from_tree.diff_stream_with_copies(&to_tree, &matcher, &copy_records);
+    let attributes = GitAttributesLookup::from_tree(to_tree.clone());
+    let stats = DiffStats::calculate(
+        repo.store(),
+        tree_diff,
+        &attributes,
+        &DiffStatOptions::default(),
+        conflict_marker_style,
+    )
+    .block_on()?;
+    Ok(stats)
+}
+
+/// Writes the diff stats in the same format as `git format-patch`, in which
+/// lines are indented by a space.
+fn write_diff_stats(
+    formatter: &mut dyn Formatter,
+    stats: &DiffStats,
+    path_converter: &RepoPathUiConverter,
+) -> io::Result<()> {
+    let mut entries = vec![];
+    show_diff_stat_entries(
+        &mut PlainTextFormatter::new(&mut entries),
+        stats,
+        path_converter,
+        71,
+    )?;
+    for line in entries.split_inclusive(|b| *b == b'\n') {
+        formatter.write_all(b" ")?;
+        formatter.write_all(line)?;
+    }
+    let files = stats.entries().len();
+    let insertions = stats.count_total_added();
+    let deletions = stats.count_total_removed();
+    write!(
+        formatter,
+        " {files} file{} changed",
+        if files == 1 { "" } else { "s" }
+    )?;
+    // Like Git, zero counts are omitted unless both counts are zero.
+    if insertions > 0 || deletions == 0 {
+        write!(
+            formatter,
+            ", {insertions} insertion{}(+)",
+            if insertions == 1 { "" } else { "s" }
+        )?;
+    }
+    if deletions > 0 || insertions == 0 {
+        write!(
+            formatter,
+            ", {deletions} deletion{}(-)",
+            if deletions == 1 { "" } else { "s" }
+        )?;
+    }
+    writeln!(formatter)
+}
+
+/// Maximum length of header lines, which are folded if longer.
+const MAX_HEADER_LEN: usize = 78;
+/// Maximum length of lines containing RFC 2047 encoded words.
+const MAX_ENCODED_HEADER_LEN: usize = 76;
+
+/// Formats the `From` header in the same way as Git. The name is encoded or
+/// quoted as needed.
+fn format_from_header(name: &str, email: &str) -> String {
+    let mut header = "From: ".to_owned();
+    let max_len = if needs_rfc2047_encoding(name) {
+        add_rfc2047(&mut header, name, true);
+        MAX_ENCODED_HEADER_LEN
+    } else if needs_rfc822_quoting(name) {
+        header.push('"');
+        for c in name.chars() {
+            if c == '"' || c == '\\' {
+                header.push('\\');
+            }
+            header.push(c);
+        }
+        header.push('"');
+        MAX_HEADER_LEN
+    } else {
+        header.push_str(name);
+        MAX_HEADER_LEN
+    };
+    if last_line_len(&header) + " <".len() + email.len() + ">".len() > max_len {
+        header.push('\n');
+    }
+    write!(header, " <{email}>").unwrap();
+    header
+}
+
+/// Formats the `Subject` header in the same way as Git. The subject is encoded
+/// if needed, and long lines are folded.
+fn format_subject_header(prefix: &str, subject: &str) -> String {
+    let mut header = format!("Subject: {prefix} ");
+    if needs_rfc2047_encoding(subject) {
+        add_rfc2047(&mut header, subject, false);
+        return header;
+    }
+    let mut line_len = header.len();
+    for (i, word) in subject.split(' ').enumerate() {
+        if i > 0 {
+            if line_len + " ".len() + word.len() > MAX_HEADER_LEN {
+                header.push_str("\n ");
+                line_len = " ".len();
+            } else {
+                header.push(' ');
+                line_len += " ".len();
+            }
+        }
+        header.push_str(word);
+        line_len += word.len();
+    }
+    header
+}
+
+fn last_line_len(text: &str) -> usize {
+    text.len() - text.rfind('\n').map_or(0, |pos| pos + 1)
+}
+
+fn needs_rfc2047_encoding(text: &str) -> bool {
+    text.bytes().any(|b| !b.is_ascii() || b == b'\x1b') || text.contains("=?")
+}
+
+fn needs_rfc822_quoting(text: &str) -> bool {
+    text.contains(|c| "()<>@,;:\\\".[]".contains(c))
+}
+
+/// Appends the `text` as RFC 2047 "Q" encoded words. Lines are folded so
+/// encoded words don't exceed the maximum length.
+fn add_rfc2047(out: &mut String, text: &str, is_address: bool) {
+    const WORD_START: &str = "=?UTF-8?q?";
+    const WORD_END: &str = "?=";
+    out.push_str(WORD_START);
+    let mut line_len = last_line_len(out);
+    for c in text.chars() {
+        let mut buf = [0; 4];
+        let bytes = c.encode_utf8(&mut buf).as_bytes();
+        let is_special = bytes.len() > 1 || is_rfc2047_special(bytes[0], is_address);
+        let encoded_len = if is_special { 3 * bytes.len() } else { 1 };
+        if line_len + encoded_len + WORD_END.len() > MAX_ENCODED_HEADER_LEN {
+            write!(out, "{WORD_END}\n {WORD_START}").unwrap();
+            line_len = " ".len() + WORD_START.len();
+        }
+        if is_special {
+            for b in bytes {
+                write!(out, "={b:02X}").unwrap();
+            }
+        } else {
+            out.push(c);
+        }
+        line_len += encoded_len;
+    }
+    out.push_str(WORD_END);
+}
+
+/// Returns true if the ASCII character `b` can't be represented as itself in
+/// an encoded word.
+fn is_rfc2047_special(b: u8, is_address: bool) -> bool {
+    if !b.is_ascii_graphic() || matches!(b, b'=' | b'?' | b'_') {
+        return true;
+    }
+    // Words in addresses are more restricted.
+    is_address && !(b.is_ascii_alphanumeric() || matches!(b, b'!' | b'*' | b'+' | b'-' | b'/'))
+}
+
+/// Splits the description into the subject line and the body.
+fn split_description(description: &str) -> (&str, &str) {
+    let description = description.trim_end();
+    match description.split_once('\n') {
+        Some((subject, body)) => (subject, body.trim_start_matches('\n')),
+        None => (description, ""),
+    }
+}
+
+/// Returns a file name like `0001-fix-the-bug.patch`, sanitized in the same
+/// way as `git format-patch`.
+fn patch_file_name(number: usize, description: &str) -> String {
+    const MAX_LEN: usize = 64;
+    let (subject, _) = split_description(description);
+    let mut name = format!("{number:04}-");
+    let prefix_len = name.len();
+    let mut pending_dash = false;
+    for c in subject.chars() {
+        if c.is_ascii_alphanumeric() || c == '.' || c == '_' {
+            if c == '.' && name.len() == prefix_len {
+                continue;
+            }
+            if pending_dash && name.len() > prefix_len {
+                name.push('-');
+            }
+            pending_dash = false;
+            name.push(c);
+        } else {
+            pending_dash = true;
+        }
+    }
+    while name.len() > prefix_len && name.ends_with('.') {
+        name.pop();
+    }
+    name.truncate(MAX_LEN);
+    name.push_str(".patch");
+    name
+}
@@ -12,10 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod am;
 mod clone;
 mod colocation;
 mod export;
 mod fetch;
+mod format_patch;
 mod import;
 mod init;
 mod push;
@@ -34,6 +36,8 @@ use jj_lib::ref_name::RemoteNameBuf;
 use jj_lib::ref_name::RemoteRefSymbol;
 use jj_lib::store::Store;
 
+use self::am::GitAmArgs;
+use self::am::cmd_git_am;
 use self::clone::GitCloneArgs;
 use self::clone::cmd_git_clone;
 use self::colocation::GitColocationCommand;
@@ -42,6 +46,8 @@ use self::export::GitExportArgs;
 use self::export::cmd_git_export;
 use self::fetch::GitFetchArgs;
 use self::fetch::cmd_git_fetch;
+use self::format_patch::GitFormatPatchArgs;
+use self::format_patch::cmd_git_format_patch;
 use self::import::GitImportArgs;
 use self::import::cmd_git_import;
 use self::init::GitInitArgs;
@@ -71,11 +77,13 @@ use crate::ui::Ui;
 ///     https://docs.jj-vcs.dev/latest/git-command-table
 #[derive(Subcommand, Clone, Debug)]
 pub enum GitCommand {
+    Am(GitAmArgs),
     Clone(GitCloneArgs),
     #[command(subcommand)]
     Colocation(GitColocationCommand),
     Export(GitExportArgs),
     Fetch(GitFetchArgs),
+    FormatPatch(GitFormatPatchArgs),
     Import(GitImportArgs),
     Init(GitInitArgs),
     Push(GitPushArgs),
@@ -90,10 +98,12 @@ pub fn cmd_git(
     subcommand: &GitCommand,
 ) -> Result<(), CommandError> {
     match subcommand {
+        GitCommand::Am(args) => cmd_git_am(ui, command, args),
         GitCommand::Clone(args) => cmd_git_clone(ui, command, args),
         GitCommand::Colocation(subcommand) => cmd_git_colocation(ui, command, subcommand),
         GitCommand::Export(args) => cmd_git_export(ui, command, args),
         GitCommand::Fetch(args) => cmd_git_fetch(ui, command, args),
+        GitCommand::FormatPatch(args) => cmd_git_format_patch(ui, command, args),
         GitCommand::Import(args) => cmd_git_import(ui, command, args),
         GitCommand::Init(args) => cmd_git_init(ui, command, args),
         GitCommand::Push(args) => cmd_git_push(ui, command, args),
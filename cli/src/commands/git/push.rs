@@ -46,6 +46,7 @@ use jj_lib::refs::classify_bookmark_push_action;
 use jj_lib::repo::Repo;
 use jj_lib::revset::RemoteRefSymbolExpression;
 use jj_lib::revset::RevsetExpression;
+use jj_lib::revset::RevsetIteratorExt as _;
 use jj_lib::signing::SignBehavior;
 use jj_lib::str_util::StringExpression;
 use jj_lib::view::View;
@@ -67,6 +68,8 @@ use crate::complete;
 use crate::formatter::Formatter;
 use crate::git_util::GitSubprocessUi;
 use crate::git_util::print_push_stats;
+use crate::git_util::push_lfs_objects;
+use crate::hooks::run_pre_push_hook;
 use crate::progress::ProgressWriter;
 use crate::revset_util::parse_bookmark_name;
 use crate::revset_util::parse_union_name_patterns;
@@ -199,6 +202,20 @@ pub struct GitPushArgs {
     #[arg(add = ArgValueCompleter::new(complete::branch_name_equals_any_revision))]
     named: Vec<String>,
 
+    /// Transmit the given string to the server as a push option (can be
+    /// repeated)
+    ///
+    /// The server-side hooks receive the push options. This defaults to the
+    /// `git.push-options` setting.
+    #[arg(long = "option", short = 'o', value_name = "STRING")]
+    options: Vec<String>,
+
+    /// Update either all the bookmarks on the remote, or none of them
+    ///
+    /// The remote must support atomic pushes.
+    #[arg(long)]
+    atomic: bool,
+
     /// Only display what will change on the remote
     #[arg(long)]
     dry_run: bool,
@@ -466,15 +483,55 @@ pub fn cmd_git_push(
         return Ok(());
     }
 
+    let commits_to_push: Vec<Commit> = {
+        let repo = tx.repo();
+        let new_heads = bookmark_updates
+            .iter()
+            .filter_map(|(_, update)| update.new_target.clone())
+            .collect_vec();
+        let old_heads = repo
+            .view()
+            .remote_bookmarks(remote)
+            .flat_map(|(_, old_head)| old_head.target.added_ids())
+            .cloned()
+            .collect_vec();
+        RevsetExpression::commits(old_heads)
+            .union(&RevsetExpression::root())
+            .range(&RevsetExpression::commits(new_heads))
+            .evaluate(repo)?
+            .iter()
+            .commits(repo.store())
+            .try_collect()?
+    };
+    run_pre_push_hook(
+        ui,
+        tx.settings(),
+        tx.base_workspace_helper().workspace_root(),
+        remote.as_str(),
+        &commits_to_push,
+    )?;
+    push_lfs_objects(ui, tx.repo(), remote, &commits_to_push)?;
+
     let targets = GitBranchPushTargets {
         branch_updates: bookmark_updates,
     };
     let git_settings = GitSettings::from_settings(tx.settings())?;
+    let push_options = if args.options.is_empty() {
+        tx.settings().get::<Vec<String>>("git.push-options")?
+    } else {
+        args.options.clone()
+    };
+    let extra_args = push_options
+        .iter()
+        .map(|option| format!("--push-option={option}"))
+        .chain(args.atomic.then(|| "--atomic".to_owned()))
+        .collect_vec();
     let push_stats = git::push_branches(
         tx.repo_mut(),
         git_settings.to_subprocess_options(),
         remote,
         &targets,
+        &extra_args.iter().map(|arg| arg.as_str()).collect_vec(),
         &mut GitSubprocessUi::new(ui),
     )?;
     print_push_stats(ui, &push_stats)?;
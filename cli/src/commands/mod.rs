@@ -94,7 +94,6 @@ const STYLES: Styles = Styles::styled()
 enum Command {
     Abandon(abandon::AbandonArgs),
     Absorb(absorb::AbsorbArgs),
-    #[command(hide = true)]
     Arrange(arrange::ArrangeArgs),
     #[cfg(feature = "bench")]
     #[command(subcommand)]
@@ -141,8 +140,6 @@ enum Command {
     Restore(restore::RestoreArgs),
     Revert(revert::RevertArgs),
     Root(root::RootArgs),
-    #[command(hide = true)]
-    // TODO: Flesh out.
     Run(run::RunArgs),
     Show(show::ShowArgs),
     Sign(sign::SignArgs),
@@ -34,6 +34,7 @@ use crate::command_error::CommandError;
 use crate::complete;
 use crate::description_util::add_trailers;
 use crate::description_util::join_message_paragraphs;
+use crate::hooks::run_commit_msg_hook;
 use crate::text_util;
 use crate::ui::Ui;
 
@@ -230,6 +231,12 @@ pub(crate) fn cmd_new(
         // trailer to an empty description would break that logic.
         commit_builder.set_description(&description);
         let description = add_trailers(ui, &tx, &commit_builder)?;
+        let description = run_commit_msg_hook(
+            ui,
+            tx.settings(),
+            tx.base_workspace_helper().workspace_root(),
+            description,
+        )?;
         commit_builder.set_description(&description);
     }
     let new_commit = commit_builder.write(tx.repo_mut()).block_on()?;
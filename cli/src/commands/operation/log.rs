@@ -29,6 +29,7 @@ use crate::cli_util::CommandHelper;
 use crate::cli_util::LogContentFormat;
 use crate::cli_util::WorkspaceCommandEnvironment;
 use crate::cli_util::format_template;
+use crate::cli_util::write_json_line;
 use crate::command_error::CommandError;
 use crate::complete;
 use crate::diff_util::DiffFormatArgs;
@@ -78,6 +79,18 @@ pub struct OperationLogArgs {
     #[arg(add = ArgValueCandidates::new(complete::template_aliases))]
     template: Option<String>,
 
+    /// Print each operation as a line of JSON instead of rendering a template
+    ///
+    /// The graph isn't shown. See [JSON output] for the format.
+    ///
+    /// [JSON output]:
+    ///     https://docs.jj-vcs.dev/latest/json-output/
+    #[arg(
+        long,
+        conflicts_with_all = ["template", "op_diff", "patch", "short-format", "long-format"]
+    )]
+    json: bool,
+
     /// Show changes to the repository at each operation
     #[arg(long, short = 'd')]
     op_diff: bool,
@@ -123,6 +136,10 @@ fn do_op_log(
     current_op: &Operation,
     args: &OperationLogArgs,
 ) -> Result<(), CommandError> {
+    if args.json {
+        return print_op_log_json(ui, current_op, args);
+    }
+
     let settings = repo_loader.settings();
     let graph_style = GraphStyle::from_settings(settings)?;
     let with_content_format = LogContentFormat::new(ui, settings)?;
@@ -262,3 +279,23 @@ fn do_op_log(
 
     Ok(())
 }
+
+fn print_op_log_json(
+    ui: &mut Ui,
+    current_op: &Operation,
+    args: &OperationLogArgs,
+) -> Result<(), CommandError> {
+    ui.request_pager();
+    let mut formatter = ui.stdout_formatter();
+    let iter =
+        op_walk::walk_ancestors(slice::from_ref(current_op)).take(args.limit.unwrap_or(usize::MAX));
+    let iter: Box<dyn Iterator<Item = _>> = if args.reversed {
+        Box::new(iter.collect_vec().into_iter().rev())
+    } else {
+        Box::new(iter)
+    };
+    for op in iter {
+        write_json_line(formatter.as_mut(), &op?)?;
+    }
+    Ok(())
+}
@@ -23,26 +23,30 @@ use crate::cli_util::CommandHelper;
 use crate::cli_util::RevisionArg;
 use crate::cli_util::print_conflicted_paths;
 use crate::cli_util::print_unmatched_explicit_paths;
+use crate::cli_util::record_conflict_resolutions;
 use crate::command_error::CommandError;
 use crate::command_error::cli_error;
 use crate::complete;
 use crate::formatter::FormatterExt as _;
+use crate::merge_tools::MergeEditor;
 use crate::ui::Ui;
 
 /// Resolve conflicted files with an external merge tool
 ///
-/// Only conflicts that can be resolved with a 3-way merge are supported. See
-/// docs for merge tool configuration instructions. External merge tools will be
-/// invoked for each conflicted file one-by-one until all conflicts are
-/// resolved. To stop resolving conflicts, exit the merge tool without making
-/// any changes.
+/// Only conflicts that can be resolved with a 3-way merge are supported by
+/// merge tools. See docs for merge tool configuration instructions. External
+/// merge tools will be invoked for each conflicted file one-by-one until all
+/// conflicts are resolved. To stop resolving conflicts, exit the merge tool
+/// without making any changes.
+///
+/// With `--editor`, the conflict markers of each file are opened in the text
+/// editor instead. This works for conflicts with any number of sides, and in
+/// revisions other than the working-copy commit.
 ///
 /// Note that conflicts can also be resolved without using this command. You may
 /// edit the conflict markers in the conflicted file directly with a text
 /// editor.
 //  TODOs:
-//   - `jj resolve --editor` to resolve a conflict in the default text editor. Should work for
-//     conflicts with 3+ adds. Useful to resolve conflicts in a commit other than the current one.
 //   - A way to help split commits with conflicts that are too complicated (more than two sides)
 //     into commits with simpler conflicts. In case of a tree with many merges, we could for example
 //     point to existing commits with simpler conflicts where resolving those conflicts would help
@@ -67,6 +71,14 @@ pub(crate) struct ResolveArgs {
     #[arg(add = ArgValueCandidates::new(complete::merge_editors))]
     tool: Option<String>,
 
+    /// Edit the conflict markers in the text editor
+    ///
+    /// The conflict markers are written in the style configured by
+    /// `ui.conflict-marker-style`. Conflicts with more than two sides are
+    /// supported.
+    #[arg(long, conflicts_with_all = ["list", "tool"])]
+    editor: bool,
+
     /// Only resolve conflicts in these paths. You can use the `--list` argument
     /// to find paths to use here.
     #[arg(value_name = "FILESETS", value_hint = clap::ValueHint::AnyPath)]
@@ -109,9 +121,18 @@ pub(crate) fn cmd_resolve(
         .map(|(path, _)| path.as_ref())
         .collect_vec();
     workspace_command.check_rewritable([commit.id()])?;
-    let merge_editor = workspace_command.merge_editor(ui, args.tool.as_deref())?;
+    let merge_editor = if args.editor {
+        MergeEditor::with_text_editor(
+            workspace_command.text_editor()?,
+            workspace_command.path_converter().clone(),
+            workspace_command.env().conflict_marker_style(),
+        )
+    } else {
+        workspace_command.merge_editor(ui, args.tool.as_deref())?
+    };
     let mut tx = workspace_command.start_transaction();
     let (new_tree, partial_resolution_error) = merge_editor.edit_files(ui, &tree, &repo_paths)?;
+    record_conflict_resolutions(ui, &tree, &new_tree).block_on()?;
     let new_commit = tx
         .repo_mut()
         .rewrite_commit(&commit)
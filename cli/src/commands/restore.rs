@@ -21,6 +21,7 @@ use itertools::Itertools as _;
 use jj_lib::commit::conflict_label_for_commits;
 use jj_lib::merge::Diff;
 use jj_lib::object_id::ObjectId as _;
+use jj_lib::repo::Repo as _;
 use pollster::FutureExt as _;
 use tracing::instrument;
 
@@ -30,6 +31,7 @@ use crate::cli_util::print_unmatched_explicit_paths;
 use crate::command_error::CommandError;
 use crate::command_error::user_error;
 use crate::complete;
+use crate::diff_util::get_copy_records_between;
 use crate::ui::Ui;
 
 /// Restore paths from another revision
@@ -154,6 +156,14 @@ pub(crate) fn cmd_restore(
             to_commit = workspace_command.format_commit_summary(&to_commit),
         }
     };
+    let copy_records = || {
+        get_copy_records_between(
+            workspace_command.repo().store(),
+            [to_commit.id()],
+            from_commits.iter().map(|commit| commit.id()),
+            &matcher,
+        )
+    };
     let new_tree = diff_selector.select(
         ui,
         Diff::new(&to_tree, &from_tree),
@@ -162,6 +172,7 @@ pub(crate) fn cmd_restore(
             conflict_label_for_commits(&from_commits),
         ),
         &matcher,
+        copy_records,
         format_instructions,
     )?;
 
@@ -14,26 +14,73 @@
 
 //! This file contains the internal implementation of `run`.
 
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::io::Write as _;
+use std::path::Path;
+use std::process::Command;
+use std::process::ExitStatus;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::mpsc::channel;
+
+use clap_complete::ArgValueCompleter;
 use itertools::Itertools as _;
+use jj_lib::backend::CommitId;
+use jj_lib::commit::Commit;
+use jj_lib::fsmonitor::FsmonitorSettings;
+use jj_lib::local_working_copy::TreeState;
+use jj_lib::local_working_copy::TreeStateError;
+use jj_lib::local_working_copy::TreeStateSettings;
+use jj_lib::lock::FileLock;
+use jj_lib::merge::Merge;
+use jj_lib::merged_tree::MergedTree;
+use jj_lib::repo::Repo as _;
+use jj_lib::revset::RevsetIteratorExt as _;
+use jj_lib::settings::UserSettings;
+use jj_lib::store::Store;
+use jj_lib::working_copy::CheckoutError;
+use jj_lib::working_copy::SnapshotError;
+use jj_lib::working_copy::SnapshotOptions;
+use pollster::FutureExt as _;
+use thiserror::Error;
+use tracing::instrument;
 
 use crate::cli_util::CommandHelper;
 use crate::cli_util::RevisionArg;
 use crate::command_error::CommandError;
+use crate::command_error::internal_error_with_message;
 use crate::command_error::user_error;
+use crate::complete;
 use crate::ui::Ui;
 
-/// (**Stub**, does not work yet) Run a command across a set of revisions.
+/// Run a command across a set of revisions
+///
+/// Each selected revision is checked out into a temporary working copy, where
+/// the command is run. Any changes the command makes to the files are then
+/// amended into the revision, and descendants are rebased on top of the
+/// result. The whole run is recorded as a single operation, so it can be
+/// reverted with `jj undo`.
 ///
+/// The temporary working copies live in the `.jj/repo/run` directory, which
+/// is shared by all workspaces of the repo, and are reused between
+/// invocations. Files ignored by the command (such as build outputs) are left
+/// in place so incremental builds can reuse them. Pass `--clean` to remove the
+/// working copies and reclaim the disk space.
 ///
-/// All recorded state will be persisted in the `.jj` directory, so occasionally
-/// a `jj run --clean` is needed to clean up disk space.
+/// The command is run by the system shell (`sh -c` on Unix, `cmd /C` on
+/// Windows) with the temporary working copy as current directory. Revisions
+/// are processed in topological order. If the command fails for a revision,
+/// that revision is left unchanged and `jj run` exits with an error after
+/// processing the remaining revisions.
 ///
-/// # Example
+/// ### Example
 ///
-/// # Run pre-commit on your local work
-/// $ jj run 'pre-commit run .github/pre-commit.yaml' -r (trunk()..@) -j 4
+/// Run pre-commit on your local work, with at most 4 commands in parallel:
 ///
-/// This allows pre-commit integration and other funny stuff.
+/// ```shell
+/// $ jj run 'pre-commit run --config .github/pre-commit.yaml' -r 'trunk()..@' -j 4
+/// ```
 #[derive(clap::Args, Clone, Debug)]
 #[command(verbatim_doc_comment)]
 pub struct RunArgs {
@@ -42,6 +89,7 @@ pub struct RunArgs {
 
     /// The revisions to change.
     #[arg(long, short, default_value = "@", value_name = "REVSETS")]
+    #[arg(add = ArgValueCompleter::new(complete::revset_expression_mutable))]
     revisions: Vec<RevisionArg>,
 
     /// A no-op option to match the interface of `git rebase -x`.
@@ -51,23 +99,296 @@ pub struct RunArgs {
     /// How many processes should run in parallel, uses by default all cores.
     #[arg(long, short)]
     jobs: Option<usize>,
+
+    /// Remove the temporary working copies (including any ignored files in
+    /// them) before running the command.
+    #[arg(long)]
+    clean: bool,
+}
+
+/// Error while running the command in a temporary working copy.
+#[derive(Debug, Error)]
+enum RunError {
+    #[error(transparent)]
+    TreeState(#[from] TreeStateError),
+    #[error(transparent)]
+    Checkout(#[from] CheckoutError),
+    #[error(transparent)]
+    Snapshot(#[from] SnapshotError),
+    #[error("Failed to start `{command}`")]
+    Spawn {
+        command: String,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+/// A temporary working copy that commands are run in. The lock is held for as
+/// long as the slot is in use.
+struct RunSlot {
+    tree_state: TreeState,
+    _lock: FileLock,
 }
 
+/// The result of running the command on a single commit.
+struct RunOutput {
+    status: ExitStatus,
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+    /// The snapshotted working copy after the command finished.
+    tree: MergedTree,
+}
+
+#[instrument(skip_all)]
 pub fn cmd_run(ui: &mut Ui, command: &CommandHelper, args: &RunArgs) -> Result<(), CommandError> {
-    let workspace_command = command.workspace_helper(ui)?;
-    let _resolved_commits: Vec<_> = workspace_command
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let target_expr = workspace_command
         .parse_union_revsets(ui, &args.revisions)?
-        .evaluate_to_commits()?
+        .resolve()?;
+    workspace_command.check_rewritable_expr(&target_expr)?;
+    let repo = workspace_command.repo().clone();
+    // Reverse the (reverse topological) revset order so parents are run first.
+    let commits: Vec<Commit> = target_expr
+        .evaluate(repo.as_ref())?
+        .iter()
+        .commits(repo.store())
         .try_collect()?;
+    let commits = commits.into_iter().rev().collect_vec();
     // Jobs are resolved in this order:
     // 1. Commandline argument iff > 0.
     // 2. the amount of cores available.
     // 3. a single job, if all of the above fails.
-    let _jobs = match args.jobs {
+    let jobs = match args.jobs {
         Some(0) | None => std::thread::available_parallelism().map(|t| t.into()).ok(),
         Some(jobs) => Some(jobs),
     }
     // Fallback to a single user-visible job.
-    .unwrap_or(1usize);
-    Err(user_error("This is a stub, do not use"))
+    .unwrap_or(1usize)
+    .min(commits.len().max(1));
+
+    let run_dir = workspace_command.repo_path().join("run");
+    if args.clean && run_dir.exists() {
+        std::fs::remove_dir_all(&run_dir).map_err(|err| {
+            internal_error_with_message("Failed to remove temporary working copies", err)
+        })?;
+    }
+    if commits.is_empty() {
+        writeln!(ui.status(), "No revisions to run on.")?;
+        return Ok(());
+    }
+
+    let auto_tracking_matcher = workspace_command.auto_tracking_matcher(ui)?;
+    let snapshot_options =
+        workspace_command.snapshot_options_with_start_tracking_matcher(&auto_tracking_matcher)?;
+    let summaries: HashMap<CommitId, String> = commits
+        .iter()
+        .map(|commit| {
+            let summary = workspace_command.format_commit_summary(commit);
+            (commit.id().clone(), summary)
+        })
+        .collect();
+
+    let slots: Vec<RunSlot> = (0..jobs)
+        .map(|index| {
+            load_slot(
+                &run_dir.join(index.to_string()),
+                repo.store(),
+                command.settings(),
+            )
+        })
+        .try_collect()?;
+
+    let queue = Mutex::new(commits.iter().collect::<VecDeque<_>>());
+    let mut new_trees: HashMap<CommitId, MergedTree> = HashMap::new();
+    let mut failed_commits: Vec<CommitId> = vec![];
+    std::thread::scope(|s| -> Result<(), CommandError> {
+        let (results_tx, results_rx) = channel();
+        for mut slot in slots {
+            let queue = &queue;
+            let results_tx = results_tx.clone();
+            let snapshot_options = &snapshot_options;
+            s.spawn(move || {
+                loop {
+                    let Some(commit) = queue.lock().unwrap().pop_front() else {
+                        break;
+                    };
+                    let result =
+                        run_in_slot(&mut slot, commit, &args.shell_command, snapshot_options);
+                    if results_tx.send((commit, result)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(results_tx);
+        // The outputs are reported in the order of the commits, regardless of
+        // which command finishes first.
+        let mut commits_to_report = commits.iter().peekable();
+        let mut finished = HashMap::new();
+        for (commit, result) in results_rx {
+            let output = match result {
+                Ok(output) => output,
+                Err(err) => {
+                    // Stop scheduling more work, but let running commands finish.
+                    queue.lock().unwrap().clear();
+                    return Err(internal_error_with_message(
+                        format!("Failed to run command on {}", summaries[commit.id()]),
+                        err,
+                    ));
+                }
+            };
+            finished.insert(commit.id(), output);
+            while let Some(&commit) = commits_to_report.peek()
+                && let Some(output) = finished.remove(commit.id())
+            {
+                commits_to_report.next();
+                writeln!(ui.status(), "Running on {}", summaries[commit.id()])?;
+                ui.stdout().write_all(&output.stdout)?;
+                ui.stderr().write_all(&output.stderr)?;
+                if !output.status.success() {
+                    writeln!(
+                        ui.warning_default(),
+                        "Command `{}` failed on {} ({})",
+                        args.shell_command,
+                        summaries[commit.id()],
+                        output.status
+                    )?;
+                    failed_commits.push(commit.id().clone());
+                } else if output.tree.tree_ids_and_labels() != commit.tree().tree_ids_and_labels() {
+                    new_trees.insert(commit.id().clone(), output.tree);
+                }
+            }
+        }
+        Ok(())
+    })?;
+
+    let mut tx = workspace_command.start_transaction();
+    let mut num_rewritten = 0;
+    // Keep the topological order so that `transform_descendants()` visits the
+    // commits in a deterministic way.
+    let root_ids = commits
+        .iter()
+        .map(|commit| commit.id())
+        .filter(|id| new_trees.contains_key(id))
+        .cloned()
+        .collect_vec();
+    tx.repo_mut()
+        .transform_descendants(root_ids, async |rewriter| {
+            let old_commit = rewriter.old_commit().clone();
+            if let Some(run_tree) = new_trees.get(old_commit.id()) {
+                // Apply the changes made by the command on top of the rebased commit, so
+                // that changes made to ancestors are preserved.
+                let label = old_commit.conflict_label();
+                let builder = rewriter.rebase().await?;
+                let new_tree = MergedTree::merge(Merge::from_vec(vec![
+                    (builder.tree(), format!("{label} (rebased revision)")),
+                    (old_commit.tree(), format!("{label} (before run)")),
+                    (run_tree.clone(), format!("{label} (after run)")),
+                ]))
+                .await?;
+                builder.set_tree(new_tree).write().await?;
+                num_rewritten += 1;
+            } else if rewriter.parents_changed() {
+                rewriter.rebase().await?.write().await?;
+            }
+            Ok(())
+        })
+        .block_on()?;
+    if failed_commits.is_empty() {
+        writeln!(
+            ui.status(),
+            "Ran command on {} commit{}, {num_rewritten} changed.",
+            commits.len(),
+            if commits.len() == 1 { "" } else { "s" },
+        )?;
+    }
+    tx.finish(ui, format!("run command '{}'", args.shell_command))?;
+
+    if failed_commits.is_empty() {
+        Ok(())
+    } else {
+        Err(user_error(format!(
+            "Command failed on {} of {} commits",
+            failed_commits.len(),
+            commits.len()
+        )))
+    }
+}
+
+/// Loads (or creates) the temporary working copy at `slot_dir`. Blocks until
+/// no other process is using it.
+fn load_slot(
+    slot_dir: &Path,
+    store: &Arc<Store>,
+    settings: &UserSettings,
+) -> Result<RunSlot, CommandError> {
+    let working_copy_path = slot_dir.join("working_copy");
+    let state_path = slot_dir.join("state");
+    for dir in [&working_copy_path, &state_path] {
+        std::fs::create_dir_all(dir).map_err(|err| {
+            internal_error_with_message("Failed to set up temporary working copy", err)
+        })?;
+    }
+    let lock = FileLock::lock(slot_dir.join("lock"))
+        .map_err(|err| internal_error_with_message("Failed to lock temporary working copy", err))?;
+    let tree_state_settings = TreeStateSettings {
+        // Nothing is watching the temporary working copies.
+        fsmonitor_settings: FsmonitorSettings::None,
+        ..TreeStateSettings::try_from_user_settings(settings)?
+    };
+    let tree_state = TreeState::load(
+        store.clone(),
+        working_copy_path,
+        state_path,
+        &tree_state_settings,
+    )
+    .map_err(|err| internal_error_with_message("Failed to load temporary working copy", err))?;
+    Ok(RunSlot {
+        tree_state,
+        _lock: lock,
+    })
+}
+
+/// Checks out `commit` in the slot, runs `shell_command` in it and snapshots
+/// the result.
+fn run_in_slot(
+    slot: &mut RunSlot,
+    commit: &Commit,
+    shell_command: &str,
+    snapshot_options: &SnapshotOptions,
+) -> Result<RunOutput, RunError> {
+    let tree_state = &mut slot.tree_state;
+    // Pick up any leftovers from an interrupted run so that the checkout below
+    // doesn't trip over them.
+    tree_state.snapshot(snapshot_options).block_on()?;
+    tree_state.check_out(&commit.tree())?;
+    tree_state.save()?;
+    let output = shell_command_in(tree_state.working_copy_path(), shell_command)
+        .output()
+        .map_err(|source| RunError::Spawn {
+            command: shell_command.to_owned(),
+            source,
+        })?;
+    tree_state.snapshot(snapshot_options).block_on()?;
+    tree_state.save()?;
+    Ok(RunOutput {
+        status: output.status,
+        stdout: output.stdout,
+        stderr: output.stderr,
+        tree: tree_state.current_tree().clone(),
+    })
+}
+
+fn shell_command_in(dir: &Path, shell_command: &str) -> Command {
+    let mut command = if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.arg("/C");
+        command
+    } else {
+        let mut command = Command::new("sh");
+        command.arg("-c");
+        command
+    };
+    command.arg(shell_command).current_dir(dir);
+    command
 }
@@ -23,6 +23,7 @@ use jj_lib::merge::Diff;
 use jj_lib::merge::Merge;
 use jj_lib::merged_tree::MergedTree;
 use jj_lib::object_id::ObjectId as _;
+use jj_lib::repo::Repo as _;
 use jj_lib::rewrite::CommitWithSelection;
 use jj_lib::rewrite::EmptyBehavior;
 use jj_lib::rewrite::MoveCommitsLocation;
@@ -47,6 +48,8 @@ use crate::description_util::add_trailers;
 use crate::description_util::description_template;
 use crate::description_util::edit_description;
 use crate::description_util::join_message_paragraphs;
+use crate::diff_util::get_copy_records_between;
+use crate::hooks::run_commit_msg_hook;
 use crate::ui::Ui;
 
 /// Split a revision in two
@@ -321,6 +324,12 @@ pub(crate) fn cmd_split(
         } else {
             description
         };
+        let description = run_commit_msg_hook(
+            ui,
+            tx.settings(),
+            tx.base_workspace_helper().workspace_root(),
+            description,
+        )?;
         commit_builder.set_description(description);
         commit_builder.write(tx.repo_mut()).block_on()?
     };
@@ -378,7 +387,13 @@ pub(crate) fn cmd_split(
             let temp_commit = commit_builder.write_hidden().block_on()?;
             let intro = "Enter a description for the remaining changes.";
             let template = description_template(ui, &tx, intro, &temp_commit)?;
-            edit_description(&text_editor, &template)?
+            let description = edit_description(&text_editor, &template)?;
+            run_commit_msg_hook(
+                ui,
+                tx.settings(),
+                tx.base_workspace_helper().workspace_root(),
+                description,
+            )?
         } else {
             description
         };
@@ -549,6 +564,14 @@ The changes that are not selected will replace the original commit.
         )
     };
     let parent_tree = target_commit.parent_tree(tx.repo())?;
+    let copy_records = || {
+        get_copy_records_between(
+            tx.repo().store(),
+            target_commit.parent_ids(),
+            [target_commit.id()],
+            matcher,
+        )
+    };
     let selected_tree = diff_selector.select(
         ui,
         Diff::new(&parent_tree, &target_commit.tree()),
@@ -557,6 +580,7 @@ The changes that are not selected will replace the original commit.
             target_commit.conflict_label(),
         ),
         matcher,
+        copy_records,
         format_instructions,
     )?;
     let selection = CommitWithSelection {
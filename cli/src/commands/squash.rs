@@ -46,6 +46,8 @@ use crate::description_util::description_template;
 use crate::description_util::edit_description;
 use crate::description_util::join_message_paragraphs;
 use crate::description_util::try_combine_messages;
+use crate::diff_util::get_copy_records_between;
+use crate::hooks::run_commit_msg_hook;
 use crate::ui::Ui;
 
 /// Move changes from a revision into another revision
@@ -372,6 +374,16 @@ pub(crate) fn cmd_squash(
             let template = description_template(ui, &tx, intro, &temp_commit)?;
             edit_description(&text_editor, &template)?
         };
+        let description = if description != destination.description() {
+            run_commit_msg_hook(
+                ui,
+                tx.settings(),
+                tx.base_workspace_helper().workspace_root(),
+                description,
+            )?
+        } else {
+            description
+        };
         commit_builder.set_description(description);
         if insert_destination_commit {
             // forget about the intermediate commit
@@ -486,11 +498,20 @@ fn select_diff(
                 destination = tx.format_commit_summary(destination),
             }
         };
+        let copy_records = || {
+            get_copy_records_between(
+                tx.repo().store(),
+                source.parent_ids(),
+                [source.id()],
+                matcher,
+            )
+        };
         let selected_tree = diff_selector.select(
             ui,
             Diff::new(&parent_tree, &source_tree),
             Diff::new(source.parents_conflict_label()?, source.conflict_label()),
             matcher,
+            copy_records,
             format_instructions,
         )?;
         source_commits.push(CommitWithSelection {
@@ -12,11 +12,14 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use futures::StreamExt as _;
 use itertools::Itertools as _;
-use jj_lib::copies::CopyRecords;
+use jj_lib::commit::Commit;
+use jj_lib::copies::CopiesTreeDiffEntry;
+use jj_lib::matchers::Matcher;
 use jj_lib::merge::Diff;
 use jj_lib::merged_tree::MergedTree;
-use jj_lib::repo::Repo as _;
+use jj_lib::repo::Repo;
 use jj_lib::repo_path::RepoPath;
 use jj_lib::repo_path::RepoPathBuf;
 use jj_lib::revset::RevsetExpression;
@@ -28,9 +31,11 @@ use crate::cli_util::CommandHelper;
 use crate::cli_util::print_conflicted_paths;
 use crate::cli_util::print_snapshot_stats;
 use crate::cli_util::print_unmatched_explicit_paths;
+use crate::cli_util::write_json_line;
 use crate::command_error::CommandError;
 use crate::diff_util::DiffFormat;
-use crate::diff_util::get_copy_records;
+use crate::diff_util::diff_status;
+use crate::diff_util::get_copy_records_between;
 use crate::formatter::FormatterExt as _;
 use crate::ui::Ui;
 
@@ -55,6 +60,15 @@ pub(crate) struct StatusArgs {
     /// Restrict the status display to these paths
     #[arg(value_name = "FILESETS", value_hint = clap::ValueHint::AnyPath)]
     paths: Vec<String>,
+
+    /// Print the status as a JSON object
+    ///
+    /// See [JSON output] for the format.
+    ///
+    /// [JSON output]:
+    ///     https://docs.jj-vcs.dev/latest/json-output/
+    #[arg(long)]
+    json: bool,
 }
 
 #[instrument(skip_all)]
@@ -76,6 +90,26 @@ pub(crate) fn cmd_status(
         .transpose()?;
     let fileset_expression = workspace_command.parse_file_patterns(ui, &args.paths)?;
     let matcher = fileset_expression.to_matcher();
+    if args.json {
+        if let Some(wc_commit) = &maybe_wc_commit {
+            print_unmatched_explicit_paths(
+                ui,
+                &workspace_command,
+                &fileset_expression,
+                [&wc_commit.tree()],
+            )?;
+        }
+        let status = StatusJson::collect(
+            repo.as_ref(),
+            maybe_wc_commit.as_ref(),
+            matcher.as_ref(),
+            snapshot_stats.untracked_paths.keys(),
+        )
+        .block_on()?;
+        ui.request_pager();
+        write_json_line(ui.stdout_formatter().as_mut(), &status)?;
+        return Ok(());
+    }
     ui.request_pager();
     let mut formatter = ui.stdout_formatter();
     let formatter = formatter.as_mut();
@@ -93,11 +127,12 @@ pub(crate) fn cmd_status(
         } else {
             if wc_has_changes {
                 writeln!(formatter, "Working copy changes:")?;
-                let mut copy_records = CopyRecords::default();
-                for parent in wc_commit.parent_ids() {
-                    let records = get_copy_records(repo.store(), parent, wc_commit.id(), &matcher)?;
-                    copy_records.add_records(records)?;
-                }
+                let copy_records = get_copy_records_between(
+                    repo.store(),
+                    wc_commit.parent_ids(),
+                    [wc_commit.id()],
+                    &matcher,
+                )?;
                 let diff_renderer = workspace_command.diff_renderer(vec![DiffFormat::Summary]);
                 let width = ui.term_width();
                 diff_renderer
@@ -233,6 +268,104 @@ pub(crate) fn cmd_status(
     Ok(())
 }
 
+/// Status of the working copy as printed by `jj status --json`.
+#[derive(Debug, serde::Serialize)]
+struct StatusJson {
+    working_copy: Option<Commit>,
+    parents: Vec<Commit>,
+    changes: Vec<ChangedPathJson>,
+    /// Untracked paths. Untracked directories end with `/`.
+    untracked_paths: Vec<String>,
+    conflicts: Vec<RepoPathBuf>,
+    conflicted_bookmarks: Vec<String>,
+    conflicted_remote_bookmarks: Vec<RemoteBookmarkJson>,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct ChangedPathJson {
+    status: &'static str,
+    path: RepoPathBuf,
+    /// Source path of a copied or renamed file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source: Option<RepoPathBuf>,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct RemoteBookmarkJson {
+    name: String,
+    remote: String,
+}
+
+impl StatusJson {
+    async fn collect(
+        repo: &dyn Repo,
+        wc_commit: Option<&Commit>,
+        matcher: &dyn Matcher,
+        untracked_paths: impl IntoIterator<Item = impl AsRef<RepoPath>>,
+    ) -> Result<Self, CommandError> {
+        let mut parents = vec![];
+        let mut changes = vec![];
+        let mut untracked = vec![];
+        let mut conflicts = vec![];
+        if let Some(wc_commit) = wc_commit {
+            parents = wc_commit.parents().try_collect()?;
+            let parent_tree = wc_commit.parent_tree(repo)?;
+            let tree = wc_commit.tree();
+            let copy_records = get_copy_records_between(
+                repo.store(),
+                wc_commit.parent_ids(),
+                [wc_commit.id()],
+                matcher,
+            )?;
+            let mut tree_diff = parent_tree.diff_stream_with_copies(&tree, matcher, &copy_records);
+            while let Some(CopiesTreeDiffEntry { path, values }) = tree_diff.next().await {
+                let status = diff_status(&path, &values?);
+                changes.push(ChangedPathJson {
+                    status: status.label(),
+                    path: path.target,
+                    source: path.source.map(|(source, _)| source),
+                });
+            }
+            visit_collapsed_untracked_files(untracked_paths, tree.clone(), |path, is_dir| {
+                untracked.push(if is_dir {
+                    path.to_internal_dir_string()
+                } else {
+                    path.as_internal_file_string().to_owned()
+                });
+                Ok(())
+            })
+            .await?;
+            conflicts = tree
+                .conflicts_matching(matcher)
+                .map(|(path, _)| path)
+                .collect();
+        }
+        let view = repo.view();
+        let conflicted_bookmarks = view
+            .local_bookmarks()
+            .filter(|(_, target)| target.has_conflict())
+            .map(|(name, _)| name.as_str().to_owned())
+            .collect();
+        let conflicted_remote_bookmarks = view
+            .all_remote_bookmarks()
+            .filter(|(_, remote_ref)| remote_ref.target.has_conflict())
+            .map(|(symbol, _)| RemoteBookmarkJson {
+                name: symbol.name.as_str().to_owned(),
+                remote: symbol.remote.as_str().to_owned(),
+            })
+            .collect();
+        Ok(Self {
+            working_copy: wc_commit.cloned(),
+            parents,
+            changes,
+            untracked_paths: untracked,
+            conflicts,
+            conflicted_bookmarks,
+            conflicted_remote_bookmarks,
+        })
+    }
+}
+
 async fn visit_collapsed_untracked_files(
     untracked_paths: impl IntoIterator<Item = impl AsRef<RepoPath>>,
     tree: MergedTree,
@@ -12,15 +12,21 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::io::Write as _;
 use std::slice;
 use std::time::Duration;
 use std::time::SystemTime;
 
+use jj_lib::default_index::DefaultIndexStore;
+use jj_lib::gc::GcOptions;
 use jj_lib::repo::Repo as _;
+use jj_lib::time_util;
 
 use crate::cli_util::CommandHelper;
 use crate::command_error::CommandError;
+use crate::command_error::internal_error;
 use crate::command_error::user_error;
+use crate::command_error::user_error_with_message;
 use crate::ui::Ui;
 
 /// Run backend-dependent garbage collection.
@@ -34,11 +40,17 @@ pub struct UtilGcArgs {
     /// By default, only obsolete objects and operations older than 2 weeks are
     /// pruned.
     ///
-    /// Only the string "now" can be passed to this parameter. Support for
-    /// arbitrary absolute and relative timestamps will come in a subsequent
-    /// release.
+    /// Accepts "now", an absolute date such as "2024-03-10", or a relative
+    /// date such as "3 days ago".
     #[arg(long)]
     expire: Option<String>,
+
+    /// Report what would be pruned without removing anything
+    ///
+    /// The number of commits is an upper bound since the backend may keep
+    /// some of them for other reasons.
+    #[arg(long)]
+    dry_run: bool,
 }
 
 pub fn cmd_util_gc(
@@ -53,14 +65,48 @@ pub fn cmd_util_gc(
     }
     let keep_newer = match args.expire.as_deref() {
         None => SystemTime::now() - Duration::from_secs(14 * 86400),
-        Some("now") => SystemTime::now() - Duration::ZERO,
-        _ => return Err(user_error("--expire only accepts 'now'")),
+        Some(expire) => parse_expire(expire)?,
     };
     let workspace_command = command.workspace_helper(ui)?;
 
     let repo = workspace_command.repo();
-    repo.op_store()
-        .gc(slice::from_ref(repo.op_id()), keep_newer)?;
-    repo.store().gc(repo.index(), keep_newer)?;
+    let options = GcOptions {
+        keep_newer,
+        dry_run: args.dry_run,
+    };
+    let mut stats = repo
+        .op_store()
+        .gc(slice::from_ref(repo.op_id()), &options)?;
+    if let Some(index_store) = repo.index_store().downcast_ref::<DefaultIndexStore>() {
+        stats += index_store
+            .gc(slice::from_ref(repo.operation()), &options)
+            .map_err(internal_error)?;
+    }
+    stats += repo.store().gc(repo.index(), &options)?;
+    if args.dry_run {
+        writeln!(
+            ui.status(),
+            "Would remove {} operations, {} commits, {} index segments, and {} extra metadata \
+             entries.",
+            stats.operations,
+            stats.commits,
+            stats.index_segments,
+            stats.extras_entries,
+        )?;
+    }
     Ok(())
 }
+
+fn parse_expire(expire: &str) -> Result<SystemTime, CommandError> {
+    let now = chrono::Local::now();
+    let millis = time_util::parse_date(expire, now)
+        .map_err(|err| user_error_with_message(format!("Invalid --expire time: {expire}"), err))?;
+    if millis.0 > now.timestamp_millis() {
+        return Err(user_error(format!(
+            "--expire time must not be in the future: {expire}"
+        )));
+    }
+    // Negative timestamps mean nothing will be pruned.
+    let since_epoch = Duration::from_millis(millis.0.try_into().unwrap_or(0));
+    Ok(SystemTime::UNIX_EPOCH + since_epoch)
+}
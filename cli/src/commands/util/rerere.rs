@@ -0,0 +1,109 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+
+use clap::Subcommand;
+use itertools::Itertools as _;
+use jj_lib::rerere::ResolutionStore;
+
+use crate::cli_util::CommandHelper;
+use crate::command_error::CommandError;
+use crate::command_error::user_error;
+use crate::ui::Ui;
+
+/// Manage recorded conflict resolutions
+///
+/// If `merge.rerere` is enabled, resolutions of conflicts in the working copy
+/// or made with `jj resolve` are recorded, and reused when the same conflict
+/// appears again.
+#[derive(Subcommand, Clone, Debug)]
+pub enum UtilRerereCommand {
+    List(UtilRerereListArgs),
+    Forget(UtilRerereForgetArgs),
+}
+
+/// List recorded conflict resolutions, most recent first
+#[derive(clap::Args, Clone, Debug)]
+pub struct UtilRerereListArgs {}
+
+/// Forget recorded conflict resolutions
+#[derive(clap::Args, Clone, Debug)]
+#[command(group(clap::ArgGroup::new("target").args(&["ids", "all"]).required(true)))]
+pub struct UtilRerereForgetArgs {
+    /// Ids (or unique id prefixes) of the resolutions to forget
+    ids: Vec<String>,
+    /// Forget all recorded resolutions
+    #[arg(long)]
+    all: bool,
+}
+
+pub fn cmd_util_rerere(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    subcommand: &UtilRerereCommand,
+) -> Result<(), CommandError> {
+    // Snapshot so that conflicts resolved in the working copy are recorded
+    let workspace_command = command.workspace_helper(ui)?;
+    let resolutions = ResolutionStore::for_repo(workspace_command.repo_path());
+    match subcommand {
+        UtilRerereCommand::List(UtilRerereListArgs {}) => {
+            let mut formatter = ui.stdout_formatter();
+            for resolution in resolutions.list()? {
+                writeln!(
+                    formatter,
+                    "{} {}",
+                    &resolution.id[..12],
+                    workspace_command.format_file_path(&resolution.path)
+                )?;
+            }
+        }
+        UtilRerereCommand::Forget(args) => {
+            let recorded = resolutions.list()?;
+            let ids = if args.all {
+                recorded
+                    .iter()
+                    .map(|resolution| &resolution.id)
+                    .collect_vec()
+            } else {
+                args.ids
+                    .iter()
+                    .map(|prefix| {
+                        let matches = recorded
+                            .iter()
+                            .filter(|resolution| resolution.id.starts_with(prefix.as_str()))
+                            .collect_vec();
+                        match matches[..] {
+                            [resolution] => Ok(&resolution.id),
+                            [] => Err(user_error(format!(
+                                "No recorded resolution with id {prefix}"
+                            ))),
+                            _ => Err(user_error(format!(
+                                "Resolution id prefix {prefix} is ambiguous"
+                            ))),
+                        }
+                    })
+                    .try_collect()?
+            };
+            let mut num_forgotten = 0;
+            for id in ids.into_iter().unique() {
+                if resolutions.forget(id)? {
+                    num_forgotten += 1;
+                }
+            }
+            writeln!(ui.status(), "Forgot {num_forgotten} recorded resolutions")?;
+        }
+    }
+    Ok(())
+}
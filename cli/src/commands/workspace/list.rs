@@ -17,6 +17,7 @@ use jj_lib::repo::Repo as _;
 use tracing::instrument;
 
 use crate::cli_util::CommandHelper;
+use crate::cli_util::write_json_line;
 use crate::command_error::CommandError;
 use crate::commit_templater::WorkspaceRef;
 use crate::complete;
@@ -40,6 +41,15 @@ pub struct WorkspaceListArgs {
     #[arg(long, short = 'T')]
     #[arg(add = ArgValueCandidates::new(complete::template_aliases))]
     template: Option<String>,
+
+    /// Print each workspace as a line of JSON instead of rendering a template
+    ///
+    /// See [JSON output] for the format.
+    ///
+    /// [JSON output]:
+    ///     https://docs.jj-vcs.dev/latest/json-output/
+    #[arg(long, conflicts_with = "template")]
+    json: bool,
 }
 
 #[instrument(skip_all)]
@@ -50,7 +60,9 @@ pub fn cmd_workspace_list(
 ) -> Result<(), CommandError> {
     let workspace_command = command.workspace_helper(ui)?;
 
-    let template: TemplateRenderer<WorkspaceRef> = {
+    let template: Option<TemplateRenderer<WorkspaceRef>> = if args.json {
+        None
+    } else {
         let language = workspace_command.commit_template_language();
 
         let text = match &args.template {
@@ -60,9 +72,10 @@ pub fn cmd_workspace_list(
                 .get("templates.workspace_list")?,
         };
 
-        workspace_command
+        let template = workspace_command
             .parse_template(ui, &language, &text)?
-            .labeled(["workspace_list"])
+            .labeled(["workspace_list"]);
+        Some(template)
     };
 
     let repo = workspace_command.repo();
@@ -72,7 +85,11 @@ pub fn cmd_workspace_list(
         let commit = repo.store().get_commit(wc_commit_id)?;
         let ws_ref = WorkspaceRef::new(name.clone(), commit);
 
-        template.format(&ws_ref, formatter.as_mut())?;
+        if let Some(template) = &template {
+            template.format(&ws_ref, formatter.as_mut())?;
+        } else {
+            write_json_line(formatter.as_mut(), &ws_ref)?;
+        }
     }
 
     Ok(())
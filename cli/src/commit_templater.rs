@@ -46,6 +46,7 @@ use jj_lib::extensions_map::ExtensionsMap;
 use jj_lib::fileset;
 use jj_lib::fileset::FilesetDiagnostics;
 use jj_lib::fileset::FilesetExpression;
+use jj_lib::gitattributes::GitAttributesLookup;
 use jj_lib::id_prefix::IdPrefixContext;
 use jj_lib::id_prefix::IdPrefixIndex;
 use jj_lib::index::IndexResult;
@@ -376,6 +377,11 @@ impl<'repo> TemplateLanguage<'repo> for CommitTemplateLanguage<'repo> {
                 let build = template_parser::lookup_method(type_name, table, function)?;
                 build(self, diagnostics, build_ctx, property, function)
             }
+            CommitTemplatePropertyKind::FileSearchMatch(property) => {
+                let table = &self.build_fn_table.file_search_match_methods;
+                let build = template_parser::lookup_method(type_name, table, function)?;
+                build(self, diagnostics, build_ctx, property, function)
+            }
             CommitTemplatePropertyKind::Trailer(property) => {
                 let table = &self.build_fn_table.trailer_methods;
                 let build = template_parser::lookup_method(type_name, table, function)?;
@@ -451,6 +457,7 @@ pub enum CommitTemplatePropertyKind<'repo> {
     DiffStatEntryList(BoxedTemplateProperty<'repo, Vec<DiffStatEntry>>),
     CryptographicSignatureOpt(BoxedTemplateProperty<'repo, Option<CryptographicSignature>>),
     AnnotationLine(BoxedTemplateProperty<'repo, AnnotationLine>),
+    FileSearchMatch(BoxedTemplateProperty<'repo, FileSearchMatch>),
     Trailer(BoxedTemplateProperty<'repo, Trailer>),
     TrailerList(BoxedTemplateProperty<'repo, Vec<Trailer>>),
 }
@@ -485,6 +492,7 @@ template_builder::impl_property_wrappers!(<'repo> CommitTemplatePropertyKind<'re
     DiffStatEntryList(Vec<DiffStatEntry>),
     CryptographicSignatureOpt(Option<CryptographicSignature>),
     AnnotationLine(AnnotationLine),
+    FileSearchMatch(FileSearchMatch),
     Trailer(Trailer),
     TrailerList(Vec<Trailer>),
 });
@@ -529,6 +537,7 @@ impl<'repo> CoreTemplatePropertyVar<'repo> for CommitTemplatePropertyKind<'repo>
             Self::DiffStatEntryList(_) => "List<DiffStatEntry>",
             Self::CryptographicSignatureOpt(_) => "Option<CryptographicSignature>",
             Self::AnnotationLine(_) => "AnnotationLine",
+            Self::FileSearchMatch(_) => "FileSearchMatch",
             Self::Trailer(_) => "Trailer",
             Self::TrailerList(_) => "List<Trailer>",
         }
@@ -569,6 +578,7 @@ impl<'repo> CoreTemplatePropertyVar<'repo> for CommitTemplatePropertyKind<'repo>
                 Some(property.map(|sig| sig.is_some()).into_dyn())
             }
             Self::AnnotationLine(_) => None,
+            Self::FileSearchMatch(_) => None,
             Self::Trailer(_) => None,
             Self::TrailerList(property) => Some(property.map(|l| !l.is_empty()).into_dyn()),
         }
@@ -638,6 +648,7 @@ impl<'repo> CoreTemplatePropertyVar<'repo> for CommitTemplatePropertyKind<'repo>
             Self::DiffStatEntryList(_) => None,
             Self::CryptographicSignatureOpt(_) => None,
             Self::AnnotationLine(_) => None,
+            Self::FileSearchMatch(_) => None,
             Self::Trailer(_) => None,
             Self::TrailerList(_) => None,
         }
@@ -674,6 +685,7 @@ impl<'repo> CoreTemplatePropertyVar<'repo> for CommitTemplatePropertyKind<'repo>
             Self::DiffStatEntryList(_) => None,
             Self::CryptographicSignatureOpt(_) => None,
             Self::AnnotationLine(_) => None,
+            Self::FileSearchMatch(_) => None,
             Self::Trailer(property) => Some(property.into_template()),
             Self::TrailerList(property) => Some(property.into_template()),
         }
@@ -743,6 +755,7 @@ impl<'repo> CoreTemplatePropertyVar<'repo> for CommitTemplatePropertyKind<'repo>
             (Self::DiffStatEntryList(_), _) => None,
             (Self::CryptographicSignatureOpt(_), _) => None,
             (Self::AnnotationLine(_), _) => None,
+            (Self::FileSearchMatch(_), _) => None,
             (Self::Trailer(_), _) => None,
             (Self::TrailerList(_), _) => None,
         }
@@ -785,6 +798,7 @@ impl<'repo> CoreTemplatePropertyVar<'repo> for CommitTemplatePropertyKind<'repo>
             (Self::DiffStatEntryList(_), _) => None,
             (Self::CryptographicSignatureOpt(_), _) => None,
             (Self::AnnotationLine(_), _) => None,
+            (Self::FileSearchMatch(_), _) => None,
             (Self::Trailer(_), _) => None,
             (Self::TrailerList(_), _) => None,
         }
@@ -823,6 +837,7 @@ pub struct CommitTemplateBuildFnTable<'repo> {
     pub cryptographic_signature_methods:
         CommitTemplateBuildMethodFnMap<'repo, CryptographicSignature>,
     pub annotation_line_methods: CommitTemplateBuildMethodFnMap<'repo, AnnotationLine>,
+    pub file_search_match_methods: CommitTemplateBuildMethodFnMap<'repo, FileSearchMatch>,
     pub trailer_methods: CommitTemplateBuildMethodFnMap<'repo, Trailer>,
     pub trailer_list_methods: CommitTemplateBuildMethodFnMap<'repo, Vec<Trailer>>,
 }
@@ -853,6 +868,7 @@ impl CommitTemplateBuildFnTable<'_> {
             diff_stat_entry_list_methods: HashMap::new(),
             cryptographic_signature_methods: HashMap::new(),
             annotation_line_methods: HashMap::new(),
+            file_search_match_methods: HashMap::new(),
             trailer_methods: HashMap::new(),
             trailer_list_methods: HashMap::new(),
         }
@@ -883,6 +899,7 @@ impl CommitTemplateBuildFnTable<'_> {
             diff_stat_entry_list_methods,
             cryptographic_signature_methods,
             annotation_line_methods,
+            file_search_match_methods,
             trailer_methods,
             trailer_list_methods,
         } = other;
@@ -928,6 +945,10 @@ impl CommitTemplateBuildFnTable<'_> {
             cryptographic_signature_methods,
         );
         merge_fn_map(&mut self.annotation_line_methods, annotation_line_methods);
+        merge_fn_map(
+            &mut self.file_search_match_methods,
+            file_search_match_methods,
+        );
         merge_fn_map(&mut self.trailer_methods, trailer_methods);
         merge_fn_map(&mut self.trailer_list_methods, trailer_list_methods);
     }
@@ -960,6 +981,7 @@ impl CommitTemplateBuildFnTable<'_> {
             diff_stat_entry_list_methods: template_builder::builtin_unformattable_list_methods(),
             cryptographic_signature_methods: builtin_cryptographic_signature_methods(),
             annotation_line_methods: builtin_annotation_line_methods(),
+            file_search_match_methods: builtin_file_search_match_methods(),
             trailer_methods: builtin_trailer_methods(),
             trailer_list_methods: builtin_trailer_list_methods(),
         }
@@ -1506,11 +1528,17 @@ fn builtin_commit_evolution_entry_methods<'repo>()
                 let from_tree =
                     rebase_to_dest_parent(repo, &predecessors, &entry.commit).block_on()?;
                 let to_tree = entry.commit.tree();
+                let copy_records = diff_util::get_copy_records_between(
+                    repo.store(),
+                    predecessors.iter().map(Commit::id),
+                    [entry.commit.id()],
+                    &*matcher,
+                )?;
                 Ok(TreeDiff {
                     from_tree,
                     to_tree,
                     matcher: matcher.clone(),
-                    copy_records: CopyRecords::default(), // TODO: copy tracking
+                    copy_records,
                 })
             });
             Ok(out_property.into_dyn_wrapped())
@@ -2277,12 +2305,12 @@ impl TreeDiff {
         commit: &Commit,
         matcher: Rc<dyn Matcher>,
     ) -> BackendResult<Self> {
-        let mut copy_records = CopyRecords::default();
-        for parent in commit.parent_ids() {
-            let records =
-                diff_util::get_copy_records(repo.store(), parent, commit.id(), &*matcher)?;
-            copy_records.add_records(records)?;
-        }
+        let copy_records = diff_util::get_copy_records_between(
+            repo.store(),
+            commit.parent_ids(),
+            [commit.id()],
+            &*matcher,
+        )?;
         Ok(Self {
             from_tree: commit.parent_tree(repo)?,
             to_tree: commit.tree(),
@@ -2310,6 +2338,7 @@ impl TreeDiff {
             &Store,
             BoxStream<CopiesTreeDiffEntry>,
             Diff<&ConflictLabels>,
+            &GitAttributesLookup,
         ) -> Result<(), E>,
         E: Into<TemplatePropertyError>,
     {
@@ -2330,6 +2359,7 @@ where
         &Store,
         BoxStream<CopiesTreeDiffEntry>,
         Diff<&ConflictLabels>,
+        &GitAttributesLookup,
     ) -> Result<(), E>,
     E: Into<TemplatePropertyError>,
 {
@@ -2338,8 +2368,15 @@ where
         let store = self.diff.from_tree.store();
         let tree_diff = self.diff.diff_stream();
         let conflict_labels = Diff::new(self.diff.from_tree.labels(), self.diff.to_tree.labels());
-        show(formatter.as_mut(), store, tree_diff, conflict_labels)
-            .or_else(|err| formatter.handle_error(err.into()))
+        let attributes = GitAttributesLookup::from_tree(self.diff.to_tree.clone());
+        show(
+            formatter.as_mut(),
+            store,
+            tree_diff,
+            conflict_labels,
+            &attributes,
+        )
+        .or_else(|err| formatter.handle_error(err.into()))
     }
 }
 
@@ -2385,18 +2422,21 @@ fn builtin_tree_diff_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, T
                     if let Some(context) = context {
                         options.context = context;
                     }
-                    diff.into_formatted(move |formatter, store, tree_diff, conflict_labels| {
-                        diff_util::show_color_words_diff(
-                            formatter,
-                            store,
-                            tree_diff,
-                            conflict_labels,
-                            path_converter,
-                            &options,
-                            conflict_marker_style,
-                        )
-                        .block_on()
-                    })
+                    diff.into_formatted(
+                        move |formatter, store, tree_diff, conflict_labels, attributes| {
+                            diff_util::show_color_words_diff(
+                                formatter,
+                                store,
+                                tree_diff,
+                                conflict_labels,
+                                attributes,
+                                path_converter,
+                                &options,
+                                conflict_marker_style,
+                            )
+                            .block_on()
+                        },
+                    )
                 })
                 .into_template();
             Ok(P::wrap_template(template))
@@ -2428,17 +2468,20 @@ fn builtin_tree_diff_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, T
                     if let Some(context) = context {
                         options.context = context;
                     }
-                    diff.into_formatted(move |formatter, store, trees, tree_diff| {
-                        diff_util::show_git_diff(
-                            formatter,
-                            store,
-                            trees,
-                            tree_diff,
-                            &options,
-                            conflict_marker_style,
-                        )
-                        .block_on()
-                    })
+                    diff.into_formatted(
+                        move |formatter, store, tree_diff, conflict_labels, attributes| {
+                            diff_util::show_git_diff(
+                                formatter,
+                                store,
+                                tree_diff,
+                                conflict_labels,
+                                attributes,
+                                &options,
+                                conflict_marker_style,
+                            )
+                            .block_on()
+                        },
+                    )
                 })
                 .into_template();
             Ok(P::wrap_template(template))
@@ -2466,8 +2509,15 @@ fn builtin_tree_diff_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, T
             let out_property = (self_property, width_property).and_then(move |(diff, width)| {
                 let store = diff.from_tree.store();
                 let tree_diff = diff.diff_stream();
-                let stats = DiffStats::calculate(store, tree_diff, &options, conflict_marker_style)
-                    .block_on()?;
+                let attributes = GitAttributesLookup::from_tree(diff.to_tree.clone());
+                let stats = DiffStats::calculate(
+                    store,
+                    tree_diff,
+                    &attributes,
+                    &options,
+                    conflict_marker_style,
+                )
+                .block_on()?;
                 Ok(DiffStatsFormatted {
                     stats,
                     path_converter,
@@ -2485,10 +2535,12 @@ fn builtin_tree_diff_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, T
             let path_converter = language.path_converter;
             let template = self_property
                 .map(move |diff| {
-                    diff.into_formatted(move |formatter, _store, tree_diff, _conflict_labels| {
-                        diff_util::show_diff_summary(formatter, tree_diff, path_converter)
-                            .block_on()
-                    })
+                    diff.into_formatted(
+                        move |formatter, _store, tree_diff, _conflict_labels, _attributes| {
+                            diff_util::show_diff_summary(formatter, tree_diff, path_converter)
+                                .block_on()
+                        },
+                    )
                 })
                 .into_template();
             Ok(P::wrap_template(template))
@@ -2876,6 +2928,7 @@ pub struct AnnotationLine {
     pub content: BString,
     pub line_number: usize,
     pub original_line_number: usize,
+    pub original_path: RepoPathBuf,
     pub first_line_in_hunk: bool,
 }
 
@@ -2917,6 +2970,14 @@ fn builtin_annotation_line_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'r
             Ok(out_property.into_dyn_wrapped())
         },
     );
+    map.insert(
+        "original_path",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|line| line.original_path);
+            Ok(out_property.into_dyn_wrapped())
+        },
+    );
     map.insert(
         "first_line_in_hunk",
         |_language, _diagnostics, _build_ctx, self_property, function| {
@@ -2928,6 +2989,71 @@ fn builtin_annotation_line_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'r
     map
 }
 
+#[derive(Debug, Clone)]
+pub struct FileSearchMatch {
+    pub commit: Commit,
+    pub path: RepoPathBuf,
+    pub content: BString,
+    pub line_number: usize,
+    pub is_context: bool,
+    pub conflict_side: Option<String>,
+}
+
+fn builtin_file_search_match_methods<'repo>()
+-> CommitTemplateBuildMethodFnMap<'repo, FileSearchMatch> {
+    type P<'repo> = CommitTemplatePropertyKind<'repo>;
+    let mut map = CommitTemplateBuildMethodFnMap::<FileSearchMatch>::new();
+    map.insert(
+        "commit",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|line| line.commit);
+            Ok(out_property.into_dyn_wrapped())
+        },
+    );
+    map.insert(
+        "path",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|line| line.path);
+            Ok(out_property.into_dyn_wrapped())
+        },
+    );
+    map.insert(
+        "content",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|line| line.content);
+            Ok(P::wrap_template(out_property.into_template()))
+        },
+    );
+    map.insert(
+        "line_number",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.and_then(|line| Ok(i64::try_from(line.line_number)?));
+            Ok(out_property.into_dyn_wrapped())
+        },
+    );
+    map.insert(
+        "is_context",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|line| line.is_context);
+            Ok(out_property.into_dyn_wrapped())
+        },
+    );
+    map.insert(
+        "conflict_side",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|line| line.conflict_side.unwrap_or_default());
+            Ok(out_property.into_dyn_wrapped())
+        },
+    );
+    map
+}
+
 impl Template for Trailer {
     fn format(&self, formatter: &mut TemplateFormatter) -> io::Result<()> {
         write!(formatter, "{}: {}", self.key, self.value)
@@ -64,6 +64,8 @@ use jj_lib::files::ConflictDiffHunk;
 use jj_lib::files::DiffLineHunkSide;
 use jj_lib::files::DiffLineIterator;
 use jj_lib::files::DiffLineNumber;
+use jj_lib::gitattributes::AttributeValue;
+use jj_lib::gitattributes::GitAttributesLookup;
 use jj_lib::matchers::Matcher;
 use jj_lib::merge::Diff;
 use jj_lib::merge::Merge;
@@ -475,6 +477,7 @@ impl<'a> DiffRenderer<'a> {
                 .diff_stream_with_copies(trees.after, matcher, copy_records)
         };
         let conflict_labels = trees.map(|tree| tree.labels());
+        let attributes = GitAttributesLookup::from_tree(trees.after.clone());
 
         let store = self.repo.store();
         let path_converter = self.path_converter;
@@ -486,9 +489,14 @@ impl<'a> DiffRenderer<'a> {
                 }
                 DiffFormat::Stat(options) => {
                     let tree_diff = diff_stream();
-                    let stats =
-                        DiffStats::calculate(store, tree_diff, options, self.conflict_marker_style)
-                            .block_on()?;
+                    let stats = DiffStats::calculate(
+                        store,
+                        tree_diff,
+                        &attributes,
+                        options,
+                        self.conflict_marker_style,
+                    )
+                    .block_on()?;
                     show_diff_stats(formatter, &stats, path_converter, width)?;
                 }
                 DiffFormat::Types => {
@@ -506,6 +514,7 @@ impl<'a> DiffRenderer<'a> {
                         store,
                         tree_diff,
                         conflict_labels,
+                        &attributes,
                         options,
                         self.conflict_marker_style,
                     )
@@ -518,6 +527,7 @@ impl<'a> DiffRenderer<'a> {
                         store,
                         tree_diff,
                         conflict_labels,
+                        &attributes,
                         path_converter,
                         options,
                         self.conflict_marker_style,
@@ -638,7 +648,12 @@ impl<'a> DiffRenderer<'a> {
         let to_description = Merge::resolved(to_commit.description());
         let from_tree = rebase_to_dest_parent(self.repo, from_commits, to_commit).await?;
         let to_tree = to_commit.tree();
-        let copy_records = CopyRecords::default(); // TODO
+        let copy_records = get_copy_records_between(
+            self.repo.store(),
+            from_commits.iter().map(Commit::id),
+            [to_commit.id()],
+            matcher,
+        )?;
         self.show_diff_commit_descriptions(
             *formatter,
             Diff::new(&from_description, &to_description),
@@ -665,11 +680,12 @@ impl<'a> DiffRenderer<'a> {
     ) -> Result<(), DiffRenderError> {
         let from_tree = commit.parent_tree_async(self.repo).await?;
         let to_tree = commit.tree();
-        let mut copy_records = CopyRecords::default();
-        for parent_id in commit.parent_ids() {
-            let records = get_copy_records(self.repo.store(), parent_id, commit.id(), matcher)?;
-            copy_records.add_records(records)?;
-        }
+        let copy_records = get_copy_records_between(
+            self.repo.store(),
+            commit.parent_ids(),
+            [commit.id()],
+            matcher,
+        )?;
         self.show_diff(
             ui,
             formatter,
@@ -694,6 +710,25 @@ pub fn get_copy_records<'a>(
     Ok(block_on_stream(stream).filter_ok(|record| matcher.matches(&record.target)))
 }
 
+/// Collects the copy records from each of `from_ids` to each of `to_ids`
+/// which match the `matcher`.
+pub fn get_copy_records_between<'a>(
+    store: &Store,
+    from_ids: impl IntoIterator<Item = &'a CommitId>,
+    to_ids: impl IntoIterator<Item = &'a CommitId>,
+    matcher: &dyn Matcher,
+) -> BackendResult<CopyRecords> {
+    let to_ids = to_ids.into_iter().collect_vec();
+    let mut copy_records = CopyRecords::default();
+    for from_id in from_ids {
+        for to_id in &to_ids {
+            let records = get_copy_records(store, from_id, to_id, matcher)?;
+            copy_records.add_records(records)?;
+        }
+    }
+    Ok(copy_records)
+}
+
 /// How conflicts are processed and rendered in diffs.
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Deserialize)]
 #[serde(rename_all = "kebab-case")]
@@ -1337,6 +1372,13 @@ fn diff_content_with<T>(
     }
 }
 
+/// Returns true if the file should be diffed as binary regardless of its
+/// contents, because its `diff` attribute is unset (e.g. by the `binary`
+/// macro).
+fn is_binary_by_attributes(attributes: &GitAttributesLookup, path: &RepoPath) -> bool {
+    attributes.get(path, "diff") == AttributeValue::Unset
+}
+
 fn basic_diff_file_type(value: &MaterializedTreeValue) -> &'static str {
     match value {
         MaterializedTreeValue::Absent => {
@@ -1359,11 +1401,13 @@ fn basic_diff_file_type(value: &MaterializedTreeValue) -> &'static str {
     }
 }
 
+#[expect(clippy::too_many_arguments)]
 pub async fn show_color_words_diff(
     formatter: &mut dyn Formatter,
     store: &Store,
     tree_diff: BoxStream<'_, CopiesTreeDiffEntry>,
     conflict_labels: Diff<&ConflictLabels>,
+    attributes: &GitAttributesLookup,
     path_converter: &RepoPathUiConverter,
     options: &ColorWordsDiffOptions,
     marker_style: ConflictMarkerStyle,
@@ -1380,6 +1424,7 @@ pub async fn show_color_words_diff(
         let right_path = path.target();
         let left_ui_path = path_converter.format_file_path(left_path);
         let right_ui_path = path_converter.format_file_path(right_path);
+        let is_binary_path = is_binary_by_attributes(attributes, right_path);
         let Diff {
             before: left_value,
             after: right_value,
@@ -1413,7 +1458,7 @@ pub async fn show_color_words_diff(
             let right_content = diff_content_as_merge(right_path, right_value)?;
             if right_content.contents.is_empty() {
                 writeln!(formatter.labeled("empty"), "    (empty)")?;
-            } else if right_content.is_binary {
+            } else if right_content.is_binary || is_binary_path {
                 writeln!(formatter.labeled("binary"), "    (binary)")?;
             } else {
                 show_color_words_diff_hunks(
@@ -1484,7 +1529,7 @@ pub async fn show_color_words_diff(
                     "{description} {right_ui_path} ({left_ui_path} => {right_ui_path}):"
                 )?;
             }
-            if left_content.is_binary || right_content.is_binary {
+            if left_content.is_binary || right_content.is_binary || is_binary_path {
                 writeln!(formatter.labeled("binary"), "    (binary)")?;
             } else if left_content.contents != right_content.contents {
                 show_color_words_diff_hunks(
@@ -1510,7 +1555,7 @@ pub async fn show_color_words_diff(
             let left_content = diff_content_as_merge(left_path, left_value)?;
             if left_content.contents.is_empty() {
                 writeln!(formatter.labeled("empty"), "    (empty)")?;
-            } else if left_content.is_binary {
+            } else if left_content.is_binary || is_binary_path {
                 writeln!(formatter.labeled("binary"), "    (binary)")?;
             } else {
                 show_color_words_diff_hunks(
@@ -1703,6 +1748,7 @@ pub async fn show_git_diff(
     store: &Store,
     tree_diff: BoxStream<'_, CopiesTreeDiffEntry>,
     conflict_labels: Diff<&ConflictLabels>,
+    attributes: &GitAttributesLookup,
     options: &UnifiedDiffOptions,
     marker_style: ConflictMarkerStyle,
 ) -> Result<(), DiffRenderError> {
@@ -1775,7 +1821,10 @@ pub async fn show_git_diff(
             Some(_) => format!("b/{right_path_string}"),
             None => "/dev/null".to_owned(),
         };
-        if left_part.content.is_binary || right_part.content.is_binary {
+        if left_part.content.is_binary
+            || right_part.content.is_binary
+            || is_binary_by_attributes(attributes, path.target())
+        {
             // TODO: add option to emit Git binary diff
             writeln!(
                 formatter,
@@ -1891,6 +1940,7 @@ impl DiffStats {
     pub async fn calculate(
         store: &Store,
         tree_diff: BoxStream<'_, CopiesTreeDiffEntry>,
+        attributes: &GitAttributesLookup,
         options: &DiffStatOptions,
         marker_style: ConflictMarkerStyle,
     ) -> BackendResult<Self> {
@@ -1909,8 +1959,14 @@ impl DiffStats {
             let values = values?;
             let status =
                 diff_status_inner(&path, values.before.is_present(), values.after.is_present());
-            let left_content = diff_content(path.source(), values.before, &materialize_options)?;
-            let right_content = diff_content(path.target(), values.after, &materialize_options)?;
+            let mut left_content =
+                diff_content(path.source(), values.before, &materialize_options)?;
+            let mut right_content =
+                diff_content(path.target(), values.after, &materialize_options)?;
+            if is_binary_by_attributes(attributes, path.target()) {
+                left_content.is_binary = true;
+                right_content.is_binary = true;
+            }
             let stat = get_diff_stat_entry(
                 path,
                 status,
@@ -2029,6 +2085,30 @@ pub fn show_diff_stats(
     stats: &DiffStats,
     path_converter: &RepoPathUiConverter,
     display_width: usize,
+) -> io::Result<()> {
+    show_diff_stat_entries(formatter, stats, path_converter, display_width)?;
+    let total_added = stats.count_total_added();
+    let total_removed = stats.count_total_removed();
+    let total_files = stats.entries().len();
+    writeln!(
+        formatter.labeled("stat-summary"),
+        "{} file{} changed, {} insertion{}(+), {} deletion{}(-)",
+        total_files,
+        if total_files == 1 { "" } else { "s" },
+        total_added,
+        if total_added == 1 { "" } else { "s" },
+        total_removed,
+        if total_removed == 1 { "" } else { "s" },
+    )?;
+    Ok(())
+}
+
+/// Shows the per-file lines of the diff stats without the summary line.
+pub fn show_diff_stat_entries(
+    formatter: &mut dyn Formatter,
+    stats: &DiffStats,
+    path_converter: &RepoPathUiConverter,
+    display_width: usize,
 ) -> io::Result<()> {
     let ui_paths = stats
         .entries()
@@ -2146,20 +2226,6 @@ pub fn show_diff_stats(
             writeln!(formatter)?;
         }
     }
-
-    let total_added = stats.count_total_added();
-    let total_removed = stats.count_total_removed();
-    let total_files = stats.entries().len();
-    writeln!(
-        formatter.labeled("stat-summary"),
-        "{} file{} changed, {} insertion{}(+), {} deletion{}(-)",
-        total_files,
-        if total_files == 1 { "" } else { "s" },
-        total_added,
-        if total_added == 1 { "" } else { "s" },
-        total_removed,
-        if total_removed == 1 { "" } else { "s" },
-    )?;
     Ok(())
 }
 
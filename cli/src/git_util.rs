@@ -14,6 +14,8 @@
 
 //! Git utilities shared by various commands.
 
+use std::collections::BTreeMap;
+use std::collections::HashMap;
 use std::error;
 use std::io;
 use std::io::Write as _;
@@ -40,20 +42,31 @@ use jj_lib::git::GitRefKind;
 use jj_lib::git::GitSettings;
 use jj_lib::git::GitSidebandLineTerminator;
 use jj_lib::git::GitSubprocessCallback;
+use jj_lib::git_backend::GitBackend;
+use jj_lib::git_submodule;
+use jj_lib::lfs;
+use jj_lib::lfs::LfsPointer;
+use jj_lib::merged_tree::MergedTree;
 use jj_lib::op_store::RefTarget;
 use jj_lib::op_store::RemoteRef;
+use jj_lib::ref_name::RemoteName;
 use jj_lib::ref_name::RemoteRefSymbol;
 use jj_lib::repo::ReadonlyRepo;
 use jj_lib::repo::Repo;
+use jj_lib::repo_path::RepoPathUiConverter;
 use jj_lib::settings::RemoteSettingsMap;
+use jj_lib::submodule_store::SubmoduleConfig;
 use jj_lib::workspace::Workspace;
+use pollster::FutureExt as _;
 use unicode_width::UnicodeWidthStr as _;
 
 use crate::cleanup_guard::CleanupGuard;
 use crate::cli_util::WorkspaceCommandTransaction;
 use crate::cli_util::print_updated_commits;
+use crate::cli_util::short_commit_hash;
 use crate::command_error::CommandError;
 use crate::command_error::cli_error;
+use crate::command_error::print_error_sources;
 use crate::command_error::user_error;
 use crate::formatter::Formatter;
 use crate::formatter::FormatterExt as _;
@@ -516,14 +529,233 @@ pub fn print_git_export_stats(ui: &Ui, stats: &GitExportStats) -> Result<(), std
     Ok(())
 }
 
+/// Downloads the LFS objects referenced by the fetched remote bookmarks.
+///
+/// As `git lfs fetch` does by default, only the objects in the new bookmark
+/// targets are downloaded. Only local remotes are supported, and nothing is
+/// done for local remotes without an LFS object store.
+pub fn fetch_lfs_objects(
+    ui: &Ui,
+    repo: &dyn Repo,
+    stats: &GitImportStats,
+) -> Result<(), CommandError> {
+    let git_backend = git::get_git_backend(repo.store())?;
+    let git_repo = git_backend.git_repo();
+    let mut remotes_to_check: HashMap<&RemoteName, bool> = HashMap::new();
+    let mut pointers_by_remote: BTreeMap<&RemoteName, Vec<LfsPointer>> = BTreeMap::new();
+    for (symbol, (old_remote_ref, new_target)) in &stats.changed_remote_bookmarks {
+        let should_check = *remotes_to_check.entry(&symbol.remote).or_insert_with(|| {
+            // Non-local remotes are still checked to warn about the LFS
+            // objects which can't be fetched.
+            lfs::remote_object_store(&git_repo, &symbol.remote, gix::remote::Direction::Fetch)
+                .is_none_or(|store| store.path().is_dir())
+        });
+        if !should_check {
+            continue;
+        }
+        let old_tree = match old_remote_ref.target.as_normal() {
+            Some(id) => repo.store().get_commit(id)?.tree(),
+            None => repo.store().empty_merged_tree(),
+        };
+        for id in new_target.added_ids() {
+            let new_tree = repo.store().get_commit(id)?.tree();
+            let pointers = lfs::changed_lfs_pointers(&old_tree, &new_tree).block_on()?;
+            pointers_by_remote
+                .entry(&symbol.remote)
+                .or_default()
+                .extend(pointers);
+        }
+    }
+    for (remote, pointers) in pointers_by_remote {
+        transfer_lfs_objects(
+            ui,
+            git_backend,
+            remote,
+            gix::remote::Direction::Fetch,
+            pointers,
+        )?;
+    }
+    Ok(())
+}
+
+/// Uploads the LFS objects referenced by the `commits` to be pushed.
+///
+/// Only local remotes are supported.
+pub fn push_lfs_objects(
+    ui: &Ui,
+    repo: &dyn Repo,
+    remote: &RemoteName,
+    commits: &[Commit],
+) -> Result<(), CommandError> {
+    let git_backend = git::get_git_backend(repo.store())?;
+    let mut pointers = vec![];
+    for commit in commits {
+        let parent_tree = commit.parent_tree(repo)?;
+        pointers.extend(lfs::changed_lfs_pointers(&parent_tree, &commit.tree()).block_on()?);
+    }
+    transfer_lfs_objects(
+        ui,
+        git_backend,
+        remote,
+        gix::remote::Direction::Push,
+        pointers,
+    )
+}
+
+fn transfer_lfs_objects(
+    ui: &Ui,
+    git_backend: &GitBackend,
+    remote: &RemoteName,
+    direction: gix::remote::Direction,
+    pointers: Vec<LfsPointer>,
+) -> Result<(), CommandError> {
+    let local_store = git_backend.lfs_store();
+    let mut pointers = pointers.into_iter().unique().collect_vec();
+    if direction == gix::remote::Direction::Fetch {
+        pointers.retain(|pointer| !local_store.contains(pointer));
+    }
+    if pointers.is_empty() {
+        return Ok(());
+    }
+    let git_repo = git_backend.git_repo();
+    let Some(remote_store) = lfs::remote_object_store(&git_repo, remote, direction) else {
+        writeln!(
+            ui.warning_default(),
+            "Cannot transfer {} LFS objects for remote {}: only local remotes are supported",
+            pointers.len(),
+            remote.as_symbol()
+        )?;
+        return Ok(());
+    };
+    let (source, dest) = match direction {
+        gix::remote::Direction::Fetch => (&remote_store, &local_store),
+        gix::remote::Direction::Push => (&local_store, &remote_store),
+    };
+    let mut num_transferred = 0;
+    let mut num_missing = 0;
+    for pointer in &pointers {
+        if dest.contains(pointer) {
+            continue;
+        } else if source.contains(pointer) {
+            source.copy_to(dest, pointer)?;
+            num_transferred += 1;
+        } else {
+            num_missing += 1;
+        }
+    }
+    let remote = remote.as_symbol();
+    if num_transferred > 0 {
+        match direction {
+            gix::remote::Direction::Fetch => writeln!(
+                ui.status(),
+                "Downloaded {num_transferred} LFS objects from remote {remote}"
+            )?,
+            gix::remote::Direction::Push => writeln!(
+                ui.status(),
+                "Uploaded {num_transferred} LFS objects to remote {remote}"
+            )?,
+        }
+    }
+    if num_missing > 0 {
+        match direction {
+            gix::remote::Direction::Fetch => writeln!(
+                ui.warning_default(),
+                "{num_missing} LFS objects are missing on remote {remote}"
+            )?,
+            gix::remote::Direction::Push => writeln!(
+                ui.warning_default(),
+                "{num_missing} LFS objects are missing locally and were not uploaded to remote \
+                 {remote}"
+            )?,
+        }
+    }
+    Ok(())
+}
+
+/// Checks out the submodules whose recorded commits changed between
+/// `old_tree` and `new_tree` at the commits recorded in `new_tree`.
+///
+/// Relative submodule URLs are resolved against the URL of the "origin"
+/// remote, or the workspace root if there's no such remote.
+pub fn update_submodules(
+    ui: &Ui,
+    repo: &ReadonlyRepo,
+    workspace_root: &Path,
+    path_converter: &RepoPathUiConverter,
+    old_tree: &MergedTree,
+    new_tree: &MergedTree,
+) -> Result<(), CommandError> {
+    let changed = git_submodule::changed_submodules(old_tree, new_tree).block_on()?;
+    if changed.is_empty() {
+        return Ok(());
+    }
+    let configs = git_submodule::read_gitmodules(new_tree).block_on()?;
+    let base_url = get_remote_url(repo, "origin")
+        .unwrap_or_else(|| workspace_root.to_string_lossy().into_owned());
+    for (path, commit_id) in changed {
+        let ui_path = path_converter.format_file_path(&path);
+        let Some(config) = configs.iter().find(|config| config.path == path) else {
+            writeln!(
+                ui.warning_default(),
+                "No submodule mapping found in .gitmodules for path {ui_path}"
+            )?;
+            continue;
+        };
+        let config = SubmoduleConfig {
+            url: git_submodule::resolve_submodule_url(&base_url, &config.url),
+            ..config.clone()
+        };
+        let disk_path = path.to_fs_path_unchecked(workspace_root);
+        match repo
+            .submodule_store()
+            .check_out(&config, &disk_path, &commit_id)
+        {
+            Ok(()) => writeln!(
+                ui.status(),
+                "Checked out submodule {ui_path} at {}",
+                short_commit_hash(&commit_id)
+            )?,
+            Err(err) => {
+                writeln!(
+                    ui.warning_default(),
+                    "Failed to check out submodule {ui_path}"
+                )?;
+                print_error_sources(ui, Some(&err))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn get_remote_url(repo: &ReadonlyRepo, remote_name: &str) -> Option<String> {
+    let git_repo = git::get_git_repo(repo.store()).ok()?;
+    let remote = git_repo.try_find_remote(remote_name)?.ok()?;
+    let url = remote.url(gix::remote::Direction::Fetch)?;
+    if url.scheme == gix::url::Scheme::File {
+        Some(
+            gix::path::from_bstr(url.path.as_bstr())
+                .to_string_lossy()
+                .into_owned(),
+        )
+    } else {
+        Some(url.to_bstring().to_string())
+    }
+}
+
 pub fn print_push_stats(ui: &Ui, stats: &GitPushStats) -> io::Result<()> {
-    if !stats.rejected.is_empty() {
+    // With `--atomic`, the references that could have been pushed are rejected
+    // because of the other failed references.
+    let (atomic_rejected, rejected): (Vec<_>, Vec<_>) = stats
+        .rejected
+        .iter()
+        .partition(|(_, reason)| reason.as_deref() == Some("atomic push failed"));
+    if !rejected.is_empty() {
         writeln!(
             ui.warning_default(),
             "The following references unexpectedly moved on the remote:"
         )?;
         let mut formatter = ui.stderr_formatter();
-        for (reference, reason) in &stats.rejected {
+        for (reference, reason) in rejected {
             write!(formatter, "  ")?;
             write!(formatter.labeled("git_ref"), "{}", reference.as_symbol())?;
             if let Some(r) = reason {
@@ -538,6 +770,17 @@ pub fn print_push_stats(ui: &Ui, stats: &GitPushStats) -> io::Result<()> {
              be, and push again.",
         )?;
     }
+    if !atomic_rejected.is_empty() {
+        writeln!(
+            ui.warning_default(),
+            "The following references were not pushed because the atomic push failed:"
+        )?;
+        let mut formatter = ui.stderr_formatter();
+        for (reference, _) in atomic_rejected {
+            write!(formatter, "  ")?;
+            writeln!(formatter.labeled("git_ref"), "{}", reference.as_symbol())?;
+        }
+    }
     if !stats.remote_rejected.is_empty() {
         writeln!(
             ui.warning_default(),
@@ -0,0 +1,211 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Client-side hooks configured in the `[hooks]` table.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write as _;
+use std::path::Path;
+use std::process::Stdio;
+use std::thread;
+
+use itertools::Itertools as _;
+use jj_lib::commit::Commit;
+use jj_lib::config::ConfigGetResultExt as _;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::settings::UserSettings;
+
+use crate::command_error::CommandError;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_message;
+use crate::config::CommandNameAndArgs;
+use crate::ui::Ui;
+
+/// Point at which a hook runs.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum HookKind {
+    /// Before the working-copy commit is committed by `jj commit`.
+    PreCommit,
+    /// After a description is edited. The hook can reject or rewrite the
+    /// description.
+    CommitMsg,
+    /// Before commits are pushed to a remote.
+    PrePush,
+}
+
+impl HookKind {
+    /// Name of the hook in the `[hooks]` table.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::PreCommit => "pre-commit",
+            Self::CommitMsg => "commit-msg",
+            Self::PrePush => "pre-push",
+        }
+    }
+}
+
+fn load_hook(
+    settings: &UserSettings,
+    kind: HookKind,
+) -> Result<Option<CommandNameAndArgs>, CommandError> {
+    let hook = settings
+        .get::<CommandNameAndArgs>(["hooks", kind.name()])
+        .optional()?;
+    Ok(hook)
+}
+
+/// Runs the hook in the `workspace_root`. Fails if the hook exits with a
+/// non-zero status.
+fn run_hook(
+    ui: &Ui,
+    workspace_root: &Path,
+    kind: HookKind,
+    hook: &CommandNameAndArgs,
+    variables: &HashMap<&str, &str>,
+    stdin: &[u8],
+) -> Result<(), CommandError> {
+    let mut cmd = hook.to_command_with_variables(variables);
+    cmd.current_dir(workspace_root)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(ui.stderr_for_child()?);
+    tracing::info!(?cmd, "running {} hook", kind.name());
+    let mut child = cmd.spawn().map_err(|err| {
+        user_error_with_message(
+            format!("Failed to run the {} hook `{hook}`", kind.name()),
+            err,
+        )
+    })?;
+    let mut child_stdin = child.stdin.take().unwrap();
+    let output = thread::scope(|s| {
+        // The hook may exit without reading its input.
+        s.spawn(move || child_stdin.write_all(stdin).ok());
+        child.wait_with_output()
+    })?;
+    // Hooks report to the user, so their output shouldn't be mixed with the
+    // command output.
+    ui.stderr().write_all(&output.stdout)?;
+    let status = output.status;
+    if !status.success() {
+        return Err(
+            user_error(format!("The {} hook failed ({status})", kind.name()))
+                .hinted(format!("The hook command was `{hook}`")),
+        );
+    }
+    Ok(())
+}
+
+/// Runs the `pre-commit` hook before the working-copy `commit` is committed.
+///
+/// The hook can inspect the files in the working copy. Its arguments can
+/// refer to `$commit_id` and `$change_id`.
+pub fn run_pre_commit_hook(
+    ui: &Ui,
+    settings: &UserSettings,
+    workspace_root: &Path,
+    commit: &Commit,
+) -> Result<(), CommandError> {
+    let Some(hook) = load_hook(settings, HookKind::PreCommit)? else {
+        return Ok(());
+    };
+    let commit_id = commit.id().hex();
+    let change_id = commit.change_id().reverse_hex();
+    let variables = HashMap::from([
+        ("commit_id", commit_id.as_str()),
+        ("change_id", change_id.as_str()),
+    ]);
+    run_hook(
+        ui,
+        workspace_root,
+        HookKind::PreCommit,
+        &hook,
+        &variables,
+        &[],
+    )
+}
+
+/// Runs the `commit-msg` hook on the edited `description`, and returns the
+/// possibly rewritten description.
+///
+/// The description is written to a file which can be referred to as
+/// `$message_file`, and read back after the hook exits. Empty descriptions
+/// aren't passed to the hook.
+pub fn run_commit_msg_hook(
+    ui: &Ui,
+    settings: &UserSettings,
+    workspace_root: &Path,
+    description: String,
+) -> Result<String, CommandError> {
+    if description.is_empty() {
+        return Ok(description);
+    }
+    let Some(hook) = load_hook(settings, HookKind::CommitMsg)? else {
+        return Ok(description);
+    };
+    let mut file = tempfile::Builder::new()
+        .prefix("jj-commit-msg-")
+        .tempfile()?;
+    file.write_all(description.as_bytes())?;
+    file.flush()?;
+    let path = file
+        .path()
+        .to_str()
+        .expect("temp_dir should be valid utf-8");
+    let variables = HashMap::from([("message_file", path)]);
+    run_hook(
+        ui,
+        workspace_root,
+        HookKind::CommitMsg,
+        &hook,
+        &variables,
+        &[],
+    )?;
+    let new_description = fs::read_to_string(file.path()).map_err(|err| {
+        user_error_with_message(
+            "Failed to read the message rewritten by the commit-msg hook",
+            err,
+        )
+    })?;
+    Ok(new_description)
+}
+
+/// Runs the `pre-push` hook before the `commits` are pushed to the `remote`.
+///
+/// The full ids of the commits are written to the hook's stdin, one per line.
+/// Its arguments can refer to `$remote`.
+pub fn run_pre_push_hook(
+    ui: &Ui,
+    settings: &UserSettings,
+    workspace_root: &Path,
+    remote: &str,
+    commits: &[Commit],
+) -> Result<(), CommandError> {
+    let Some(hook) = load_hook(settings, HookKind::PrePush)? else {
+        return Ok(());
+    };
+    let stdin = commits
+        .iter()
+        .map(|commit| format!("{}\n", commit.id().hex()))
+        .join("");
+    let variables = HashMap::from([("remote", remote)]);
+    run_hook(
+        ui,
+        workspace_root,
+        HookKind::PrePush,
+        &hook,
+        &variables,
+        stdin.as_bytes(),
+    )
+}
@@ -1,4 +1,5 @@
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::path::Path;
 use std::sync::Arc;
 
@@ -16,6 +17,8 @@ use jj_lib::conflicts::MaterializedTreeValue;
 use jj_lib::conflicts::materialize_merge_result_to_bytes;
 use jj_lib::conflicts::materialized_diff_stream;
 use jj_lib::copies::CopiesTreeDiffEntry;
+use jj_lib::copies::CopiesTreeDiffEntryPath;
+use jj_lib::copies::CopyOperation;
 use jj_lib::copies::CopyRecords;
 use jj_lib::diff::ContentDiff;
 use jj_lib::diff::DiffHunkKind;
@@ -275,7 +278,7 @@ async fn make_diff_files(
     trees: Diff<&MergedTree>,
     tree_diff: BoxStream<'_, CopiesTreeDiffEntry>,
     marker_style: ConflictMarkerStyle,
-) -> Result<(Vec<RepoPathBuf>, Vec<scm_record::File<'static>>), BuiltinToolError> {
+) -> Result<(Vec<CopiesTreeDiffEntryPath>, Vec<scm_record::File<'static>>), BuiltinToolError> {
     let materialize_options = ConflictMaterializeOptions {
         marker_style,
         marker_len: None,
@@ -292,8 +295,16 @@ async fn make_diff_files(
         let left_info = read_file_contents(values.before, left_path, &materialize_options)?;
         let right_info = read_file_contents(values.after, right_path, &materialize_options)?;
         let mut sections = Vec::new();
+        // A copied file doesn't exist at the target path, so creating it is
+        // shown as a file mode change. Its contents are compared with the
+        // source.
+        let left_file_mode = if entry.path.copy_operation().is_some() {
+            mode::ABSENT
+        } else {
+            left_info.file_mode
+        };
 
-        if left_info.file_mode != right_info.file_mode {
+        if left_file_mode != right_info.file_mode {
             sections.push(scm_record::Section::FileMode {
                 is_checked: false,
                 mode: right_info.file_mode,
@@ -382,13 +393,16 @@ async fn make_diff_files(
         }
 
         files.push(scm_record::File {
-            old_path: None,
-            // Path for displaying purposes, not for file access.
+            // Paths for displaying purposes, not for file access.
+            old_path: entry
+                .path
+                .to_diff()
+                .map(|paths| Cow::Owned(paths.before.to_fs_path_unchecked(Path::new("")))),
             path: Cow::Owned(right_path.to_fs_path_unchecked(Path::new(""))),
-            file_mode: left_info.file_mode,
+            file_mode: left_file_mode,
             sections,
         });
-        changed_files.push(entry.path.target);
+        changed_files.push(entry.path);
     }
     Ok((changed_files, files))
 }
@@ -406,7 +420,7 @@ fn apply_diff_builtin(
     store: &Arc<Store>,
     left_tree: &MergedTree,
     right_tree: &MergedTree,
-    changed_files: Vec<RepoPathBuf>,
+    changed_files: Vec<CopiesTreeDiffEntryPath>,
     files: &[scm_record::File],
 ) -> BackendResult<MergedTree> {
     // Start with the right tree to match external tool behavior.
@@ -415,19 +429,44 @@ fn apply_diff_builtin(
 
     // First, revert all changed files to their left versions
     for path in &changed_files {
-        let left_value = left_tree.path_value(path)?;
-        tree_builder.set_or_remove(path.clone(), left_value);
+        let left_value = left_tree.path_value(path.target())?;
+        tree_builder.set_or_remove(path.target.clone(), left_value);
+        // The source of a rename is kept unless the rename is selected.
+        if let Some((source, CopyOperation::Rename)) = &path.source {
+            tree_builder.set_or_remove(source.clone(), left_tree.path_value(source)?);
+        }
     }
 
+    // Copied files are based off their sources, but keep the copy ids of the
+    // targets.
+    let copy_sources: HashMap<&RepoPath, &RepoPath> = changed_files
+        .iter()
+        .filter_map(|path| Some((path.target(), path.to_diff()?.before)))
+        .collect();
+    let select_left = |path: &RepoPath| -> BackendResult<MergedTreeValue> {
+        let Some(&source) = copy_sources.get(path) else {
+            return left_tree.path_value(path);
+        };
+        let copy_id =
+            resolve_file_copy_id(&right_tree.path_value(path)?).unwrap_or_else(CopyId::placeholder);
+        Ok(override_file_copy_id(
+            left_tree.path_value(source)?,
+            &copy_id,
+        ))
+    };
+
     // Then apply only the selected changes
     apply_changes(
         &mut tree_builder,
-        changed_files,
+        changed_files
+            .iter()
+            .map(|path| path.target.clone())
+            .collect(),
         files,
-        |path| left_tree.path_value(path),
+        select_left,
         |path| right_tree.path_value(path),
         |path, contents, executable| {
-            let old_value = left_tree.path_value(path)?;
+            let old_value = select_left(path)?;
             let copy_id = resolve_file_copy_id(&old_value).unwrap_or_else(CopyId::placeholder);
             let new_value = if old_value.is_resolved() {
                 let id = store.write_file(path, &mut &contents[..]).block_on()?;
@@ -460,6 +499,15 @@ fn apply_diff_builtin(
             Ok(new_value)
         },
     )?;
+
+    // Finally, remove the sources of the selected renames
+    for (path, file) in changed_files.iter().zip(files) {
+        if let Some((source, CopyOperation::Rename)) = &path.source
+            && is_file_mode_change_selected(file)
+        {
+            tree_builder.set_or_remove(source.clone(), Merge::absent());
+        }
+    }
     tree_builder.write_tree().block_on()
 }
 
@@ -478,14 +526,7 @@ fn apply_changes(
     );
     // TODO: Write files concurrently
     for (path, file) in changed_files.into_iter().zip(files) {
-        let file_mode_change_selected = file
-            .sections
-            .iter()
-            .find_map(|sec| match sec {
-                scm_record::Section::FileMode { is_checked, .. } => Some(*is_checked),
-                _ => None,
-            })
-            .unwrap_or(false);
+        let file_mode_change_selected = is_file_mode_change_selected(file);
 
         let (
             scm_record::SelectedChanges {
@@ -543,6 +584,28 @@ fn apply_changes(
     Ok(())
 }
 
+fn is_file_mode_change_selected(file: &scm_record::File) -> bool {
+    file.sections
+        .iter()
+        .find_map(|sec| match sec {
+            scm_record::Section::FileMode { is_checked, .. } => Some(*is_checked),
+            _ => None,
+        })
+        .unwrap_or(false)
+}
+
+fn override_file_copy_id(
+    mut merged_tree_value: MergedTreeValue,
+    new_copy_id: &CopyId,
+) -> MergedTreeValue {
+    for tree_value in merged_tree_value.iter_mut().flatten() {
+        if let TreeValue::File { copy_id, .. } = tree_value {
+            *copy_id = new_copy_id.clone();
+        }
+    }
+    merged_tree_value
+}
+
 fn override_file_executable_bit(
     mut merged_tree_value: MergedTreeValue,
     new_executable_bit: bool,
@@ -559,14 +622,13 @@ fn override_file_executable_bit(
 pub fn edit_diff_builtin(
     trees: Diff<&MergedTree>,
     matcher: &dyn Matcher,
+    copy_records: &CopyRecords,
     conflict_marker_style: ConflictMarkerStyle,
 ) -> Result<MergedTree, BuiltinToolError> {
     let store = trees.before.store().clone();
-    // TODO: handle copy tracking
-    let copy_records = CopyRecords::default();
     let tree_diff = trees
         .before
-        .diff_stream_with_copies(trees.after, matcher, &copy_records);
+        .diff_stream_with_copies(trees.after, matcher, copy_records);
     let (changed_files, files) =
         make_diff_files(&store, trees, tree_diff, conflict_marker_style).block_on()?;
     let mut input = scm_record::helpers::CrosstermInput;
@@ -762,6 +824,7 @@ fn apply_merge_builtin(
 mod tests {
     use std::collections::BTreeSet;
 
+    use jj_lib::backend::CopyRecord;
     use jj_lib::backend::FileId;
     use jj_lib::conflict_labels::ConflictLabels;
     use jj_lib::conflicts::extract_as_single_hunk;
@@ -797,8 +860,31 @@ mod tests {
         right_tree: &MergedTree,
         matcher: &dyn Matcher,
     ) -> (Vec<RepoPathBuf>, Vec<scm_record::File<'static>>) {
-        let copy_records = CopyRecords::default();
-        let tree_diff = left_tree.diff_stream_with_copies(right_tree, matcher, &copy_records);
+        let (changed_files, files) = make_diff_with_copies(
+            store,
+            left_tree,
+            right_tree,
+            matcher,
+            &CopyRecords::default(),
+        );
+        let changed_files = changed_files
+            .into_iter()
+            .map(|path| {
+                assert_eq!(path.copy_operation(), None);
+                path.target
+            })
+            .collect();
+        (changed_files, files)
+    }
+
+    fn make_diff_with_copies(
+        store: &Arc<Store>,
+        left_tree: &MergedTree,
+        right_tree: &MergedTree,
+        matcher: &dyn Matcher,
+        copy_records: &CopyRecords,
+    ) -> (Vec<CopiesTreeDiffEntryPath>, Vec<scm_record::File<'static>>) {
+        let tree_diff = left_tree.diff_stream_with_copies(right_tree, matcher, copy_records);
         make_diff_files(
             store,
             Diff::new(left_tree, right_tree),
@@ -816,7 +902,14 @@ mod tests {
         changed_files: &[RepoPathBuf],
         files: &[scm_record::File],
     ) -> MergedTree {
-        apply_diff_builtin(store, left_tree, right_tree, changed_files.to_vec(), files).unwrap()
+        let changed_files = changed_files
+            .iter()
+            .map(|path| CopiesTreeDiffEntryPath {
+                source: None,
+                target: path.clone(),
+            })
+            .collect();
+        apply_diff_builtin(store, left_tree, right_tree, changed_files, files).unwrap()
     }
 
     #[test]
@@ -1267,6 +1360,127 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_edit_diff_builtin_rename() {
+        let test_repo = TestRepo::init();
+        let store = test_repo.repo.store();
+
+        let source_path = repo_path("source");
+        let target_path = repo_path("target");
+        let left_tree = testutils::create_tree(&test_repo.repo, &[(source_path, "a\nb\n")]);
+        let right_tree = testutils::create_tree(&test_repo.repo, &[(target_path, "a\nB\n")]);
+        let Some(TreeValue::File {
+            id: source_file, ..
+        }) = left_tree
+            .path_value(source_path)
+            .unwrap()
+            .into_resolved()
+            .unwrap()
+        else {
+            panic!("the source should be a file");
+        };
+        let commit_id = store.root_commit_id().clone();
+        let mut copy_records = CopyRecords::default();
+        copy_records
+            .add_records([Ok(CopyRecord {
+                target: target_path.to_owned(),
+                target_commit: commit_id.clone(),
+                source: source_path.to_owned(),
+                source_file,
+                source_commit: commit_id,
+            })])
+            .unwrap();
+
+        let (changed_files, files) = make_diff_with_copies(
+            store,
+            &left_tree,
+            &right_tree,
+            &EverythingMatcher,
+            &copy_records,
+        );
+        insta::assert_debug_snapshot!(files, @r#"
+        [
+            File {
+                old_path: Some(
+                    "source",
+                ),
+                path: "target",
+                file_mode: Absent,
+                sections: [
+                    FileMode {
+                        is_checked: false,
+                        mode: Unix(
+                            33188,
+                        ),
+                    },
+                    Unchanged {
+                        lines: [
+                            "a\n",
+                        ],
+                    },
+                    Changed {
+                        lines: [
+                            SectionChangedLine {
+                                is_checked: false,
+                                change_type: Removed,
+                                line: "b\n",
+                            },
+                            SectionChangedLine {
+                                is_checked: false,
+                                change_type: Added,
+                                line: "B\n",
+                            },
+                        ],
+                    },
+                ],
+            },
+        ]
+        "#);
+        let no_changes_tree = apply_diff_builtin(
+            store,
+            &left_tree,
+            &right_tree,
+            changed_files.clone(),
+            &files,
+        )
+        .unwrap();
+        assert_tree_eq!(left_tree, no_changes_tree, "no-changes tree was different");
+
+        // Selecting only the rename moves the original contents
+        let mut rename_only_files = files.clone();
+        for section in &mut rename_only_files[0].sections {
+            if matches!(section, scm_record::Section::FileMode { .. }) {
+                section.toggle_all();
+            }
+        }
+        let rename_only_tree = apply_diff_builtin(
+            store,
+            &left_tree,
+            &right_tree,
+            changed_files.clone(),
+            &rename_only_files,
+        )
+        .unwrap();
+        let expected_tree = testutils::create_tree(&test_repo.repo, &[(target_path, "a\nb\n")]);
+        assert_tree_eq!(
+            expected_tree,
+            rename_only_tree,
+            "rename-only tree was different",
+        );
+
+        let mut files = files;
+        for file in &mut files {
+            file.toggle_all();
+        }
+        let all_changes_tree =
+            apply_diff_builtin(store, &left_tree, &right_tree, changed_files, &files).unwrap();
+        assert_tree_eq!(
+            right_tree,
+            all_changes_tree,
+            "all-changes tree was different",
+        );
+    }
+
     #[test]
     fn test_edit_diff_builtin_add_executable_file() {
         let test_repo = TestRepo::init();
@@ -2041,8 +2255,7 @@ mod tests {
 
         assert_eq!(changed_files, vec![matched_path.to_owned()]);
 
-        let result_tree =
-            apply_diff_builtin(store, &left_tree, &right_tree, changed_files, &files).unwrap();
+        let result_tree = apply_diff(store, &left_tree, &right_tree, &changed_files, &files);
 
         assert_eq!(
             result_tree.path_value(matched_path).unwrap(),
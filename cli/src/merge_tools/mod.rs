@@ -15,12 +15,15 @@
 mod builtin;
 mod diff_working_copies;
 mod external;
+mod text_editor;
 
 use std::sync::Arc;
 
 use itertools::Itertools as _;
 use jj_lib::backend::BackendError;
+use jj_lib::backend::BackendResult;
 use jj_lib::backend::CopyId;
+use jj_lib::backend::FileId;
 use jj_lib::backend::TreeValue;
 use jj_lib::config::ConfigGetError;
 use jj_lib::config::ConfigGetResultExt as _;
@@ -28,6 +31,7 @@ use jj_lib::config::ConfigNamePathBuf;
 use jj_lib::conflicts::ConflictMarkerStyle;
 use jj_lib::conflicts::MaterializedFileConflictValue;
 use jj_lib::conflicts::try_materialize_file_conflict_value;
+use jj_lib::copies::CopyRecords;
 use jj_lib::gitignore::GitIgnoreFile;
 use jj_lib::matchers::Matcher;
 use jj_lib::merge::Diff;
@@ -55,7 +59,10 @@ use self::external::ExternalToolError;
 use self::external::edit_diff_external;
 pub use self::external::generate_diff;
 pub use self::external::invoke_external_diff;
+use self::text_editor::run_mergetool_text_editor;
 use crate::config::CommandNameAndArgs;
+use crate::description_util::TempTextEditError;
+use crate::description_util::TextEditor;
 use crate::ui::Ui;
 
 const BUILTIN_EDITOR_NAME: &str = ":builtin";
@@ -112,6 +119,8 @@ pub enum ConflictResolveError {
     )]
     EmptyOrUnchanged,
     #[error(transparent)]
+    TextEdit(#[from] TempTextEditError),
+    #[error(transparent)]
     Backend(#[from] jj_lib::backend::BackendError),
     #[error(transparent)]
     Io(#[from] std::io::Error),
@@ -141,6 +150,8 @@ pub enum MergeTool {
     Theirs,
     // Boxed because ExternalMergeTool is big compared to the Builtin variant.
     External(Box<ExternalMergeTool>),
+    /// Edits conflict markers in the text editor.
+    TextEditor(Box<TextEditor>),
 }
 
 impl MergeTool {
@@ -293,16 +304,22 @@ impl DiffEditor {
     }
 
     /// Starts a diff editor on the two directories.
+    ///
+    /// The `copy_records` are loaded to show copied and renamed files if the
+    /// editor supports them.
     pub fn edit(
         &self,
         trees: Diff<&MergedTree>,
         matcher: &dyn Matcher,
+        copy_records: impl FnOnce() -> BackendResult<CopyRecords>,
         format_instructions: impl FnOnce() -> String,
     ) -> Result<MergedTree, DiffEditError> {
         match &self.tool {
             DiffEditTool::Builtin => {
+                let copy_records =
+                    copy_records().map_err(|err| Box::new(BuiltinToolError::from(err)))?;
                 Ok(
-                    edit_diff_builtin(trees, matcher, self.conflict_marker_style)
+                    edit_diff_builtin(trees, matcher, &copy_records, self.conflict_marker_style)
                         .map_err(Box::new)?,
                 )
             }
@@ -345,13 +362,6 @@ impl MergeToolFile {
                     path: repo_path.to_owned(),
                     summary: conflict.describe(tree.labels()),
                 })?;
-        // We only support conflicts with 2 sides (3-way conflicts)
-        if file.ids.num_sides() > 2 {
-            return Err(ConflictResolveError::ConflictTooComplicated {
-                path: repo_path.to_owned(),
-                sides: file.ids.num_sides(),
-            });
-        }
         if file.executable.is_none() {
             return Err(ConflictResolveError::ExecutableConflict {
                 path: repo_path.to_owned(),
@@ -364,6 +374,23 @@ impl MergeToolFile {
             file,
         })
     }
+
+    /// Returns the tree value with the file contents replaced by
+    /// `new_file_ids`.
+    fn new_tree_value(&self, new_file_ids: &Merge<Option<FileId>>) -> MergedTreeValue {
+        match new_file_ids.as_resolved() {
+            Some(file_id) => {
+                let executable = self.file.executable.expect("should have been resolved");
+                Merge::resolved(file_id.clone().map(|id| TreeValue::File {
+                    id,
+                    executable,
+                    copy_id: CopyId::placeholder(),
+                }))
+            }
+            // Update the file ids only, leaving the executable flags unchanged
+            None => self.conflict.with_new_file_ids(new_file_ids),
+        }
+    }
 }
 
 /// Configured 3-way merge editor.
@@ -405,6 +432,20 @@ impl MergeEditor {
         Self::new_inner(&args, tool, path_converter, conflict_marker_style)
     }
 
+    /// Creates a merge editor that lets the user edit conflict markers in the
+    /// text editor.
+    pub fn with_text_editor(
+        editor: TextEditor,
+        path_converter: RepoPathUiConverter,
+        conflict_marker_style: ConflictMarkerStyle,
+    ) -> Self {
+        Self {
+            tool: MergeTool::TextEditor(Box::new(editor)),
+            path_converter,
+            conflict_marker_style,
+        }
+    }
+
     fn new_inner(
         name: impl ToString,
         tool: MergeTool,
@@ -436,6 +477,18 @@ impl MergeEditor {
             .iter()
             .map(|&repo_path| MergeToolFile::from_tree_and_path(tree, repo_path))
             .try_collect()?;
+        // Only the text editor supports conflicts with more than 2 sides
+        // (3-way conflicts)
+        if !matches!(self.tool, MergeTool::TextEditor(_))
+            && let Some(merge_tool_file) = merge_tool_files
+                .iter()
+                .find(|merge_tool_file| merge_tool_file.file.ids.num_sides() > 2)
+        {
+            return Err(ConflictResolveError::ConflictTooComplicated {
+                path: merge_tool_file.repo_path.clone(),
+                sides: merge_tool_file.file.ids.num_sides(),
+            });
+        }
 
         match &self.tool {
             MergeTool::Builtin => {
@@ -458,6 +511,14 @@ impl MergeEditor {
                 &merge_tool_files,
                 self.conflict_marker_style,
             ),
+            MergeTool::TextEditor(editor) => run_mergetool_text_editor(
+                ui,
+                &self.path_converter,
+                editor,
+                tree,
+                &merge_tool_files,
+                self.conflict_marker_style,
+            ),
         }
     }
 }
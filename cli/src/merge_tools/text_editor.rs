@@ -0,0 +1,119 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+
+use jj_lib::conflicts;
+use jj_lib::conflicts::ConflictMarkerStyle;
+use jj_lib::conflicts::ConflictMaterializeOptions;
+use jj_lib::conflicts::choose_materialized_conflict_marker_len;
+use jj_lib::conflicts::materialize_merge_result_to_bytes;
+use jj_lib::merged_tree::MergedTree;
+use jj_lib::merged_tree_builder::MergedTreeBuilder;
+use jj_lib::repo_path::RepoPathUiConverter;
+use jj_lib::store::Store;
+use pollster::FutureExt as _;
+
+use super::ConflictResolveError;
+use super::MergeToolFile;
+use super::MergeToolPartialResolutionError;
+use crate::description_util::TextEditor;
+use crate::ui::Ui;
+
+/// Opens the materialized conflict in the text editor, and parses the
+/// conflict markers back.
+///
+/// Unlike external merge tools, this works for conflicts with any number of
+/// sides.
+fn edit_merge_file_in_text_editor(
+    editor: &TextEditor,
+    store: &Store,
+    merge_tool_file: &MergeToolFile,
+    conflict_marker_style: ConflictMarkerStyle,
+    tree_builder: &mut MergedTreeBuilder,
+) -> Result<(), ConflictResolveError> {
+    let MergeToolFile {
+        repo_path, file, ..
+    } = merge_tool_file;
+    let conflict_marker_len = choose_materialized_conflict_marker_len(&file.contents);
+    let options = ConflictMaterializeOptions {
+        marker_style: conflict_marker_style,
+        marker_len: Some(conflict_marker_len),
+        merge: store.merge_options().clone(),
+    };
+    let initial_content = materialize_merge_result_to_bytes(&file.contents, &file.labels, &options);
+    // Keep the file name so that the editor can pick the right syntax.
+    let suffix = repo_path
+        .components()
+        .next_back()
+        .map(|name| format!("_{}", name.as_internal_str()));
+    let content = editor
+        .edit_str(&initial_content, suffix.as_deref())
+        .map_err(|err| err.with_name("conflicts"))?;
+    if content.as_bytes() == initial_content.as_slice() {
+        return Err(ConflictResolveError::EmptyOrUnchanged);
+    }
+
+    let new_file_ids = conflicts::update_from_content(
+        &file.unsimplified_ids,
+        store,
+        repo_path,
+        content.as_bytes(),
+        conflict_marker_len,
+    )
+    .block_on()?;
+    tree_builder.set_or_remove(
+        repo_path.clone(),
+        merge_tool_file.new_tree_value(&new_file_ids),
+    );
+    Ok(())
+}
+
+pub fn run_mergetool_text_editor(
+    ui: &Ui,
+    path_converter: &RepoPathUiConverter,
+    editor: &TextEditor,
+    tree: &MergedTree,
+    merge_tool_files: &[MergeToolFile],
+    conflict_marker_style: ConflictMarkerStyle,
+) -> Result<(MergedTree, Option<MergeToolPartialResolutionError>), ConflictResolveError> {
+    let mut tree_builder = MergedTreeBuilder::new(tree.clone());
+    let mut partial_resolution_error = None;
+    for (i, merge_tool_file) in merge_tool_files.iter().enumerate() {
+        writeln!(
+            ui.status(),
+            "Resolving conflicts in: {}",
+            path_converter.format_file_path(&merge_tool_file.repo_path)
+        )?;
+        match edit_merge_file_in_text_editor(
+            editor,
+            tree.store(),
+            merge_tool_file,
+            conflict_marker_style,
+            &mut tree_builder,
+        ) {
+            Ok(()) => {}
+            Err(err) if i == 0 => return Err(err),
+            Err(err) => {
+                partial_resolution_error = Some(MergeToolPartialResolutionError {
+                    source: err,
+                    resolved_count: i,
+                });
+                break;
+            }
+        }
+    }
+    let new_tree = tree_builder.write_tree().block_on()?;
+    Ok((new_tree, partial_resolution_error))
+}
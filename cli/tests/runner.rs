@@ -32,20 +32,26 @@ mod test_duplicate_command;
 mod test_edit_command;
 mod test_evolog_command;
 mod test_file_annotate_command;
+mod test_file_archive_command;
 mod test_file_chmod_command;
+mod test_file_cp_mv_commands;
 mod test_file_list_command;
 mod test_file_search_command;
 mod test_file_show_command;
 mod test_file_track_untrack_commands;
 mod test_fix_command;
+mod test_fsmonitor;
 mod test_generate_md_cli_help;
 mod test_gerrit_upload;
+mod test_git_am;
 mod test_git_clone;
 mod test_git_colocated;
 mod test_git_colocation;
 mod test_git_fetch;
+mod test_git_format_patch;
 mod test_git_import_export;
 mod test_git_init;
+mod test_git_lfs;
 mod test_git_private_commits;
 mod test_git_push;
 mod test_git_remotes;
@@ -53,6 +59,7 @@ mod test_git_root;
 mod test_gitignores;
 mod test_global_opts;
 mod test_help_command;
+mod test_hooks;
 mod test_identical_commits;
 mod test_immutable_commits;
 mod test_interdiff_command;
@@ -71,6 +78,7 @@ mod test_restore_command;
 mod test_revert_command;
 mod test_revset_output;
 mod test_root;
+mod test_run_command;
 mod test_show_command;
 mod test_sign_unsign_commands;
 mod test_simplify_parents_command;
@@ -561,6 +561,173 @@ fn test_bisect_run_jj_command() {
     ");
 }
 
+#[test]
+fn test_bisect_manual() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    create_commit(&work_dir, "a", &[]);
+    create_commit(&work_dir, "b", &["a"]);
+    create_commit(&work_dir, "c", &["b"]);
+    create_commit(&work_dir, "d", &["c"]);
+    create_commit(&work_dir, "e", &["d"]);
+    create_commit(&work_dir, "f", &["e"]);
+
+    // Marking without a session fails
+    let output = work_dir.run_jj(["bisect", "good"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Error: No bisection in progress
+    Hint: Use `jj bisect start` to start one.
+    [EOF]
+    [exit status: 1]
+    ");
+
+    let output = work_dir.run_jj(["bisect", "start", "--range=a..f"]);
+    insta::assert_snapshot!(output, @"
+    Now evaluating: royxmykx dffaa0d4 c | c
+    4 revisions left to test (roughly 3 steps)
+    Mark it with `jj bisect good`, `jj bisect bad`, or `jj bisect skip`.
+    [EOF]
+    ------- stderr -------
+    Working copy  (@) now at: kxryzmor 076636e4 (empty) (no description set)
+    Parent commit (@-)      : royxmykx dffaa0d4 c | c
+    Added 0 files, modified 0 files, removed 3 files
+    [EOF]
+    ");
+
+    // Starting another session fails
+    let output = work_dir.run_jj(["bisect", "start", "--range=a..f"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Error: A bisection is already in progress
+    Hint: Use `jj bisect reset` to end it first.
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // The state survives between invocations
+    let output = work_dir.run_jj(["bisect", "good"]);
+    insta::assert_snapshot!(output, @"
+    Now evaluating: vruxwmqv 86be7a22 d | d
+    2 revisions left to test (roughly 2 steps)
+    Mark it with `jj bisect good`, `jj bisect bad`, or `jj bisect skip`.
+    [EOF]
+    ------- stderr -------
+    Working copy  (@) now at: uyznsvlq d88b4d7a (empty) (no description set)
+    Parent commit (@-)      : vruxwmqv 86be7a22 d | d
+    Added 1 files, modified 0 files, removed 0 files
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["bisect", "skip"]);
+    insta::assert_snapshot!(output, @"
+    Now evaluating: znkkpsqq 62d30ded e | e
+    1 revision left to test (roughly 1 step)
+    Mark it with `jj bisect good`, `jj bisect bad`, or `jj bisect skip`.
+    [EOF]
+    ------- stderr -------
+    Working copy  (@) now at: xznxytkn 8ee5879b (empty) (no description set)
+    Parent commit (@-)      : znkkpsqq 62d30ded e | e
+    Added 1 files, modified 0 files, removed 0 files
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["bisect", "bad"]);
+    insta::assert_snapshot!(output, @"
+    The first bad revision is: znkkpsqq 62d30ded e | e
+    Search complete. Run `jj bisect reset` to end the bisection (or `jj op restore 9152b6b19cce` to also discard any revisions created during search).
+    [EOF]
+    ");
+
+    // Goes back to the original working copy
+    let output = work_dir.run_jj(["bisect", "reset"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Working copy  (@) now at: kmkuslsw 8b67af28 f | f
+    Parent commit (@-)      : znkkpsqq 62d30ded e | e
+    Added 1 files, modified 0 files, removed 0 files
+    Bisection reset. To discard any revisions created during search, run:
+      jj op restore 9152b6b19cce
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_output(&work_dir), @"
+    @  kmkuslswpqwq 8b67af288466 'f' files: f
+    ○  znkkpsqqskkl 62d30ded0e8f 'e' files: e
+    ○  vruxwmqvtpmx 86be7a223919 'd' files: d
+    ○  royxmykxtrkr dffaa0d4dacc 'c' files: c
+    ○  zsuskulnrvyr 123b4d91f6e5 'b' files: b
+    ○  rlvkpnrzqnoo 7d980be7a1d4 'a' files: a
+    ◆  zzzzzzzzzzzz 000000000000 '' files:
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["bisect", "reset"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Error: No bisection in progress
+    Hint: Use `jj bisect start` to start one.
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
+#[test]
+fn test_bisect_manual_explicit_revisions() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    create_commit(&work_dir, "a", &[]);
+    create_commit(&work_dir, "b", &["a"]);
+    create_commit(&work_dir, "c", &["b"]);
+    create_commit(&work_dir, "d", &["c"]);
+    work_dir.run_jj(["new", "root()"]).success();
+
+    work_dir
+        .run_jj(["bisect", "start", "--range=..d"])
+        .success();
+    // The heads of the range can't be good
+    let output = work_dir.run_jj(["bisect", "good", "d"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Error: Revision vruxwmqv 86be7a22 d | d is a head of the bisection range, which is assumed to be bad
+    [EOF]
+    [exit status: 1]
+    ");
+    let output = work_dir.run_jj(["bisect", "good", "a | b"]);
+    insta::assert_snapshot!(output, @"
+    Now evaluating: royxmykx dffaa0d4 c | c
+    1 revision left to test (roughly 1 step)
+    Mark it with `jj bisect good`, `jj bisect bad`, or `jj bisect skip`.
+    [EOF]
+    ------- stderr -------
+    Working copy  (@) now at: wqnwkozp f7a28459 (empty) (no description set)
+    Parent commit (@-)      : royxmykx dffaa0d4 c | c
+    Added 1 files, modified 0 files, removed 0 files
+    [EOF]
+    ");
+    // A mistake can be corrected by marking the revision again
+    let output = work_dir.run_jj(["bisect", "bad", "b"]);
+    insta::assert_snapshot!(output, @"
+    The first bad revision is: zsuskuln 123b4d91 b | b
+    Search complete. Run `jj bisect reset` to end the bisection (or `jj op restore 61fa0a65765f` to also discard any revisions created during search).
+    [EOF]
+    ");
+
+    // The original (empty) working-copy commit was abandoned, so an equivalent
+    // one is created
+    work_dir.run_jj(["bisect", "reset"]).success();
+    insta::assert_snapshot!(get_log_output(&work_dir), @"
+    @  kxryzmorwvtz f5a334d32294 '' files:
+    │ ○  vruxwmqvtpmx 86be7a223919 'd' files: d
+    │ ○  royxmykxtrkr dffaa0d4dacc 'c' files: c
+    │ ○  zsuskulnrvyr 123b4d91f6e5 'b' files: b
+    │ ○  rlvkpnrzqnoo 7d980be7a1d4 'a' files: a
+    ├─╯
+    ◆  zzzzzzzzzzzz 000000000000 '' files:
+    [EOF]
+    ");
+}
+
 #[must_use]
 fn get_log_output(work_dir: &TestWorkDir) -> CommandOutput {
     let template = r#"separate(" ",
@@ -1979,6 +1979,9 @@ fn test_bookmark_list() {
     Hint: Bookmarks marked as deleted can be *deleted permanently* on the remote by running `jj git push --deleted`. Use `jj bookmark forget` if you don't want that.
     [EOF]
     "#);
+
+    let json_output = local_dir.run_jj(["bookmark", "list", "--json"]);
+    assert_eq!(json_output, output);
 }
 
 #[test]
@@ -1297,6 +1297,7 @@ fn test_config() {
     let output = test_env.complete_fish(["config", "get", "f"]);
     insta::assert_snapshot!(output, @"
     fsmonitor.backend	Whether to use an external filesystem monitor, useful for large repos
+    fsmonitor.inotify.start-daemon	Whether to start the inotify daemon in the background when taking snapshots.
     fsmonitor.watchman.register-snapshot-trigger	Whether to use triggers to monitor for changes in the background.
     [EOF]
     ");
@@ -1305,6 +1306,8 @@ fn test_config() {
     insta::assert_snapshot!(output, @"
     fsmonitor	External filesystem monitor settings, useful for large repos
     fsmonitor.backend	Whether to use an external filesystem monitor, useful for large repos
+    fsmonitor.inotify
+    fsmonitor.inotify.start-daemon	Whether to start the inotify daemon in the background when taking snapshots.
     fsmonitor.watchman
     fsmonitor.watchman.register-snapshot-trigger	Whether to use triggers to monitor for changes in the background.
     [EOF]
@@ -1313,6 +1316,7 @@ fn test_config() {
     let output = test_env.complete_fish(["log", "--config", "f"]);
     insta::assert_snapshot!(output, @"
     fsmonitor.backend=	Whether to use an external filesystem monitor, useful for large repos
+    fsmonitor.inotify.start-daemon=	Whether to start the inotify daemon in the background when taking snapshots.
     fsmonitor.watchman.register-snapshot-trigger=	Whether to use triggers to monitor for changes in the background.
     [EOF]
     ");
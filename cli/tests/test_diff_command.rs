@@ -3863,6 +3863,69 @@ fn test_diff_binary() {
     ");
 }
 
+#[test]
+fn test_diff_binary_gitattributes() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file(".gitattributes", "*.lock binary\n*.min.js -diff\n");
+    work_dir.write_file("deps.lock", "a\n");
+    work_dir.write_file("app.min.js", "a\n");
+    work_dir.write_file("file.txt", "a\n");
+    work_dir.run_jj(["new"]).success();
+    work_dir.write_file("deps.lock", "b\n");
+    work_dir.write_file("app.min.js", "b\n");
+    work_dir.write_file("file.txt", "b\n");
+
+    let output = work_dir.run_jj(["diff"]);
+    insta::assert_snapshot!(output, @"
+    Modified regular file app.min.js:
+        (binary)
+    Modified regular file deps.lock:
+        (binary)
+    Modified regular file file.txt:
+       1    1: ab
+    [EOF]
+    ");
+
+    let output = work_dir.run_jj(["diff", "--git"]);
+    insta::assert_snapshot!(output, @"
+    diff --git a/app.min.js b/app.min.js
+    index 7898192261..6178079822 100644
+    Binary files a/app.min.js and b/app.min.js differ
+    diff --git a/deps.lock b/deps.lock
+    index 7898192261..6178079822 100644
+    Binary files a/deps.lock and b/deps.lock differ
+    diff --git a/file.txt b/file.txt
+    index 7898192261..6178079822 100644
+    --- a/file.txt
+    +++ b/file.txt
+    @@ -1,1 +1,1 @@
+    -a
+    +b
+    [EOF]
+    ");
+
+    let output = work_dir.run_jj(["diff", "--stat"]);
+    insta::assert_snapshot!(output, @"
+    app.min.js | (binary)
+    deps.lock  | (binary)
+    file.txt   | 2 +-
+    3 files changed, 1 insertion(+), 1 deletion(-)
+    [EOF]
+    ");
+
+    let output = work_dir.run_jj(["log", "--no-graph", "-r@", "-T", "self.diff().stat()"]);
+    insta::assert_snapshot!(output, @"
+    app.min.js | (binary)
+    deps.lock  | (binary)
+    file.txt   | 2 +-
+    3 files changed, 1 insertion(+), 1 deletion(-)
+    [EOF]
+    ");
+}
+
 /// Test diff --stat output width for diffs that have different cases of right
 /// side text: solely "(binary)", a mixture of text and binary diffs, and binary
 /// size changes.
@@ -90,8 +90,7 @@ fn test_evolog_with_or_without_diff() {
     │  Modified regular file file1:
     │     1    1: foo
     │          2: bar
-    │  Added regular file file2:
-    │          1: foo
+    │  Modified regular file file2 (file1 => file2):
     ○  rlvkpnrz/3 test.user@example.com 2001-02-03 08:05:08 b955b72e (hidden)
        (empty) my description
        -- operation e0f8e58b3800 new empty commit
@@ -187,13 +186,9 @@ fn test_evolog_with_or_without_diff() {
     @@ -1,1 +1,2 @@
      foo
     +bar
-    diff --git a/file2 b/file2
-    new file mode 100644
-    index 0000000000..257cc5642c
-    --- /dev/null
-    +++ b/file2
-    @@ -0,0 +1,1 @@
-    +foo
+    diff --git a/file1 b/file2
+    copy from file1
+    copy to file2
     rlvkpnrz/3 test.user@example.com 2001-02-03 08:05:08 b955b72e (hidden)
     (empty) my description
     -- operation e0f8e58b3800 new empty commit
@@ -823,3 +818,51 @@ fn test_evolog_template_predecessors_and_inter_diff() {
     [EOF]
     ");
 }
+
+#[test]
+fn test_evolog_with_rename() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("file1", "a\nb\nc\n");
+    work_dir.run_jj(["file", "mv", "file1", "file2"]).success();
+    work_dir.write_file("file2", "x\n");
+
+    // The rename is shown in the diff from the predecessor
+    let output = work_dir.run_jj(["evolog", "--summary"]);
+    insta::assert_snapshot!(output, @"
+    @  qpvuntsm test.user@example.com 2001-02-03 08:05:09 48cffde6
+    │  (no description set)
+    │  -- operation 3cda63c689db snapshot working copy
+    │  M file2
+    ○  qpvuntsm/1 test.user@example.com 2001-02-03 08:05:08 ac597d5b (hidden)
+    │  (no description set)
+    │  -- operation 7289320c03ca move file1 to file2 in commit ae454e27e16b90bcb160da3daae5dc101bc59274
+    │  R {file1 => file2}
+    ○  qpvuntsm/2 test.user@example.com 2001-02-03 08:05:08 ae454e27 (hidden)
+    │  (no description set)
+    │  -- operation b9224eeed43c snapshot working copy
+    │  A file1
+    ○  qpvuntsm/3 test.user@example.com 2001-02-03 08:05:07 e8849ae1 (hidden)
+       (empty) (no description set)
+       -- operation 8f47435a3990 add workspace 'default'
+    [EOF]
+    ");
+    let output = work_dir.run_jj([
+        "evolog",
+        "--no-graph",
+        "-T",
+        r#"commit.commit_id().short() ++ "\n" ++ inter_diff.summary()"#,
+    ]);
+    insta::assert_snapshot!(output, @"
+    48cffde6f9d3
+    M file2
+    ac597d5ba364
+    R {file1 => file2}
+    ae454e27e16b
+    A file1
+    e8849ae12c70
+    [EOF]
+    ");
+}
@@ -251,6 +251,89 @@ fn test_annotate_abandoned() {
     ");
 }
 
+#[test]
+fn test_annotate_renamed() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("old.txt", "line1\nline2\n");
+    work_dir.run_jj(["commit", "-m=initial"]).success();
+    work_dir.remove_file("old.txt");
+    work_dir.write_file("new.txt", "line1\nline2\n");
+    work_dir.run_jj(["commit", "-m=rename"]).success();
+    work_dir.write_file("new.txt", "line1\nline2\nline3\n");
+
+    let output = work_dir.run_jj(["file", "annotate", "new.txt"]);
+    insta::assert_snapshot!(output, @"
+    qpvuntsm test.use 2001-02-03 08:05:08    1: line1
+    qpvuntsm test.use 2001-02-03 08:05:08    2: line2
+    kkmpptxz test.use 2001-02-03 08:05:10    3: line3
+    [EOF]
+    ");
+
+    let template =
+        r#"commit.description().first_line() ++ " " ++ original_path ++ ": " ++ content"#;
+    let output = work_dir.run_jj(["file", "annotate", "new.txt", "-T", template]);
+    insta::assert_snapshot!(output, @"
+    initial old.txt: line1
+    initial old.txt: line2
+     new.txt: line3
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_annotate_ignore_revs() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("file.txt", "line1\nline2\n");
+    work_dir.run_jj(["commit", "-m=initial"]).success();
+    work_dir.write_file("file.txt", "LINE1\nLINE2\n");
+    work_dir.run_jj(["commit", "-m=reformat"]).success();
+    work_dir.write_file("file.txt", "LINE1\nLINE2\nline3\n");
+
+    let output = work_dir.run_jj(["file", "annotate", "file.txt", "--ignore-rev=@-"]);
+    insta::assert_snapshot!(output, @"
+    qpvuntsm test.use 2001-02-03 08:05:08    1: LINE1
+    qpvuntsm test.use 2001-02-03 08:05:08    2: LINE2
+    kkmpptxz test.use 2001-02-03 08:05:10    3: line3
+    [EOF]
+    ");
+
+    let reformat_id = work_dir
+        .run_jj(["log", "--no-graph", "-r=@-", "-T=commit_id"])
+        .success()
+        .stdout
+        .into_raw();
+    work_dir.write_file(
+        ".git-blame-ignore-revs",
+        format!(
+            "# Reformat\n{reformat_id}\n\n{} # not in the repo\n",
+            "0".repeat(40)
+        ),
+    );
+    test_env.add_config("annotate.ignore-revs-file = '.git-blame-ignore-revs'");
+    let output = work_dir.run_jj(["file", "annotate", "file.txt"]);
+    insta::assert_snapshot!(output, @"
+    qpvuntsm test.use 2001-02-03 08:05:08    1: LINE1
+    qpvuntsm test.use 2001-02-03 08:05:08    2: LINE2
+    kkmpptxz test.use 2001-02-03 08:05:12    3: line3
+    [EOF]
+    ");
+
+    work_dir.write_file(".git-blame-ignore-revs", "bad\n");
+    let output = work_dir.run_jj(["file", "annotate", "file.txt"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Error: Invalid commit id in ignore-revs file $TEST_ENV/repo/.git-blame-ignore-revs: bad
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
 #[test]
 fn test_annotate_with_template() {
     let test_env = TestEnvironment::default();
@@ -0,0 +1,244 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fs::File;
+use std::io::Read as _;
+use std::path::Path;
+
+use itertools::Itertools as _;
+
+use crate::common::TestEnvironment;
+use crate::common::create_commit_with_files;
+
+/// Lists the entries of a tar archive with their modes and contents.
+fn list_tar(archive: impl std::io::Read) -> String {
+    let mut archive = tar::Archive::new(archive);
+    archive
+        .entries()
+        .unwrap()
+        .map(|entry| {
+            let mut entry = entry.unwrap();
+            let path = entry.path().unwrap().display().to_string();
+            let mode = entry.header().mode().unwrap();
+            if let Some(target) = entry.link_name().unwrap() {
+                return format!("--- {path} {mode:o} -> {}\n", target.display());
+            }
+            let mut contents = String::new();
+            entry.read_to_string(&mut contents).unwrap();
+            format!("--- {path} {mode:o}\n{contents}")
+        })
+        .join("")
+}
+
+/// Lists the entries of a zip archive with their modes and contents.
+fn list_zip(path: &Path) -> String {
+    let mut archive = zip::ZipArchive::new(File::open(path).unwrap()).unwrap();
+    (0..archive.len())
+        .map(|i| {
+            let mut entry = archive.by_index(i).unwrap();
+            let name = entry.name().unwrap().into_owned();
+            let mode = entry.unix_mode().unwrap();
+            let mut contents = String::new();
+            entry.read_to_string(&mut contents).unwrap();
+            if entry.is_symlink() {
+                format!("--- {name} {mode:o} -> {contents}\n")
+            } else {
+                format!("--- {name} {mode:o}\n{contents}")
+            }
+        })
+        .join("")
+}
+
+#[test]
+fn test_archive() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("file1", "a\n");
+    work_dir.create_dir("dir");
+    work_dir.write_file("dir/file2", "b\n");
+    work_dir.write_file("script", "exit 0\n");
+    work_dir.run_jj(["file", "chmod", "x", "script"]).success();
+    work_dir.run_jj(["new"]).success();
+    work_dir.write_file("file1", "changed\n");
+
+    let output = work_dir.run_jj(["file", "archive", "-r@-", "-o", "../out.tar"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Wrote 3 files to ../out.tar
+    [EOF]
+    ");
+    let archive = File::open(test_env.env_root().join("out.tar")).unwrap();
+    insta::assert_snapshot!(list_tar(archive), @"
+    --- dir/file2 644
+    b
+    --- file1 644
+    a
+    --- script 755
+    exit 0
+    ");
+
+    // Filter by filesets, and prepend a prefix
+    let output = work_dir.run_jj([
+        "file",
+        "archive",
+        "-o",
+        "../out.tar.gz",
+        "--prefix=repo-1.0/",
+        "file1",
+        "dir",
+    ]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Wrote 2 files to ../out.tar.gz
+    [EOF]
+    ");
+    let archive = File::open(test_env.env_root().join("out.tar.gz")).unwrap();
+    let archive = flate2::read::GzDecoder::new(archive);
+    insta::assert_snapshot!(list_tar(archive), @"
+    --- repo-1.0/dir/file2 644
+    b
+    --- repo-1.0/file1 644
+    changed
+    ");
+
+    let output = work_dir.run_jj(["file", "archive", "-o", "../out.zip"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Wrote 3 files to ../out.zip
+    [EOF]
+    ");
+    insta::assert_snapshot!(list_zip(&test_env.env_root().join("out.zip")), @"
+    --- dir/file2 100644
+    b
+    --- file1 100644
+    changed
+    --- script 100755
+    exit 0
+    ");
+
+    // The format can be specified explicitly
+    let output = work_dir.run_jj(["file", "archive", "-o", "../out", "--format=zip", "file1"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Wrote 1 file to ../out
+    [EOF]
+    ");
+    insta::assert_snapshot!(list_zip(&test_env.env_root().join("out")), @"
+    --- file1 100644
+    changed
+    ");
+
+    // Error if the format can't be detected
+    let output = work_dir.run_jj(["file", "archive", "-o", "../out.txt"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Error: Cannot detect the archive format of ../out.txt
+    Hint: Use --format to specify the format, or use .tar, .tar.gz or .zip extension.
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_archive_symlink() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("file1", "a\n");
+    std::os::unix::fs::symlink("file1", work_dir.root().join("symlink1")).unwrap();
+
+    work_dir
+        .run_jj(["file", "archive", "-o", "../out.tar"])
+        .success();
+    let archive = File::open(test_env.env_root().join("out.tar")).unwrap();
+    insta::assert_snapshot!(list_tar(archive), @"
+    --- file1 644
+    a
+    --- symlink1 777 -> file1
+    ");
+
+    work_dir
+        .run_jj(["file", "archive", "-o", "../out.zip"])
+        .success();
+    insta::assert_snapshot!(list_zip(&test_env.env_root().join("out.zip")), @"
+    --- file1 100644
+    a
+    --- symlink1 120777 -> file1
+    ");
+}
+
+#[test]
+fn test_archive_conflicts() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    create_commit_with_files(&work_dir, "base", &[], &[("file", "base\n")]);
+    create_commit_with_files(&work_dir, "a", &["base"], &[("file", "a\n")]);
+    create_commit_with_files(&work_dir, "b", &["base"], &[("file", "b\n")]);
+    work_dir.run_jj(["new", "a", "b"]).success();
+
+    // Conflicted files are rejected by default
+    let output = work_dir.run_jj(["file", "archive", "-o", "../out.tar"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Error: Cannot archive conflicted file file
+    Hint: Use --allow-conflicts to write conflicted files with conflict markers.
+    [EOF]
+    [exit status: 1]
+    ");
+    assert!(!test_env.env_root().join("out.tar").exists());
+    // An existing file isn't replaced on error
+    std::fs::write(test_env.env_root().join("out.tar"), "old").unwrap();
+    let output = work_dir.run_jj(["file", "archive", "-o", "../out.tar"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        std::fs::read(test_env.env_root().join("out.tar")).unwrap(),
+        b"old"
+    );
+    // The temporary file is removed
+    assert!(
+        std::fs::read_dir(test_env.env_root())
+            .unwrap()
+            .all(|entry| !entry
+                .unwrap()
+                .file_name()
+                .to_string_lossy()
+                .starts_with(".tmp"))
+    );
+
+    let output = work_dir.run_jj(["file", "archive", "-o", "../out.tar", "--allow-conflicts"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Warning: Archived conflicted file file with conflict markers
+    Wrote 1 file to ../out.tar
+    [EOF]
+    ");
+    let archive = File::open(test_env.env_root().join("out.tar")).unwrap();
+    insta::assert_snapshot!(list_tar(archive), @r#"
+    --- file 644
+    <<<<<<< conflict 1 of 1
+    %%%%%%% diff from: rlvkpnrz 1792382a "base"
+    \\\\\\\        to: zsuskuln 45537d53 "a"
+    -base
+    +a
+    +++++++ royxmykx 89d1b299 "b"
+    b
+    >>>>>>> conflict 1 of 1 ends
+    "#);
+}
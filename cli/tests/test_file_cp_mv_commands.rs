@@ -0,0 +1,165 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::common::TestEnvironment;
+
+#[test]
+fn test_file_mv() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("file1", "a\nb\nc\n");
+    work_dir.create_dir("dir");
+    work_dir.write_file("dir/file2", "d\n");
+    work_dir.write_file("dir/file3", "e\n");
+    work_dir.run_jj(["new"]).success();
+
+    let output = work_dir.run_jj(["file", "mv", "file1", "renamed"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Working copy  (@) now at: rlvkpnrz db3a643c (no description set)
+    Parent commit (@-)      : qpvuntsm 55ae35bc (no description set)
+    Added 1 files, modified 0 files, removed 1 files
+    [EOF]
+    ");
+    assert!(!work_dir.root().join("file1").exists());
+    assert_eq!(work_dir.read_file("renamed"), "a\nb\nc\n");
+
+    // The rename is still tracked after the contents are rewritten
+    work_dir.write_file("renamed", "x\ny\n");
+    let output = work_dir.run_jj(["diff", "--summary"]);
+    insta::assert_snapshot!(output, @"
+    R {file1 => renamed}
+    [EOF]
+    ");
+
+    // Move a directory into another directory
+    work_dir.create_dir("other");
+    work_dir.write_file("other/file4", "f\n");
+    let output = work_dir.run_jj(["file", "mv", "dir", "other"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Working copy  (@) now at: rlvkpnrz f6e4c2d0 (no description set)
+    Parent commit (@-)      : qpvuntsm 55ae35bc (no description set)
+    Added 2 files, modified 0 files, removed 2 files
+    [EOF]
+    ");
+    work_dir.write_file("other/dir/file2", "changed\n");
+    let output = work_dir.run_jj(["diff", "--summary"]);
+    insta::assert_snapshot!(output, @"
+    R {dir => other/dir}/file2
+    R {dir => other/dir}/file3
+    A other/file4
+    R {file1 => renamed}
+    [EOF]
+    ");
+
+    // Move in a revision other than the working copy
+    let output = work_dir.run_jj(["file", "mv", "-r@-", "file1", "moved"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Rebased 1 descendant commits
+    Working copy  (@) now at: rlvkpnrz 89609d28 (no description set)
+    Parent commit (@-)      : qpvuntsm 5cc9c673 (no description set)
+    Added 1 files, modified 0 files, removed 0 files
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["diff", "--summary", "-r@-"]);
+    insta::assert_snapshot!(output, @"
+    A dir/file2
+    A dir/file3
+    A moved
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_file_cp() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("file1", "a\nb\nc\n");
+    work_dir.run_jj(["new"]).success();
+
+    let output = work_dir.run_jj(["file", "cp", "file1", "copy"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Working copy  (@) now at: rlvkpnrz 065d1e7a (no description set)
+    Parent commit (@-)      : qpvuntsm ae454e27 (no description set)
+    Added 1 files, modified 0 files, removed 0 files
+    [EOF]
+    ");
+    assert_eq!(work_dir.read_file("file1"), "a\nb\nc\n");
+    assert_eq!(work_dir.read_file("copy"), "a\nb\nc\n");
+
+    work_dir.write_file("copy", "x\n");
+    let output = work_dir.run_jj(["diff", "--git"]);
+    insta::assert_snapshot!(output, @"
+    diff --git a/file1 b/copy
+    copy from file1
+    copy to copy
+    index de980441c3..587be6b4c3 100644
+    --- a/file1
+    +++ b/copy
+    @@ -1,3 +1,1 @@
+    -a
+    -b
+    -c
+    +x
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_file_cp_mv_errors() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("file1", "a\n");
+    work_dir.write_file("file2", "b\n");
+    work_dir.create_dir("dir");
+    work_dir.write_file("dir/file3", "c\n");
+
+    let output = work_dir.run_jj(["file", "mv", "nonexistent", "file3"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Error: No such path: nonexistent
+    [EOF]
+    [exit status: 1]
+    ");
+    let output = work_dir.run_jj(["file", "mv", "file1", "file2"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Error: Destination file2 already exists
+    [EOF]
+    [exit status: 1]
+    ");
+    let output = work_dir.run_jj(["file", "cp", "dir", "dir/sub"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Error: Cannot copy dir into itself
+    [EOF]
+    [exit status: 1]
+    ");
+    let output = work_dir.run_jj(["file", "mv", "-r", "root()", "file1", "file3"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Error: The root commit 000000000000 is immutable
+    [EOF]
+    [exit status: 1]
+    ");
+}
@@ -107,4 +107,108 @@ fn test_file_search_conflicts() {
     // Doesn't list file if the pattern doesn't match
     let output = work_dir.run_jj(["file", "search", "--pattern=*qux*"]);
     insta::assert_snapshot!(output.normalize_backslash(), @"");
+
+    // Shows the conflict side of matching lines
+    let output = work_dir.run_jj(["file", "search", "--lines", "--pattern=regex:foo|baz"]);
+    insta::assert_snapshot!(output.normalize_backslash(), @"
+    file1[qpvuntsm fae24a95 (rebase destination)]:1:-foo-
+    file1[kkmpptxz 51957a05 (rebased revision)]:1:-baz-
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_file_search_pattern_kinds() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("file1", "foo\nFoo: bar\n");
+    work_dir.write_file("file2", "bar\n");
+
+    // A pattern without a known kind prefix is a glob
+    let output = work_dir.run_jj(["file", "search", "--pattern=Foo:*"]);
+    insta::assert_snapshot!(output.normalize_backslash(), @"
+    file1
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "search", "--pattern=substring:ar"]);
+    insta::assert_snapshot!(output.normalize_backslash(), @"
+    file1
+    file2
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "search", "--pattern=regex-i:^foo$"]);
+    insta::assert_snapshot!(output.normalize_backslash(), @"
+    file1
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "search", "--pattern=exact:bar"]);
+    insta::assert_snapshot!(output.normalize_backslash(), @"
+    file2
+    [EOF]
+    ");
+
+    // Invalid regex
+    let output = work_dir.run_jj(["file", "search", "--pattern=regex:("]);
+    insta::assert_snapshot!(output.normalize_backslash(), @"
+    ------- stderr -------
+    error: invalid value 'regex:(' for '--pattern <PATTERN>': regex parse error:
+        (
+        ^
+    error: unclosed group
+
+    For more information, try '--help'.
+    [EOF]
+    [exit status: 2]
+    ");
+}
+
+#[test]
+fn test_file_search_lines() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("file1", "a\nfoo1\nb\nc\nd\ne\nfoo2\nf\nfoo3\n");
+    work_dir.write_file("file2", "bar\nfoo");
+
+    // Prints matching lines with line numbers
+    let output = work_dir.run_jj(["file", "search", "--lines", "--pattern=substring:foo"]);
+    insta::assert_snapshot!(output.normalize_backslash(), @"
+    file1:2:foo1
+    file1:7:foo2
+    file1:9:foo3
+    file2:2:foo
+    [EOF]
+    ");
+
+    // Prints context lines around matches
+    let output = work_dir.run_jj(["file", "search", "-C1", "--pattern=substring:foo", "file1"]);
+    insta::assert_snapshot!(output.normalize_backslash(), @"
+    file1-1-a
+    file1:2:foo1
+    file1-3-b
+    file1-6-e
+    file1:7:foo2
+    file1-8-f
+    file1:9:foo3
+    [EOF]
+    ");
+
+    // Custom template
+    let output = work_dir.run_jj([
+        "file",
+        "search",
+        "--pattern=substring:foo",
+        "-T",
+        r#"commit.change_id().short() ++ " " ++ path ++ "@" ++ line_number ++ "\n""#,
+    ]);
+    insta::assert_snapshot!(output.normalize_backslash(), @"
+    qpvuntsmwlqt file1@2
+    qpvuntsmwlqt file1@7
+    qpvuntsmwlqt file1@9
+    qpvuntsmwlqt file2@2
+    [EOF]
+    ");
 }
@@ -970,6 +970,54 @@ fn test_fix_large_file() {
     assert!(stdout_bytes == large_contents.to_ascii_lowercase());
 }
 
+#[test]
+fn test_fix_line_ranges() {
+    let test_env = TestEnvironment::default();
+    let echo_path = assert_cmd::cargo::cargo_bin!("fake-echo");
+    test_env.add_config(formatdoc! {"
+        [fix.tools.echo]
+        command = [{echo_path}, '$line_ranges', '--lines=$first_line:$last_line']
+        patterns = ['all()']
+        ",
+        echo_path = to_toml_value(echo_path.to_str().unwrap()),
+    });
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    work_dir.write_file("changed", "a\nb\nc\n");
+    work_dir.write_file("deleted_lines", "a\nb\nc\n");
+    work_dir.run_jj(["new"]).success();
+    work_dir.write_file("changed", "a\nB\nc\nd\n");
+    work_dir.write_file("deleted_lines", "a\nc\n");
+    work_dir.write_file("added", "x\ny\n");
+
+    let output = work_dir.run_jj(["fix", "-s", "@"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Fixed 1 commits of 1 checked.
+    Working copy  (@) now at: rlvkpnrz 3d4eb278 (no description set)
+    Parent commit (@-)      : qpvuntsm 1f73129f (no description set)
+    Added 0 files, modified 2 files, removed 0 files
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "show", "changed", "-r", "@"]);
+    insta::assert_snapshot!(output, @"
+    2-2,4-4 --lines=2:2 --lines=4:4
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "show", "added", "-r", "@"]);
+    insta::assert_snapshot!(output, @"
+    1-2 --lines=1:2
+    [EOF]
+    ");
+    // The tool isn't run on files without changed lines
+    let output = work_dir.run_jj(["file", "show", "deleted_lines", "-r", "@"]);
+    insta::assert_snapshot!(output, @"
+    a
+    c
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_fix_some_paths() {
     let mut test_env = TestEnvironment::default();
@@ -0,0 +1,70 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::common::TestEnvironment;
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_inotify_daemon() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    test_env.add_config(r#"fsmonitor.backend = "inotify""#);
+    test_env.add_config("fsmonitor.inotify.start-daemon = true");
+    let work_dir = test_env.work_dir("repo");
+
+    let output = work_dir.run_jj(["debug", "inotify", "status"]);
+    insta::assert_snapshot!(output, @"
+    Inotify is enabled via `fsmonitor.backend`.
+    The inotify daemon is not running.
+    [EOF]
+    ");
+
+    // The first snapshot starts the daemon, and crawls the working copy
+    work_dir.write_file("file1", "");
+    let output = work_dir.run_jj(["file", "list"]);
+    insta::assert_snapshot!(output, @"
+    file1
+    [EOF]
+    ");
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+    while !work_dir
+        .run_jj(["debug", "inotify", "status"])
+        .stdout
+        .raw()
+        .contains("daemon is running")
+    {
+        assert!(std::time::Instant::now() < deadline, "daemon didn't start");
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    let output = work_dir.run_jj(["file", "list"]);
+    insta::assert_snapshot!(output, @"
+    file1
+    [EOF]
+    ");
+
+    // Later snapshots only look at the paths reported by the daemon
+    work_dir.write_file("file2", "");
+    work_dir.create_dir("dir").write_file("file3", "");
+    let output = work_dir.run_jj(["debug", "inotify", "query-changed-files"]);
+    let stdout = output.stdout.raw();
+    assert!(stdout.contains(r#"Changed files: Some(["dir""#), "{output}");
+    assert!(stdout.contains(r#""file2"])"#), "{output}");
+    work_dir.remove_file("file1");
+    let output = work_dir.run_jj(["file", "list"]);
+    insta::assert_snapshot!(output, @"
+    dir/file3
+    file2
+    [EOF]
+    ");
+}
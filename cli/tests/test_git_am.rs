@@ -0,0 +1,188 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use indoc::indoc;
+
+use crate::common::TestEnvironment;
+
+#[test]
+fn test_git_am_round_trip() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("file", "a\nb\nc\n");
+    work_dir.write_file("gone", "gone\n");
+    work_dir
+        .run_jj(["commit", "-m", "Add files\n\nWith a body.\n"])
+        .success();
+    work_dir.write_file("file", "a\nB\nc\n");
+    work_dir.write_file("new", "new");
+    work_dir.remove_file("gone");
+    work_dir
+        .run_jj([
+            "describe",
+            "-m",
+            "Change files",
+            "--author",
+            "Other <other@example.com>",
+        ])
+        .success();
+    let patches = work_dir
+        .run_jj(["git", "format-patch", "-r::@ ~ root()", "--stdout"])
+        .success()
+        .stdout
+        .into_raw();
+
+    let output = work_dir.run_jj_with(|cmd| {
+        cmd.args(["git", "am", "--onto", "root()"])
+            .write_stdin(patches)
+    });
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Applied 2 patches:
+      mzvwutvl 54ce3d98 Add files
+      zxsnswpr 5cb9f34f Change files
+    [EOF]
+    ");
+    let output = work_dir.run_jj([
+        "log",
+        "-T",
+        r#"description.first_line() ++ " " ++ author ++ " " ++ author.timestamp() ++ "\n""#,
+    ]);
+    insta::assert_snapshot!(output, @"
+    @  Change files Other <other@example.com> 2001-02-03 04:05:09.000 +07:00
+    ○  Add files Test User <test.user@example.com> 2001-02-03 04:05:08.000 +07:00
+    │ ○  Change files Other <other@example.com> 2001-02-03 04:05:09.000 +07:00
+    │ ○  Add files Test User <test.user@example.com> 2001-02-03 04:05:08.000 +07:00
+    ├─╯
+    ◆    1970-01-01 00:00:00.000 +00:00
+    [EOF]
+    ");
+    // The applied patches have the same content
+    let output = work_dir.run_jj(["diff", "--from", "@", "--to", "heads(all()) ~ @"]);
+    insta::assert_snapshot!(output, @"");
+    let output = work_dir.run_jj(["file", "list", "-rheads(all()) ~ @"]);
+    insta::assert_snapshot!(output, @"
+    file
+    new
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_git_am_conflict() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("file", "1\n2\n3\n4\n5\n6\n7\n8\n9\n");
+    work_dir.run_jj(["commit", "-m", "base"]).success();
+    work_dir.write_file(
+        "patch.mbox",
+        indoc! {"
+        From 0123456789abcdef0123456789abcdef01234567 Mon Sep 17 00:00:00 2001
+        From: =?UTF-8?q?J=C3=B6rg?= <jorg@example.com>
+        Date: Tue, 2 Jan 2001 03:04:05 +0600
+        Subject: [PATCH] Change two lines
+
+        ---
+         file | 4 ++--
+         1 file changed, 2 insertions(+), 2 deletions(-)
+
+        diff --git a/file b/file
+        index 1111111111..2222222222 100644
+        --- a/file
+        +++ b/file
+        @@ -1,3 +1,3 @@
+         1
+        -2
+        +two
+         3
+        @@ -7,3 +7,3 @@
+         7
+        -8
+        +eight
+         9
+        -- 
+        2.45.0
+    "},
+    );
+    work_dir.write_file("file", "1\n2\n3\n4\n5\n6\n7\n8 changed\n9\n");
+
+    let output = work_dir.run_jj(["git", "am", "patch.mbox"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Warning: Patch 1/1 didn't apply cleanly. These paths have conflicts:
+      file
+    Applied 1 patches:
+      kkmpptxz e7aedabd (conflict) Change two lines
+    New conflicts appeared in 1 commits:
+      kkmpptxz e7aedabd (conflict) Change two lines
+    Hint: To resolve the conflicts, start by creating a commit on top of
+    the conflicted commit:
+      jj new kkmpptxz
+    Then use `jj resolve`, or edit the conflict markers in the file directly.
+    Once the conflicts are resolved, you can inspect the result with `jj diff`.
+    Then run `jj squash` to move the resolution into the conflicted commit.
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["log", "-rchildren(@)", "--no-graph", "-Tauthor"]);
+    insta::assert_snapshot!(output, @"Jörg <jorg@example.com>[EOF]");
+    let output = work_dir.run_jj(["file", "show", "-rchildren(@)", "file"]);
+    insta::assert_snapshot!(output, @r"
+    1
+    two
+    3
+    4
+    5
+    6
+    7
+    <<<<<<< conflict 1 of 1
+    +++++++ side #1
+    8 changed
+    %%%%%%% diff from: base
+    \\\\\\\        to: side #2
+    -8
+    +eight
+    >>>>>>> conflict 1 of 1 ends
+    9
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_git_am_errors() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("bad.mbox", "Subject: no author\n\n");
+    let output = work_dir.run_jj(["git", "am", "bad.mbox"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Error: Failed to parse $TEST_ENV/repo/bad.mbox
+    Caused by: Malformed patch at line 2: Missing From header
+    [EOF]
+    [exit status: 1]
+    ");
+
+    work_dir.write_file("empty.mbox", "");
+    let output = work_dir.run_jj(["git", "am", "empty.mbox"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    No patches to apply.
+    [EOF]
+    ");
+}
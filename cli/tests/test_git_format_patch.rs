@@ -0,0 +1,297 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::common::TestEnvironment;
+
+#[test]
+fn test_git_format_patch() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("file", "a\nb\nc\n");
+    work_dir.write_file("gone", "gone\n");
+    work_dir
+        .run_jj(["commit", "-m", "Add files\n\nWith a body.\n"])
+        .success();
+    work_dir.write_file("file", "a\nB\nc\n");
+    work_dir.write_file("new", "new");
+    work_dir.remove_file("gone");
+    work_dir.run_jj(["describe", "-m", "Change: b/B"]).success();
+
+    let output = work_dir.run_jj(["git", "format-patch", "-r::@ ~ root()", "--stdout"]);
+    insta::assert_snapshot!(output, @r"
+    From f18e4c05780ee21dc247ebd9cca7b0922008f5f4 Mon Sep 17 00:00:00 2001
+    From: Test User <test.user@example.com>
+    Date: Sat, 3 Feb 2001 04:05:08 +0700
+    Subject: [PATCH 1/2] Add files
+    MIME-Version: 1.0
+    Content-Type: text/plain; charset=UTF-8
+    Content-Transfer-Encoding: 8bit
+
+    With a body.
+    ---
+     file | 3 +++
+     gone | 1 +
+     2 files changed, 4 insertions(+)
+
+    diff --git a/file b/file
+    new file mode 100644
+    index 0000000000..de980441c3
+    --- /dev/null
+    +++ b/file
+    @@ -0,0 +1,3 @@
+    +a
+    +b
+    +c
+    diff --git a/gone b/gone
+    new file mode 100644
+    index 0000000000..286c5f5776
+    --- /dev/null
+    +++ b/gone
+    @@ -0,0 +1,1 @@
+    +gone
+    From 759a720349511a1a52b7aa63587160de56ce9213 Mon Sep 17 00:00:00 2001
+    From: Test User <test.user@example.com>
+    Date: Sat, 3 Feb 2001 04:05:09 +0700
+    Subject: [PATCH 2/2] Change: b/B
+    MIME-Version: 1.0
+    Content-Type: text/plain; charset=UTF-8
+    Content-Transfer-Encoding: 8bit
+
+    ---
+     file | 2 +-
+     gone | 1 -
+     new  | 1 +
+     3 files changed, 2 insertions(+), 2 deletions(-)
+
+    diff --git a/file b/file
+    index de980441c3..7be73ce3c1 100644
+    --- a/file
+    +++ b/file
+    @@ -1,3 +1,3 @@
+     a
+    -b
+    +B
+     c
+    diff --git a/gone b/gone
+    deleted file mode 100644
+    index 286c5f5776..0000000000
+    --- a/gone
+    +++ /dev/null
+    @@ -1,1 +0,0 @@
+    -gone
+    diff --git a/new b/new
+    new file mode 100644
+    index 0000000000..3e5126c4e7
+    --- /dev/null
+    +++ b/new
+    @@ -0,0 +1,1 @@
+    +new
+    \ No newline at end of file
+    [EOF]
+    ");
+
+    // Patches are written to files by default
+    let output = work_dir.run_jj(["git", "format-patch", "-r::@ ~ root()"]);
+    insta::assert_snapshot!(output, @"
+    0001-Add-files.patch
+    0002-Change-b-B.patch
+    [EOF]
+    ");
+    insta::assert_snapshot!(work_dir.read_file("0002-Change-b-B.patch"), @r"
+    From 759a720349511a1a52b7aa63587160de56ce9213 Mon Sep 17 00:00:00 2001
+    From: Test User <test.user@example.com>
+    Date: Sat, 3 Feb 2001 04:05:09 +0700
+    Subject: [PATCH 2/2] Change: b/B
+    MIME-Version: 1.0
+    Content-Type: text/plain; charset=UTF-8
+    Content-Transfer-Encoding: 8bit
+
+    ---
+     file | 2 +-
+     gone | 1 -
+     new  | 1 +
+     3 files changed, 2 insertions(+), 2 deletions(-)
+
+    diff --git a/file b/file
+    index de980441c3..7be73ce3c1 100644
+    --- a/file
+    +++ b/file
+    @@ -1,3 +1,3 @@
+     a
+    -b
+    +B
+     c
+    diff --git a/gone b/gone
+    deleted file mode 100644
+    index 286c5f5776..0000000000
+    --- a/gone
+    +++ /dev/null
+    @@ -1,1 +0,0 @@
+    -gone
+    diff --git a/new b/new
+    new file mode 100644
+    index 0000000000..3e5126c4e7
+    --- /dev/null
+    +++ b/new
+    @@ -0,0 +1,1 @@
+    +new
+    \ No newline at end of file
+    ");
+
+    let output = work_dir.run_jj(["git", "format-patch", "-o", "out"]);
+    insta::assert_snapshot!(output, @"
+    out/0001-Change-b-B.patch
+    [EOF]
+    ");
+    assert!(work_dir.root().join("out/0001-Change-b-B.patch").is_file());
+}
+
+#[test]
+fn test_git_format_patch_mail_headers() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("file", "a\n");
+    work_dir
+        .run_jj([
+            "describe",
+            "-m",
+            "Übersetzung der Hilfetexte für die Befehle zum Exportieren von Patches aktualisieren\n\n\
+             From now on, lines like this are escaped.\n\
+             >From this one too.\n",
+            "--author",
+            "Jörg Doe <jorg@example.com>",
+        ])
+        .success();
+    let output = work_dir.run_jj(["git", "format-patch", "--stdout"]);
+    insta::assert_snapshot!(output, @r"
+    From ee19d91b7fd667ff74c01ab49931f47f119f0392 Mon Sep 17 00:00:00 2001
+    From: =?UTF-8?q?J=C3=B6rg=20Doe?= <jorg@example.com>
+    Date: Sat, 3 Feb 2001 04:05:08 +0700
+    Subject: [PATCH] =?UTF-8?q?=C3=9Cbersetzung=20der=20Hilfetexte=20f=C3=BCr?=
+     =?UTF-8?q?=20die=20Befehle=20zum=20Exportieren=20von=20Patches=20aktualis?=
+     =?UTF-8?q?ieren?=
+    MIME-Version: 1.0
+    Content-Type: text/plain; charset=UTF-8
+    Content-Transfer-Encoding: 8bit
+
+    >From now on, lines like this are escaped.
+    >>From this one too.
+    ---
+     file | 1 +
+     1 file changed, 1 insertion(+)
+
+    diff --git a/file b/file
+    new file mode 100644
+    index 0000000000..7898192261
+    --- /dev/null
+    +++ b/file
+    @@ -0,0 +1,1 @@
+    +a
+    [EOF]
+    ");
+    let first_patch = test_env.env_root().join("first.patch");
+    std::fs::write(&first_patch, output.stdout.raw()).unwrap();
+
+    work_dir
+        .run_jj([
+            "describe",
+            "-m",
+            "Add a file with a long description which doesn't fit in a single line of the subject",
+            "--author",
+            "Doe, \"Jane\" <jane@example.com>",
+        ])
+        .success();
+    let output = work_dir.run_jj(["git", "format-patch", "--stdout"]);
+    insta::assert_snapshot!(output, @r#"
+    From e330191813d60d04813966fec7c4d2e531434ff3 Mon Sep 17 00:00:00 2001
+    From: "Doe, \"Jane\"" <jane@example.com>
+    Date: Sat, 3 Feb 2001 04:05:08 +0700
+    Subject: [PATCH] Add a file with a long description which doesn't fit in a
+     single line of the subject
+    MIME-Version: 1.0
+    Content-Type: text/plain; charset=UTF-8
+    Content-Transfer-Encoding: 8bit
+
+    ---
+     file | 1 +
+     1 file changed, 1 insertion(+)
+
+    diff --git a/file b/file
+    new file mode 100644
+    index 0000000000..7898192261
+    --- /dev/null
+    +++ b/file
+    @@ -0,0 +1,1 @@
+    +a
+    [EOF]
+    "#);
+
+    let second_patch = test_env.env_root().join("second.patch");
+    std::fs::write(&second_patch, output.stdout.raw()).unwrap();
+
+    // The author and description are restored when the patches are applied
+    for patch in [&first_patch, &second_patch] {
+        work_dir
+            .run_jj(["git", "am", "--onto=root()", patch.to_str().unwrap()])
+            .success();
+    }
+    let output = work_dir.run_jj([
+        "log",
+        "--no-graph",
+        "-r=root()+ ~ @",
+        r#"-Tauthor ++ "\n" ++ description"#,
+    ]);
+    insta::assert_snapshot!(output, @r#"
+    Doe, "Jane" <jane@example.com>
+    Add a file with a long description which doesn't fit in a single line of the subject
+    Jörg Doe <jorg@example.com>
+    Übersetzung der Hilfetexte für die Befehle zum Exportieren von Patches aktualisieren
+
+    From now on, lines like this are escaped.
+    >From this one too.
+    [EOF]
+    "#);
+}
+
+#[test]
+fn test_git_format_patch_merge() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.run_jj(["new", "root()", "-m", "a"]).success();
+    work_dir
+        .run_jj(["bookmark", "create", "-r@", "a"])
+        .success();
+    work_dir.run_jj(["new", "root()", "-m", "b"]).success();
+    work_dir.run_jj(["new", "a", "@", "-m", "merge"]).success();
+    let output = work_dir.run_jj(["git", "format-patch"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Error: Cannot export merge commit ec2146101a4d as a patch
+    [EOF]
+    [exit status: 1]
+    ");
+
+    let output = work_dir.run_jj(["git", "format-patch", "-rnone()"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    No revisions to export.
+    [EOF]
+    ");
+}
@@ -0,0 +1,271 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::Path;
+
+use testutils::git;
+
+use crate::common::TestEnvironment;
+
+fn lfs_object_paths(lfs_dir: &Path) -> Vec<String> {
+    fn visit(dir: &Path, prefix: &str, paths: &mut Vec<String>) {
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let entry = entry.unwrap();
+            let name = format!("{prefix}{}", entry.file_name().to_str().unwrap());
+            if entry.file_type().unwrap().is_dir() {
+                visit(&entry.path(), &format!("{name}/"), paths);
+            } else {
+                paths.push(name);
+            }
+        }
+    }
+    let mut paths = vec![];
+    visit(&lfs_dir.join("objects"), "", &mut paths);
+    paths.sort();
+    paths
+}
+
+#[test]
+fn test_git_lfs_push_and_fetch() {
+    let test_env = TestEnvironment::default();
+    test_env.add_config("remotes.origin.auto-track-bookmarks = '*'");
+    let remote_path = test_env.env_root().join("remote.git");
+    git::init_bare(&remote_path);
+
+    test_env.run_jj_in(".", ["git", "init", "local"]).success();
+    let local_dir = test_env.work_dir("local");
+    local_dir
+        .run_jj(["git", "remote", "add", "origin", "../remote.git"])
+        .success();
+    local_dir.write_file(".gitattributes", "*.bin filter=lfs\n");
+    local_dir.write_file("large.bin", "large contents\n");
+    local_dir.write_file("small.txt", "small contents\n");
+    local_dir.run_jj(["commit", "-m=add files"]).success();
+    local_dir
+        .run_jj(["bookmark", "create", "-r@-", "main"])
+        .success();
+
+    // Only the pointer file is stored in the commit
+    let output = local_dir.run_jj(["file", "show", "-r@-", "large.bin"]);
+    insta::assert_snapshot!(output, @"
+    version https://git-lfs.github.com/spec/v1
+    oid sha256:7004f11b91b38ea3219d0b2fb175e673cee200839aa75c1c8434e8693ebfab0b
+    size 15
+    [EOF]
+    ");
+    let output = local_dir.run_jj(["file", "show", "-r@-", "small.txt"]);
+    insta::assert_snapshot!(output, @"
+    small contents
+    [EOF]
+    ");
+
+    // The LFS object is uploaded along with the commit
+    let output = local_dir.run_jj(["git", "push", "--bookmark=main"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Changes to push to origin:
+      Add bookmark main to 642ac4fffc42
+    Uploaded 1 LFS objects to remote origin
+    [EOF]
+    ");
+    insta::assert_snapshot!(lfs_object_paths(&remote_path.join("lfs")).join("\n"), @"70/04/7004f11b91b38ea3219d0b2fb175e673cee200839aa75c1c8434e8693ebfab0b");
+
+    // Nothing to upload if the objects are already on the remote
+    local_dir.write_file("small.txt", "modified\n");
+    local_dir.run_jj(["commit", "-m=modify file"]).success();
+    local_dir
+        .run_jj(["bookmark", "set", "-r@-", "main"])
+        .success();
+    let output = local_dir.run_jj(["git", "push", "--bookmark=main"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Changes to push to origin:
+      Move forward bookmark main from 642ac4fffc42 to 0ee0de8afd37
+    [EOF]
+    ");
+
+    // The LFS object is downloaded and smudged on fetch
+    test_env.run_jj_in(".", ["git", "init", "other"]).success();
+    let other_dir = test_env.work_dir("other");
+    other_dir
+        .run_jj(["git", "remote", "add", "origin", "../remote.git"])
+        .success();
+    let output = other_dir.run_jj(["git", "fetch"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    bookmark: main@origin [new] tracked
+    Downloaded 1 LFS objects from remote origin
+    [EOF]
+    ");
+    other_dir.run_jj(["new", "main"]).success();
+    insta::assert_snapshot!(other_dir.read_file("large.bin"), @"large contents");
+    let output = other_dir.run_jj(["diff", "--summary"]);
+    insta::assert_snapshot!(output, @"");
+
+    // Only new objects are downloaded
+    local_dir.write_file("large.bin", "new large contents\n");
+    local_dir.write_file("sub/other.bin", "other large contents\n");
+    local_dir
+        .run_jj(["commit", "-m=modify lfs files"])
+        .success();
+    local_dir
+        .run_jj(["bookmark", "set", "-r@-", "main"])
+        .success();
+    local_dir
+        .run_jj(["git", "push", "--bookmark=main"])
+        .success();
+    let output = other_dir.run_jj(["git", "fetch"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    bookmark: main@origin [updated] tracked
+    Downloaded 2 LFS objects from remote origin
+    [EOF]
+    ");
+    other_dir.run_jj(["new", "main"]).success();
+    insta::assert_snapshot!(other_dir.read_file("large.bin"), @"new large contents");
+    insta::assert_snapshot!(other_dir.read_file("sub/other.bin"), @"other large contents");
+}
+
+#[test]
+fn test_git_lfs_missing_object() {
+    let test_env = TestEnvironment::default();
+    test_env.add_config("remotes.origin.auto-track-bookmarks = '*'");
+    let remote_path = test_env.env_root().join("remote.git");
+    git::init_bare(&remote_path);
+
+    test_env.run_jj_in(".", ["git", "init", "local"]).success();
+    let local_dir = test_env.work_dir("local");
+    local_dir
+        .run_jj(["git", "remote", "add", "origin", "../remote.git"])
+        .success();
+    local_dir.write_file(".gitattributes", "*.bin filter=lfs\n");
+    local_dir.write_file("large.bin", "large contents\n");
+    local_dir.run_jj(["commit", "-m=add files"]).success();
+    local_dir
+        .run_jj(["bookmark", "create", "-r@-", "main"])
+        .success();
+
+    // Push the commit without the object
+    std::fs::remove_dir_all(local_dir.root().join(".jj/repo/store/git/lfs")).unwrap();
+    let output = local_dir.run_jj(["git", "push", "--bookmark=main"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Changes to push to origin:
+      Add bookmark main to 89df92a3ec4d
+    Warning: 1 LFS objects are missing locally and were not uploaded to remote origin
+    [EOF]
+    ");
+
+    // The pointer file is checked out as is
+    test_env.run_jj_in(".", ["git", "init", "other"]).success();
+    let other_dir = test_env.work_dir("other");
+    other_dir
+        .run_jj(["git", "remote", "add", "origin", "../remote.git"])
+        .success();
+    let output = other_dir.run_jj(["git", "fetch"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    bookmark: main@origin [new] tracked
+    Downloaded 1 LFS objects from remote origin
+    [EOF]
+    ");
+    other_dir.run_jj(["new", "main"]).success();
+    insta::assert_snapshot!(other_dir.read_file("large.bin"), @"
+    version https://git-lfs.github.com/spec/v1
+    oid sha256:7004f11b91b38ea3219d0b2fb175e673cee200839aa75c1c8434e8693ebfab0b
+    size 15
+    ");
+    let output = other_dir.run_jj(["diff", "--summary"]);
+    insta::assert_snapshot!(output, @"");
+}
+
+#[test]
+fn test_git_lfs_push_to_empty_remote() {
+    let test_env = TestEnvironment::default();
+    let remote_path = test_env.env_root().join("remote.git");
+    git::init_bare(&remote_path);
+
+    test_env.run_jj_in(".", ["git", "init", "local"]).success();
+    let local_dir = test_env.work_dir("local");
+    local_dir
+        .run_jj(["git", "remote", "add", "origin", "../remote.git"])
+        .success();
+    local_dir.write_file(".gitattributes", "*.bin filter=lfs\n");
+    local_dir.write_file("first.bin", "first contents\n");
+    local_dir.run_jj(["commit", "-m=first"]).success();
+    local_dir.write_file("second.bin", "second contents\n");
+    local_dir.run_jj(["commit", "-m=second"]).success();
+    local_dir
+        .run_jj(["bookmark", "create", "-r@-", "main"])
+        .success();
+
+    // Objects of all ancestors of the new bookmark are uploaded
+    let output = local_dir.run_jj(["git", "push", "--bookmark=main"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Changes to push to origin:
+      Add bookmark main to fc832b245f24
+    Uploaded 2 LFS objects to remote origin
+    [EOF]
+    ");
+    insta::assert_snapshot!(lfs_object_paths(&remote_path.join("lfs")).join("\n"), @"
+    af/c3/afc3bfa73bc4059bf90ac4be9fad82369e05891f14364674c774f5a4443d9c56
+    f5/4c/f54c41e7f9ba48e828d15cc151c3cf35b84fb0dbc5d4ec9d3d76bf7d5ef8c5c5
+    ");
+}
+
+#[test]
+fn test_git_lfs_nested_gitattributes() {
+    let test_env = TestEnvironment::default();
+    let remote_path = test_env.env_root().join("remote.git");
+    git::init_bare(&remote_path);
+
+    test_env.run_jj_in(".", ["git", "init", "local"]).success();
+    let local_dir = test_env.work_dir("local");
+    local_dir
+        .run_jj(["git", "remote", "add", "origin", "../remote.git"])
+        .success();
+    local_dir.write_file(".gitattributes", "# *.bin filter=lfs\n");
+    local_dir.write_file("top.bin", "top contents\n");
+    local_dir.write_file("sub/.gitattributes", "*.bin filter=lfs\n");
+    local_dir.write_file("sub/large.bin", "large contents\n");
+    local_dir.run_jj(["commit", "-m=add files"]).success();
+    local_dir
+        .run_jj(["bookmark", "create", "-r@-", "main"])
+        .success();
+
+    // Only the file in the subdirectory is stored in LFS
+    let output = local_dir.run_jj(["file", "show", "-r@-", "top.bin"]);
+    insta::assert_snapshot!(output, @"
+    top contents
+    [EOF]
+    ");
+    let output = local_dir.run_jj(["file", "show", "-r@-", "sub/large.bin"]);
+    insta::assert_snapshot!(output, @"
+    version https://git-lfs.github.com/spec/v1
+    oid sha256:7004f11b91b38ea3219d0b2fb175e673cee200839aa75c1c8434e8693ebfab0b
+    size 15
+    [EOF]
+    ");
+
+    let output = local_dir.run_jj(["git", "push", "--bookmark=main"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Changes to push to origin:
+      Add bookmark main to e320f1a17681
+    Uploaded 1 LFS objects to remote origin
+    [EOF]
+    ");
+    insta::assert_snapshot!(lfs_object_paths(&remote_path.join("lfs")).join("\n"), @"70/04/7004f11b91b38ea3219d0b2fb175e673cee200839aa75c1c8434e8693ebfab0b");
+}
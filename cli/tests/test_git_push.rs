@@ -2648,6 +2648,177 @@ fn test_git_push_rejected_by_remote() {
     });
 }
 
+#[cfg(unix)]
+#[test]
+fn test_git_push_options() {
+    let test_env = TestEnvironment::default();
+    set_up(&test_env);
+    let work_dir = test_env.work_dir("local");
+    let origin_git_repo_path = git_repo_dir_for_jj_repo(&test_env.work_dir("origin"));
+
+    // Print the push options received by the remote
+    {
+        use std::io::Write as _;
+        let mut config_file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(origin_git_repo_path.join("config"))
+            .unwrap();
+        writeln!(config_file, "[receive]\n\tadvertisePushOptions = true").unwrap();
+    }
+    let hooks_dir = origin_git_repo_path.join("hooks");
+    std::fs::create_dir_all(&hooks_dir).unwrap();
+    let hook_path = hooks_dir.join("pre-receive");
+    std::fs::write(
+        &hook_path,
+        indoc! {r#"
+            #!/bin/sh
+            i=0
+            while [ "$i" -lt "${GIT_PUSH_OPTION_COUNT:-0}" ]; do
+                eval "echo option: \$GIT_PUSH_OPTION_$i"
+                i=$((i + 1))
+            done
+        "#},
+    )
+    .unwrap();
+    {
+        use std::os::unix::fs::PermissionsExt as _;
+        std::fs::set_permissions(&hook_path, std::fs::Permissions::from_mode(0o700)).unwrap();
+    }
+
+    // Push options from the command line
+    work_dir.run_jj(["new", "bookmark1", "-m=first"]).success();
+    work_dir
+        .run_jj(["bookmark", "set", "bookmark1", "-r@"])
+        .success();
+    let output = work_dir.run_jj([
+        "git",
+        "push",
+        "-bbookmark1",
+        "-o=merge_request.create",
+        "--option=ci.skip",
+    ]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Changes to push to origin:
+      Move forward bookmark bookmark1 from 9b2e76de3920 to 91655339928e
+    remote: option: merge_request.create        
+    remote: option: ci.skip        
+    [EOF]
+    ");
+
+    // Push options from the config
+    test_env.add_config(r#"git.push-options = ["from-config"]"#);
+    work_dir.run_jj(["new", "-m=second"]).success();
+    work_dir
+        .run_jj(["bookmark", "set", "bookmark1", "-r@"])
+        .success();
+    let output = work_dir.run_jj(["git", "push", "-bbookmark1"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Changes to push to origin:
+      Move forward bookmark bookmark1 from 91655339928e to 7cd33addaa03
+    remote: option: from-config        
+    [EOF]
+    ");
+
+    // The command line overrides the config
+    work_dir.run_jj(["new", "-m=third"]).success();
+    work_dir
+        .run_jj(["bookmark", "set", "bookmark1", "-r@"])
+        .success();
+    let output = work_dir.run_jj(["git", "push", "-bbookmark1", "-o=from-arg"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Changes to push to origin:
+      Move forward bookmark bookmark1 from 7cd33addaa03 to 0c73ef555298
+    remote: option: from-arg        
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_git_push_atomic() {
+    let test_env = TestEnvironment::default();
+    set_up(&test_env);
+    let work_dir = test_env.work_dir("local");
+    let origin_dir = test_env.work_dir("origin");
+    let origin_git_repo = git::open(git_repo_dir_for_jj_repo(&origin_dir));
+    // The remote's jj view isn't updated by pushes, so check its Git refs
+    let get_remote_bookmarks = || {
+        ["bookmark1", "bookmark2"]
+            .map(|name| {
+                let reference = origin_git_repo
+                    .find_reference(&format!("refs/heads/{name}"))
+                    .unwrap();
+                format!("{name}: {}\n", reference.id().to_hex_with_len(12))
+            })
+            .concat()
+    };
+
+    // Move bookmark2 on the remote, so that the push of it will be rejected
+    origin_dir
+        .run_jj([
+            "bookmark",
+            "set",
+            "bookmark2",
+            "-r=bookmark1",
+            "--allow-backwards",
+        ])
+        .success();
+    origin_dir.run_jj(["git", "export"]).success();
+    work_dir.run_jj(["new", "bookmark1", "-m=new"]).success();
+    work_dir
+        .run_jj([
+            "bookmark",
+            "set",
+            "bookmark1",
+            "bookmark2",
+            "-r@",
+            "--allow-backwards",
+        ])
+        .success();
+
+    // Neither bookmark is updated with --atomic
+    let output = work_dir.run_jj(["git", "push", "--atomic", "--all"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Changes to push to origin:
+      Move forward bookmark bookmark1 from 9b2e76de3920 to 8316b1d1e3cc
+      Move sideways bookmark bookmark2 from 38a204733702 to 8316b1d1e3cc
+    Warning: The following references unexpectedly moved on the remote:
+      refs/heads/bookmark2 (reason: stale info)
+    Hint: Try fetching from the remote, then make the bookmark point to where you want it to be, and push again.
+    Warning: The following references were not pushed because the atomic push failed:
+      refs/heads/bookmark1
+    Error: Failed to push some bookmarks
+    [EOF]
+    [exit status: 1]
+    ");
+    insta::assert_snapshot!(get_remote_bookmarks(), @"
+    bookmark1: 9b2e76de3920
+    bookmark2: 9b2e76de3920
+    ");
+
+    // Without --atomic, bookmark1 is updated even though bookmark2 is rejected
+    let output = work_dir.run_jj(["git", "push", "--all"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Changes to push to origin:
+      Move forward bookmark bookmark1 from 9b2e76de3920 to 8316b1d1e3cc
+      Move sideways bookmark bookmark2 from 38a204733702 to 8316b1d1e3cc
+    Warning: The following references unexpectedly moved on the remote:
+      refs/heads/bookmark2 (reason: stale info)
+    Hint: Try fetching from the remote, then make the bookmark point to where you want it to be, and push again.
+    Error: Failed to push some bookmarks
+    [EOF]
+    [exit status: 1]
+    ");
+    insta::assert_snapshot!(get_remote_bookmarks(), @"
+    bookmark1: 8316b1d1e3cc
+    bookmark2: 9b2e76de3920
+    ");
+}
+
 #[test]
 fn test_git_push_unmapped_refs() {
     let test_env = TestEnvironment::default();
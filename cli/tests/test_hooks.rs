@@ -0,0 +1,246 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use testutils::git;
+
+use crate::common::TestEnvironment;
+
+#[cfg(unix)]
+#[test]
+fn test_pre_commit_hook() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    test_env.add_config(
+        r#"hooks.pre-commit = ["sh", "-c", "echo checking $change_id; test ! -e secret"]"#,
+    );
+
+    // The hook can reject the commit
+    work_dir.write_file("secret", "password\n");
+    let output = work_dir.run_jj(["commit", "-m=add secret"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    checking qpvuntsmwlqtpsluzzsnyyzlmlwvmlnu
+    Error: The pre-commit hook failed (exit status: 1)
+    Hint: The hook command was `sh -c echo checking $change_id; test ! -e secret`
+    [EOF]
+    [exit status: 1]
+    ");
+
+    work_dir.remove_file("secret");
+    work_dir.write_file("file", "contents\n");
+    let output = work_dir.run_jj(["commit", "-m=add file"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    checking qpvuntsmwlqtpsluzzsnyyzlmlwvmlnu
+    Working copy  (@) now at: kkmpptxz 9ff58c6d (empty) (no description set)
+    Parent commit (@-)      : qpvuntsm 722c62c3 add file
+    [EOF]
+    ");
+
+    // Other commands don't run the hook
+    work_dir.write_file("secret", "password\n");
+    let output = work_dir.run_jj(["describe", "-m=add secret"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Working copy  (@) now at: kkmpptxz 85c3882d add secret
+    Parent commit (@-)      : qpvuntsm 722c62c3 add file
+    [EOF]
+    ");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_commit_msg_hook() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    test_env.add_config(
+        r#"hooks.commit-msg = [
+            "sh", "-c", "! grep -q WIP $message_file && echo 'Reviewed-by: Test User' >> $message_file"
+        ]"#,
+    );
+
+    // The hook can reject the description
+    let output = work_dir.run_jj(["describe", "-m=WIP: first"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Error: The commit-msg hook failed (exit status: 1)
+    Hint: The hook command was `sh -c ! grep -q WIP $message_file && echo 'Reviewed-by: Test User' >> $message_file`
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // The hook can rewrite the description
+    let output = work_dir.run_jj(["describe", "-m=first"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Working copy  (@) now at: qpvuntsm 5b2b0747 (empty) first
+    Parent commit (@-)      : zzzzzzzz 00000000 (empty) (no description set)
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["log", "-r@", "-T=description"]);
+    insta::assert_snapshot!(output, @"
+    @  first
+    │  Reviewed-by: Test User
+    ~
+    [EOF]
+    ");
+
+    // Unchanged descriptions aren't passed to the hook
+    let output = work_dir.run_jj(["describe", "-m=first\nReviewed-by: Test User"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Nothing changed.
+    [EOF]
+    ");
+
+    // Also runs on jj commit and jj new
+    let output = work_dir.run_jj(["commit", "-m=WIP: second"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Error: The commit-msg hook failed (exit status: 1)
+    Hint: The hook command was `sh -c ! grep -q WIP $message_file && echo 'Reviewed-by: Test User' >> $message_file`
+    [EOF]
+    [exit status: 1]
+    ");
+    let output = work_dir.run_jj(["new", "-m=third"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Working copy  (@) now at: yqosqzyt 086ff523 (empty) third
+    Parent commit (@-)      : qpvuntsm 5b2b0747 (empty) first
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["log", "-T=description"]);
+    insta::assert_snapshot!(output, @"
+    @  third
+    │  Reviewed-by: Test User
+    ○  first
+    │  Reviewed-by: Test User
+    ◆
+    [EOF]
+    ");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_pre_push_hook() {
+    let test_env = TestEnvironment::default();
+    let remote_path = test_env.env_root().join("remote.git");
+    git::init_bare(&remote_path);
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    work_dir
+        .run_jj(["git", "remote", "add", "origin", "../remote.git"])
+        .success();
+    work_dir.write_file("file", "a\n");
+    work_dir.run_jj(["commit", "-m=a"]).success();
+    work_dir.write_file("file", "b\n");
+    work_dir.run_jj(["commit", "-m=b"]).success();
+    work_dir
+        .run_jj(["bookmark", "create", "-r@-", "main"])
+        .success();
+
+    // The hook can reject the push
+    test_env.add_config(
+        r#"hooks.pre-push = ["sh", "-c", "echo pushing to $remote; cat > ../pushed; exit 1"]"#,
+    );
+    let output = work_dir.run_jj(["git", "push", "--bookmark=main"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Changes to push to origin:
+      Add bookmark main to b0878eff4326
+    pushing to origin
+    Error: The pre-push hook failed (exit status: 1)
+    Hint: The hook command was `sh -c echo pushing to $remote; cat > ../pushed; exit 1`
+    [EOF]
+    [exit status: 1]
+    ");
+    insta::assert_snapshot!(test_env.work_dir("").read_file("pushed"), @"
+    b0878eff4326ba1ddb6acce77059b45a78307519
+    0b9cba2f36970168c648f66fb0aa2a3eb92b9d9d
+    ");
+    let output = work_dir.run_jj(["bookmark", "list", "--all-remotes"]);
+    insta::assert_snapshot!(output, @"
+    main: kkmpptxz b0878eff b
+    [EOF]
+    ");
+
+    test_env.add_config(r#"hooks.pre-push = ["sh", "-c", "cat > ../pushed"]"#);
+    let output = work_dir.run_jj(["git", "push", "--bookmark=main"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Changes to push to origin:
+      Add bookmark main to b0878eff4326
+    [EOF]
+    ");
+
+    // Only the new commits are passed to the hook
+    work_dir.run_jj(["describe", "-m=c"]).success();
+    work_dir
+        .run_jj(["bookmark", "set", "-r@", "main"])
+        .success();
+    work_dir
+        .run_jj(["git", "push", "--bookmark=main"])
+        .success();
+    insta::assert_snapshot!(test_env.work_dir("").read_file("pushed"), @"93f26dc4cdf85cad36963212ed57efe06e196cf3");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_pre_push_hook_gerrit_upload() {
+    let test_env = TestEnvironment::default();
+    let remote_path = test_env.env_root().join("remote.git");
+    git::init_bare(&remote_path);
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    work_dir
+        .run_jj(["git", "remote", "add", "origin", "../remote.git"])
+        .success();
+    work_dir.write_file("file", "a\n");
+    work_dir.run_jj(["commit", "-m=a"]).success();
+    test_env.add_config(r#"hooks.pre-push = ["sh", "-c", "cat; exit 1"]"#);
+
+    // The commits with the Change-Id trailers are passed to the hook
+    let output = work_dir.run_jj([
+        "gerrit",
+        "upload",
+        "-r@-",
+        "--remote=origin",
+        "--remote-branch=main",
+    ]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Found 1 heads to push to Gerrit (remote 'origin'), target branch 'main'
+    484a3e1cfe4bb51d58008f185d6c36e4473fd851
+    Error: The pre-push hook failed (exit status: 1)
+    Hint: The hook command was `sh -c cat; exit 1`
+    [EOF]
+    [exit status: 1]
+    ");
+    let output = work_dir.run_jj([
+        "gerrit",
+        "upload",
+        "-r@-",
+        "--remote=origin",
+        "--remote-branch=main",
+        "--dry-run",
+    ]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Found 1 heads to push to Gerrit (remote 'origin'), target branch 'main'
+    Dry-run: Would push qpvuntsm 0b9cba2f a
+    [EOF]
+    ");
+}
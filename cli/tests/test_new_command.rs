@@ -12,11 +12,14 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use indoc::indoc;
+
 use crate::common::CommandOutput;
 use crate::common::TestEnvironment;
 use crate::common::TestWorkDir;
 use crate::common::create_commit;
 use crate::common::create_commit_with_files;
+use crate::common::to_toml_value;
 
 #[test]
 fn test_new() {
@@ -1006,6 +1009,128 @@ fn setup_before_insertion(work_dir: &TestWorkDir) {
         .success();
 }
 
+#[test]
+fn test_new_merge_with_merge_drivers() {
+    let test_env = TestEnvironment::default();
+    let driver_path = assert_cmd::cargo::cargo_bin!("fake-formatter");
+    test_env.add_config(format!(
+        "merge-drivers.resolve.command = {}\nmerge-drivers.fail.command = {}",
+        to_toml_value(toml_edit::Array::from_iter([
+            driver_path.to_str().unwrap(),
+            "--stdout=resolved by driver\n",
+            "--tee=$left",
+        ])),
+        to_toml_value(toml_edit::Array::from_iter([
+            driver_path.to_str().unwrap(),
+            "--fail",
+        ])),
+    ));
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    let files = ["union", "ours", "binary", "driver", "failing", "undefined"];
+    let attributes = indoc! {"
+        union merge=union
+        ours merge=ours
+        binary -merge
+        driver merge=resolve
+        failing merge=fail
+        undefined merge=undefined
+    "};
+    let with_content = |content: &'static str| files.map(|file| (file, content));
+    create_commit_with_files(
+        &work_dir,
+        "base",
+        &[],
+        &[
+            &with_content("a\nbase\n")[..],
+            &[(".gitattributes", attributes)],
+        ]
+        .concat(),
+    );
+    create_commit_with_files(&work_dir, "left", &["base"], &with_content("a\nleft\n"));
+    create_commit_with_files(&work_dir, "right", &["base"], &with_content("a\nright\n"));
+
+    // Merging with the attributes of the base, since the sides don't change
+    // them
+    let output = work_dir.run_jj(["new", "left", "right"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Working copy  (@) now at: vruxwmqv a35fbbbc (conflict) (empty) (no description set)
+    Parent commit (@-)      : zsuskuln cbd561f5 left | left
+    Parent commit (@-)      : royxmykx 9abe15e7 right | right
+    Added 0 files, modified 6 files, removed 0 files
+    Warning: There are unresolved conflicts at these paths:
+    binary       2-sided conflict
+    failing      2-sided conflict
+    undefined    2-sided conflict
+    New conflicts appeared in 1 commits:
+      vruxwmqv a35fbbbc (conflict) (empty) (no description set)
+    Hint: To resolve the conflicts, start by creating a commit on top of
+    the conflicted commit:
+      jj new vruxwmqv
+    Then use `jj resolve`, or edit the conflict markers in the file directly.
+    Once the conflicts are resolved, you can inspect the result with `jj diff`.
+    Then run `jj squash` to move the resolution into the conflicted commit.
+    [EOF]
+    ");
+    insta::assert_snapshot!(work_dir.read_file("union"), @"
+    a
+    left
+    right
+    ");
+    insta::assert_snapshot!(work_dir.read_file("ours"), @"
+    a
+    left
+    ");
+    insta::assert_snapshot!(work_dir.read_file("driver"), @"
+    a
+    left
+    resolved by driver
+    ");
+    insta::assert_snapshot!(work_dir.read_file("undefined"), @r#"
+    a
+    <<<<<<< conflict 1 of 1
+    %%%%%%% diff from: rlvkpnrz dcc58e4f "base"
+    \\\\\\\        to: zsuskuln cbd561f5 "left"
+    -base
+    +left
+    +++++++ royxmykx 9abe15e7 "right"
+    right
+    >>>>>>> conflict 1 of 1 ends
+    "#);
+
+    // A driver which can't be run is reported
+    work_dir.run_jj(["new", "base"]).success();
+    let output = work_dir.run_jj([
+        "new",
+        "left",
+        "right",
+        "--config=merge-drivers.fail.command=['nonexistent-merge-driver']",
+    ]);
+    if cfg!(unix) {
+        insta::assert_snapshot!(output, @"
+        ------- stderr -------
+        Error: Failed to merge failing with merge driver 'fail'
+        Caused by:
+        1: Failed to run merge driver 'nonexistent-merge-driver'
+        2: No such file or directory (os error 2)
+        [EOF]
+        [exit status: 1]
+        ");
+    } else if cfg!(windows) {
+        insta::assert_snapshot!(output, @"
+        ------- stderr -------
+        Error: Failed to merge failing with merge driver 'fail'
+        Caused by:
+        1: Failed to run merge driver 'nonexistent-merge-driver'
+        2: program not found
+        [EOF]
+        [exit status: 1]
+        ");
+    }
+}
+
 #[must_use]
 fn get_log_output(work_dir: &TestWorkDir) -> CommandOutput {
     let template = r#"commit_id ++ " " ++ description"#;
@@ -408,6 +408,12 @@ fn test_op_log_template() {
     [EOF]
     "#);
 
+    // JSON output serializes the same data, without the graph
+    let output = work_dir.run_jj(["op", "log", "--json"]);
+    let template_output =
+        work_dir.run_jj(["op", "log", "--no-graph", "-T", r#"json(self) ++ "\n""#]);
+    assert_eq!(output, template_output);
+
     // Test the default template, i.e. with relative start time and duration. We
     // don't generally use that template because it depends on the current time,
     // so we need to reset the time range format here.
@@ -974,6 +980,102 @@ fn test_op_abandon_multiple_heads() {
     ");
 }
 
+#[test]
+fn test_snapshot_compaction() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    let op_log = || {
+        work_dir.run_jj([
+            "op",
+            "log",
+            "--no-graph",
+            "-T",
+            r#"self.time().end() ++ " " ++ description ++ "\n""#,
+        ])
+    };
+
+    for i in 0..3 {
+        work_dir.write_file("file", i.to_string());
+        work_dir.run_jj(["util", "snapshot"]).success();
+    }
+    work_dir.run_jj(["describe", "-m", "description"]).success();
+    for i in 3..5 {
+        work_dir.write_file("file", i.to_string());
+        work_dir.run_jj(["util", "snapshot"]).success();
+    }
+
+    // Compaction is disabled by default
+    work_dir.write_file("file", "5");
+    work_dir.run_jj(["util", "snapshot"]).success();
+    insta::assert_snapshot!(op_log(), @"
+    2001-02-03 04:05:14.000 +07:00 snapshot working copy
+    2001-02-03 04:05:13.000 +07:00 snapshot working copy
+    2001-02-03 04:05:12.000 +07:00 snapshot working copy
+    2001-02-03 04:05:11.000 +07:00 describe commit 69f390c797b6066e780a7c526677f56afb192908
+    2001-02-03 04:05:10.000 +07:00 snapshot working copy
+    2001-02-03 04:05:09.000 +07:00 snapshot working copy
+    2001-02-03 04:05:08.000 +07:00 snapshot working copy
+    2001-02-03 04:05:07.000 +07:00 add workspace 'default'
+    1970-01-01 00:00:00.000 +00:00 
+    [EOF]
+    ");
+
+    // Only old snapshot operations are compacted
+    test_env.add_config(
+        r#"
+        snapshot.compaction.enabled = true
+        snapshot.compaction.older-than = "2001-02-03T04:05:14+07:00"
+        "#,
+    );
+    work_dir.write_file("file", "6");
+    let output = work_dir.run_jj(["util", "snapshot"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Compacted 3 old snapshot operations.
+    Snapshot complete.
+    [EOF]
+    ");
+    insta::assert_snapshot!(op_log(), @"
+    2001-02-03 04:05:16.000 +07:00 snapshot working copy
+    2001-02-03 04:05:14.000 +07:00 snapshot working copy
+    2001-02-03 04:05:13.000 +07:00 snapshot working copy
+    2001-02-03 04:05:11.000 +07:00 describe commit 69f390c797b6066e780a7c526677f56afb192908
+    2001-02-03 04:05:10.000 +07:00 snapshot working copy
+    2001-02-03 04:05:07.000 +07:00 add workspace 'default'
+    1970-01-01 00:00:00.000 +00:00 
+    [EOF]
+    ");
+
+    // Recent operations can still be undone one by one
+    work_dir.run_jj(["undo"]).success();
+    insta::assert_snapshot!(work_dir.run_jj(["file", "show", "file"]), @"
+    5[EOF]
+    ");
+
+    // Compaction runs at most once a day
+    work_dir.write_file("file", "7");
+    let output = work_dir.run_jj(["util", "snapshot"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Snapshot complete.
+    [EOF]
+    ");
+
+    // Invalid threshold
+    test_env.add_config(r#"snapshot.compaction.older-than = "foo""#);
+    work_dir.write_file("file", "8");
+    let output = work_dir.run_jj(["util", "snapshot"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Config error: Invalid `snapshot.compaction.older-than`
+    Caused by: expected unsupported identifier as position 0..3
+    For help, see https://docs.jj-vcs.dev/latest/config/ or use `jj help -k config`.
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
 #[test]
 fn test_op_recover_from_bad_gc() {
     let test_env = TestEnvironment::default();
@@ -751,12 +751,158 @@ fn test_too_many_parents() {
     Hint: Using default editor ':builtin'; run `jj config set --user ui.merge-editor :builtin` to disable this message.
     Error: Failed to resolve conflicts
     Caused by: The conflict at "file" has 3 sides. At most 2 sides are supported.
-    Hint: Edit the conflict markers manually to resolve this.
+    Hint: Use `jj resolve --editor` or edit the conflict markers manually to resolve this.
     [EOF]
     [exit status: 1]
     "#);
 }
 
+#[test]
+fn test_resolve_with_editor() {
+    let mut test_env = TestEnvironment::default();
+    let editor_script = test_env.set_up_fake_editor();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    create_commit_with_files(&work_dir, "base", &[], &[("file", "base\n")]);
+    create_commit_with_files(&work_dir, "a", &["base"], &[("file", "a\n")]);
+    create_commit_with_files(&work_dir, "b", &["base"], &[("file", "b\n")]);
+    create_commit_with_files(&work_dir, "c", &["base"], &[("file", "c\n")]);
+    create_commit_with_files(&work_dir, "conflict", &["a", "b", "c"], &[]);
+    create_commit_with_files(&work_dir, "child", &["conflict"], &[("other", "x\n")]);
+
+    // Exiting the editor without making changes aborts
+    std::fs::write(&editor_script, "dump editor").unwrap();
+    let output = work_dir.run_jj(["resolve", "--editor", "-r=conflict"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Resolving conflicts in: file
+    Error: Failed to resolve conflicts
+    Caused by: The output file is either unchanged or empty after the editor quit (run with --debug to see the exact invocation).
+    [EOF]
+    [exit status: 1]
+    ");
+    insta::assert_snapshot!(std::fs::read_to_string(test_env.env_root().join("editor")).unwrap(), @r#"
+    <<<<<<< conflict 1 of 1
+    %%%%%%% diff from: rlvkpnrz 1792382a "base"
+    \\\\\\\        to: zsuskuln 45537d53 "a"
+    -base
+    +a
+    %%%%%%% diff from: rlvkpnrz 1792382a "base"
+    \\\\\\\        to: royxmykx 89d1b299 "b"
+    -base
+    +b
+    +++++++ vruxwmqv a87962b8 "c"
+    c
+    >>>>>>> conflict 1 of 1 ends
+    "#);
+
+    // A 3-sided conflict in a non-working-copy commit can be resolved, and
+    // descendants are rebased
+    std::fs::write(&editor_script, "write\nresolved\n").unwrap();
+    let output = work_dir.run_jj(["resolve", "--editor", "-r=conflict"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Resolving conflicts in: file
+    Rebased 1 descendant commits
+    Working copy  (@) now at: kmkuslsw 74a464db child | child
+    Parent commit (@-)      : znkkpsqq dddc286e conflict | conflict
+    Added 0 files, modified 1 files, removed 0 files
+    Existing conflicts were resolved or abandoned from 1 commits.
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "show", "-r=conflict", "file"]);
+    insta::assert_snapshot!(output, @"
+    resolved
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["resolve", "--list"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Error: No conflicts found at this revision
+    [EOF]
+    [exit status: 2]
+    ");
+
+    // Conflict markers in the configured style are parsed back
+    work_dir.run_jj(["undo"]).success();
+    std::fs::write(
+        &editor_script,
+        indoc! {"
+            write
+            <<<<<<<
+            +++++++
+            a
+            -------
+            base
+            +++++++
+            b
+            -------
+            base
+            +++++++
+            resolved c
+            >>>>>>>
+        "},
+    )
+    .unwrap();
+    let output = work_dir.run_jj([
+        "resolve",
+        "--editor",
+        "-r=conflict",
+        "--config=ui.conflict-marker-style=snapshot",
+    ]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Resolving conflicts in: file
+    Rebased 1 descendant commits
+    Working copy  (@) now at: kmkuslsw dd86219c child | (conflict) child
+    Parent commit (@-)      : znkkpsqq f2c05a40 conflict | (conflict) conflict
+    Added 0 files, modified 1 files, removed 0 files
+    Warning: There are unresolved conflicts at these paths:
+    file    3-sided conflict
+    New conflicts appeared in 1 commits:
+      znkkpsqq f2c05a40 conflict | (conflict) conflict
+    Hint: To resolve the conflicts, start by creating a commit on top of
+    the first conflicted commit:
+      jj new znkkpsqq
+    Then use `jj resolve`, or edit the conflict markers in the file directly.
+    Once the conflicts are resolved, you can inspect the result with `jj diff`.
+    Then run `jj squash` to move the resolution into the conflicted commit.
+    Warning: After this operation, some files at this revision still have conflicts:
+    file    3-sided conflict
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "show", "-r=conflict", "file"]);
+    insta::assert_snapshot!(output, @r#"
+    <<<<<<< conflict 1 of 1
+    %%%%%%% diff from: rlvkpnrz 1792382a "base"
+    \\\\\\\        to: zsuskuln 45537d53 "a"
+    -base
+    +a
+    %%%%%%% diff from: rlvkpnrz 1792382a "base"
+    \\\\\\\        to: royxmykx 89d1b299 "b"
+    -base
+    +b
+    +++++++ vruxwmqv a87962b8 "c"
+    resolved c
+    >>>>>>> conflict 1 of 1 ends
+    [EOF]
+    "#);
+
+    // The editor can't be combined with a merge tool
+    let output = work_dir.run_jj(["resolve", "--editor", "--tool=:ours"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    error: the argument '--editor' cannot be used with '--tool <NAME>'
+
+    Usage: jj resolve --editor [FILESETS]...
+
+    For more information, try '--help'.
+    [EOF]
+    [exit status: 2]
+    ");
+}
+
 #[test]
 fn test_simplify_conflict_sides() {
     let mut test_env = TestEnvironment::default();
@@ -2312,3 +2458,91 @@ fn test_resolve_with_contents_of_side() {
     [exit status: 2]
     ");
 }
+
+#[test]
+fn test_resolve_reuses_recorded_resolution() {
+    let mut test_env = TestEnvironment::default();
+    let editor_script = test_env.set_up_fake_editor();
+    test_env.add_config("merge.rerere = true");
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    create_commit_with_files(
+        &work_dir,
+        "base",
+        &[],
+        &[("file1", "base\n"), ("file2", "base\n")],
+    );
+    create_commit_with_files(
+        &work_dir,
+        "a",
+        &["base"],
+        &[("file1", "a1\n"), ("file2", "a2\n")],
+    );
+    create_commit_with_files(
+        &work_dir,
+        "b",
+        &["base"],
+        &[("file1", "b1\n"), ("file2", "b2\n")],
+    );
+    create_commit_with_files(&work_dir, "conflict", &["a", "b"], &[]);
+
+    // Resolve one conflict with `jj resolve`
+    std::fs::write(&editor_script, "write\nresolution1\n").unwrap();
+    work_dir
+        .run_jj(["resolve", "--tool=fake-editor", "file1"])
+        .success();
+    // Resolve the other conflict in the working copy
+    work_dir.write_file("file2", "resolution2\n");
+    insta::assert_snapshot!(work_dir.run_jj(["util", "rerere", "list"]), @"
+    c0e22b3f50e0 file2
+    a3bd20fb1485 file1
+    [EOF]
+    ");
+
+    // The same conflicts are resolved when they appear again
+    let output = work_dir.run_jj(["new", "a", "b"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Resolved conflicts in file1 using previous resolutions
+    Resolved conflicts in file2 using previous resolutions
+    Working copy  (@) now at: kmkuslsw 275d7c6a (empty) (no description set)
+    Parent commit (@-)      : zsuskuln 9f699d79 a | a
+    Parent commit (@-)      : royxmykx b28188cf b | b
+    [EOF]
+    ");
+    insta::assert_snapshot!(work_dir.read_file("file1"), @"
+    resolution1
+    ");
+    insta::assert_snapshot!(work_dir.read_file("file2"), @"
+    resolution2
+    ");
+
+    // Forgotten resolutions are no longer used
+    let output = work_dir.run_jj(["util", "rerere", "forget", "zz"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Error: No recorded resolution with id zz
+    [EOF]
+    [exit status: 1]
+    ");
+    let output = work_dir.run_jj(["util", "rerere", "forget", "--all"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Forgot 2 recorded resolutions
+    [EOF]
+    ");
+    insta::assert_snapshot!(work_dir.run_jj(["util", "rerere", "list"]), @"");
+    let output = work_dir.run_jj(["new", "a", "b"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Working copy  (@) now at: nkmrtpmo 4ccaab95 (conflict) (empty) (no description set)
+    Parent commit (@-)      : zsuskuln 9f699d79 a | a
+    Parent commit (@-)      : royxmykx b28188cf b | b
+    Added 0 files, modified 2 files, removed 0 files
+    Warning: There are unresolved conflicts at these paths:
+    file1    2-sided conflict
+    file2    2-sided conflict
+    [EOF]
+    ");
+}
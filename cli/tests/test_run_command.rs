@@ -0,0 +1,216 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::common::CommandOutput;
+use crate::common::TestEnvironment;
+use crate::common::TestWorkDir;
+
+#[cfg(unix)]
+#[test]
+fn test_run_no_changes() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    work_dir.write_file("file", "a\n");
+    work_dir.run_jj(["commit", "-m=a"]).success();
+    work_dir.write_file("file", "b\n");
+    work_dir.run_jj(["describe", "-m=b"]).success();
+
+    let output = work_dir.run_jj(["run", "cat file", "-r=all() ~ root()", "-j=1"]);
+    insta::assert_snapshot!(output, @"
+    a
+    b
+    [EOF]
+    ------- stderr -------
+    Running on qpvuntsm 7febbd38 a
+    Running on rlvkpnrz ab6f28ce b
+    Ran command on 2 commits, 0 changed.
+    Nothing changed.
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_output(&work_dir), @"
+    @  ab6f28ce5179 b
+    ○  7febbd3832d7 a
+    ◆  000000000000
+    [EOF]
+    ");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_rewrites_stack() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    work_dir.write_file("a", "a\n");
+    work_dir.run_jj(["commit", "-m=a"]).success();
+    work_dir.write_file("b", "b\n");
+    work_dir.run_jj(["commit", "-m=b"]).success();
+    work_dir.write_file("c", "c\n");
+    work_dir.run_jj(["describe", "-m=c"]).success();
+
+    // Only the selected revisions are modified, but descendants are rebased on
+    // top of the changes.
+    let output = work_dir.run_jj([
+        "run",
+        "for f in *; do tr a-z A-Z < $f > $f.new && mv $f.new $f; done",
+        "-r=subject(a) | subject(c)",
+    ]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Running on qpvuntsm 5878e8dd a
+    Running on kkmpptxz 68d09474 c
+    Ran command on 2 commits, 2 changed.
+    Working copy  (@) now at: kkmpptxz 2f135072 c
+    Parent commit (@-)      : rlvkpnrz 64c7399d b
+    Added 0 files, modified 3 files, removed 0 files
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_output(&work_dir), @"
+    @  2f135072bf7c c
+    ○  64c7399df6f4 b
+    ○  fe13b4907677 a
+    ◆  000000000000
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "show", "glob:*", "-r=subject(b)"]);
+    insta::assert_snapshot!(output, @"
+    A
+    b
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "show", "glob:*", "-r=@"]);
+    insta::assert_snapshot!(output, @"
+    A
+    B
+    C
+    [EOF]
+    ");
+
+    // The whole run is a single operation
+    work_dir.run_jj(["undo"]).success();
+    let output = work_dir.run_jj(["file", "show", "glob:*", "-r=@"]);
+    insta::assert_snapshot!(output, @"
+    a
+    b
+    c
+    [EOF]
+    ");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_new_and_ignored_files() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    work_dir.write_file(".gitignore", "target\n");
+    work_dir.run_jj(["describe", "-m=a"]).success();
+
+    // New files are tracked, ignored files are left in the temporary working
+    // copy and reused by the next run.
+    work_dir
+        .run_jj([
+            "run",
+            "echo generated > gen && mkdir -p target && echo 1 >> target/cache",
+        ])
+        .success();
+    let output = work_dir.run_jj(["file", "list"]);
+    insta::assert_snapshot!(output, @"
+    .gitignore
+    gen
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["run", "cat target/cache"]);
+    insta::assert_snapshot!(output, @"
+    1
+    [EOF]
+    ------- stderr -------
+    Running on qpvuntsm be35c827 a
+    Ran command on 1 commit, 0 changed.
+    Nothing changed.
+    [EOF]
+    ");
+
+    // --clean removes the leftovers
+    let output = work_dir.run_jj(["run", "--clean", "ls target"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Running on qpvuntsm be35c827 a
+    ls: cannot access 'target': No such file or directory
+    Warning: Command `ls target` failed on qpvuntsm be35c827 a (exit status: 2)
+    Nothing changed.
+    Error: Command failed on 1 of 1 commits
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_failure() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    work_dir.write_file("file", "a\n");
+    work_dir.run_jj(["commit", "-m=a"]).success();
+    work_dir.write_file("file", "b\n");
+    work_dir.run_jj(["describe", "-m=b"]).success();
+
+    // Changes made by a failing command are discarded, but the other revisions
+    // are still rewritten.
+    let output = work_dir.run_jj([
+        "run",
+        "echo changed >> file; ! grep -qx a file",
+        "-r=all() ~ root()",
+        "-j=1",
+    ]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Running on qpvuntsm 7febbd38 a
+    Warning: Command `echo changed >> file; ! grep -qx a file` failed on qpvuntsm 7febbd38 a (exit status: 1)
+    Running on rlvkpnrz ab6f28ce b
+    Working copy  (@) now at: rlvkpnrz bbf43aae b
+    Parent commit (@-)      : qpvuntsm 7febbd38 a
+    Added 0 files, modified 1 files, removed 0 files
+    Error: Command failed on 1 of 2 commits
+    [EOF]
+    [exit status: 1]
+    ");
+    insta::assert_snapshot!(get_log_output(&work_dir), @"
+    @  bbf43aae72d1 b
+    ○  7febbd3832d7 a
+    ◆  000000000000
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_run_immutable() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    let output = work_dir.run_jj(["run", "true", "-r=root()"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Error: The root commit 000000000000 is immutable
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
+fn get_log_output(work_dir: &TestWorkDir) -> CommandOutput {
+    work_dir.run_jj(["log", "-T", "commit_id.short() ++ ' ' ++ description"])
+}
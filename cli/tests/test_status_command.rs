@@ -41,6 +41,41 @@ fn test_status_copies() {
     ");
 }
 
+#[test]
+fn test_status_json() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    test_env.add_config(r#"snapshot.auto-track = "none()""#);
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("file", "base");
+    work_dir.write_file("rename-source", "rename");
+    work_dir
+        .run_jj(["file", "track", "file", "rename-source"])
+        .success();
+    work_dir.run_jj(["new"]).success();
+    work_dir.write_file("file", "modified");
+    work_dir.remove_file("rename-source");
+    work_dir.write_file("rename-target", "rename");
+    work_dir.create_dir("untracked").write_file("file", "");
+    work_dir
+        .run_jj(["file", "track", "rename-target"])
+        .success();
+
+    let output = work_dir.run_jj(["status", "--json"]);
+    insta::assert_snapshot!(output, @r#"
+    {"working_copy":{"commit_id":"2bcc8f9c01a77d46ae6997e1e5c4a7b1653765b6","parents":["9c59dec95dfd8f0ae22908d511b504cdf7b7f1d9"],"change_id":"kkmpptxzrspxrzommnulwmwkkqwworpl","description":"","author":{"name":"Test User","email":"test.user@example.com","timestamp":"2001-02-03T04:05:10+07:00"},"committer":{"name":"Test User","email":"test.user@example.com","timestamp":"2001-02-03T04:05:11+07:00"}},"parents":[{"commit_id":"9c59dec95dfd8f0ae22908d511b504cdf7b7f1d9","parents":["0000000000000000000000000000000000000000"],"change_id":"qpvuntsmwlqtpsluzzsnyyzlmlwvmlnu","description":"","author":{"name":"Test User","email":"test.user@example.com","timestamp":"2001-02-03T04:05:09+07:00"},"committer":{"name":"Test User","email":"test.user@example.com","timestamp":"2001-02-03T04:05:09+07:00"}}],"changes":[{"status":"modified","path":"file"},{"status":"renamed","path":"rename-target","source":"rename-source"}],"untracked_paths":["untracked/"],"conflicts":[],"conflicted_bookmarks":[],"conflicted_remote_bookmarks":[]}
+    [EOF]
+    "#);
+
+    // Paths are filtered, but the commits are still included
+    let output = work_dir.run_jj(["status", "--json", "file"]);
+    insta::assert_snapshot!(output, @r#"
+    {"working_copy":{"commit_id":"2bcc8f9c01a77d46ae6997e1e5c4a7b1653765b6","parents":["9c59dec95dfd8f0ae22908d511b504cdf7b7f1d9"],"change_id":"kkmpptxzrspxrzommnulwmwkkqwworpl","description":"","author":{"name":"Test User","email":"test.user@example.com","timestamp":"2001-02-03T04:05:10+07:00"},"committer":{"name":"Test User","email":"test.user@example.com","timestamp":"2001-02-03T04:05:11+07:00"}},"parents":[{"commit_id":"9c59dec95dfd8f0ae22908d511b504cdf7b7f1d9","parents":["0000000000000000000000000000000000000000"],"change_id":"qpvuntsmwlqtpsluzzsnyyzlmlwvmlnu","description":"","author":{"name":"Test User","email":"test.user@example.com","timestamp":"2001-02-03T04:05:09+07:00"},"committer":{"name":"Test User","email":"test.user@example.com","timestamp":"2001-02-03T04:05:09+07:00"}}],"changes":[{"status":"modified","path":"file"}],"untracked_paths":["untracked/"],"conflicts":[],"conflicted_bookmarks":[],"conflicted_remote_bookmarks":[]}
+    [EOF]
+    "#);
+}
+
 #[test]
 fn test_status_merge() {
     let test_env = TestEnvironment::default();
@@ -60,10 +60,61 @@ fn test_gc_args() {
     let output = work_dir.run_jj(["util", "gc", "--expire=foobar"]);
     insta::assert_snapshot!(output, @"
     ------- stderr -------
-    Error: --expire only accepts 'now'
+    Error: Invalid --expire time: foobar
+    Caused by: expected unsupported identifier as position 0..6
     [EOF]
     [exit status: 1]
     ");
+
+    let output = work_dir.run_jj(["util", "gc", "--expire=tomorrow"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Error: --expire time must not be in the future: tomorrow
+    [EOF]
+    [exit status: 1]
+    ");
+
+    let output = work_dir.run_jj(["util", "gc", "--expire=2 weeks ago"]);
+    insta::assert_snapshot!(output, @"");
+
+    let output = work_dir.run_jj(["util", "gc", "--expire=2000-01-01"]);
+    insta::assert_snapshot!(output, @"");
+}
+
+#[test]
+fn test_gc_dry_run() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    // Create operations and a commit that will become unreachable.
+    work_dir.write_file("file", "a change\n");
+    work_dir.run_jj(["commit", "-m", "a change"]).success();
+    work_dir.run_jj(["abandon", "@-"]).success();
+    work_dir.run_jj(["operation", "abandon", "..@-"]).success();
+
+    let output = work_dir.run_jj(["util", "gc", "--expire=now", "--dry-run"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Would remove 4 operations, 2 commits, 4 index segments, and 2 extra metadata entries.
+    [EOF]
+    ");
+
+    // Nothing has been removed by the dry run
+    let output = work_dir.run_jj(["util", "gc", "--expire=now", "--dry-run"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Would remove 4 operations, 2 commits, 4 index segments, and 2 extra metadata entries.
+    [EOF]
+    ");
+
+    work_dir.run_jj(["util", "gc", "--expire=now"]).success();
+    let output = work_dir.run_jj(["util", "gc", "--expire=now", "--dry-run"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Would remove 0 operations, 0 commits, 0 index segments, and 0 extra metadata entries.
+    [EOF]
+    ");
 }
 
 #[test]
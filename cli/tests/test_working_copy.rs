@@ -467,8 +467,9 @@ fn test_conflict_marker_length_stored_in_working_copy() {
 }
 
 #[test]
-fn test_submodule_ignored() {
+fn test_submodule() {
     let test_env = TestEnvironment::default();
+    test_env.add_config("git.update-submodules = true");
 
     test_env
         .run_jj_in(".", ["git", "init", "--colocate", "submodule"])
@@ -496,7 +497,7 @@ fn test_submodule_ignored() {
         "protocol.file.allow=always",
         "submodule",
         "add",
-        &format!("{}/submodule", test_env.env_root().display()),
+        "../submodule",
         "sub",
     ]);
     insta::assert_snapshot!(output, @"
@@ -505,34 +506,112 @@ fn test_submodule_ignored() {
     done.
     [EOF]
     ");
-    // Use git to commit since jj won't play nice with the submodule.
+    // Commit with a fixed date to get stable commit ids.
     work_dir
-        .run_jj([
-            "util",
-            "exec",
-            "--",
-            "git",
-            "-c",
-            "user.email=test@example.com",
-            "-c",
-            "user.name=Test user",
-            "commit",
-            "-m",
-            "Add submodule",
-        ])
+        .run_jj_with(|cmd| {
+            cmd.env("GIT_AUTHOR_DATE", "2001-02-03T04:05:06+07:00")
+                .env("GIT_COMMITTER_DATE", "2001-02-03T04:05:06+07:00")
+                .args([
+                    "util",
+                    "exec",
+                    "--",
+                    "git",
+                    "-c",
+                    "user.email=test@example.com",
+                    "-c",
+                    "user.name=Test user",
+                    "commit",
+                    "-m",
+                    "Add submodule",
+                ])
+        })
         .success();
 
-    // This should be empty. We shouldn't track the submodule itself.
+    // The submodule checked out at the recorded commit isn't a change.
     let output = work_dir.run_jj(["diff", "--summary"]);
-    insta::assert_snapshot!(output, @r#"
+    insta::assert_snapshot!(output, @"
     ------- stderr -------
-    ignoring git submodule at "sub"
     Done importing changes from the underlying Git repo.
     [EOF]
+    ");
+
+    // Check out another commit in the submodule
+    submodule_dir.write_file("sub", "sub2");
+    submodule_dir
+        .run_jj(["commit", "-m", "Submodule commit 2"])
+        .success();
+    let output = work_dir.run_jj([
+        "util", "exec", "--", "git", "-C", "sub", "pull", "--quiet", "origin", "HEAD",
+    ]);
+    insta::assert_snapshot!(output, @"");
+    let output = work_dir.run_jj(["status"]);
+    insta::assert_snapshot!(output, @"
+    Working copy changes:
+    M sub
+    Working copy  (@) : royxmykx ee84a119 (no description set)
+    Parent commit (@-): yqmkmkrn 4b4c9dbf master | Add submodule
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["diff", "--git"]);
+    insta::assert_snapshot!(output, @"
+    diff --git a/sub b/sub
+    index e544d06371..aa891f95c2 160000
+    --- a/sub
+    +++ b/sub
+    @@ -1,1 +1,1 @@
+    -Subproject commit e544d06371671bfcc3f5ec0a72054212ceb47e9b
+    +Subproject commit aa891f95c2cf22131f167483242d54366d4153f1
+    [EOF]
+    ");
+
+    // Checking out the parent restores the recorded commit
+    let output = work_dir.run_jj(["new", "@-"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Checked out submodule sub at e544d0637167
+    Working copy  (@) now at: kpqxywon 5a5d7c36 (empty) (no description set)
+    Parent commit (@-)      : yqmkmkrn 4b4c9dbf master | Add submodule
+    Added 0 files, modified 1 files, removed 0 files
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["diff", "--summary"]);
+    insta::assert_snapshot!(output, @"");
+    insta::assert_snapshot!(work_dir.read_file("sub/sub"), @"sub");
+
+    // The submodule is cloned when checked out in a new workspace
+    let output = work_dir.run_jj(["workspace", "add", "../secondary", "-r", "master"]);
+    insta::assert_snapshot!(output, @r#"
+    ------- stderr -------
+    Created workspace in "../secondary"
+    Checked out submodule ../secondary/sub at e544d0637167
+    Working copy  (@) now at: mouksmqu 2acdce62 (empty) (no description set)
+    Parent commit (@-)      : yqmkmkrn 4b4c9dbf master | Add submodule
+    Added 2 files, modified 0 files, removed 0 files
+    [EOF]
+    "#);
+    insta::assert_snapshot!(test_env.work_dir("secondary").read_file("sub/sub"), @"sub");
+
+    // Submodules aren't touched if git.update-submodules is disabled
+    let output = work_dir.run_jj([
+        "workspace",
+        "add",
+        "../third",
+        "-r",
+        "master",
+        "--config=git.update-submodules=false",
+    ]);
+    insta::assert_snapshot!(output, @r#"
+    ------- stderr -------
+    Created workspace in "../third"
+    Working copy  (@) now at: rsllmpnm e3bf1553 (empty) (no description set)
+    Parent commit (@-)      : yqmkmkrn 4b4c9dbf master | Add submodule
+    Added 2 files, modified 0 files, removed 0 files
+    [EOF]
     "#);
+    assert!(!test_env.work_dir("third").root().join("sub/sub").exists());
 
     // Switch to a historical commit before the submodule was checked in.
-    work_dir.run_jj(["prev"]).success();
+    work_dir.run_jj(["new", "root()+"]).success();
     // jj new (or equivalently prev) should always leave you with an empty working
     // copy.
     let output = work_dir.run_jj(["diff", "--summary"]);
@@ -1602,6 +1602,11 @@ fn test_list_workspaces_template() {
     second: 058f604dffcd
     [EOF]
     ");
+
+    // JSON output serializes the same data as the json() template function
+    let output = main_dir.run_jj(["workspace", "list", "--json"]);
+    let template_output = main_dir.run_jj(["workspace", "list", "-T", r#"json(self) ++ "\n""#]);
+    assert_eq!(output, template_output);
 }
 
 /// Test getting the workspace root from primary and secondary workspaces
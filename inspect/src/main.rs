@@ -1,7 +1,9 @@
+mod repo;
+
 use std::collections::HashMap;
 use std::io::{self, Stdout, Write};
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Command, Stdio};
 use std::time::Duration;
 
 use anyhow::{Context, Result, bail};
@@ -16,20 +18,7 @@ use crossterm::{
 use serde::Deserialize;
 use unicode_width::UnicodeWidthStr;
 
-const EMPTY_TREE_HASH: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
-
-#[derive(Debug, Clone)]
-struct CommitItem {
-    id: String,
-    summary: String,
-}
-
-#[derive(Debug, Clone)]
-struct FileItem {
-    status: String,
-    path: String,
-    original_path: Option<String>,
-}
+use crate::repo::{CommitItem, DiffLine, DiffLineKind, FileItem, RepoView};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ViewMode {
@@ -37,17 +26,18 @@ enum ViewMode {
     Queue,
 }
 
-#[derive(Debug)]
 struct AppState {
-    repo: PathBuf,
+    repo_path: PathBuf,
+    repo: RepoView,
     base_revset: String,
     mode: ViewMode,
+    limit: usize,
     commits: Vec<CommitItem>,
     commit_index: usize,
     file_index: usize,
     diff_scroll: usize,
     files_cache: HashMap<String, Vec<FileItem>>,
-    diff_cache: HashMap<String, Vec<String>>,
+    diff_cache: HashMap<String, Vec<DiffLine>>,
     status: String,
     input_mode: bool,
     input_buffer: String,
@@ -63,9 +53,15 @@ struct CommitQueueEntry {
 }
 
 fn main() -> Result<()> {
-    let (repo, base_revset, limit, mode) = parse_args()?;
-    let mut app = AppState::new(repo, base_revset, mode)?;
-    app.refresh(limit)?;
+    let (repo_path, base_revset, limit, mode) = parse_args()?;
+    let repo = RepoView::load(&repo_path)?;
+    let base_revset = if base_revset.is_empty() && mode == ViewMode::Stack {
+        resolve_default_base(&repo)
+    } else {
+        base_revset
+    };
+    let mut app = AppState::new(repo_path, repo, base_revset, mode, limit);
+    app.refresh()?;
     if app.commits.is_empty() {
         println!("No commits found.");
         return Ok(());
@@ -105,10 +101,6 @@ fn parse_args() -> Result<(PathBuf, String, usize, ViewMode)> {
         }
     }
 
-    if base_revset.is_empty() && mode == ViewMode::Stack {
-        base_revset = resolve_default_base(&repo)?;
-    }
-
     Ok((repo, base_revset, limit, mode))
 }
 
@@ -122,11 +114,19 @@ Modes:\n  --queue        Show Flow commit-queue entries (from .ai/internal/commi
 }
 
 impl AppState {
-    fn new(repo: PathBuf, base_revset: String, mode: ViewMode) -> Result<Self> {
-        Ok(Self {
+    fn new(
+        repo_path: PathBuf,
+        repo: RepoView,
+        base_revset: String,
+        mode: ViewMode,
+        limit: usize,
+    ) -> Self {
+        Self {
+            repo_path,
             repo,
             base_revset,
             mode,
+            limit,
             commits: Vec::new(),
             commit_index: 0,
             file_index: 0,
@@ -136,13 +136,14 @@ impl AppState {
             status: String::new(),
             input_mode: false,
             input_buffer: String::new(),
-        })
+        }
     }
 
-    fn refresh(&mut self, limit: usize) -> Result<()> {
+    fn refresh(&mut self) -> Result<()> {
+        self.repo.reload()?;
         let commits = match self.mode {
-            ViewMode::Stack => load_stack_commits(&self.repo, &self.base_revset, limit)?,
-            ViewMode::Queue => load_queue_commits(&self.repo, limit)?,
+            ViewMode::Stack => self.repo.stack_commits(&self.base_revset, self.limit)?,
+            ViewMode::Queue => load_queue_commits(&self.repo_path, &self.repo, self.limit)?,
         };
         self.commits = commits;
         self.commit_index = 0;
@@ -171,20 +172,19 @@ impl AppState {
         self.files_cache.get(commit_id)
     }
 
-    fn ensure_files_loaded(&mut self) -> Result<()> {
+    fn ensure_files_loaded(&mut self) {
         let commit_id = match self.selected_commit() {
             Some(commit) => commit.id.clone(),
-            None => return Ok(()),
+            None => return,
         };
         if !self.files_cache.contains_key(&commit_id) {
-            let files = match self.mode {
-                ViewMode::Stack => load_stack_files(&self.repo, &commit_id)?,
-                ViewMode::Queue => load_queue_files(&self.repo, &commit_id)?,
-            };
+            let files = self.repo.files(&commit_id).unwrap_or_else(|err| {
+                self.status = format!("failed to load files: {err:#}");
+                Vec::new()
+            });
             self.files_cache.insert(commit_id, files);
             self.file_index = 0;
         }
-        Ok(())
     }
 
     fn move_file_selection(&mut self, delta: isize) {
@@ -208,7 +208,7 @@ impl AppState {
     }
 
     fn jump_file_bottom(&mut self) {
-        let len = self.files_for_selected_commit().map(|v| v.len()).unwrap_or(0);
+        let len = self.files_for_selected_commit().map_or(0, |v| v.len());
         if len > 0 {
             self.file_index = len - 1;
             self.diff_scroll = 0;
@@ -233,30 +233,23 @@ impl AppState {
         self.diff_scroll = next.max(0) as usize;
     }
 
-    fn diff_lines(&mut self, commit_id: &str, file: Option<&FileItem>) -> Result<Vec<String>> {
+    fn diff_lines(&mut self, commit_id: &str, file: Option<&FileItem>) -> Result<Vec<DiffLine>> {
         let key = match file {
             Some(item) => format!("{}::{}", commit_id, item.path),
             None => commit_id.to_string(),
         };
         if !self.diff_cache.contains_key(&key) {
-            let diff = match self.mode {
-                ViewMode::Stack => load_stack_diff(&self.repo, commit_id, file)?,
-                ViewMode::Queue => load_queue_diff(&self.repo, commit_id, file)?,
-            };
+            let diff = self.repo.diff(commit_id, file)?;
             self.diff_cache.insert(key.clone(), diff);
         }
-        Ok(self
-            .diff_cache
-            .get(&key)
-            .map(|v| v.clone())
-            .unwrap_or_default())
+        Ok(self.diff_cache.get(&key).cloned().unwrap_or_default())
     }
 
     fn run_command(&mut self, command: &str) {
         if command.trim().is_empty() {
             return;
         }
-        match run_shell(&self.repo, command) {
+        match run_shell(&self.repo_path, command) {
             Ok(output) => {
                 let line = output.lines().next().unwrap_or("ok");
                 self.status = format!("> {}", line);
@@ -285,15 +278,14 @@ fn run_tui(app: &mut AppState) -> Result<()> {
 
     let tick_rate = Duration::from_millis(120);
     loop {
-        app.ensure_files_loaded()?;
+        app.ensure_files_loaded();
         draw_ui(app, &mut stdout)?;
 
-        if event::poll(tick_rate)? {
-            if let Event::Key(key) = event::read()? {
-                if handle_key(app, key)? {
-                    break;
-                }
-            }
+        if event::poll(tick_rate)?
+            && let Event::Key(key) = event::read()?
+            && handle_key(app, key)?
+        {
+            break;
         }
     }
 
@@ -350,7 +342,7 @@ fn handle_key(app: &mut AppState, key: KeyEvent) -> Result<bool> {
         KeyCode::PageUp => app.scroll_diff(-10),
         KeyCode::Char('g') => app.jump_file_top(),
         KeyCode::Char('G') => app.jump_file_bottom(),
-        KeyCode::Char('r') => app.refresh(50)?,
+        KeyCode::Char('r') => app.refresh()?,
         KeyCode::Char(':') => {
             app.input_mode = true;
             app.input_buffer.clear();
@@ -359,7 +351,8 @@ fn handle_key(app: &mut AppState, key: KeyEvent) -> Result<bool> {
         KeyCode::Enter => {
             if let Some(commit) = app.selected_commit() {
                 let file = app.selected_file();
-                open_full_diff(&app.repo, &commit.id, file, app.mode)?;
+                let lines = app.repo.diff(&commit.id, file)?;
+                open_full_diff(&app.repo_path, &lines)?;
             }
         }
         KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(true),
@@ -377,7 +370,7 @@ fn draw_ui(app: &mut AppState, stdout: &mut Stdout) -> Result<()> {
     queue!(stdout, Clear(ClearType::All), cursor::MoveTo(0, 0))?;
 
     let header_left = match app.selected_commit() {
-        Some(commit) => format!("{} {}", short_id(&commit.id), commit.summary),
+        Some(commit) => format_commit_header(commit),
         None => "(no commit)".to_string(),
     };
     queue!(
@@ -401,23 +394,16 @@ fn draw_ui(app: &mut AppState, stdout: &mut Stdout) -> Result<()> {
         let mut left_line = String::new();
         if let Some(file) = files.get(row) {
             let prefix = if row == selected { "▸ " } else { "  " };
-            let display_path = if file.status.starts_with('R') {
-                if let Some(original) = file.original_path.as_ref() {
-                    format!("{} -> {}", original, file.path)
-                } else {
-                    file.path.clone()
-                }
-            } else {
-                file.path.clone()
+            let display_path = match file.original_path.as_ref() {
+                Some(original) => format!("{} -> {}", original, file.path),
+                None => file.path.clone(),
             };
             left_line = format!("{}{} {}", prefix, file.status, display_path);
         }
 
-        let diff_index = diff_scroll + row;
-        let mut diff_line = if diff_index < diff_lines.len() {
-            diff_lines[diff_index].replace('\t', "    ")
-        } else {
-            String::new()
+        let (diff_color, mut diff_line) = match diff_lines.get(diff_scroll + row) {
+            Some(line) => (diff_line_color(line.kind), line.text.replace('\t', "    ")),
+            None => (Color::Reset, String::new()),
         };
 
         left_line = truncate_to_width(&left_line, list_width as usize);
@@ -428,7 +414,13 @@ fn draw_ui(app: &mut AppState, stdout: &mut Stdout) -> Result<()> {
             queue!(stdout, SetForegroundColor(Color::Yellow))?;
         }
         queue!(stdout, Print(left_line), SetForegroundColor(Color::Reset))?;
-        queue!(stdout, cursor::MoveTo(list_width + 1, y), Print(diff_line))?;
+        queue!(
+            stdout,
+            cursor::MoveTo(list_width + 1, y),
+            SetForegroundColor(diff_color),
+            Print(diff_line),
+            SetForegroundColor(Color::Reset)
+        )?;
     }
 
     let status = format!("{}  |  {} files  |  commit {}/{}", app.status, files.len(), app.commit_index + 1, app.commits.len());
@@ -460,41 +452,41 @@ fn truncate_to_width(value: &str, width: usize) -> String {
         out.push(ch);
         current += w;
     }
-    if out.len() < value.len() && width > 1 {
-        if current + 1 <= width {
-            out.push('…');
-        }
+    if out.len() < value.len() && width > 1 && current < width {
+        out.push('…');
     }
     out
 }
 
-fn load_stack_commits(repo: &Path, base_revset: &str, limit: usize) -> Result<Vec<CommitItem>> {
-    let revset = format!("ancestors(@) & ~ancestors({})", base_revset);
-    let template = "commit_id ++ \"\\t\" ++ description.first_line()";
-    let output = run_jj(repo, &["log", "-r", &revset, "--no-graph", "-T", template])?;
-    let mut commits = Vec::new();
-    for line in output.lines() {
-        let mut parts = line.splitn(2, '\t');
-        let id = parts.next().unwrap_or("").trim().to_string();
-        if id.is_empty() {
-            continue;
-        }
-        let summary = parts.next().unwrap_or("").trim().to_string();
-        commits.push(CommitItem { id, summary });
-        if commits.len() >= limit {
-            break;
-        }
+fn format_commit_header(commit: &CommitItem) -> String {
+    let mut header = short_id(&commit.id);
+    if !commit.change_id.is_empty() {
+        header = format!("{} {}", short_id(&commit.change_id), header);
     }
-    Ok(commits)
+    for bookmark in &commit.bookmarks {
+        header.push(' ');
+        header.push_str(bookmark);
+    }
+    if commit.conflict {
+        header.push_str(" (conflict)");
+    }
+    header.push(' ');
+    header.push_str(&commit.summary);
+    header
 }
 
-fn load_stack_files(repo: &Path, commit_id: &str) -> Result<Vec<FileItem>> {
-    let output = run_jj(repo, &["diff", "-r", commit_id, "--name-status", "--color", "never"])?;
-    Ok(parse_name_status(&output))
+fn diff_line_color(kind: DiffLineKind) -> Color {
+    match kind {
+        DiffLineKind::Header => Color::Yellow,
+        DiffLineKind::HunkHeader => Color::Cyan,
+        DiffLineKind::Context => Color::Reset,
+        DiffLineKind::Removed => Color::Red,
+        DiffLineKind::Added => Color::Green,
+    }
 }
 
-fn load_queue_commits(repo: &Path, limit: usize) -> Result<Vec<CommitItem>> {
-    let queue_dir = repo.join(".ai").join("internal").join("commit-queue");
+fn load_queue_commits(repo_path: &Path, repo: &RepoView, limit: usize) -> Result<Vec<CommitItem>> {
+    let queue_dir = repo_path.join(".ai").join("internal").join("commit-queue");
     if !queue_dir.exists() {
         return Ok(Vec::new());
     }
@@ -514,93 +506,56 @@ fn load_queue_commits(repo: &Path, limit: usize) -> Result<Vec<CommitItem>> {
 
     let mut commits = Vec::new();
     for entry in entries.into_iter().take(limit) {
-        let mut summary = entry.message.lines().next().unwrap_or("").to_string();
-        if summary.is_empty() {
-            summary = "(no message)".to_string();
+        let mut item = match repo.find_commit(&entry.commit_sha) {
+            Some(commit) => repo.commit_item(&commit),
+            None => CommitItem {
+                id: entry.commit_sha.clone(),
+                change_id: String::new(),
+                summary: String::new(),
+                bookmarks: Vec::new(),
+                conflict: false,
+            },
+        };
+        item.summary = entry.message.lines().next().unwrap_or("").to_string();
+        if item.summary.is_empty() {
+            item.summary = "(no message)".to_string();
         }
-        if let Some(bookmark) = entry.review_bookmark.as_deref() {
-            summary = format!("{}  [{}]", summary, bookmark);
+        if let Some(bookmark) = entry.review_bookmark
+            && !item.bookmarks.contains(&bookmark)
+        {
+            item.bookmarks.push(bookmark);
         }
-        commits.push(CommitItem {
-            id: entry.commit_sha,
-            summary,
-        });
+        commits.push(item);
     }
     Ok(commits)
 }
 
-fn load_queue_files(repo: &Path, commit_id: &str) -> Result<Vec<FileItem>> {
-    let output = run_git(repo, &["diff-tree", "--root", "--no-commit-id", "--name-status", "-r", "-M", commit_id])?;
-    Ok(parse_name_status(&output))
-}
-
-fn load_stack_diff(repo: &Path, commit_id: &str, file: Option<&FileItem>) -> Result<Vec<String>> {
-    let mut args = vec!["diff", "-r", commit_id, "--color", "never"];
-    if let Some(item) = file {
-        args.push("--");
-        args.push(&item.path);
-    }
-    let output = run_jj(repo, &args)?;
-    Ok(output.lines().map(|s| s.to_string()).collect())
-}
-
-fn load_queue_diff(repo: &Path, commit_id: &str, file: Option<&FileItem>) -> Result<Vec<String>> {
-    let parent = match run_git(repo, &["rev-parse", &format!("{}^", commit_id)]) {
-        Ok(parent) if !parent.trim().is_empty() => parent.trim().to_string(),
-        _ => EMPTY_TREE_HASH.to_string(),
-    };
-    let mut args = vec!["diff", &parent, commit_id, "--color", "never"];
-    if let Some(item) = file {
-        args.push("--");
-        args.push(&item.path);
-    }
-    let output = run_git(repo, &args)?;
-    Ok(output.lines().map(|s| s.to_string()).collect())
-}
-
-fn open_full_diff(
-    repo: &Path,
-    commit_id: &str,
-    file: Option<&FileItem>,
-    mode: ViewMode,
-) -> Result<()> {
+fn open_full_diff(repo_path: &Path, lines: &[DiffLine]) -> Result<()> {
     terminal::disable_raw_mode().ok();
     let pager = std::env::var("PAGER").unwrap_or_else(|_| "less -R".to_string());
-    let cmd = match mode {
-        ViewMode::Stack => {
-            if let Some(item) = file {
-                format!(
-                    "jj diff -r {} --color always -- {}",
-                    commit_id,
-                    shell_quote(&item.path)
-                )
-            } else {
-                format!("jj diff -r {} --color always", commit_id)
-            }
-        }
-        ViewMode::Queue => {
-            let parent = match run_git(repo, &["rev-parse", &format!("{}^", commit_id)]) {
-                Ok(parent) if !parent.trim().is_empty() => parent.trim().to_string(),
-                _ => EMPTY_TREE_HASH.to_string(),
+    let mut child = Command::new("sh")
+        .args(["-c", &pager])
+        .current_dir(repo_path)
+        .stdin(Stdio::piped())
+        .spawn()
+        .context("run pager")?;
+    if let Some(mut stdin) = child.stdin.take() {
+        for line in lines {
+            let color = match line.kind {
+                DiffLineKind::Header => "\x1b[1m",
+                DiffLineKind::HunkHeader => "\x1b[36m",
+                DiffLineKind::Context => "",
+                DiffLineKind::Removed => "\x1b[31m",
+                DiffLineKind::Added => "\x1b[32m",
             };
-            if let Some(item) = file {
-                format!(
-                    "git diff --color=always {} {} -- {}",
-                    parent,
-                    commit_id,
-                    shell_quote(&item.path)
-                )
-            } else {
-                format!("git diff --color=always {} {}", parent, commit_id)
+            let reset = if color.is_empty() { "" } else { "\x1b[0m" };
+            // The pager may exit before reading everything.
+            if writeln!(stdin, "{color}{}{reset}", line.text).is_err() {
+                break;
             }
         }
-    };
-    let cmd = format!("{} | {}", cmd, pager);
-    let status = Command::new("sh")
-        .args(["-c", &cmd])
-        .current_dir(repo)
-        .status()
-        .context("run pager")?;
+    }
+    let status = child.wait().context("run pager")?;
     if !status.success() {
         println!("Failed to open pager.");
     }
@@ -621,57 +576,6 @@ fn run_shell(repo: &Path, command: &str) -> Result<String> {
     Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
 }
 
-fn run_jj(repo: &Path, args: &[&str]) -> Result<String> {
-    let output = Command::new("jj")
-        .args(args)
-        .current_dir(repo)
-        .output()
-        .context("run jj")?;
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        bail!("jj failed: {}", stderr.trim());
-    }
-    Ok(String::from_utf8_lossy(&output.stdout).to_string())
-}
-
-fn run_git(repo: &Path, args: &[&str]) -> Result<String> {
-    let output = Command::new("git")
-        .args(args)
-        .current_dir(repo)
-        .output()
-        .context("run git")?;
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        bail!("git failed: {}", stderr.trim());
-    }
-    Ok(String::from_utf8_lossy(&output.stdout).to_string())
-}
-
-fn parse_name_status(output: &str) -> Vec<FileItem> {
-    let mut items = Vec::new();
-    for line in output.lines() {
-        let parts: Vec<&str> = line.split('\t').collect();
-        if parts.is_empty() {
-            continue;
-        }
-        let status = parts[0].trim().to_string();
-        if status.starts_with('R') && parts.len() >= 3 {
-            items.push(FileItem {
-                status: status.clone(),
-                path: parts[2].to_string(),
-                original_path: Some(parts[1].to_string()),
-            });
-        } else if parts.len() >= 2 {
-            items.push(FileItem {
-                status: status.clone(),
-                path: parts[1].to_string(),
-                original_path: None,
-            });
-        }
-    }
-    items
-}
-
 fn short_id(commit_id: &str) -> String {
     if commit_id.len() <= 8 {
         commit_id.to_string()
@@ -680,38 +584,12 @@ fn short_id(commit_id: &str) -> String {
     }
 }
 
-fn resolve_default_base(repo: &Path) -> Result<String> {
+fn resolve_default_base(repo: &RepoView) -> String {
     let candidates = ["trunk()", "main@origin", "master@origin", "main", "master"];
     for candidate in candidates {
-        if jj_revset_exists(repo, candidate) {
-            return Ok(candidate.to_string());
-        }
-    }
-    Ok("root()".to_string())
-}
-
-fn jj_revset_exists(repo: &Path, revset: &str) -> bool {
-    let output = Command::new("jj")
-        .args(["log", "-r", revset, "--no-graph", "-T", "commit_id"])
-        .current_dir(repo)
-        .output();
-    if let Ok(output) = output {
-        output.status.success() && !output.stdout.is_empty()
-    } else {
-        false
-    }
-}
-
-fn shell_quote(value: &str) -> String {
-    let mut out = String::new();
-    out.push('\'');
-    for ch in value.chars() {
-        if ch == '\'' {
-            out.push_str("'\\''");
-        } else {
-            out.push(ch);
+        if repo.revset_exists(candidate) {
+            return candidate.to_string();
         }
     }
-    out.push('\'');
-    out
+    "root()".to_string()
 }
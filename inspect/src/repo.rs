@@ -0,0 +1,468 @@
+//! Repository access through jj-lib.
+//!
+//! The repo is loaded once and kept in memory, so moving between commits
+//! doesn't need to spawn processes or reload the operation log.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Context, Result, anyhow, bail};
+use bstr::BStr;
+use futures::StreamExt as _;
+use futures::executor::block_on_stream;
+use jj_cli::cli_util::load_revset_aliases;
+use jj_cli::command_error::CommandError;
+use jj_cli::config::{
+    ConfigEnv, config_from_environment, default_config_layers, default_config_migrations,
+};
+use jj_cli::ui::Ui;
+use jj_lib::backend::{CommitId, CopyRecord};
+use jj_lib::commit::Commit;
+use jj_lib::config::StackedConfig;
+use jj_lib::conflicts::{
+    ConflictMarkerStyle, ConflictMaterializeOptions, MaterializedTreeDiffEntry,
+    materialized_diff_stream,
+};
+use jj_lib::copies::{CopiesTreeDiffEntryPath, CopyOperation, CopyRecords};
+use jj_lib::diff_presentation::LineCompareMode;
+use jj_lib::diff_presentation::unified::{DiffLineType, git_diff_part, unified_diff_hunks};
+use jj_lib::git::REMOTE_NAME_FOR_LOCAL_GIT_REPO;
+use jj_lib::matchers::{EverythingMatcher, FilesMatcher, Matcher};
+use jj_lib::merge::Diff;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::repo::{ReadonlyRepo, Repo as _, StoreFactories};
+use jj_lib::repo_path::{RepoPathBuf, RepoPathUiConverter};
+use jj_lib::revset::{
+    self, RevsetAliasesMap, RevsetDiagnostics, RevsetExtensions, RevsetParseContext,
+    RevsetWorkspaceContext, SymbolResolver,
+};
+use jj_lib::settings::UserSettings;
+use jj_lib::workspace::{
+    DefaultWorkspaceLoaderFactory, Workspace, WorkspaceLoader, WorkspaceLoaderFactory as _,
+    default_working_copy_factories,
+};
+use pollster::FutureExt as _;
+
+const DIFF_CONTEXT_LINES: usize = 3;
+
+#[derive(Debug, Clone)]
+pub struct CommitItem {
+    pub id: String,
+    pub change_id: String,
+    pub summary: String,
+    pub bookmarks: Vec<String>,
+    pub conflict: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct FileItem {
+    pub status: String,
+    pub path: String,
+    pub original_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffLineKind {
+    Header,
+    HunkHeader,
+    Context,
+    Removed,
+    Added,
+}
+
+#[derive(Debug, Clone)]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    pub text: String,
+}
+
+pub struct RepoView {
+    workspace: Workspace,
+    repo: Arc<ReadonlyRepo>,
+    aliases_map: RevsetAliasesMap,
+    extensions: Arc<RevsetExtensions>,
+    path_converter: RepoPathUiConverter,
+    marker_style: ConflictMarkerStyle,
+    use_glob_by_default: bool,
+}
+
+impl RepoView {
+    /// Loads the workspace containing `path`.
+    pub fn load(path: &Path) -> Result<Self> {
+        let path = path
+            .canonicalize()
+            .with_context(|| format!("resolve {}", path.display()))?;
+        let workspace_root = find_workspace_root(&path)?;
+        let loader = DefaultWorkspaceLoaderFactory
+            .create(&workspace_root)
+            .with_context(|| format!("load workspace at {}", workspace_root.display()))?;
+        let ui = Ui::null();
+        let config = load_config(&ui, loader.as_ref())?;
+        let aliases_map = load_revset_aliases(&ui, &config).map_err(command_error)?;
+        let settings = UserSettings::from_config(config)?;
+        let marker_style = settings.get("ui.conflict-marker-style")?;
+        let use_glob_by_default = settings.get("ui.revsets-use-glob-by-default")?;
+        let workspace = loader
+            .load(
+                &settings,
+                &StoreFactories::default(),
+                &default_working_copy_factories(),
+            )
+            .with_context(|| format!("load workspace at {}", workspace_root.display()))?;
+        let repo = workspace
+            .repo_loader()
+            .load_at_head()
+            .block_on()
+            .context("load repo")?;
+        let path_converter = RepoPathUiConverter::Fs {
+            cwd: workspace_root.clone(),
+            base: workspace_root,
+        };
+        Ok(Self {
+            workspace,
+            repo,
+            aliases_map,
+            extensions: Arc::new(RevsetExtensions::default()),
+            path_converter,
+            marker_style,
+            use_glob_by_default,
+        })
+    }
+
+    /// Reloads the repo at the latest operation.
+    pub fn reload(&mut self) -> Result<()> {
+        self.repo = self
+            .workspace
+            .repo_loader()
+            .load_at_head()
+            .block_on()
+            .context("load repo")?;
+        Ok(())
+    }
+
+    /// Evaluates `revset_str` to commit ids, newest first.
+    pub fn evaluate_revset(&self, revset_str: &str) -> Result<Vec<CommitId>> {
+        let workspace_context = RevsetWorkspaceContext {
+            path_converter: &self.path_converter,
+            workspace_name: self.workspace.workspace_name(),
+        };
+        let context = RevsetParseContext {
+            aliases_map: &self.aliases_map,
+            local_variables: HashMap::new(),
+            user_email: self.repo.settings().user_email(),
+            date_pattern_context: chrono::Local::now().into(),
+            default_ignored_remote: Some(REMOTE_NAME_FOR_LOCAL_GIT_REPO),
+            use_glob_by_default: self.use_glob_by_default,
+            extensions: &self.extensions,
+            workspace: Some(workspace_context),
+        };
+        let mut diagnostics = RevsetDiagnostics::new();
+        let expression = revset::parse(&mut diagnostics, revset_str, &context)
+            .map_err(|err| anyhow!("invalid revset `{revset_str}`: {err}"))?;
+        let repo = self.repo.as_ref();
+        let symbol_resolver = SymbolResolver::new(repo, self.extensions.symbol_resolvers());
+        let resolved = expression
+            .resolve_user_expression(repo, &symbol_resolver)
+            .map_err(|err| anyhow!("failed to resolve revset `{revset_str}`: {err}"))?;
+        let revset = resolved.evaluate(repo)?;
+        let ids = revset.iter().collect::<Result<Vec<_>, _>>()?;
+        Ok(ids)
+    }
+
+    /// Returns true if `revset_str` resolves to at least one commit.
+    pub fn revset_exists(&self, revset_str: &str) -> bool {
+        self.evaluate_revset(revset_str)
+            .is_ok_and(|ids| !ids.is_empty())
+    }
+
+    /// Returns the commits between the `base_revset` and the working copy.
+    pub fn stack_commits(&self, base_revset: &str, limit: usize) -> Result<Vec<CommitItem>> {
+        let revset_str = format!("ancestors(@) & ~ancestors({base_revset})");
+        let ids = self.evaluate_revset(&revset_str)?;
+        ids.iter()
+            .take(limit)
+            .map(|id| Ok(self.commit_item(&self.repo.store().get_commit(id)?)))
+            .collect()
+    }
+
+    /// Looks up the commit by full hex id. Returns `None` if the commit isn't
+    /// known to the repo.
+    pub fn find_commit(&self, hex: &str) -> Option<Commit> {
+        let id = CommitId::try_from_hex(hex)?;
+        self.repo.store().get_commit(&id).ok()
+    }
+
+    pub fn commit_item(&self, commit: &Commit) -> CommitItem {
+        let bookmarks = self
+            .repo
+            .view()
+            .local_bookmarks_for_commit(commit.id())
+            .map(|(name, _)| name.as_symbol().to_string())
+            .collect();
+        let summary = commit.description().lines().next().unwrap_or("").to_owned();
+        CommitItem {
+            id: commit.id().hex(),
+            change_id: commit.change_id().reverse_hex(),
+            summary,
+            bookmarks,
+            conflict: commit.has_conflict(),
+        }
+    }
+
+    /// Lists files changed by the commit, with renames and copies detected.
+    pub fn files(&self, commit_hex: &str) -> Result<Vec<FileItem>> {
+        let commit = self.get_commit(commit_hex)?;
+        let copy_records = self.copy_records(&commit)?;
+        let from_tree = commit.parent_tree(self.repo.as_ref())?;
+        let to_tree = commit.tree();
+        let mut files = Vec::new();
+        let mut stream =
+            from_tree.diff_stream_with_copies(&to_tree, &EverythingMatcher, &copy_records);
+        while let Some(entry) = stream.next().block_on() {
+            let values = entry.values?;
+            let status = if values.after.is_present() && !values.after.is_resolved() {
+                "U"
+            } else {
+                match (
+                    entry.path.copy_operation(),
+                    values.before.is_absent(),
+                    values.after.is_absent(),
+                ) {
+                    (Some(CopyOperation::Rename), _, _) => "R",
+                    (Some(CopyOperation::Copy), _, _) => "C",
+                    (None, true, _) => "A",
+                    (None, _, true) => "D",
+                    (None, false, false) => "M",
+                }
+            };
+            files.push(FileItem {
+                status: status.to_owned(),
+                path: entry.path.target().as_internal_file_string().to_owned(),
+                original_path: entry
+                    .path
+                    .source
+                    .as_ref()
+                    .map(|(source, _)| source.as_internal_file_string().to_owned()),
+            });
+        }
+        Ok(files)
+    }
+
+    /// Renders the diff of the commit in Git format, limited to the `file` if
+    /// specified.
+    pub fn diff(&self, commit_hex: &str, file: Option<&FileItem>) -> Result<Vec<DiffLine>> {
+        let commit = self.get_commit(commit_hex)?;
+        let copy_records = self.copy_records(&commit)?;
+        let from_tree = commit.parent_tree(self.repo.as_ref())?;
+        let to_tree = commit.tree();
+        let matcher: Box<dyn Matcher> = match file {
+            Some(item) => {
+                let mut paths = vec![parse_repo_path(&item.path)?];
+                if let Some(original) = &item.original_path {
+                    paths.push(parse_repo_path(original)?);
+                }
+                Box::new(FilesMatcher::new(paths))
+            }
+            None => Box::new(EverythingMatcher),
+        };
+        let store = self.repo.store();
+        let materialize_options = ConflictMaterializeOptions {
+            marker_style: self.marker_style,
+            marker_len: None,
+            merge: store.merge_options().clone(),
+        };
+        let tree_diff =
+            from_tree.diff_stream_with_copies(&to_tree, matcher.as_ref(), &copy_records);
+        let conflict_labels = Diff::new(from_tree.labels(), to_tree.labels());
+        let mut diff_stream = materialized_diff_stream(store, tree_diff, conflict_labels);
+        let mut lines = Vec::new();
+        while let Some(MaterializedTreeDiffEntry { path, values }) = diff_stream.next().block_on() {
+            let values = values?;
+            let left = git_diff_part(path.source(), values.before, &materialize_options)?;
+            let right = git_diff_part(path.target(), values.after, &materialize_options)?;
+            push_file_header(
+                &mut lines,
+                &path,
+                (left.mode, &left.hash),
+                (right.mode, &right.hash),
+            );
+            if left.content.contents == right.content.contents {
+                continue;
+            }
+            let left_path = match left.mode {
+                Some(_) => format!("a/{}", path.source().as_internal_file_string()),
+                None => "/dev/null".to_owned(),
+            };
+            let right_path = match right.mode {
+                Some(_) => format!("b/{}", path.target().as_internal_file_string()),
+                None => "/dev/null".to_owned(),
+            };
+            if left.content.is_binary || right.content.is_binary {
+                push_line(
+                    &mut lines,
+                    DiffLineKind::Header,
+                    format!("Binary files {left_path} and {right_path} differ"),
+                );
+                continue;
+            }
+            push_line(&mut lines, DiffLineKind::Header, format!("--- {left_path}"));
+            push_line(
+                &mut lines,
+                DiffLineKind::Header,
+                format!("+++ {right_path}"),
+            );
+            let contents =
+                Diff::new(&left.content.contents, &right.content.contents).map(BStr::new);
+            for hunk in unified_diff_hunks(contents, DIFF_CONTEXT_LINES, LineCompareMode::Exact) {
+                push_line(
+                    &mut lines,
+                    DiffLineKind::HunkHeader,
+                    format!(
+                        "@@ -{},{} +{},{} @@",
+                        hunk_line_number(&hunk.left_line_range),
+                        hunk.left_line_range.len(),
+                        hunk_line_number(&hunk.right_line_range),
+                        hunk.right_line_range.len()
+                    ),
+                );
+                for (line_type, tokens) in &hunk.lines {
+                    let (kind, sigil) = match line_type {
+                        DiffLineType::Context => (DiffLineKind::Context, ' '),
+                        DiffLineType::Removed => (DiffLineKind::Removed, '-'),
+                        DiffLineType::Added => (DiffLineKind::Added, '+'),
+                    };
+                    let content: Vec<u8> = tokens
+                        .iter()
+                        .flat_map(|(_, token)| token.iter().copied())
+                        .collect();
+                    let text = String::from_utf8_lossy(&content);
+                    push_line(
+                        &mut lines,
+                        kind,
+                        format!("{sigil}{}", text.trim_end_matches('\n')),
+                    );
+                    if !content.ends_with(b"\n") {
+                        push_line(
+                            &mut lines,
+                            DiffLineKind::Context,
+                            "\\ No newline at end of file".to_owned(),
+                        );
+                    }
+                }
+            }
+        }
+        Ok(lines)
+    }
+
+    fn get_commit(&self, commit_hex: &str) -> Result<Commit> {
+        self.find_commit(commit_hex)
+            .with_context(|| format!("commit {commit_hex} not found in the repo"))
+    }
+
+    fn copy_records(&self, commit: &Commit) -> Result<CopyRecords> {
+        let mut copy_records = CopyRecords::default();
+        let store = self.repo.store();
+        for parent_id in commit.parent_ids() {
+            let stream = store.get_copy_records(None, parent_id, commit.id())?;
+            let records: Vec<CopyRecord> = block_on_stream(stream).collect::<Result<_, _>>()?;
+            copy_records.add_records(records.into_iter().map(Ok))?;
+        }
+        Ok(copy_records)
+    }
+}
+
+fn push_line(lines: &mut Vec<DiffLine>, kind: DiffLineKind, text: String) {
+    lines.push(DiffLine { kind, text });
+}
+
+fn push_file_header(
+    lines: &mut Vec<DiffLine>,
+    path: &CopiesTreeDiffEntryPath,
+    (left_mode, left_hash): (Option<&str>, &str),
+    (right_mode, right_hash): (Option<&str>, &str),
+) {
+    let left_path = path.source().as_internal_file_string();
+    let right_path = path.target().as_internal_file_string();
+    let mut header = vec![format!("diff --git a/{left_path} b/{right_path}")];
+    match (left_mode, right_mode) {
+        (None, Some(right_mode)) => {
+            header.push(format!("new file mode {right_mode}"));
+            header.push(format!("index {left_hash}..{right_hash}"));
+        }
+        (Some(left_mode), None) => {
+            header.push(format!("deleted file mode {left_mode}"));
+            header.push(format!("index {left_hash}..{right_hash}"));
+        }
+        (Some(left_mode), Some(right_mode)) => {
+            if let Some(op) = path.copy_operation() {
+                let operation = match op {
+                    CopyOperation::Copy => "copy",
+                    CopyOperation::Rename => "rename",
+                };
+                header.push(format!("{operation} from {left_path}"));
+                header.push(format!("{operation} to {right_path}"));
+            }
+            if left_mode != right_mode {
+                header.push(format!("old mode {left_mode}"));
+                header.push(format!("new mode {right_mode}"));
+                if left_hash != right_hash {
+                    header.push(format!("index {left_hash}..{right_hash}"));
+                }
+            } else if left_hash != right_hash {
+                header.push(format!("index {left_hash}..{right_hash} {left_mode}"));
+            }
+        }
+        (None, None) => {}
+    }
+    for text in header {
+        push_line(lines, DiffLineKind::Header, text);
+    }
+}
+
+/// Start line of a hunk range, which is one lower for empty ranges as in
+/// `diff -u`.
+fn hunk_line_number(range: &std::ops::Range<usize>) -> usize {
+    if range.is_empty() {
+        range.start
+    } else {
+        range.start + 1
+    }
+}
+
+fn parse_repo_path(path: &str) -> Result<RepoPathBuf> {
+    RepoPathBuf::from_internal_string(path).map_err(|err| anyhow!("invalid path {path}: {err}"))
+}
+
+/// Finds the closest ancestor of `path` containing a `.jj` directory.
+fn find_workspace_root(path: &Path) -> Result<PathBuf> {
+    for dir in path.ancestors() {
+        if dir.join(".jj").is_dir() {
+            return Ok(dir.to_owned());
+        }
+    }
+    bail!("there is no jj repo in {}", path.display())
+}
+
+/// Loads the config layers in the same order as jj does: the defaults, the
+/// user's config files, then the repo and workspace config files.
+fn load_config(ui: &Ui, loader: &dyn WorkspaceLoader) -> Result<StackedConfig> {
+    let mut raw_config = config_from_environment(default_config_layers());
+    let mut config_env = ConfigEnv::from_environment();
+    config_env.reload_user_config(&mut raw_config)?;
+    config_env.reset_repo_path(loader.repo_path());
+    config_env
+        .reload_repo_config(ui, &mut raw_config)
+        .map_err(command_error)?;
+    config_env.reset_workspace_path(loader.workspace_root());
+    config_env
+        .reload_workspace_config(ui, &mut raw_config)
+        .map_err(command_error)?;
+    let mut config = config_env.resolve_config(&raw_config)?;
+    jj_lib::config::migrate(&mut config, &default_config_migrations())?;
+    Ok(config)
+}
+
+fn command_error(err: CommandError) -> anyhow::Error {
+    anyhow!("{}", err.error)
+}
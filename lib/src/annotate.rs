@@ -15,10 +15,14 @@
 //! Methods that allow annotation (attribution and blame) for a file in a
 //! repository.
 //!
+//! Lines are traced through renames and copies reported by the backend's copy
+//! records.
+//!
 //! TODO: Add support for different blame layers with a trait in the future.
 //! Like commit metadata and more.
 
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::collections::hash_map;
 use std::iter;
 use std::ops::Range;
@@ -26,6 +30,7 @@ use std::sync::Arc;
 
 use bstr::BStr;
 use bstr::BString;
+use futures::TryStreamExt as _;
 use itertools::Itertools as _;
 use pollster::FutureExt as _;
 
@@ -152,8 +157,6 @@ impl FileAnnotation {
 /// Annotation process for a specific file.
 #[derive(Clone, Debug)]
 pub struct FileAnnotator {
-    // If we add copy-tracing support, file_path might be tracked by state.
-    file_path: RepoPathBuf,
     starting_text: BString,
     state: AnnotationState,
 }
@@ -164,7 +167,7 @@ impl FileAnnotator {
     /// If the file is not found, the result would be empty.
     pub fn from_commit(starting_commit: &Commit, file_path: &RepoPath) -> BackendResult<Self> {
         let source = Source::load(starting_commit, file_path)?;
-        Ok(Self::with_source(starting_commit.id(), file_path, source))
+        Ok(Self::with_source(starting_commit.id(), source))
     }
 
     /// Initializes annotator for a specific file path starting with the given
@@ -178,15 +181,11 @@ impl FileAnnotator {
         file_path: &RepoPath,
         starting_text: impl Into<Vec<u8>>,
     ) -> Self {
-        let source = Source::new(BString::new(starting_text.into()));
-        Self::with_source(starting_commit_id, file_path, source)
+        let source = Source::new(file_path.to_owned(), BString::new(starting_text.into()));
+        Self::with_source(starting_commit_id, source)
     }
 
-    fn with_source(
-        starting_commit_id: &CommitId,
-        file_path: &RepoPath,
-        mut source: Source,
-    ) -> Self {
+    fn with_source(starting_commit_id: &CommitId, mut source: Source) -> Self {
         source.fill_line_map();
         let starting_text = source.text.clone();
         let state = AnnotationState {
@@ -195,14 +194,14 @@ impl FileAnnotator {
                     Err(LineOrigin {
                         commit_id: starting_commit_id.clone(),
                         line_number,
+                        path: source.path.clone(),
                     })
                 })
                 .collect(),
             commit_source_map: HashMap::from([(starting_commit_id.clone(), source)]),
-            num_unresolved_roots: 0,
+            unresolved_roots: HashSet::new(),
         };
         Self {
-            file_path: file_path.to_owned(),
             starting_text,
             state,
         }
@@ -211,14 +210,31 @@ impl FileAnnotator {
     /// Computes line-by-line annotation within the `domain`.
     ///
     /// The `domain` expression narrows the range of ancestors to search. It
-    /// will be intersected as `domain & ::pending_commits & files(file_path)`.
+    /// will be intersected as `domain & ::pending_commits & files(file_paths)`,
+    /// where `file_paths` are the paths the file had in the pending commits.
     /// The `pending_commits` is assumed to be included in the `domain`.
     pub fn compute(
         &mut self,
         repo: &dyn Repo,
         domain: &Arc<ResolvedRevsetExpression>,
     ) -> Result<(), RevsetEvaluationError> {
-        process_commits(repo, &mut self.state, domain, &self.file_path)
+        process_commits(repo, &mut self.state, domain, &RevsetExpression::none())
+    }
+
+    /// Computes line-by-line annotation within the `domain` like
+    /// [`Self::compute()`], but skips the `ignored` commits.
+    ///
+    /// Lines changed by an ignored commit are attributed to the lines they
+    /// replaced in the parent commit, which are matched by position within the
+    /// changed hunk. Lines added without replacing any are still attributed
+    /// to the ignored commit.
+    pub fn compute_ignoring(
+        &mut self,
+        repo: &dyn Repo,
+        domain: &Arc<ResolvedRevsetExpression>,
+        ignored: &Arc<ResolvedRevsetExpression>,
+    ) -> Result<(), RevsetEvaluationError> {
+        process_commits(repo, &mut self.state, domain, ignored)
     }
 
     /// Remaining commit ids to visit from.
@@ -244,8 +260,8 @@ struct AnnotationState {
     original_line_map: OriginalLineMap,
     /// Commits to file line mappings and contents.
     commit_source_map: HashMap<CommitId, Source>,
-    /// Number of unresolved root commits in `commit_source_map`.
-    num_unresolved_roots: usize,
+    /// Unresolved root commits in `commit_source_map`.
+    unresolved_roots: HashSet<CommitId>,
 }
 
 /// Line mapping and file content at a certain commit.
@@ -254,14 +270,17 @@ struct Source {
     /// Mapping of line numbers in the file at the current commit to the
     /// starting file, sorted by the line numbers at the current commit.
     line_map: Vec<(usize, usize)>,
+    /// Path of the file at the current commit.
+    path: RepoPathBuf,
     /// File content at the current commit.
     text: BString,
 }
 
 impl Source {
-    fn new(text: BString) -> Self {
+    fn new(path: RepoPathBuf, text: BString) -> Self {
         Self {
             line_map: Vec::new(),
+            path,
             text,
         }
     }
@@ -269,7 +288,7 @@ impl Source {
     fn load(commit: &Commit, file_path: &RepoPath) -> Result<Self, BackendError> {
         let tree = commit.tree();
         let text = get_file_contents(commit.store(), file_path, &tree).block_on()?;
-        Ok(Self::new(text))
+        Ok(Self::new(file_path.to_owned(), text))
     }
 
     fn fill_line_map(&mut self) {
@@ -289,6 +308,9 @@ pub struct LineOrigin {
     pub commit_id: CommitId,
     /// 0-based line number of the line in the origin commit.
     pub line_number: usize,
+    /// Path of the file in the origin commit. This differs from the annotated
+    /// path if the file was renamed or copied since.
+    pub path: RepoPathBuf,
 }
 
 /// Starting from the source commits, compute changes at that commit relative to
@@ -297,54 +319,121 @@ fn process_commits(
     repo: &dyn Repo,
     state: &mut AnnotationState,
     domain: &Arc<ResolvedRevsetExpression>,
-    file_name: &RepoPath,
+    ignored: &Arc<ResolvedRevsetExpression>,
 ) -> Result<(), RevsetEvaluationError> {
-    let predicate = RevsetFilterPredicate::File(FilesetExpression::file_path(file_name.to_owned()));
-    // TODO: If the domain isn't a contiguous range, changes masked out by it
-    // might not be caught by the closest ancestor revision. For example,
-    // domain=merges() would pick up almost nothing because merge revisions
-    // are usually empty. Perhaps, we want to query `files(file_path,
-    // within_sub_graph=domain)`, not `domain & files(file_path)`.
-    let heads = RevsetExpression::commits(state.commit_source_map.keys().cloned().collect());
-    let revset = heads
-        .union(&domain.intersection(&heads.ancestors()).filtered(predicate))
-        .evaluate(repo)?;
-
-    state.num_unresolved_roots = 0;
-    for node in revset.iter_graph() {
-        let (commit_id, edge_list) = node?;
-        process_commit(repo, file_name, state, &commit_id, &edge_list)?;
-        if state.commit_source_map.len() == state.num_unresolved_roots {
-            // No more lines to propagate to ancestors.
-            break;
+    state.unresolved_roots.clear();
+    // The history is walked again whenever the file is found to be copied from
+    // a path the walk doesn't filter by.
+    'walk: loop {
+        let file_paths: HashSet<RepoPathBuf> = state
+            .commit_source_map
+            .values()
+            .map(|source| source.path.clone())
+            .collect();
+        let predicate = RevsetFilterPredicate::File(FilesetExpression::union_all(
+            file_paths
+                .iter()
+                .map(|path| FilesetExpression::file_path(path.clone()))
+                .collect(),
+        ));
+        // TODO: If the domain isn't a contiguous range, changes masked out by it
+        // might not be caught by the closest ancestor revision. For example,
+        // domain=merges() would pick up almost nothing because merge revisions
+        // are usually empty. Perhaps, we want to query `files(file_path,
+        // within_sub_graph=domain)`, not `domain & files(file_path)`.
+        let heads = RevsetExpression::commits(
+            state
+                .commit_source_map
+                .keys()
+                .filter(|id| !state.unresolved_roots.contains(*id))
+                .cloned()
+                .collect(),
+        );
+        let revset = heads
+            .union(&domain.intersection(&heads.ancestors()).filtered(predicate))
+            .evaluate(repo)?;
+        let ignored_ids: HashSet<CommitId> = ignored
+            .intersection(&heads.ancestors())
+            .evaluate(repo)?
+            .iter()
+            .try_collect()?;
+
+        for node in revset.iter_graph() {
+            let (commit_id, edge_list) = node?;
+            if state.unresolved_roots.contains(&commit_id) {
+                continue;
+            }
+            let found_new_path = process_commit(
+                repo,
+                state,
+                &file_paths,
+                &commit_id,
+                &edge_list,
+                ignored_ids.contains(&commit_id),
+            )?;
+            if state.commit_source_map.len() == state.unresolved_roots.len() {
+                // No more lines to propagate to ancestors.
+                break 'walk;
+            }
+            if found_new_path {
+                continue 'walk;
+            }
         }
+        break;
     }
     Ok(())
 }
 
 /// For a given commit, for each parent, we compare the version in the parent
 /// tree with the current version, updating the mappings for any lines in
-/// common. If the parent doesn't have the file, we skip it.
+/// common. If the parent doesn't have the file, we look it up in the copy
+/// records, and skip the parent if the file wasn't copied from another path.
+///
+/// If the commit is `ignored`, the lines which don't match any parent are
+/// then matched with the changed lines of the parents by position.
+///
+/// Returns true if the file was copied from a path not in the `walked_paths`,
+/// in which case the history needs to be walked again.
 fn process_commit(
     repo: &dyn Repo,
-    file_name: &RepoPath,
     state: &mut AnnotationState,
+    walked_paths: &HashSet<RepoPathBuf>,
     current_commit_id: &CommitId,
     edges: &[GraphEdge<CommitId>],
-) -> Result<(), BackendError> {
+    ignored: bool,
+) -> Result<bool, BackendError> {
     let Some(mut current_source) = state.commit_source_map.remove(current_commit_id) else {
-        return Ok(());
+        return Ok(false);
     };
 
-    for parent_edge in edges {
+    let mut found_new_path = false;
+    let passes: &[bool] = if ignored { &[false, true] } else { &[false] };
+    for (&fuzzy, parent_edge) in passes.iter().cartesian_product(edges) {
         let parent_commit_id = &parent_edge.target;
         let parent_source = match state.commit_source_map.entry(parent_commit_id.clone()) {
-            hash_map::Entry::Occupied(entry) => entry.into_mut(),
+            hash_map::Entry::Occupied(entry) => {
+                let parent_source = entry.into_mut();
+                if parent_source.path != current_source.path {
+                    let commit = repo.store().get_commit(parent_commit_id)?;
+                    let parent_path =
+                        find_parent_path(repo, &commit, current_commit_id, &current_source.path)?;
+                    if parent_source.path != parent_path {
+                        // The lines can only be traced to one path in the
+                        // parent.
+                        continue;
+                    }
+                }
+                parent_source
+            }
             hash_map::Entry::Vacant(entry) => {
                 let commit = repo.store().get_commit(entry.key())?;
-                entry.insert(Source::load(&commit, file_name)?)
+                let parent_path =
+                    find_parent_path(repo, &commit, current_commit_id, &current_source.path)?;
+                entry.insert(Source::load(&commit, &parent_path)?)
             }
         };
+        let is_new_path = !walked_paths.contains(&parent_source.path);
+        found_new_path |= is_new_path;
 
         // For two versions of the same file, for all the lines in common,
         // overwrite the new mapping in the results for the new commit. Let's
@@ -360,6 +449,7 @@ fn process_commit(
         copy_same_lines_with(
             &current_source.text,
             &parent_source.text,
+            fuzzy,
             |current_start, parent_start, count| {
                 new_current_line_map
                     .extend(current_lines.peeking_take_while(|&(cur, _)| cur < current_start));
@@ -380,17 +470,20 @@ fn process_commit(
         };
         if parent_source.line_map.is_empty() {
             state.commit_source_map.remove(parent_commit_id);
-        } else if parent_edge.is_missing() {
+        } else if parent_edge.is_missing() && !is_new_path {
             // If an omitted parent had the file, leave these lines unresolved.
+            // The parent might only have been omitted because the walk didn't
+            // filter by its path, in which case it's visited by the next walk.
             // The origin of the unresolved lines is represented as
             // Err(LineOrigin { parent_commit_id, parent_line_number }).
             for &(parent_line_number, starting_line_number) in &parent_source.line_map {
                 state.original_line_map[starting_line_number] = Err(LineOrigin {
                     commit_id: parent_commit_id.clone(),
                     line_number: parent_line_number,
+                    path: parent_source.path.clone(),
                 });
             }
-            state.num_unresolved_roots += 1;
+            state.unresolved_roots.insert(parent_commit_id.clone());
         }
     }
 
@@ -401,17 +494,47 @@ fn process_commit(
         state.original_line_map[starting_line_number] = Ok(LineOrigin {
             commit_id: current_commit_id.clone(),
             line_number: current_line_number,
+            path: current_source.path.clone(),
         });
     }
 
-    Ok(())
+    Ok(found_new_path)
+}
+
+/// Returns the path of the file at `path` in the `parent_commit`. If the
+/// parent doesn't have the file, the path it was copied from is looked up in
+/// the copy records between the parent and the current commit.
+fn find_parent_path(
+    repo: &dyn Repo,
+    parent_commit: &Commit,
+    current_commit_id: &CommitId,
+    path: &RepoPath,
+) -> BackendResult<RepoPathBuf> {
+    if !parent_commit.tree().path_value(path)?.is_absent() {
+        return Ok(path.to_owned());
+    }
+    let paths = [path.to_owned()];
+    let mut records =
+        repo.store()
+            .get_copy_records(Some(&paths), parent_commit.id(), current_commit_id)?;
+    while let Some(record) = records.try_next().block_on()? {
+        if record.target.as_ref() == path {
+            return Ok(record.source);
+        }
+    }
+    Ok(path.to_owned())
 }
 
 /// For two files, calls `copy(current_start, parent_start, count)` for each
 /// range of contiguous lines in common (e.g. line 8-10 maps to line 9-11.)
+///
+/// If `fuzzy` is true, the lines in the changed hunks are also mapped to the
+/// lines they replaced by position. Excess lines map to the last replaced
+/// line.
 fn copy_same_lines_with(
     current_contents: &[u8],
     parent_contents: &[u8],
+    fuzzy: bool,
     mut copy: impl FnMut(usize, usize, usize),
 ) {
     let diff = ContentDiff::by_line([current_contents, parent_contents]);
@@ -428,8 +551,16 @@ fn copy_same_lines_with(
             DiffHunkKind::Different => {
                 let current_output = hunk.contents[0];
                 let parent_output = hunk.contents[1];
-                current_line_counter += current_output.split_inclusive(|b| *b == b'\n').count();
-                parent_line_counter += parent_output.split_inclusive(|b| *b == b'\n').count();
+                let current_count = current_output.split_inclusive(|b| *b == b'\n').count();
+                let parent_count = parent_output.split_inclusive(|b| *b == b'\n').count();
+                if fuzzy && parent_count > 0 {
+                    for i in 0..current_count {
+                        let parent_line = parent_line_counter + i.min(parent_count - 1);
+                        copy(current_line_counter + i, parent_line, 1);
+                    }
+                }
+                current_line_counter += current_count;
+                parent_line_counter += parent_count;
             }
         }
     }
@@ -473,6 +604,7 @@ mod tests {
         LineOrigin {
             commit_id: commit_id.clone(),
             line_number,
+            path: RepoPathBuf::from_internal_string("file").unwrap(),
         }
     }
 
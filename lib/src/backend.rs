@@ -18,7 +18,6 @@ use std::any::Any;
 use std::fmt::Debug;
 use std::pin::Pin;
 use std::slice;
-use std::time::SystemTime;
 
 use async_trait::async_trait;
 use chrono::TimeZone as _;
@@ -27,6 +26,8 @@ use thiserror::Error;
 use tokio::io::AsyncRead;
 
 use crate::content_hash::ContentHash;
+use crate::gc::GcOptions;
+use crate::gc::GcStats;
 use crate::hex_util;
 use crate::index::Index;
 use crate::merge::Merge;
@@ -523,9 +524,13 @@ pub trait Backend: Any + Send + Sync + Debug {
     /// Perform garbage collection.
     ///
     /// All commits found in the `index` won't be removed. In addition to that,
-    /// objects created after `keep_newer` will be preserved. This mitigates a
-    /// risk of deleting new commits created concurrently by another process.
-    fn gc(&self, index: &dyn Index, keep_newer: SystemTime) -> BackendResult<()>;
+    /// objects created after `options.keep_newer` will be preserved. This
+    /// mitigates a risk of deleting new commits created concurrently by
+    /// another process.
+    ///
+    /// Returns what was removed, or what would be removed if
+    /// `options.dry_run` is set.
+    fn gc(&self, index: &dyn Index, options: &GcOptions) -> BackendResult<GcStats>;
 }
 
 impl dyn Backend {
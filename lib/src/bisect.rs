@@ -177,27 +177,37 @@ impl<'repo> Bisector<'repo> {
         &self.skipped_commits
     }
 
-    /// Find the next commit to evaluate, or determine that there are no more
-    /// steps.
-    pub fn next_step(&mut self) -> Result<NextStep, BisectionError> {
-        if self.aborted {
-            return Ok(NextStep::Done(BisectionResult::Abort));
-        }
+    /// The commits that may still be the first bad commit and have not been
+    /// evaluated yet.
+    fn candidates_expr(&self) -> Arc<ResolvedRevsetExpression> {
         let good_expr = RevsetExpression::commits(self.good_commits.iter().cloned().collect());
         let bad_expr = RevsetExpression::commits(self.bad_commits.iter().cloned().collect());
         let skipped_expr =
             RevsetExpression::commits(self.skipped_commits.iter().cloned().collect());
-        // Intersect the input range with the current bad range and then bisect it to
-        // find the next commit to evaluate.
+        // Intersect the input range with the current bad range.
         // Skipped revisions are simply subtracted from the set.
         // TODO: Handle long ranges of skipped revisions better
-        let to_evaluate_expr = self
-            .input_range
+        self.input_range
             .intersection(&good_expr.heads().range(&bad_expr.roots()))
             .minus(&bad_expr)
             .minus(&skipped_expr)
-            .bisect()
-            .latest(1);
+    }
+
+    /// The number of commits that are left to evaluate.
+    pub fn num_candidates(&self) -> Result<usize, BisectionError> {
+        let candidates = self.candidates_expr().evaluate(self.repo)?;
+        Ok(candidates.iter().process_results(|iter| iter.count())?)
+    }
+
+    /// Find the next commit to evaluate, or determine that there are no more
+    /// steps.
+    pub fn next_step(&mut self) -> Result<NextStep, BisectionError> {
+        if self.aborted {
+            return Ok(NextStep::Done(BisectionResult::Abort));
+        }
+        let bad_expr = RevsetExpression::commits(self.bad_commits.iter().cloned().collect());
+        // Bisect the remaining candidates to find the next commit to evaluate.
+        let to_evaluate_expr = self.candidates_expr().bisect().latest(1);
         let to_evaluate_set = to_evaluate_expr.evaluate(self.repo)?;
         if let Some(commit) = to_evaluate_set
             .iter()
@@ -152,6 +152,9 @@ impl Commit {
 
     /// Returns whether commit's content is empty. Commit description is not
     /// taken into consideration.
+    ///
+    /// A change to the copy ids alone is a change to the content, so that
+    /// recorded copies aren't lost when empty commits are abandoned.
     pub fn is_empty(&self, repo: &dyn Repo) -> BackendResult<bool> {
         if let Some(empty) = is_commit_empty_by_index(repo, &self.id)? {
             return Ok(empty);
@@ -15,6 +15,7 @@
 //! Code for working with copies and renames.
 
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::pin::Pin;
 use std::task::Context;
 use std::task::Poll;
@@ -23,7 +24,10 @@ use std::task::ready;
 use futures::Stream;
 
 use crate::backend::BackendResult;
+use crate::backend::CopyHistory;
+use crate::backend::CopyId;
 use crate::backend::CopyRecord;
+use crate::dag_walk;
 use crate::merge::Diff;
 use crate::merge::MergedTreeValue;
 use crate::merged_tree::MergedTree;
@@ -223,3 +227,40 @@ impl Stream for CopiesTreeDiffStream<'_> {
         Poll::Ready(None)
     }
 }
+
+/// Returns the copy histories related to `copy_id` out of `copies`, children
+/// before parents. Implements [`Backend::get_related_copies()`] for backends
+/// which can list all copy histories.
+///
+/// [`Backend::get_related_copies()`]: crate::backend::Backend::get_related_copies
+pub(crate) fn related_copy_histories(
+    copies: &HashMap<CopyId, CopyHistory>,
+    copy_id: &CopyId,
+) -> Vec<CopyHistory> {
+    let parents_of =
+        |id: &CopyId| -> &[CopyId] { copies.get(id).map_or(&[], |copy| copy.parents.as_slice()) };
+    let mut children: HashMap<&CopyId, Vec<&CopyId>> = HashMap::new();
+    for (id, copy) in copies {
+        for parent_id in &copy.parents {
+            children.entry(parent_id).or_default().push(id);
+        }
+    }
+    let ancestors = dag_walk::dfs([copy_id], |id| *id, |id| parents_of(id));
+    let related: HashSet<&CopyId> = dag_walk::dfs(
+        ancestors,
+        |id| *id,
+        |id| children.get(id).cloned().unwrap_or_default(),
+    )
+    .collect();
+    let sorted_ids = dag_walk::topo_order_reverse(
+        related.iter().copied(),
+        |id| *id,
+        |id| parents_of(id).iter().filter(|id| related.contains(id)),
+        |_| panic!("graph has cycle"),
+    )
+    .unwrap();
+    sorted_ids
+        .into_iter()
+        .filter_map(|id| copies.get(id).cloned())
+        .collect()
+}
@@ -275,6 +275,44 @@ impl Debug for ReadonlyCommitIndexSegment {
     }
 }
 
+/// Reads the format version and the parent segment id of the commit segment
+/// file.
+fn read_commit_segment_header(
+    file: &mut dyn Read,
+    id: &CommitIndexSegmentId,
+) -> Result<Option<CommitIndexSegmentId>, ReadonlyIndexLoadError> {
+    let from_io_err = |err| ReadonlyIndexLoadError::from_io_err("commit", id.hex(), err);
+    let read_u32 = |file: &mut dyn Read| {
+        let mut buf = [0; 4];
+        file.read_exact(&mut buf).map_err(from_io_err)?;
+        Ok(u32::from_le_bytes(buf))
+    };
+    let format_version = read_u32(file)?;
+    if format_version != COMMIT_INDEX_SEGMENT_FILE_FORMAT_VERSION {
+        return Err(ReadonlyIndexLoadError::UnexpectedVersion {
+            kind: "commit",
+            found_version: format_version,
+            expected_version: COMMIT_INDEX_SEGMENT_FILE_FORMAT_VERSION,
+        });
+    }
+    let parent_filename_len = read_u32(file)?;
+    if parent_filename_len == 0 {
+        return Ok(None);
+    }
+    let mut parent_filename_bytes = vec![0; parent_filename_len as usize];
+    file.read_exact(&mut parent_filename_bytes)
+        .map_err(from_io_err)?;
+    let parent_file_id =
+        CommitIndexSegmentId::try_from_hex(parent_filename_bytes).ok_or_else(|| {
+            ReadonlyIndexLoadError::invalid_data(
+                "commit",
+                id.hex(),
+                "parent file name is not valid hex",
+            )
+        })?;
+    Ok(Some(parent_file_id))
+}
+
 impl ReadonlyCommitIndexSegment {
     /// Loads both parent segments and local entries from the given file `name`.
     pub(super) fn load(
@@ -294,41 +332,24 @@ impl ReadonlyCommitIndexSegment {
         id: CommitIndexSegmentId,
         lengths: FieldLengths,
     ) -> Result<Arc<Self>, ReadonlyIndexLoadError> {
-        let from_io_err = |err| ReadonlyIndexLoadError::from_io_err("commit", id.hex(), err);
-        let read_u32 = |file: &mut dyn Read| {
-            let mut buf = [0; 4];
-            file.read_exact(&mut buf).map_err(from_io_err)?;
-            Ok(u32::from_le_bytes(buf))
-        };
-        let format_version = read_u32(file)?;
-        if format_version != COMMIT_INDEX_SEGMENT_FILE_FORMAT_VERSION {
-            return Err(ReadonlyIndexLoadError::UnexpectedVersion {
-                kind: "commit",
-                found_version: format_version,
-                expected_version: COMMIT_INDEX_SEGMENT_FILE_FORMAT_VERSION,
-            });
-        }
-        let parent_filename_len = read_u32(file)?;
-        let maybe_parent_file = if parent_filename_len > 0 {
-            let mut parent_filename_bytes = vec![0; parent_filename_len as usize];
-            file.read_exact(&mut parent_filename_bytes)
-                .map_err(from_io_err)?;
-            let parent_file_id = CommitIndexSegmentId::try_from_hex(parent_filename_bytes)
-                .ok_or_else(|| {
-                    ReadonlyIndexLoadError::invalid_data(
-                        "commit",
-                        id.hex(),
-                        "parent file name is not valid hex",
-                    )
-                })?;
-            let parent_file = Self::load(dir, parent_file_id, lengths)?;
-            Some(parent_file)
-        } else {
-            None
+        let maybe_parent_file = match read_commit_segment_header(file, &id)? {
+            Some(parent_file_id) => Some(Self::load(dir, parent_file_id, lengths)?),
+            None => None,
         };
         Self::load_with_parent_file(file, id, maybe_parent_file, lengths)
     }
 
+    /// Reads the parent segment id from the file of the given segment `id`.
+    /// The local entries aren't loaded.
+    pub(super) fn load_parent_id(
+        dir: &Path,
+        id: &CommitIndexSegmentId,
+    ) -> Result<Option<CommitIndexSegmentId>, ReadonlyIndexLoadError> {
+        let mut file = File::open(dir.join(id.hex()))
+            .map_err(|err| ReadonlyIndexLoadError::from_io_err("commit", id.hex(), err))?;
+        read_commit_segment_header(&mut file, id)
+    }
+
     /// Loads local entries from the given `file`, returns new segment linked to
     /// the given `parent_file`.
     pub(super) fn load_with_parent_file(
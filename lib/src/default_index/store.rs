@@ -50,6 +50,8 @@ use crate::file_util;
 use crate::file_util::IoResultExt as _;
 use crate::file_util::PathError;
 use crate::file_util::persist_temp_file;
+use crate::gc::GcOptions;
+use crate::gc::GcStats;
 use crate::index::IndexStore;
 use crate::index::IndexStoreError;
 use crate::index::IndexStoreResult;
@@ -94,10 +96,19 @@ pub enum DefaultIndexStoreError {
         op_id: OperationId,
         source: BackendError,
     },
+    #[error("Failed to remove unused index files")]
+    Gc(#[source] PathError),
     #[error(transparent)]
     OpStore(#[from] OpStoreError),
 }
 
+/// Segment files associated with an operation.
+struct OpLink {
+    commit_segment_id: CommitIndexSegmentId,
+    changed_path_start_commit_pos: Option<GlobalCommitPosition>,
+    changed_path_segment_ids: Vec<ChangedPathIndexSegmentId>,
+}
+
 #[derive(Debug)]
 pub struct DefaultIndexStore {
     dir: PathBuf,
@@ -177,14 +188,8 @@ impl DefaultIndexStore {
         self.dir.join("changed_paths")
     }
 
-    fn load_index_at_operation(
-        &self,
-        op_id: &OperationId,
-        lengths: FieldLengths,
-    ) -> Result<DefaultReadonlyIndex, DefaultIndexStoreError> {
-        let commit_segment_id;
-        let changed_path_start_commit_pos;
-        let changed_path_segment_ids;
+    /// Reads the segment file names associated with the given operation.
+    fn load_op_link(&self, op_id: &OperationId) -> Result<OpLink, DefaultIndexStoreError> {
         let op_link_file = self.op_links_dir().join(op_id.hex());
         match fs::read(&op_link_file).context(&op_link_file) {
             Ok(data) => {
@@ -192,15 +197,17 @@ impl DefaultIndexStore {
                     .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
                     .context(&op_link_file)
                     .map_err(DefaultIndexStoreError::LoadAssociation)?;
-                commit_segment_id = CommitIndexSegmentId::new(proto.commit_segment_id);
-                changed_path_start_commit_pos = proto
-                    .changed_path_start_commit_pos
-                    .map(GlobalCommitPosition);
-                changed_path_segment_ids = proto
-                    .changed_path_segment_ids
-                    .into_iter()
-                    .map(ChangedPathIndexSegmentId::new)
-                    .collect_vec();
+                Ok(OpLink {
+                    commit_segment_id: CommitIndexSegmentId::new(proto.commit_segment_id),
+                    changed_path_start_commit_pos: proto
+                        .changed_path_start_commit_pos
+                        .map(GlobalCommitPosition),
+                    changed_path_segment_ids: proto
+                        .changed_path_segment_ids
+                        .into_iter()
+                        .map(ChangedPathIndexSegmentId::new)
+                        .collect_vec(),
+                })
             }
             // TODO: drop support for legacy operation link file in jj 0.39 or so
             Err(PathError { source: error, .. }) if error.kind() == io::ErrorKind::NotFound => {
@@ -208,17 +215,32 @@ impl DefaultIndexStore {
                 let data = fs::read(&op_id_file)
                     .context(&op_id_file)
                     .map_err(DefaultIndexStoreError::LoadAssociation)?;
-                commit_segment_id = CommitIndexSegmentId::try_from_hex(&data)
+                let commit_segment_id = CommitIndexSegmentId::try_from_hex(&data)
                     .ok_or_else(|| {
                         io::Error::new(io::ErrorKind::InvalidData, "file name is not valid hex")
                     })
                     .context(&op_id_file)
                     .map_err(DefaultIndexStoreError::LoadAssociation)?;
-                changed_path_start_commit_pos = None;
-                changed_path_segment_ids = vec![];
+                Ok(OpLink {
+                    commit_segment_id,
+                    changed_path_start_commit_pos: None,
+                    changed_path_segment_ids: vec![],
+                })
             }
-            Err(err) => return Err(DefaultIndexStoreError::LoadAssociation(err)),
+            Err(err) => Err(DefaultIndexStoreError::LoadAssociation(err)),
         }
+    }
+
+    fn load_index_at_operation(
+        &self,
+        op_id: &OperationId,
+        lengths: FieldLengths,
+    ) -> Result<DefaultReadonlyIndex, DefaultIndexStoreError> {
+        let OpLink {
+            commit_segment_id,
+            changed_path_start_commit_pos,
+            changed_path_segment_ids,
+        } = self.load_op_link(op_id)?;
 
         let commits = ReadonlyCommitIndexSegment::load(
             &self.commit_segments_dir(),
@@ -240,6 +262,129 @@ impl DefaultIndexStore {
         Ok(DefaultReadonlyIndex::from_segment(commits, changed_paths))
     }
 
+    /// Prunes index files which are no longer used by the `head_ops` and
+    /// their ancestors.
+    ///
+    /// Links of unreachable operations and the segment files no longer
+    /// referenced by the remaining links are removed. Files created after
+    /// `options.keep_newer` are preserved.
+    #[tracing::instrument(skip_all)]
+    pub fn gc(
+        &self,
+        head_ops: &[Operation],
+        options: &GcOptions,
+    ) -> Result<GcStats, DefaultIndexStoreError> {
+        let reachable_op_ids: HashSet<OperationId> = op_walk::walk_ancestors(head_ops)
+            .map_ok(|op| op.id().clone())
+            .try_collect()?;
+        // Returns true if the file was (or would be) removed.
+        let remove_file_if_not_new = |entry: &fs::DirEntry| -> Result<bool, PathError> {
+            let path = entry.path();
+            let metadata = entry.metadata().context(&path)?;
+            let mtime = metadata.modified().expect("unsupported platform?");
+            if mtime > options.keep_newer {
+                tracing::trace!(?path, "not removing");
+                Ok(false)
+            } else if options.dry_run {
+                tracing::trace!(?path, "would remove");
+                Ok(true)
+            } else {
+                tracing::trace!(?path, "removing");
+                fs::remove_file(&path).context(&path)?;
+                Ok(true)
+            }
+        };
+
+        let mut live_op_ids = HashSet::new();
+        for dir in [self.op_links_dir(), self.legacy_operations_dir()] {
+            for entry in dir
+                .read_dir()
+                .context(&dir)
+                .map_err(DefaultIndexStoreError::Gc)?
+            {
+                let entry = entry.context(&dir).map_err(DefaultIndexStoreError::Gc)?;
+                let Some(op_id) = entry
+                    .file_name()
+                    .to_str()
+                    .and_then(OperationId::try_from_hex)
+                else {
+                    continue;
+                };
+                if reachable_op_ids.contains(&op_id)
+                    || !remove_file_if_not_new(&entry).map_err(DefaultIndexStoreError::Gc)?
+                {
+                    live_op_ids.insert(op_id);
+                }
+            }
+        }
+
+        let mut live_commit_segments = HashSet::new();
+        let mut live_changed_path_segments = HashSet::new();
+        let commit_segments_dir = self.commit_segments_dir();
+        for op_id in &live_op_ids {
+            let link = match self.load_op_link(op_id) {
+                Ok(link) => link,
+                // The link may have been removed by another process.
+                Err(DefaultIndexStoreError::LoadAssociation(PathError {
+                    source: error, ..
+                })) if error.kind() == io::ErrorKind::NotFound => {
+                    continue;
+                }
+                Err(err) => return Err(err),
+            };
+            let mut maybe_segment_id = Some(link.commit_segment_id);
+            while let Some(segment_id) = maybe_segment_id {
+                if !live_commit_segments.insert(segment_id.hex()) {
+                    break;
+                }
+                maybe_segment_id =
+                    ReadonlyCommitIndexSegment::load_parent_id(&commit_segments_dir, &segment_id)
+                        .map_err(DefaultIndexStoreError::LoadIndex)?;
+            }
+            live_changed_path_segments.extend(
+                link.changed_path_segment_ids
+                    .iter()
+                    .map(|segment_id| segment_id.hex()),
+            );
+        }
+
+        let mut index_segments = 0;
+        for (dir, live_segments) in [
+            (commit_segments_dir, &live_commit_segments),
+            (
+                self.changed_path_segments_dir(),
+                &live_changed_path_segments,
+            ),
+        ] {
+            for entry in dir
+                .read_dir()
+                .context(&dir)
+                .map_err(DefaultIndexStoreError::Gc)?
+            {
+                let entry = entry.context(&dir).map_err(DefaultIndexStoreError::Gc)?;
+                let Some(name) = entry
+                    .file_name()
+                    .into_string()
+                    .ok()
+                    .filter(|name| name.len() == SEGMENT_FILE_NAME_LENGTH)
+                else {
+                    // Skip temporary files, etc.
+                    continue;
+                };
+                if live_segments.contains(&name) {
+                    continue;
+                }
+                if remove_file_if_not_new(&entry).map_err(DefaultIndexStoreError::Gc)? {
+                    index_segments += 1;
+                }
+            }
+        }
+        Ok(GcStats {
+            index_segments,
+            ..GcStats::default()
+        })
+    }
+
     /// Rebuilds index for the given `operation`.
     ///
     /// The index to be built will be calculated from one of the ancestor
@@ -14,38 +14,161 @@
 
 #![expect(missing_docs)]
 
+use std::ffi::OsStr;
+use std::fs;
 use std::path::Path;
 use std::path::PathBuf;
+use std::process::Command;
+use std::process::Stdio;
 
+use crate::backend::CommitId;
+use crate::config::ConfigGetError;
+use crate::object_id::ObjectId as _;
+use crate::settings::UserSettings;
+use crate::submodule_store::SubmoduleConfig;
 use crate::submodule_store::SubmoduleStore;
+use crate::submodule_store::SubmoduleStoreError;
 
+/// Stores the Git repositories of submodules in `<store_path>/repos/<name>`,
+/// similar to how Git stores them in `.git/modules/<name>`.
+///
+/// The working copy of a submodule contains a `.git` file pointing to its
+/// repository, so `git` commands can be run in it.
 #[derive(Debug)]
 pub struct DefaultSubmoduleStore {
-    #[expect(dead_code)]
     path: PathBuf,
+    git_executable: PathBuf,
 }
 
 impl DefaultSubmoduleStore {
     /// Load an existing SubmoduleStore
-    pub fn load(store_path: &Path) -> Self {
-        Self {
+    pub fn load(settings: &UserSettings, store_path: &Path) -> Result<Self, ConfigGetError> {
+        Ok(Self {
             path: store_path.to_path_buf(),
-        }
+            git_executable: settings.get("git.executable-path")?,
+        })
     }
 
-    pub fn init(store_path: &Path) -> Self {
-        Self {
-            path: store_path.to_path_buf(),
-        }
+    pub fn init(settings: &UserSettings, store_path: &Path) -> Result<Self, ConfigGetError> {
+        Self::load(settings, store_path)
     }
 
     pub fn name() -> &'static str {
         "default"
     }
+
+    /// Path to the Git repository of the submodule `name`.
+    pub fn git_dir(&self, name: &str) -> Result<PathBuf, SubmoduleStoreError> {
+        // Git rejects names that could escape the modules directory.
+        let is_valid = !name.is_empty()
+            && name
+                .split(['/', '\\'])
+                .all(|component| !matches!(component, "" | "." | ".."));
+        if !is_valid {
+            return Err(SubmoduleStoreError::InvalidName(name.to_owned()));
+        }
+        Ok(self.path.join("repos").join(name))
+    }
+
+    fn run_git(
+        &self,
+        work_dir: Option<&Path>,
+        args: &[&OsStr],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut command = Command::new(&self.git_executable);
+        if let Some(work_dir) = work_dir {
+            command.arg("-C").arg(work_dir);
+        }
+        let output = command
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .output()?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            Err(format!("git exited with {}: {}", output.status, stderr.trim_end()).into())
+        }
+    }
 }
 
 impl SubmoduleStore for DefaultSubmoduleStore {
     fn name(&self) -> &str {
         Self::name()
     }
+
+    fn check_out(
+        &self,
+        submodule: &SubmoduleConfig,
+        disk_path: &Path,
+        commit_id: &CommitId,
+    ) -> Result<(), SubmoduleStoreError> {
+        let git_dir = self.git_dir(&submodule.name)?;
+        let clone_error = |source| SubmoduleStoreError::Clone {
+            name: submodule.name.clone(),
+            url: submodule.url.clone(),
+            source,
+        };
+        let check_out_error = |source| SubmoduleStoreError::CheckOut {
+            name: submodule.name.clone(),
+            commit_id: commit_id.clone(),
+            source,
+        };
+        if disk_path.join(".git").symlink_metadata().is_err() {
+            if git_dir.exists() {
+                // The working copy was removed, but the repository is still
+                // there.
+                fs::create_dir_all(disk_path).map_err(|err| check_out_error(err.into()))?;
+                let content = format!("gitdir: {}\n", git_dir.display());
+                fs::write(disk_path.join(".git"), content)
+                    .map_err(|err| check_out_error(err.into()))?;
+            } else {
+                fs::create_dir_all(git_dir.parent().unwrap())
+                    .map_err(|err| clone_error(err.into()))?;
+                self.run_git(
+                    None,
+                    &[
+                        "clone".as_ref(),
+                        "--quiet".as_ref(),
+                        "--no-checkout".as_ref(),
+                        "--separate-git-dir".as_ref(),
+                        git_dir.as_ref(),
+                        "--".as_ref(),
+                        submodule.url.as_ref(),
+                        disk_path.as_ref(),
+                    ],
+                )
+                .map_err(clone_error)?;
+            }
+        }
+
+        let hex = commit_id.hex();
+        let commit_spec = format!("{hex}^{{commit}}");
+        let has_commit = || {
+            self.run_git(
+                Some(disk_path),
+                &["cat-file".as_ref(), "-e".as_ref(), commit_spec.as_ref()],
+            )
+            .is_ok()
+        };
+        if !has_commit() {
+            self.run_git(
+                Some(disk_path),
+                &["fetch".as_ref(), "--quiet".as_ref(), "origin".as_ref()],
+            )
+            .map_err(check_out_error)?;
+        }
+        self.run_git(
+            Some(disk_path),
+            &[
+                "checkout".as_ref(),
+                "--quiet".as_ref(),
+                "--detach".as_ref(),
+                hex.as_ref(),
+            ],
+        )
+        .map_err(check_out_error)
+    }
 }
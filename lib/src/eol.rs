@@ -19,6 +19,9 @@ use tokio::io::AsyncRead;
 use tokio::io::AsyncReadExt as _;
 
 use crate::config::ConfigGetError;
+use crate::gitattributes::AttributeValue;
+use crate::gitattributes::GitAttributesLookup;
+use crate::repo_path::RepoPath;
 use crate::settings::UserSettings;
 
 fn is_binary(bytes: &[u8]) -> bool {
@@ -40,6 +43,42 @@ fn is_binary(bytes: &[u8]) -> bool {
     false
 }
 
+/// Line ending settings of a file from the `text` and `eol` attributes in
+/// `.gitattributes` files. They take precedence over the
+/// `working-copy.eol-conversion` setting.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct EolAttributes {
+    text: AttributeValue,
+    eol: AttributeValue,
+}
+
+impl EolAttributes {
+    /// Attributes of a file which no `.gitattributes` pattern matches.
+    #[cfg(test)]
+    fn unspecified() -> Self {
+        Self {
+            text: AttributeValue::Unspecified,
+            eol: AttributeValue::Unspecified,
+        }
+    }
+
+    pub(crate) fn from_lookup(attributes: &GitAttributesLookup, path: &RepoPath) -> Self {
+        Self {
+            text: attributes.get(path, "text"),
+            eol: attributes.get(path, "eol"),
+        }
+    }
+}
+
+/// How to convert the line endings of a file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct EolConversion {
+    /// Whether to skip the conversion if the contents look binary.
+    detect_binary: bool,
+    snapshot: TargetEol,
+    update: TargetEol,
+}
+
 #[derive(Clone)]
 pub(crate) struct TargetEolStrategy {
     eol_conversion_mode: EolConversionMode,
@@ -83,44 +122,87 @@ impl TargetEolStrategy {
         Ok(is_binary(slice_to_check))
     }
 
-    pub(crate) async fn convert_eol_for_snapshot<'a>(
-        &self,
+    /// Determines the conversion of a file with the given `attributes`,
+    /// following Git's rules:
+    ///
+    /// * `-text` (or `binary`) disables the conversion.
+    /// * `text` always normalizes line endings to LF in the store.
+    /// * `text=auto` normalizes them unless the file looks binary.
+    /// * `eol=lf` or `eol=crlf` implies `text`, and sets the line endings in
+    ///   the working copy.
+    /// * Otherwise, the `working-copy.eol-conversion` setting applies.
+    fn conversion_for(&self, attributes: &EolAttributes) -> EolConversion {
+        let mode_update = match self.eol_conversion_mode {
+            EolConversionMode::None | EolConversionMode::Input => TargetEol::PassThrough,
+            EolConversionMode::InputOutput => TargetEol::Crlf,
+        };
+        // Files are checked out as stored unless CRLF is requested, as in Git.
+        let attribute_update = match attributes.eol.as_value() {
+            Some("crlf") => Some(TargetEol::Crlf),
+            Some("lf") => Some(TargetEol::PassThrough),
+            _ => None,
+        };
+        let text = |detect_binary| EolConversion {
+            detect_binary,
+            snapshot: TargetEol::Lf,
+            update: attribute_update.unwrap_or(mode_update),
+        };
+        match &attributes.text {
+            AttributeValue::Unset => EolConversion {
+                detect_binary: false,
+                snapshot: TargetEol::PassThrough,
+                update: TargetEol::PassThrough,
+            },
+            AttributeValue::Set => text(false),
+            AttributeValue::Value(value) if value == "auto" => text(true),
+            _ if attribute_update.is_some() => text(false),
+            _ => EolConversion {
+                detect_binary: true,
+                snapshot: match self.eol_conversion_mode {
+                    EolConversionMode::None => TargetEol::PassThrough,
+                    EolConversionMode::Input | EolConversionMode::InputOutput => TargetEol::Lf,
+                },
+                update: mode_update,
+            },
+        }
+    }
+
+    async fn convert<'a>(
         mut contents: impl AsyncRead + Send + Unpin + 'a,
+        detect_binary: bool,
+        target_eol: TargetEol,
     ) -> Result<Box<dyn AsyncRead + Send + Unpin + 'a>, std::io::Error> {
-        match self.eol_conversion_mode {
-            EolConversionMode::None => Ok(Box::new(contents)),
-            EolConversionMode::Input | EolConversionMode::InputOutput => {
-                let mut peek = vec![];
-                let target_eol = if Self::probe_for_binary(&mut contents, &mut peek).await? {
-                    TargetEol::PassThrough
-                } else {
-                    TargetEol::Lf
-                };
-                let peek = Cursor::new(peek);
-                let contents = peek.chain(contents);
-                convert_eol(contents, target_eol).await
-            }
+        if target_eol == TargetEol::PassThrough {
+            return Ok(Box::new(contents));
         }
+        let mut peek = vec![];
+        let target_eol =
+            if detect_binary && Self::probe_for_binary(&mut contents, &mut peek).await? {
+                TargetEol::PassThrough
+            } else {
+                target_eol
+            };
+        let peek = Cursor::new(peek);
+        let contents = peek.chain(contents);
+        convert_eol(contents, target_eol).await
+    }
+
+    pub(crate) async fn convert_eol_for_snapshot<'a>(
+        &self,
+        attributes: &EolAttributes,
+        contents: impl AsyncRead + Send + Unpin + 'a,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin + 'a>, std::io::Error> {
+        let conversion = self.conversion_for(attributes);
+        Self::convert(contents, conversion.detect_binary, conversion.snapshot).await
     }
 
     pub(crate) async fn convert_eol_for_update<'a>(
         &self,
-        mut contents: impl AsyncRead + Send + Unpin + 'a,
+        attributes: &EolAttributes,
+        contents: impl AsyncRead + Send + Unpin + 'a,
     ) -> Result<Box<dyn AsyncRead + Send + Unpin + 'a>, std::io::Error> {
-        match self.eol_conversion_mode {
-            EolConversionMode::None | EolConversionMode::Input => Ok(Box::new(contents)),
-            EolConversionMode::InputOutput => {
-                let mut peek = vec![];
-                let target_eol = if Self::probe_for_binary(&mut contents, &mut peek).await? {
-                    TargetEol::PassThrough
-                } else {
-                    TargetEol::Crlf
-                };
-                let peek = Cursor::new(peek);
-                let contents = peek.chain(contents);
-                convert_eol(contents, target_eol).await
-            }
-        }
+        let conversion = self.conversion_for(attributes);
+        Self::convert(contents, conversion.detect_binary, conversion.update).await
     }
 }
 
@@ -324,7 +406,7 @@ mod tests {
     ) {
         let mut actual_output = vec![];
         strategy
-            .convert_eol_for_snapshot(contents)
+            .convert_eol_for_snapshot(&EolAttributes::unspecified(), contents)
             .await
             .unwrap()
             .read_to_end(&mut actual_output)
@@ -356,7 +438,7 @@ mod tests {
     ) {
         let mut actual_output = vec![];
         strategy
-            .convert_eol_for_update(contents)
+            .convert_eol_for_update(&EolAttributes::unspecified(), contents)
             .await
             .unwrap()
             .read_to_end(&mut actual_output)
@@ -364,4 +446,54 @@ mod tests {
             .unwrap();
         assert_eq!(actual_output, expected_output);
     }
+
+    #[tokio::main(flavor = "current_thread")]
+    #[test_case(EolConversionMode::None, "* text", b"a\r\n\0", b"a\n\0", b"a\n\0";
+        "text set ignores binary detection")]
+    #[test_case(EolConversionMode::InputOutput, "* -text", b"a\r\n", b"a\r\n", b"a\r\n";
+        "text unset disables the conversion")]
+    #[test_case(EolConversionMode::InputOutput, "* binary", b"a\r\n", b"a\r\n", b"a\r\n";
+        "binary macro disables the conversion")]
+    #[test_case(EolConversionMode::None, "* text=auto", b"a\r\n", b"a\n", b"a\n";
+        "text auto normalizes text files")]
+    #[test_case(EolConversionMode::None, "* text=auto", b"a\r\n\0", b"a\r\n\0", b"a\r\n\0";
+        "text auto keeps binary files")]
+    #[test_case(EolConversionMode::None, "* eol=crlf", b"a\r\n", b"a\n", b"a\r\n";
+        "eol crlf")]
+    #[test_case(EolConversionMode::InputOutput, "* eol=lf", b"a\r\n", b"a\n", b"a\n";
+        "eol lf overrides the setting")]
+    #[test_case(EolConversionMode::InputOutput, "*.bin -text", b"a\r\n", b"a\n", b"a\r\n";
+        "unmatched path uses the setting")]
+    async fn test_eol_strategy_convert_eol_with_attributes(
+        eol_conversion_mode: EolConversionMode,
+        gitattributes: &'static str,
+        contents: &[u8],
+        expected_snapshot: &[u8],
+        expected_update: &[u8],
+    ) {
+        let strategy = TargetEolStrategy {
+            eol_conversion_mode,
+        };
+        let lookup = GitAttributesLookup::new(|_| Some(gitattributes.as_bytes().to_vec()));
+        let attributes =
+            EolAttributes::from_lookup(&lookup, RepoPath::from_internal_string("file").unwrap());
+        let mut actual_snapshot = vec![];
+        strategy
+            .convert_eol_for_snapshot(&attributes, contents)
+            .await
+            .unwrap()
+            .read_to_end(&mut actual_snapshot)
+            .await
+            .unwrap();
+        assert_eq!(actual_snapshot, expected_snapshot);
+        let mut actual_update = vec![];
+        strategy
+            .convert_eol_for_update(&attributes, expected_snapshot)
+            .await
+            .unwrap()
+            .read_to_end(&mut actual_update)
+            .await
+            .unwrap();
+        assert_eq!(actual_update, expected_update);
+    }
 }
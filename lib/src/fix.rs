@@ -17,32 +17,38 @@
 
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::ops::Range;
 use std::sync::mpsc::channel;
 
+use bstr::ByteSlice as _;
 use futures::StreamExt as _;
 use itertools::Itertools as _;
 use jj_lib::backend::BackendError;
 use jj_lib::backend::CommitId;
 use jj_lib::backend::FileId;
 use jj_lib::backend::TreeValue;
+use jj_lib::copies::CopiesTreeDiffEntry;
+use jj_lib::copies::CopyRecords;
+use jj_lib::diff::ContentDiff;
+use jj_lib::diff::DiffHunkKind;
 use jj_lib::matchers::Matcher;
-use jj_lib::merged_tree::TreeDiffEntry;
+use jj_lib::merged_tree::MergedTree;
 use jj_lib::merged_tree_builder::MergedTreeBuilder;
+use jj_lib::object_id::ObjectId as _;
 use jj_lib::repo::MutableRepo;
 use jj_lib::repo::Repo as _;
+use jj_lib::repo_path::RepoPath;
 use jj_lib::repo_path::RepoPathBuf;
 use jj_lib::revset::RevsetExpression;
 use jj_lib::revset::RevsetIteratorExt as _;
 use jj_lib::store::Store;
 use rayon::iter::IntoParallelIterator as _;
 use rayon::prelude::ParallelIterator as _;
+use tokio::io::AsyncReadExt as _;
 
 use crate::revset::RevsetEvaluationError;
 
 /// Represents a file whose content may be transformed by a FileFixer.
-// TODO: Add the set of changed line/byte ranges, so those can be passed into code formatters via
-// flags. This will help avoid introducing unrelated changes when working on code with out of date
-// formatting.
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub struct FileToFix {
     /// Unique identifier for the file content.
@@ -53,6 +59,15 @@ pub struct FileToFix {
     ///  - Update parts of the file's content that should be derived from the
     ///    file's path.
     pub repo_path: RepoPathBuf,
+
+    /// The 0-based ranges of lines that were changed compared to the parent
+    /// revision, or to the revision before the first fixed ancestor.
+    ///
+    /// This allows code formatters to only modify the changed lines, which
+    /// avoids introducing unrelated changes when working on code with out of
+    /// date formatting. It is only populated if requested, and is empty if the
+    /// file only has deletions.
+    pub line_ranges: Vec<Range<usize>>,
 }
 
 /// Error fixing files.
@@ -170,10 +185,17 @@ where
 /// that the fixes are not lost. This will never result in new conflicts. Files
 /// with existing conflicts are updated on all sides of the conflict, which
 /// can potentially increase or decrease the number of conflict markers.
+///
+/// If `include_line_ranges` is true, the files to fix are annotated with the
+/// ranges of lines that were changed. Fixes in ancestors are propagated to
+/// descendants, so the ranges are computed against the parent of the first
+/// fixed ancestor, or against the empty tree if `include_unchanged_files` is
+/// true.
 pub async fn fix_files(
     root_commits: Vec<CommitId>,
     matcher: &dyn Matcher,
     include_unchanged_files: bool,
+    include_line_ranges: bool,
     repo_mut: &mut MutableRepo,
     file_fixer: &mut impl FileFixer,
 ) -> Result<FixSummary, FixError> {
@@ -207,9 +229,11 @@ pub async fn fix_files(
     );
 
     let mut unique_files_to_fix: HashSet<FileToFix> = HashSet::new();
-    let mut commit_paths: HashMap<CommitId, HashSet<RepoPathBuf>> = HashMap::new();
+    let mut commit_files: HashMap<CommitId, HashMap<RepoPathBuf, Vec<FileToFix>>> = HashMap::new();
+    // The trees that changed line ranges are computed against.
+    let mut base_trees: HashMap<CommitId, MergedTree> = HashMap::new();
     for commit in commits.iter().rev() {
-        let mut paths: HashSet<RepoPathBuf> = HashSet::new();
+        let mut files: HashMap<RepoPathBuf, Vec<FileToFix>> = HashMap::new();
 
         // If --include-unchanged-files, we always fix every matching file in the tree.
         // Otherwise, we fix the matching changed files in this commit, plus any that
@@ -219,20 +243,54 @@ pub async fn fix_files(
             repo_mut.store().empty_merged_tree()
         } else {
             for parent_id in commit.parent_ids() {
-                if let Some(parent_paths) = commit_paths.get(parent_id) {
-                    paths.extend(parent_paths.iter().cloned());
+                if let Some(parent_files) = commit_files.get(parent_id) {
+                    for (repo_path, files_to_fix) in parent_files {
+                        files
+                            .entry(repo_path.clone())
+                            .or_default()
+                            .extend(files_to_fix.iter().cloned());
+                    }
                 }
             }
             commit.parent_tree_async(repo_mut).await?
         };
-        // TODO: handle copy tracking
-        let mut diff_stream = parent_tree.diff_stream(&commit.tree(), &matcher);
-        while let Some(TreeDiffEntry {
-            path: repo_path,
-            values,
-        }) = diff_stream.next().await
-        {
+        let base_tree = if include_line_ranges {
+            let base_tree = commit
+                .parent_ids()
+                .iter()
+                .find_map(|parent_id| base_trees.get(parent_id))
+                .unwrap_or(&parent_tree)
+                .clone();
+            base_trees.insert(commit.id().clone(), base_tree.clone());
+            Some(base_tree)
+        } else {
+            None
+        };
+        // Copied and renamed files only need the lines changed since the
+        // source to be fixed.
+        let mut copy_records = CopyRecords::default();
+        if !include_unchanged_files {
+            for parent_id in commit.parent_ids() {
+                let records: Vec<_> = repo_mut
+                    .store()
+                    .get_copy_records(None, parent_id, commit.id())?
+                    .collect()
+                    .await;
+                copy_records.add_records(records)?;
+            }
+        }
+        let mut diff_stream =
+            parent_tree.diff_stream_with_copies(&commit.tree(), &matcher, &copy_records);
+        while let Some(CopiesTreeDiffEntry { path, values }) = diff_stream.next().await {
             let after = values?.after;
+            let base_content = match &base_tree {
+                Some(base_tree) => {
+                    Some(read_resolved_file(repo_mut.store(), path.source(), base_tree).await?)
+                }
+                None => None,
+            };
+            let repo_path = path.target;
+            let mut path_files = vec![];
             // Deleted files have no file content to fix, and they have no terms in `after`,
             // so we don't add any files-to-fix for them. Conflicted files produce one
             // file-to-fix for each side of the conflict.
@@ -247,17 +305,28 @@ pub async fn fix_files(
                 {
                     // TODO: Skip the file if its content is larger than some configured size,
                     // preferably without actually reading it yet.
+                    let line_ranges = match &base_content {
+                        Some(base_content) => {
+                            let content = read_file(repo_mut.store(), &repo_path, &id).await?;
+                            changed_line_ranges(base_content, &content)
+                        }
+                        None => vec![],
+                    };
                     let file_to_fix = FileToFix {
                         file_id: id.clone(),
                         repo_path: repo_path.clone(),
+                        line_ranges,
                     };
                     unique_files_to_fix.insert(file_to_fix.clone());
-                    paths.insert(repo_path.clone());
+                    path_files.push(file_to_fix);
                 }
             }
+            if !path_files.is_empty() {
+                files.insert(repo_path, path_files);
+            }
         }
 
-        commit_paths.insert(commit.id().clone(), paths);
+        commit_files.insert(commit.id().clone(), files);
     }
 
     tracing::debug!(
@@ -278,11 +347,11 @@ pub async fn fix_files(
             // TODO: Build the trees in parallel before `transform_descendants()` and only
             // keep the tree IDs in memory, so we can pass them to the rewriter.
             let old_commit_id = rewriter.old_commit().id().clone();
-            let repo_paths = commit_paths.get(&old_commit_id).unwrap();
+            let files = commit_files.get(&old_commit_id).unwrap();
             let old_tree = rewriter.old_commit().tree();
             let mut tree_builder = MergedTreeBuilder::new(old_tree.clone());
             let mut has_changes = false;
-            for repo_path in repo_paths {
+            for (repo_path, files_to_fix) in files {
                 let old_value = old_tree.path_value_async(repo_path).await?;
                 let new_value = old_value.map(|old_term| {
                     if let Some(TreeValue::File {
@@ -290,18 +359,16 @@ pub async fn fix_files(
                         executable,
                         copy_id,
                     }) = old_term
+                        && let Some(new_id) = files_to_fix
+                            .iter()
+                            .find(|file_to_fix| file_to_fix.file_id == *id)
+                            .and_then(|file_to_fix| fixed_file_ids.get(file_to_fix))
                     {
-                        let file_to_fix = FileToFix {
-                            file_id: id.clone(),
-                            repo_path: repo_path.clone(),
-                        };
-                        if let Some(new_id) = fixed_file_ids.get(&file_to_fix) {
-                            return Some(TreeValue::File {
-                                id: new_id.clone(),
-                                executable: *executable,
-                                copy_id: copy_id.clone(),
-                            });
-                        }
+                        return Some(TreeValue::File {
+                            id: new_id.clone(),
+                            executable: *executable,
+                            copy_id: copy_id.clone(),
+                        });
                     }
                     old_term.clone()
                 });
@@ -332,3 +399,64 @@ pub async fn fix_files(
     tracing::debug!(?summary);
     Ok(summary)
 }
+
+/// Reads the content of the file at `path`, or returns empty content if the
+/// path isn't a resolved file in the `tree`.
+async fn read_resolved_file(
+    store: &Store,
+    path: &RepoPath,
+    tree: &MergedTree,
+) -> Result<Vec<u8>, FixError> {
+    match tree.path_value_async(path).await?.into_resolved() {
+        Ok(Some(TreeValue::File { id, .. })) => read_file(store, path, &id).await,
+        _ => Ok(vec![]),
+    }
+}
+
+async fn read_file(store: &Store, path: &RepoPath, id: &FileId) -> Result<Vec<u8>, FixError> {
+    let mut content = vec![];
+    let mut reader = store.read_file(path, id).await?;
+    reader
+        .read_to_end(&mut content)
+        .await
+        .map_err(|err| BackendError::ReadObject {
+            object_type: id.object_type(),
+            hash: id.hex(),
+            source: err.into(),
+        })?;
+    Ok(content)
+}
+
+/// Returns the 0-based ranges of lines in `new` that differ from `old`.
+fn changed_line_ranges(old: &[u8], new: &[u8]) -> Vec<Range<usize>> {
+    let mut ranges = vec![];
+    let mut line = 0;
+    for hunk in ContentDiff::by_line([old, new]).hunks() {
+        let num_lines = hunk.contents[1].lines_with_terminator().count();
+        if hunk.kind == DiffHunkKind::Different && num_lines > 0 {
+            ranges.push(line..line + num_lines);
+        }
+        line += num_lines;
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changed_line_ranges() {
+        assert!(changed_line_ranges(b"", b"").is_empty());
+        assert_eq!(changed_line_ranges(b"", b"a\nb\n"), vec![0..2]);
+        assert!(changed_line_ranges(b"a\nb\n", b"").is_empty());
+        assert_eq!(
+            changed_line_ranges(b"a\nb\nc\nd\n", b"a\nB\nc\nd\ne\n"),
+            vec![1..2, 4..5]
+        );
+        // Deleted lines don't produce a range
+        assert!(changed_line_ranges(b"a\nb\nc\n", b"a\nc\n").is_empty());
+        // Missing newline at end of file
+        assert_eq!(changed_line_ranges(b"a\nb", b"a\nc"), vec![1..2]);
+    }
+}
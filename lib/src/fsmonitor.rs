@@ -34,12 +34,22 @@ pub struct WatchmanConfig {
     pub register_trigger: bool,
 }
 
+/// Config for the built-in inotify filesystem monitor.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct InotifyConfig {
+    /// Whether to start the daemon in the background when taking snapshots.
+    pub start_daemon: bool,
+}
+
 /// The recognized kinds of filesystem monitors.
 #[derive(Eq, PartialEq, Clone, Debug)]
 pub enum FsmonitorSettings {
     /// The Watchman filesystem monitor (<https://facebook.github.io/watchman/>).
     Watchman(WatchmanConfig),
 
+    /// The built-in filesystem monitor based on the Linux inotify API.
+    Inotify(InotifyConfig),
+
     /// Only used in tests.
     Test {
         /// The set of changed files to pretend that the filesystem monitor is
@@ -63,6 +73,9 @@ impl FsmonitorSettings {
                 register_trigger: settings
                     .get_bool("fsmonitor.watchman.register-snapshot-trigger")?,
             })),
+            "inotify" => Ok(Self::Inotify(InotifyConfig {
+                start_daemon: settings.get_bool("fsmonitor.inotify.start-daemon")?,
+            })),
             "test" => Err(ConfigGetError::Type {
                 name: name.to_owned(),
                 error: "Cannot use test fsmonitor in real repository".into(),
@@ -337,3 +350,865 @@ pub mod watchman {
         }
     }
 }
+
+/// Filesystem monitor based on the Linux inotify API.
+///
+/// Inotify watches only live as long as the process which created them, so a
+/// daemon process watches the working copy in the background. It appends the
+/// paths of the changed files and directories to a journal in the working copy
+/// state directory, and clocks are positions in that journal.
+#[cfg(target_os = "linux")]
+pub mod inotify {
+    use std::collections::BTreeSet;
+    use std::collections::HashMap;
+    use std::collections::HashSet;
+    use std::ffi::CStr;
+    use std::ffi::OsStr;
+    use std::fs;
+    use std::fs::File;
+    use std::io;
+    use std::io::BufReader;
+    use std::io::Read as _;
+    use std::io::Write as _;
+    use std::mem::MaybeUninit;
+    use std::os::fd::AsFd as _;
+    use std::os::fd::BorrowedFd;
+    use std::os::fd::OwnedFd;
+    use std::os::unix::ffi::OsStrExt as _;
+    use std::os::unix::fs::MetadataExt as _;
+    use std::path::Path;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use std::time::Instant;
+    use std::time::SystemTime;
+
+    use bstr::ByteSlice as _;
+    use rustix::event::PollFd;
+    use rustix::event::PollFlags;
+    use rustix::event::Timespec;
+    use rustix::fs::FlockOperation;
+    use rustix::fs::inotify::CreateFlags;
+    use rustix::fs::inotify::ReadFlags;
+    use rustix::fs::inotify::Reader;
+    use rustix::fs::inotify::WatchFlags;
+    use rustix::io::Errno;
+    use thiserror::Error;
+    use tracing::info;
+    use tracing::instrument;
+    use tracing::warn;
+
+    use crate::gitignore::GitIgnoreFile;
+
+    const LOCK_FILE_NAME: &str = "daemon.lock";
+    const JOURNAL_FILE_NAME: &str = "journal";
+    /// Directories which aren't watched because they are ignored. They are
+    /// reported as changed by every query.
+    const IGNORED_DIRS_FILE_NAME: &str = "ignored_dirs";
+    const COOKIE_PREFIX: &[u8] = b"cookie-";
+    /// The journal is restarted when it grows larger than this, which makes
+    /// the next snapshot crawl the entire working copy.
+    const MAX_JOURNAL_SIZE: usize = 16 << 20;
+    /// How long to wait for the daemon to catch up with the filesystem before
+    /// falling back to crawling the entire working copy.
+    const SYNC_TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// Represents an instance in time from the perspective of the filesystem
+    /// monitor.
+    ///
+    /// The clock points to a position in the journal written by the daemon.
+    /// It is invalidated when the daemon restarts the journal, for example
+    /// because it was restarted itself or because it missed some events.
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    pub struct Clock {
+        journal_id: String,
+        offset: u64,
+    }
+
+    impl From<crate::protos::local_working_copy::InotifyClock> for Clock {
+        fn from(clock: crate::protos::local_working_copy::InotifyClock) -> Self {
+            Self {
+                journal_id: clock.journal_id,
+                offset: clock.offset,
+            }
+        }
+    }
+
+    impl From<Clock> for crate::protos::local_working_copy::InotifyClock {
+        fn from(clock: Clock) -> Self {
+            Self {
+                journal_id: clock.journal_id,
+                offset: clock.offset,
+            }
+        }
+    }
+
+    #[expect(missing_docs)]
+    #[derive(Debug, Error)]
+    pub enum Error {
+        #[error("Failed to access the inotify state at {}", path.display())]
+        State {
+            path: PathBuf,
+            #[source]
+            source: io::Error,
+        },
+
+        #[error("Failed to watch the working copy")]
+        Watch(#[source] io::Error),
+    }
+
+    /// Handle to the inotify daemon of a working copy.
+    pub struct Fsmonitor {
+        working_copy_path: PathBuf,
+        state_dir: PathBuf,
+    }
+
+    impl Fsmonitor {
+        /// Creates a handle for the daemon watching `working_copy_path`, which
+        /// keeps its state in `state_dir`.
+        pub fn new(working_copy_path: &Path, state_dir: &Path) -> Self {
+            Self {
+                working_copy_path: working_copy_path.to_owned(),
+                state_dir: state_dir.to_owned(),
+            }
+        }
+
+        fn state_error(&self, path: impl AsRef<Path>) -> impl FnOnce(io::Error) -> Error {
+            let path = self.state_dir.join(path);
+            move |source| Error::State { path, source }
+        }
+
+        /// Returns whether a daemon is watching the working copy.
+        pub fn is_daemon_running(&self) -> Result<bool, Error> {
+            let file = match File::open(self.state_dir.join(LOCK_FILE_NAME)) {
+                Ok(file) => file,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(false),
+                Err(err) => return Err(self.state_error(LOCK_FILE_NAME)(err)),
+            };
+            // The daemon holds an exclusive lock for as long as it's running.
+            match rustix::fs::flock(&file, FlockOperation::NonBlockingLockShared) {
+                Ok(()) => Ok(false),
+                Err(Errno::WOULDBLOCK) => Ok(true),
+                Err(errno) => Err(self.state_error(LOCK_FILE_NAME)(errno.into())),
+            }
+        }
+
+        /// Query for changed files since the previous point in time.
+        ///
+        /// The returned list of paths is sorted and relative to the working
+        /// copy path.
+        /// Changed directories are included, in which case anything under them
+        /// may have changed. If the list is `None`, then the caller must crawl
+        /// the entire working copy themselves. The returned clock is `None` if
+        /// no daemon is running.
+        #[instrument(skip(self))]
+        pub fn query_changed_files(
+            &self,
+            previous_clock: Option<Clock>,
+        ) -> Result<(Option<Clock>, Option<Vec<PathBuf>>), Error> {
+            info!("Querying the inotify daemon for changed files...");
+            if !self.is_daemon_running()? {
+                info!("The inotify daemon is not running");
+                return Ok((None, None));
+            }
+            let Some(tail) = self.sync(previous_clock.as_ref())? else {
+                info!("Timed out waiting for the inotify daemon");
+                return Ok((None, None));
+            };
+            let clock = Clock {
+                journal_id: tail.journal_id,
+                offset: tail.end,
+            };
+            if !tail.follows_previous_clock {
+                return Ok((Some(clock), None));
+            }
+            let ignored_dirs = match fs::read(self.state_dir.join(IGNORED_DIRS_FILE_NAME)) {
+                Ok(content) => content,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => vec![],
+                Err(err) => return Err(self.state_error(IGNORED_DIRS_FILE_NAME)(err)),
+            };
+            let paths: BTreeSet<_> = tail
+                .records
+                .lines()
+                .filter_map(|line| line.strip_prefix(b"P "))
+                .chain(ignored_dirs.lines())
+                .collect();
+            let paths = paths
+                .into_iter()
+                .map(|path| PathBuf::from(OsStr::from_bytes(path)))
+                .collect();
+            Ok((Some(clock), Some(paths)))
+        }
+
+        /// Waits until the daemon has processed the filesystem events which
+        /// happened before this call, by creating a cookie file for the daemon
+        /// to acknowledge.
+        ///
+        /// Returns the journal records up to the acknowledgement, or `None`
+        /// if the daemon didn't respond in time. Only the records after the
+        /// `previous_clock` are read if it's still valid.
+        fn sync(&self, previous_clock: Option<&Clock>) -> Result<Option<JournalTail>, Error> {
+            let journal_path = self.state_dir.join(JOURNAL_FILE_NAME);
+            // Watch the state directory before opening the journal, so that
+            // neither its restart nor new records are missed.
+            let watcher = StateWatcher::new(&self.state_dir)?;
+            let mut tail = JournalTail::open(&journal_path, previous_clock, false)
+                .map_err(self.state_error(JOURNAL_FILE_NAME))?;
+
+            let nanos = SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos();
+            let mut cookie = COOKIE_PREFIX.to_vec();
+            write!(cookie, "{}-{nanos}", std::process::id()).unwrap();
+            let cookie_path = self.state_dir.join(OsStr::from_bytes(&cookie));
+            File::create(&cookie_path).map_err(self.state_error(&cookie_path))?;
+            let marker = [b"C ", cookie.as_slice(), b"\n"].concat();
+            let deadline = Instant::now() + SYNC_TIMEOUT;
+            loop {
+                if tail
+                    .is_restarted(&journal_path)
+                    .map_err(self.state_error(JOURNAL_FILE_NAME))?
+                {
+                    tail = JournalTail::open(&journal_path, None, true)
+                        .map_err(self.state_error(JOURNAL_FILE_NAME))?;
+                }
+                if tail
+                    .read_until(&marker)
+                    .map_err(self.state_error(JOURNAL_FILE_NAME))?
+                {
+                    return Ok(Some(tail));
+                }
+                if !watcher.wait(deadline)? {
+                    fs::remove_file(&cookie_path).ok();
+                    return Ok(None);
+                }
+            }
+        }
+
+        /// Watches the working copy until it or its `.jj` directory is
+        /// deleted.
+        ///
+        /// Returns immediately if another daemon is already running.
+        #[instrument(skip(self))]
+        pub fn run_daemon(&self) -> Result<(), Error> {
+            fs::create_dir_all(&self.state_dir).map_err(self.state_error(""))?;
+            let lock_file = File::options()
+                .create(true)
+                .truncate(false)
+                .write(true)
+                .open(self.state_dir.join(LOCK_FILE_NAME))
+                .map_err(self.state_error(LOCK_FILE_NAME))?;
+            match rustix::fs::flock(&lock_file, FlockOperation::NonBlockingLockExclusive) {
+                Ok(()) => {}
+                Err(Errno::WOULDBLOCK) => {
+                    info!("The inotify daemon is already running");
+                    return Ok(());
+                }
+                Err(errno) => return Err(self.state_error(LOCK_FILE_NAME)(errno.into())),
+            }
+
+            info!("Starting the inotify daemon...");
+            let fd = rustix::fs::inotify::init(CreateFlags::CLOEXEC)
+                .map_err(|errno| Error::Watch(errno.into()))?;
+            let mut daemon = Daemon::new(self, fd.as_fd())?;
+            let mut buf = [MaybeUninit::uninit(); 16384];
+            let mut reader = Reader::new(fd.as_fd(), &mut buf);
+            loop {
+                if reader.is_buffer_empty() {
+                    daemon.flush()?;
+                }
+                let event = match reader.next() {
+                    Ok(event) => event,
+                    Err(Errno::INTR) => continue,
+                    Err(errno) => return Err(Error::Watch(errno.into())),
+                };
+                if !daemon.handle_event(event.wd(), event.events(), event.file_name())? {
+                    info!("Stopping the inotify daemon");
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Waits for changes in the state directory of the daemon.
+    struct StateWatcher {
+        fd: OwnedFd,
+    }
+
+    impl StateWatcher {
+        fn new(state_dir: &Path) -> Result<Self, Error> {
+            let fd = rustix::fs::inotify::init(CreateFlags::CLOEXEC | CreateFlags::NONBLOCK)
+                .map_err(|errno| Error::Watch(errno.into()))?;
+            rustix::fs::inotify::add_watch(
+                &fd,
+                state_dir,
+                WatchFlags::CREATE | WatchFlags::MODIFY | WatchFlags::MOVED_TO,
+            )
+            .map_err(|errno| Error::Watch(errno.into()))?;
+            Ok(Self { fd })
+        }
+
+        /// Waits until a file in the state directory is created or modified.
+        /// Returns `false` if nothing changed before the `deadline`.
+        fn wait(&self, deadline: Instant) -> Result<bool, Error> {
+            let Some(timeout) = deadline.checked_duration_since(Instant::now()) else {
+                return Ok(false);
+            };
+            let timeout = Timespec::try_from(timeout).expect("timeout should be short");
+            let mut fds = [PollFd::new(&self.fd, PollFlags::IN)];
+            match rustix::event::poll(&mut fds, Some(&timeout)) {
+                Ok(0) => return Ok(false),
+                Ok(_) | Err(Errno::INTR) => {}
+                Err(errno) => return Err(Error::Watch(errno.into())),
+            }
+            // Drain the events. Only the fact that something changed matters.
+            let mut buf = [MaybeUninit::uninit(); 4096];
+            let mut reader = Reader::new(&self.fd, &mut buf);
+            loop {
+                match reader.next() {
+                    Ok(_) | Err(Errno::INTR) => {}
+                    Err(Errno::AGAIN) => return Ok(true),
+                    Err(errno) => return Err(Error::Watch(errno.into())),
+                }
+            }
+        }
+    }
+
+    /// The records read from the journal since a clock.
+    struct JournalTail {
+        reader: BufReader<File>,
+        journal_id: String,
+        /// Whether the records start at the previous clock. Otherwise, they
+        /// start where the journal was when it was opened.
+        follows_previous_clock: bool,
+        records: Vec<u8>,
+        /// The offset of the end of the `records` in the journal.
+        end: u64,
+    }
+
+    impl JournalTail {
+        /// Opens the journal at `path` to read the records after the
+        /// `previous_clock` if it's valid, or the records written from now on.
+        /// If the journal was just restarted, `from_start` should be set so
+        /// that no record of the new journal is missed.
+        fn open(path: &Path, previous_clock: Option<&Clock>, from_start: bool) -> io::Result<Self> {
+            let file = File::open(path)?;
+            let len = file.metadata()?.len();
+            let mut reader = BufReader::new(file);
+            let mut header = vec![];
+            io::BufRead::read_until(&mut reader, b'\n', &mut header)?;
+            let journal_id = header.trim_end_with(|c| c == '\n');
+            let journal_id = journal_id.to_str_lossy().into_owned();
+            let previous_offset = previous_clock
+                .filter(|clock| clock.journal_id == journal_id)
+                .map(|clock| clock.offset)
+                .filter(|&offset| offset >= header.len() as u64 && offset <= len);
+            let header_len = header.len() as u64;
+            let start = match previous_offset {
+                Some(offset) => offset,
+                None if from_start => header_len,
+                None => len.max(header_len),
+            };
+            reader.seek_relative((start - header_len) as i64)?;
+            Ok(Self {
+                reader,
+                journal_id,
+                follows_previous_clock: previous_offset.is_some(),
+                records: vec![],
+                end: start,
+            })
+        }
+
+        /// Returns whether the journal was restarted since it was opened.
+        fn is_restarted(&self, path: &Path) -> io::Result<bool> {
+            let current = fs::metadata(path)?;
+            let opened = self.reader.get_ref().metadata()?;
+            Ok((current.dev(), current.ino()) != (opened.dev(), opened.ino()))
+        }
+
+        /// Reads the new records, and returns whether the `marker` was found.
+        /// The records after the marker are discarded.
+        fn read_until(&mut self, marker: &[u8]) -> io::Result<bool> {
+            let old_len = self.records.len();
+            self.reader.read_to_end(&mut self.records)?;
+            let search_start = old_len.saturating_sub(marker.len() - 1);
+            if let Some(pos) = self.records[search_start..].find(marker) {
+                let new_len = search_start + pos + marker.len();
+                self.end += (new_len - old_len) as u64;
+                self.records.truncate(new_len);
+                Ok(true)
+            } else {
+                self.end += (self.records.len() - old_len) as u64;
+                Ok(false)
+            }
+        }
+    }
+
+    const WORKING_COPY_WATCH_FLAGS: WatchFlags = WatchFlags::ATTRIB
+        .union(WatchFlags::CLOSE_WRITE)
+        .union(WatchFlags::CREATE)
+        .union(WatchFlags::DELETE)
+        .union(WatchFlags::DELETE_SELF)
+        .union(WatchFlags::MODIFY)
+        .union(WatchFlags::MOVE_SELF)
+        .union(WatchFlags::MOVED_FROM)
+        .union(WatchFlags::MOVED_TO)
+        .union(WatchFlags::DONT_FOLLOW)
+        .union(WatchFlags::EXCL_UNLINK)
+        .union(WatchFlags::ONLYDIR);
+
+    const STATE_DIR_WATCH_FLAGS: WatchFlags = WatchFlags::CREATE
+        .union(WatchFlags::DELETE)
+        .union(WatchFlags::DELETE_SELF)
+        .union(WatchFlags::MOVED_FROM)
+        .union(WatchFlags::MOVE_SELF)
+        .union(WatchFlags::ONLYDIR);
+
+    struct Daemon<'a> {
+        monitor: &'a Fsmonitor,
+        fd: BorrowedFd<'a>,
+        state_wd: i32,
+        /// Watched directories, relative to the working copy path.
+        dirs: HashMap<i32, PathBuf>,
+        /// Directories which aren't watched because they are ignored.
+        ignored_dirs: BTreeSet<PathBuf>,
+        ignored_dirs_changed: bool,
+        /// Whether some directories couldn't be watched because the inotify
+        /// watch limit was reached. The journal is then restarted on every
+        /// flush, so that snapshots crawl the entire working copy.
+        watch_limit_reached: bool,
+        journal: File,
+        journal_size: usize,
+        /// Journal records which haven't been written yet.
+        pending: Vec<u8>,
+        pending_paths: HashSet<PathBuf>,
+    }
+
+    impl<'a> Daemon<'a> {
+        fn new(monitor: &'a Fsmonitor, fd: BorrowedFd<'a>) -> Result<Self, Error> {
+            // Watch the state directory first, so no cookie is missed.
+            let state_wd =
+                rustix::fs::inotify::add_watch(fd, &monitor.state_dir, STATE_DIR_WATCH_FLAGS)
+                    .map_err(|errno| Error::Watch(errno.into()))?;
+            let journal = File::create(monitor.state_dir.join(JOURNAL_FILE_NAME))
+                .map_err(monitor.state_error(JOURNAL_FILE_NAME))?;
+            let mut daemon = Self {
+                monitor,
+                fd,
+                state_wd,
+                dirs: HashMap::new(),
+                ignored_dirs: BTreeSet::new(),
+                ignored_dirs_changed: true,
+                watch_limit_reached: false,
+                journal,
+                journal_size: 0,
+                pending: vec![],
+                pending_paths: HashSet::new(),
+            };
+            daemon.restart_journal()?;
+            // Acknowledge the cookies created before the watch was added.
+            let entries = fs::read_dir(&monitor.state_dir).map_err(monitor.state_error(""))?;
+            for entry in entries {
+                let entry = entry.map_err(monitor.state_error(""))?;
+                daemon.acknowledge_cookie(entry.file_name().as_bytes());
+            }
+            daemon.watch_recursively(Path::new(""))?;
+            Ok(daemon)
+        }
+
+        /// Starts a new journal with a new id, which invalidates all clocks.
+        fn restart_journal(&mut self) -> Result<(), Error> {
+            let nanos = SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos();
+            let journal_id = format!("{:x}-{nanos:x}\n", std::process::id());
+            let temp_path = self.monitor.state_dir.join("journal.tmp");
+            let journal_path = self.monitor.state_dir.join(JOURNAL_FILE_NAME);
+            fs::write(&temp_path, &journal_id).map_err(self.monitor.state_error(&temp_path))?;
+            fs::rename(&temp_path, &journal_path)
+                .map_err(self.monitor.state_error(&journal_path))?;
+            self.journal = File::options()
+                .append(true)
+                .open(&journal_path)
+                .map_err(self.monitor.state_error(&journal_path))?;
+            self.journal_size = journal_id.len();
+            // The changed paths are irrelevant for the new journal, but the
+            // acknowledged cookies must still be written.
+            let pending = std::mem::take(&mut self.pending);
+            for line in pending.lines_with_terminator() {
+                if line.starts_with(b"C ") {
+                    self.pending.extend_from_slice(line);
+                }
+            }
+            self.pending_paths.clear();
+            Ok(())
+        }
+
+        fn flush(&mut self) -> Result<(), Error> {
+            // The ignored directories must be saved before the journal
+            // records, which may acknowledge a query's cookie.
+            if self.ignored_dirs_changed {
+                self.save_ignored_dirs()?;
+            }
+            if self.pending.is_empty() {
+                return Ok(());
+            }
+            if self.watch_limit_reached || self.journal_size + self.pending.len() > MAX_JOURNAL_SIZE
+            {
+                self.restart_journal()?;
+            }
+            self.journal
+                .write_all(&self.pending)
+                .map_err(self.monitor.state_error(JOURNAL_FILE_NAME))?;
+            self.journal_size += self.pending.len();
+            self.pending.clear();
+            self.pending_paths.clear();
+            Ok(())
+        }
+
+        fn save_ignored_dirs(&mut self) -> Result<(), Error> {
+            let mut content = vec![];
+            for dir in &self.ignored_dirs {
+                content.extend_from_slice(dir.as_os_str().as_bytes());
+                content.push(b'\n');
+            }
+            let temp_path = self.monitor.state_dir.join("ignored_dirs.tmp");
+            let path = self.monitor.state_dir.join(IGNORED_DIRS_FILE_NAME);
+            fs::write(&temp_path, content).map_err(self.monitor.state_error(&temp_path))?;
+            fs::rename(&temp_path, &path).map_err(self.monitor.state_error(&path))?;
+            self.ignored_dirs_changed = false;
+            Ok(())
+        }
+
+        fn acknowledge_cookie(&mut self, name: &[u8]) {
+            if name.starts_with(COOKIE_PREFIX) {
+                self.pending.extend_from_slice(b"C ");
+                self.pending.extend_from_slice(name);
+                self.pending.push(b'\n');
+                fs::remove_file(self.monitor.state_dir.join(OsStr::from_bytes(name))).ok();
+            }
+        }
+
+        fn record_path(&mut self, path: PathBuf) -> Result<(), Error> {
+            if path.as_os_str().as_bytes().contains(&b'\n') {
+                // Can't be represented in the journal
+                return self.restart_journal();
+            }
+            if !self.pending_paths.contains(&path) {
+                self.pending.extend_from_slice(b"P ");
+                self.pending.extend_from_slice(path.as_os_str().as_bytes());
+                self.pending.push(b'\n');
+                self.pending_paths.insert(path);
+            }
+            Ok(())
+        }
+
+        /// Handles an inotify event. Returns whether to keep running.
+        fn handle_event(
+            &mut self,
+            wd: i32,
+            flags: ReadFlags,
+            file_name: Option<&CStr>,
+        ) -> Result<bool, Error> {
+            if flags.contains(ReadFlags::QUEUE_OVERFLOW) {
+                info!("The inotify event queue overflowed");
+                self.restart_journal()?;
+                return Ok(true);
+            }
+            // The open files in the state directory keep it from being
+            // reported as deleted, so the daemon stops when the lock file is
+            // removed instead.
+            let is_removed = flags.intersects(ReadFlags::DELETE | ReadFlags::MOVED_FROM);
+            if wd == self.state_wd {
+                let Some(name) = file_name else {
+                    return Ok(!flags.intersects(ReadFlags::DELETE_SELF | ReadFlags::MOVE_SELF));
+                };
+                if is_removed {
+                    return Ok(name.to_bytes() != LOCK_FILE_NAME.as_bytes());
+                }
+                if flags.contains(ReadFlags::CREATE) {
+                    self.acknowledge_cookie(name.to_bytes());
+                }
+                return Ok(true);
+            }
+            if flags.contains(ReadFlags::IGNORED) {
+                self.dirs.remove(&wd);
+                return Ok(true);
+            }
+            let Some(dir) = self.dirs.get(&wd) else {
+                return Ok(true);
+            };
+            let is_root = dir.as_os_str().is_empty();
+            let Some(name) = file_name else {
+                // Changes to the directory itself are reported by its parent,
+                // except for the root.
+                return Ok(
+                    !(is_root && flags.intersects(ReadFlags::DELETE_SELF | ReadFlags::MOVE_SELF))
+                );
+            };
+            let name = OsStr::from_bytes(name.to_bytes());
+            if is_root && is_excluded(name) {
+                // Stop if the `.jj` directory is removed.
+                return Ok(!(is_removed && name == ".jj"));
+            }
+            let path = dir.join(name);
+            if flags.contains(ReadFlags::ISDIR) {
+                if flags.intersects(ReadFlags::CREATE | ReadFlags::MOVED_TO) {
+                    self.watch_recursively(&path)?;
+                } else if is_removed {
+                    self.unwatch_recursively(&path);
+                }
+            } else if name == ".gitignore" {
+                // Directories which are no longer ignored must be watched.
+                // Directories which became ignored are kept watched.
+                let dir = dir.clone();
+                let unignored = self.forget_ignored_dirs(&dir);
+                for ignored_dir in unignored {
+                    self.watch_recursively(&ignored_dir)?;
+                }
+            }
+            self.record_path(path)?;
+            Ok(true)
+        }
+
+        /// Watches `dir` and its subdirectories, except for the ignored ones.
+        fn watch_recursively(&mut self, dir: &Path) -> Result<(), Error> {
+            if let Some(parent) = dir.parent()
+                && is_ignored_dir(&self.git_ignore_for(parent), dir)
+            {
+                self.ignored_dirs.insert(dir.to_owned());
+                self.ignored_dirs_changed = true;
+                return Ok(());
+            }
+            let mut dirs_to_visit = vec![(dir.to_owned(), self.git_ignore_for(dir))];
+            while let Some((dir, git_ignore)) = dirs_to_visit.pop() {
+                let disk_dir = self.monitor.working_copy_path.join(&dir);
+                match rustix::fs::inotify::add_watch(self.fd, &disk_dir, WORKING_COPY_WATCH_FLAGS) {
+                    Ok(wd) => {
+                        self.dirs.insert(wd, dir.clone());
+                    }
+                    // The directory was removed or replaced in the meantime.
+                    Err(Errno::NOENT | Errno::NOTDIR | Errno::ACCESS) => continue,
+                    Err(Errno::NOSPC) => {
+                        if !self.watch_limit_reached {
+                            warn!(
+                                "The inotify watch limit was reached, falling back to crawling \
+                                 the working copy"
+                            );
+                            self.watch_limit_reached = true;
+                        }
+                        continue;
+                    }
+                    Err(errno) => return Err(Error::Watch(errno.into())),
+                }
+                let Ok(entries) = fs::read_dir(&disk_dir) else {
+                    continue;
+                };
+                for entry in entries.flatten() {
+                    let name = entry.file_name();
+                    if dir.as_os_str().is_empty() && is_excluded(&name) {
+                        continue;
+                    }
+                    if !entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+                        continue;
+                    }
+                    let sub_dir = dir.join(name);
+                    if is_ignored_dir(&git_ignore, &sub_dir) {
+                        self.ignored_dirs.insert(sub_dir);
+                        self.ignored_dirs_changed = true;
+                        continue;
+                    }
+                    let git_ignore = match sub_dir.to_str() {
+                        Some(path) => chain_git_ignore(
+                            &git_ignore,
+                            &format!("{path}/"),
+                            &self.monitor.working_copy_path.join(&sub_dir),
+                        ),
+                        None => git_ignore.clone(),
+                    };
+                    dirs_to_visit.push((sub_dir, git_ignore));
+                }
+            }
+            Ok(())
+        }
+
+        /// Returns the ignore patterns which apply to the content of `dir`.
+        ///
+        /// Only the `.gitignore` files in the working copy are considered, so
+        /// a directory which is ignored by other means is still watched.
+        fn git_ignore_for(&self, dir: &Path) -> Arc<GitIgnoreFile> {
+            let mut git_ignore =
+                chain_git_ignore(&GitIgnoreFile::empty(), "", &self.monitor.working_copy_path);
+            let mut prefix = String::new();
+            for component in dir.components() {
+                let Some(name) = component.as_os_str().to_str() else {
+                    return GitIgnoreFile::empty();
+                };
+                prefix.push_str(name);
+                prefix.push('/');
+                git_ignore = chain_git_ignore(
+                    &git_ignore,
+                    &prefix,
+                    &self.monitor.working_copy_path.join(&prefix),
+                );
+            }
+            git_ignore
+        }
+
+        /// Removes the ignored directories under `dir`, and returns them.
+        fn forget_ignored_dirs(&mut self, dir: &Path) -> Vec<PathBuf> {
+            let (forgotten, kept) = std::mem::take(&mut self.ignored_dirs)
+                .into_iter()
+                .partition(|ignored_dir| ignored_dir.starts_with(dir));
+            self.ignored_dirs = kept;
+            let forgotten: Vec<_> = forgotten.into_iter().collect();
+            if !forgotten.is_empty() {
+                self.ignored_dirs_changed = true;
+            }
+            forgotten
+        }
+
+        fn unwatch_recursively(&mut self, dir: &Path) {
+            self.forget_ignored_dirs(dir);
+            self.dirs.retain(|&wd, watched_dir| {
+                if watched_dir.starts_with(dir) {
+                    rustix::fs::inotify::remove_watch(self.fd, wd).ok();
+                    false
+                } else {
+                    true
+                }
+            });
+        }
+    }
+
+    fn is_excluded(name: &OsStr) -> bool {
+        name == ".jj" || name == ".git"
+    }
+
+    /// Returns whether `dir` is ignored by `git_ignore`. Paths which can't be
+    /// listed in the ignored directories file are never ignored.
+    fn is_ignored_dir(git_ignore: &GitIgnoreFile, dir: &Path) -> bool {
+        dir.to_str()
+            .filter(|path| !path.contains('\n'))
+            .is_some_and(|path| git_ignore.matches(&format!("{path}/")))
+    }
+
+    /// Adds the `.gitignore` file in `disk_dir` to `git_ignore`. Invalid
+    /// files are skipped, which means that fewer directories are ignored.
+    fn chain_git_ignore(
+        git_ignore: &Arc<GitIgnoreFile>,
+        prefix: &str,
+        disk_dir: &Path,
+    ) -> Arc<GitIgnoreFile> {
+        git_ignore
+            .chain_with_file(prefix, disk_dir.join(".gitignore"))
+            .unwrap_or_else(|_| git_ignore.clone())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::thread;
+
+        use super::*;
+
+        fn wait_for_daemon(monitor: &Fsmonitor, watcher: &StateWatcher) {
+            let deadline = Instant::now() + SYNC_TIMEOUT;
+            while !monitor.is_daemon_running().unwrap() {
+                assert!(watcher.wait(deadline).unwrap(), "daemon didn't start");
+            }
+        }
+
+        fn query(monitor: &Fsmonitor, clock: &Clock) -> (Clock, Vec<PathBuf>) {
+            let (new_clock, paths) = monitor.query_changed_files(Some(clock.clone())).unwrap();
+            (new_clock.unwrap(), paths.unwrap())
+        }
+
+        #[test]
+        fn test_query_changed_files() {
+            let temp_dir = testutils::new_temp_dir();
+            let root = temp_dir.path().join("repo");
+            let state_dir = root.join(".jj").join("working_copy").join("inotify");
+            fs::create_dir_all(root.join("dir")).unwrap();
+            fs::write(root.join("dir").join("file"), "a").unwrap();
+            fs::create_dir_all(root.join("ignored").join("sub")).unwrap();
+            fs::write(root.join(".gitignore"), "/ignored\n").unwrap();
+            fs::create_dir_all(&state_dir).unwrap();
+            let watcher = StateWatcher::new(&state_dir).unwrap();
+            let monitor = Fsmonitor::new(&root, &state_dir);
+            assert!(!monitor.is_daemon_running().unwrap());
+            assert_eq!(monitor.query_changed_files(None).unwrap(), (None, None));
+
+            let daemon = thread::spawn({
+                let root = root.clone();
+                let state_dir = state_dir.clone();
+                move || Fsmonitor::new(&root, &state_dir).run_daemon()
+            });
+            wait_for_daemon(&monitor, &watcher);
+            // A second daemon exits immediately
+            monitor.run_daemon().unwrap();
+
+            // Without a clock, the whole working copy must be crawled
+            let (clock, paths) = monitor.query_changed_files(None).unwrap();
+            let clock = clock.unwrap();
+            assert_eq!(paths, None);
+            // Ignored directories aren't watched, and are always reported
+            let (clock, paths) = query(&monitor, &clock);
+            assert_eq!(paths, [Path::new("ignored")]);
+            fs::write(root.join("ignored").join("sub").join("file"), "").unwrap();
+            let (clock, paths) = query(&monitor, &clock);
+            assert_eq!(paths, [Path::new("ignored")]);
+
+            fs::write(root.join("dir").join("file"), "b").unwrap();
+            fs::write(root.join("new"), "").unwrap();
+            fs::write(root.join(".jj").join("ignored"), "").unwrap();
+            let (clock, paths) = query(&monitor, &clock);
+            assert_eq!(
+                paths,
+                [
+                    Path::new("dir/file"),
+                    Path::new("ignored"),
+                    Path::new("new")
+                ]
+            );
+
+            // Directories are watched once they are no longer ignored
+            fs::write(root.join(".gitignore"), "").unwrap();
+            let (clock, paths) = query(&monitor, &clock);
+            assert_eq!(paths, [Path::new(".gitignore")]);
+            fs::write(root.join("ignored").join("sub").join("file"), "a").unwrap();
+            let (clock, paths) = query(&monitor, &clock);
+            assert_eq!(paths, [Path::new("ignored/sub/file")]);
+
+            // Files in new directories are watched. The subdirectory may also be
+            // reported if it was created after the watch was added.
+            fs::create_dir_all(root.join("new_dir").join("sub")).unwrap();
+            let (clock, paths) = query(&monitor, &clock);
+            assert_eq!(paths[0], Path::new("new_dir"));
+            fs::write(root.join("new_dir").join("sub").join("file"), "").unwrap();
+            let (clock, paths) = query(&monitor, &clock);
+            assert_eq!(paths, [Path::new("new_dir/sub/file")]);
+
+            // Moved directories are reported, but not their content
+            fs::rename(root.join("new_dir"), root.join("dir").join("moved")).unwrap();
+            let (clock, paths) = query(&monitor, &clock);
+            assert_eq!(paths, [Path::new("dir/moved"), Path::new("new_dir")]);
+            fs::write(root.join("dir").join("moved").join("sub").join("file"), "a").unwrap();
+            let (clock, paths) = query(&monitor, &clock);
+            assert_eq!(paths, [Path::new("dir/moved/sub/file")]);
+
+            // A clock from another journal is invalid
+            let other_clock = Clock {
+                journal_id: "other".to_owned(),
+                offset: clock.offset,
+            };
+            let (_, paths) = monitor.query_changed_files(Some(other_clock)).unwrap();
+            assert_eq!(paths, None);
+
+            // The daemon stops when the working copy is deleted
+            fs::remove_dir_all(&root).unwrap();
+            daemon.join().unwrap().unwrap();
+        }
+    }
+}
@@ -0,0 +1,63 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Options and statistics of garbage collection.
+
+use std::ops::AddAssign;
+use std::time::SystemTime;
+
+/// Parameters of garbage collection.
+#[derive(Clone, Debug)]
+pub struct GcOptions {
+    /// Unreachable objects created after this time are preserved. This
+    /// mitigates a risk of deleting new objects created concurrently by
+    /// another process.
+    pub keep_newer: SystemTime,
+    /// If true, nothing is removed, and the returned [`GcStats`] describe what
+    /// would be removed.
+    pub dry_run: bool,
+}
+
+impl GcOptions {
+    /// Creates options to remove unreachable objects created at or before
+    /// `keep_newer`.
+    pub fn new(keep_newer: SystemTime) -> Self {
+        Self {
+            keep_newer,
+            dry_run: false,
+        }
+    }
+}
+
+/// Numbers of objects removed by garbage collection.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct GcStats {
+    /// Number of operations.
+    pub operations: usize,
+    /// Number of commits.
+    pub commits: usize,
+    /// Number of index segment files.
+    pub index_segments: usize,
+    /// Number of entries removed from the backend's extra metadata table.
+    pub extras_entries: usize,
+}
+
+impl AddAssign for GcStats {
+    fn add_assign(&mut self, other: Self) {
+        self.operations += other.operations;
+        self.commits += other.commits;
+        self.index_segments += other.index_segments;
+        self.extras_entries += other.extras_entries;
+    }
+}
@@ -1654,7 +1654,7 @@ fn reset_index(
             // If the parent tree is resolved, we can use gix's `index_from_tree` method.
             // This is more efficient than iterating over the tree and adding each entry.
             git_repo
-                .index_from_tree(&gix::ObjectId::from_bytes_or_panic(tree_id.as_bytes()))
+                .index_from_tree(&crate::git_backend::git_tree_id(tree_id))
                 .map_err(GitResetHeadError::from_git)?
         }
     } else {
@@ -2985,11 +2985,15 @@ pub struct GitRefUpdate {
 }
 
 /// Pushes the specified branches and updates the repo view accordingly.
+///
+/// The `extra_args` (such as `--push-option` or `--atomic`) are passed to
+/// `git push`.
 pub fn push_branches(
     mut_repo: &mut MutableRepo,
     subprocess_options: GitSubprocessOptions,
     remote: &RemoteName,
     targets: &GitBranchPushTargets,
+    extra_args: &[&str],
     callback: &mut dyn GitSubprocessCallback,
 ) -> Result<GitPushStats, GitPushError> {
     validate_remote_name(remote)?;
@@ -3009,7 +3013,7 @@ pub fn push_branches(
         subprocess_options,
         remote,
         &ref_updates,
-        &[],
+        extra_args,
         callback,
     )?;
     tracing::debug!(?push_stats);
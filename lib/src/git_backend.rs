@@ -14,7 +14,9 @@
 
 #![expect(missing_docs)]
 
+use std::collections::HashMap;
 use std::collections::HashSet;
+use std::collections::VecDeque;
 use std::ffi::OsStr;
 use std::fmt::Debug;
 use std::fmt::Error;
@@ -70,16 +72,22 @@ use crate::backend::TreeId;
 use crate::backend::TreeValue;
 use crate::backend::make_root_commit;
 use crate::config::ConfigGetError;
+use crate::content_hash::blake2b_hash;
+use crate::copies::related_copy_histories;
 use crate::file_util;
 use crate::file_util::BadPathEncoding;
 use crate::file_util::IoResultExt as _;
 use crate::file_util::PathError;
+use crate::gc::GcOptions;
+use crate::gc::GcStats;
 use crate::git::GitSettings;
 use crate::index::Index;
+use crate::lfs::LfsObjectStore;
 use crate::lock::FileLock;
 use crate::merge::Merge;
 use crate::merge::MergeBuilder;
 use crate::object_id::ObjectId;
+use crate::protos::git_store::tree_copies;
 use crate::repo_path::RepoPath;
 use crate::repo_path::RepoPathBuf;
 use crate::repo_path::RepoPathComponentBuf;
@@ -91,6 +99,8 @@ use crate::stacked_table::TableStore;
 use crate::stacked_table::TableStoreError;
 
 const HASH_LENGTH: usize = 20;
+const COPY_ID_LENGTH: usize = 64;
+const TREE_COPIES_KEY_LENGTH: usize = 64;
 const CHANGE_ID_LENGTH: usize = 16;
 /// Ref namespace used only for preventing GC.
 const NO_GC_REF_NAMESPACE: &str = "refs/jj/keep/";
@@ -162,6 +172,94 @@ pub enum GitGcError {
     GcCommandErrorStatus(ExitStatus),
 }
 
+/// Table store of non-git metadata with the cached head table.
+struct CachedTableStore {
+    store: TableStore,
+    cached_head: Mutex<Option<Arc<ReadonlyTable>>>,
+}
+
+impl CachedTableStore {
+    fn new(store: TableStore) -> Self {
+        Self {
+            store,
+            cached_head: Mutex::new(None),
+        }
+    }
+
+    fn cached_head(&self) -> BackendResult<Arc<ReadonlyTable>> {
+        let mut locked_head = self.cached_head.lock().unwrap();
+        match locked_head.as_ref() {
+            Some(head) => Ok(head.clone()),
+            None => {
+                let table = self
+                    .store
+                    .get_head()
+                    .map_err(GitBackendError::ReadMetadata)?;
+                *locked_head = Some(table.clone());
+                Ok(table)
+            }
+        }
+    }
+
+    /// Reloads the head table, which may have been updated by another
+    /// process.
+    fn reload_head(&self) -> BackendResult<Arc<ReadonlyTable>> {
+        *self.cached_head.lock().unwrap() = None;
+        self.cached_head()
+    }
+
+    fn head_locked(&self) -> BackendResult<(Arc<ReadonlyTable>, FileLock)> {
+        let table = self
+            .store
+            .get_head_locked()
+            .map_err(GitBackendError::ReadMetadata)?;
+        Ok(table)
+    }
+
+    fn save_table(&self, mut_table: MutableTable, _table_lock: &FileLock) -> BackendResult<()> {
+        let table = self
+            .store
+            .save_table(mut_table)
+            .map_err(GitBackendError::WriteMetadata)?;
+        // Since the parent table was the head, saved table are likely to be new head.
+        // If it's not, cache will be reloaded when entry can't be found.
+        *self.cached_head.lock().unwrap() = Some(table);
+        Ok(())
+    }
+
+    /// Rewrites the head table without the entries for which `f` returns
+    /// false. Returns the number of removed entries.
+    fn retain_entries(&self, f: impl FnMut(&[u8], &[u8]) -> bool) -> BackendResult<usize> {
+        let (table, num_removed) = self
+            .store
+            .retain_entries(f)
+            .map_err(GitBackendError::WriteMetadata)?;
+        *self.cached_head.lock().unwrap() = Some(table);
+        Ok(num_removed)
+    }
+
+    fn gc(&self, keep_newer: SystemTime) -> BackendResult<()> {
+        // No locking is needed since we aren't going to add new entries.
+        let table = self.cached_head()?;
+        self.store
+            .gc(&table, keep_newer)
+            .map_err(|err| BackendError::Other(err.into()))
+    }
+}
+
+fn init_table_store(dir: PathBuf, key_size: usize) -> Result<TableStore, PathError> {
+    fs::create_dir(&dir).context(&dir)?;
+    Ok(TableStore::init(dir, key_size))
+}
+
+fn load_or_init_table_store(dir: PathBuf, key_size: usize) -> Result<TableStore, PathError> {
+    if dir.exists() {
+        Ok(TableStore::load(dir, key_size))
+    } else {
+        init_table_store(dir, key_size)
+    }
+}
+
 pub struct GitBackend {
     // While gix::Repository can be created from gix::ThreadSafeRepository, it's
     // cheaper to cache the thread-local instance behind a mutex than creating
@@ -173,8 +271,14 @@ pub struct GitBackend {
     root_change_id: ChangeId,
     empty_tree_id: TreeId,
     shallow_root_ids: OnceLock<Vec<CommitId>>,
-    extra_metadata_store: TableStore,
-    cached_extra_metadata: Mutex<Option<Arc<ReadonlyTable>>>,
+    extra_metadata_store: CachedTableStore,
+    /// `CopyHistory` objects keyed by `CopyId`.
+    copy_store: CachedTableStore,
+    /// Copy ids of the tree entries keyed by their hash.
+    tree_copy_store: CachedTableStore,
+    /// Copy ids of the trees written since the last commit, to be saved to
+    /// the `tree_copy_store` by the next commit.
+    pending_tree_copies: Mutex<HashMap<Vec<u8>, Vec<u8>>>,
     git_executable: PathBuf,
     write_change_id_header: bool,
 }
@@ -187,6 +291,8 @@ impl GitBackend {
     fn new(
         base_repo: gix::ThreadSafeRepository,
         extra_metadata_store: TableStore,
+        copy_store: TableStore,
+        tree_copy_store: TableStore,
         git_settings: GitSettings,
     ) -> Self {
         let repo = Mutex::new(base_repo.to_thread_local());
@@ -200,8 +306,10 @@ impl GitBackend {
             root_change_id,
             empty_tree_id,
             shallow_root_ids: OnceLock::new(),
-            extra_metadata_store,
-            cached_extra_metadata: Mutex::new(None),
+            extra_metadata_store: CachedTableStore::new(extra_metadata_store),
+            copy_store: CachedTableStore::new(copy_store),
+            tree_copy_store: CachedTableStore::new(tree_copy_store),
+            pending_tree_copies: Mutex::new(HashMap::new()),
             git_executable: git_settings.executable_path,
             write_change_id_header: git_settings.write_change_id_header,
         }
@@ -300,7 +408,18 @@ impl GitBackend {
             .context(&target_path)
             .map_err(GitBackendInitError::Path)?;
         let extra_metadata_store = TableStore::init(extra_path, HASH_LENGTH);
-        Ok(Self::new(repo, extra_metadata_store, git_settings))
+        let copy_store = init_table_store(store_path.join("copies"), COPY_ID_LENGTH)
+            .map_err(GitBackendInitError::Path)?;
+        let tree_copy_store =
+            init_table_store(store_path.join("tree_copies"), TREE_COPIES_KEY_LENGTH)
+                .map_err(GitBackendInitError::Path)?;
+        Ok(Self::new(
+            repo,
+            extra_metadata_store,
+            copy_store,
+            tree_copy_store,
+            git_settings,
+        ))
     }
 
     pub fn load(
@@ -325,9 +444,21 @@ impl GitBackend {
         )
         .map_err(GitBackendLoadError::OpenRepository)?;
         let extra_metadata_store = TableStore::load(store_path.join("extra"), HASH_LENGTH);
+        // Repositories created by older jj versions don't have the copy tables.
+        let copy_store = load_or_init_table_store(store_path.join("copies"), COPY_ID_LENGTH)
+            .map_err(GitBackendLoadError::Path)?;
+        let tree_copy_store =
+            load_or_init_table_store(store_path.join("tree_copies"), TREE_COPIES_KEY_LENGTH)
+                .map_err(GitBackendLoadError::Path)?;
         let git_settings =
             GitSettings::from_settings(settings).map_err(GitBackendLoadError::Config)?;
-        Ok(Self::new(repo, extra_metadata_store, git_settings))
+        Ok(Self::new(
+            repo,
+            extra_metadata_store,
+            copy_store,
+            tree_copy_store,
+            git_settings,
+        ))
     }
 
     fn lock_git_repo(&self) -> MutexGuard<'_, gix::Repository> {
@@ -349,6 +480,12 @@ impl GitBackend {
         self.base_repo.work_dir()
     }
 
+    /// Local cache of Git LFS objects, stored in the `lfs` directory of the
+    /// Git repository as `git lfs` does.
+    pub fn lfs_store(&self) -> LfsObjectStore {
+        LfsObjectStore::new(self.git_repo_path().join("lfs"))
+    }
+
     fn shallow_root_ids(&self, git_repo: &gix::Repository) -> BackendResult<&[CommitId]> {
         // The list of shallow roots is cached by gix, but it's still expensive
         // to stat file on every read_object() call. Refreshing shallow roots is
@@ -369,41 +506,19 @@ impl GitBackend {
     }
 
     fn cached_extra_metadata_table(&self) -> BackendResult<Arc<ReadonlyTable>> {
-        let mut locked_head = self.cached_extra_metadata.lock().unwrap();
-        match locked_head.as_ref() {
-            Some(head) => Ok(head.clone()),
-            None => {
-                let table = self
-                    .extra_metadata_store
-                    .get_head()
-                    .map_err(GitBackendError::ReadMetadata)?;
-                *locked_head = Some(table.clone());
-                Ok(table)
-            }
-        }
+        self.extra_metadata_store.cached_head()
     }
 
     fn read_extra_metadata_table_locked(&self) -> BackendResult<(Arc<ReadonlyTable>, FileLock)> {
-        let table = self
-            .extra_metadata_store
-            .get_head_locked()
-            .map_err(GitBackendError::ReadMetadata)?;
-        Ok(table)
+        self.extra_metadata_store.head_locked()
     }
 
     fn save_extra_metadata_table(
         &self,
         mut_table: MutableTable,
-        _table_lock: &FileLock,
+        table_lock: &FileLock,
     ) -> BackendResult<()> {
-        let table = self
-            .extra_metadata_store
-            .save_table(mut_table)
-            .map_err(GitBackendError::WriteMetadata)?;
-        // Since the parent table was the head, saved table are likely to be new head.
-        // If it's not, cache will be reloaded when entry can't be found.
-        *self.cached_extra_metadata.lock().unwrap() = Some(table);
-        Ok(())
+        self.extra_metadata_store.save_table(mut_table, table_lock)
     }
 
     /// Imports the given commits and ancestors from the backing Git repo.
@@ -490,18 +605,348 @@ impl GitBackend {
         &self,
         repo: &'repo gix::Repository,
         id: &CommitId,
-    ) -> BackendResult<gix::Tree<'repo>> {
+    ) -> BackendResult<(TreeId, gix::Tree<'repo>)> {
         let tree = self.read_commit(id).block_on()?.root_tree;
         // TODO(kfm): probably want to do something here if it is a merge
         let tree_id = tree.first().clone();
-        let gix_id = validate_git_object_id(&tree_id)?;
-        repo.find_object(gix_id)
+        let gix_id = split_tree_id(&tree_id)?.0;
+        let tree = repo
+            .find_object(gix_id)
             .map_err(|err| map_not_found_err(err, &tree_id))?
             .try_into_tree()
-            .map_err(|err| to_read_object_err(err, &tree_id))
+            .map_err(|err| to_read_object_err(err, &tree_id))?;
+        Ok((tree_id, tree))
+    }
+
+    fn read_copy_sync(&self, id: &CopyId) -> BackendResult<CopyHistory> {
+        if id.as_bytes().len() != COPY_ID_LENGTH {
+            return Err(BackendError::InvalidHashLength {
+                expected: COPY_ID_LENGTH,
+                actual: id.as_bytes().len(),
+                object_type: "copy".to_owned(),
+                hash: id.hex(),
+            });
+        }
+        let mut table = self.copy_store.cached_head()?;
+        if table.get_value(id.as_bytes()).is_none() {
+            // The copy might have been written by another process.
+            table = self.copy_store.reload_head()?;
+        }
+        let bytes = table
+            .get_value(id.as_bytes())
+            .ok_or_else(|| BackendError::ObjectNotFound {
+                object_type: "copy".to_owned(),
+                hash: id.hex(),
+                source: "".into(),
+            })?;
+        deserialize_copy_history(bytes).map_err(|err| BackendError::ReadObject {
+            object_type: "copy".to_owned(),
+            hash: id.hex(),
+            source: err,
+        })
+    }
+
+    fn read_tree_sync(&self, id: &TreeId) -> BackendResult<Tree> {
+        if id == &self.empty_tree_id {
+            return Ok(Tree::default());
+        }
+        let (git_tree_id, copies_key) = split_tree_id(id)?;
+
+        let locked_repo = self.lock_git_repo();
+        let git_tree = locked_repo
+            .find_object(git_tree_id)
+            .map_err(|err| map_not_found_err(err, id))?
+            .try_into_tree()
+            .map_err(|err| to_read_object_err(err, id))?;
+        let mut entries: Vec<_> = git_tree
+            .iter()
+            .map(|entry| -> BackendResult<_> {
+                let entry = entry.map_err(|err| to_read_object_err(err, id))?;
+                let name = RepoPathComponentBuf::new(
+                    str::from_utf8(entry.filename()).map_err(|err| to_invalid_utf8_err(err, id))?,
+                )
+                .unwrap();
+                let value = match entry.mode().kind() {
+                    gix::object::tree::EntryKind::Tree => {
+                        let id = TreeId::from_bytes(entry.oid().as_bytes());
+                        TreeValue::Tree(id)
+                    }
+                    gix::object::tree::EntryKind::Blob => {
+                        let id = FileId::from_bytes(entry.oid().as_bytes());
+                        TreeValue::File {
+                            id,
+                            executable: false,
+                            copy_id: CopyId::placeholder(),
+                        }
+                    }
+                    gix::object::tree::EntryKind::BlobExecutable => {
+                        let id = FileId::from_bytes(entry.oid().as_bytes());
+                        TreeValue::File {
+                            id,
+                            executable: true,
+                            copy_id: CopyId::placeholder(),
+                        }
+                    }
+                    gix::object::tree::EntryKind::Link => {
+                        let id = SymlinkId::from_bytes(entry.oid().as_bytes());
+                        TreeValue::Symlink(id)
+                    }
+                    gix::object::tree::EntryKind::Commit => {
+                        let id = CommitId::from_bytes(entry.oid().as_bytes());
+                        TreeValue::GitSubmodule(id)
+                    }
+                };
+                Ok((name, value))
+            })
+            .try_collect()?;
+        drop(git_tree);
+        drop(locked_repo);
+        if let Some(key) = copies_key {
+            let tree_copies = self.read_tree_copies(id, key)?;
+            let copy_ids: HashMap<&str, &[u8]> = tree_copies
+                .entries
+                .iter()
+                .map(|entry| (entry.name.as_str(), entry.copy_id.as_slice()))
+                .collect();
+            let subtree_ids: HashMap<&str, &[u8]> = tree_copies
+                .subtrees
+                .iter()
+                .map(|subtree| (subtree.name.as_str(), subtree.tree_id.as_slice()))
+                .collect();
+            for (name, value) in &mut entries {
+                match value {
+                    TreeValue::File { copy_id, .. } => {
+                        if let Some(id) = copy_ids.get(name.as_internal_str()) {
+                            *copy_id = CopyId::from_bytes(id);
+                        }
+                    }
+                    TreeValue::Tree(tree_id) => {
+                        if let Some(id) = subtree_ids.get(name.as_internal_str()) {
+                            *tree_id = TreeId::from_bytes(id);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        // While Git tree entries are sorted, the rule is slightly different.
+        // Directory names are sorted as if they had trailing "/".
+        if !entries.is_sorted_by_key(|(name, _)| name) {
+            entries.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        }
+        Ok(Tree::from_sorted_entries(entries))
+    }
+
+    /// Reads the copy ids recorded under the `key` of an extended tree id.
+    fn read_tree_copies(
+        &self,
+        id: &TreeId,
+        key: &[u8],
+    ) -> BackendResult<crate::protos::git_store::TreeCopies> {
+        let pending = self.pending_tree_copies.lock().unwrap();
+        let bytes = if let Some(bytes) = pending.get(key) {
+            bytes.clone()
+        } else {
+            drop(pending);
+            let mut table = self.tree_copy_store.cached_head()?;
+            if table.get_value(key).is_none() {
+                // The tree might have been written by another process.
+                table = self.tree_copy_store.reload_head()?;
+            }
+            table
+                .get_value(key)
+                .ok_or_else(|| BackendError::ObjectNotFound {
+                    object_type: "tree".to_owned(),
+                    hash: id.hex(),
+                    source: "Copy ids of the tree not found".into(),
+                })?
+                .to_vec()
+        };
+        crate::protos::git_store::TreeCopies::decode(bytes.as_slice()).map_err(|err| {
+            BackendError::ReadObject {
+                object_type: "tree".to_owned(),
+                hash: id.hex(),
+                source: err.into(),
+            }
+        })
+    }
+
+    /// Saves the copy ids recorded by the trees written so far. This is called
+    /// once per commit so the trees don't have to take the table lock.
+    fn flush_tree_copies(&self) -> BackendResult<()> {
+        let mut pending = self.pending_tree_copies.lock().unwrap();
+        if pending.is_empty() {
+            return Ok(());
+        }
+        let (table, table_lock) = self.tree_copy_store.head_locked()?;
+        let mut mut_table = table.start_mutation();
+        for (key, bytes) in pending.drain() {
+            // Entries are keyed by the hash of their contents, so existing
+            // entries never change.
+            if table.get_value(&key).is_none() {
+                mut_table.add_entry(key, bytes);
+            }
+        }
+        self.tree_copy_store.save_table(mut_table, &table_lock)
+    }
+
+    /// Removes the copy ids of trees and the copy histories which are no
+    /// longer reachable from the commits in the extra metadata table.
+    fn prune_copy_records(&self) -> BackendResult<()> {
+        let to_read_error = |err: prost::DecodeError| BackendError::Other(err.into());
+        let extras_table = self.extra_metadata_store.cached_head()?;
+        let mut pending_tree_keys: Vec<Vec<u8>> = vec![];
+        for (_, value) in extras_table
+            .ancestor_segments()
+            .flat_map(|segment| segment.segment_entries())
+        {
+            let proto = crate::protos::git_store::Commit::decode(value).map_err(to_read_error)?;
+            pending_tree_keys.extend(
+                proto
+                    .root_tree_with_copies
+                    .into_iter()
+                    .filter(|id| id.len() == HASH_LENGTH + TREE_COPIES_KEY_LENGTH)
+                    .map(|id| id[HASH_LENGTH..].to_vec()),
+            );
+        }
+
+        let tree_copies_table = self.tree_copy_store.reload_head()?;
+        let mut reachable_tree_keys: HashSet<Vec<u8>> = HashSet::new();
+        let mut pending_copy_ids: Vec<Vec<u8>> = vec![];
+        while let Some(key) = pending_tree_keys.pop() {
+            let Some(bytes) = tree_copies_table.get_value(&key) else {
+                continue;
+            };
+            let tree_copies =
+                crate::protos::git_store::TreeCopies::decode(bytes).map_err(to_read_error)?;
+            reachable_tree_keys.insert(key);
+            pending_copy_ids.extend(tree_copies.entries.into_iter().map(|entry| entry.copy_id));
+            pending_tree_keys.extend(
+                tree_copies
+                    .subtrees
+                    .into_iter()
+                    .filter(|subtree| subtree.tree_id.len() == HASH_LENGTH + TREE_COPIES_KEY_LENGTH)
+                    .map(|subtree| subtree.tree_id[HASH_LENGTH..].to_vec())
+                    .filter(|key| !reachable_tree_keys.contains(key)),
+            );
+        }
+
+        let copies_table = self.copy_store.reload_head()?;
+        let mut reachable_copy_ids: HashSet<Vec<u8>> = HashSet::new();
+        while let Some(id) = pending_copy_ids.pop() {
+            let Some(bytes) = copies_table.get_value(&id) else {
+                continue;
+            };
+            if reachable_copy_ids.contains(&id) {
+                continue;
+            }
+            let history = deserialize_copy_history(bytes).map_err(BackendError::Other)?;
+            reachable_copy_ids.insert(id);
+            pending_copy_ids.extend(history.parents.into_iter().map(|id| id.to_bytes()));
+        }
+
+        // Entries added by another process since the tables were read above
+        // are kept.
+        self.tree_copy_store.retain_entries(|key, _| {
+            reachable_tree_keys.contains(key) || tree_copies_table.get_value(key).is_none()
+        })?;
+        self.copy_store.retain_entries(|key, _| {
+            reachable_copy_ids.contains(key) || copies_table.get_value(key).is_none()
+        })?;
+        Ok(())
+    }
+
+    /// Looks up the copy id of the file at `path` in the tree `root_id`.
+    /// Returns `None` if the file has no tracked copy history.
+    fn copy_id_in_tree(&self, root_id: &TreeId, path: &RepoPath) -> BackendResult<Option<CopyId>> {
+        let Some((dir, name)) = path.split() else {
+            return Ok(None);
+        };
+        let mut tree_id = root_id.clone();
+        for component in dir.components() {
+            // Trees without copy ids don't have subtrees with copy ids.
+            if split_tree_id(&tree_id)?.1.is_none() {
+                return Ok(None);
+            }
+            match self.read_tree_sync(&tree_id)?.value(component) {
+                Some(TreeValue::Tree(id)) => tree_id = id.clone(),
+                _ => return Ok(None),
+            }
+        }
+        if split_tree_id(&tree_id)?.1.is_none() {
+            return Ok(None);
+        }
+        match self.read_tree_sync(&tree_id)?.value(name) {
+            Some(TreeValue::File { copy_id, .. }) if *copy_id != CopyId::placeholder() => {
+                Ok(Some(copy_id.clone()))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Finds the source of the file at `target` in the `head_tree_id` by
+    /// walking its copy history. Returns the path and the file id of the
+    /// nearest ancestor that exists in the `root_tree`.
+    fn find_tracked_copy_source(
+        &self,
+        (root_tree_id, root_tree): (&TreeId, &gix::Tree),
+        head_tree_id: &TreeId,
+        target: &RepoPath,
+    ) -> BackendResult<Option<(RepoPathBuf, FileId)>> {
+        let Some(copy_id) = self.copy_id_in_tree(head_tree_id, target)? else {
+            return Ok(None);
+        };
+        let mut queue = VecDeque::from(self.read_copy_sync(&copy_id)?.parents);
+        let mut visited = HashSet::new();
+        while let Some(id) = queue.pop_front() {
+            if !visited.insert(id.clone()) {
+                continue;
+            }
+            let history = self.read_copy_sync(&id)?;
+            let path = &history.current_path;
+            let entry = root_tree
+                .lookup_entry(path.components().map(|c| c.as_internal_str().as_bytes()))
+                .map_err(|err| BackendError::Other(err.into()))?;
+            if let Some(entry) = entry
+                && entry.mode().is_blob()
+            {
+                // A file without tracked copy history is the origin of the
+                // copy history recorded at the same path.
+                let root_copy_id = self.copy_id_in_tree(root_tree_id, path)?;
+                if root_copy_id.is_none_or(|root_copy_id| root_copy_id == id) {
+                    let file_id = FileId::from_bytes(entry.oid().as_bytes());
+                    return Ok(Some((history.current_path, file_id)));
+                }
+            }
+            queue.extend(history.parents);
+        }
+        Ok(None)
     }
 }
 
+/// Splits the tree `id` into the Git tree id and the key of the copy ids
+/// recorded for the tree.
+///
+/// Git trees can't store copy ids, so a tree containing copy ids is identified
+/// by the Git tree id followed by the hash of its copy ids. This keeps tree ids
+/// content-addressed: the same Git tree with different copy ids gets a
+/// different id.
+fn split_tree_id(id: &TreeId) -> BackendResult<(gix::ObjectId, Option<&[u8]>)> {
+    let bytes = id.as_bytes();
+    if bytes.len() == HASH_LENGTH + TREE_COPIES_KEY_LENGTH {
+        let (git_id, key) = bytes.split_at(HASH_LENGTH);
+        Ok((gix::ObjectId::from_bytes_or_panic(git_id), Some(key)))
+    } else {
+        Ok((validate_git_object_id(id)?, None))
+    }
+}
+
+/// Returns the Git tree id of the tree `id`, dropping the hash of the copy ids
+/// if any.
+pub(crate) fn git_tree_id(id: &TreeId) -> gix::ObjectId {
+    gix::ObjectId::from_bytes_or_panic(&id.as_bytes()[..HASH_LENGTH])
+}
+
 /// Canonicalizes the given `path` except for the last `".git"` component.
 ///
 /// The last path component matters when opening a Git repo without `core.bare`
@@ -738,11 +1183,23 @@ fn serialize_extras(commit: &Commit) -> Vec<u8> {
         ..Default::default()
     };
     proto.uses_tree_conflict_format = true;
+    // Conflicts are stored for the sake of jj versions <0.28 (before commit
+    // f7b14be) being able to read the repo. At some point in the future, we
+    // can stop doing it.
     if !commit.root_tree.is_resolved() {
-        // This is done for the sake of jj versions <0.28 (before commit
-        // f7b14be) being able to read the repo. At some point in the
-        // future, we can stop doing it.
-        proto.root_tree = commit.root_tree.iter().map(|r| r.to_bytes()).collect();
+        proto.root_tree = commit
+            .root_tree
+            .iter()
+            .map(|id| git_tree_id(id).as_bytes().to_vec())
+            .collect();
+    }
+    // Trees with copy ids can't be stored in the Git commit.
+    if commit
+        .root_tree
+        .iter()
+        .any(|id| id.as_bytes().len() != HASH_LENGTH)
+    {
+        proto.root_tree_with_copies = commit.root_tree.iter().map(|id| id.to_bytes()).collect();
     }
     for predecessor in &commit.predecessors {
         proto.predecessors.push(predecessor.to_bytes());
@@ -755,12 +1212,15 @@ fn deserialize_extras(commit: &mut Commit, bytes: &[u8]) {
     if !proto.change_id.is_empty() {
         commit.change_id = ChangeId::new(proto.change_id);
     }
-    if commit.root_tree.is_resolved()
-        && proto.uses_tree_conflict_format
-        && !proto.root_tree.is_empty()
-    {
-        let merge_builder: MergeBuilder<_> = proto
-            .root_tree
+    // The trees with copy ids are the same as the ones in the Git commit
+    // except for the copy ids.
+    let root_tree = if proto.root_tree_with_copies.is_empty() {
+        &proto.root_tree
+    } else {
+        &proto.root_tree_with_copies
+    };
+    if proto.uses_tree_conflict_format && !root_tree.is_empty() {
+        let merge_builder: MergeBuilder<_> = root_tree
             .iter()
             .map(|id_bytes| TreeId::from_bytes(id_bytes))
             .collect();
@@ -771,6 +1231,26 @@ fn deserialize_extras(commit: &mut Commit, bytes: &[u8]) {
     }
 }
 
+fn serialize_copy_history(copy: &CopyHistory) -> Vec<u8> {
+    let proto = crate::protos::git_store::CopyHistory {
+        current_path: copy.current_path.as_internal_file_string().to_owned(),
+        parents: copy.parents.iter().map(|id| id.to_bytes()).collect(),
+        salt: copy.salt.clone(),
+    };
+    proto.encode_to_vec()
+}
+
+fn deserialize_copy_history(
+    bytes: &[u8],
+) -> Result<CopyHistory, Box<dyn std::error::Error + Send + Sync>> {
+    let proto = crate::protos::git_store::CopyHistory::decode(bytes)?;
+    Ok(CopyHistory {
+        current_path: RepoPathBuf::from_internal_string(proto.current_path)?,
+        parents: proto.parents.into_iter().map(CopyId::new).collect(),
+        salt: proto.salt,
+    })
+}
+
 /// Returns `RefEdit` that will create a ref in `refs/jj/keep` if not exist.
 /// Used for preventing GC of commits we create.
 fn to_no_gc_ref_update(id: &CommitId) -> gix::refs::transaction::RefEdit {
@@ -1058,83 +1538,44 @@ impl Backend for GitBackend {
         Ok(SymlinkId::new(oid.as_bytes().to_vec()))
     }
 
-    async fn read_copy(&self, _id: &CopyId) -> BackendResult<CopyHistory> {
-        Err(BackendError::Unsupported(
-            "The Git backend doesn't support tracked copies yet".to_string(),
-        ))
+    async fn read_copy(&self, id: &CopyId) -> BackendResult<CopyHistory> {
+        self.read_copy_sync(id)
     }
 
-    async fn write_copy(&self, _contents: &CopyHistory) -> BackendResult<CopyId> {
-        Err(BackendError::Unsupported(
-            "The Git backend doesn't support tracked copies yet".to_string(),
-        ))
-    }
-
-    async fn get_related_copies(&self, _copy_id: &CopyId) -> BackendResult<Vec<CopyHistory>> {
-        Err(BackendError::Unsupported(
-            "The Git backend doesn't support tracked copies yet".to_string(),
-        ))
+    async fn write_copy(&self, contents: &CopyHistory) -> BackendResult<CopyId> {
+        let id = CopyId::new(blake2b_hash(contents).to_vec());
+        let (table, table_lock) = self.copy_store.head_locked()?;
+        if table.get_value(id.as_bytes()).is_none() {
+            let mut mut_table = table.start_mutation();
+            mut_table.add_entry(id.to_bytes(), serialize_copy_history(contents));
+            self.copy_store.save_table(mut_table, &table_lock)?;
+        }
+        Ok(id)
+    }
+
+    async fn get_related_copies(&self, copy_id: &CopyId) -> BackendResult<Vec<CopyHistory>> {
+        // Make sure the copy exists, which also reloads the table if needed.
+        self.read_copy_sync(copy_id)?;
+        let table = self.copy_store.cached_head()?;
+        let copies: HashMap<CopyId, CopyHistory> = table
+            .ancestor_segments()
+            .flat_map(|segment| segment.segment_entries())
+            .map(|(key, value)| {
+                let id = CopyId::from_bytes(key);
+                let copy =
+                    deserialize_copy_history(value).map_err(|err| BackendError::ReadObject {
+                        object_type: "copy".to_owned(),
+                        hash: id.hex(),
+                        source: err,
+                    })?;
+                Ok((id, copy))
+            })
+            .try_collect::<_, _, BackendError>()?;
+        Ok(related_copy_histories(&copies, copy_id))
     }
 
     async fn read_tree(&self, _path: &RepoPath, id: &TreeId) -> BackendResult<Tree> {
-        if id == &self.empty_tree_id {
-            return Ok(Tree::default());
-        }
-        let git_tree_id = validate_git_object_id(id)?;
-
-        let locked_repo = self.lock_git_repo();
-        let git_tree = locked_repo
-            .find_object(git_tree_id)
-            .map_err(|err| map_not_found_err(err, id))?
-            .try_into_tree()
-            .map_err(|err| to_read_object_err(err, id))?;
-        let mut entries: Vec<_> = git_tree
-            .iter()
-            .map(|entry| -> BackendResult<_> {
-                let entry = entry.map_err(|err| to_read_object_err(err, id))?;
-                let name = RepoPathComponentBuf::new(
-                    str::from_utf8(entry.filename()).map_err(|err| to_invalid_utf8_err(err, id))?,
-                )
-                .unwrap();
-                let value = match entry.mode().kind() {
-                    gix::object::tree::EntryKind::Tree => {
-                        let id = TreeId::from_bytes(entry.oid().as_bytes());
-                        TreeValue::Tree(id)
-                    }
-                    gix::object::tree::EntryKind::Blob => {
-                        let id = FileId::from_bytes(entry.oid().as_bytes());
-                        TreeValue::File {
-                            id,
-                            executable: false,
-                            copy_id: CopyId::placeholder(),
-                        }
-                    }
-                    gix::object::tree::EntryKind::BlobExecutable => {
-                        let id = FileId::from_bytes(entry.oid().as_bytes());
-                        TreeValue::File {
-                            id,
-                            executable: true,
-                            copy_id: CopyId::placeholder(),
-                        }
-                    }
-                    gix::object::tree::EntryKind::Link => {
-                        let id = SymlinkId::from_bytes(entry.oid().as_bytes());
-                        TreeValue::Symlink(id)
-                    }
-                    gix::object::tree::EntryKind::Commit => {
-                        let id = CommitId::from_bytes(entry.oid().as_bytes());
-                        TreeValue::GitSubmodule(id)
-                    }
-                };
-                Ok((name, value))
-            })
-            .try_collect()?;
-        // While Git tree entries are sorted, the rule is slightly different.
-        // Directory names are sorted as if they had trailing "/".
-        if !entries.is_sorted_by_key(|(name, _)| name) {
-            entries.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
-        }
-        Ok(Tree::from_sorted_entries(entries))
+        self.read_tree_sync(id)
     }
 
     async fn write_tree(&self, _path: &RepoPath, contents: &Tree) -> BackendResult<TreeId> {
@@ -1148,7 +1589,7 @@ impl Backend for GitBackend {
                     TreeValue::File {
                         id,
                         executable: false,
-                        copy_id: _, // TODO: Use the value
+                        copy_id: _,
                     } => gix::objs::tree::Entry {
                         mode: gix::object::tree::EntryKind::Blob.into(),
                         filename,
@@ -1157,7 +1598,7 @@ impl Backend for GitBackend {
                     TreeValue::File {
                         id,
                         executable: true,
-                        copy_id: _, // TODO: Use the value
+                        copy_id: _,
                     } => gix::objs::tree::Entry {
                         mode: gix::object::tree::EntryKind::BlobExecutable.into(),
                         filename,
@@ -1171,7 +1612,7 @@ impl Backend for GitBackend {
                     TreeValue::Tree(id) => gix::objs::tree::Entry {
                         mode: gix::object::tree::EntryKind::Tree.into(),
                         filename,
-                        oid: gix::ObjectId::from_bytes_or_panic(id.as_bytes()),
+                        oid: git_tree_id(id),
                     },
                     TreeValue::GitSubmodule(id) => gix::objs::tree::Entry {
                         mode: gix::object::tree::EntryKind::Commit.into(),
@@ -1182,14 +1623,49 @@ impl Backend for GitBackend {
             })
             .sorted_unstable()
             .collect();
-        let locked_repo = self.lock_git_repo();
-        let oid = locked_repo
-            .write_object(gix::objs::Tree { entries })
-            .map_err(|err| BackendError::WriteObject {
-                object_type: "tree",
-                source: Box::new(err),
-            })?;
-        Ok(TreeId::from_bytes(oid.as_bytes()))
+        let id = {
+            let locked_repo = self.lock_git_repo();
+            let oid = locked_repo
+                .write_object(gix::objs::Tree { entries })
+                .map_err(|err| BackendError::WriteObject {
+                    object_type: "tree",
+                    source: Box::new(err),
+                })?;
+            TreeId::from_bytes(oid.as_bytes())
+        };
+        // Git trees can't store copy ids, so they are recorded separately and
+        // the hash of the record is appended to the tree id.
+        let mut tree_copies = crate::protos::git_store::TreeCopies::default();
+        for entry in contents.entries() {
+            let name = entry.name().as_internal_str();
+            match entry.value() {
+                TreeValue::File { copy_id, .. } if *copy_id != CopyId::placeholder() => {
+                    tree_copies.entries.push(tree_copies::Entry {
+                        name: name.to_owned(),
+                        copy_id: copy_id.to_bytes(),
+                    });
+                }
+                TreeValue::Tree(id) if id.as_bytes().len() != HASH_LENGTH => {
+                    tree_copies.subtrees.push(tree_copies::Subtree {
+                        name: name.to_owned(),
+                        tree_id: id.to_bytes(),
+                    });
+                }
+                _ => {}
+            }
+        }
+        if tree_copies.entries.is_empty() && tree_copies.subtrees.is_empty() {
+            return Ok(id);
+        }
+        let bytes = tree_copies.encode_to_vec();
+        let key = blake2b_hash(&bytes).to_vec();
+        let id = TreeId::new([id.as_bytes(), &key].concat());
+        self.pending_tree_copies
+            .lock()
+            .unwrap()
+            .entry(key)
+            .or_insert(bytes);
+        Ok(id)
     }
 
     #[tracing::instrument(skip(self))]
@@ -1238,10 +1714,11 @@ impl Backend for GitBackend {
     ) -> BackendResult<(CommitId, Commit)> {
         assert!(contents.secure_sig.is_none(), "commit.secure_sig was set");
 
+        self.flush_tree_copies()?;
         let locked_repo = self.lock_git_repo();
         let tree_ids = &contents.root_tree;
-        let git_tree_id = match tree_ids.as_resolved() {
-            Some(tree_id) => validate_git_object_id(tree_id)?,
+        let tree_oid = match tree_ids.as_resolved() {
+            Some(tree_id) => split_tree_id(tree_id)?.0,
             None => write_tree_conflict(&locked_repo, tree_ids)?,
         };
         let author = signature_to_git(&contents.author);
@@ -1287,7 +1764,10 @@ impl Backend for GitBackend {
             ));
         }
         if !tree_ids.is_resolved() {
-            let value = tree_ids.iter().map(|id| id.hex()).join(" ");
+            let value = tree_ids
+                .iter()
+                .map(|id| git_tree_id(id).to_string())
+                .join(" ");
             extra_headers.push((JJ_TREES_COMMIT_HEADER.into(), value.into()));
         }
         if self.write_change_id_header {
@@ -1321,7 +1801,7 @@ impl Backend for GitBackend {
         let id = loop {
             let mut commit = gix::objs::Commit {
                 message: message.to_owned().into(),
-                tree: git_tree_id,
+                tree: tree_oid,
                 author: author.clone(),
                 committer: committer.clone(),
                 encoding: None,
@@ -1395,43 +1875,75 @@ impl Backend for GitBackend {
         head_id: &CommitId,
     ) -> BackendResult<BoxStream<'_, BackendResult<CopyRecord>>> {
         let repo = self.git_repo();
-        let root_tree = self.read_tree_for_commit(&repo, root_id)?;
-        let head_tree = self.read_tree_for_commit(&repo, head_id)?;
+        let (root_tree_id, root_tree) = self.read_tree_for_commit(&repo, root_id)?;
+        let (head_tree_id, head_tree) = self.read_tree_for_commit(&repo, head_id)?;
 
         let change_to_copy_record =
             |change: gix::object::tree::diff::Change| -> BackendResult<Option<CopyRecord>> {
-                let gix::object::tree::diff::Change::Rewrite {
-                    source_location,
-                    source_entry_mode,
-                    source_id,
-                    entry_mode: dest_entry_mode,
-                    location: dest_location,
-                    ..
-                } = change
-                else {
-                    return Ok(None);
+                // Only added files can be copy targets. The source of a
+                // rewrite is detected by content similarity.
+                let (dest_location, dest_entry_mode, similar_source) = match change {
+                    gix::object::tree::diff::Change::Addition {
+                        location,
+                        entry_mode,
+                        ..
+                    } => (location, entry_mode, None),
+                    gix::object::tree::diff::Change::Rewrite {
+                        source_location,
+                        source_entry_mode,
+                        source_id,
+                        entry_mode,
+                        location,
+                        ..
+                    } => (
+                        location,
+                        entry_mode,
+                        Some((source_location, source_entry_mode, source_id)),
+                    ),
+                    _ => return Ok(None),
                 };
                 // TODO: Renamed symlinks cannot be returned because CopyRecord
                 // expects `source_file: FileId`.
-                if !source_entry_mode.is_blob() || !dest_entry_mode.is_blob() {
+                if !dest_entry_mode.is_blob() {
                     return Ok(None);
                 }
 
-                let source = str::from_utf8(source_location)
-                    .map_err(|err| to_invalid_utf8_err(err, root_id))?;
                 let dest = str::from_utf8(dest_location)
                     .map_err(|err| to_invalid_utf8_err(err, head_id))?;
-
                 let target = RepoPathBuf::from_internal_string(dest).unwrap();
                 if !paths.is_none_or(|paths| paths.contains(&target)) {
                     return Ok(None);
                 }
 
+                // Tracked copies take precedence over the detected ones.
+                let (source, source_file) = match self.find_tracked_copy_source(
+                    (&root_tree_id, &root_tree),
+                    &head_tree_id,
+                    &target,
+                )? {
+                    Some(tracked_source) => tracked_source,
+                    None => {
+                        let Some((source_location, source_entry_mode, source_id)) = similar_source
+                        else {
+                            return Ok(None);
+                        };
+                        if !source_entry_mode.is_blob() {
+                            return Ok(None);
+                        }
+                        let source = str::from_utf8(source_location)
+                            .map_err(|err| to_invalid_utf8_err(err, root_id))?;
+                        (
+                            RepoPathBuf::from_internal_string(source).unwrap(),
+                            FileId::from_bytes(source_id.as_bytes()),
+                        )
+                    }
+                };
+
                 Ok(Some(CopyRecord {
                     target,
                     target_commit: head_id.clone(),
-                    source: RepoPathBuf::from_internal_string(source).unwrap(),
-                    source_file: FileId::from_bytes(source_id.as_bytes()),
+                    source,
+                    source_file,
                     source_commit: root_id.clone(),
                 }))
             };
@@ -1468,33 +1980,68 @@ impl Backend for GitBackend {
     }
 
     #[tracing::instrument(skip(self, index))]
-    fn gc(&self, index: &dyn Index, keep_newer: SystemTime) -> BackendResult<()> {
+    fn gc(&self, index: &dyn Index, options: &GcOptions) -> BackendResult<GcStats> {
+        if options.dry_run {
+            // Commits not in the index will no longer be protected by no-gc
+            // refs. Some of them may still be kept by "git gc" if they are
+            // reachable from other refs or new enough, so this is an upper
+            // bound.
+            let table = self.extra_metadata_store.cached_head()?;
+            let keys: HashSet<&[u8]> = table
+                .ancestor_segments()
+                .flat_map(|segment| segment.segment_entries().map(|(key, _)| key))
+                .collect();
+            let mut num_commits = 0;
+            for key in keys {
+                let id = CommitId::from_bytes(key);
+                if !index
+                    .has_id(&id)
+                    .map_err(|err| BackendError::Other(err.into()))?
+                {
+                    num_commits += 1;
+                }
+            }
+            return Ok(GcStats {
+                commits: num_commits,
+                extras_entries: num_commits,
+                ..GcStats::default()
+            });
+        }
+
         let git_repo = self.lock_git_repo();
         let new_heads = index
             .all_heads_for_gc()
             .map_err(|err| BackendError::Other(err.into()))?
             .filter(|id| *id != self.root_commit_id);
-        recreate_no_gc_refs(&git_repo, new_heads, keep_newer)?;
-
-        // No locking is needed since we aren't going to add new "commits".
-        let table = self.cached_extra_metadata_table()?;
-        // TODO: remove unreachable entries from extras table if segment file
-        // mtime <= keep_newer? (it won't be consistent with no-gc refs
-        // preserved by the keep_newer timestamp though)
-        self.extra_metadata_store
-            .gc(&table, keep_newer)
-            .map_err(|err| BackendError::Other(err.into()))?;
+        recreate_no_gc_refs(&git_repo, new_heads, options.keep_newer)?;
 
         run_git_gc(
             self.git_executable.as_ref(),
             self.git_repo_path(),
-            keep_newer,
+            options.keep_newer,
         )
         .map_err(|err| BackendError::Other(err.into()))?;
         // Since "git gc" will move loose refs into packed refs, in-memory
         // packed-refs cache should be invalidated without relying on mtime.
         git_repo.refs.force_refresh_packed_buffer().ok();
-        Ok(())
+
+        // Remove extra metadata of the commits pruned by "git gc". The repo is
+        // reopened so that objects in the deleted pack files aren't found.
+        let fresh_repo = gix::open_opts(self.git_repo_path(), git_repo.open_options().clone())
+            .map_err(|err| BackendError::Other(err.into()))?;
+        let num_removed = self.extra_metadata_store.retain_entries(|key, _| {
+            let oid = gix::ObjectId::from_bytes_or_panic(key);
+            fresh_repo.has_object(oid)
+        })?;
+        self.extra_metadata_store.gc(options.keep_newer)?;
+        self.prune_copy_records()?;
+        self.copy_store.gc(options.keep_newer)?;
+        self.tree_copy_store.gc(options.keep_newer)?;
+        Ok(GcStats {
+            commits: num_removed,
+            extras_entries: num_removed,
+            ..GcStats::default()
+        })
     }
 }
 
@@ -1521,7 +2068,7 @@ fn write_tree_conflict(
     .map(|(name, tree_id)| gix::objs::tree::Entry {
         mode: gix::object::tree::EntryKind::Tree.into(),
         filename: name.into(),
-        oid: gix::ObjectId::from_bytes_or_panic(tree_id.as_bytes()),
+        oid: git_tree_id(tree_id),
     })
     .collect_vec();
     let readme_id = repo
@@ -1550,7 +2097,7 @@ recover.
     });
     let first_tree_id = conflict.first();
     let first_tree = repo
-        .find_tree(gix::ObjectId::from_bytes_or_panic(first_tree_id.as_bytes()))
+        .find_tree(git_tree_id(first_tree_id))
         .map_err(|err| to_read_object_err(err, first_tree_id))?;
     for entry in first_tree.iter() {
         let entry = entry.map_err(|err| to_read_object_err(err, first_tree_id))?;
@@ -1580,7 +2127,6 @@ mod tests {
 
     use super::*;
     use crate::config::StackedConfig;
-    use crate::content_hash::blake2b_hash;
     use crate::hex_util;
     use crate::tests::new_temp_dir;
 
@@ -2237,6 +2783,65 @@ mod tests {
         );
     }
 
+    #[test]
+    fn extras_with_copy_ids() {
+        let tree_id = |n: u8| TreeId::new(vec![n; HASH_LENGTH]);
+        let tree_id_with_copies = |n: u8| {
+            TreeId::new([[n; HASH_LENGTH].as_slice(), &[n; TREE_COPIES_KEY_LENGTH]].concat())
+        };
+        let mut commit = Commit {
+            parents: vec![],
+            predecessors: vec![],
+            root_tree: Merge::resolved(tree_id_with_copies(1)),
+            conflict_labels: Merge::resolved(String::new()),
+            change_id: ChangeId::from_hex("abc123"),
+            description: "".to_string(),
+            author: create_signature(),
+            committer: create_signature(),
+            secure_sig: None,
+        };
+        let decode = |commit: &Commit| {
+            crate::protos::git_store::Commit::decode(&*serialize_extras(commit)).unwrap()
+        };
+        // Reads the commit as if the Git commit had been read first
+        let round_trip = |commit: &Commit| {
+            let mut read_commit = Commit {
+                root_tree: commit
+                    .root_tree
+                    .map(|id| TreeId::from_bytes(git_tree_id(id).as_bytes())),
+                ..commit.clone()
+            };
+            deserialize_extras(&mut read_commit, &serialize_extras(commit));
+            read_commit.root_tree
+        };
+
+        // Jj versions without copy tracking read the Git tree from the commit
+        let proto = decode(&commit);
+        assert!(proto.root_tree.is_empty());
+        assert_eq!(
+            proto.root_tree_with_copies,
+            [tree_id_with_copies(1).to_bytes()]
+        );
+        assert_eq!(round_trip(&commit), commit.root_tree);
+
+        // They read conflicts from the root_tree field, which must only contain
+        // Git tree ids
+        commit.root_tree = Merge::from_vec(vec![tree_id_with_copies(1), tree_id(2), tree_id(3)]);
+        let proto = decode(&commit);
+        assert_eq!(
+            proto.root_tree,
+            [tree_id(1), tree_id(2), tree_id(3)].map(|id| id.to_bytes())
+        );
+        assert_eq!(proto.root_tree_with_copies.len(), 3);
+        assert_eq!(round_trip(&commit), commit.root_tree);
+
+        // Nothing is stored for resolved trees without copy ids
+        commit.root_tree = Merge::resolved(tree_id(1));
+        let proto = decode(&commit);
+        assert!(proto.root_tree.is_empty());
+        assert!(proto.root_tree_with_copies.is_empty());
+    }
+
     #[test]
     fn commit_has_ref() {
         let settings = user_settings();
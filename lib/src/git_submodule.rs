@@ -0,0 +1,194 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Helpers for Git submodules.
+
+use std::path::Path;
+
+use bstr::ByteSlice as _;
+use futures::StreamExt as _;
+use tokio::io::AsyncReadExt as _;
+
+use crate::backend::BackendError;
+use crate::backend::BackendResult;
+use crate::backend::CommitId;
+use crate::backend::TreeValue;
+use crate::file_util;
+use crate::matchers::EverythingMatcher;
+use crate::merged_tree::MergedTree;
+use crate::merged_tree::TreeDiffEntry;
+use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathBuf;
+use crate::submodule_store::SubmoduleConfig;
+
+/// Name of the file that describes the submodules of a tree.
+pub const GITMODULES_FILE_NAME: &str = ".gitmodules";
+
+/// Parses the contents of a `.gitmodules` file.
+///
+/// Entries without a valid path or URL are ignored, as Git does.
+pub fn parse_gitmodules(content: &[u8]) -> Vec<SubmoduleConfig> {
+    let Ok(config) = gix::config::File::try_from(content.as_bstr()) else {
+        return vec![];
+    };
+    config
+        .sections_by_name("submodule")
+        .into_iter()
+        .flatten()
+        .filter_map(|section| {
+            let name = section.header().subsection_name()?.to_str().ok()?;
+            let path = section.value("path")?;
+            let path = RepoPathBuf::from_relative_path(path.to_str().ok()?).ok()?;
+            let url = section.value("url")?.to_str().ok()?.to_owned();
+            if path.is_root() {
+                return None;
+            }
+            Some(SubmoduleConfig {
+                name: name.to_owned(),
+                path,
+                url,
+            })
+        })
+        .collect()
+}
+
+/// Reads and parses the `.gitmodules` file at the root of the `tree`.
+///
+/// Returns an empty list if the file doesn't exist or is conflicted.
+pub async fn read_gitmodules(tree: &MergedTree) -> BackendResult<Vec<SubmoduleConfig>> {
+    let path = RepoPath::from_internal_string(GITMODULES_FILE_NAME).unwrap();
+    let value = tree.path_value_async(path).await?;
+    let Some(Some(TreeValue::File { id, .. })) = value.as_resolved() else {
+        return Ok(vec![]);
+    };
+    let mut reader = tree.store().read_file(path, id).await?;
+    let mut content = vec![];
+    reader
+        .read_to_end(&mut content)
+        .await
+        .map_err(|err| BackendError::ReadFile {
+            path: path.to_owned(),
+            id: id.clone(),
+            source: err.into(),
+        })?;
+    Ok(parse_gitmodules(&content))
+}
+
+/// Returns the submodules whose recorded commits were added or changed
+/// between `from_tree` and `to_tree`.
+pub async fn changed_submodules(
+    from_tree: &MergedTree,
+    to_tree: &MergedTree,
+) -> BackendResult<Vec<(RepoPathBuf, CommitId)>> {
+    let mut submodules = vec![];
+    let mut diff_stream = from_tree.diff_stream(to_tree, &EverythingMatcher);
+    while let Some(TreeDiffEntry { path, values }) = diff_stream.next().await {
+        if let Some(Some(TreeValue::GitSubmodule(id))) = values?.after.as_resolved() {
+            submodules.push((path, id.clone()));
+        }
+    }
+    Ok(submodules)
+}
+
+/// Returns the commit checked out in the submodule working copy at
+/// `work_dir`, or `None` if the submodule isn't initialized.
+pub fn read_submodule_head(work_dir: &Path) -> Option<CommitId> {
+    // Don't let gix find the superproject if the submodule doesn't have its
+    // own repository.
+    work_dir.join(".git").symlink_metadata().ok()?;
+    let repo = gix::open(work_dir).ok()?;
+    let id = repo.head_id().ok()?;
+    Some(CommitId::from_bytes(id.as_bytes()))
+}
+
+/// Resolves a submodule URL relative to the URL of the superproject, as Git
+/// does for URLs starting with `./` or `../`.
+pub fn resolve_submodule_url(base_url: &str, url: &str) -> String {
+    if !url.starts_with("./") && !url.starts_with("../") {
+        return url.to_owned();
+    }
+    if !base_url.contains("://") {
+        let path = Path::new(base_url).join(url);
+        return file_util::normalize_path(&path)
+            .to_string_lossy()
+            .into_owned();
+    }
+    let mut base = base_url.trim_end_matches('/').to_owned();
+    let mut rest = url;
+    loop {
+        if let Some(tail) = rest.strip_prefix("./") {
+            rest = tail;
+        } else if let Some(tail) = rest.strip_prefix("../") {
+            if let Some(pos) = base.rfind('/') {
+                base.truncate(pos);
+            }
+            rest = tail;
+        } else {
+            break;
+        }
+    }
+    format!("{base}/{rest}")
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_gitmodules() {
+        let content = indoc! {r#"
+            [submodule "lib"]
+                path = third_party/lib
+                url = https://example.com/lib.git
+            [submodule "no-url"]
+                path = no-url
+            [submodule "bad-path"]
+                path = ../outside
+                url = ../outside.git
+            [core]
+                bare = false
+        "#};
+        assert_eq!(
+            parse_gitmodules(content.as_bytes()),
+            vec![SubmoduleConfig {
+                name: "lib".to_owned(),
+                path: RepoPathBuf::from_internal_string("third_party/lib").unwrap(),
+                url: "https://example.com/lib.git".to_owned(),
+            }]
+        );
+        assert_eq!(parse_gitmodules(b"[invalid"), vec![]);
+    }
+
+    #[test]
+    fn test_resolve_submodule_url() {
+        assert_eq!(
+            resolve_submodule_url("https://example.com/a/b.git", "https://x.org/c"),
+            "https://x.org/c"
+        );
+        assert_eq!(
+            resolve_submodule_url("https://example.com/a/b.git", "../c.git"),
+            "https://example.com/a/c.git"
+        );
+        assert_eq!(
+            resolve_submodule_url("https://example.com/a/b.git/", "./c"),
+            "https://example.com/a/b.git/c"
+        );
+        assert_eq!(
+            resolve_submodule_url("/repos/a/b", "../c"),
+            Path::new("/repos/a/c").to_string_lossy()
+        );
+    }
+}
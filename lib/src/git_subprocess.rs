@@ -267,8 +267,8 @@ impl GitSubprocessContext {
     ) -> Result<GitPushStats, GitSubprocessError> {
         let mut command = self.create_command();
         command.stdout(Stdio::piped());
-        // Currently jj does not support commit hooks, so we prevent git from running
-        // them
+        // jj runs its own hooks configured in the `[hooks]` table, so we prevent
+        // git from running the ones in the repository
         //
         // https://github.com/jj-vcs/jj/issues/3577 and https://github.com/jj-vcs/jj/issues/405
         // offer more context
@@ -0,0 +1,374 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Support for `.gitattributes` files.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::iter;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use ignore::gitignore;
+use pollster::FutureExt as _;
+use tokio::io::AsyncReadExt as _;
+
+use crate::backend::TreeValue;
+use crate::merged_tree::MergedTree;
+use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathBuf;
+use crate::repo_path::RepoPathComponent;
+
+const GITATTRIBUTES_FILE_NAME: &str = ".gitattributes";
+
+/// The state of an attribute for a path.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AttributeValue {
+    /// The attribute is set (e.g. `text`).
+    Set,
+    /// The attribute is unset (e.g. `-text`).
+    Unset,
+    /// The attribute is set to a value (e.g. `filter=lfs`).
+    Value(String),
+    /// No pattern specifies the attribute, or it was reset (e.g. `!text`).
+    Unspecified,
+}
+
+impl AttributeValue {
+    /// Returns the value if the attribute is set to a value.
+    pub fn as_value(&self) -> Option<&str> {
+        match self {
+            Self::Value(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct AttributesRule {
+    matcher: gitignore::Gitignore,
+    attributes: Vec<(String, AttributeValue)>,
+}
+
+/// Models the effective contents of multiple `.gitattributes` files.
+#[derive(Debug, Default)]
+pub struct GitAttributesFile {
+    parent: Option<Arc<Self>>,
+    prefix: String,
+    rules: Vec<AttributesRule>,
+}
+
+impl GitAttributesFile {
+    /// Returns attributes that don't specify anything.
+    pub fn empty() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// Concatenates new `.gitattributes` content at the `prefix` directory.
+    ///
+    /// The `prefix` should be a slash-separated path relative to the workspace
+    /// root. Lines that can't be parsed are ignored, as Git does.
+    pub fn chain(self: &Arc<Self>, prefix: &str, input: &[u8]) -> Arc<Self> {
+        let rules: Vec<_> = input
+            .split(|b| *b == b'\n')
+            .filter_map(|line| parse_rule(prefix, str::from_utf8(line).ok()?))
+            .collect();
+        if rules.is_empty() {
+            return self.clone();
+        }
+        let parent = if self.rules.is_empty() {
+            self.parent.clone() // omit the empty root
+        } else {
+            Some(self.clone())
+        };
+        Arc::new(Self {
+            parent,
+            prefix: prefix.to_owned(),
+            rules,
+        })
+    }
+
+    /// Returns the state of the attribute `name` for the file at `path`.
+    ///
+    /// The `path` should be a slash-separated path relative to the workspace
+    /// root. As in Git, the last matching line wins, and files in deeper
+    /// directories take precedence.
+    pub fn get(&self, path: &str, name: &str) -> AttributeValue {
+        iter::successors(Some(self), |file| file.parent.as_deref())
+            .filter(|file| path.starts_with(&file.prefix))
+            .flat_map(|file| file.rules.iter().rev())
+            .filter(|rule| rule.matcher.matched(path, false).is_ignore())
+            .find_map(|rule| {
+                rule.attributes
+                    .iter()
+                    .rev()
+                    .find(|(attr_name, _)| attr_name == name)
+                    .map(|(_, value)| value.clone())
+            })
+            .unwrap_or(AttributeValue::Unspecified)
+    }
+}
+
+fn parse_rule(prefix: &str, line: &str) -> Option<AttributesRule> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let mut tokens = line.split_ascii_whitespace();
+    let pattern = tokens.next()?;
+    // Negative patterns are forbidden, and macro definitions aren't supported.
+    if pattern.starts_with('!') || pattern.starts_with("[attr]") {
+        return None;
+    }
+    let mut builder = gitignore::GitignoreBuilder::new(prefix);
+    builder.add_line(None, pattern).ok()?;
+    let matcher = builder.build().ok()?;
+    let mut attributes = vec![];
+    for token in tokens {
+        // Like Git, values of unset and unspecified attributes are ignored.
+        if let Some(name) = token.strip_prefix('-') {
+            let name = name.split('=').next().unwrap();
+            attributes.push((name.to_owned(), AttributeValue::Unset));
+        } else if let Some(name) = token.strip_prefix('!') {
+            let name = name.split('=').next().unwrap();
+            attributes.push((name.to_owned(), AttributeValue::Unspecified));
+        } else if let Some((name, value)) = token.split_once('=') {
+            attributes.push((name.to_owned(), AttributeValue::Value(value.to_owned())));
+        } else if token == "binary" {
+            // The only built-in macro attribute.
+            attributes.push((token.to_owned(), AttributeValue::Set));
+            for name in ["diff", "merge", "text"] {
+                attributes.push((name.to_owned(), AttributeValue::Unset));
+            }
+        } else {
+            attributes.push((token.to_owned(), AttributeValue::Set));
+        }
+    }
+    Some(AttributesRule {
+        matcher,
+        attributes,
+    })
+}
+
+type AttributesLoader = dyn Fn(&RepoPath) -> Option<Vec<u8>> + Send + Sync;
+
+/// Looks up attributes of paths, loading the `.gitattributes` files of their
+/// ancestor directories on demand.
+pub struct GitAttributesLookup {
+    load: Box<AttributesLoader>,
+    files: Mutex<HashMap<RepoPathBuf, Arc<GitAttributesFile>>>,
+}
+
+impl GitAttributesLookup {
+    /// Creates a lookup that calls `load` with a directory path to get the
+    /// contents of the `.gitattributes` file in it, if any.
+    pub fn new(load: impl Fn(&RepoPath) -> Option<Vec<u8>> + Send + Sync + 'static) -> Self {
+        Self {
+            load: Box::new(load),
+            files: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Creates a lookup that reads `.gitattributes` files from the working
+    /// copy at `root`.
+    pub fn from_disk(root: PathBuf) -> Self {
+        Self::new(move |dir| {
+            let path = dir
+                .to_fs_path_unchecked(&root)
+                .join(GITATTRIBUTES_FILE_NAME);
+            match fs::read(&path) {
+                Ok(content) => Some(content),
+                Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+                Err(err) => {
+                    tracing::warn!(?err, ?path, "failed to read .gitattributes");
+                    None
+                }
+            }
+        })
+    }
+
+    /// Creates a lookup that reads `.gitattributes` files from the `tree`.
+    ///
+    /// Conflicted `.gitattributes` files are ignored.
+    pub fn from_tree(tree: MergedTree) -> Self {
+        Self::new(move |dir| {
+            let name = RepoPathComponent::new(GITATTRIBUTES_FILE_NAME).unwrap();
+            let path = dir.join(name);
+            let read = async || {
+                let value = tree.path_value_async(&path).await.ok()?;
+                let Some(Some(TreeValue::File { id, .. })) = value.as_resolved() else {
+                    return None;
+                };
+                let mut reader = tree.store().read_file(&path, id).await.ok()?;
+                let mut content = vec![];
+                reader.read_to_end(&mut content).await.ok()?;
+                Some(content)
+            };
+            read().block_on()
+        })
+    }
+
+    /// Returns the effective attributes of the files in `dir`.
+    pub fn for_dir(&self, dir: &RepoPath) -> Arc<GitAttributesFile> {
+        if let Some(file) = self.files.lock().unwrap().get(dir) {
+            return file.clone();
+        }
+        let parent = match dir.parent() {
+            Some(parent) => self.for_dir(parent),
+            None => GitAttributesFile::empty(),
+        };
+        let file = match (self.load)(dir) {
+            Some(content) => parent.chain(&dir.to_internal_dir_string(), &content),
+            None => parent,
+        };
+        self.files
+            .lock()
+            .unwrap()
+            .insert(dir.to_owned(), file.clone());
+        file
+    }
+
+    /// Returns the state of the attribute `name` for the file at `path`.
+    pub fn get(&self, path: &RepoPath, name: &str) -> AttributeValue {
+        let Some(dir) = path.parent() else {
+            return AttributeValue::Unspecified;
+        };
+        self.for_dir(dir).get(path.as_internal_file_string(), name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get(input: &str, path: &str, name: &str) -> AttributeValue {
+        GitAttributesFile::empty()
+            .chain("", input.as_bytes())
+            .get(path, name)
+    }
+
+    #[test]
+    fn test_gitattributes_values() {
+        let input = "*.bin filter=lfs -text !diff eol\n";
+        assert_eq!(
+            get(input, "a.bin", "filter"),
+            AttributeValue::Value("lfs".to_owned())
+        );
+        assert_eq!(get(input, "a.bin", "text"), AttributeValue::Unset);
+        assert_eq!(get(input, "a.bin", "diff"), AttributeValue::Unspecified);
+        assert_eq!(get(input, "a.bin", "eol"), AttributeValue::Set);
+        assert_eq!(get(input, "a.bin", "merge"), AttributeValue::Unspecified);
+        assert_eq!(get(input, "a.txt", "filter"), AttributeValue::Unspecified);
+
+        let input = "*.bin -filter=lfs !diff=x\n";
+        assert_eq!(get(input, "a.bin", "filter"), AttributeValue::Unset);
+        assert_eq!(get(input, "a.bin", "diff"), AttributeValue::Unspecified);
+    }
+
+    #[test]
+    fn test_gitattributes_binary_macro() {
+        let input = "*.png binary\n";
+        assert_eq!(get(input, "a.png", "binary"), AttributeValue::Set);
+        assert_eq!(get(input, "a.png", "text"), AttributeValue::Unset);
+        assert_eq!(get(input, "a.png", "diff"), AttributeValue::Unset);
+        assert_eq!(get(input, "a.png", "merge"), AttributeValue::Unset);
+    }
+
+    #[test]
+    fn test_gitattributes_last_line_wins() {
+        let input = "# comment\n*.bin filter=lfs\n\ndir/*.bin -filter\n";
+        assert_eq!(
+            get(input, "a.bin", "filter"),
+            AttributeValue::Value("lfs".to_owned())
+        );
+        assert_eq!(
+            get(input, "sub/a.bin", "filter"),
+            AttributeValue::Value("lfs".to_owned())
+        );
+        assert_eq!(get(input, "dir/a.bin", "filter"), AttributeValue::Unset);
+        // Attributes not mentioned by the last matching line come from earlier
+        // lines.
+        assert_eq!(
+            get("* text\n*.bin -diff\n", "a.bin", "text"),
+            AttributeValue::Set
+        );
+    }
+
+    #[test]
+    fn test_gitattributes_patterns() {
+        // Directory patterns don't apply to files in the directory.
+        assert_eq!(
+            get("dir/ text\n", "dir/a", "text"),
+            AttributeValue::Unspecified
+        );
+        assert_eq!(
+            get("dir text\n", "dir/a", "text"),
+            AttributeValue::Unspecified
+        );
+        assert_eq!(get("dir/** text\n", "dir/a", "text"), AttributeValue::Set);
+        // Anchored patterns
+        assert_eq!(get("/a text\n", "a", "text"), AttributeValue::Set);
+        assert_eq!(
+            get("/a text\n", "dir/a", "text"),
+            AttributeValue::Unspecified
+        );
+        // Negative patterns are ignored
+        assert_eq!(get("!a text\n", "a", "text"), AttributeValue::Unspecified);
+    }
+
+    #[test]
+    fn test_gitattributes_chain() {
+        let file = GitAttributesFile::empty()
+            .chain("", b"*.bin filter=lfs\n")
+            .chain("dir/", b"*.bin -filter\n/top.txt text\n");
+        assert_eq!(
+            file.get("a.bin", "filter"),
+            AttributeValue::Value("lfs".to_owned())
+        );
+        assert_eq!(file.get("dir/a.bin", "filter"), AttributeValue::Unset);
+        assert_eq!(file.get("dir/top.txt", "text"), AttributeValue::Set);
+        assert_eq!(
+            file.get("dir/sub/top.txt", "text"),
+            AttributeValue::Unspecified
+        );
+    }
+
+    #[test]
+    fn test_gitattributes_lookup() {
+        let lookup = GitAttributesLookup::new(|dir| match dir.as_internal_file_string() {
+            "" => Some(b"*.bin filter=lfs\n".to_vec()),
+            "dir" => Some(b"*.bin -filter\n".to_vec()),
+            _ => None,
+        });
+        let path = |s| RepoPath::from_internal_string(s).unwrap();
+        assert_eq!(
+            lookup.get(path("a.bin"), "filter"),
+            AttributeValue::Value("lfs".to_owned())
+        );
+        assert_eq!(
+            lookup.get(path("sub/a.bin"), "filter"),
+            AttributeValue::Value("lfs".to_owned())
+        );
+        assert_eq!(
+            lookup.get(path("dir/a.bin"), "filter"),
+            AttributeValue::Unset
+        );
+        assert_eq!(
+            lookup.get(path("dir/sub/a.bin"), "filter"),
+            AttributeValue::Unset
+        );
+    }
+}
@@ -0,0 +1,385 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Git LFS pointer files and object storage.
+//!
+//! Files with the `filter=lfs` attribute are stored in the repository as small
+//! pointer files, while their contents live in a separate object store. Objects
+//! are laid out as in Git LFS, so the store in a Git repository's `lfs`
+//! directory can be shared with `git lfs`.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::io::Write as _;
+use std::path::Path;
+use std::path::PathBuf;
+
+use digest::Digest as _;
+use futures::StreamExt as _;
+use sha2::Sha256;
+use tempfile::NamedTempFile;
+use thiserror::Error;
+use tokio::io::AsyncReadExt as _;
+
+use crate::backend::BackendError;
+use crate::backend::TreeValue;
+use crate::file_util::persist_content_addressed_temp_file;
+use crate::gitattributes::GitAttributesLookup;
+use crate::hex_util;
+use crate::matchers::EverythingMatcher;
+use crate::merged_tree::MergedTree;
+use crate::merged_tree::TreeDiffEntry;
+use crate::repo_path::RepoPath;
+
+/// URL identifying the pointer file format.
+pub const LFS_SPEC_URL: &str = "https://git-lfs.github.com/spec/v1";
+
+/// Pointer files are never larger than this.
+pub const MAX_POINTER_SIZE: usize = 1024;
+
+/// Error accessing LFS objects.
+#[derive(Debug, Error)]
+pub enum LfsError {
+    /// Failed to read or write an object.
+    #[error("Failed to access LFS object {path}")]
+    Io {
+        /// Path to the object or object store.
+        path: PathBuf,
+        /// The underlying error.
+        source: io::Error,
+    },
+    /// Failed to read pointer files from the backend.
+    #[error(transparent)]
+    Backend(#[from] BackendError),
+}
+
+/// Contents of an LFS pointer file.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct LfsPointer {
+    /// Hex-encoded SHA-256 hash of the object.
+    pub oid: String,
+    /// Size of the object in bytes.
+    pub size: u64,
+}
+
+impl LfsPointer {
+    /// Parses the contents of a pointer file. Returns `None` if `content`
+    /// isn't a valid pointer.
+    pub fn parse(content: &[u8]) -> Option<Self> {
+        if content.len() > MAX_POINTER_SIZE {
+            return None;
+        }
+        let text = str::from_utf8(content).ok()?;
+        let mut lines = text.strip_suffix('\n')?.split('\n');
+        if lines.next()? != format!("version {LFS_SPEC_URL}") {
+            return None;
+        }
+        let mut oid = None;
+        let mut size = None;
+        for line in lines {
+            let (key, value) = line.split_once(' ')?;
+            match key {
+                "oid" => {
+                    let hex = value.strip_prefix("sha256:")?;
+                    let is_valid = hex.len() == 64
+                        && hex.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'));
+                    oid = Some(is_valid.then(|| hex.to_owned())?);
+                }
+                "size" => size = Some(value.parse().ok()?),
+                // Extensions aren't supported, but other keys are allowed.
+                _ if key.starts_with("ext-") => return None,
+                _ => {}
+            }
+        }
+        Some(Self {
+            oid: oid?,
+            size: size?,
+        })
+    }
+
+    /// Serializes the pointer to the contents of a pointer file.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut text = String::new();
+        writeln!(text, "version {LFS_SPEC_URL}").unwrap();
+        writeln!(text, "oid sha256:{}", self.oid).unwrap();
+        writeln!(text, "size {}", self.size).unwrap();
+        text.into_bytes()
+    }
+}
+
+/// Object store in the Git LFS layout, such as the local cache in the Git
+/// repository or the storage of a local remote.
+#[derive(Clone, Debug)]
+pub struct LfsObjectStore {
+    path: PathBuf,
+}
+
+impl LfsObjectStore {
+    /// Creates a store backed by the `lfs` directory at `path`. The directory
+    /// is created when the first object is written.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Path to the `lfs` directory.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Path to the file that contains the object with the given `oid`.
+    pub fn object_path(&self, oid: &str) -> PathBuf {
+        self.path
+            .join("objects")
+            .join(&oid[0..2])
+            .join(&oid[2..4])
+            .join(oid)
+    }
+
+    /// Returns whether the object the `pointer` refers to is present.
+    pub fn contains(&self, pointer: &LfsPointer) -> bool {
+        self.object_path(&pointer.oid)
+            .metadata()
+            .is_ok_and(|metadata| metadata.is_file() && metadata.len() == pointer.size)
+    }
+
+    /// Opens the object the `pointer` refers to for reading.
+    pub fn open(&self, pointer: &LfsPointer) -> Result<File, LfsError> {
+        let path = self.object_path(&pointer.oid);
+        File::open(&path).map_err(|source| LfsError::Io { path, source })
+    }
+
+    /// Writes `contents` to the store, and returns the pointer to it.
+    pub fn write(&self, contents: &mut dyn Read) -> Result<LfsPointer, LfsError> {
+        let temp_dir = self.path.join("tmp");
+        let io_error = |source| LfsError::Io {
+            path: temp_dir.clone(),
+            source,
+        };
+        fs::create_dir_all(&temp_dir).map_err(io_error)?;
+        let mut temp_file = NamedTempFile::new_in(&temp_dir).map_err(io_error)?;
+        let mut hasher = Sha256::new();
+        let mut size = 0;
+        let mut buf = vec![0; 1 << 16];
+        loop {
+            let len = match contents.read(&mut buf) {
+                Ok(0) => break,
+                Ok(len) => len,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(io_error(err)),
+            };
+            hasher.update(&buf[..len]);
+            temp_file.write_all(&buf[..len]).map_err(io_error)?;
+            size += len as u64;
+        }
+        let pointer = LfsPointer {
+            oid: hex_util::encode_hex(&hasher.finalize()),
+            size,
+        };
+        if !self.contains(&pointer) {
+            self.persist(temp_file, &pointer)?;
+        }
+        Ok(pointer)
+    }
+
+    /// Copies the object the `pointer` refers to into the `dest` store.
+    pub fn copy_to(&self, dest: &Self, pointer: &LfsPointer) -> Result<(), LfsError> {
+        let mut file = self.open(pointer)?;
+        let temp_dir = dest.path.join("tmp");
+        let io_error = |source| LfsError::Io {
+            path: temp_dir.clone(),
+            source,
+        };
+        fs::create_dir_all(&temp_dir).map_err(io_error)?;
+        let mut temp_file = NamedTempFile::new_in(&temp_dir).map_err(io_error)?;
+        io::copy(&mut file, &mut temp_file).map_err(io_error)?;
+        dest.persist(temp_file, pointer)
+    }
+
+    fn persist(&self, temp_file: NamedTempFile, pointer: &LfsPointer) -> Result<(), LfsError> {
+        let path = self.object_path(&pointer.oid);
+        let io_error = |source| LfsError::Io {
+            path: path.clone(),
+            source,
+        };
+        fs::create_dir_all(path.parent().unwrap()).map_err(io_error)?;
+        persist_content_addressed_temp_file(temp_file, &path).map_err(io_error)?;
+        Ok(())
+    }
+}
+
+/// Returns whether the file at `path` is stored in LFS according to the
+/// `.gitattributes` files.
+pub fn is_lfs_path(attributes: &GitAttributesLookup, path: &RepoPath) -> bool {
+    attributes.get(path, "filter").as_value() == Some("lfs")
+}
+
+/// Reads the pointer file with the given `id` at `path`. Returns `None` if the
+/// file isn't a valid pointer.
+async fn read_pointer(
+    tree: &MergedTree,
+    path: &RepoPath,
+    value: &TreeValue,
+) -> Result<Option<LfsPointer>, LfsError> {
+    let TreeValue::File { id, .. } = value else {
+        return Ok(None);
+    };
+    let mut reader = tree.store().read_file(path, id).await?;
+    let mut content = vec![];
+    (&mut reader)
+        .take(MAX_POINTER_SIZE as u64 + 1)
+        .read_to_end(&mut content)
+        .await
+        .map_err(|err| BackendError::ReadFile {
+            path: path.to_owned(),
+            id: id.clone(),
+            source: err.into(),
+        })?;
+    Ok(LfsPointer::parse(&content))
+}
+
+/// Finds the LFS pointers of the files that were added or modified between
+/// `from_tree` and `to_tree`.
+///
+/// Only files with the `filter=lfs` attribute in `to_tree` are considered.
+pub async fn changed_lfs_pointers(
+    from_tree: &MergedTree,
+    to_tree: &MergedTree,
+) -> Result<Vec<LfsPointer>, LfsError> {
+    let attributes = GitAttributesLookup::from_tree(to_tree.clone());
+    let mut pointers = vec![];
+    let mut diff_stream = from_tree.diff_stream(to_tree, &EverythingMatcher);
+    while let Some(TreeDiffEntry { path, values }) = diff_stream.next().await {
+        let values = values?;
+        let Some(Some(value)) = values.after.as_resolved() else {
+            continue;
+        };
+        if !is_lfs_path(&attributes, &path) {
+            continue;
+        }
+        if let Some(pointer) = read_pointer(to_tree, &path, value).await? {
+            pointers.push(pointer);
+        }
+    }
+    Ok(pointers)
+}
+
+/// Returns the LFS object store of a remote, if the remote is a local
+/// repository.
+///
+/// Objects are stored in the `lfs` directory of the remote Git repository, as
+/// `git lfs` does for local remotes.
+#[cfg(feature = "git")]
+pub fn remote_object_store(
+    git_repo: &gix::Repository,
+    remote_name: &crate::ref_name::RemoteName,
+    direction: gix::remote::Direction,
+) -> Option<LfsObjectStore> {
+    use bstr::ByteSlice as _;
+
+    let remote = git_repo.try_find_remote(remote_name.as_str())?.ok()?;
+    let url = remote.url(direction)?;
+    if url.scheme != gix::url::Scheme::File {
+        return None;
+    }
+    let path = gix::path::from_bstr(url.path.as_bstr()).into_owned();
+    let path = match git_repo.workdir() {
+        Some(workdir) if path.is_relative() => workdir.join(path),
+        _ => path,
+    };
+    let git_dir = path.join(".git");
+    if git_dir.is_dir() {
+        Some(LfsObjectStore::new(git_dir.join("lfs")))
+    } else if path.is_dir() {
+        Some(LfsObjectStore::new(path.join("lfs")))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    const OID: &str = "4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393";
+
+    #[test]
+    fn test_parse_pointer() {
+        let content = indoc! {"
+            version https://git-lfs.github.com/spec/v1
+            oid sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393
+            size 12345
+        "};
+        let pointer = LfsPointer::parse(content.as_bytes()).unwrap();
+        assert_eq!(pointer.oid, OID);
+        assert_eq!(pointer.size, 12345);
+        assert_eq!(pointer.to_bytes(), content.as_bytes());
+    }
+
+    #[test]
+    fn test_parse_pointer_invalid() {
+        let valid = LfsPointer {
+            oid: OID.to_owned(),
+            size: 1,
+        }
+        .to_bytes();
+        assert!(LfsPointer::parse(&valid).is_some());
+        // Missing trailing newline
+        assert!(LfsPointer::parse(&valid[..valid.len() - 1]).is_none());
+        // Not a pointer
+        assert!(LfsPointer::parse(b"hello\n").is_none());
+        assert!(LfsPointer::parse(b"").is_none());
+        // Missing size
+        let content = format!("version {LFS_SPEC_URL}\noid sha256:{OID}\n");
+        assert!(LfsPointer::parse(content.as_bytes()).is_none());
+        // Bad oid
+        let content = format!("version {LFS_SPEC_URL}\noid sha256:{}\nsize 1\n", &OID[1..]);
+        assert!(LfsPointer::parse(content.as_bytes()).is_none());
+        let content = format!("version {LFS_SPEC_URL}\noid sha1:{OID}\nsize 1\n");
+        assert!(LfsPointer::parse(content.as_bytes()).is_none());
+    }
+
+    #[test]
+    fn test_object_store() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = LfsObjectStore::new(temp_dir.path().join("lfs"));
+        let pointer = store.write(&mut b"hello".as_slice()).unwrap();
+        assert_eq!(
+            pointer.oid,
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+        assert_eq!(pointer.size, 5);
+        assert!(store.contains(&pointer));
+        assert_eq!(
+            store.object_path(&pointer.oid),
+            temp_dir.path().join("lfs/objects/2c/f2").join(&pointer.oid)
+        );
+        let mut content = String::new();
+        store
+            .open(&pointer)
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, "hello");
+
+        let other_store = LfsObjectStore::new(temp_dir.path().join("other"));
+        assert!(!other_store.contains(&pointer));
+        store.copy_to(&other_store, &pointer).unwrap();
+        assert!(other_store.contains(&pointer));
+    }
+}
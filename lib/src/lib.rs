@@ -55,12 +55,16 @@ mod fileset_parser;
 pub mod fix;
 pub mod fmt_util;
 pub mod fsmonitor;
+pub mod gc;
 #[cfg(feature = "git")]
 pub mod git;
 #[cfg(feature = "git")]
 pub mod git_backend;
 #[cfg(feature = "git")]
+pub mod git_submodule;
+#[cfg(feature = "git")]
 mod git_subprocess;
+pub mod gitattributes;
 pub mod gitignore;
 pub mod gpg_signing;
 pub mod graph;
@@ -68,10 +72,12 @@ pub mod hex_util;
 pub mod id_prefix;
 pub mod index;
 pub mod iter_util;
+pub mod lfs;
 pub mod local_working_copy;
 pub mod lock;
 pub mod matchers;
 pub mod merge;
+pub mod merge_driver;
 pub mod merged_tree;
 pub mod merged_tree_builder;
 pub mod object_id;
@@ -79,12 +85,14 @@ pub mod op_heads_store;
 pub mod op_store;
 pub mod op_walk;
 pub mod operation;
+pub mod patch;
 #[expect(missing_docs)]
 pub mod protos;
 pub mod ref_name;
 pub mod refs;
 pub mod repo;
 pub mod repo_path;
+pub mod rerere;
 pub mod revset;
 mod revset_parser;
 pub mod rewrite;
@@ -77,6 +77,7 @@ use crate::conflicts::MaterializedTreeValue;
 use crate::conflicts::choose_materialized_conflict_marker_len;
 use crate::conflicts::materialize_merge_result_to_bytes;
 use crate::conflicts::materialize_tree_value;
+use crate::eol::EolAttributes;
 pub use crate::eol::EolConversionMode;
 use crate::eol::TargetEolStrategy;
 use crate::file_util::BlockingAsyncReader;
@@ -88,9 +89,15 @@ use crate::file_util::symlink_file;
 use crate::fsmonitor::FsmonitorSettings;
 #[cfg(feature = "watchman")]
 use crate::fsmonitor::WatchmanConfig;
+#[cfg(target_os = "linux")]
+use crate::fsmonitor::inotify;
 #[cfg(feature = "watchman")]
 use crate::fsmonitor::watchman;
+use crate::gitattributes::GitAttributesLookup;
 use crate::gitignore::GitIgnoreFile;
+use crate::lfs;
+use crate::lfs::LfsObjectStore;
+use crate::lfs::LfsPointer;
 use crate::lock::FileLock;
 use crate::matchers::DifferenceMatcher;
 use crate::matchers::EverythingMatcher;
@@ -936,6 +943,7 @@ fn file_state(metadata: &Metadata) -> Result<Option<FileState>, MtimeOutOfRange>
 struct FsmonitorMatcher {
     matcher: Option<Box<dyn Matcher>>,
     watchman_clock: Option<crate::protos::local_working_copy::WatchmanClock>,
+    inotify_clock: Option<crate::protos::local_working_copy::InotifyClock>,
 }
 
 /// Settings specific to the tree state of the [`LocalWorkingCopy`] backend.
@@ -982,10 +990,18 @@ pub struct TreeState {
     /// Watchman has been queried at least once.
     watchman_clock: Option<crate::protos::local_working_copy::WatchmanClock>,
 
+    /// The most recent clock value returned by the inotify daemon. Will only
+    /// be set if the repo is configured to use the inotify filesystem monitor
+    /// and the daemon was running when it was last queried.
+    inotify_clock: Option<crate::protos::local_working_copy::InotifyClock>,
+
     conflict_marker_style: ConflictMarkerStyle,
     exec_policy: ExecChangePolicy,
     fsmonitor_settings: FsmonitorSettings,
     target_eol_strategy: TargetEolStrategy,
+    /// Where to store the contents of files with the `filter=lfs` attribute.
+    /// Only set if the backend is Git.
+    lfs_store: Option<LfsObjectStore>,
 }
 
 #[derive(Debug, Error)]
@@ -1049,6 +1065,12 @@ impl TreeState {
         }: &TreeStateSettings,
     ) -> Self {
         let exec_policy = ExecChangePolicy::new(exec_change_setting, &state_path);
+        #[cfg(feature = "git")]
+        let lfs_store = store
+            .backend_impl::<crate::git_backend::GitBackend>()
+            .map(|backend| backend.lfs_store());
+        #[cfg(not(feature = "git"))]
+        let lfs_store = None;
         Self {
             store: store.clone(),
             working_copy_path,
@@ -1059,10 +1081,12 @@ impl TreeState {
             own_mtime: MillisSinceEpoch(0),
             symlink_support: check_symlink_support().unwrap_or(false),
             watchman_clock: None,
+            inotify_clock: None,
             conflict_marker_style,
             exec_policy,
             fsmonitor_settings: fsmonitor_settings.clone(),
             target_eol_strategy: TargetEolStrategy::new(eol_conversion_mode),
+            lfs_store,
         }
     }
 
@@ -1137,6 +1161,7 @@ impl TreeState {
             FileStatesMap::from_proto(proto.file_states, proto.is_file_states_sorted);
         self.sparse_patterns = sparse_patterns_from_proto(proto.sparse_patterns.as_ref());
         self.watchman_clock = proto.watchman_clock;
+        self.inotify_clock = proto.inotify_clock;
         Ok(())
     }
 
@@ -1161,6 +1186,7 @@ impl TreeState {
         }
         proto.sparse_patterns = Some(sparse_patterns);
         proto.watchman_clock = self.watchman_clock.clone();
+        proto.inotify_clock = self.inotify_clock.clone();
 
         let wrap_write_err = |source| TreeStateError::WriteTreeState {
             path: self.state_path.clone(),
@@ -1190,6 +1216,23 @@ impl TreeState {
         self.watchman_clock.take();
     }
 
+    /// Returns the inotify filesystem monitor of this working copy.
+    #[cfg(target_os = "linux")]
+    pub fn inotify_fsmonitor(&self) -> inotify::Fsmonitor {
+        inotify::Fsmonitor::new(&self.working_copy_path, &self.state_path.join("inotify"))
+    }
+
+    #[cfg(target_os = "linux")]
+    #[instrument(skip(self))]
+    pub fn query_inotify(
+        &self,
+    ) -> Result<(Option<inotify::Clock>, Option<Vec<PathBuf>>), TreeStateError> {
+        let previous_clock = self.inotify_clock.clone().map(inotify::Clock::from);
+        self.inotify_fsmonitor()
+            .query_changed_files(previous_clock)
+            .map_err(|err| TreeStateError::Fsmonitor(Box::new(err)))
+    }
+
     #[cfg(feature = "watchman")]
     #[instrument(skip(self))]
     pub async fn query_watchman(
@@ -1273,6 +1316,7 @@ impl TreeState {
         let FsmonitorMatcher {
             matcher: fsmonitor_matcher,
             watchman_clock,
+            inotify_clock,
         } = self
             .make_fsmonitor_matcher(&self.fsmonitor_settings)
             .await?;
@@ -1288,6 +1332,7 @@ impl TreeState {
         if matcher.visit(RepoPath::root()).is_nothing() {
             // No need to load the current tree, set up channels, etc.
             self.watchman_clock = watchman_clock;
+            self.inotify_clock = inotify_clock;
             return Ok((is_dirty, SnapshotStats::default()));
         }
 
@@ -1311,6 +1356,7 @@ impl TreeState {
                 error: OnceLock::new(),
                 progress,
                 max_new_file_size,
+                attributes: GitAttributesLookup::from_disk(self.working_copy_path.clone()),
             };
             let directory_to_visit = DirectoryToVisit {
                 dir: RepoPathBuf::root(),
@@ -1374,10 +1420,11 @@ impl TreeState {
         // Since untracked paths aren't cached in the tree state, we'll need to
         // rescan the working directory changes to report or track them later.
         // TODO: store untracked paths and update watchman_clock?
-        if stats.untracked_paths.is_empty() || watchman_clock.is_none() {
+        if stats.untracked_paths.is_empty() || watchman_clock.is_none() && inotify_clock.is_none() {
             self.watchman_clock = watchman_clock;
+            self.inotify_clock = inotify_clock;
         } else {
-            tracing::info!("not updating watchman clock because there are untracked files");
+            tracing::info!("not updating fsmonitor clock because there are untracked files");
         }
         Ok((is_dirty, stats))
     }
@@ -1387,9 +1434,30 @@ impl TreeState {
         &self,
         fsmonitor_settings: &FsmonitorSettings,
     ) -> Result<FsmonitorMatcher, SnapshotError> {
+        let mut inotify_clock = None;
         let (watchman_clock, changed_files) = match fsmonitor_settings {
             FsmonitorSettings::None => (None, None),
             FsmonitorSettings::Test { changed_files } => (None, Some(changed_files.clone())),
+            #[cfg(target_os = "linux")]
+            FsmonitorSettings::Inotify(_) => match self.query_inotify() {
+                Ok((clock, changed_files)) => {
+                    inotify_clock = clock.map(Into::into);
+                    (None, changed_files)
+                }
+                Err(err) => {
+                    tracing::warn!(?err, "Failed to query filesystem monitor");
+                    (None, None)
+                }
+            },
+            #[cfg(not(target_os = "linux"))]
+            FsmonitorSettings::Inotify(_) => {
+                return Err(SnapshotError::Other {
+                    message: "Failed to query the filesystem monitor".to_string(),
+                    err: "The inotify filesystem monitor is only supported on Linux (consider \
+                          disabling `fsmonitor.backend`)"
+                        .into(),
+                });
+            }
             #[cfg(feature = "watchman")]
             FsmonitorSettings::Watchman(config) => match self.query_watchman(config).await {
                 Ok((watchman_clock, changed_files)) => (Some(watchman_clock.into()), changed_files),
@@ -1430,7 +1498,13 @@ impl TreeState {
                         (repo_paths, gitignore_prefixes)
                     });
 
-                let matcher: Box<dyn Matcher> = if gitignore_prefixes.is_empty() {
+                let matcher: Box<dyn Matcher> = if inotify_clock.is_some() {
+                    // The inotify daemon reports changed directories instead of
+                    // their content.
+                    Box::new(PrefixMatcher::new(
+                        repo_paths.into_iter().chain(gitignore_prefixes),
+                    ))
+                } else if gitignore_prefixes.is_empty() {
                     Box::new(FilesMatcher::new(repo_paths))
                 } else {
                     Box::new(UnionMatcher::new(
@@ -1445,6 +1519,7 @@ impl TreeState {
         Ok(FsmonitorMatcher {
             matcher,
             watchman_clock,
+            inotify_clock,
         })
     }
 }
@@ -1482,6 +1557,8 @@ struct FileSnapshotter<'a> {
     error: OnceLock<SnapshotError>,
     progress: Option<&'a SnapshotProgress<'a>>,
     max_new_file_size: u64,
+    /// Attributes of the files on disk.
+    attributes: GitAttributesLookup,
 }
 
 impl FileSnapshotter<'_> {
@@ -1574,6 +1651,9 @@ impl FileSnapshotter<'_> {
         if let Some(file_state) = &maybe_current_file_state
             && file_state.file_type == FileType::GitSubmodule
         {
+            if file_type.is_dir() && self.matcher.matches(&path) {
+                self.process_submodule(path, &entry.path())?;
+            }
             return Ok(None);
         }
 
@@ -1671,16 +1751,41 @@ impl FileSnapshotter<'_> {
         }
     }
 
+    /// Records the commit checked out in the submodule at `disk_path` if it
+    /// differs from the one in the current tree.
+    fn process_submodule(&self, path: RepoPathBuf, disk_path: &Path) -> Result<(), SnapshotError> {
+        #[cfg(feature = "git")]
+        let head = crate::git_submodule::read_submodule_head(disk_path);
+        #[cfg(not(feature = "git"))]
+        let head: Option<crate::backend::CommitId> = {
+            let _ = disk_path;
+            None
+        };
+        // Uninitialized submodules keep the commit recorded in the tree.
+        let Some(commit_id) = head else {
+            return Ok(());
+        };
+        let current_tree_values = self.current_tree.path_value(&path)?;
+        let new_tree_values = Merge::normal(TreeValue::GitSubmodule(commit_id));
+        if new_tree_values != current_tree_values {
+            self.tree_entries_tx.send((path, new_tree_values)).ok();
+        }
+        Ok(())
+    }
+
     /// Visits only paths we're already tracking.
     fn visit_tracked_files(&self, file_states: FileStates<'_>) -> Result<(), SnapshotError> {
         for (tracked_path, current_file_state) in file_states {
-            if current_file_state.file_type == FileType::GitSubmodule {
-                continue;
-            }
             if !self.matcher.matches(tracked_path) {
                 continue;
             }
             let disk_path = tracked_path.to_fs_path(&self.tree_state.working_copy_path)?;
+            if current_file_state.file_type == FileType::GitSubmodule {
+                if disk_path.is_dir() {
+                    self.process_submodule(tracked_path.to_owned(), &disk_path)?;
+                }
+                continue;
+            }
             let metadata = match disk_path.symlink_metadata() {
                 Ok(metadata) => Some(metadata),
                 Err(err) if err.kind() == io::ErrorKind::NotFound => None,
@@ -1820,7 +1925,9 @@ impl FileSnapshotter<'_> {
                         .block_on()?;
                     Merge::normal(TreeValue::Symlink(id))
                 }
-                FileType::GitSubmodule => panic!("git submodule cannot be written to store"),
+                // Submodules are processed separately, and their file states
+                // are never replaced by the ones read from disk.
+                FileType::GitSubmodule => return Ok(None),
             };
             if new_tree_values != current_tree_values {
                 Ok(Some(new_tree_values))
@@ -1888,9 +1995,10 @@ impl FileSnapshotter<'_> {
                 message: format!("Failed to open file {}", disk_path.display()),
                 err: err.into(),
             })?;
+            let eol_attributes = EolAttributes::from_lookup(&self.attributes, repo_path);
             self.tree_state
                 .target_eol_strategy
-                .convert_eol_for_snapshot(BlockingAsyncReader::new(file))
+                .convert_eol_for_snapshot(&eol_attributes, BlockingAsyncReader::new(file))
                 .await
                 .map_err(|err| SnapshotError::Other {
                     message: "Failed to convert the EOL".to_string(),
@@ -1948,14 +2056,22 @@ impl FileSnapshotter<'_> {
         path: &RepoPath,
         disk_path: &Path,
     ) -> Result<FileId, SnapshotError> {
+        if let Some(lfs_store) = &self.tree_state.lfs_store
+            && lfs::is_lfs_path(&self.attributes, path)
+        {
+            return self
+                .write_lfs_file_to_store(lfs_store, path, disk_path)
+                .await;
+        }
         let file = File::open(disk_path).map_err(|err| SnapshotError::Other {
             message: format!("Failed to open file {}", disk_path.display()),
             err: err.into(),
         })?;
+        let eol_attributes = EolAttributes::from_lookup(&self.attributes, path);
         let mut contents = self
             .tree_state
             .target_eol_strategy
-            .convert_eol_for_snapshot(BlockingAsyncReader::new(file))
+            .convert_eol_for_snapshot(&eol_attributes, BlockingAsyncReader::new(file))
             .await
             .map_err(|err| SnapshotError::Other {
                 message: "Failed to convert the EOL".to_string(),
@@ -1964,6 +2080,42 @@ impl FileSnapshotter<'_> {
         Ok(self.store().write_file(path, &mut contents).await?)
     }
 
+    /// Moves the contents of the file to the LFS store, and writes a pointer
+    /// file to the backend instead. Files that are already pointers (because
+    /// the object wasn't available at checkout) are stored as is.
+    async fn write_lfs_file_to_store(
+        &self,
+        lfs_store: &LfsObjectStore,
+        path: &RepoPath,
+        disk_path: &Path,
+    ) -> Result<FileId, SnapshotError> {
+        let read_error = |err: io::Error| SnapshotError::Other {
+            message: format!("Failed to read file {}", disk_path.display()),
+            err: err.into(),
+        };
+        let mut file = File::open(disk_path).map_err(read_error)?;
+        let mut head = vec![];
+        (&mut file)
+            .take(lfs::MAX_POINTER_SIZE as u64 + 1)
+            .read_to_end(&mut head)
+            .map_err(read_error)?;
+        let pointer = if LfsPointer::parse(&head).is_some() {
+            head
+        } else {
+            lfs_store
+                .write(&mut io::Read::chain(head.as_slice(), file))
+                .map_err(|err| SnapshotError::Other {
+                    message: format!("Failed to store LFS object for {}", disk_path.display()),
+                    err: err.into(),
+                })?
+                .to_bytes()
+        };
+        Ok(self
+            .store()
+            .write_file(path, &mut pointer.as_slice())
+            .await?)
+    }
+
     async fn write_symlink_to_store(
         &self,
         path: &RepoPath,
@@ -2008,7 +2160,7 @@ impl TreeState {
         disk_path: &Path,
         contents: impl AsyncRead + Send + Unpin,
         exec_bit: ExecBit,
-        apply_eol_conversion: bool,
+        eol_attributes: Option<&EolAttributes>,
     ) -> Result<FileState, CheckoutError> {
         let mut file = File::options()
             .write(true)
@@ -2018,9 +2170,9 @@ impl TreeState {
                 message: format!("Failed to open file {} for writing", disk_path.display()),
                 err: err.into(),
             })?;
-        let contents = if apply_eol_conversion {
+        let contents = if let Some(eol_attributes) = eol_attributes {
             self.target_eol_strategy
-                .convert_eol_for_update(contents)
+                .convert_eol_for_update(eol_attributes, contents)
                 .await
                 .map_err(|err| CheckoutError::Other {
                     message: "Failed to convert the EOL for the content".to_string(),
@@ -2051,6 +2203,43 @@ impl TreeState {
             .map_err(|err| checkout_error_for_mtime_out_of_range(err, disk_path))
     }
 
+    /// Writes the LFS object the pointer file refers to. If the object isn't
+    /// in the LFS store, the pointer file is written instead.
+    async fn write_lfs_file(
+        &self,
+        lfs_store: &LfsObjectStore,
+        disk_path: &Path,
+        mut contents: impl AsyncRead + Send + Unpin,
+        exec_bit: ExecBit,
+        eol_attributes: &EolAttributes,
+    ) -> Result<FileState, CheckoutError> {
+        let mut head = vec![];
+        (&mut contents)
+            .take(lfs::MAX_POINTER_SIZE as u64 + 1)
+            .read_to_end(&mut head)
+            .await
+            .map_err(|err| CheckoutError::Other {
+                message: format!("Failed to read the content for {}", disk_path.display()),
+                err: err.into(),
+            })?;
+        if let Some(pointer) = LfsPointer::parse(&head)
+            && lfs_store.contains(&pointer)
+        {
+            let object = lfs_store
+                .open(&pointer)
+                .map_err(|err| CheckoutError::Other {
+                    message: format!("Failed to read LFS object for {}", disk_path.display()),
+                    err: err.into(),
+                })?;
+            self.write_file(disk_path, BlockingAsyncReader::new(object), exec_bit, None)
+                .await
+        } else {
+            let contents = tokio::io::AsyncReadExt::chain(head.as_slice(), contents);
+            self.write_file(disk_path, contents, exec_bit, Some(eol_attributes))
+                .await
+        }
+    }
+
     fn write_symlink(&self, disk_path: &Path, target: String) -> Result<FileState, CheckoutError> {
         let target = symlink_target_convert_to_disk(&target);
 
@@ -2094,10 +2283,11 @@ impl TreeState {
         disk_path: &Path,
         contents: &[u8],
         exec_bit: ExecBit,
+        eol_attributes: &EolAttributes,
     ) -> Result<FileState, CheckoutError> {
         let contents = self
             .target_eol_strategy
-            .convert_eol_for_update(contents)
+            .convert_eol_for_update(eol_attributes, contents)
             .await
             .map_err(|err| CheckoutError::Other {
                 message: "Failed to convert the EOL when writing a merge conflict".to_string(),
@@ -2180,6 +2370,7 @@ impl TreeState {
         let mut changed_file_states = Vec::new();
         let mut deleted_files = HashSet::new();
         let mut prev_created_path: RepoPathBuf = RepoPathBuf::root();
+        let attributes = GitAttributesLookup::from_tree(new_tree.clone());
 
         let mut process_diff_entry = async |path: RepoPathBuf,
                                             before: MergedTreeValue,
@@ -2194,16 +2385,15 @@ impl TreeState {
             }
 
             // Existing Git submodule can be a non-empty directory on disk. We
-            // shouldn't attempt to manage it as a tracked path.
+            // shouldn't attempt to manage it as a tracked path. The contents
+            // of the submodule are updated by the submodule store.
             //
             // TODO: It might be better to add general support for paths not
             // tracked by jj than processing submodules specially. For example,
             // paths excluded by .gitignore can be marked as such so that
             // newly-"unignored" paths won't be snapshotted automatically.
-            if matches!(before.as_normal(), Some(TreeValue::GitSubmodule(_)))
-                && matches!(after, MaterializedTreeValue::GitSubmodule(_))
-            {
-                eprintln!("ignoring git submodule at {path:?}");
+            let was_submodule = matches!(before.as_normal(), Some(TreeValue::GitSubmodule(_)));
+            if was_submodule && matches!(after, MaterializedTreeValue::GitSubmodule(_)) {
                 // Not updating the file state as if there were no diffs. Leave
                 // the state type as FileType::GitSubmodule if it was before.
                 return Ok(());
@@ -2258,6 +2448,27 @@ impl TreeState {
                 disk_path
             };
 
+            // The directory of an uninitialized submodule is empty, and can be
+            // removed.
+            if was_submodule {
+                match fs::remove_dir(&disk_path) {
+                    Ok(()) => {}
+                    Err(err)
+                        if matches!(
+                            err.kind(),
+                            io::ErrorKind::NotFound | io::ErrorKind::DirectoryNotEmpty
+                        ) => {}
+                    Err(err) => {
+                        return Err(CheckoutError::Other {
+                            message: format!(
+                                "Failed to remove submodule directory {}",
+                                disk_path.display()
+                            ),
+                            err: err.into(),
+                        });
+                    }
+                }
+            }
             // If the path was present, check reserved path first and delete it.
             let present_file_deleted = before.is_present() && remove_old_file(&disk_path)?;
             // If not, create temporary file to test the path validity.
@@ -2294,20 +2505,42 @@ impl TreeState {
                 MaterializedTreeValue::File(file) => {
                     let exec_bit =
                         ExecBit::new_from_repo(file.executable, self.exec_policy, get_prev_exec);
-                    self.write_file(&disk_path, file.reader, exec_bit, true)
+                    let eol_attributes = EolAttributes::from_lookup(&attributes, &path);
+                    if let Some(lfs_store) = &self.lfs_store
+                        && lfs::is_lfs_path(&attributes, &path)
+                    {
+                        self.write_lfs_file(
+                            lfs_store,
+                            &disk_path,
+                            file.reader,
+                            exec_bit,
+                            &eol_attributes,
+                        )
                         .await?
+                    } else {
+                        self.write_file(&disk_path, file.reader, exec_bit, Some(&eol_attributes))
+                            .await?
+                    }
                 }
                 MaterializedTreeValue::Symlink { id: _, target } => {
                     if self.symlink_support {
                         self.write_symlink(&disk_path, target)?
                     } else {
                         // The fake symlink file shouldn't be executable.
-                        self.write_file(&disk_path, target.as_bytes(), ExecBit(false), false)
+                        self.write_file(&disk_path, target.as_bytes(), ExecBit(false), None)
                             .await?
                     }
                 }
                 MaterializedTreeValue::GitSubmodule(_) => {
-                    eprintln!("ignoring git submodule at {path:?}");
+                    // Create an empty directory as Git does for uninitialized
+                    // submodules.
+                    fs::create_dir(&disk_path).map_err(|err| CheckoutError::Other {
+                        message: format!(
+                            "Failed to create submodule directory {}",
+                            disk_path.display()
+                        ),
+                        err: err.into(),
+                    })?;
                     FileState::for_gitsubmodule()
                 }
                 MaterializedTreeValue::Tree(_) => {
@@ -2328,8 +2561,10 @@ impl TreeState {
                     );
                     let contents =
                         materialize_merge_result_to_bytes(&file.contents, &file.labels, &options);
-                    let mut file_state =
-                        self.write_conflict(&disk_path, &contents, exec_bit).await?;
+                    let eol_attributes = EolAttributes::from_lookup(&attributes, &path);
+                    let mut file_state = self
+                        .write_conflict(&disk_path, &contents, exec_bit, &eol_attributes)
+                        .await?;
                     file_state.materialized_conflict_data = Some(MaterializedConflictData {
                         conflict_marker_len: conflict_marker_len.try_into().unwrap_or(u32::MAX),
                     });
@@ -2340,8 +2575,14 @@ impl TreeState {
                     // better than trying to describe the merge.
                     let contents = id.describe(&labels);
                     // Since this is a dummy file, it shouldn't be executable.
-                    self.write_conflict(&disk_path, contents.as_bytes(), ExecBit(false))
-                        .await?
+                    let eol_attributes = EolAttributes::from_lookup(&attributes, &path);
+                    self.write_conflict(
+                        &disk_path,
+                        contents.as_bytes(),
+                        ExecBit(false),
+                        &eol_attributes,
+                    )
+                    .await?
                 }
             };
             changed_file_states.push((path, file_state));
@@ -2430,10 +2671,7 @@ impl TreeState {
                             FileType::Normal { exec_bit }
                         }
                         TreeValue::Symlink(_id) => FileType::Symlink,
-                        TreeValue::GitSubmodule(_id) => {
-                            eprintln!("ignoring git submodule at {path:?}");
-                            FileType::GitSubmodule
-                        }
+                        TreeValue::GitSubmodule(_id) => FileType::GitSubmodule,
                         TreeValue::Tree(_id) => {
                             panic!("unexpected tree entry in diff at {path:?}");
                         }
@@ -2706,6 +2944,24 @@ impl LocalWorkingCopy {
             })
     }
 
+    /// Returns the inotify filesystem monitor of this working copy.
+    #[cfg(target_os = "linux")]
+    pub fn inotify_fsmonitor(&self) -> inotify::Fsmonitor {
+        inotify::Fsmonitor::new(&self.working_copy_path, &self.state_path.join("inotify"))
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn query_inotify(
+        &self,
+    ) -> Result<(Option<inotify::Clock>, Option<Vec<PathBuf>>), WorkingCopyStateError> {
+        self.tree_state()?
+            .query_inotify()
+            .map_err(|err| WorkingCopyStateError {
+                message: "Failed to query the inotify daemon".to_string(),
+                err: err.into(),
+            })
+    }
+
     #[cfg(feature = "watchman")]
     pub async fn is_watchman_trigger_registered(
         &self,
@@ -0,0 +1,272 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Per-path merge drivers selected by the `merge` attribute in
+//! `.gitattributes` files.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::process::Command;
+use std::process::ExitStatus;
+use std::process::Stdio;
+use std::sync::LazyLock;
+
+use bstr::BString;
+use itertools::Itertools as _;
+use regex::Captures;
+use regex::Regex;
+use thiserror::Error;
+
+use crate::config::ConfigGetError;
+use crate::file_util::IoResultExt as _;
+use crate::file_util::PathError;
+use crate::files;
+use crate::files::MergeResult;
+use crate::gitattributes::AttributeValue;
+use crate::merge::Merge;
+use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathBuf;
+use crate::settings::UserSettings;
+use crate::tree_merge::MergeOptions;
+
+/// How to merge the contents of a conflicted file.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum MergeDriver {
+    /// The built-in line-based merge. This is the default.
+    Text,
+    /// Leave the conflict unresolved without merging the contents.
+    Binary,
+    /// Keep the conflicting lines of all sides, without conflict markers.
+    Union,
+    /// Keep the contents of the first side, which is the destination when
+    /// rebasing, or the first parent when merging.
+    Ours,
+    /// Run the external driver configured in `merge-drivers.<name>`.
+    External(String),
+}
+
+impl MergeDriver {
+    /// Returns the driver selected by the state of the `merge` attribute.
+    ///
+    /// As in Git, a driver name that isn't one of the built-in drivers refers
+    /// to an external driver. It falls back to [`MergeDriver::Text`] if no
+    /// such driver is configured.
+    pub fn from_attribute(value: &AttributeValue) -> Self {
+        match value {
+            AttributeValue::Set | AttributeValue::Unspecified => Self::Text,
+            AttributeValue::Unset => Self::Binary,
+            AttributeValue::Value(name) => match name.as_str() {
+                "text" => Self::Text,
+                "binary" => Self::Binary,
+                "union" => Self::Union,
+                "ours" => Self::Ours,
+                _ => Self::External(name.clone()),
+            },
+        }
+    }
+}
+
+/// Table of external merge drivers by name.
+pub type ExternalMergeDriverMap = HashMap<String, ExternalMergeDriver>;
+
+/// External merge driver configured in `merge-drivers.<name>`.
+#[derive(Clone, Debug, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ExternalMergeDriver {
+    /// Program and arguments to run. The `$base`, `$left` and `$right`
+    /// variables are replaced with paths to temporary files containing the
+    /// contents of each term, and `$path` with the path of the file in the
+    /// repo. The driver should overwrite `$left` with the merged contents, and
+    /// exit with a non-zero status if it couldn't resolve the conflict.
+    pub command: Vec<String>,
+}
+
+/// Error that may occur while running an external merge driver.
+#[derive(Debug, Error)]
+pub enum MergeDriverError {
+    /// The command is empty.
+    #[error("The merge driver command is empty")]
+    EmptyCommand,
+    /// Failed to write or read the temporary files.
+    #[error(transparent)]
+    Io(#[from] PathError),
+    /// Failed to start the driver.
+    #[error("Failed to run merge driver '{program}'")]
+    Spawn {
+        /// Program of the driver.
+        program: String,
+        /// Underlying error.
+        source: io::Error,
+    },
+    /// The driver was terminated without an exit code, e.g. by a signal.
+    #[error("Merge driver '{program}' was terminated ({status})")]
+    Terminated {
+        /// Program of the driver.
+        program: String,
+        /// Exit status of the driver.
+        status: ExitStatus,
+    },
+}
+
+/// Error that occurred while merging a file with an external merge driver.
+#[derive(Debug, Error)]
+#[error("Failed to merge {path} with merge driver '{name}'", path = path.as_internal_file_string())]
+pub struct ExternalMergeError {
+    /// Name of the driver.
+    pub name: String,
+    /// Path of the merged file.
+    pub path: RepoPathBuf,
+    /// Underlying error.
+    pub source: MergeDriverError,
+}
+
+static VARIABLE_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\$([a-z0-9_]+)\b").unwrap());
+
+impl ExternalMergeDriver {
+    /// Loads the table of external merge drivers from `settings`.
+    pub fn table_from_settings(
+        settings: &UserSettings,
+    ) -> Result<ExternalMergeDriverMap, ConfigGetError> {
+        settings
+            .table_keys("merge-drivers")
+            .map(|name| Ok((name.to_owned(), settings.get(["merge-drivers", name])?)))
+            .try_collect()
+    }
+
+    /// Runs the driver to merge the `contents` of a 2-sided conflict of the
+    /// file at `path`. Returns `None` if the driver exited with a non-zero
+    /// status because it couldn't resolve the conflict.
+    pub fn merge(
+        &self,
+        path: &RepoPath,
+        contents: &Merge<impl AsRef<[u8]>>,
+    ) -> Result<Option<BString>, MergeDriverError> {
+        let [left, base, right] = contents.as_slice() else {
+            return Ok(None);
+        };
+        let (program, args) = self
+            .command
+            .split_first()
+            .ok_or(MergeDriverError::EmptyCommand)?;
+        let temp_dir = tempfile::Builder::new()
+            .prefix("jj-merge-driver-")
+            .tempdir()
+            .context(std::env::temp_dir())?;
+        let write = |name: &str, content: &[u8]| {
+            let file_path = temp_dir.path().join(name);
+            fs::write(&file_path, content).context(&file_path)?;
+            Ok::<_, PathError>(file_path)
+        };
+        let base_path = write("base", base.as_ref())?;
+        let left_path = write("left", left.as_ref())?;
+        let right_path = write("right", right.as_ref())?;
+        let variables = HashMap::from([
+            ("base", base_path.to_string_lossy()),
+            ("left", left_path.to_string_lossy()),
+            ("right", right_path.to_string_lossy()),
+            ("path", path.as_internal_file_string().into()),
+        ]);
+        let args = args.iter().map(|arg| {
+            VARIABLE_REGEX.replace_all(arg, |caps: &Captures| {
+                variables
+                    .get(&caps[1])
+                    .map_or_else(|| caps[0].to_owned(), |value| value.to_string())
+            })
+        });
+        let output = Command::new(program)
+            .args(args.map(|arg| arg.into_owned()))
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|source| MergeDriverError::Spawn {
+                program: program.clone(),
+                source,
+            })?;
+        if output.status.code().is_none() {
+            return Err(MergeDriverError::Terminated {
+                program: program.clone(),
+                status: output.status,
+            });
+        } else if !output.status.success() {
+            tracing::info!(
+                ?path,
+                status = ?output.status,
+                stderr = %String::from_utf8_lossy(&output.stderr),
+                "merge driver didn't resolve the conflict"
+            );
+            return Ok(None);
+        }
+        let merged = fs::read(&left_path).context(&left_path)?;
+        Ok(Some(merged.into()))
+    }
+}
+
+/// Merges `contents` by keeping the conflicting lines of all sides in order.
+pub fn union_merge(contents: &Merge<impl AsRef<[u8]>>, options: &MergeOptions) -> BString {
+    match files::merge_hunks(contents, options) {
+        MergeResult::Resolved(content) => content,
+        MergeResult::Conflict(hunks) => {
+            let mut content = BString::default();
+            for hunk in hunks {
+                if let Some(resolved) = hunk.as_resolved() {
+                    content.extend_from_slice(resolved);
+                } else {
+                    for side in hunk.adds() {
+                        content.extend_from_slice(side);
+                    }
+                }
+            }
+            content
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::files::FileMergeHunkLevel;
+    use crate::merge::SameChange;
+
+    #[test]
+    fn test_from_attribute() {
+        let driver = |value| MergeDriver::from_attribute(&value);
+        assert_eq!(driver(AttributeValue::Unspecified), MergeDriver::Text);
+        assert_eq!(driver(AttributeValue::Set), MergeDriver::Text);
+        assert_eq!(driver(AttributeValue::Unset), MergeDriver::Binary);
+        let value = |name: &str| AttributeValue::Value(name.to_owned());
+        assert_eq!(driver(value("text")), MergeDriver::Text);
+        assert_eq!(driver(value("binary")), MergeDriver::Binary);
+        assert_eq!(driver(value("union")), MergeDriver::Union);
+        assert_eq!(driver(value("ours")), MergeDriver::Ours);
+        assert_eq!(
+            driver(value("lockfile")),
+            MergeDriver::External("lockfile".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_union_merge() {
+        let options = MergeOptions {
+            hunk_level: FileMergeHunkLevel::Line,
+            same_change: SameChange::Accept,
+        };
+        let contents = Merge::from_vec(vec!["a\nleft\nz\n", "a\nz\n", "a\nright\nz\n"]);
+        assert_eq!(union_merge(&contents, &options), "a\nleft\nright\nz\n");
+        // Lines removed by a side are dropped
+        let contents = Merge::from_vec(vec!["a\nleft\n", "a\nbase\n", "a\nright\n"]);
+        assert_eq!(union_merge(&contents, &options), "a\nleft\nright\n");
+        let contents = Merge::from_vec(vec!["a\nleft\n", "a\n", "a\n"]);
+        assert_eq!(union_merge(&contents, &options), "a\nleft\n");
+    }
+}
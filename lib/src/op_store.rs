@@ -21,7 +21,6 @@ use std::collections::HashSet;
 use std::fmt::Debug;
 use std::iter;
 use std::sync::LazyLock;
-use std::time::SystemTime;
 
 use async_trait::async_trait;
 use itertools::Itertools as _;
@@ -31,6 +30,8 @@ use crate::backend::CommitId;
 use crate::backend::MillisSinceEpoch;
 use crate::backend::Timestamp;
 use crate::content_hash::ContentHash;
+use crate::gc::GcOptions;
+use crate::gc::GcStats;
 use crate::merge::Merge;
 use crate::object_id::HexPrefix;
 use crate::object_id::ObjectId as _;
@@ -481,11 +482,13 @@ pub trait OpStore: Any + Send + Sync + Debug {
     /// Prunes unreachable operations and views.
     ///
     /// All operations and views reachable from the `head_ids` won't be
-    /// removed. In addition to that, objects created after `keep_newer` will be
-    /// preserved. This mitigates a risk of deleting new heads created
-    /// concurrently by another process.
-    // TODO: return stats?
-    fn gc(&self, head_ids: &[OperationId], keep_newer: SystemTime) -> OpStoreResult<()>;
+    /// removed. In addition to that, objects created after
+    /// `options.keep_newer` will be preserved. This mitigates a risk of
+    /// deleting new heads created concurrently by another process.
+    ///
+    /// Returns the number of operations removed, or the number of operations
+    /// that would be removed if `options.dry_run` is set.
+    fn gc(&self, head_ids: &[OperationId], options: &GcOptions) -> OpStoreResult<GcStats>;
 }
 
 impl dyn OpStore {
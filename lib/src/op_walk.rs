@@ -15,6 +15,7 @@
 //! Utility for operation id resolution and traversal.
 
 use std::cmp::Ordering;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::slice;
@@ -24,6 +25,8 @@ use itertools::Itertools as _;
 use pollster::FutureExt as _;
 use thiserror::Error;
 
+use crate::backend::CommitId;
+use crate::backend::MillisSinceEpoch;
 use crate::dag_walk;
 use crate::object_id::HexPrefix;
 use crate::object_id::PrefixResolution;
@@ -31,6 +34,7 @@ use crate::op_heads_store;
 use crate::op_heads_store::OpHeadResolutionError;
 use crate::op_heads_store::OpHeadsStore;
 use crate::op_heads_store::OpHeadsStoreError;
+use crate::op_store;
 use crate::op_store::OpStore;
 use crate::op_store::OpStoreError;
 use crate::op_store::OpStoreResult;
@@ -331,7 +335,7 @@ fn collect_ancestors_until_roots(
     items
 }
 
-/// Stats about `reparent_range()`.
+/// Stats about `reparent_range()` and `compact_snapshot_operations()`.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ReparentStats {
     /// New head operation ids in order of the old `head_ops`.
@@ -368,19 +372,20 @@ pub fn reparent_range(
             .is_none_or(|op| op.id() != op_store.root_operation_id()),
         "root operation cannot be rewritten"
     );
-    let mut rewritten_ids = HashMap::new();
-    for old_op in ops_to_reparent.into_iter().rev() {
-        let mut data = old_op.store_operation().clone();
-        let mut dest_once = Some(dest_op.id());
-        data.parents = data
-            .parents
-            .iter()
-            .filter_map(|id| rewritten_ids.get(id).or_else(|| dest_once.take()))
-            .cloned()
-            .collect();
-        let new_id = op_store.write_operation(&data).block_on()?;
-        rewritten_ids.insert(old_op.id().clone(), new_id);
-    }
+    let (rewritten_ids, rewritten_count) = rewrite_operations(
+        op_store,
+        &ops_to_reparent,
+        |old_op, rewritten_ids| {
+            let mut dest_once = Some(dest_op.id());
+            old_op
+                .parent_ids()
+                .iter()
+                .filter_map(|id| rewritten_ids.get(id).or_else(|| dest_once.take()))
+                .cloned()
+                .collect()
+        },
+        |_, _| RewriteAction::Rewrite,
+    )?;
 
     let mut dest_once = Some(dest_op.id());
     let new_head_ids = head_ops
@@ -390,7 +395,151 @@ pub fn reparent_range(
         .collect();
     Ok(ReparentStats {
         new_head_ids,
-        rewritten_count: rewritten_ids.len(),
+        rewritten_count,
         unreachable_count,
     })
 }
+
+/// Squashes runs of consecutive snapshot operations that ended before
+/// `older_than` into single operations.
+///
+/// Within each run, the last snapshot operation is kept, and the preceding
+/// ones are abandoned. The kept operation inherits the commit predecessors of
+/// the abandoned operations. Descendant operations are reparented in the same
+/// way as [`reparent_range()`]. Operations ended at or after `older_than` are
+/// left intact, so the recent history can still be undone step by step.
+pub fn compact_snapshot_operations(
+    op_store: &dyn OpStore,
+    head_ops: &[Operation],
+    older_than: MillisSinceEpoch,
+) -> OpStoreResult<ReparentStats> {
+    let ops: Vec<_> = walk_ancestors(head_ops).try_collect()?;
+    let mut child_ops: HashMap<&OperationId, Vec<&Operation>> = HashMap::new();
+    for op in &ops {
+        for parent_id in op.parent_ids() {
+            child_ops.entry(parent_id).or_default().push(op);
+        }
+    }
+    let is_old_linear_snapshot = |op: &Operation| {
+        op.metadata().is_snapshot
+            && op.metadata().time.end.timestamp < older_than
+            && op.parent_ids().len() == 1
+    };
+    // An operation can be abandoned if its only child is an old snapshot
+    // operation which will supersede it.
+    let abandoned_ids: HashSet<&OperationId> = ops
+        .iter()
+        .filter(|op| is_old_linear_snapshot(op))
+        .filter(|op| match child_ops.get(op.id()).map(Vec::as_slice) {
+            Some([child]) => is_old_linear_snapshot(child),
+            _ => false,
+        })
+        .map(|op| op.id())
+        .collect();
+
+    // Commit predecessors of the abandoned operations to be carried over to
+    // the next operation in the run.
+    let mut pending_predecessors: HashMap<OperationId, _> = HashMap::new();
+    let (rewritten_ids, rewritten_count) = rewrite_operations(
+        op_store,
+        &ops,
+        |old_op, rewritten_ids| {
+            old_op
+                .parent_ids()
+                .iter()
+                .map(|id| rewritten_ids.get(id).unwrap_or(id).clone())
+                .collect()
+        },
+        |old_op, data| {
+            let inherited_predecessors = old_op
+                .parent_ids()
+                .iter()
+                .find_map(|id| pending_predecessors.remove(id));
+            if abandoned_ids.contains(old_op.id()) {
+                let own_predecessors = data.commit_predecessors.take();
+                let predecessors = match inherited_predecessors {
+                    Some(predecessors) => merge_commit_predecessors(predecessors, own_predecessors),
+                    None => own_predecessors,
+                };
+                pending_predecessors.insert(old_op.id().clone(), predecessors);
+                return RewriteAction::Abandon;
+            }
+            if let Some(predecessors) = inherited_predecessors {
+                data.commit_predecessors =
+                    merge_commit_predecessors(predecessors, data.commit_predecessors.take());
+            }
+            if data == old_op.store_operation() {
+                RewriteAction::Keep
+            } else {
+                RewriteAction::Rewrite
+            }
+        },
+    )?;
+
+    let new_head_ids = head_ops
+        .iter()
+        .map(|op| rewritten_ids.get(op.id()).unwrap_or(op.id()).clone())
+        .collect();
+    Ok(ReparentStats {
+        new_head_ids,
+        rewritten_count,
+        unreachable_count: abandoned_ids.len(),
+    })
+}
+
+/// What to do with an operation visited by `rewrite_operations()`.
+enum RewriteAction {
+    /// Writes the operation with the new data.
+    Rewrite,
+    /// Leaves the operation as is. The new data must be unchanged.
+    Keep,
+    /// Abandons the operation, replacing it with its only new parent.
+    Abandon,
+}
+
+/// Rewrites `ops` (in the order returned by [`walk_ancestors()`]) onto new
+/// parents, parents first.
+///
+/// `map_parent_ids()` computes the new parent ids of an operation given the
+/// ids of the operations rewritten so far. `rewrite_data()` can then modify
+/// the operation data, and decides what to do with the operation.
+///
+/// Returns the mapping from old to new operation ids, and the number of
+/// rewritten operations.
+fn rewrite_operations(
+    op_store: &dyn OpStore,
+    ops: &[Operation],
+    mut map_parent_ids: impl FnMut(&Operation, &HashMap<OperationId, OperationId>) -> Vec<OperationId>,
+    mut rewrite_data: impl FnMut(&Operation, &mut op_store::Operation) -> RewriteAction,
+) -> OpStoreResult<(HashMap<OperationId, OperationId>, usize)> {
+    let mut rewritten_ids = HashMap::new();
+    let mut rewritten_count = 0;
+    for old_op in ops.iter().rev() {
+        let mut data = old_op.store_operation().clone();
+        data.parents = map_parent_ids(old_op, &rewritten_ids);
+        let new_id = match rewrite_data(old_op, &mut data) {
+            RewriteAction::Rewrite => {
+                rewritten_count += 1;
+                op_store.write_operation(&data).block_on()?
+            }
+            RewriteAction::Keep => old_op.id().clone(),
+            RewriteAction::Abandon => {
+                let [parent_id] = data.parents.try_into().unwrap();
+                parent_id
+            }
+        };
+        rewritten_ids.insert(old_op.id().clone(), new_id);
+    }
+    Ok((rewritten_ids, rewritten_count))
+}
+
+/// Merges commit predecessors recorded by consecutive operations. The result
+/// is `None` if either operation didn't record the predecessors.
+fn merge_commit_predecessors(
+    older: Option<BTreeMap<CommitId, Vec<CommitId>>>,
+    newer: Option<BTreeMap<CommitId, Vec<CommitId>>>,
+) -> Option<BTreeMap<CommitId, Vec<CommitId>>> {
+    let (mut merged, newer) = older.zip(newer)?;
+    merged.extend(newer);
+    Some(merged)
+}
@@ -0,0 +1,1032 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parsing of mailbox patches as produced by `git format-patch`, and
+//! application of the parsed patches to trees.
+
+use std::iter;
+
+use bstr::BStr;
+use bstr::BString;
+use bstr::ByteSlice as _;
+use thiserror::Error;
+use tokio::io::AsyncReadExt as _;
+
+use crate::backend::BackendError;
+use crate::backend::BackendResult;
+use crate::backend::CopyId;
+use crate::backend::Timestamp;
+use crate::backend::TreeValue;
+use crate::diff_presentation::unified::DiffLineType;
+use crate::merge::Merge;
+use crate::merge::MergedTreeValue;
+use crate::merged_tree::MergedTree;
+use crate::merged_tree_builder::MergedTreeBuilder;
+use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathBuf;
+use crate::store::Store;
+
+/// Error parsing a mailbox.
+#[derive(Debug, Error)]
+#[error("Malformed patch at line {line}: {message}")]
+pub struct PatchParseError {
+    /// 1-based line number in the mailbox.
+    pub line: usize,
+    /// Description of the problem.
+    pub message: String,
+}
+
+/// A single patch email.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PatchMessage {
+    /// Author name from the `From` header.
+    pub author_name: String,
+    /// Author email from the `From` header.
+    pub author_email: String,
+    /// Author date from the `Date` header.
+    pub author_timestamp: Option<Timestamp>,
+    /// Subject (without the `[PATCH]` prefix) and body of the message.
+    pub description: String,
+    /// Changes to the files.
+    pub files: Vec<FilePatch>,
+}
+
+/// Type of a file as recorded by the mode lines of a patch.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PatchFileMode {
+    /// Mode `100644`
+    Normal,
+    /// Mode `100755`
+    Executable,
+    /// Mode `120000`
+    Symlink,
+    /// Mode `160000`, which can't be applied.
+    GitSubmodule,
+}
+
+impl PatchFileMode {
+    fn parse(mode: &str) -> Option<Self> {
+        match mode {
+            "100644" => Some(Self::Normal),
+            "100755" => Some(Self::Executable),
+            "120000" => Some(Self::Symlink),
+            "160000" => Some(Self::GitSubmodule),
+            _ => None,
+        }
+    }
+}
+
+/// Changes to a single file.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FilePatch {
+    /// Path before the change, or `None` if the file is added.
+    pub old_path: Option<RepoPathBuf>,
+    /// Path after the change, or `None` if the file is deleted.
+    pub new_path: Option<RepoPathBuf>,
+    /// Mode after the change, if recorded in the patch.
+    pub new_mode: Option<PatchFileMode>,
+    /// Whether the old path is kept, as in a copy.
+    pub is_copy: bool,
+    /// Whether the change is to binary content, which can't be applied.
+    pub is_binary: bool,
+    /// Changed line ranges.
+    pub hunks: Vec<PatchHunk>,
+}
+
+/// Hunk of a unified diff.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PatchHunk {
+    /// 1-based line number of the first line in the old file, or 0 if the
+    /// old range is empty and starts at the beginning of the file.
+    pub old_start: usize,
+    /// Lines including their line terminators.
+    pub lines: Vec<(DiffLineType, BString)>,
+}
+
+impl PatchHunk {
+    /// Lines before the change.
+    pub fn old_lines(&self) -> impl Iterator<Item = &BStr> {
+        self.lines
+            .iter()
+            .filter(|(line_type, _)| *line_type != DiffLineType::Added)
+            .map(|(_, line)| line.as_bstr())
+    }
+
+    /// Lines after the change.
+    pub fn new_lines(&self) -> impl Iterator<Item = &BStr> {
+        self.lines
+            .iter()
+            .filter(|(line_type, _)| *line_type != DiffLineType::Removed)
+            .map(|(_, line)| line.as_bstr())
+    }
+}
+
+/// Splits the mailbox into messages, and parses each of them.
+pub fn parse_mailbox(data: &[u8]) -> Result<Vec<PatchMessage>, PatchParseError> {
+    let lines = data.split_inclusive(|b| *b == b'\n').collect::<Vec<_>>();
+    let mut starts = vec![];
+    for (i, line) in lines.iter().enumerate() {
+        // The mbox separator is "From <sender> <date>", which should be
+        // followed by the mail headers.
+        let next_is_header = lines
+            .get(i + 1)
+            .is_some_and(|next| parse_header_line(next).is_some());
+        if line.starts_with(b"From ") && next_is_header {
+            starts.push(i + 1);
+        }
+    }
+    if starts.is_empty() && !lines.is_empty() {
+        // A single message without the mbox separator
+        starts.push(0);
+    }
+    let ends = starts
+        .iter()
+        .skip(1)
+        .map(|&start| start - 1)
+        .chain([lines.len()]);
+    iter::zip(&starts, ends)
+        .map(|(&start, end)| {
+            let mut parser = LineParser {
+                lines: &lines[..end],
+                pos: start,
+            };
+            parser.parse_message()
+        })
+        .collect()
+}
+
+fn parse_header_line(line: &[u8]) -> Option<(&str, &str)> {
+    let line = line.to_str().ok()?.trim_end();
+    let (name, value) = line.split_once(':')?;
+    let is_name = !name.is_empty() && name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-');
+    is_name.then(|| (name, value.trim()))
+}
+
+struct LineParser<'a> {
+    lines: &'a [&'a [u8]],
+    pos: usize,
+}
+
+impl<'a> LineParser<'a> {
+    fn peek(&self) -> Option<&'a [u8]> {
+        self.lines.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<&'a [u8]> {
+        let line = self.peek()?;
+        self.pos += 1;
+        Some(line)
+    }
+
+    fn error(&self, message: impl Into<String>) -> PatchParseError {
+        PatchParseError {
+            line: self.pos,
+            message: message.into(),
+        }
+    }
+
+    fn parse_message(&mut self) -> Result<PatchMessage, PatchParseError> {
+        let mut from = None;
+        let mut date = None;
+        let mut subject = None;
+        let mut headers: Vec<(String, String)> = vec![];
+        while let Some(line) = self.next() {
+            if line.trim_ascii().is_empty() {
+                break;
+            }
+            if line.starts_with(b" ") || line.starts_with(b"\t") {
+                // Folded header
+                let (_, value) = headers
+                    .last_mut()
+                    .ok_or_else(|| self.error("Unexpected continuation line"))?;
+                value.push(' ');
+                value.push_str(line.to_str_lossy().trim());
+                continue;
+            }
+            let (name, value) =
+                parse_header_line(line).ok_or_else(|| self.error("Invalid header line"))?;
+            headers.push((name.to_ascii_lowercase(), value.to_owned()));
+        }
+        for (name, value) in headers {
+            match name.as_str() {
+                "from" => from = Some(decode_header_words(&value)),
+                "date" => date = Some(value),
+                "subject" => subject = Some(decode_header_words(&value)),
+                _ => {}
+            }
+        }
+        let from = from.ok_or_else(|| self.error("Missing From header"))?;
+        let (author_name, author_email) = parse_address(&from);
+        let author_timestamp = date
+            .map(|date| {
+                chrono::DateTime::parse_from_rfc2822(&date)
+                    .map(Timestamp::from_datetime)
+                    .map_err(|err| self.error(format!("Invalid Date header: {err}")))
+            })
+            .transpose()?;
+        let subject = strip_subject_prefixes(subject.as_deref().unwrap_or_default());
+
+        let mut body = String::new();
+        while let Some(line) = self.peek() {
+            if line.trim_ascii_end() == b"---" || line.starts_with(b"diff --git ") {
+                break;
+            }
+            // Lines starting with "From " are escaped as ">From " in mboxrd
+            // format.
+            let line = match line.strip_prefix(b">") {
+                Some(rest) if rest.trim_start_with(|c| c == '>').starts_with(b"From ") => rest,
+                _ => line,
+            };
+            body.push_str(&line.to_str_lossy());
+            self.pos += 1;
+        }
+        let body = body.trim();
+        let mut description = subject.to_owned();
+        if !body.is_empty() {
+            description.push_str("\n\n");
+            description.push_str(body);
+        }
+        if !description.is_empty() {
+            description.push('\n');
+        }
+
+        let mut files = vec![];
+        while let Some(line) = self.next() {
+            if let Some(header) = line.strip_prefix(b"diff --git ") {
+                files.push(self.parse_file_patch(header)?);
+            }
+        }
+        Ok(PatchMessage {
+            author_name,
+            author_email,
+            author_timestamp,
+            description,
+            files,
+        })
+    }
+
+    fn parse_file_patch(&mut self, header: &[u8]) -> Result<FilePatch, PatchParseError> {
+        let (mut old_path, mut new_path) = self
+            .parse_git_header_paths(header)
+            .ok_or_else(|| self.error("Invalid diff header"))?;
+        let mut new_mode = None;
+        let mut is_copy = false;
+        let mut is_binary = false;
+        while let Some(line) = self.peek() {
+            let line_str = line.to_str_lossy();
+            let line_str = line_str.trim_end();
+            if line.starts_with(b"diff --git ") || line.starts_with(b"@@ ") {
+                break;
+            } else if let Some(mode) = line_str.strip_prefix("new file mode ") {
+                old_path = None;
+                new_mode = PatchFileMode::parse(mode);
+            } else if line_str.starts_with("deleted file mode ") {
+                new_path = None;
+            } else if let Some(mode) = line_str.strip_prefix("new mode ") {
+                new_mode = PatchFileMode::parse(mode);
+            } else if let Some(path) = line_str
+                .strip_prefix("rename from ")
+                .or_else(|| line_str.strip_prefix("copy from "))
+            {
+                is_copy = line_str.starts_with("copy ");
+                old_path = Some(self.parse_path(path)?);
+            } else if let Some(path) = line_str
+                .strip_prefix("rename to ")
+                .or_else(|| line_str.strip_prefix("copy to "))
+            {
+                new_path = Some(self.parse_path(path)?);
+            } else if let Some(path) = line_str.strip_prefix("--- ") {
+                if path != "/dev/null" {
+                    old_path = Some(self.parse_prefixed_path(path)?);
+                }
+            } else if let Some(path) = line_str.strip_prefix("+++ ") {
+                if path != "/dev/null" {
+                    new_path = Some(self.parse_prefixed_path(path)?);
+                }
+            } else if line_str.starts_with("Binary files ") || line_str == "GIT binary patch" {
+                is_binary = true;
+            } else if !line_str.starts_with("old mode ")
+                && !line_str.starts_with("index ")
+                && !line_str.starts_with("similarity index ")
+                && !line_str.starts_with("dissimilarity index ")
+                && !is_binary
+            {
+                break;
+            }
+            self.pos += 1;
+        }
+        let mut hunks = vec![];
+        while let Some(line) = self.peek()
+            && line.starts_with(b"@@ ")
+        {
+            self.pos += 1;
+            hunks.push(self.parse_hunk(line)?);
+        }
+        Ok(FilePatch {
+            old_path,
+            new_path,
+            new_mode,
+            is_copy,
+            is_binary,
+            hunks,
+        })
+    }
+
+    /// Parses the paths in `a/<old> b/<new>`. If the paths contain " b/",
+    /// they can only be found if they're the same.
+    fn parse_git_header_paths(
+        &self,
+        header: &[u8],
+    ) -> Option<(Option<RepoPathBuf>, Option<RepoPathBuf>)> {
+        let header = header.to_str().ok()?.trim_end();
+        let (old, new) = if header.starts_with('"') {
+            let (old, rest) = split_quoted(header)?;
+            (old, unquote(rest.trim_start())?)
+        } else {
+            let mid = header.len().saturating_sub(1) / 2;
+            match header.get(..mid).zip(header.get(mid..)) {
+                Some((old, rest)) if old.strip_prefix("a/") == rest.strip_prefix(" b/") => {
+                    (old.to_owned(), rest[1..].to_owned())
+                }
+                _ => {
+                    let (old, new) = header.split_once(" b/")?;
+                    (old.to_owned(), format!("b/{new}"))
+                }
+            }
+        };
+        let old = old.strip_prefix("a/")?;
+        let new = new.strip_prefix("b/")?;
+        Some((
+            RepoPathBuf::from_internal_string(old).ok(),
+            RepoPathBuf::from_internal_string(new).ok(),
+        ))
+    }
+
+    fn parse_path(&self, path: &str) -> Result<RepoPathBuf, PatchParseError> {
+        let path = unquote(path).ok_or_else(|| self.error("Invalid quoted path"))?;
+        RepoPathBuf::from_internal_string(path).map_err(|err| self.error(err.to_string()))
+    }
+
+    fn parse_prefixed_path(&self, path: &str) -> Result<RepoPathBuf, PatchParseError> {
+        let path = unquote(path).ok_or_else(|| self.error("Invalid quoted path"))?;
+        // Strip the "a/" or "b/" prefix
+        let (_, path) = path
+            .split_once('/')
+            .ok_or_else(|| self.error("Path without a/ or b/ prefix"))?;
+        RepoPathBuf::from_internal_string(path).map_err(|err| self.error(err.to_string()))
+    }
+
+    fn parse_hunk(&mut self, header: &[u8]) -> Result<PatchHunk, PatchParseError> {
+        let parse_range = |range: &str| -> Option<(usize, usize)> {
+            match range.split_once(',') {
+                Some((start, len)) => Some((start.parse().ok()?, len.parse().ok()?)),
+                None => Some((range.parse().ok()?, 1)),
+            }
+        };
+        let ranges = header
+            .to_str()
+            .ok()
+            .and_then(|header| {
+                let (old, new) = header
+                    .strip_prefix("@@ -")?
+                    .split_once(" @@")?
+                    .0
+                    .split_once(" +")?;
+                Some((parse_range(old)?, parse_range(new)?))
+            })
+            .ok_or_else(|| self.error("Invalid hunk header"))?;
+        let ((old_start, mut old_remaining), (_, mut new_remaining)) = ranges;
+        let mut lines: Vec<(DiffLineType, BString)> = vec![];
+        while old_remaining > 0 || new_remaining > 0 {
+            let line = self
+                .next()
+                .ok_or_else(|| self.error("Unexpected end of hunk"))?;
+            let (line_type, content) = match line.first() {
+                Some(b' ') => (DiffLineType::Context, &line[1..]),
+                // Some mail clients strip the space from empty context lines
+                Some(b'\n') => (DiffLineType::Context, line),
+                Some(b'-') => (DiffLineType::Removed, &line[1..]),
+                Some(b'+') => (DiffLineType::Added, &line[1..]),
+                Some(b'\\') => continue,
+                _ => return Err(self.error("Invalid hunk line")),
+            };
+            if line_type != DiffLineType::Added {
+                old_remaining = old_remaining
+                    .checked_sub(1)
+                    .ok_or_else(|| self.error("Hunk is longer than its header says"))?;
+            }
+            if line_type != DiffLineType::Removed {
+                new_remaining = new_remaining
+                    .checked_sub(1)
+                    .ok_or_else(|| self.error("Hunk is longer than its header says"))?;
+            }
+            lines.push((line_type, content.into()));
+            self.strip_missing_newline(&mut lines);
+        }
+        Ok(PatchHunk { old_start, lines })
+    }
+
+    fn strip_missing_newline(&mut self, lines: &mut [(DiffLineType, BString)]) {
+        if let Some(line) = self.peek()
+            && line.starts_with(b"\\ ")
+        {
+            self.pos += 1;
+            let (_, content) = lines.last_mut().unwrap();
+            if content.ends_with(b"\n") {
+                content.pop();
+            }
+        }
+    }
+}
+
+/// Splits `"quoted" rest` into the unquoted string and the rest.
+fn split_quoted(s: &str) -> Option<(String, &str)> {
+    let mut escaped = false;
+    let end = s.char_indices().skip(1).find_map(|(i, c)| {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == '"' {
+            return Some(i);
+        }
+        None
+    })?;
+    Some((unquote(&s[..=end])?, &s[end + 1..]))
+}
+
+/// Decodes C-style quoted path as written by Git.
+fn unquote(s: &str) -> Option<String> {
+    let Some(quoted) = s.strip_prefix('"').and_then(|s| s.strip_suffix('"')) else {
+        return Some(s.to_owned());
+    };
+    let mut bytes = vec![];
+    let mut chars = quoted.bytes();
+    while let Some(b) = chars.next() {
+        if b != b'\\' {
+            bytes.push(b);
+            continue;
+        }
+        let escaped = match chars.next()? {
+            b'n' => b'\n',
+            b't' => b'\t',
+            b'"' => b'"',
+            b'\\' => b'\\',
+            d @ b'0'..=b'7' => {
+                let digits = [d, chars.next()?, chars.next()?];
+                u8::from_str_radix(str::from_utf8(&digits).ok()?, 8).ok()?
+            }
+            _ => return None,
+        };
+        bytes.push(escaped);
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Splits `Name <email>` into name and email.
+fn parse_address(address: &str) -> (String, String) {
+    match address.rsplit_once('<') {
+        Some((name, email)) => {
+            let name = name.trim();
+            let name = match name
+                .strip_prefix('"')
+                .and_then(|name| name.strip_suffix('"'))
+            {
+                Some(quoted) => unquote_rfc822(quoted),
+                None => name.to_owned(),
+            };
+            let email = email.trim_end_matches('>').trim();
+            (name, email.to_owned())
+        }
+        None => (String::new(), address.trim().to_owned()),
+    }
+}
+
+/// Removes backslash escapes from the contents of an RFC 822 quoted string.
+fn unquote_rfc822(quoted: &str) -> String {
+    let mut unquoted = String::new();
+    let mut chars = quoted.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => unquoted.extend(chars.next()),
+            _ => unquoted.push(c),
+        }
+    }
+    unquoted
+}
+
+/// Removes `[PATCH ...]` and `Re:` prefixes from the subject.
+fn strip_subject_prefixes(mut subject: &str) -> &str {
+    loop {
+        subject = subject.trim_start();
+        if subject.starts_with('[')
+            && let Some(end) = subject.find(']')
+        {
+            subject = &subject[end + 1..];
+        } else if let Some(rest) = subject
+            .strip_prefix("Re:")
+            .or_else(|| subject.strip_prefix("RE:"))
+        {
+            subject = rest;
+        } else {
+            return subject.trim_end();
+        }
+    }
+}
+
+/// Decodes RFC 2047 "Q" encoded words such as `=?UTF-8?q?J=C3=B6rg?=`. Other
+/// encodings are left as is.
+fn decode_header_words(value: &str) -> String {
+    let mut decoded = String::new();
+    let mut rest = value;
+    let mut after_encoded_word = false;
+    while let Some(start) = rest.find("=?") {
+        let word = rest[start + 2..].splitn(4, '?').collect::<Vec<_>>();
+        let [_charset, encoding, text, tail] = word[..] else {
+            break;
+        };
+        let Some(tail) = tail.strip_prefix('=') else {
+            break;
+        };
+        if !encoding.eq_ignore_ascii_case("q") {
+            break;
+        }
+        let Some(text) = decode_q(text) else {
+            break;
+        };
+        // Whitespace between adjacent encoded words is ignored.
+        let between = &rest[..start];
+        if !(after_encoded_word && between.trim().is_empty()) {
+            decoded.push_str(between);
+        }
+        decoded.push_str(&text);
+        rest = tail;
+        after_encoded_word = true;
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+fn decode_q(text: &str) -> Option<String> {
+    let mut bytes = vec![];
+    let mut iter = text.bytes();
+    while let Some(b) = iter.next() {
+        match b {
+            b'_' => bytes.push(b' '),
+            b'=' => {
+                let hex = [iter.next()?, iter.next()?];
+                bytes.push(u8::from_str_radix(str::from_utf8(&hex).ok()?, 16).ok()?);
+            }
+            _ => bytes.push(b),
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Result of applying hunks to a file content.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AppliedHunks<'a> {
+    /// Content with the applicable hunks applied.
+    pub content: BString,
+    /// Hunks which couldn't be located in the content.
+    pub rejected: Vec<&'a PatchHunk>,
+}
+
+/// Applies the `hunks` to the `content`.
+///
+/// Each hunk is looked up near its recorded position, so hunks still apply
+/// if lines were added or removed before them. Hunks whose old lines can't be
+/// found are rejected.
+pub fn apply_hunks<'a>(content: &[u8], hunks: &'a [PatchHunk]) -> AppliedHunks<'a> {
+    let lines = content.split_inclusive(|b| *b == b'\n').collect::<Vec<_>>();
+    let mut new_content = BString::default();
+    let mut rejected = vec![];
+    // Index of the first line not copied to the new content yet
+    let mut copied = 0;
+    // Difference between the actual and the recorded line positions
+    let mut offset: isize = 0;
+    for hunk in hunks {
+        let old_lines = hunk.old_lines().collect::<Vec<_>>();
+        let expected = hunk
+            .old_start
+            .saturating_sub(1)
+            .saturating_add_signed(offset);
+        let matches_at = |pos: usize| {
+            pos >= copied
+                && pos + old_lines.len() <= lines.len()
+                && iter::zip(&lines[pos..], &old_lines).all(|(a, b)| *a == b.as_bytes())
+        };
+        let Some(pos) = (0..=lines.len())
+            .flat_map(|delta| [expected.checked_add(delta), expected.checked_sub(delta)])
+            .flatten()
+            .find(|&pos| matches_at(pos))
+        else {
+            rejected.push(hunk);
+            continue;
+        };
+        for line in &lines[copied..pos] {
+            new_content.extend_from_slice(line);
+        }
+        for line in hunk.new_lines() {
+            new_content.extend_from_slice(line);
+        }
+        copied = pos + old_lines.len();
+        offset = pos as isize - hunk.old_start.saturating_sub(1) as isize;
+    }
+    for line in &lines[copied..] {
+        new_content.extend_from_slice(line);
+    }
+    AppliedHunks {
+        content: new_content,
+        rejected,
+    }
+}
+
+/// Result of applying a patch to a tree.
+#[derive(Clone, Debug)]
+pub struct AppliedPatch {
+    /// Tree with the changes applied.
+    pub tree: MergedTree,
+    /// Paths where the changes couldn't be applied cleanly, and which were
+    /// recorded as conflicts.
+    pub conflicted_paths: Vec<RepoPathBuf>,
+    /// Paths with changes that can't be applied at all, such as binary
+    /// changes.
+    pub skipped_paths: Vec<RepoPathBuf>,
+}
+
+/// Applies the file changes to the `tree`.
+///
+/// Changes that don't apply cleanly are recorded as conflicts between the
+/// current file content and the change from the old to the new lines of the
+/// rejected hunks.
+pub async fn apply_patch(tree: &MergedTree, files: &[FilePatch]) -> BackendResult<AppliedPatch> {
+    let store = tree.store();
+    let mut tree_builder = MergedTreeBuilder::new(tree.clone());
+    let mut conflicted_paths = vec![];
+    let mut skipped_paths = vec![];
+    for file in files {
+        let Some(path) = file.new_path.as_ref().or(file.old_path.as_ref()) else {
+            continue;
+        };
+        if file.is_binary || file.new_mode == Some(PatchFileMode::GitSubmodule) {
+            skipped_paths.push(path.clone());
+            continue;
+        }
+        let current_path = file.old_path.as_ref().unwrap_or(path);
+        let current = tree.path_value_async(current_path).await?;
+        let (applied, rejected_hunks): (MergedTreeValue, Vec<PatchHunk>) =
+            match (current.as_resolved(), &file.old_path, &file.new_path) {
+                // Adding a file which doesn't exist yet
+                (Some(None), None, Some(_)) => {
+                    let content = hunk_text(&file.hunks, PatchHunk::new_lines);
+                    let value = new_tree_value(store, path, &content, file.new_mode).await?;
+                    (Merge::normal(value), vec![])
+                }
+                (
+                    Some(Some(value @ (TreeValue::File { .. } | TreeValue::Symlink(_)))),
+                    Some(_),
+                    new_path,
+                ) => {
+                    let content = read_value(store, current_path, value).await?;
+                    let applied_hunks = apply_hunks(&content, &file.hunks);
+                    if new_path.is_none() {
+                        // The whole content should be removed by the hunks
+                        if applied_hunks.rejected.is_empty() && applied_hunks.content.is_empty() {
+                            (Merge::absent(), vec![])
+                        } else {
+                            (current.clone(), file.hunks.clone())
+                        }
+                    } else {
+                        let new_value =
+                            write_value(store, path, &applied_hunks.content, value, file.new_mode)
+                                .await?;
+                        let rejected = applied_hunks.rejected.into_iter().cloned().collect();
+                        (Merge::normal(new_value), rejected)
+                    }
+                }
+                _ => (current.clone(), file.hunks.clone()),
+            };
+        if let Some(old_path) = &file.old_path
+            && old_path != path
+            && !file.is_copy
+        {
+            tree_builder.set_or_remove(old_path.clone(), Merge::absent());
+        }
+        if rejected_hunks.is_empty() {
+            tree_builder.set_or_remove(path.clone(), applied);
+            continue;
+        }
+        // Record the change as a conflict. The removed and added terms only
+        // contain the rejected hunks, not the whole file.
+        let base = match &file.old_path {
+            Some(_) => {
+                let content = hunk_text(&rejected_hunks, PatchHunk::old_lines);
+                Some(new_tree_value(store, path, &content, None).await?)
+            }
+            None => None,
+        };
+        let new = match &file.new_path {
+            Some(_) => {
+                let content = hunk_text(&rejected_hunks, PatchHunk::new_lines);
+                Some(new_tree_value(store, path, &content, file.new_mode).await?)
+            }
+            None => None,
+        };
+        let conflict = Merge::from_removes_adds(
+            applied.removes().cloned().chain([base]),
+            applied.adds().cloned().chain([new]),
+        );
+        tree_builder.set_or_remove(path.clone(), conflict);
+        conflicted_paths.push(path.clone());
+    }
+    let tree = tree_builder.write_tree().await?;
+    Ok(AppliedPatch {
+        tree,
+        conflicted_paths,
+        skipped_paths,
+    })
+}
+
+/// Concatenates the lines selected from the hunks.
+fn hunk_text<'a, I>(hunks: &'a [PatchHunk], select: impl Fn(&'a PatchHunk) -> I) -> BString
+where
+    I: Iterator<Item = &'a BStr>,
+{
+    hunks
+        .iter()
+        .flat_map(select)
+        .flat_map(|line| line.iter().copied())
+        .collect()
+}
+
+async fn read_value(store: &Store, path: &RepoPath, value: &TreeValue) -> BackendResult<BString> {
+    match value {
+        TreeValue::File { id, .. } => {
+            let mut reader = store.read_file(path, id).await?;
+            let mut content = vec![];
+            reader
+                .read_to_end(&mut content)
+                .await
+                .map_err(|err| BackendError::ReadFile {
+                    path: path.to_owned(),
+                    id: id.clone(),
+                    source: err.into(),
+                })?;
+            Ok(content.into())
+        }
+        TreeValue::Symlink(id) => Ok(store.read_symlink(path, id).await?.into()),
+        _ => panic!("unexpected tree value at {path:?}"),
+    }
+}
+
+/// Writes the `content` as a new value of the same type as `current_value`,
+/// unless the `mode` says otherwise.
+async fn write_value(
+    store: &Store,
+    path: &RepoPath,
+    content: &[u8],
+    current_value: &TreeValue,
+    mode: Option<PatchFileMode>,
+) -> BackendResult<TreeValue> {
+    let mode = mode.unwrap_or(match current_value {
+        TreeValue::File {
+            executable: true, ..
+        } => PatchFileMode::Executable,
+        TreeValue::Symlink(_) => PatchFileMode::Symlink,
+        _ => PatchFileMode::Normal,
+    });
+    let mut value = new_tree_value(store, path, content, Some(mode)).await?;
+    if let (
+        TreeValue::File { copy_id, .. },
+        TreeValue::File {
+            copy_id: current_copy_id,
+            ..
+        },
+    ) = (&mut value, current_value)
+    {
+        *copy_id = current_copy_id.clone();
+    }
+    Ok(value)
+}
+
+async fn new_tree_value(
+    store: &Store,
+    path: &RepoPath,
+    content: &[u8],
+    mode: Option<PatchFileMode>,
+) -> BackendResult<TreeValue> {
+    match mode {
+        Some(PatchFileMode::Symlink) => {
+            let target = content.to_str_lossy();
+            let id = store.write_symlink(path, &target).await?;
+            Ok(TreeValue::Symlink(id))
+        }
+        _ => {
+            let id = store.write_file(path, &mut &content[..]).await?;
+            Ok(TreeValue::File {
+                id,
+                executable: mode == Some(PatchFileMode::Executable),
+                copy_id: CopyId::placeholder(),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    fn repo_path(value: &str) -> RepoPathBuf {
+        RepoPathBuf::from_internal_string(value).unwrap()
+    }
+
+    fn hunk(old_start: usize, lines: &[(DiffLineType, &str)]) -> PatchHunk {
+        PatchHunk {
+            old_start,
+            lines: lines
+                .iter()
+                .map(|(line_type, line)| (*line_type, BString::from(*line)))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_parse_mailbox() {
+        let mailbox = indoc! {r#"
+            From 0123456789abcdef0123456789abcdef01234567 Mon Sep 17 00:00:00 2001
+            From: =?UTF-8?q?J=C3=B6rg?= Doe <jorg@example.com>
+            Date: Tue, 2 Jan 2001 03:04:05 +0600
+            Subject: [PATCH 1/2] Fix the
+             frobnicator
+
+            Some details.
+            >From here on.
+            >>From there.
+            ---
+             file | 2 +-
+             1 file changed, 1 insertion(+), 1 deletion(-)
+
+            diff --git a/file b/file
+            index 1111111..2222222 100644
+            --- a/file
+            +++ b/file
+            @@ -1,2 +1,2 @@
+             a
+            -b
+            +c
+            \ No newline at end of file
+            From 0123456789abcdef0123456789abcdef01234567 Mon Sep 17 00:00:00 2001
+            From: "Doe, \"Jane\"" <jane@example.com>
+            Subject: [PATCH 2/2] Move file
+
+            diff --git a/file b/dir/new
+            similarity index 100%
+            rename from file
+            rename to dir/new
+            diff --git a/exec b/exec
+            old mode 100644
+            new mode 100755
+            diff --git a/gone b/gone
+            deleted file mode 100644
+            index 3333333..0000000
+            --- a/gone
+            +++ /dev/null
+            @@ -1 +0,0 @@
+            -gone
+        "#};
+        let messages = parse_mailbox(mailbox.as_bytes()).unwrap();
+        assert_eq!(messages.len(), 2);
+
+        assert_eq!(messages[0].author_name, "Jörg Doe");
+        assert_eq!(messages[0].author_email, "jorg@example.com");
+        assert_eq!(
+            messages[0].author_timestamp,
+            Some(Timestamp {
+                timestamp: crate::backend::MillisSinceEpoch(978_383_045_000),
+                tz_offset: 360,
+            })
+        );
+        assert_eq!(
+            messages[0].description,
+            "Fix the frobnicator\n\nSome details.\nFrom here on.\n>From there.\n"
+        );
+        assert_eq!(
+            messages[0].files,
+            vec![FilePatch {
+                old_path: Some(repo_path("file")),
+                new_path: Some(repo_path("file")),
+                new_mode: None,
+                is_copy: false,
+                is_binary: false,
+                hunks: vec![hunk(
+                    1,
+                    &[
+                        (DiffLineType::Context, "a\n"),
+                        (DiffLineType::Removed, "b\n"),
+                        (DiffLineType::Added, "c"),
+                    ]
+                )],
+            }]
+        );
+
+        assert_eq!(messages[1].author_name, r#"Doe, "Jane""#);
+        assert_eq!(messages[1].author_timestamp, None);
+        assert_eq!(messages[1].description, "Move file\n");
+        let files = &messages[1].files;
+        assert_eq!(files.len(), 3);
+        assert_eq!(files[0].old_path, Some(repo_path("file")));
+        assert_eq!(files[0].new_path, Some(repo_path("dir/new")));
+        assert!(files[0].hunks.is_empty());
+        assert_eq!(files[1].new_mode, Some(PatchFileMode::Executable));
+        assert_eq!(files[2].old_path, Some(repo_path("gone")));
+        assert_eq!(files[2].new_path, None);
+        assert_eq!(files[2].hunks.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_mailbox_errors() {
+        let mailbox = indoc! {"
+            From: Jane <jane@example.com>
+            Subject: Bad hunk
+
+            diff --git a/file b/file
+            --- a/file
+            +++ b/file
+            @@ -1,2 +1,2 @@
+             a
+        "};
+        insta::assert_snapshot!(
+            parse_mailbox(mailbox.as_bytes()).unwrap_err(),
+            @"Malformed patch at line 8: Unexpected end of hunk");
+
+        let mailbox = indoc! {"
+            Subject: No author
+        "};
+        insta::assert_snapshot!(
+            parse_mailbox(mailbox.as_bytes()).unwrap_err(),
+            @"Malformed patch at line 1: Missing From header");
+    }
+
+    #[test]
+    fn test_parse_header_paths_with_spaces() {
+        let parser = LineParser { lines: &[], pos: 0 };
+        assert_eq!(
+            parser.parse_git_header_paths(b"a/b c/d b/b c/d\n"),
+            Some((Some(repo_path("b c/d")), Some(repo_path("b c/d"))))
+        );
+        assert_eq!(
+            parser.parse_git_header_paths(br#""a/x\"y" "b/x\303\266""#),
+            Some((Some(repo_path("x\"y")), Some(repo_path("xö"))))
+        );
+    }
+
+    #[test]
+    fn test_strip_subject_prefixes() {
+        assert_eq!(strip_subject_prefixes("[PATCH] foo"), "foo");
+        assert_eq!(strip_subject_prefixes("Re: [PATCH v2 3/4] foo "), "foo");
+        assert_eq!(strip_subject_prefixes("foo [bar]"), "foo [bar]");
+    }
+
+    #[test]
+    fn test_apply_hunks() {
+        use DiffLineType::*;
+        let hunks = [
+            hunk(2, &[(Context, "b\n"), (Removed, "c\n"), (Added, "C\n")]),
+            hunk(5, &[(Context, "e\n"), (Added, "E\n")]),
+        ];
+
+        // Applies at the recorded positions
+        let applied = apply_hunks(b"a\nb\nc\nd\ne\n", &hunks);
+        assert_eq!(applied.content, "a\nb\nC\nd\ne\nE\n");
+        assert!(applied.rejected.is_empty());
+
+        // Applies with offsets
+        let applied = apply_hunks(b"0\n1\na\nb\nc\nd\ne\n", &hunks);
+        assert_eq!(applied.content, "0\n1\na\nb\nC\nd\ne\nE\n");
+        assert!(applied.rejected.is_empty());
+
+        // Rejects hunks which don't match
+        let applied = apply_hunks(b"a\nb\nx\nd\ne\n", &hunks);
+        assert_eq!(applied.content, "a\nb\nx\nd\ne\nE\n");
+        assert_eq!(applied.rejected, vec![&hunks[0]]);
+
+        // Adds to empty content
+        let hunks = [hunk(0, &[(Added, "new\n")])];
+        let applied = apply_hunks(b"", &hunks);
+        assert_eq!(applied.content, "new\n");
+    }
+}
@@ -12,6 +12,11 @@ pub struct Commit {
     /// TODO(#1624): delete when we assume that all commits use this format
     #[prost(bool, tag = "10")]
     pub uses_tree_conflict_format: bool,
+    /// Root trees with the hashes of their copy ids appended, set only if some
+    /// tree contains copy ids. They are stored separately from root_tree, which
+    /// jj versions without copy tracking expect to contain Git tree ids.
+    #[prost(bytes = "vec", repeated, tag = "11")]
+    pub root_tree_with_copies: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
     #[deprecated]
     #[prost(bool, tag = "8")]
     pub is_open: bool,
@@ -19,3 +24,40 @@ pub struct Commit {
     #[prost(bool, tag = "9")]
     pub is_pruned: bool,
 }
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct CopyHistory {
+    #[prost(string, tag = "1")]
+    pub current_path: ::prost::alloc::string::String,
+    #[prost(bytes = "vec", repeated, tag = "2")]
+    pub parents: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub salt: ::prost::alloc::vec::Vec<u8>,
+}
+/// Copy ids of the entries in a Git tree, which can't be stored in the tree
+/// object itself. The record is keyed by its hash, which is appended to the
+/// Git tree id.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TreeCopies {
+    #[prost(message, repeated, tag = "1")]
+    pub entries: ::prost::alloc::vec::Vec<tree_copies::Entry>,
+    /// Subtrees which contain copy ids, with their extended tree ids.
+    #[prost(message, repeated, tag = "2")]
+    pub subtrees: ::prost::alloc::vec::Vec<tree_copies::Subtree>,
+}
+/// Nested message and enum types in `TreeCopies`.
+pub mod tree_copies {
+    #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+    pub struct Entry {
+        #[prost(string, tag = "1")]
+        pub name: ::prost::alloc::string::String,
+        #[prost(bytes = "vec", tag = "2")]
+        pub copy_id: ::prost::alloc::vec::Vec<u8>,
+    }
+    #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+    pub struct Subtree {
+        #[prost(string, tag = "1")]
+        pub name: ::prost::alloc::string::String,
+        #[prost(bytes = "vec", tag = "2")]
+        pub tree_id: ::prost::alloc::vec::Vec<u8>,
+    }
+}
@@ -62,6 +62,7 @@ use crate::index::ResolvedChangeTargets;
 use crate::merge::MergeBuilder;
 use crate::merge::SameChange;
 use crate::merge::trivial_merge;
+use crate::merge_driver::ExternalMergeDriver;
 use crate::merged_tree::MergedTree;
 use crate::object_id::HexPrefix;
 use crate::object_id::PrefixResolution;
@@ -90,6 +91,7 @@ use crate::refs::diff_named_ref_targets;
 use crate::refs::diff_named_remote_refs;
 use crate::refs::merge_ref_targets;
 use crate::refs::merge_remote_refs;
+use crate::rerere::ResolutionStore;
 use crate::revset;
 use crate::revset::RevsetEvaluationError;
 use crate::revset::RevsetExpression;
@@ -196,7 +198,11 @@ impl ReadonlyRepo {
     }
 
     pub fn default_submodule_store_initializer() -> &'static SubmoduleStoreInitializer<'static> {
-        &|_settings, store_path| Ok(Box::new(DefaultSubmoduleStore::init(store_path)))
+        &|settings, store_path| {
+            let store = DefaultSubmoduleStore::init(settings, store_path)
+                .map_err(|err| BackendInitError(err.into()))?;
+            Ok(Box::new(store))
+        }
     }
 
     #[expect(clippy::too_many_arguments)]
@@ -219,7 +225,17 @@ impl ReadonlyRepo {
         fs::write(&backend_path, backend.name()).context(&backend_path)?;
         let merge_options =
             MergeOptions::from_settings(settings).map_err(|err| BackendInitError(err.into()))?;
-        let store = Store::new(backend, signer, merge_options);
+        let resolution_store = ResolutionStore::from_settings(settings, &repo_path)
+            .map_err(|err| BackendInitError(err.into()))?;
+        let merge_drivers = ExternalMergeDriver::table_from_settings(settings)
+            .map_err(|err| BackendInitError(err.into()))?;
+        let store = Store::new(
+            backend,
+            signer,
+            merge_options,
+            merge_drivers,
+            resolution_store,
+        );
 
         let op_store_path = repo_path.join("op_store");
         fs::create_dir(&op_store_path).context(&op_store_path)?;
@@ -477,7 +493,11 @@ impl Default for StoreFactories {
         // SubmoduleStores
         factories.add_submodule_store(
             DefaultSubmoduleStore::name(),
-            Box::new(|_settings, store_path| Ok(Box::new(DefaultSubmoduleStore::load(store_path)))),
+            Box::new(|settings, store_path| {
+                let store = DefaultSubmoduleStore::load(settings, store_path)
+                    .map_err(|err| BackendLoadError(err.into()))?;
+                Ok(Box::new(store))
+            }),
         );
 
         factories
@@ -704,10 +724,16 @@ impl RepoLoader {
     ) -> Result<Self, StoreLoadError> {
         let merge_options =
             MergeOptions::from_settings(settings).map_err(|err| BackendLoadError(err.into()))?;
+        let merge_drivers = ExternalMergeDriver::table_from_settings(settings)
+            .map_err(|err| BackendLoadError(err.into()))?;
+        let resolution_store = ResolutionStore::from_settings(settings, repo_path)
+            .map_err(|err| BackendLoadError(err.into()))?;
         let store = Store::new(
             store_factories.load_backend(settings, &repo_path.join("store"))?,
             Signer::from_settings(settings)?,
             merge_options,
+            merge_drivers,
+            resolution_store,
         );
         let root_op_data = RootOperationData {
             root_commit_id: store.root_commit_id().clone(),
@@ -0,0 +1,517 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reuse of recorded conflict resolutions ("rerere").
+//!
+//! When a conflicted file is resolved, the resolution of each conflicting
+//! hunk is recorded, keyed by the content of the hunk's terms. When the same
+//! hunks conflict again, for example because a stack of commits was rebased
+//! again, the recorded resolutions are applied by the tree merge.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::io;
+use std::mem;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use bstr::BString;
+use bstr::ByteSlice as _;
+use thiserror::Error;
+use tokio::io::AsyncReadExt as _;
+
+use crate::backend::BackendError;
+use crate::backend::BackendResult;
+use crate::backend::TreeValue;
+use crate::config::ConfigGetError;
+use crate::content_hash::blake2b_hash;
+use crate::file_util::IoResultExt as _;
+use crate::file_util::PathError;
+use crate::files;
+use crate::files::MergeResult;
+use crate::hex_util;
+use crate::merge::Merge;
+use crate::merge::MergedTreeValue;
+use crate::merged_tree::MergedTree;
+use crate::object_id::ObjectId as _;
+use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathBuf;
+use crate::settings::UserSettings;
+use crate::store::Store;
+
+const PATH_FILE_NAME: &str = "path";
+const RESOLUTION_FILE_NAME: &str = "resolution";
+const TERM_FILE_PREFIX: &str = "term-";
+
+/// Error that may occur while recording or reading resolutions.
+#[derive(Debug, Error)]
+pub enum RerereError {
+    /// Failed to read file contents from the backend.
+    #[error(transparent)]
+    Backend(#[from] BackendError),
+    /// Failed to access the resolution store.
+    #[error(transparent)]
+    Io(#[from] PathError),
+    /// The recorded resolution is malformed.
+    #[error("Recorded resolution {id} is corrupt")]
+    Corrupt {
+        /// Id of the recorded resolution.
+        id: String,
+    },
+}
+
+/// Resolution of a conflicting hunk recorded in a [`ResolutionStore`].
+#[derive(Clone, Debug)]
+pub struct RecordedResolution {
+    /// Hex-encoded id, which is a hash of the conflicting hunk.
+    pub id: String,
+    /// Path of the file where the resolution was recorded.
+    pub path: RepoPathBuf,
+    /// Content of the conflicting hunk.
+    pub conflict: Merge<BString>,
+    /// Content which replaces the conflicting hunk.
+    pub resolution: BString,
+    /// When the resolution was recorded.
+    pub time: SystemTime,
+}
+
+/// Stores recorded resolutions of conflicting hunks on disk, one directory per
+/// resolution.
+#[derive(Debug)]
+pub struct ResolutionStore {
+    path: PathBuf,
+    /// Paths of the files resolved by [`ResolutionStore::resolve_hunks()`]
+    /// which haven't been reported yet.
+    resolved_paths: Mutex<BTreeSet<RepoPathBuf>>,
+}
+
+impl ResolutionStore {
+    /// Creates a store in `path`. The directory is created when the first
+    /// resolution is recorded.
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            resolved_paths: Mutex::new(BTreeSet::new()),
+        }
+    }
+
+    /// Returns the store of the repo at `repo_path`.
+    pub fn for_repo(repo_path: &Path) -> Self {
+        Self::new(repo_path.join("rerere"))
+    }
+
+    /// Returns the store of the repo at `repo_path` if reusing recorded
+    /// resolutions is enabled by `merge.rerere`.
+    pub fn from_settings(
+        settings: &UserSettings,
+        repo_path: &Path,
+    ) -> Result<Option<Self>, ConfigGetError> {
+        let enabled = settings.get_bool("merge.rerere")?;
+        Ok(enabled.then(|| Self::for_repo(repo_path)))
+    }
+
+    /// Returns the id of a conflicting hunk.
+    pub fn conflict_id(conflict: &Merge<BString>) -> String {
+        let terms = conflict.map(|term| term.to_vec());
+        hex_util::encode_hex(&blake2b_hash(&terms)[..20])
+    }
+
+    /// Records `resolution` as the resolution of the conflicting hunk.
+    /// Replaces the previously recorded resolution of the same hunk, if any.
+    pub fn record(
+        &self,
+        path: &RepoPath,
+        conflict: &Merge<BString>,
+        resolution: &[u8],
+    ) -> Result<String, RerereError> {
+        let id = Self::conflict_id(conflict);
+        fs::create_dir_all(&self.path).context(&self.path)?;
+        let temp_dir = tempfile::tempdir_in(&self.path).context(&self.path)?;
+        let write = |name: &str, content: &[u8]| {
+            let file_path = temp_dir.path().join(name);
+            fs::write(&file_path, content).context(&file_path)
+        };
+        write(PATH_FILE_NAME, path.as_internal_file_string().as_bytes())?;
+        for (i, term) in conflict.iter().enumerate() {
+            write(&format!("{TERM_FILE_PREFIX}{i}"), term)?;
+        }
+        write(RESOLUTION_FILE_NAME, resolution)?;
+        let entry_path = self.path.join(&id);
+        match fs::remove_dir_all(&entry_path) {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => {
+                return Err(PathError {
+                    path: entry_path,
+                    source: err,
+                }
+                .into());
+            }
+        }
+        fs::rename(temp_dir.keep(), &entry_path).context(&entry_path)?;
+        Ok(id)
+    }
+
+    /// Returns the recorded resolution of the conflicting hunk, if any.
+    pub fn lookup(&self, conflict: &Merge<BString>) -> Result<Option<BString>, RerereError> {
+        let id = Self::conflict_id(conflict);
+        let resolution_path = self.path.join(&id).join(RESOLUTION_FILE_NAME);
+        match fs::read(&resolution_path) {
+            Ok(content) => Ok(Some(content.into())),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(PathError {
+                path: resolution_path,
+                source: err,
+            }
+            .into()),
+        }
+    }
+
+    /// Resolves the conflicting `hunks` of the file at `path` by using the
+    /// recorded resolutions. Returns `None` unless all of them have been
+    /// recorded. The path is remembered so it can be reported later by
+    /// [`ResolutionStore::take_resolved_paths()`].
+    pub fn resolve_hunks(
+        &self,
+        path: &RepoPath,
+        hunks: &[Merge<BString>],
+    ) -> Result<Option<BString>, RerereError> {
+        let mut content = BString::default();
+        for hunk in hunks {
+            if let Some(resolved) = hunk.as_resolved() {
+                content.extend_from_slice(resolved);
+            } else if let Some(resolution) = self.lookup(hunk)? {
+                content.extend_from_slice(&resolution);
+            } else {
+                return Ok(None);
+            }
+        }
+        self.resolved_paths.lock().unwrap().insert(path.to_owned());
+        Ok(Some(content))
+    }
+
+    /// Returns the paths of the files resolved by using the recorded
+    /// resolutions since the last call.
+    pub fn take_resolved_paths(&self) -> BTreeSet<RepoPathBuf> {
+        mem::take(&mut *self.resolved_paths.lock().unwrap())
+    }
+
+    /// Lists all recorded resolutions, most recent first.
+    pub fn list(&self) -> Result<Vec<RecordedResolution>, RerereError> {
+        let entries = match fs::read_dir(&self.path) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(err) => {
+                return Err(PathError {
+                    path: self.path.clone(),
+                    source: err,
+                }
+                .into());
+            }
+        };
+        let mut resolutions = vec![];
+        for entry in entries {
+            let entry = entry.context(&self.path)?;
+            let Some(id) = entry.file_name().to_str().map(str::to_owned) else {
+                continue;
+            };
+            // Skip temporary directories of concurrent writers
+            if hex_util::decode_hex(&id).is_none() {
+                continue;
+            }
+            resolutions.push(self.read_entry(&entry.path(), id)?);
+        }
+        resolutions.sort_by(|a, b| b.time.cmp(&a.time).then_with(|| a.id.cmp(&b.id)));
+        Ok(resolutions)
+    }
+
+    fn read_entry(&self, entry_path: &Path, id: String) -> Result<RecordedResolution, RerereError> {
+        let read = |name: &str| {
+            let file_path = entry_path.join(name);
+            fs::read(&file_path).context(&file_path)
+        };
+        let corrupt = || RerereError::Corrupt { id: id.clone() };
+        let path = String::from_utf8(read(PATH_FILE_NAME)?).map_err(|_| corrupt())?;
+        let path = RepoPathBuf::from_internal_string(path).map_err(|_| corrupt())?;
+        let mut terms = vec![];
+        loop {
+            let term_path = entry_path.join(format!("{TERM_FILE_PREFIX}{}", terms.len()));
+            match fs::read(&term_path) {
+                Ok(content) => terms.push(BString::from(content)),
+                Err(err) if err.kind() == io::ErrorKind::NotFound => break,
+                Err(err) => {
+                    return Err(PathError {
+                        path: term_path,
+                        source: err,
+                    }
+                    .into());
+                }
+            }
+        }
+        if terms.len() % 2 == 0 {
+            return Err(corrupt());
+        }
+        let resolution_path = entry_path.join(RESOLUTION_FILE_NAME);
+        let resolution = fs::read(&resolution_path).context(&resolution_path)?;
+        let time = fs::metadata(&resolution_path)
+            .and_then(|metadata| metadata.modified())
+            .context(&resolution_path)?;
+        Ok(RecordedResolution {
+            id,
+            path,
+            conflict: Merge::from_vec(terms),
+            resolution: resolution.into(),
+            time,
+        })
+    }
+
+    /// Forgets the recorded resolution with the given id. Returns whether it
+    /// existed.
+    pub fn forget(&self, id: &str) -> Result<bool, RerereError> {
+        let entry_path = self.path.join(id);
+        match fs::remove_dir_all(&entry_path) {
+            Ok(()) => Ok(true),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(err) => Err(PathError {
+                path: entry_path,
+                source: err,
+            }
+            .into()),
+        }
+    }
+}
+
+/// Reads the contents of a file conflict, after simplifying it the same way as
+/// the tree merge does. Returns `None` if some terms aren't files.
+pub(crate) async fn read_file_conflict(
+    store: &Store,
+    path: &RepoPath,
+    value: &MergedTreeValue,
+) -> BackendResult<Option<Merge<BString>>> {
+    let Ok(file_ids) = value.simplify().try_map(|term| match term {
+        Some(TreeValue::File { id, .. }) => Ok(id.clone()),
+        _ => Err(()),
+    }) else {
+        return Ok(None);
+    };
+    let contents = file_ids
+        .simplify()
+        .try_map_async(async |file_id| {
+            let mut content = vec![];
+            let mut reader = store.read_file(path, file_id).await?;
+            reader
+                .read_to_end(&mut content)
+                .await
+                .map_err(|err| BackendError::ReadObject {
+                    object_type: file_id.object_type(),
+                    hash: file_id.hex(),
+                    source: err.into(),
+                })?;
+            BackendResult::Ok(BString::from(content))
+        })
+        .await?;
+    Ok(Some(contents))
+}
+
+/// Records the resolutions of the file conflicts in `old_tree` which are
+/// resolved in `new_tree`. Returns the number of recorded hunk resolutions.
+pub async fn record_resolutions(
+    resolutions: &ResolutionStore,
+    old_tree: &MergedTree,
+    new_tree: &MergedTree,
+) -> Result<usize, RerereError> {
+    let store = old_tree.store();
+    let mut count = 0;
+    for (path, value) in old_tree.conflicts() {
+        let Ok(Some(TreeValue::File { id, .. })) =
+            new_tree.path_value_async(&path).await?.into_resolved()
+        else {
+            continue;
+        };
+        let Some(contents) = read_file_conflict(store, &path, &value?).await? else {
+            continue;
+        };
+        let MergeResult::Conflict(hunks) = files::merge_hunks(&contents, store.merge_options())
+        else {
+            continue;
+        };
+        let mut resolved_content = vec![];
+        let mut reader = store.read_file(&path, &id).await?;
+        reader
+            .read_to_end(&mut resolved_content)
+            .await
+            .map_err(|err| BackendError::ReadObject {
+                object_type: id.object_type(),
+                hash: id.hex(),
+                source: err.into(),
+            })?;
+        for (hunk, resolution) in match_hunk_resolutions(&hunks, &resolved_content) {
+            resolutions.record(&path, hunk, resolution)?;
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// Finds the resolution of each conflicting hunk in `resolved_content`, using
+/// the resolved hunks around it as anchors. Conflicting hunks whose resolution
+/// can't be determined unambiguously are skipped.
+fn match_hunk_resolutions<'a>(
+    hunks: &'a [Merge<BString>],
+    resolved_content: &'a [u8],
+) -> Vec<(&'a Merge<BString>, &'a [u8])> {
+    let mut matches = vec![];
+    let mut pos = 0;
+    // Conflicting hunk which ends where the next anchor starts
+    let mut pending: Option<&Merge<BString>> = None;
+    for (i, hunk) in hunks.iter().enumerate() {
+        let Some(anchor) = hunk.as_resolved() else {
+            // Adjacent conflicting hunks can't be told apart
+            if pending.is_some() {
+                return matches;
+            }
+            pending = Some(hunk);
+            continue;
+        };
+        let rest = &resolved_content[pos..];
+        let offset = if pending.is_none() {
+            rest.starts_with(anchor).then_some(0)
+        } else if i == hunks.len() - 1 {
+            rest.ends_with(anchor).then(|| rest.len() - anchor.len())
+        } else {
+            rest.find(anchor)
+        };
+        let Some(offset) = offset else {
+            return matches;
+        };
+        if let Some(conflict) = pending.take() {
+            matches.push((conflict, &rest[..offset]));
+        }
+        pos += offset + anchor.len();
+    }
+    if let Some(conflict) = pending {
+        matches.push((conflict, &resolved_content[pos..]));
+    }
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conflict(terms: &[&str]) -> Merge<BString> {
+        Merge::from_vec(
+            terms
+                .iter()
+                .map(|&term| BString::from(term))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    fn resolved(text: &str) -> Merge<BString> {
+        Merge::resolved(BString::from(text))
+    }
+
+    #[test]
+    fn test_match_hunk_resolutions() {
+        let hunks = [
+            resolved("a\n"),
+            conflict(&["b1\n", "b\n", "b2\n"]),
+            resolved("c\n"),
+            conflict(&["d1\n", "d\n", "d2\n"]),
+        ];
+        let matches = match_hunk_resolutions(&hunks, b"a\nb1\nb2\nc\nd\n");
+        assert_eq!(
+            matches,
+            [
+                (&hunks[1], b"b1\nb2\n".as_slice()),
+                (&hunks[3], b"d\n".as_slice())
+            ]
+        );
+
+        // The resolution may remove all lines
+        let matches = match_hunk_resolutions(&hunks, b"a\nc\n");
+        assert_eq!(
+            matches,
+            [(&hunks[1], b"".as_slice()), (&hunks[3], b"".as_slice())]
+        );
+
+        // Anchors must be kept as is
+        let matches = match_hunk_resolutions(&hunks, b"x\nb\nc\nd\n");
+        assert_eq!(matches, []);
+        let matches = match_hunk_resolutions(&hunks, b"a\nb\nd\n");
+        assert_eq!(matches, []);
+
+        // The last anchor is matched at the end
+        let hunks = [conflict(&["b1\n", "b\n", "b2\n"]), resolved("c\n")];
+        let matches = match_hunk_resolutions(&hunks, b"c\nb\nc\n");
+        assert_eq!(matches, [(&hunks[0], b"c\nb\n".as_slice())]);
+
+        // Adjacent conflicting hunks are ambiguous
+        let hunks = [
+            conflict(&["b1\n", "b\n", "b2\n"]),
+            conflict(&["c1\n", "c\n", "c2\n"]),
+        ];
+        let matches = match_hunk_resolutions(&hunks, b"b\nc\n");
+        assert_eq!(matches, []);
+    }
+
+    #[test]
+    fn test_resolution_store() {
+        let temp_dir = testutils::new_temp_dir();
+        let store = ResolutionStore::new(temp_dir.path().join("rerere"));
+        let path = RepoPath::from_internal_string("dir/file").unwrap();
+        let hunk = conflict(&["b1\n", "b\n", "b2\n"]);
+        let other_hunk = conflict(&["b2\n", "b\n", "b1\n"]);
+        assert!(store.list().unwrap().is_empty());
+        assert_eq!(store.lookup(&hunk).unwrap(), None);
+
+        let id = store.record(path, &hunk, b"b12\n").unwrap();
+        assert_eq!(id, ResolutionStore::conflict_id(&hunk));
+        assert_ne!(id, ResolutionStore::conflict_id(&other_hunk));
+        assert_eq!(store.lookup(&hunk).unwrap(), Some("b12\n".into()));
+        assert_eq!(store.lookup(&other_hunk).unwrap(), None);
+
+        // Recording again replaces the resolution
+        store.record(path, &hunk, b"b21\n").unwrap();
+        assert_eq!(store.lookup(&hunk).unwrap(), Some("b21\n".into()));
+        let resolutions = store.list().unwrap();
+        assert_eq!(resolutions.len(), 1);
+        assert_eq!(resolutions[0].id, id);
+        assert_eq!(resolutions[0].path, path.to_owned());
+        assert_eq!(resolutions[0].conflict, hunk);
+        assert_eq!(resolutions[0].resolution, "b21\n");
+
+        assert!(store.take_resolved_paths().is_empty());
+        let hunks = [resolved("a\n"), hunk.clone(), resolved("c\n")];
+        assert_eq!(
+            store.resolve_hunks(path, &hunks).unwrap(),
+            Some("a\nb21\nc\n".into())
+        );
+        let hunks = [resolved("a\n"), hunk, other_hunk];
+        let other_path = RepoPath::from_internal_string("other").unwrap();
+        assert_eq!(store.resolve_hunks(other_path, &hunks).unwrap(), None);
+        assert_eq!(
+            store.take_resolved_paths().into_iter().collect::<Vec<_>>(),
+            [path.to_owned()]
+        );
+        assert!(store.take_resolved_paths().is_empty());
+
+        assert!(store.forget(&id).unwrap());
+        assert!(!store.forget(&id).unwrap());
+        assert!(store.list().unwrap().is_empty());
+    }
+}
@@ -16,7 +16,6 @@
 
 use std::path::Path;
 use std::pin::Pin;
-use std::time::SystemTime;
 
 use async_trait::async_trait;
 use futures::stream::BoxStream;
@@ -37,6 +36,8 @@ use crate::backend::SigningFn;
 use crate::backend::SymlinkId;
 use crate::backend::Tree;
 use crate::backend::TreeId;
+use crate::gc::GcOptions;
+use crate::gc::GcStats;
 use crate::git_backend::GitBackend;
 use crate::index::Index;
 use crate::object_id::ObjectId as _;
@@ -203,7 +204,7 @@ impl Backend for SecretBackend {
         self.inner.get_copy_records(paths, root, head)
     }
 
-    fn gc(&self, index: &dyn Index, keep_newer: SystemTime) -> BackendResult<()> {
-        self.inner.gc(index, keep_newer)
+    fn gc(&self, index: &dyn Index, options: &GcOptions) -> BackendResult<GcStats> {
+        self.inner.gc(index, options)
     }
 }
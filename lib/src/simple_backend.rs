@@ -14,15 +14,26 @@
 
 #![expect(missing_docs)]
 
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::VecDeque;
 use std::fmt::Debug;
 use std::fs;
 use std::fs::File;
+use std::io;
+use std::io::BufWriter;
 use std::io::Cursor;
 use std::io::Read as _;
+use std::io::Seek as _;
+use std::io::SeekFrom;
 use std::io::Write as _;
+use std::mem;
 use std::path::Path;
 use std::path::PathBuf;
 use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::Mutex;
 use std::time::SystemTime;
 
 use async_trait::async_trait;
@@ -30,6 +41,7 @@ use blake2::Blake2b512;
 use blake2::Digest as _;
 use futures::stream;
 use futures::stream::BoxStream;
+use itertools::Itertools as _;
 use pollster::FutureExt as _;
 use prost::Message as _;
 use tempfile::NamedTempFile;
@@ -58,28 +70,84 @@ use crate::backend::TreeValue;
 use crate::backend::make_root_commit;
 use crate::conflict_labels::ConflictLabels;
 use crate::content_hash::blake2b_hash;
+use crate::copies::related_copy_histories;
 use crate::file_util::persist_content_addressed_temp_file;
+use crate::gc::GcOptions;
+use crate::gc::GcStats;
+use crate::hex_util;
 use crate::index::Index;
 use crate::merge::MergeBuilder;
-use crate::object_id::ObjectId;
+use crate::object_id::ObjectId as _;
 use crate::repo_path::RepoPath;
 use crate::repo_path::RepoPathBuf;
 use crate::repo_path::RepoPathComponentBuf;
 
 const COMMIT_ID_LENGTH: usize = 64;
 const CHANGE_ID_LENGTH: usize = 16;
+/// Length of the ids of all stored objects, which are BLAKE2b hashes.
+const OBJECT_ID_LENGTH: usize = 64;
+
+const PACK_DIR: &str = "packs";
+const PACK_MAGIC: &[u8; 8] = b"JJPACK\x00\x01";
+const PACK_INDEX_ENTRY_SIZE: usize = 1 + OBJECT_ID_LENGTH + 8 + 8;
+const PACK_FOOTER_SIZE: usize = 8 + 8;
+
+/// Kind of the objects stored by [`SimpleBackend`].
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+enum ObjectKind {
+    Commit = 1,
+    Tree = 2,
+    File = 3,
+    Symlink = 4,
+    Copy = 5,
+}
+
+impl ObjectKind {
+    const ALL: [Self; 5] = [
+        Self::Commit,
+        Self::Tree,
+        Self::File,
+        Self::Symlink,
+        Self::Copy,
+    ];
+
+    fn from_byte(byte: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| *kind as u8 == byte)
+    }
+
+    /// Name of the directory containing the loose objects of this kind.
+    fn dir_name(self) -> &'static str {
+        match self {
+            Self::Commit => "commits",
+            Self::Tree => "trees",
+            Self::File => "files",
+            Self::Symlink => "symlinks",
+            Self::Copy => "copies",
+        }
+    }
 
-fn map_not_found_err(err: std::io::Error, id: &impl ObjectId) -> BackendError {
-    if err.kind() == std::io::ErrorKind::NotFound {
+    fn object_type(self) -> &'static str {
+        match self {
+            Self::Commit => "commit",
+            Self::Tree => "tree",
+            Self::File => "file",
+            Self::Symlink => "symlink",
+            Self::Copy => "copy",
+        }
+    }
+}
+
+fn map_not_found_err(err: io::Error, kind: ObjectKind, id: &[u8]) -> BackendError {
+    if err.kind() == io::ErrorKind::NotFound {
         BackendError::ObjectNotFound {
-            object_type: id.object_type(),
-            hash: id.hex(),
+            object_type: kind.object_type().to_owned(),
+            hash: hex_util::encode_hex(id),
             source: Box::new(err),
         }
     } else {
         BackendError::ReadObject {
-            object_type: id.object_type(),
-            hash: id.hex(),
+            object_type: kind.object_type().to_owned(),
+            hash: hex_util::encode_hex(id),
             source: Box::new(err),
         }
     }
@@ -89,12 +157,177 @@ fn to_other_err(err: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Bac
     BackendError::Other(err.into())
 }
 
+/// Objects packed into a single file by [`SimpleBackend`]'s garbage
+/// collection.
+///
+/// A pack file starts with [`PACK_MAGIC`] followed by the contents of the
+/// objects. The contents are followed by an index of `(kind, id, offset,
+/// length)` entries sorted by kind and id, and a footer of the index offset
+/// and the number of entries. Integers are stored in little endian.
+#[derive(Debug)]
+struct PackFile {
+    path: PathBuf,
+    mtime: SystemTime,
+    file: Mutex<File>,
+    /// Index entries as stored in the file, sorted by kind and id.
+    index: Vec<u8>,
+}
+
+impl PackFile {
+    fn load(path: PathBuf) -> io::Result<Self> {
+        let invalid_data = |message: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {message}", path.display()),
+            )
+        };
+        let mut file = File::open(&path)?;
+        let mtime = file.metadata()?.modified()?;
+        let mut magic = [0; PACK_MAGIC.len()];
+        file.read_exact(&mut magic)?;
+        if magic != *PACK_MAGIC {
+            return Err(invalid_data("not a pack file"));
+        }
+        let mut footer = [0; PACK_FOOTER_SIZE];
+        file.seek(SeekFrom::End(-(PACK_FOOTER_SIZE as i64)))?;
+        file.read_exact(&mut footer)?;
+        let (index_offset, num_entries) = footer.split_at(8);
+        let index_offset = u64::from_le_bytes(index_offset.try_into().unwrap());
+        let num_entries = u64::from_le_bytes(num_entries.try_into().unwrap());
+        let index_len = usize::try_from(num_entries)
+            .ok()
+            .and_then(|n| n.checked_mul(PACK_INDEX_ENTRY_SIZE))
+            .ok_or_else(|| invalid_data("corrupted index"))?;
+        let mut index = vec![0; index_len];
+        file.seek(SeekFrom::Start(index_offset))?;
+        file.read_exact(&mut index)?;
+        if index
+            .chunks_exact(PACK_INDEX_ENTRY_SIZE)
+            .any(|entry| ObjectKind::from_byte(entry[0]).is_none())
+        {
+            return Err(invalid_data("unknown kind"));
+        }
+        Ok(Self {
+            path,
+            mtime,
+            file: Mutex::new(file),
+            index,
+        })
+    }
+
+    fn index_entries(&self) -> &[[u8; PACK_INDEX_ENTRY_SIZE]] {
+        let (entries, _) = self.index.as_chunks();
+        entries
+    }
+
+    fn object_keys(&self) -> impl Iterator<Item = (ObjectKind, &[u8])> {
+        self.index_entries().iter().map(|entry| {
+            let (kind, id) = index_entry_key(entry);
+            (ObjectKind::from_byte(kind).unwrap(), id)
+        })
+    }
+
+    fn read_object(&self, kind: ObjectKind, id: &[u8]) -> io::Result<Option<Vec<u8>>> {
+        let entries = self.index_entries();
+        let Ok(pos) =
+            entries.binary_search_by(|entry| index_entry_key(entry).cmp(&(kind as u8, id)))
+        else {
+            return Ok(None);
+        };
+        let (offset, len) = entries[pos][1 + OBJECT_ID_LENGTH..].split_at(8);
+        let offset = u64::from_le_bytes(offset.try_into().unwrap());
+        let len = u64::from_le_bytes(len.try_into().unwrap());
+        let mut file = self.file.lock().unwrap();
+        file.seek(SeekFrom::Start(offset))?;
+        let mut buf = vec![];
+        (&mut *file).take(len).read_to_end(&mut buf)?;
+        if buf.len() as u64 != len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok(Some(buf))
+    }
+}
+
+/// Returns the kind and id of the pack index entry.
+fn index_entry_key(entry: &[u8; PACK_INDEX_ENTRY_SIZE]) -> (u8, &[u8]) {
+    (entry[0], &entry[1..1 + OBJECT_ID_LENGTH])
+}
+
+/// Writes a new pack file. The objects must be added in (kind, id) order.
+struct PackWriter {
+    temp_file: BufWriter<NamedTempFile>,
+    hasher: Blake2b512,
+    offset: u64,
+    index: Vec<u8>,
+    num_entries: u64,
+}
+
+impl PackWriter {
+    fn new(dir: &Path) -> io::Result<Self> {
+        let mut writer = Self {
+            temp_file: BufWriter::new(NamedTempFile::new_in(dir)?),
+            hasher: Blake2b512::new(),
+            offset: 0,
+            index: vec![],
+            num_entries: 0,
+        };
+        writer.write_all(PACK_MAGIC)?;
+        Ok(writer)
+    }
+
+    fn write_all(&mut self, data: &[u8]) -> io::Result<()> {
+        self.temp_file.write_all(data)?;
+        self.hasher.update(data);
+        self.offset += data.len() as u64;
+        Ok(())
+    }
+
+    fn add_object(&mut self, kind: ObjectKind, id: &[u8], data: &[u8]) -> io::Result<()> {
+        assert_eq!(id.len(), OBJECT_ID_LENGTH);
+        self.index.push(kind as u8);
+        self.index.extend_from_slice(id);
+        self.index.extend_from_slice(&self.offset.to_le_bytes());
+        self.index
+            .extend_from_slice(&(data.len() as u64).to_le_bytes());
+        self.num_entries += 1;
+        self.write_all(data)
+    }
+
+    /// Writes the index and persists the pack file in `dir`, named after the
+    /// hash of its contents.
+    fn finish(mut self, dir: &Path) -> io::Result<PathBuf> {
+        let index_offset = self.offset;
+        let index = mem::take(&mut self.index);
+        self.write_all(&index)?;
+        self.write_all(&index_offset.to_le_bytes())?;
+        self.write_all(&self.num_entries.to_le_bytes())?;
+        let temp_file = self
+            .temp_file
+            .into_inner()
+            .map_err(|err| err.into_error())?;
+        temp_file.as_file().sync_data()?;
+        let name = format!("{}.pack", hex_util::encode_hex(&self.hasher.finalize()));
+        let path = dir.join(name);
+        persist_content_addressed_temp_file(temp_file, &path)?;
+        Ok(path)
+    }
+}
+
+/// Backend storing objects as files in the repository, without Git
+/// interoperability.
+///
+/// New objects are written as loose files in a directory per object kind.
+/// Garbage collection packs reachable objects into a pack file and deletes the
+/// loose objects.
 #[derive(Debug)]
 pub struct SimpleBackend {
     path: PathBuf,
     root_commit_id: CommitId,
     root_change_id: ChangeId,
     empty_tree_id: TreeId,
+    /// Pack files, loaded on first use and reloaded when an object is missing
+    /// or after garbage collection.
+    packs: Mutex<Option<Arc<Vec<PackFile>>>>,
 }
 
 impl SimpleBackend {
@@ -103,11 +336,11 @@ impl SimpleBackend {
     }
 
     pub fn init(store_path: &Path) -> Self {
-        fs::create_dir(store_path.join("commits")).unwrap();
-        fs::create_dir(store_path.join("trees")).unwrap();
-        fs::create_dir(store_path.join("files")).unwrap();
-        fs::create_dir(store_path.join("symlinks")).unwrap();
+        for kind in ObjectKind::ALL {
+            fs::create_dir(store_path.join(kind.dir_name())).unwrap();
+        }
         fs::create_dir(store_path.join("conflicts")).unwrap();
+        fs::create_dir(store_path.join(PACK_DIR)).unwrap();
         let backend = Self::load(store_path);
         let empty_tree_id = backend
             .write_tree(RepoPath::root(), &Tree::default())
@@ -128,24 +361,306 @@ impl SimpleBackend {
             root_commit_id,
             root_change_id,
             empty_tree_id,
+            packs: Mutex::new(None),
+        }
+    }
+
+    fn object_path(&self, kind: ObjectKind, id: &[u8]) -> PathBuf {
+        self.path
+            .join(kind.dir_name())
+            .join(hex_util::encode_hex(id))
+    }
+
+    fn packs(&self, reload: bool) -> BackendResult<Arc<Vec<PackFile>>> {
+        let mut locked_packs = self.packs.lock().unwrap();
+        if let Some(packs) = &*locked_packs
+            && !reload
+        {
+            return Ok(packs.clone());
+        }
+        let packs = Arc::new(load_pack_files(&self.path.join(PACK_DIR)).map_err(to_other_err)?);
+        *locked_packs = Some(packs.clone());
+        Ok(packs)
+    }
+
+    /// Reads the contents of the object from the loose object file or the pack
+    /// files.
+    fn read_object(&self, kind: ObjectKind, id: &[u8]) -> BackendResult<Vec<u8>> {
+        let not_found_err = match fs::read(self.object_path(kind, id)) {
+            Ok(buf) => return Ok(buf),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => err,
+            Err(err) => return Err(map_not_found_err(err, kind, id)),
+        };
+        // The object may have been packed by concurrent garbage collection,
+        // so retry with reloaded pack files if the object isn't found.
+        for reload in [false, true] {
+            for pack in self.packs(reload)?.iter() {
+                match pack.read_object(kind, id) {
+                    Ok(Some(buf)) => return Ok(buf),
+                    Ok(None) => {}
+                    Err(err) => return Err(map_not_found_err(err, kind, id)),
+                }
+            }
+        }
+        Err(map_not_found_err(not_found_err, kind, id))
+    }
+
+    /// Creates a temporary file in the directory of the loose objects of the
+    /// given kind, so it can be persisted without crossing file systems.
+    fn new_temp_file(&self, kind: ObjectKind) -> BackendResult<NamedTempFile> {
+        let dir = self.path.join(kind.dir_name());
+        if kind == ObjectKind::Copy {
+            // Repositories created by older versions don't have the directory.
+            fs::create_dir_all(&dir).map_err(to_other_err)?;
+        }
+        NamedTempFile::new_in(dir).map_err(to_other_err)
+    }
+
+    fn persist_object(
+        &self,
+        kind: ObjectKind,
+        temp_file: NamedTempFile,
+        id: &[u8],
+    ) -> BackendResult<()> {
+        persist_content_addressed_temp_file(temp_file, self.object_path(kind, id))
+            .map_err(to_other_err)?;
+        Ok(())
+    }
+
+    fn write_object(&self, kind: ObjectKind, id: &[u8], data: &[u8]) -> BackendResult<()> {
+        let mut temp_file = self.new_temp_file(kind)?;
+        temp_file.write_all(data).map_err(to_other_err)?;
+        self.persist_object(kind, temp_file, id)
+    }
+
+    /// Lists ids and paths of the loose objects of the given kind.
+    fn loose_objects(&self, kind: ObjectKind) -> BackendResult<Vec<(Vec<u8>, PathBuf)>> {
+        let entries = match fs::read_dir(self.path.join(kind.dir_name())) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(err) => return Err(to_other_err(err)),
+        };
+        let mut objects = vec![];
+        for entry in entries {
+            let entry = entry.map_err(to_other_err)?;
+            // Skip temporary files and anything else not named by an id
+            let id = entry
+                .file_name()
+                .to_str()
+                .and_then(hex_util::decode_hex)
+                .filter(|id| id.len() == OBJECT_ID_LENGTH);
+            if let Some(id) = id {
+                objects.push((id, entry.path()));
+            }
+        }
+        Ok(objects)
+    }
+
+    fn read_tree_sync(&self, id: &TreeId) -> BackendResult<Tree> {
+        let buf = self.read_object(ObjectKind::Tree, id.as_bytes())?;
+        let proto = crate::protos::simple_store::Tree::decode(&*buf).map_err(to_other_err)?;
+        Ok(tree_from_proto(proto))
+    }
+
+    fn read_commit_sync(&self, id: &CommitId) -> BackendResult<Commit> {
+        if *id == self.root_commit_id {
+            return Ok(make_root_commit(
+                self.root_change_id().clone(),
+                self.empty_tree_id.clone(),
+            ));
+        }
+
+        let buf = self.read_object(ObjectKind::Commit, id.as_bytes())?;
+        let proto = crate::protos::simple_store::Commit::decode(&*buf).map_err(to_other_err)?;
+        Ok(commit_from_proto(proto))
+    }
+
+    fn read_copy_sync(&self, id: &CopyId) -> BackendResult<CopyHistory> {
+        let buf = self.read_object(ObjectKind::Copy, id.as_bytes())?;
+        copy_history_from_proto_bytes(&buf).map_err(|err| BackendError::ReadObject {
+            object_type: id.object_type(),
+            hash: id.hex(),
+            source: err,
+        })
+    }
+
+    /// Finds the file value at `path` in the tree `root_id`.
+    fn find_file(&self, root_id: &TreeId, path: &RepoPath) -> BackendResult<Option<TreeValue>> {
+        let Some((dir, name)) = path.split() else {
+            return Ok(None);
+        };
+        let mut tree_id = root_id.clone();
+        for component in dir.components() {
+            match self.read_tree_sync(&tree_id)?.value(component) {
+                Some(TreeValue::Tree(id)) => tree_id = id.clone(),
+                _ => return Ok(None),
+            }
         }
+        let value = self.read_tree_sync(&tree_id)?.value(name).cloned();
+        Ok(value.filter(|value| matches!(value, TreeValue::File { .. })))
     }
 
-    fn file_path(&self, id: &FileId) -> PathBuf {
-        self.path.join("files").join(id.hex())
+    /// Collects the files with copy histories in `head_id` which aren't files
+    /// in `root_id`.
+    fn collect_copied_files(
+        &self,
+        dir: &RepoPath,
+        root_id: Option<&TreeId>,
+        head_id: &TreeId,
+        copied_files: &mut Vec<(RepoPathBuf, CopyId)>,
+    ) -> BackendResult<()> {
+        if root_id == Some(head_id) {
+            return Ok(());
+        }
+        let root_tree = root_id.map(|id| self.read_tree_sync(id)).transpose()?;
+        let head_tree = self.read_tree_sync(head_id)?;
+        for entry in head_tree.entries() {
+            let root_value = root_tree.as_ref().and_then(|tree| tree.value(entry.name()));
+            match entry.value() {
+                TreeValue::File { copy_id, .. } => {
+                    if *copy_id != CopyId::placeholder()
+                        && !matches!(root_value, Some(TreeValue::File { .. }))
+                    {
+                        copied_files.push((dir.join(entry.name()), copy_id.clone()));
+                    }
+                }
+                TreeValue::Tree(id) => {
+                    let root_sub_id = match root_value {
+                        Some(TreeValue::Tree(id)) => Some(id),
+                        _ => None,
+                    };
+                    self.collect_copied_files(
+                        &dir.join(entry.name()),
+                        root_sub_id,
+                        id,
+                        copied_files,
+                    )?;
+                }
+                TreeValue::Symlink(_) | TreeValue::GitSubmodule(_) => {}
+            }
+        }
+        Ok(())
     }
 
-    fn symlink_path(&self, id: &SymlinkId) -> PathBuf {
-        self.path.join("symlinks").join(id.hex())
+    /// Finds the nearest ancestor of the copy history `copy_id` whose file
+    /// exists in the tree `root_id`.
+    fn find_copy_source(
+        &self,
+        root_id: &TreeId,
+        copy_id: &CopyId,
+    ) -> BackendResult<Option<(RepoPathBuf, FileId)>> {
+        let mut queue = VecDeque::from(self.read_copy_sync(copy_id)?.parents);
+        let mut visited = HashSet::new();
+        while let Some(id) = queue.pop_front() {
+            if !visited.insert(id.clone()) {
+                continue;
+            }
+            let history = self.read_copy_sync(&id)?;
+            if let Some(TreeValue::File {
+                id: file_id,
+                copy_id: root_copy_id,
+                ..
+            }) = self.find_file(root_id, &history.current_path)?
+            {
+                // A file without tracked copy history is the origin of the
+                // copy history recorded at the same path.
+                if root_copy_id == CopyId::placeholder() || root_copy_id == id {
+                    return Ok(Some((history.current_path, file_id)));
+                }
+            }
+            queue.extend(history.parents);
+        }
+        Ok(None)
     }
 
-    fn tree_path(&self, id: &TreeId) -> PathBuf {
-        self.path.join("trees").join(id.hex())
+    /// Adds the given objects and the objects reachable from them to
+    /// `reachable`. Objects already in `reachable` aren't traversed again.
+    fn collect_reachable_objects(
+        &self,
+        reachable: &mut HashSet<(ObjectKind, Vec<u8>)>,
+        roots: impl IntoIterator<Item = (ObjectKind, Vec<u8>)>,
+    ) -> BackendResult<()> {
+        let mut commit_ids = vec![];
+        let mut tree_ids = vec![self.empty_tree_id.clone()];
+        let mut copy_ids = vec![];
+        for (kind, id) in roots {
+            match kind {
+                ObjectKind::Commit => commit_ids.push((CommitId::new(id), true)),
+                ObjectKind::Tree => tree_ids.push(TreeId::new(id)),
+                ObjectKind::Copy => copy_ids.push(CopyId::new(id)),
+                ObjectKind::File | ObjectKind::Symlink => {
+                    reachable.insert((kind, id));
+                }
+            }
+        }
+        while let Some((id, required)) = commit_ids.pop() {
+            if id == self.root_commit_id || !reachable.insert((ObjectKind::Commit, id.to_bytes())) {
+                continue;
+            }
+            let commit = match self.read_commit_sync(&id) {
+                Ok(commit) => commit,
+                // Predecessors may have been discarded
+                Err(BackendError::ObjectNotFound { .. }) if !required => {
+                    reachable.remove(&(ObjectKind::Commit, id.to_bytes()));
+                    continue;
+                }
+                Err(err) => return Err(err),
+            };
+            commit_ids.extend(commit.parents.into_iter().map(|id| (id, true)));
+            commit_ids.extend(commit.predecessors.into_iter().map(|id| (id, false)));
+            tree_ids.extend(commit.root_tree);
+        }
+        while let Some(id) = tree_ids.pop() {
+            if !reachable.insert((ObjectKind::Tree, id.to_bytes())) {
+                continue;
+            }
+            for entry in self.read_tree_sync(&id)?.entries() {
+                match entry.value() {
+                    TreeValue::File { id, copy_id, .. } => {
+                        reachable.insert((ObjectKind::File, id.to_bytes()));
+                        if *copy_id != CopyId::placeholder() {
+                            copy_ids.push(copy_id.clone());
+                        }
+                    }
+                    TreeValue::Symlink(id) => {
+                        reachable.insert((ObjectKind::Symlink, id.to_bytes()));
+                    }
+                    TreeValue::Tree(id) => tree_ids.push(id.clone()),
+                    TreeValue::GitSubmodule(_) => {}
+                }
+            }
+        }
+        while let Some(id) = copy_ids.pop() {
+            if !reachable.insert((ObjectKind::Copy, id.to_bytes())) {
+                continue;
+            }
+            copy_ids.extend(self.read_copy_sync(&id)?.parents);
+        }
+        Ok(())
     }
+}
 
-    fn commit_path(&self, id: &CommitId) -> PathBuf {
-        self.path.join("commits").join(id.hex())
+fn load_pack_files(dir: &Path) -> io::Result<Vec<PackFile>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        // Repositories created by older versions don't have the directory.
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => return Err(err),
+    };
+    let mut packs = vec![];
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension() != Some("pack".as_ref()) {
+            continue;
+        }
+        match PackFile::load(path) {
+            Ok(pack) => packs.push(pack),
+            // Deleted by concurrent garbage collection
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err),
+        }
     }
+    Ok(packs)
 }
 
 #[async_trait]
@@ -180,18 +695,10 @@ impl Backend for SimpleBackend {
 
     async fn read_file(
         &self,
-        path: &RepoPath,
+        _path: &RepoPath,
         id: &FileId,
     ) -> BackendResult<Pin<Box<dyn AsyncRead + Send>>> {
-        let disk_path = self.file_path(id);
-        let mut file = File::open(disk_path).map_err(|err| map_not_found_err(err, id))?;
-        let mut buf = vec![];
-        file.read_to_end(&mut buf)
-            .map_err(|err| BackendError::ReadFile {
-                path: path.to_owned(),
-                id: id.clone(),
-                source: err.into(),
-            })?;
+        let buf = self.read_object(ObjectKind::File, id.as_bytes())?;
         Ok(Box::pin(Cursor::new(buf)))
     }
 
@@ -200,8 +707,7 @@ impl Backend for SimpleBackend {
         _path: &RepoPath,
         contents: &mut (dyn AsyncRead + Send + Unpin),
     ) -> BackendResult<FileId> {
-        // TODO: Write temporary file in the destination directory (#5712)
-        let temp_file = NamedTempFile::new_in(&self.path).map_err(to_other_err)?;
+        let temp_file = self.new_temp_file(ObjectKind::File)?;
         let mut file = temp_file.as_file();
         let mut hasher = Blake2b512::new();
         let mut buff: Vec<u8> = vec![0; 1 << 14];
@@ -217,88 +723,80 @@ impl Backend for SimpleBackend {
         file.flush().map_err(to_other_err)?;
         let id = FileId::new(hasher.finalize().to_vec());
 
-        persist_content_addressed_temp_file(temp_file, self.file_path(&id))
-            .map_err(to_other_err)?;
+        self.persist_object(ObjectKind::File, temp_file, id.as_bytes())?;
         Ok(id)
     }
 
     async fn read_symlink(&self, _path: &RepoPath, id: &SymlinkId) -> BackendResult<String> {
-        let path = self.symlink_path(id);
-        let target = fs::read_to_string(path).map_err(|err| map_not_found_err(err, id))?;
-        Ok(target)
+        let buf = self.read_object(ObjectKind::Symlink, id.as_bytes())?;
+        String::from_utf8(buf).map_err(|err| BackendError::ReadObject {
+            object_type: id.object_type(),
+            hash: id.hex(),
+            source: err.into(),
+        })
     }
 
     async fn write_symlink(&self, _path: &RepoPath, target: &str) -> BackendResult<SymlinkId> {
-        // TODO: Write temporary file in the destination directory (#5712)
-        let mut temp_file = NamedTempFile::new_in(&self.path).map_err(to_other_err)?;
-        temp_file
-            .write_all(target.as_bytes())
-            .map_err(to_other_err)?;
         let mut hasher = Blake2b512::new();
         hasher.update(target.as_bytes());
         let id = SymlinkId::new(hasher.finalize().to_vec());
 
-        persist_content_addressed_temp_file(temp_file, self.symlink_path(&id))
-            .map_err(to_other_err)?;
+        self.write_object(ObjectKind::Symlink, id.as_bytes(), target.as_bytes())?;
         Ok(id)
     }
 
-    async fn read_copy(&self, _id: &CopyId) -> BackendResult<CopyHistory> {
-        Err(BackendError::Unsupported(
-            "The simple backend doesn't support copies".to_string(),
-        ))
+    async fn read_copy(&self, id: &CopyId) -> BackendResult<CopyHistory> {
+        self.read_copy_sync(id)
     }
 
-    async fn write_copy(&self, _contents: &CopyHistory) -> BackendResult<CopyId> {
-        Err(BackendError::Unsupported(
-            "The simple backend doesn't support copies".to_string(),
-        ))
+    async fn write_copy(&self, contents: &CopyHistory) -> BackendResult<CopyId> {
+        let id = CopyId::new(blake2b_hash(contents).to_vec());
+        let proto = copy_history_to_proto(contents);
+        self.write_object(ObjectKind::Copy, id.as_bytes(), &proto.encode_to_vec())?;
+        Ok(id)
     }
 
-    async fn get_related_copies(&self, _copy_id: &CopyId) -> BackendResult<Vec<CopyHistory>> {
-        Err(BackendError::Unsupported(
-            "The simple backend doesn't support copies".to_string(),
-        ))
+    async fn get_related_copies(&self, copy_id: &CopyId) -> BackendResult<Vec<CopyHistory>> {
+        // Make sure the copy exists, which also reloads the pack files if
+        // needed.
+        self.read_copy_sync(copy_id)?;
+        let mut ids: HashSet<Vec<u8>> = self
+            .loose_objects(ObjectKind::Copy)?
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        for pack in self.packs(false)?.iter() {
+            ids.extend(
+                pack.object_keys()
+                    .filter(|(kind, _)| *kind == ObjectKind::Copy)
+                    .map(|(_, id)| id.to_vec()),
+            );
+        }
+        let copies: HashMap<CopyId, CopyHistory> = ids
+            .into_iter()
+            .map(|id| {
+                let id = CopyId::new(id);
+                let copy = self.read_copy_sync(&id)?;
+                Ok((id, copy))
+            })
+            .try_collect::<_, _, BackendError>()?;
+        Ok(related_copy_histories(&copies, copy_id))
     }
 
     async fn read_tree(&self, _path: &RepoPath, id: &TreeId) -> BackendResult<Tree> {
-        let path = self.tree_path(id);
-        let buf = fs::read(path).map_err(|err| map_not_found_err(err, id))?;
-
-        let proto = crate::protos::simple_store::Tree::decode(&*buf).map_err(to_other_err)?;
-        Ok(tree_from_proto(proto))
+        self.read_tree_sync(id)
     }
 
     async fn write_tree(&self, _path: &RepoPath, tree: &Tree) -> BackendResult<TreeId> {
-        // TODO: Write temporary file in the destination directory (#5712)
-        let temp_file = NamedTempFile::new_in(&self.path).map_err(to_other_err)?;
-
         let proto = tree_to_proto(tree);
-        temp_file
-            .as_file()
-            .write_all(&proto.encode_to_vec())
-            .map_err(to_other_err)?;
-
         let id = TreeId::new(blake2b_hash(tree).to_vec());
 
-        persist_content_addressed_temp_file(temp_file, self.tree_path(&id))
-            .map_err(to_other_err)?;
+        self.write_object(ObjectKind::Tree, id.as_bytes(), &proto.encode_to_vec())?;
         Ok(id)
     }
 
     async fn read_commit(&self, id: &CommitId) -> BackendResult<Commit> {
-        if *id == self.root_commit_id {
-            return Ok(make_root_commit(
-                self.root_change_id().clone(),
-                self.empty_tree_id.clone(),
-            ));
-        }
-
-        let path = self.commit_path(id);
-        let buf = fs::read(path).map_err(|err| map_not_found_err(err, id))?;
-
-        let proto = crate::protos::simple_store::Commit::decode(&*buf).map_err(to_other_err)?;
-        Ok(commit_from_proto(proto))
+        self.read_commit_sync(id)
     }
 
     async fn write_commit(
@@ -313,8 +811,6 @@ impl Backend for SimpleBackend {
                 "Cannot write a commit with no parents".into(),
             ));
         }
-        // TODO: Write temporary file in the destination directory (#5712)
-        let temp_file = NamedTempFile::new_in(&self.path).map_err(to_other_err)?;
 
         let mut proto = commit_to_proto(&commit);
         if let Some(sign) = sign_with {
@@ -324,29 +820,156 @@ impl Backend for SimpleBackend {
             commit.secure_sig = Some(SecureSig { data, sig });
         }
 
-        temp_file
-            .as_file()
-            .write_all(&proto.encode_to_vec())
-            .map_err(to_other_err)?;
-
         let id = CommitId::new(blake2b_hash(&commit).to_vec());
 
-        persist_content_addressed_temp_file(temp_file, self.commit_path(&id))
-            .map_err(to_other_err)?;
+        self.write_object(ObjectKind::Commit, id.as_bytes(), &proto.encode_to_vec())?;
         Ok((id, commit))
     }
 
     fn get_copy_records(
         &self,
-        _paths: Option<&[RepoPathBuf]>,
-        _root: &CommitId,
-        _head: &CommitId,
+        paths: Option<&[RepoPathBuf]>,
+        root_id: &CommitId,
+        head_id: &CommitId,
     ) -> BackendResult<BoxStream<'_, BackendResult<CopyRecord>>> {
-        Ok(Box::pin(stream::empty()))
+        let root_commit = self.read_commit_sync(root_id)?;
+        let head_commit = self.read_commit_sync(head_id)?;
+        // TODO: Copies in conflicted trees are not reported.
+        let (Some(root_tree_id), Some(head_tree_id)) = (
+            root_commit.root_tree.as_resolved(),
+            head_commit.root_tree.as_resolved(),
+        ) else {
+            return Ok(Box::pin(stream::empty()));
+        };
+
+        let mut copied_files = vec![];
+        self.collect_copied_files(
+            RepoPath::root(),
+            Some(root_tree_id),
+            head_tree_id,
+            &mut copied_files,
+        )?;
+        let mut records = vec![];
+        for (target, copy_id) in copied_files {
+            if !paths.is_none_or(|paths| paths.contains(&target)) {
+                continue;
+            }
+            if let Some((source, source_file)) = self.find_copy_source(root_tree_id, &copy_id)? {
+                records.push(Ok(CopyRecord {
+                    target,
+                    target_commit: head_id.clone(),
+                    source,
+                    source_file,
+                    source_commit: root_id.clone(),
+                }));
+            }
+        }
+        Ok(Box::pin(stream::iter(records)))
     }
 
-    fn gc(&self, _index: &dyn Index, _keep_newer: SystemTime) -> BackendResult<()> {
-        Ok(())
+    #[tracing::instrument(skip(self, index))]
+    fn gc(&self, index: &dyn Index, options: &GcOptions) -> BackendResult<GcStats> {
+        let heads = index
+            .all_heads_for_gc()
+            .map_err(|err| BackendError::Other(err.into()))?;
+        let mut reachable = HashSet::new();
+        self.collect_reachable_objects(
+            &mut reachable,
+            heads.map(|id| (ObjectKind::Commit, id.to_bytes())),
+        )?;
+
+        // Recent loose objects are kept as they may be referenced by
+        // concurrently created commits. So are the objects referenced by them,
+        // which may only exist in old pack files.
+        let mut removable_loose_objects = vec![];
+        let mut recent_loose_objects = vec![];
+        for kind in ObjectKind::ALL {
+            for (id, path) in self.loose_objects(kind)? {
+                let key = (kind, id);
+                if reachable.contains(&key) {
+                    removable_loose_objects.push((key, path));
+                    continue;
+                }
+                let mtime = match fs::metadata(&path).and_then(|metadata| metadata.modified()) {
+                    Ok(mtime) => mtime,
+                    Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+                    Err(err) => return Err(to_other_err(err)),
+                };
+                if mtime > options.keep_newer {
+                    recent_loose_objects.push(key);
+                } else {
+                    removable_loose_objects.push((key, path));
+                }
+            }
+        }
+        self.collect_reachable_objects(&mut reachable, recent_loose_objects.iter().cloned())?;
+        // Unreachable recent objects stay loose until they get old.
+        for key in &recent_loose_objects {
+            reachable.remove(key);
+        }
+        let mut objects: BTreeSet<(ObjectKind, Vec<u8>)> = reachable.into_iter().collect();
+        // Objects in recent pack files may be referenced by concurrently
+        // created commits.
+        let old_packs = self.packs(true)?;
+        for pack in old_packs.iter() {
+            if pack.mtime > options.keep_newer {
+                objects.extend(pack.object_keys().map(|(kind, id)| (kind, id.to_vec())));
+            }
+        }
+
+        let mut removed_commit_ids: HashSet<Vec<u8>> = HashSet::new();
+        for pack in old_packs.iter() {
+            removed_commit_ids.extend(
+                pack.object_keys()
+                    .filter(|&(kind, id)| {
+                        kind == ObjectKind::Commit && !objects.contains(&(kind, id.to_vec()))
+                    })
+                    .map(|(_, id)| id.to_vec()),
+            );
+        }
+        // Packed loose objects are removed as well as old unreachable ones.
+        let mut loose_paths_to_remove = vec![];
+        for ((kind, id), path) in removable_loose_objects {
+            if kind == ObjectKind::Commit && !objects.contains(&(kind, id.clone())) {
+                removed_commit_ids.insert(id);
+            }
+            loose_paths_to_remove.push(path);
+        }
+        let stats = GcStats {
+            commits: removed_commit_ids.len(),
+            ..GcStats::default()
+        };
+        if options.dry_run {
+            return Ok(stats);
+        }
+
+        let pack_dir = self.path.join(PACK_DIR);
+        fs::create_dir_all(&pack_dir).map_err(to_other_err)?;
+        let mut writer = PackWriter::new(&pack_dir).map_err(to_other_err)?;
+        for (kind, id) in &objects {
+            let data = self.read_object(*kind, id)?;
+            writer.add_object(*kind, id, &data).map_err(to_other_err)?;
+        }
+        let new_pack_path = writer.finish(&pack_dir).map_err(to_other_err)?;
+        for pack in old_packs.iter() {
+            if pack.path != new_pack_path {
+                remove_file_if_exists(&pack.path)?;
+            }
+        }
+        // Old pack files are still readable while they're open.
+        self.packs(true)?;
+        for path in &loose_paths_to_remove {
+            remove_file_if_exists(path)?;
+        }
+        Ok(stats)
+    }
+}
+
+fn remove_file_if_exists(path: &Path) -> BackendResult<()> {
+    match fs::remove_file(path) {
+        Ok(()) => Ok(()),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(to_other_err(err)),
     }
 }
 
@@ -501,6 +1124,25 @@ fn signature_from_proto(proto: crate::protos::simple_store::commit::Signature) -
     }
 }
 
+fn copy_history_to_proto(copy: &CopyHistory) -> crate::protos::simple_store::CopyHistory {
+    crate::protos::simple_store::CopyHistory {
+        current_path: copy.current_path.as_internal_file_string().to_owned(),
+        parents: copy.parents.iter().map(|id| id.to_bytes()).collect(),
+        salt: copy.salt.clone(),
+    }
+}
+
+fn copy_history_from_proto_bytes(
+    bytes: &[u8],
+) -> Result<CopyHistory, Box<dyn std::error::Error + Send + Sync>> {
+    let proto = crate::protos::simple_store::CopyHistory::decode(bytes)?;
+    Ok(CopyHistory {
+        current_path: RepoPathBuf::from_internal_string(proto.current_path)?,
+        parents: proto.parents.into_iter().map(CopyId::new).collect(),
+        salt: proto.salt,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use assert_matches::assert_matches;
@@ -24,7 +24,6 @@ use std::io::ErrorKind;
 use std::io::Write as _;
 use std::path::Path;
 use std::path::PathBuf;
-use std::time::SystemTime;
 
 use async_trait::async_trait;
 use itertools::Itertools as _;
@@ -43,6 +42,8 @@ use crate::dag_walk;
 use crate::file_util::IoResultExt as _;
 use crate::file_util::PathError;
 use crate::file_util::persist_content_addressed_temp_file;
+use crate::gc::GcOptions;
+use crate::gc::GcStats;
 use crate::merge::Merge;
 use crate::object_id::HexPrefix;
 use crate::object_id::ObjectId;
@@ -274,7 +275,7 @@ impl OpStore for SimpleOpStore {
     }
 
     #[tracing::instrument(skip(self))]
-    fn gc(&self, head_ids: &[OperationId], keep_newer: SystemTime) -> OpStoreResult<()> {
+    fn gc(&self, head_ids: &[OperationId], options: &GcOptions) -> OpStoreResult<GcStats> {
         let to_op_id = |entry: &fs::DirEntry| -> Option<OperationId> {
             let name = entry.file_name().into_string().ok()?;
             OperationId::try_from_hex(name)
@@ -283,18 +284,23 @@ impl OpStore for SimpleOpStore {
             let name = entry.file_name().into_string().ok()?;
             ViewId::try_from_hex(name)
         };
-        let remove_file_if_not_new = |entry: &fs::DirEntry| -> Result<(), PathError> {
+        // Returns true if the file was (or would be) removed.
+        let remove_file_if_not_new = |entry: &fs::DirEntry| -> Result<bool, PathError> {
             let path = entry.path();
             // Check timestamp, but there's still TOCTOU problem if an existing
             // file is renewed.
             let metadata = entry.metadata().context(&path)?;
             let mtime = metadata.modified().expect("unsupported platform?");
-            if mtime > keep_newer {
+            if mtime > options.keep_newer {
                 tracing::trace!(?path, "not removing");
-                Ok(())
+                Ok(false)
+            } else if options.dry_run {
+                tracing::trace!(?path, "would remove");
+                Ok(true)
             } else {
                 tracing::trace!(?path, "removing");
-                fs::remove_file(&path).context(&path)
+                fs::remove_file(&path).context(&path)?;
+                Ok(true)
             }
         };
 
@@ -320,7 +326,8 @@ impl OpStore for SimpleOpStore {
             "collected reachable objects"
         );
 
-        let prune_ops = || -> Result<(), PathError> {
+        let prune_ops = || -> Result<usize, PathError> {
+            let mut count = 0;
             let op_dir = self.operations_dir();
             for entry in op_dir.read_dir().context(&op_dir)? {
                 let entry = entry.context(&op_dir)?;
@@ -334,11 +341,13 @@ impl OpStore for SimpleOpStore {
                 // If the operation was added after collecting reachable_views,
                 // its view mtime would also be renewed. So there's no need to
                 // update the reachable_views set to preserve the view.
-                remove_file_if_not_new(&entry)?;
+                if remove_file_if_not_new(&entry)? {
+                    count += 1;
+                }
             }
-            Ok(())
+            Ok(count)
         };
-        prune_ops().map_err(|err| OpStoreError::Other(err.into()))?;
+        let operations = prune_ops().map_err(|err| OpStoreError::Other(err.into()))?;
 
         let prune_views = || -> Result<(), PathError> {
             let view_dir = self.views_dir();
@@ -357,7 +366,10 @@ impl OpStore for SimpleOpStore {
         };
         prune_views().map_err(|err| OpStoreError::Other(err.into()))?;
 
-        Ok(())
+        Ok(GcStats {
+            operations,
+            ..GcStats::default()
+        })
     }
 }
 
@@ -139,6 +139,15 @@ impl ReadonlyTable {
         MutableTable::incremental(self.clone())
     }
 
+    /// Iterates `(key, value)` entries of this segment, excluding the entries
+    /// in the parent segments.
+    pub fn segment_entries(&self) -> impl Iterator<Item = (&[u8], &[u8])> {
+        (0..self.num_local_entries).map(|pos| {
+            let entry = ReadonlyTableIndexEntry::new(self, pos);
+            (entry.key(), self.segment_value_by_pos(pos))
+        })
+    }
+
     fn segment_value_offset_by_pos(&self, pos: usize) -> usize {
         if pos == self.num_local_entries {
             self.values.len()
@@ -565,6 +574,40 @@ impl TableStore {
         Ok((merged_table, lock))
     }
 
+    /// Rewrites the head table without the entries for which `f` returns
+    /// false.
+    ///
+    /// Returns the new head table and the number of removed entries. The
+    /// segments of the old head table are no longer reachable, and can be
+    /// pruned by `gc()`.
+    pub fn retain_entries(
+        &self,
+        mut f: impl FnMut(&[u8], &[u8]) -> bool,
+    ) -> TableStoreResult<(Arc<ReadonlyTable>, usize)> {
+        let (head, _lock) = self.get_head_locked()?;
+        let old_segments: Vec<_> = head.ancestor_segments().collect();
+        let mut mut_table = MutableTable::full(self.key_size);
+        for segment in old_segments.iter().rev() {
+            mut_table.add_entries_from(segment.as_ref());
+        }
+        let num_entries = mut_table.entries.len();
+        mut_table.entries.retain(|key, value| f(key, value));
+        let num_removed = num_entries - mut_table.entries.len();
+        if num_removed == 0 {
+            return Ok((head, 0));
+        }
+        let table = self.save_table(mut_table)?;
+        self.remove_head(&head);
+        {
+            // Forget the old segments so gc() can remove them.
+            let mut locked_cache = self.cached_tables.write().unwrap();
+            for segment in &old_segments {
+                locked_cache.remove(segment.name());
+            }
+        }
+        Ok((table, num_removed))
+    }
+
     /// Prunes unreachable table segments.
     ///
     /// All table segments reachable from the `head` won't be removed. In
@@ -627,6 +670,8 @@ impl TableStore {
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use test_case::test_case;
 
     use super::*;
@@ -728,6 +773,20 @@ mod tests {
         assert_eq!(mut_table.get_value(b"x94"), Some(b"value 94".as_slice()));
         assert_eq!(mut_table.get_value(b"xAA"), None);
         assert_eq!(mut_table.get_value(b"\xff\xff\xff"), None);
+
+        // Can iterate all entries through the ancestor segments
+        let saved_table = store.save_table(mut_table).unwrap();
+        let num_entries = saved_table
+            .ancestor_segments()
+            .map(|segment| segment.segment_entries().count())
+            .sum::<usize>();
+        assert_eq!(num_entries, 103);
+        let (key, value) = saved_table
+            .ancestor_segments()
+            .flat_map(|segment| segment.segment_entries())
+            .find(|(key, _)| *key == b"abc")
+            .unwrap();
+        assert_eq!((key, value), (b"abc".as_slice(), b"value1".as_slice()));
     }
 
     #[test]
@@ -816,4 +875,55 @@ mod tests {
         let table = store.get_head().unwrap();
         assert_eq!(table.get_value(b"abc"), Some(b"value".as_slice()));
     }
+
+    #[test]
+    fn stacked_table_store_retain_entries() {
+        let temp_dir = new_temp_dir();
+        let store = TableStore::init(temp_dir.path().to_path_buf(), 3);
+        let list_segments = || {
+            temp_dir
+                .path()
+                .read_dir()
+                .unwrap()
+                .map(|entry| entry.unwrap().file_name())
+                .filter(|name| name.len() == SEGMENT_FILE_NAME_LENGTH)
+                .count()
+        };
+
+        let mut mut_table = store.get_head().unwrap().start_mutation();
+        mut_table.add_entry(b"abc".to_vec(), b"value1".to_vec());
+        mut_table.add_entry(b"def".to_vec(), b"value2".to_vec());
+        store.save_table(mut_table).unwrap();
+        let mut mut_table = store.get_head().unwrap().start_mutation();
+        mut_table.add_entry(b"abc".to_vec(), b"value3".to_vec());
+        mut_table.add_entry(b"ghi".to_vec(), b"value4".to_vec());
+        store.save_table(mut_table).unwrap();
+
+        // Nothing to remove
+        let head = store.get_head().unwrap();
+        let (table, num_removed) = store.retain_entries(|_, _| true).unwrap();
+        assert_eq!(num_removed, 0);
+        assert_eq!(table.name(), head.name());
+
+        // Values in the newer segment take precedence
+        let (table, num_removed) = store
+            .retain_entries(|key, value| key != b"def" && value != b"value1")
+            .unwrap();
+        assert_eq!(num_removed, 1);
+        assert_eq!(table.get_value(b"abc"), Some(b"value3".as_slice()));
+        assert_eq!(table.get_value(b"def"), None);
+        assert_eq!(table.get_value(b"ghi"), Some(b"value4".as_slice()));
+        assert_eq!(store.get_head().unwrap().name(), table.name());
+
+        // The old segments can be pruned
+        let segment_count = list_segments();
+        store
+            .gc(&table, SystemTime::now() + Duration::from_secs(1))
+            .unwrap();
+        assert!(list_segments() < segment_count);
+        let store = TableStore::load(temp_dir.path().to_path_buf(), 3);
+        let table = store.get_head().unwrap();
+        assert_eq!(table.get_value(b"abc"), Some(b"value3".as_slice()));
+        assert_eq!(table.get_value(b"def"), None);
+    }
 }
@@ -19,7 +19,6 @@ use std::fmt::Formatter;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::sync::Mutex;
-use std::time::SystemTime;
 
 use clru::CLruCache;
 use futures::stream::BoxStream;
@@ -31,17 +30,23 @@ use crate::backend::Backend;
 use crate::backend::BackendResult;
 use crate::backend::ChangeId;
 use crate::backend::CommitId;
+use crate::backend::CopyHistory;
+use crate::backend::CopyId;
 use crate::backend::CopyRecord;
 use crate::backend::FileId;
 use crate::backend::SigningFn;
 use crate::backend::SymlinkId;
 use crate::backend::TreeId;
 use crate::commit::Commit;
+use crate::gc::GcOptions;
+use crate::gc::GcStats;
 use crate::index::Index;
 use crate::merge::Merge;
+use crate::merge_driver::ExternalMergeDriverMap;
 use crate::merged_tree::MergedTree;
 use crate::repo_path::RepoPath;
 use crate::repo_path::RepoPathBuf;
+use crate::rerere::ResolutionStore;
 use crate::signing::Signer;
 use crate::tree::Tree;
 use crate::tree_merge::MergeOptions;
@@ -59,6 +64,8 @@ pub struct Store {
     commit_cache: Mutex<CLruCache<CommitId, Arc<backend::Commit>>>,
     tree_cache: Mutex<CLruCache<(RepoPathBuf, TreeId), Arc<backend::Tree>>>,
     merge_options: MergeOptions,
+    merge_drivers: ExternalMergeDriverMap,
+    resolution_store: Option<ResolutionStore>,
 }
 
 impl Debug for Store {
@@ -74,6 +81,8 @@ impl Store {
         backend: Box<dyn Backend>,
         signer: Signer,
         merge_options: MergeOptions,
+        merge_drivers: ExternalMergeDriverMap,
+        resolution_store: Option<ResolutionStore>,
     ) -> Arc<Self> {
         Arc::new(Self {
             backend,
@@ -81,6 +90,8 @@ impl Store {
             commit_cache: Mutex::new(CLruCache::new(COMMIT_CACHE_CAPACITY.try_into().unwrap())),
             tree_cache: Mutex::new(CLruCache::new(TREE_CACHE_CAPACITY.try_into().unwrap())),
             merge_options,
+            merge_drivers,
+            resolution_store,
         })
     }
 
@@ -102,6 +113,24 @@ impl Store {
         &self.merge_options
     }
 
+    /// External merge drivers which can be selected by `.gitattributes`.
+    pub fn merge_drivers(&self) -> &ExternalMergeDriverMap {
+        &self.merge_drivers
+    }
+
+    /// Recorded conflict resolutions to reuse when merging files, if enabled.
+    pub fn resolution_store(&self) -> Option<&ResolutionStore> {
+        self.resolution_store.as_ref()
+    }
+
+    pub async fn read_copy(&self, id: &CopyId) -> BackendResult<CopyHistory> {
+        self.backend.read_copy(id).await
+    }
+
+    pub async fn write_copy(&self, copy: &CopyHistory) -> BackendResult<CopyId> {
+        self.backend.write_copy(copy).await
+    }
+
     pub fn get_copy_records(
         &self,
         paths: Option<&[RepoPathBuf]>,
@@ -259,8 +288,8 @@ impl Store {
         self.backend.write_symlink(path, contents).await
     }
 
-    pub fn gc(&self, index: &dyn Index, keep_newer: SystemTime) -> BackendResult<()> {
-        self.backend.gc(index, keep_newer)
+    pub fn gc(&self, index: &dyn Index, options: &GcOptions) -> BackendResult<GcStats> {
+        self.backend.gc(index, options)
     }
 
     /// Clear cached objects. Mainly intended for testing.
@@ -15,7 +15,54 @@
 #![expect(missing_docs)]
 
 use std::fmt::Debug;
+use std::path::Path;
+
+use thiserror::Error;
+
+use crate::backend::CommitId;
+use crate::repo_path::RepoPathBuf;
+
+/// Description of a submodule, as configured in `.gitmodules`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SubmoduleConfig {
+    /// Name of the submodule, which identifies it in the store.
+    pub name: String,
+    /// Path to the submodule in the superproject.
+    pub path: RepoPathBuf,
+    /// URL to clone the submodule from.
+    pub url: String,
+}
+
+#[derive(Debug, Error)]
+pub enum SubmoduleStoreError {
+    #[error("Invalid submodule name {0:?}")]
+    InvalidName(String),
+    #[error("Failed to clone submodule {name} from {url}")]
+    Clone {
+        name: String,
+        url: String,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    #[error("Failed to check out commit {commit_id} in submodule {name}")]
+    CheckOut {
+        name: String,
+        commit_id: CommitId,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+}
 
 pub trait SubmoduleStore: Send + Sync + Debug {
     fn name(&self) -> &str;
+
+    /// Populates the working copy of the `submodule` at `disk_path` with the
+    /// contents of `commit_id`.
+    ///
+    /// The submodule is cloned first if it isn't in the store yet, and fetched
+    /// if the commit is missing.
+    fn check_out(
+        &self,
+        submodule: &SubmoduleConfig,
+        disk_path: &Path,
+        commit_id: &CommitId,
+    ) -> Result<(), SubmoduleStoreError>;
 }
@@ -103,9 +103,7 @@ impl DatePattern {
     where
         Tz::Offset: Copy,
     {
-        let d =
-            parse_date_string(s, now, Dialect::Us).map_err(DatePatternParseError::ParseError)?;
-        let millis_since_epoch = MillisSinceEpoch(d.timestamp_millis());
+        let millis_since_epoch = parse_date(s, now)?;
         match kind {
             "after" => Ok(Self::AtOrAfter(millis_since_epoch)),
             "before" => Ok(Self::Before(millis_since_epoch)),
@@ -122,6 +120,18 @@ impl DatePattern {
     }
 }
 
+/// Parses an absolute or relative date such as "2024-03-10" or "2 weeks ago".
+///
+/// Relative dates are resolved against `now`. See
+/// [`DatePattern::from_str_kind()`] for why this is a [`DateTime<Tz>`].
+pub fn parse_date<Tz: TimeZone>(s: &str, now: DateTime<Tz>) -> Result<MillisSinceEpoch, DateError>
+where
+    Tz::Offset: Copy,
+{
+    let d = parse_date_string(s, now, Dialect::Us)?;
+    Ok(MillisSinceEpoch(d.timestamp_millis()))
+}
+
 // @TODO ideally we would have this unified with the other parsing code. However
 // we use the interim crate which does not handle explicitly given time zone
 // information
@@ -202,6 +212,22 @@ mod tests {
         test_equal(now, "yesterday 10:30", "2023-12-31T18:30:00Z");
     }
 
+    #[test]
+    fn test_parse_date() {
+        let now = DateTime::parse_from_rfc3339("2024-01-01T00:00:00-08:00").unwrap();
+        let millis =
+            |s| MillisSinceEpoch(DateTime::parse_from_rfc3339(s).unwrap().timestamp_millis());
+        assert_eq!(
+            parse_date("2 weeks ago", now).unwrap(),
+            millis("2023-12-18T08:00:00Z")
+        );
+        assert_eq!(
+            parse_date("2023-03-25", now).unwrap(),
+            millis("2023-03-25T08:00:00Z")
+        );
+        assert!(parse_date("foobar", now).is_err());
+    }
+
     #[test]
     fn test_parse_datetime_non_sense_yields_error() {
         use chrono::format::ParseErrorKind;
@@ -21,6 +21,7 @@ use std::iter::zip;
 use std::sync::Arc;
 use std::vec;
 
+use bstr::BString;
 use futures::FutureExt as _;
 use futures::StreamExt as _;
 use futures::future::BoxFuture;
@@ -35,12 +36,19 @@ use crate::backend::BackendResult;
 use crate::backend::TreeId;
 use crate::backend::TreeValue;
 use crate::config::ConfigGetError;
+use crate::conflict_labels::ConflictLabels;
 use crate::files;
 use crate::files::FileMergeHunkLevel;
+use crate::files::MergeResult;
+use crate::gitattributes::GitAttributesLookup;
 use crate::merge::Merge;
 use crate::merge::MergedTreeVal;
 use crate::merge::MergedTreeValue;
 use crate::merge::SameChange;
+use crate::merge_driver;
+use crate::merge_driver::ExternalMergeError;
+use crate::merge_driver::MergeDriver;
+use crate::merged_tree::MergedTree;
 use crate::merged_tree::all_merged_tree_entries;
 use crate::object_id::ObjectId as _;
 use crate::repo_path::RepoPath;
@@ -79,8 +87,15 @@ pub async fn merge_trees(store: &Arc<Store>, merge: Merge<TreeId>) -> BackendRes
         Err(merge) => merge,
     };
 
+    // Attributes are looked up only for files that need content merging.
+    let attributes = GitAttributesLookup::from_tree(MergedTree::new(
+        store.clone(),
+        merge.clone(),
+        ConflictLabels::unlabeled(),
+    ));
     let mut merger = TreeMerger {
         store: store.clone(),
+        attributes: Arc::new(attributes),
         trees_to_resolve: BTreeMap::new(),
         work: FuturesUnordered::new(),
         unstarted_work: BTreeMap::new(),
@@ -195,6 +210,8 @@ enum TreeMergeWorkItemKey {
 
 struct TreeMerger {
     store: Arc<Store>,
+    // Attributes of the paths, which select the merge driver of files.
+    attributes: Arc<GitAttributesLookup>,
     // Trees we're currently working on.
     trees_to_resolve: BTreeMap<RepoPathBuf, MergedTreeInput>,
     // Futures we're currently processing. In order to respect the backend's concurrency limit.
@@ -305,8 +322,13 @@ impl TreeMerger {
 
     fn enqueue_file_merge(&mut self, path: RepoPathBuf, value: MergedTreeValue) {
         let key = TreeMergeWorkItemKey::MergeFiles { path: path.clone() };
-        let work_fut = resolve_file_values_owned(self.store.clone(), path.clone(), value)
-            .map(|result| TreeMergerWorkOutput::MergedFiles { path, result });
+        let work_fut = resolve_file_values_owned(
+            self.store.clone(),
+            self.attributes.clone(),
+            path.clone(),
+            value,
+        )
+        .map(|result| TreeMergerWorkOutput::MergedFiles { path, result });
         if self.work.len() < self.store.concurrency() {
             self.work.push(Box::pin(work_fut));
         } else {
@@ -358,10 +380,11 @@ async fn write_trees(
 
 async fn resolve_file_values_owned(
     store: Arc<Store>,
+    attributes: Arc<GitAttributesLookup>,
     path: RepoPathBuf,
     values: MergedTreeValue,
 ) -> BackendResult<MergedTreeValue> {
-    let maybe_resolved = try_resolve_file_values(&store, &path, &values).await?;
+    let maybe_resolved = try_resolve_file_values(&store, Some(&attributes), &path, &values).await?;
     Ok(maybe_resolved.unwrap_or(values))
 }
 
@@ -378,12 +401,13 @@ pub async fn resolve_file_values(
         return Ok(Merge::resolved(resolved.clone()));
     }
 
-    let maybe_resolved = try_resolve_file_values(store, path, &values).await?;
+    let maybe_resolved = try_resolve_file_values(store, None, path, &values).await?;
     Ok(maybe_resolved.unwrap_or(values))
 }
 
 async fn try_resolve_file_values<T: Borrow<TreeValue>>(
     store: &Arc<Store>,
+    attributes: Option<&GitAttributesLookup>,
     path: &RepoPath,
     values: &Merge<Option<T>>,
 ) -> BackendResult<Option<MergedTreeValue>> {
@@ -394,7 +418,7 @@ async fn try_resolve_file_values<T: Borrow<TreeValue>>(
         .simplify();
     // No fast path for simplified.is_resolved(). If it could be resolved, it would
     // have been caught by values.resolve_trivial() above.
-    if let Some(resolved) = try_resolve_file_conflict(store, path, &simplified).await? {
+    if let Some(resolved) = try_resolve_file_conflict(store, attributes, path, &simplified).await? {
         Ok(Some(Merge::normal(resolved)))
     } else {
         // Failed to merge the files, or the paths are not files
@@ -405,9 +429,11 @@ async fn try_resolve_file_values<T: Borrow<TreeValue>>(
 /// Resolves file-level conflict by merging content hunks.
 ///
 /// The input `conflict` is supposed to be simplified. It shouldn't contain
-/// non-file values that cancel each other.
+/// non-file values that cancel each other. If `attributes` are given, the
+/// contents are merged by the driver selected by the `merge` attribute.
 async fn try_resolve_file_conflict(
     store: &Store,
+    attributes: Option<&GitAttributesLookup>,
     filename: &RepoPath,
     conflict: &MergedTreeVal<'_>,
 ) -> BackendResult<Option<TreeValue>> {
@@ -474,6 +500,21 @@ async fn try_resolve_file_conflict(
     //    cannot
     let file_id_conflict = file_id_conflict.simplify();
 
+    let driver = attributes.map_or(MergeDriver::Text, |attributes| {
+        MergeDriver::from_attribute(&attributes.get(filename, "merge"))
+    });
+    match &driver {
+        MergeDriver::Binary => return Ok(None),
+        MergeDriver::Ours => {
+            return Ok(Some(TreeValue::File {
+                id: (*file_id_conflict.first()).clone(),
+                executable,
+                copy_id: copy_id.clone(),
+            }));
+        }
+        MergeDriver::Text | MergeDriver::Union | MergeDriver::External(_) => {}
+    }
+
     let contents = file_id_conflict
         .try_map_async(async |file_id| {
             let mut content = vec![];
@@ -489,16 +530,56 @@ async fn try_resolve_file_conflict(
             BackendResult::Ok(content)
         })
         .await?;
-    if let Some(merged_content) = files::try_merge(&contents, options) {
-        let id = store
-            .write_file(filename, &mut merged_content.as_slice())
-            .await?;
-        Ok(Some(TreeValue::File {
-            id,
-            executable,
-            copy_id: copy_id.clone(),
-        }))
-    } else {
-        Ok(None)
+    let merged_content = match &driver {
+        MergeDriver::Union => Some(merge_driver::union_merge(&contents, options)),
+        MergeDriver::External(name) => match store.merge_drivers().get(name) {
+            Some(external) => external.merge(filename, &contents).map_err(|source| {
+                BackendError::Other(Box::new(ExternalMergeError {
+                    name: name.clone(),
+                    path: filename.to_owned(),
+                    source,
+                }))
+            })?,
+            // Undefined drivers fall back to the built-in merge as in Git.
+            None => files::try_merge(&contents, options),
+        },
+        MergeDriver::Text | MergeDriver::Binary | MergeDriver::Ours => {
+            files::try_merge(&contents, options)
+        }
+    };
+    let Some(merged_content) =
+        merged_content.or_else(|| resolve_from_recorded(store, filename, &contents))
+    else {
+        return Ok(None);
+    };
+    let id = store
+        .write_file(filename, &mut merged_content.as_slice())
+        .await?;
+    Ok(Some(TreeValue::File {
+        id,
+        executable,
+        copy_id: copy_id.clone(),
+    }))
+}
+
+/// Resolves the file conflict by using the previously recorded resolutions of
+/// its conflicting hunks, if enabled.
+fn resolve_from_recorded(
+    store: &Store,
+    filename: &RepoPath,
+    contents: &Merge<Vec<u8>>,
+) -> Option<BString> {
+    let resolutions = store.resolution_store()?;
+    let MergeResult::Conflict(hunks) = files::merge_hunks(contents, store.merge_options()) else {
+        return None;
+    };
+    match resolutions.resolve_hunks(filename, &hunks) {
+        Ok(content) => content,
+        Err(err) => {
+            // The recorded resolutions are only a cache of the user's earlier
+            // choices, so leave the conflict as is if they can't be read.
+            tracing::warn!(?err, ?filename, "failed to read recorded resolutions");
+            None
+        }
     }
 }
@@ -22,6 +22,7 @@ mod test_gpg;
 mod test_id_prefix;
 mod test_index;
 mod test_init;
+mod test_lfs;
 mod test_load_repo;
 mod test_local_working_copy;
 mod test_local_working_copy_concurrent;
@@ -38,6 +39,7 @@ mod test_rewrite;
 mod test_rewrite_duplicate;
 mod test_rewrite_transform;
 mod test_signing;
+mod test_simple_backend;
 mod test_ssh_signing;
 mod test_view;
 mod test_workspace;
@@ -32,6 +32,7 @@ use jj_lib::revset::ResolvedRevsetExpression;
 use jj_lib::revset::RevsetExpression;
 use testutils::CommitBuilderExt as _;
 use testutils::TestRepo;
+use testutils::TestRepoBackend;
 use testutils::create_tree;
 use testutils::read_file;
 use testutils::repo_path;
@@ -410,3 +411,138 @@ fn test_annotate_file_directory_transition() {
 
     insta::assert_snapshot!(annotate(tx.repo(), &commit2, file_path2), @"commit2:1 : 2");
 }
+
+#[test]
+fn test_annotate_renamed_and_copied() {
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Git);
+    let repo = &test_repo.repo;
+
+    let root_commit_id = repo.store().root_commit_id();
+    let file_path1 = repo_path("file1");
+    let file_path2 = repo_path("file2");
+    let file_path3 = repo_path("file3");
+
+    // 5    "1 2 3" at file2, "1 2 3 4 5" copied to file3
+    // |
+    // 4    "1 2 3 4" at file2
+    // |
+    // 3    "1 2 3" renamed to file2
+    // |
+    // 2    "1 2 3" at file1
+    // |
+    // 1    "1 2" at file1
+    let mut tx = repo.start_transaction();
+    let mut create_commit = create_commit_fn(tx.repo_mut());
+    let tree1 = create_tree(repo, &[(file_path1, "1\n2\n")]);
+    let tree2 = create_tree(repo, &[(file_path1, "1\n2\n3\n")]);
+    let tree3 = create_tree(repo, &[(file_path2, "1\n2\n3\n")]);
+    let tree4 = create_tree(repo, &[(file_path2, "1\n2\n3\n4\n")]);
+    let tree5 = create_tree(
+        repo,
+        &[(file_path2, "1\n2\n3\n"), (file_path3, "1\n2\n3\n4\n5\n")],
+    );
+    let commit1 = create_commit("commit1", &[root_commit_id], tree1);
+    let commit2 = create_commit("commit2", &[commit1.id()], tree2);
+    let commit3 = create_commit("commit3", &[commit2.id()], tree3);
+    let commit4 = create_commit("commit4", &[commit3.id()], tree4);
+    let commit5 = create_commit("commit5", &[commit4.id()], tree5);
+    drop(create_commit);
+
+    let format_with_paths = |commit: &Commit, file_path: &RepoPath| {
+        let mut annotator = FileAnnotator::from_commit(commit, file_path).unwrap();
+        annotator
+            .compute(tx.repo(), &RevsetExpression::all())
+            .unwrap();
+        let mut output = String::new();
+        for (origin, line) in annotator.to_annotation().line_origins() {
+            let line_origin = origin.unwrap_or_else(|line_origin| line_origin);
+            let commit = tx
+                .repo()
+                .store()
+                .get_commit(&line_origin.commit_id)
+                .unwrap();
+            let desc = commit.description().trim_end();
+            let path = line_origin.path.as_internal_file_string();
+            let line_number = line_origin.line_number + 1;
+            let sigil = if origin.is_err() { '*' } else { ' ' };
+            write!(output, "{desc}:{path}:{line_number}{sigil}: {line}").unwrap();
+        }
+        output
+    };
+
+    // The lines are traced to the commits before the rename
+    insta::assert_snapshot!(format_with_paths(&commit4, file_path2), @"
+    commit1:file1:1 : 1
+    commit1:file1:2 : 2
+    commit2:file1:3 : 3
+    commit4:file2:4 : 4
+    ");
+
+    // The lines are traced through the copy source. (The Git backend only
+    // detects copies from modified files.)
+    insta::assert_snapshot!(format_with_paths(&commit5, file_path3), @"
+    commit1:file1:1 : 1
+    commit1:file1:2 : 2
+    commit2:file1:3 : 3
+    commit4:file2:4 : 4
+    commit5:file3:5 : 5
+    ");
+}
+
+#[test]
+fn test_annotate_ignored() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let root_commit_id = repo.store().root_commit_id();
+    let file_path = repo_path("file");
+
+    let mut tx = repo.start_transaction();
+    let mut create_commit = create_commit_fn(tx.repo_mut());
+    let tree1 = create_tree(repo, &[(file_path, "1\n2 3\n4\n")]);
+    let tree2 = create_tree(repo, &[(file_path, "1\n2\n3\n4\nnew\n")]);
+    let tree3 = create_tree(repo, &[(file_path, "1\n2\n3\n4\nnew\n5\n")]);
+    let commit1 = create_commit("commit1", &[root_commit_id], tree1);
+    let commit2 = create_commit("commit2", &[commit1.id()], tree2);
+    let commit3 = create_commit("commit3", &[commit2.id()], tree3);
+    drop(create_commit);
+
+    let annotate_ignoring = |ignored: &[&CommitId]| {
+        let mut annotator = FileAnnotator::from_commit(&commit3, file_path).unwrap();
+        let ignored = RevsetExpression::commits(ignored.iter().copied().cloned().collect());
+        annotator
+            .compute_ignoring(tx.repo(), &RevsetExpression::all(), &ignored)
+            .unwrap();
+        format_annotation(tx.repo(), &annotator.to_annotation())
+    };
+
+    insta::assert_snapshot!(annotate_ignoring(&[]), @"
+    commit1:1 : 1
+    commit2:2 : 2
+    commit2:3 : 3
+    commit1:3 : 4
+    commit2:5 : new
+    commit3:6 : 5
+    ");
+
+    // The split lines are attributed to the line they replaced, but the added
+    // line is still attributed to the ignored commit.
+    insta::assert_snapshot!(annotate_ignoring(&[commit2.id()]), @"
+    commit1:1 : 1
+    commit1:2 : 2
+    commit1:2 : 3
+    commit1:3 : 4
+    commit2:5 : new
+    commit3:6 : 5
+    ");
+
+    // Ignored commits which didn't change the file don't matter
+    insta::assert_snapshot!(annotate_ignoring(&[commit2.id(), root_commit_id]), @"
+    commit1:1 : 1
+    commit1:2 : 2
+    commit1:2 : 3
+    commit1:3 : 4
+    commit2:5 : new
+    commit3:6 : 5
+    ");
+}
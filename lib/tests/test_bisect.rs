@@ -179,6 +179,36 @@ fn test_bisect_linear() {
     assert_eq!(result, BisectionResult::Found(vec![commit4.clone()]));
 }
 
+#[test]
+fn test_bisect_num_candidates() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction();
+    let commit1 = write_random_commit(tx.repo_mut());
+    let commit2 = write_random_commit_with_parents(tx.repo_mut(), &[&commit1]);
+    let commit3 = write_random_commit_with_parents(tx.repo_mut(), &[&commit2]);
+    let commit4 = write_random_commit_with_parents(tx.repo_mut(), &[&commit3]);
+    let commit5 = write_random_commit_with_parents(tx.repo_mut(), &[&commit4]);
+
+    let input_range = ResolvedRevsetExpression::commit(commit5.id().clone())
+        .ancestors()
+        .minus(&ResolvedRevsetExpression::commit(commit1.id().clone()).ancestors());
+    let mut bisector = Bisector::new(tx.repo(), input_range).unwrap();
+    // The head is assumed to be bad
+    assert_eq!(bisector.num_candidates().unwrap(), 3);
+    bisector.mark_skipped(commit3.id().clone());
+    assert_eq!(bisector.num_candidates().unwrap(), 2);
+    bisector.mark_good(commit2.id().clone());
+    assert_eq!(bisector.num_candidates().unwrap(), 1);
+    bisector.mark_bad(commit4.id().clone());
+    assert_eq!(bisector.num_candidates().unwrap(), 0);
+    assert_eq!(
+        bisector.next_step().unwrap(),
+        NextStep::Done(BisectionResult::Found(vec![commit4.clone()]))
+    );
+}
+
 #[test]
 fn test_bisect_nonlinear() {
     let test_repo = TestRepo::init();
@@ -16,10 +16,12 @@ use std::fs::File;
 use std::io::Write as _;
 
 use bstr::ByteSlice as _;
+use jj_lib::backend::TreeValue;
 use jj_lib::config::ConfigLayer;
 use jj_lib::config::ConfigSource;
 use jj_lib::repo::Repo as _;
 use jj_lib::repo::StoreFactories;
+use jj_lib::repo_path::RepoPath;
 use jj_lib::rewrite::merge_commit_trees;
 use jj_lib::settings::UserSettings;
 use jj_lib::workspace::Workspace;
@@ -589,3 +591,56 @@ fn test_eol_conversion_checkout(
     assert!(std::fs::exists(&file_disk_path).unwrap());
     std::fs::read(&file_disk_path).unwrap()
 }
+
+#[test]
+fn test_eol_conversion_gitattributes() {
+    // The text and eol attributes take precedence over the
+    // working-copy.eol-conversion setting.
+    let user_settings =
+        base_user_settings_with_extra_configs("working-copy.eol-conversion = \"input-output\"\n");
+    let mut test_workspace =
+        TestWorkspace::init_with_backend_and_settings(TestRepoBackend::Git, &user_settings);
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+    let gitattributes_path = repo_path(".gitattributes");
+    let crlf_path = repo_path("file.bat");
+    let lf_path = repo_path("file.sh");
+    let no_text_path = repo_path("file.txt");
+    testutils::write_working_copy_file(
+        &workspace_root,
+        gitattributes_path,
+        "*.bat eol=crlf\n*.sh eol=lf\n*.txt -text\n",
+    );
+    for path in [crlf_path, lf_path, no_text_path] {
+        testutils::write_working_copy_file(&workspace_root, path, CRLF_FILE_CONTENT);
+    }
+    let tree = test_workspace.snapshot().unwrap();
+    let read_stored = |path| {
+        let value = tree.path_value(path).unwrap();
+        let Some(Some(TreeValue::File { id, .. })) = value.as_resolved() else {
+            panic!("unexpected value at {path:?}: {value:?}");
+        };
+        testutils::read_file(test_workspace.repo.store(), path, id)
+    };
+    assert_eq!(read_stored(crlf_path), LF_FILE_CONTENT);
+    assert_eq!(read_stored(lf_path), LF_FILE_CONTENT);
+    assert_eq!(read_stored(no_text_path), CRLF_FILE_CONTENT);
+    let commit = commit_with_tree(test_workspace.repo.store(), tree.clone());
+
+    // Check out the root commit and back, so that the files are recreated.
+    let root_commit = test_workspace.repo.store().root_commit();
+    let workspace = &mut test_workspace.workspace;
+    workspace
+        .check_out(test_workspace.repo.op_id().clone(), None, &root_commit)
+        .block_on()
+        .unwrap();
+    workspace
+        .check_out(test_workspace.repo.op_id().clone(), None, &commit)
+        .block_on()
+        .unwrap();
+    let read_disk = |path: &RepoPath| std::fs::read(path.to_fs_path(&workspace_root).unwrap());
+    assert_eq!(read_disk(crlf_path).unwrap(), CRLF_FILE_CONTENT);
+    assert_eq!(read_disk(lf_path).unwrap(), LF_FILE_CONTENT);
+    assert_eq!(read_disk(no_text_path).unwrap(), CRLF_FILE_CONTENT);
+    let new_tree = test_workspace.snapshot().unwrap();
+    assert_tree_eq!(new_tree, tree, "The working copy should be clean.");
+}
@@ -14,6 +14,7 @@
 
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::ops::Range;
 
 use jj_lib::backend::CommitId;
 use jj_lib::backend::FileId;
@@ -30,6 +31,7 @@ use jj_lib::transaction::Transaction;
 use pollster::FutureExt as _;
 use testutils::CommitBuilderExt as _;
 use testutils::TestRepo;
+use testutils::TestRepoBackend;
 use testutils::assert_tree_eq;
 use testutils::create_tree;
 use testutils::create_tree_with;
@@ -118,6 +120,7 @@ fn test_fix_one_file() {
         root_commits,
         &EverythingMatcher,
         include_unchanged_files,
+        false,
         tx.repo_mut(),
         &mut file_fixer,
     )
@@ -155,6 +158,7 @@ fn test_fixer_does_not_change_content() {
         root_commits,
         &EverythingMatcher,
         include_unchanged_files,
+        false,
         tx.repo_mut(),
         &mut file_fixer,
     )
@@ -183,6 +187,7 @@ fn test_empty_commit() {
         root_commits,
         &EverythingMatcher,
         include_unchanged_files,
+        false,
         tx.repo_mut(),
         &mut file_fixer,
     )
@@ -212,6 +217,7 @@ fn test_fixer_fails() {
         root_commits,
         &EverythingMatcher,
         include_unchanged_files,
+        false,
         tx.repo_mut(),
         &mut file_fixer,
     )
@@ -242,6 +248,7 @@ fn test_unchanged_file_is_not_fixed() {
         root_commits,
         &EverythingMatcher,
         include_unchanged_files,
+        false,
         tx.repo_mut(),
         &mut file_fixer,
     )
@@ -273,6 +280,7 @@ fn test_unchanged_file_is_fixed() {
         root_commits,
         &EverythingMatcher,
         true,
+        false,
         tx.repo_mut(),
         &mut file_fixer,
     )
@@ -314,6 +322,7 @@ fn test_already_fixed_descendant() {
         root_commits,
         &EverythingMatcher,
         true,
+        false,
         tx.repo_mut(),
         &mut file_fixer,
     )
@@ -356,6 +365,7 @@ fn test_parallel_fixer_basic() {
         root_commits,
         &EverythingMatcher,
         include_unchanged_files,
+        false,
         tx.repo_mut(),
         &mut parallel_fixer,
     )
@@ -396,6 +406,7 @@ fn test_parallel_fixer_fixes_files() {
         root_commits,
         &EverythingMatcher,
         include_unchanged_files,
+        false,
         tx.repo_mut(),
         &mut parallel_fixer,
     )
@@ -441,6 +452,7 @@ fn test_parallel_fixer_does_not_change_content() {
         root_commits,
         &EverythingMatcher,
         include_unchanged_files,
+        false,
         tx.repo_mut(),
         &mut parallel_fixer,
     )
@@ -481,6 +493,7 @@ fn test_parallel_fixer_no_changes_upon_partial_failure() {
         root_commits,
         &EverythingMatcher,
         include_unchanged_files,
+        false,
         tx.repo_mut(),
         &mut parallel_fixer,
     )
@@ -524,6 +537,7 @@ fn test_fix_multiple_revisions() {
         root_commits,
         &EverythingMatcher,
         include_unchanged_files,
+        false,
         tx.repo_mut(),
         &mut file_fixer,
     )
@@ -538,3 +552,133 @@ fn test_fix_multiple_revisions() {
         .unwrap();
     assert_tree_eq!(new_commit_a.tree(), expected_tree_a);
 }
+
+// A file fixer that records the changed line ranges of each file it sees,
+// without changing anything.
+struct LineRangesRecorder {
+    line_ranges: Vec<(String, Vec<Range<usize>>)>,
+}
+
+impl FileFixer for LineRangesRecorder {
+    fn fix_files<'a>(
+        &mut self,
+        store: &Store,
+        files_to_fix: &'a HashSet<FileToFix>,
+    ) -> Result<HashMap<&'a FileToFix, FileId>, FixError> {
+        for file_to_fix in files_to_fix {
+            let content = read_file(store, &file_to_fix.repo_path, &file_to_fix.file_id);
+            self.line_ranges.push((
+                String::from_utf8(content).unwrap(),
+                file_to_fix.line_ranges.clone(),
+            ));
+        }
+        Ok(HashMap::new())
+    }
+}
+
+#[test]
+#[expect(clippy::single_range_in_vec_init)]
+fn test_fix_line_ranges() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    // D
+    // |
+    // C
+    // |
+    // B
+    // |
+    // A
+    let mut tx = repo.start_transaction();
+    let path1 = repo_path("file1");
+    let path2 = repo_path("file2");
+    let tree1 = create_tree(repo, &[(path1, "a\nb\nc\n")]);
+    let commit_a = create_commit(&mut tx, vec![repo.store().root_commit_id().clone()], tree1);
+    let tree2 = create_tree(repo, &[(path1, "a\nB\nc\n")]);
+    let commit_b = create_commit(&mut tx, vec![commit_a.clone()], tree2);
+    let tree3 = create_tree(repo, &[(path1, "a\nB\nc\nd\n")]);
+    let commit_c = create_commit(&mut tx, vec![commit_b.clone()], tree3);
+    let tree4 = create_tree(repo, &[(path1, "a\nB\nc\nd\n"), (path2, "x\n")]);
+    create_commit(&mut tx, vec![commit_c.clone()], tree4);
+
+    // The ranges in descendants include the changes in their fixed ancestors
+    let mut file_fixer = LineRangesRecorder {
+        line_ranges: vec![],
+    };
+    fix_files(
+        vec![commit_b.clone()],
+        &EverythingMatcher,
+        false,
+        true,
+        tx.repo_mut(),
+        &mut file_fixer,
+    )
+    .block_on()
+    .unwrap();
+    file_fixer.line_ranges.sort_by(|(a, _), (b, _)| a.cmp(b));
+    assert_eq!(
+        file_fixer.line_ranges,
+        vec![
+            ("a\nB\nc\n".to_owned(), vec![1..2]),
+            ("a\nB\nc\nd\n".to_owned(), vec![1..2, 3..4]),
+            ("x\n".to_owned(), vec![0..1]),
+        ]
+    );
+
+    // Unchanged files are fixed entirely
+    let mut file_fixer = LineRangesRecorder {
+        line_ranges: vec![],
+    };
+    fix_files(
+        vec![commit_c.clone()],
+        &EverythingMatcher,
+        true,
+        true,
+        tx.repo_mut(),
+        &mut file_fixer,
+    )
+    .block_on()
+    .unwrap();
+    file_fixer.line_ranges.sort_by(|(a, _), (b, _)| a.cmp(b));
+    assert_eq!(
+        file_fixer.line_ranges,
+        vec![
+            ("a\nB\nc\nd\n".to_owned(), vec![0..4]),
+            ("x\n".to_owned(), vec![0..1]),
+        ]
+    );
+}
+
+#[test]
+#[expect(clippy::single_range_in_vec_init)]
+fn test_fix_line_ranges_renamed_file() {
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Git);
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction();
+    let path1 = repo_path("file1");
+    let path2 = repo_path("file2");
+    let tree1 = create_tree(repo, &[(path1, "a\nb\nc\nd\n")]);
+    let commit_a = create_commit(&mut tx, vec![repo.store().root_commit_id().clone()], tree1);
+    let tree2 = create_tree(repo, &[(path2, "a\nB\nc\nd\n")]);
+    let commit_b = create_commit(&mut tx, vec![commit_a], tree2);
+
+    // Only the lines changed since the rename source are included
+    let mut file_fixer = LineRangesRecorder {
+        line_ranges: vec![],
+    };
+    fix_files(
+        vec![commit_b],
+        &EverythingMatcher,
+        false,
+        true,
+        tx.repo_mut(),
+        &mut file_fixer,
+    )
+    .block_on()
+    .unwrap();
+    assert_eq!(
+        file_fixer.line_ranges,
+        vec![("a\nB\nc\nd\n".to_owned(), vec![1..2])]
+    );
+}
@@ -4396,6 +4396,7 @@ fn test_push_bookmarks_success() {
         subprocess_options,
         "origin".as_ref(),
         &targets,
+        &[],
         &mut NullCallback,
     )
     .unwrap();
@@ -4475,6 +4476,7 @@ fn test_push_bookmarks_deletion() {
         subprocess_options,
         "origin".as_ref(),
         &targets,
+        &[],
         &mut NullCallback,
     )
     .unwrap();
@@ -4553,6 +4555,7 @@ fn test_push_bookmarks_mixed_deletion_and_addition() {
         subprocess_options,
         "origin".as_ref(),
         &targets,
+        &[],
         &mut NullCallback,
     )
     .unwrap();
@@ -4634,6 +4637,7 @@ fn test_push_bookmarks_not_fast_forward() {
         subprocess_options,
         "origin".as_ref(),
         &targets,
+        &[],
         &mut NullCallback,
     )
     .unwrap();
@@ -4687,6 +4691,7 @@ fn test_push_bookmarks_partial_success() {
         subprocess_options,
         "origin".as_ref(),
         &targets,
+        &[],
         &mut NullCallback,
     )
     .unwrap();
@@ -4796,6 +4801,7 @@ fn test_push_bookmarks_unmapped_refs() {
         subprocess_options,
         "origin".as_ref(),
         &targets,
+        &[],
         &mut NullCallback,
     )
     .unwrap();
@@ -5182,6 +5188,7 @@ fn test_push_environment_options() {
         subprocess_options,
         "origin".as_ref(),
         &targets,
+        &[],
         &mut NullCallback,
     )
     .unwrap();
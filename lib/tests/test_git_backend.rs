@@ -15,16 +15,23 @@
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::path::Path;
+use std::slice;
 use std::sync::Arc;
 use std::time::Duration;
 use std::time::SystemTime;
 
+use assert_matches::assert_matches;
 use futures::executor::block_on_stream;
 use itertools::Itertools as _;
 use jj_lib::backend::CommitId;
+use jj_lib::backend::CopyHistory;
+use jj_lib::backend::CopyId;
 use jj_lib::backend::CopyRecord;
+use jj_lib::backend::TreeId;
+use jj_lib::backend::TreeValue;
 use jj_lib::commit::Commit;
 use jj_lib::conflict_labels::ConflictLabels;
+use jj_lib::gc::GcOptions;
 use jj_lib::git_backend::GitBackend;
 use jj_lib::git_backend::JJ_TREES_COMMIT_HEADER;
 use jj_lib::merge::Merge;
@@ -49,6 +56,7 @@ use testutils::commit_with_tree;
 use testutils::create_random_commit;
 use testutils::create_single_tree;
 use testutils::create_tree;
+use testutils::create_tree_with;
 use testutils::is_external_tool_installed;
 use testutils::repo_path;
 use testutils::repo_path_buf;
@@ -170,7 +178,10 @@ fn test_gc() {
     // Empty index, but all kept by file modification time
     // (Beware that this invokes "git gc" and refs will be packed.)
     repo.store()
-        .gc(base_index.as_index(), SystemTime::UNIX_EPOCH)
+        .gc(
+            base_index.as_index(),
+            &GcOptions::new(SystemTime::UNIX_EPOCH),
+        )
         .unwrap();
     assert_eq!(
         collect_no_gc_refs(git_repo_path),
@@ -191,7 +202,9 @@ fn test_gc() {
     let now = || SystemTime::now() + Duration::from_secs(1);
 
     // All reachable: redundant no-gc refs will be removed
-    repo.store().gc(repo.index(), now()).unwrap();
+    repo.store()
+        .gc(repo.index(), &GcOptions::new(now()))
+        .unwrap();
     assert_eq!(
         collect_no_gc_refs(git_repo_path),
         hashset! {
@@ -210,7 +223,20 @@ fn test_gc() {
     mut_index.add_commit(&commit_e).block_on().unwrap();
     mut_index.add_commit(&commit_f).block_on().unwrap();
     mut_index.add_commit(&commit_h).block_on().unwrap();
-    repo.store().gc(mut_index.as_index(), now()).unwrap();
+    let options = GcOptions {
+        keep_newer: now(),
+        dry_run: true,
+    };
+    let stats = repo.store().gc(mut_index.as_index(), &options).unwrap();
+    assert_eq!(stats.commits, 1);
+    assert_eq!(stats.extras_entries, 1);
+    assert_eq!(collect_no_gc_refs(git_repo_path).len(), 3);
+    let stats = repo
+        .store()
+        .gc(mut_index.as_index(), &GcOptions::new(now()))
+        .unwrap();
+    assert_eq!(stats.commits, 1);
+    assert_eq!(stats.extras_entries, 1);
     assert_eq!(
         collect_no_gc_refs(git_repo_path),
         hashset! {
@@ -226,7 +252,9 @@ fn test_gc() {
     mut_index.add_commit(&commit_b).block_on().unwrap();
     mut_index.add_commit(&commit_c).block_on().unwrap();
     mut_index.add_commit(&commit_f).block_on().unwrap();
-    repo.store().gc(mut_index.as_index(), now()).unwrap();
+    repo.store()
+        .gc(mut_index.as_index(), &GcOptions::new(now()))
+        .unwrap();
     assert_eq!(
         collect_no_gc_refs(git_repo_path),
         hashset! {
@@ -238,7 +266,9 @@ fn test_gc() {
     // B|C|F are no longer reachable
     let mut mut_index = base_index.start_modification();
     mut_index.add_commit(&commit_a).block_on().unwrap();
-    repo.store().gc(mut_index.as_index(), now()).unwrap();
+    repo.store()
+        .gc(mut_index.as_index(), &GcOptions::new(now()))
+        .unwrap();
     assert_eq!(
         collect_no_gc_refs(git_repo_path),
         hashset! {
@@ -247,7 +277,9 @@ fn test_gc() {
     );
 
     // All unreachable
-    repo.store().gc(base_index.as_index(), now()).unwrap();
+    repo.store()
+        .gc(base_index.as_index(), &GcOptions::new(now()))
+        .unwrap();
     assert_eq!(collect_no_gc_refs(git_repo_path), hashset! {});
 }
 
@@ -299,13 +331,15 @@ fn test_gc_extra_table() {
     let index = repo.readonly_index().as_index();
 
     // All segments should be kept by modification time
-    repo.store().gc(index, SystemTime::UNIX_EPOCH).unwrap();
+    repo.store()
+        .gc(index, &GcOptions::new(SystemTime::UNIX_EPOCH))
+        .unwrap();
     assert_eq!(collect_extra_segment_num_entries(), [3, 1]);
     assert_eq!(list_dir(&extra_path).len(), 5 + 1);
 
     // All unreachable segments should be removed
     let now = SystemTime::now() + Duration::from_secs(1);
-    repo.store().gc(index, now).unwrap();
+    repo.store().gc(index, &GcOptions::new(now)).unwrap();
     assert_eq!(collect_extra_segment_num_entries(), [3, 1]);
     assert_eq!(list_dir(&extra_path).len(), 2 + 1);
 
@@ -313,6 +347,114 @@ fn test_gc_extra_table() {
     load_repo();
 }
 
+#[test]
+fn test_gc_copy_tables() {
+    if !is_external_tool_installed("git") {
+        eprintln!("Skipping because git command might fail to run");
+        return;
+    }
+
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Git);
+    let repo = &test_repo.repo;
+    let store_path = test_repo.repo_path().join("store");
+    let write_copy = |parents: Vec<CopyId>| {
+        repo.store()
+            .write_copy(&CopyHistory {
+                current_path: repo_path_buf("file"),
+                parents,
+                salt: vec![],
+            })
+            .block_on()
+            .unwrap()
+    };
+    let create_tree_with_copy_id = |copy_id: &CopyId| {
+        create_tree_with(repo, |builder| {
+            builder.file(repo_path("file"), "").copy_id(copy_id.clone());
+        })
+    };
+    let collect_table_keys = |name: &str, key_size: usize| {
+        TableStore::load(store_path.join(name), key_size)
+            .get_head()
+            .unwrap()
+            .ancestor_segments()
+            .flat_map(|segment| {
+                segment
+                    .segment_entries()
+                    .map(|(key, _)| key.to_vec())
+                    .collect_vec()
+            })
+            .collect::<HashSet<_>>()
+    };
+
+    // The copy history of the committed tree and its parent are reachable.
+    // The other copy history and the tree copy ids referring to it aren't.
+    let copy_id_parent = write_copy(vec![]);
+    let copy_id_kept = write_copy(vec![copy_id_parent.clone()]);
+    let copy_id_pruned = write_copy(vec![]);
+    let tree_kept = create_tree_with_copy_id(&copy_id_kept);
+    let tree_pruned = create_tree_with_copy_id(&copy_id_pruned);
+    let mut tx = repo.start_transaction();
+    tx.repo_mut()
+        .new_commit(
+            vec![repo.store().root_commit_id().clone()],
+            tree_kept.clone(),
+        )
+        .write()
+        .block_on()
+        .unwrap();
+    tx.commit("test").block_on().unwrap();
+
+    let copy_key_size = copy_id_kept.as_bytes().len();
+    let tree_id_kept = tree_kept.tree_ids().as_resolved().unwrap();
+    let tree_id_pruned = tree_pruned.tree_ids().as_resolved().unwrap();
+    let tree_key = |id: &TreeId| {
+        let hash_size = repo.store().root_commit_id().as_bytes().len();
+        id.as_bytes()[hash_size..].to_vec()
+    };
+    let tree_key_size = tree_key(tree_id_kept).len();
+    assert_eq!(
+        collect_table_keys("copies", copy_key_size),
+        hashset! {
+            copy_id_parent.to_bytes(),
+            copy_id_kept.to_bytes(),
+            copy_id_pruned.to_bytes(),
+        }
+    );
+    assert_eq!(
+        collect_table_keys("tree_copies", tree_key_size),
+        hashset! {tree_key(tree_id_kept), tree_key(tree_id_pruned)}
+    );
+
+    // Reload repo to invalidate cache in TableStore
+    let repo = test_repo
+        .env
+        .load_repo_at_head(repo.settings(), test_repo.repo_path());
+    let index = repo.readonly_index().as_index();
+    let now = SystemTime::now() + Duration::from_secs(1);
+    repo.store().gc(index, &GcOptions::new(now)).unwrap();
+    assert_eq!(
+        collect_table_keys("copies", copy_key_size),
+        hashset! {copy_id_parent.to_bytes(), copy_id_kept.to_bytes()}
+    );
+    assert_eq!(
+        collect_table_keys("tree_copies", tree_key_size),
+        hashset! {tree_key(tree_id_kept)}
+    );
+
+    // Ensure that the kept copy ids can still be read
+    let repo = test_repo
+        .env
+        .load_repo_at_head(repo.settings(), test_repo.repo_path());
+    repo.store().read_copy(&copy_id_parent).block_on().unwrap();
+    let value = MergedTree::resolved(repo.store().clone(), tree_id_kept.clone())
+        .path_value(repo_path("file"))
+        .unwrap();
+    assert_matches!(
+        value.as_resolved(),
+        Some(Some(TreeValue::File { copy_id, .. })) if *copy_id == copy_id_kept
+    );
+}
+
 #[test]
 fn test_copy_detection() {
     let test_repo = TestRepo::init_with_backend(TestRepoBackend::Git);
@@ -406,6 +548,229 @@ fn test_copy_detection_file_and_dir() {
     );
 }
 
+#[test]
+fn test_copy_detection_tracked() {
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Git);
+    let repo = &test_repo.repo;
+    let store = repo.store();
+
+    let write_copy = |path: &str, parents: &[CopyId]| {
+        let copy = CopyHistory {
+            current_path: repo_path_buf(path),
+            parents: parents.to_vec(),
+            salt: vec![],
+        };
+        store.write_copy(&copy).block_on().unwrap()
+    };
+    // file0 -> file1 -> file2, with contents changed on each rename
+    let copy0_id = write_copy("file0", &[]);
+    let copy1_id = write_copy("file1", slice::from_ref(&copy0_id));
+    let copy2_id = write_copy("file2", slice::from_ref(&copy1_id));
+
+    let mut tx = repo.start_transaction();
+    let tree_a = create_tree(repo, &[(repo_path("file0"), "a\n")]);
+    let commit_a = tx
+        .repo_mut()
+        .new_commit(vec![store.root_commit_id().clone()], tree_a)
+        .write_unwrap();
+    let tree_b = create_tree_with(repo, |builder| {
+        builder.file(repo_path("file1"), "b\n").copy_id(copy1_id);
+    });
+    let commit_b = tx
+        .repo_mut()
+        .new_commit(vec![commit_a.id().clone()], tree_b)
+        .write_unwrap();
+    let tree_c = create_tree_with(repo, |builder| {
+        builder
+            .file(repo_path("dir/file2"), "c\n")
+            .copy_id(copy2_id);
+    });
+    let commit_c = tx
+        .repo_mut()
+        .new_commit(vec![commit_b.id().clone()], tree_c)
+        .write_unwrap();
+
+    assert_eq!(
+        get_copy_records(store, None, &commit_a, &commit_b),
+        hashmap! { "file1".to_owned() => "file0".to_owned() }
+    );
+    assert_eq!(
+        get_copy_records(store, None, &commit_b, &commit_c),
+        hashmap! { "dir/file2".to_owned() => "file1".to_owned() }
+    );
+    assert_eq!(
+        get_copy_records(store, None, &commit_a, &commit_c),
+        hashmap! { "dir/file2".to_owned() => "file0".to_owned() }
+    );
+    assert_eq!(
+        get_copy_records(store, Some(&[repo_path_buf("file1")]), &commit_a, &commit_c),
+        HashMap::default(),
+    );
+}
+
+#[test]
+fn test_copy_histories() {
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Git);
+    let repo = &test_repo.repo;
+    let store = repo.store();
+
+    let copy_history = |path: &str, parents: &[CopyId]| CopyHistory {
+        current_path: repo_path_buf(path),
+        parents: parents.to_vec(),
+        salt: vec![],
+    };
+    let copy1 = copy_history("foo1", &[]);
+    let copy1_id = store.write_copy(&copy1).block_on().unwrap();
+    let copy2 = copy_history("foo2", slice::from_ref(&copy1_id));
+    let copy2_id = store.write_copy(&copy2).block_on().unwrap();
+    let copy3 = copy_history("foo3", slice::from_ref(&copy2_id));
+    let copy3_id = store.write_copy(&copy3).block_on().unwrap();
+    let unrelated = copy_history("bar", &[]);
+    store.write_copy(&unrelated).block_on().unwrap();
+
+    // Copy histories are content-addressed
+    assert_eq!(store.write_copy(&copy1).block_on().unwrap(), copy1_id);
+    assert_eq!(store.read_copy(&copy2_id).block_on().unwrap(), copy2);
+    assert!(
+        store
+            .read_copy(&CopyId::from_hex("abcd"))
+            .block_on()
+            .is_err()
+    );
+
+    // Children are returned before parents, and unrelated histories are
+    // excluded
+    let related = store
+        .backend()
+        .get_related_copies(&copy2_id)
+        .block_on()
+        .unwrap();
+    assert_eq!(related, vec![copy3.clone(), copy2.clone(), copy1.clone()]);
+
+    // Copy histories and copy ids in trees are persisted. The copy ids are
+    // saved when a commit containing the tree is written.
+    let tree = create_tree_with(repo, |builder| {
+        builder
+            .file(repo_path("dir/file"), "contents")
+            .copy_id(copy3_id.clone());
+        builder.file(repo_path("dir/other"), "contents");
+    });
+    let commit = commit_with_tree(repo.store(), tree);
+    let repo = test_repo
+        .env
+        .load_repo_at_head(repo.settings(), test_repo.repo_path());
+    assert_eq!(repo.store().read_copy(&copy3_id).block_on().unwrap(), copy3);
+    let tree = repo.store().get_commit(commit.id()).unwrap().tree();
+    let copy_id_at = |path: &RepoPath| match tree.path_value(path).unwrap().into_resolved() {
+        Ok(Some(TreeValue::File { copy_id, .. })) => copy_id,
+        value => panic!("unexpected value: {value:?}"),
+    };
+    assert_eq!(copy_id_at(repo_path("dir/file")), copy3_id);
+    assert_eq!(copy_id_at(repo_path("dir/other")), CopyId::placeholder());
+
+    // Trees which differ only in copy ids have different ids, so writing one
+    // doesn't change the other
+    let other_tree = create_tree_with(&repo, |builder| {
+        builder
+            .file(repo_path("dir/file"), "contents")
+            .copy_id(copy1_id.clone());
+        builder.file(repo_path("dir/other"), "contents");
+    });
+    assert_ne!(other_tree.tree_ids(), tree.tree_ids());
+    assert_eq!(copy_id_at(repo_path("dir/file")), copy3_id);
+    let plain_tree = create_tree_with(&repo, |builder| {
+        builder.file(repo_path("dir/file"), "contents");
+        builder.file(repo_path("dir/other"), "contents");
+    });
+    let git_tree_id =
+        |tree: &MergedTree| tree.tree_ids().as_resolved().unwrap().as_bytes()[..20].to_vec();
+    assert_eq!(git_tree_id(&other_tree), git_tree_id(&plain_tree));
+    assert_eq!(git_tree_id(&tree), git_tree_id(&plain_tree));
+}
+
+#[test]
+fn test_copy_id_only_change_is_not_empty() {
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Git);
+    let repo = &test_repo.repo;
+    let copy_id = repo
+        .store()
+        .write_copy(&CopyHistory {
+            current_path: repo_path_buf("file"),
+            parents: vec![],
+            salt: vec![],
+        })
+        .block_on()
+        .unwrap();
+    let plain_tree = create_tree(repo, &[(repo_path("file"), "contents")]);
+    let tree_with_copies = create_tree_with(repo, |builder| {
+        builder.file(repo_path("file"), "contents").copy_id(copy_id);
+    });
+
+    let mut tx = repo.start_transaction();
+    let mut write_commit = |parent: &Commit, tree: &MergedTree| {
+        tx.repo_mut()
+            .new_commit(vec![parent.id().clone()], tree.clone())
+            .write()
+            .block_on()
+            .unwrap()
+    };
+    let commit1 = write_commit(&repo.store().root_commit(), &plain_tree);
+    let commit2 = write_commit(&commit1, &tree_with_copies);
+    let commit3 = write_commit(&commit2, &tree_with_copies);
+    let commit4 = write_commit(&commit3, &plain_tree);
+    assert!(!commit2.is_empty(tx.repo()).unwrap());
+    assert!(commit3.is_empty(tx.repo()).unwrap());
+    assert!(!commit4.is_empty(tx.repo()).unwrap());
+}
+
+#[test]
+fn test_conflicted_tree_with_copy_ids() {
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Git);
+    let repo = &test_repo.repo;
+    let copy_id = repo
+        .store()
+        .write_copy(&CopyHistory {
+            current_path: repo_path_buf("file"),
+            parents: vec![],
+            salt: vec![],
+        })
+        .block_on()
+        .unwrap();
+    let create_tree_with_copy_id = |contents: &str| {
+        let tree = create_tree_with(repo, |builder| {
+            builder
+                .file(repo_path("file"), contents)
+                .copy_id(copy_id.clone());
+        });
+        tree.tree_ids().as_resolved().unwrap().clone()
+    };
+    let merged_tree = MergedTree::new(
+        repo.store().clone(),
+        Merge::from_vec(vec![
+            create_tree_with_copy_id("a"),
+            create_tree_with_copy_id("base"),
+            create_tree_with_copy_id("b"),
+        ]),
+        ConflictLabels::unlabeled(),
+    );
+
+    // The Git commit tree is written from the first term without copy ids
+    let commit = commit_with_tree(repo.store(), merged_tree.clone());
+    let repo = test_repo
+        .env
+        .load_repo_at_head(repo.settings(), test_repo.repo_path());
+    let commit = repo.store().get_commit(commit.id()).unwrap();
+    assert_eq!(commit.tree_ids(), merged_tree.tree_ids());
+    let value = commit.tree().path_value(repo_path("file")).unwrap();
+    assert_eq!(value.num_sides(), 2);
+    for value in &value {
+        assert_matches!(
+            value,
+            Some(TreeValue::File { copy_id: id, .. }) if *id == copy_id
+        );
+    }
+}
+
 #[test]
 fn test_jj_trees_header_with_one_tree() {
     let test_repo = TestRepo::init_with_backend(TestRepoBackend::Git);
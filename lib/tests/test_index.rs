@@ -13,7 +13,10 @@
 // limitations under the License.
 
 use std::fs;
+use std::slice;
 use std::sync::Arc;
+use std::time::Duration;
+use std::time::SystemTime;
 
 use assert_matches::assert_matches;
 use itertools::Itertools as _;
@@ -24,6 +27,7 @@ use jj_lib::default_index::DefaultIndexStore;
 use jj_lib::default_index::DefaultIndexStoreError;
 use jj_lib::default_index::DefaultMutableIndex;
 use jj_lib::default_index::DefaultReadonlyIndex;
+use jj_lib::gc::GcOptions;
 use jj_lib::index::Index;
 use jj_lib::index::ResolvedChangeState;
 use jj_lib::index::ResolvedChangeTargets;
@@ -822,6 +826,66 @@ fn test_read_legacy_operation_link_file() {
     assert!(op_links_dir.join(repo.op_id().hex()).exists());
 }
 
+#[test]
+fn test_gc() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+    let index_dir = test_repo.repo_path().join("index");
+    let count_files = |name: &str| fs::read_dir(index_dir.join(name)).unwrap().count();
+
+    // B and C are based on A. Segments of B and C are stacked on A's.
+    let repo_a = create_n_commits(repo, 4);
+    let repo_b = create_n_commits(&repo_a, 1);
+    let repo_c = create_n_commits(&repo_a, 1);
+    assert_eq!(commits_by_level(&repo_b), vec![5, 1]);
+    assert_eq!(commits_by_level(&repo_c), vec![5, 1]);
+    let num_op_links = count_files("op_links");
+    let num_segments = count_files("segments");
+    let default_index_store: &DefaultIndexStore = repo.index_store().downcast_ref().unwrap();
+
+    // All kept by file modification time
+    let stats = default_index_store
+        .gc(
+            slice::from_ref(repo_b.operation()),
+            &GcOptions::new(SystemTime::UNIX_EPOCH),
+        )
+        .unwrap();
+    assert_eq!(stats.index_segments, 0);
+    assert_eq!(count_files("op_links"), num_op_links);
+
+    // Dry run doesn't remove anything
+    let now = SystemTime::now() + Duration::from_secs(1);
+    let options = GcOptions {
+        keep_newer: now,
+        dry_run: true,
+    };
+    let stats = default_index_store
+        .gc(slice::from_ref(repo_b.operation()), &options)
+        .unwrap();
+    assert_eq!(stats.index_segments, 1);
+    assert_eq!(count_files("op_links"), num_op_links);
+    assert_eq!(count_files("segments"), num_segments);
+
+    // C is unreachable, so its link and segment are removed
+    let stats = default_index_store
+        .gc(slice::from_ref(repo_b.operation()), &GcOptions::new(now))
+        .unwrap();
+    assert_eq!(stats.index_segments, 1);
+    assert_eq!(count_files("op_links"), num_op_links - 1);
+    assert_eq!(count_files("operations"), num_op_links - 1);
+    assert_eq!(count_files("segments"), num_segments - 1);
+    assert!(
+        !index_dir
+            .join("op_links")
+            .join(repo_c.op_id().hex())
+            .exists()
+    );
+
+    // Index of B is still loadable
+    let repo = repo_b.reload_at(repo_b.operation()).block_on().unwrap();
+    assert_eq!(commits_by_level(&repo), vec![5, 1]);
+}
+
 #[test]
 fn test_changed_path_segments() {
     let test_repo = TestRepo::init();
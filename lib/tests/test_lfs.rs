@@ -0,0 +1,98 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use jj_lib::lfs::LFS_SPEC_URL;
+use jj_lib::lfs::LfsPointer;
+use jj_lib::lfs::changed_lfs_pointers;
+use jj_lib::repo::Repo as _;
+use pollster::FutureExt as _;
+use testutils::TestRepo;
+use testutils::create_tree;
+use testutils::repo_path;
+
+#[test]
+fn test_changed_lfs_pointers() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+    let oid = |n: u8| format!("{n:02x}").repeat(32);
+    let pointer = |n: u8| format!("version {LFS_SPEC_URL}\noid sha256:{}\nsize {n}\n", oid(n));
+    let pointer1 = pointer(1);
+    let pointer2 = pointer(2);
+    let pointer3 = pointer(3);
+    let empty_tree = repo.store().empty_merged_tree();
+
+    // Only added or modified files with the filter=lfs attribute are found
+    let tree1 = create_tree(
+        repo,
+        &[
+            (repo_path(".gitattributes"), "*.bin filter=lfs\n"),
+            (repo_path("a.bin"), &pointer1),
+            (repo_path("b.bin"), &pointer2),
+            (repo_path("c.txt"), &pointer3),
+            (repo_path("d.bin"), "not a pointer\n"),
+        ],
+    );
+    let pointers = changed_lfs_pointers(&empty_tree, &tree1)
+        .block_on()
+        .unwrap();
+    assert_eq!(
+        pointers
+            .iter()
+            .map(|pointer| pointer.size)
+            .collect::<Vec<_>>(),
+        [1, 2]
+    );
+    let tree2 = create_tree(
+        repo,
+        &[
+            (repo_path(".gitattributes"), "*.bin filter=lfs\n"),
+            (repo_path("a.bin"), &pointer1),
+            (repo_path("b.bin"), &pointer3),
+        ],
+    );
+    let pointers = changed_lfs_pointers(&tree1, &tree2).block_on().unwrap();
+    assert_eq!(
+        pointers,
+        [LfsPointer {
+            oid: oid(3),
+            size: 3,
+        }]
+    );
+
+    // Attributes of nested .gitattributes files are respected
+    let tree3 = create_tree(
+        repo,
+        &[
+            (
+                repo_path(".gitattributes"),
+                "# *.bin filter=lfs\n*.bin text\n",
+            ),
+            (repo_path("a.bin"), &pointer1),
+            (repo_path("sub/.gitattributes"), "*.bin filter=lfs\n"),
+            (repo_path("sub/b.bin"), &pointer2),
+            (repo_path("sub/dir/.gitattributes"), "*.bin -filter=lfs\n"),
+            (repo_path("sub/dir/c.bin"), &pointer3),
+        ],
+    );
+    let pointers = changed_lfs_pointers(&empty_tree, &tree3)
+        .block_on()
+        .unwrap();
+    assert_eq!(
+        pointers,
+        [LfsPointer {
+            oid: oid(2),
+            size: 2,
+        }]
+    );
+}
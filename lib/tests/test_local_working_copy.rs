@@ -29,6 +29,7 @@ use bstr::BString;
 use gix::odb::pack::FindExt as _;
 use indoc::indoc;
 use itertools::Itertools as _;
+use jj_lib::backend::CommitId;
 use jj_lib::backend::CopyId;
 use jj_lib::backend::TreeId;
 use jj_lib::backend::TreeValue;
@@ -42,6 +43,7 @@ use jj_lib::files::FileMergeHunkLevel;
 use jj_lib::fsmonitor::FsmonitorSettings;
 use jj_lib::git::get_git_backend;
 use jj_lib::gitignore::GitIgnoreFile;
+use jj_lib::lfs::LfsPointer;
 use jj_lib::local_working_copy::LocalWorkingCopy;
 use jj_lib::local_working_copy::TreeState;
 use jj_lib::local_working_copy::TreeStateSettings;
@@ -385,8 +387,10 @@ fn test_checkout_file_transitions(backend: TestRepoBackend) {
                 assert!(metadata.is_dir(), "{path:?} should be a directory");
             }
             Kind::GitSubmodule => {
-                // Not supported for now
-                assert!(maybe_metadata.is_err(), "{path:?} should not exist");
+                // An empty directory is created for uninitialized submodules
+                assert!(maybe_metadata.is_ok(), "{path:?} should exist");
+                let metadata = maybe_metadata.unwrap();
+                assert!(metadata.is_dir(), "{path:?} should be a directory");
             }
         }
     }
@@ -1773,7 +1777,8 @@ fn test_dotgit_ignored() {
 #[test_case(""; "ignore nothing")]
 #[test_case("/*\n"; "ignore all")]
 fn test_git_submodule(gitignore_content: &str) {
-    // Tests that git submodules are ignored.
+    // Tests that the contents of git submodules are ignored, and that the
+    // commits checked out in them are snapshotted.
 
     let mut test_workspace = TestWorkspace::init_with_backend(TestRepoBackend::Git);
     let repo = test_workspace.repo.clone();
@@ -1829,7 +1834,10 @@ fn test_git_submodule(gitignore_content: &str) {
         .block_on()
         .unwrap();
 
-    std::fs::create_dir(submodule_path.to_fs_path_unchecked(&workspace_root)).unwrap();
+    // The submodule directory is created empty
+    let submodule_disk_path = submodule_path.to_fs_path_unchecked(&workspace_root);
+    assert!(submodule_disk_path.is_dir());
+    assert_eq!(submodule_disk_path.read_dir().unwrap().count(), 0);
 
     testutils::write_working_copy_file(
         &workspace_root,
@@ -1872,6 +1880,24 @@ fn test_git_submodule(gitignore_content: &str) {
         .unwrap();
     assert_tree_eq!(new_tree, tree_id2);
 
+    // Check out a commit in the submodule, which should be snapshotted
+    let submodule_repo = testutils::git::init(&submodule_disk_path);
+    let submodule_commit =
+        testutils::git::add_commit(&submodule_repo, "HEAD", "file", b"content", "message", &[]);
+    let (new_tree, _stats) = test_workspace
+        .snapshot_with_options(&snapshot_options)
+        .unwrap();
+    assert_eq!(
+        new_tree.path_value(submodule_path).unwrap(),
+        Merge::normal(TreeValue::GitSubmodule(CommitId::from_bytes(
+            submodule_commit.commit_id.as_bytes()
+        )))
+    );
+    assert_eq!(
+        new_tree.path_value(added_submodule_path).unwrap(),
+        Merge::absent()
+    );
+
     // Check out the empty tree, which shouldn't fail
     let ws = &mut test_workspace.workspace;
     let stats = ws
@@ -1881,6 +1907,71 @@ fn test_git_submodule(gitignore_content: &str) {
     assert_eq!(stats.skipped_files, 1);
 }
 
+#[test]
+fn test_lfs() {
+    let mut test_workspace = TestWorkspace::init_with_backend(TestRepoBackend::Git);
+    let repo = test_workspace.repo.clone();
+    let store = repo.store().clone();
+    let lfs_store = get_git_backend(&store).unwrap().lfs_store();
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+
+    let attributes_path = repo_path(".gitattributes");
+    let lfs_file_path = repo_path("dir/large.bin");
+    let normal_file_path = repo_path("small.txt");
+    testutils::write_working_copy_file(&workspace_root, attributes_path, "*.bin filter=lfs\n");
+    testutils::write_working_copy_file(&workspace_root, lfs_file_path, "large contents\n");
+    testutils::write_working_copy_file(&workspace_root, normal_file_path, "small contents\n");
+
+    // The contents of LFS files are moved to the LFS store
+    let tree = test_workspace.snapshot().unwrap();
+    let read_tree_file = |path: &RepoPath| {
+        let value = tree.path_value(path).unwrap();
+        let Some(Some(TreeValue::File { id, .. })) = value.as_resolved() else {
+            panic!("unexpected value at {path:?}: {value:?}");
+        };
+        testutils::read_file(&store, path, id)
+    };
+    let pointer = LfsPointer::parse(&read_tree_file(lfs_file_path)).unwrap();
+    assert_eq!(pointer.size, 15);
+    assert!(lfs_store.contains(&pointer));
+    assert_eq!(read_tree_file(normal_file_path), b"small contents\n");
+
+    // The contents are restored on checkout
+    let commit = commit_with_tree(&store, tree.clone());
+    let ws = &mut test_workspace.workspace;
+    ws.check_out(repo.op_id().clone(), None, &store.root_commit())
+        .block_on()
+        .unwrap();
+    assert!(!lfs_file_path.to_fs_path_unchecked(&workspace_root).exists());
+    ws.check_out(repo.op_id().clone(), None, &commit)
+        .block_on()
+        .unwrap();
+    assert_eq!(
+        std::fs::read(lfs_file_path.to_fs_path_unchecked(&workspace_root)).unwrap(),
+        b"large contents\n"
+    );
+    // Nothing changed according to the snapshot
+    let new_tree = test_workspace.snapshot().unwrap();
+    assert_tree_eq!(new_tree, tree);
+
+    // Pointers to missing objects are checked out and snapshotted as is
+    std::fs::remove_file(lfs_store.object_path(&pointer.oid)).unwrap();
+    let ws = &mut test_workspace.workspace;
+    ws.check_out(repo.op_id().clone(), None, &store.root_commit())
+        .block_on()
+        .unwrap();
+    ws.check_out(repo.op_id().clone(), None, &commit)
+        .block_on()
+        .unwrap();
+    assert_eq!(
+        std::fs::read(lfs_file_path.to_fs_path_unchecked(&workspace_root)).unwrap(),
+        pointer.to_bytes()
+    );
+    let new_tree = test_workspace.snapshot().unwrap();
+    assert_tree_eq!(new_tree, tree);
+    assert!(!lfs_store.contains(&pointer));
+}
+
 #[test]
 fn test_check_out_existing_file_cannot_be_removed() {
     let mut test_workspace = TestWorkspace::init();
@@ -15,14 +15,18 @@
 use std::path::Path;
 use std::slice;
 use std::sync::Arc;
+use std::time::Duration;
 use std::time::SystemTime;
 
 use assert_matches::assert_matches;
 use itertools::Itertools as _;
 use jj_lib::backend::CommitId;
+use jj_lib::backend::MillisSinceEpoch;
+use jj_lib::commit::Commit;
 use jj_lib::config::ConfigLayer;
 use jj_lib::config::ConfigSource;
 use jj_lib::evolution::walk_predecessors;
+use jj_lib::gc::GcOptions;
 use jj_lib::index::Index;
 use jj_lib::object_id::ObjectId as _;
 use jj_lib::op_store::OperationId;
@@ -642,6 +646,122 @@ fn test_reparent_discarding_predecessors(op_stores_commit_predecessors: bool) {
     assert_eq!(get_predecessors(&repo, commit_a1.id()), []);
 }
 
+#[test]
+fn test_compact_snapshot_operations() {
+    let test_repo = TestRepo::init();
+    let repo_0 = test_repo.repo;
+    let loader = repo_0.loader();
+    let op_store = repo_0.op_store();
+
+    let read_op = |id: &OperationId| loader.load_operation(id).block_on().unwrap();
+    let repo_at = |id: &OperationId| loader.load_at(&read_op(id)).block_on().unwrap();
+    let op_parent_ids = |id| read_op(id).parent_ids().to_vec();
+    let snapshot = |repo: &Arc<ReadonlyRepo>, commit: &Commit| {
+        let mut tx = repo.start_transaction();
+        tx.set_is_snapshot(true);
+        let new_commit = tx
+            .repo_mut()
+            .rewrite_commit(commit)
+            .set_description(format!("{}.", commit.description()))
+            .write_unwrap();
+        tx.repo_mut().rebase_descendants().block_on().unwrap();
+        (tx.commit("snapshot").block_on().unwrap(), new_commit)
+    };
+
+    // Set up operation graph:
+    // S6 (snapshot, recent)
+    // S5 (snapshot)
+    // S4 (snapshot)
+    // B
+    // S3 (snapshot)
+    // S2 (snapshot)
+    // S1 (snapshot)
+    // A
+    // 0 (initial)
+    let mut tx = repo_0.start_transaction();
+    let commit_w0 = write_random_commit(tx.repo_mut());
+    let repo_a = tx.commit("op A").block_on().unwrap();
+    let (repo_s1, commit_w1) = snapshot(&repo_a, &commit_w0);
+    let (repo_s2, commit_w2) = snapshot(&repo_s1, &commit_w1);
+    let (repo_s3, commit_w3) = snapshot(&repo_s2, &commit_w2);
+    let mut tx = repo_s3.start_transaction();
+    write_random_commit(tx.repo_mut());
+    let repo_b = tx.commit("op B").block_on().unwrap();
+    let (repo_s4, commit_w4) = snapshot(&repo_b, &commit_w3);
+    let (repo_s5, commit_w5) = snapshot(&repo_s4, &commit_w4);
+    // Make sure S6 ends strictly after S5.
+    std::thread::sleep(Duration::from_millis(2));
+    let (repo_s6, commit_w6) = snapshot(&repo_s5, &commit_w5);
+    let older_than = repo_s6.operation().metadata().time.end.timestamp;
+
+    // Nothing is old enough
+    let stats = op_walk::compact_snapshot_operations(
+        op_store.as_ref(),
+        slice::from_ref(repo_s6.operation()),
+        MillisSinceEpoch(0),
+    )
+    .unwrap();
+    assert_eq!(stats.new_head_ids, vec![repo_s6.op_id().clone()]);
+    assert_eq!(stats.rewritten_count, 0);
+    assert_eq!(stats.unreachable_count, 0);
+
+    // S1, S2, and S4 are squashed into S3 and S5. S6 is recent.
+    // S6'
+    // S5'
+    // B'
+    // S3'
+    // A
+    // 0 (initial)
+    let stats = op_walk::compact_snapshot_operations(
+        op_store.as_ref(),
+        slice::from_ref(repo_s6.operation()),
+        older_than,
+    )
+    .unwrap();
+    assert_eq!(stats.new_head_ids.len(), 1);
+    assert_eq!(stats.rewritten_count, 4);
+    assert_eq!(stats.unreachable_count, 3);
+    let new_op_s6 = read_op(&stats.new_head_ids[0]);
+    assert_eq!(new_op_s6.metadata(), repo_s6.operation().metadata());
+    assert_eq!(new_op_s6.view_id(), repo_s6.operation().view_id());
+    let [new_op_s5_id] = op_parent_ids(new_op_s6.id()).try_into().unwrap();
+    assert_eq!(
+        read_op(&new_op_s5_id).view_id(),
+        repo_s5.operation().view_id()
+    );
+    let [new_op_b_id] = op_parent_ids(&new_op_s5_id).try_into().unwrap();
+    assert_eq!(
+        read_op(&new_op_b_id).view_id(),
+        repo_b.operation().view_id()
+    );
+    let [new_op_s3_id] = op_parent_ids(&new_op_b_id).try_into().unwrap();
+    assert_eq!(
+        read_op(&new_op_s3_id).view_id(),
+        repo_s3.operation().view_id()
+    );
+    assert_eq!(op_parent_ids(&new_op_s3_id), vec![repo_a.op_id().clone()]);
+
+    // Predecessors recorded by the abandoned operations are preserved
+    let repo = repo_at(new_op_s6.id());
+    let commits = [
+        &commit_w0, &commit_w1, &commit_w2, &commit_w3, &commit_w4, &commit_w5, &commit_w6,
+    ];
+    for (old, new) in commits.iter().tuple_windows() {
+        assert_eq!(get_predecessors(&repo, new.id()), [old.id().clone()]);
+    }
+
+    // Compacting again doesn't change anything
+    let stats = op_walk::compact_snapshot_operations(
+        op_store.as_ref(),
+        slice::from_ref(&new_op_s6),
+        older_than,
+    )
+    .unwrap();
+    assert_eq!(stats.new_head_ids, vec![new_op_s6.id().clone()]);
+    assert_eq!(stats.rewritten_count, 0);
+    assert_eq!(stats.unreachable_count, 0);
+}
+
 fn stable_op_id_settings() -> UserSettings {
     let mut config = testutils::base_user_config();
     config.add_layer(
@@ -1033,19 +1153,36 @@ fn test_gc() {
     assert_eq!(expected_view_entries.len(), 5);
 
     // No heads, but all kept by file modification time
-    op_store.gc(&[], SystemTime::UNIX_EPOCH).unwrap();
+    op_store
+        .gc(&[], &GcOptions::new(SystemTime::UNIX_EPOCH))
+        .unwrap();
     assert_eq!(list_dir(&op_dir), expected_op_entries);
     assert_eq!(list_dir(&view_dir), expected_view_entries);
 
     // All reachable from heads
     let now = SystemTime::now();
     let head_ids = [repo_d.op_id().clone(), repo_f.op_id().clone()];
-    op_store.gc(&head_ids, now).unwrap();
+    op_store.gc(&head_ids, &GcOptions::new(now)).unwrap();
+    assert_eq!(list_dir(&op_dir), expected_op_entries);
+    assert_eq!(list_dir(&view_dir), expected_view_entries);
+
+    // Dry run doesn't remove anything
+    let options = GcOptions {
+        keep_newer: now,
+        dry_run: true,
+    };
+    let stats = op_store
+        .gc(slice::from_ref(repo_d.op_id()), &options)
+        .unwrap();
+    assert_eq!(stats.operations, 2);
     assert_eq!(list_dir(&op_dir), expected_op_entries);
     assert_eq!(list_dir(&view_dir), expected_view_entries);
 
     // E|F are no longer reachable, but E's view is still reachable
-    op_store.gc(slice::from_ref(repo_d.op_id()), now).unwrap();
+    let stats = op_store
+        .gc(slice::from_ref(repo_d.op_id()), &GcOptions::new(now))
+        .unwrap();
+    assert_eq!(stats.operations, 2);
     expected_op_entries
         .retain(|name| *name != repo_e.op_id().hex() && *name != repo_f.op_id().hex());
     expected_view_entries.retain(|name| *name != repo_f.operation().view_id().hex());
@@ -1053,7 +1190,9 @@ fn test_gc() {
     assert_eq!(list_dir(&view_dir), expected_view_entries);
 
     // B|C|D are no longer reachable
-    op_store.gc(slice::from_ref(repo_a.op_id()), now).unwrap();
+    op_store
+        .gc(slice::from_ref(repo_a.op_id()), &GcOptions::new(now))
+        .unwrap();
     expected_op_entries.retain(|name| {
         *name != repo_b.op_id().hex()
             && *name != repo_c.op_id().hex()
@@ -0,0 +1,383 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::slice;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use assert_matches::assert_matches;
+use futures::executor::block_on_stream;
+use itertools::Itertools as _;
+use jj_lib::backend::BackendError;
+use jj_lib::backend::CopyHistory;
+use jj_lib::backend::CopyId;
+use jj_lib::backend::CopyRecord;
+use jj_lib::commit::Commit;
+use jj_lib::gc::GcOptions;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::repo::Repo as _;
+use jj_lib::repo_path::RepoPathBuf;
+use jj_lib::store::Store;
+use maplit::hashmap;
+use pollster::FutureExt as _;
+use testutils::CommitBuilderExt as _;
+use testutils::TestRepo;
+use testutils::TestRepoBackend;
+use testutils::create_random_commit;
+use testutils::create_tree;
+use testutils::create_tree_with;
+use testutils::repo_path;
+use testutils::repo_path_buf;
+use testutils::write_random_commit;
+use testutils::write_random_commit_with_parents;
+
+fn get_copy_records(
+    store: &Store,
+    paths: Option<&[RepoPathBuf]>,
+    a: &Commit,
+    b: &Commit,
+) -> HashMap<String, String> {
+    let stream = store.get_copy_records(paths, a.id(), b.id()).unwrap();
+    block_on_stream(stream)
+        .map(|record| {
+            let CopyRecord { target, source, .. } = record.unwrap();
+            (
+                target.as_internal_file_string().to_owned(),
+                source.as_internal_file_string().to_owned(),
+            )
+        })
+        .collect()
+}
+
+fn list_dir(dir: &Path) -> Vec<String> {
+    std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_str().unwrap().to_owned())
+        .sorted()
+        .collect()
+}
+
+#[test]
+fn test_gc() {
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Simple);
+    let store_path = test_repo.repo_path().join("store");
+    let repo = test_repo.repo;
+    let base_index = repo.readonly_index();
+
+    // Set up commits:
+    //
+    //   E (predecessor: C)
+    // D |
+    // C |
+    // |/
+    // B
+    // A
+    let mut tx = repo.start_transaction();
+    let commit_a = write_random_commit(tx.repo_mut());
+    let commit_b = write_random_commit_with_parents(tx.repo_mut(), &[&commit_a]);
+    let commit_c = write_random_commit_with_parents(tx.repo_mut(), &[&commit_b]);
+    let commit_d = write_random_commit_with_parents(tx.repo_mut(), &[&commit_c]);
+    let commit_e = create_random_commit(tx.repo_mut())
+        .set_parents(vec![commit_b.id().clone()])
+        .set_predecessors(vec![commit_c.id().clone()])
+        .write_unwrap();
+    let repo = tx.commit("test").block_on().unwrap();
+    let store = repo.store();
+    let is_readable = |commit: &Commit| {
+        store.clear_caches();
+        match store.get_commit(commit.id()) {
+            Ok(_) => true,
+            Err(BackendError::ObjectNotFound { .. }) => false,
+            Err(err) => panic!("unexpected error: {err}"),
+        }
+    };
+    let commit_ids = |commits: &[&Commit]| {
+        commits
+            .iter()
+            .map(|commit| commit.id().hex())
+            .sorted()
+            .collect_vec()
+    };
+    assert_eq!(
+        list_dir(&store_path.join("commits")),
+        commit_ids(&[&commit_a, &commit_b, &commit_c, &commit_d, &commit_e]),
+    );
+    assert!(list_dir(&store_path.join("packs")).is_empty());
+
+    // Empty index, but all kept by file modification time
+    store
+        .gc(
+            base_index.as_index(),
+            &GcOptions::new(SystemTime::UNIX_EPOCH),
+        )
+        .unwrap();
+    assert_eq!(
+        list_dir(&store_path.join("commits")),
+        commit_ids(&[&commit_a, &commit_b, &commit_c, &commit_d, &commit_e]),
+    );
+
+    // Don't rely on the exact system time because file modification time might
+    // have lower precision for example.
+    let now = || SystemTime::now() + Duration::from_secs(1);
+
+    // All reachable: loose objects are packed
+    store.gc(repo.index(), &GcOptions::new(now())).unwrap();
+    assert!(list_dir(&store_path.join("commits")).is_empty());
+    assert!(list_dir(&store_path.join("trees")).is_empty());
+    assert!(list_dir(&store_path.join("files")).is_empty());
+    assert_eq!(list_dir(&store_path.join("packs")).len(), 1);
+    assert!(is_readable(&commit_a));
+    assert!(is_readable(&commit_d));
+    assert!(is_readable(&commit_e));
+    let tree_e = store.get_commit(commit_e.id()).unwrap().tree();
+    assert_eq!(tree_e.tree_ids(), commit_e.tree().tree_ids());
+    assert!(
+        tree_e
+            .entries()
+            .all(|(_, value)| value.unwrap().is_present())
+    );
+
+    // D is no longer reachable, but C is kept as the predecessor of E
+    let mut mut_index = base_index.start_modification();
+    mut_index.add_commit(&commit_a).block_on().unwrap();
+    mut_index.add_commit(&commit_b).block_on().unwrap();
+    mut_index.add_commit(&commit_e).block_on().unwrap();
+    let options = GcOptions {
+        keep_newer: now(),
+        dry_run: true,
+    };
+    let stats = store.gc(mut_index.as_index(), &options).unwrap();
+    assert_eq!(stats.commits, 1);
+    assert!(is_readable(&commit_d));
+    let stats = store
+        .gc(mut_index.as_index(), &GcOptions::new(now()))
+        .unwrap();
+    assert_eq!(stats.commits, 1);
+    assert_eq!(list_dir(&store_path.join("packs")).len(), 1);
+    assert!(is_readable(&commit_c));
+    assert!(!is_readable(&commit_d));
+    assert!(is_readable(&commit_e));
+
+    // New objects are written as loose objects after packing
+    let mut tx = repo.start_transaction();
+    let commit_f = write_random_commit_with_parents(tx.repo_mut(), &[&commit_e]);
+    tx.commit("test").block_on().unwrap();
+    assert_eq!(
+        list_dir(&store_path.join("commits")),
+        commit_ids(&[&commit_f]),
+    );
+
+    // Objects in a recent pack file are kept
+    store
+        .gc(
+            base_index.as_index(),
+            &GcOptions::new(SystemTime::UNIX_EPOCH),
+        )
+        .unwrap();
+    assert!(is_readable(&commit_c));
+    assert!(is_readable(&commit_e));
+    assert!(is_readable(&commit_f));
+
+    // Nothing reachable but the root
+    store
+        .gc(base_index.as_index(), &GcOptions::new(now()))
+        .unwrap();
+    assert!(list_dir(&store_path.join("commits")).is_empty());
+    assert!(!is_readable(&commit_a));
+    assert!(!is_readable(&commit_f));
+    store.clear_caches();
+    assert!(
+        store
+            .get_tree(RepoPathBuf::root(), store.empty_tree_id())
+            .is_ok()
+    );
+}
+
+#[test]
+fn test_gc_keeps_objects_referenced_by_recent_objects() {
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Simple);
+    let store_path = test_repo.repo_path().join("store");
+    let repo = test_repo.repo;
+    let base_index = repo.readonly_index();
+
+    let mut tx = repo.start_transaction();
+    let commit_a = write_random_commit(tx.repo_mut());
+    let repo = tx.commit("test").block_on().unwrap();
+    let store = repo.store();
+    store
+        .gc(
+            repo.index(),
+            &GcOptions::new(SystemTime::now() + Duration::from_secs(1)),
+        )
+        .unwrap();
+    let pack_names = list_dir(&store_path.join("packs"));
+    assert_eq!(pack_names.len(), 1);
+    // Make the pack file old
+    std::fs::File::options()
+        .write(true)
+        .open(store_path.join("packs").join(&pack_names[0]))
+        .unwrap()
+        .set_modified(SystemTime::UNIX_EPOCH)
+        .unwrap();
+
+    // B is a new loose commit referencing the parent and tree which only exist
+    // in the old pack file
+    let mut tx = repo.start_transaction();
+    let commit_b = tx
+        .repo_mut()
+        .new_commit(vec![commit_a.id().clone()], commit_a.tree())
+        .write_unwrap();
+    drop(tx);
+    assert_eq!(list_dir(&store_path.join("commits")), [commit_b.id().hex()]);
+    assert!(list_dir(&store_path.join("trees")).is_empty());
+
+    // B isn't reachable from the index, but it's recent, so it and the objects
+    // it references are kept
+    store
+        .gc(
+            base_index.as_index(),
+            &GcOptions::new(SystemTime::UNIX_EPOCH + Duration::from_secs(1)),
+        )
+        .unwrap();
+    assert_eq!(list_dir(&store_path.join("commits")), [commit_b.id().hex()]);
+    store.clear_caches();
+    let commit_b = store.get_commit(commit_b.id()).unwrap();
+    assert_eq!(commit_b.parent_ids(), [commit_a.id().clone()]);
+    assert!(store.get_commit(commit_a.id()).is_ok());
+    assert!(
+        commit_b
+            .tree()
+            .entries()
+            .all(|(_, value)| value.unwrap().is_present())
+    );
+}
+
+#[test]
+fn test_copy_histories() {
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Simple);
+    let repo = &test_repo.repo;
+    let store = repo.store();
+
+    let copy_history = |path: &str, parents: &[CopyId]| CopyHistory {
+        current_path: repo_path_buf(path),
+        parents: parents.to_vec(),
+        salt: vec![],
+    };
+    let copy1 = copy_history("foo1", &[]);
+    let copy1_id = store.write_copy(&copy1).block_on().unwrap();
+    let copy2 = copy_history("foo2", slice::from_ref(&copy1_id));
+    let copy2_id = store.write_copy(&copy2).block_on().unwrap();
+    let copy3 = copy_history("foo3", slice::from_ref(&copy2_id));
+    store.write_copy(&copy3).block_on().unwrap();
+    let unrelated = copy_history("bar", &[]);
+    store.write_copy(&unrelated).block_on().unwrap();
+
+    // Copy histories are content-addressed
+    assert_eq!(store.write_copy(&copy1).block_on().unwrap(), copy1_id);
+    assert_eq!(store.read_copy(&copy2_id).block_on().unwrap(), copy2);
+    assert_matches!(
+        store.read_copy(&CopyId::from_bytes(&[0xab; 64])).block_on(),
+        Err(BackendError::ObjectNotFound { .. })
+    );
+
+    // Children are returned before parents, and unrelated histories are
+    // excluded
+    let related = store
+        .backend()
+        .get_related_copies(&copy2_id)
+        .block_on()
+        .unwrap();
+    assert_eq!(related, vec![copy3, copy2, copy1]);
+}
+
+#[test]
+fn test_copy_detection_tracked() {
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Simple);
+    let repo = &test_repo.repo;
+    let store = repo.store();
+
+    let write_copy = |path: &str, parents: &[CopyId]| {
+        let copy = CopyHistory {
+            current_path: repo_path_buf(path),
+            parents: parents.to_vec(),
+            salt: vec![],
+        };
+        store.write_copy(&copy).block_on().unwrap()
+    };
+    // file0 -> file1 -> file2, with contents changed on each rename
+    let copy0_id = write_copy("file0", &[]);
+    let copy1_id = write_copy("file1", slice::from_ref(&copy0_id));
+    let copy2_id = write_copy("file2", slice::from_ref(&copy1_id));
+
+    let mut tx = repo.start_transaction();
+    let tree_a = create_tree(repo, &[(repo_path("file0"), "a\n")]);
+    let commit_a = tx
+        .repo_mut()
+        .new_commit(vec![store.root_commit_id().clone()], tree_a)
+        .write_unwrap();
+    let tree_b = create_tree_with(repo, |builder| {
+        builder.file(repo_path("file1"), "b\n").copy_id(copy1_id);
+    });
+    let commit_b = tx
+        .repo_mut()
+        .new_commit(vec![commit_a.id().clone()], tree_b)
+        .write_unwrap();
+    let tree_c = create_tree_with(repo, |builder| {
+        builder
+            .file(repo_path("dir/file2"), "c\n")
+            .copy_id(copy2_id);
+    });
+    let commit_c = tx
+        .repo_mut()
+        .new_commit(vec![commit_b.id().clone()], tree_c)
+        .write_unwrap();
+    let repo = tx.commit("test").block_on().unwrap();
+
+    assert_eq!(
+        get_copy_records(store, None, &commit_a, &commit_b),
+        hashmap! { "file1".to_owned() => "file0".to_owned() }
+    );
+    assert_eq!(
+        get_copy_records(store, None, &commit_b, &commit_c),
+        hashmap! { "dir/file2".to_owned() => "file1".to_owned() }
+    );
+    assert_eq!(
+        get_copy_records(store, None, &commit_a, &commit_c),
+        hashmap! { "dir/file2".to_owned() => "file0".to_owned() }
+    );
+    assert_eq!(
+        get_copy_records(store, Some(&[repo_path_buf("file1")]), &commit_a, &commit_c),
+        HashMap::default(),
+    );
+    // Files without copy histories aren't detected as copies
+    assert_eq!(
+        get_copy_records(store, None, &commit_c, &commit_a),
+        HashMap::default(),
+    );
+
+    // Copy histories survive garbage collection
+    store
+        .gc(
+            repo.index(),
+            &GcOptions::new(SystemTime::now() + Duration::from_secs(1)),
+        )
+        .unwrap();
+    store.clear_caches();
+    assert_eq!(
+        get_copy_records(store, None, &commit_a, &commit_c),
+        hashmap! { "dir/file2".to_owned() => "file0".to_owned() }
+    );
+}
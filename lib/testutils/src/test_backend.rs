@@ -23,7 +23,6 @@ use std::pin::Pin;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::sync::MutexGuard;
-use std::time::SystemTime;
 
 use async_trait::async_trait;
 use futures::stream;
@@ -45,6 +44,8 @@ use jj_lib::backend::Tree;
 use jj_lib::backend::TreeId;
 use jj_lib::backend::make_root_commit;
 use jj_lib::dag_walk::topo_order_reverse;
+use jj_lib::gc::GcOptions;
+use jj_lib::gc::GcStats;
 use jj_lib::index::Index;
 use jj_lib::object_id::ObjectId as _;
 use jj_lib::repo_path::RepoPath;
@@ -422,8 +423,8 @@ impl Backend for TestBackend {
         Ok(Box::pin(stream::empty()))
     }
 
-    fn gc(&self, _index: &dyn Index, _keep_newer: SystemTime) -> BackendResult<()> {
-        Ok(())
+    fn gc(&self, _index: &dyn Index, _options: &GcOptions) -> BackendResult<GcStats> {
+        Ok(GcStats::default())
     }
 }
 